@@ -0,0 +1,29 @@
+//! Synthetic backtest throughput; run with `cargo bench -p stratcraft-core`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use stratcraft_core::benchmark::{
+    run_strategy, SyntheticMarket, BENCHMARK_STRATEGIES, DEFAULT_SEED,
+};
+
+/// (tickers, years) markets every strategy is measured on.
+const MARKETS: &[(usize, usize)] = &[(20, 5), (100, 10)];
+
+fn backtest_throughput(c: &mut Criterion) {
+    for &(tickers, years) in MARKETS {
+        let market = SyntheticMarket::generate(tickers, years, DEFAULT_SEED);
+        let mut group = c.benchmark_group(format!("backtest/{tickers}x{years}y"));
+        group.sample_size(10);
+        group.throughput(Throughput::Elements(market.candles.len() as u64));
+        for strategy_id in BENCHMARK_STRATEGIES {
+            group.bench_with_input(
+                BenchmarkId::from_parameter(strategy_id),
+                strategy_id,
+                |b, strategy_id| b.iter(|| run_strategy(&market, strategy_id).unwrap()),
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, backtest_throughput);
+criterion_main!(benches);
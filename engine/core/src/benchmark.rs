@@ -0,0 +1,180 @@
+use crate::candle_utils::weekdays_from;
+use crate::config::{
+    EngineRuntimeSettings, LocalOptimizationObjective, PartialFillRemainder, WarmupPolicy,
+};
+use crate::engine::Engine;
+use crate::models::Candle;
+use crate::strategy::{create_strategy, parameter_specs};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Strategies that need no trained model, in the order benchmarks run them.
+pub const BENCHMARK_STRATEGIES: &[&str] = &[
+    "buy_and_hold",
+    "rsi",
+    "macd",
+    "williams_r",
+    "adx",
+    "atr",
+    "psar",
+    "weighted_momentum",
+];
+
+/// Seed of the synthetic market, so every run measures the same candles.
+pub const DEFAULT_SEED: u64 = 42;
+
+const TRADING_DAYS_PER_YEAR: usize = 252;
+const INITIAL_CAPITAL: f64 = 100_000.0;
+
+/// Random-walk daily candles for `SYN0001`.. tickers on consecutive weekdays from 2000-01-03.
+pub struct SyntheticMarket {
+    pub tickers: Vec<String>,
+    /// Sorted by date, then ticker.
+    pub candles: Vec<Candle>,
+    pub dates: Vec<DateTime<Utc>>,
+}
+
+impl SyntheticMarket {
+    pub fn generate(ticker_count: usize, years: usize, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let tickers: Vec<String> = (1..=ticker_count)
+            .map(|index| format!("SYN{index:04}"))
+            .collect();
+        let dates = weekdays_from(
+            NaiveDate::from_ymd_opt(2000, 1, 3).expect("valid start date"),
+            years * TRADING_DAYS_PER_YEAR,
+        );
+        let mut closes: Vec<f64> = tickers.iter().map(|_| rng.gen_range(10.0..200.0)).collect();
+
+        let mut candles = Vec::with_capacity(tickers.len() * dates.len());
+        for date in &dates {
+            for (ticker, close) in tickers.iter().zip(closes.iter_mut()) {
+                let open = *close;
+                *close = (open * (1.0 + rng.gen_range(-0.03..0.0306))).max(0.5);
+                let high = open.max(*close) * (1.0 + rng.gen_range(0.0..0.01));
+                let low = open.min(*close) * (1.0 - rng.gen_range(0.0..0.01));
+                candles.push(Candle {
+                    ticker: ticker.clone(),
+                    date: *date,
+                    open,
+                    high,
+                    low,
+                    close: *close,
+                    unadjusted_close: None,
+                    volume_shares: rng.gen_range(200_000..5_000_000),
+                });
+            }
+        }
+
+        Self {
+            tickers,
+            candles,
+            dates,
+        }
+    }
+}
+
+/// Throughput of one strategy backtest over a synthetic market.
+#[derive(Debug, Clone)]
+pub struct BenchmarkResult {
+    pub strategy_id: String,
+    pub candles: usize,
+    pub trades: usize,
+    pub elapsed: Duration,
+}
+
+impl BenchmarkResult {
+    pub fn candles_per_second(&self) -> f64 {
+        self.candles as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Fixed fees, slippage and entry filters so results only move with the engine itself.
+pub fn benchmark_runtime_settings() -> EngineRuntimeSettings {
+    EngineRuntimeSettings {
+        trade_close_fee_rate: 0.0005,
+        trade_slippage_rate: 0.003,
+        short_borrow_fee_annual_rate: 0.003,
+        borrow_fee_multiplier: 1.0,
+        trade_entry_price_min: 1.0,
+        trade_entry_price_max: 1000.0,
+        minimum_dollar_volume_for_entry: 150_000.0,
+        minimum_dollar_volume_lookback: 5,
+        local_optimization_version: 0,
+        local_optimization_step_multipliers: vec![-1.0, 1.0],
+        local_optimization_objective: LocalOptimizationObjective::Cagr,
+        max_allowed_drawdown_ratio: 1.0,
+        capacity_max_volume_share: 0.01,
+        stop_fill_at_stop_price: false,
+        order_rejection_rate: 0.0,
+        partial_fill_max_volume_share: 0.0,
+        partial_fill_remainder: PartialFillRemainder::Cancel,
+        warmup_policy: WarmupPolicy::Skip,
+        min_ticker_history_candles: 0,
+        ticker_overrides: HashMap::new(),
+        session_calendar: Default::default(),
+        hedge_ticker: "SPY".to_string(),
+        volatility_index_ticker: "VIX".to_string(),
+    }
+}
+
+/// Backtests `strategy_id` with its default parameters on a fresh engine, so indicator and
+/// cross-sectional work is included in the measurement.
+pub fn run_strategy(market: &SyntheticMarket, strategy_id: &str) -> Result<BenchmarkResult> {
+    let specs = parameter_specs(strategy_id)
+        .ok_or_else(|| anyhow!("Unknown strategy template: {}", strategy_id))?;
+    let mut parameters: HashMap<String, f64> = specs
+        .iter()
+        .map(|spec| (spec.name.to_string(), spec.default))
+        .collect();
+    parameters.insert("initialCapital".to_string(), INITIAL_CAPITAL);
+
+    let started = Instant::now();
+    let strategy = create_strategy(strategy_id, parameters.clone())?;
+    let engine = Engine::from_parameters(&parameters, benchmark_runtime_settings());
+    let run = engine.backtest(
+        Some(strategy.as_ref()),
+        strategy_id,
+        &market.tickers,
+        &market.candles,
+        &market.dates,
+        None,
+        None,
+        None,
+    )?;
+    Ok(BenchmarkResult {
+        strategy_id: strategy_id.to_string(),
+        candles: market.candles.len(),
+        trades: run.result.trades.len(),
+        elapsed: started.elapsed(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Weekday};
+
+    #[test]
+    fn synthetic_market_is_deterministic_and_backtests() {
+        let market = SyntheticMarket::generate(3, 1, DEFAULT_SEED);
+        assert_eq!(market.dates.len(), TRADING_DAYS_PER_YEAR);
+        assert_eq!(market.candles.len(), 3 * TRADING_DAYS_PER_YEAR);
+        assert!(market
+            .dates
+            .iter()
+            .all(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)));
+
+        let again = SyntheticMarket::generate(3, 1, DEFAULT_SEED);
+        assert_eq!(market.candles[100].close, again.candles[100].close);
+
+        for strategy_id in BENCHMARK_STRATEGIES {
+            let result = run_strategy(&market, strategy_id).unwrap();
+            assert_eq!(result.candles, market.candles.len());
+            assert!(result.candles_per_second() > 0.0);
+        }
+    }
+}
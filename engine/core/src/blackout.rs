@@ -0,0 +1,176 @@
+use crate::models::{AuxiliaryData, EconomicEventKind};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Skip reason recorded for entries suppressed by a blackout window.
+pub const BLACKOUT_SKIP_REASON: &str = "blackout_window";
+
+/// Per-strategy blackout settings, stored as JSON in `strategies.blackout_windows`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BlackoutConfig {
+    /// Calendar days before each earnings date of a ticker to block its entries; blocking
+    /// around earnings is enabled when either this or `earnings_days_after` is set.
+    pub earnings_days_before: Option<u32>,
+    pub earnings_days_after: Option<u32>,
+    /// Blocks all entries on the FOMC announcement days in `economic_events`.
+    pub fomc: bool,
+    pub ranges: Vec<BlackoutRange>,
+}
+
+/// Custom inclusive date range in which the strategy opens no positions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlackoutRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl BlackoutConfig {
+    /// Parses the stored JSON; blank or invalid values disable blackouts.
+    pub fn parse(json: Option<&str>) -> Self {
+        json.filter(|value| !value.trim().is_empty())
+            .and_then(|value| serde_json::from_str(value).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn uses_earnings(&self) -> bool {
+        self.earnings_days_before.is_some() || self.earnings_days_after.is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.uses_earnings() && !self.fomc && self.ranges.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Window {
+    start: NaiveDate,
+    end: NaiveDate,
+    details: String,
+}
+
+impl Window {
+    fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+/// Blackout windows of one strategy resolved against earnings dates and the economic calendar.
+#[derive(Debug, Clone, Default)]
+pub struct BlackoutCalendar {
+    market: Vec<Window>,
+    by_ticker: HashMap<String, Vec<Window>>,
+}
+
+impl BlackoutCalendar {
+    /// Resolves `config` against the earnings dates and economic calendar of `auxiliary`.
+    pub fn build(config: &BlackoutConfig, auxiliary: &AuxiliaryData) -> Self {
+        let mut calendar = Self::default();
+        for range in &config.ranges {
+            calendar.market.push(Window {
+                start: range.start,
+                end: range.end,
+                details: match &range.label {
+                    Some(label) if !label.trim().is_empty() => format!("custom: {}", label.trim()),
+                    _ => format!("custom: {} to {}", range.start, range.end),
+                },
+            });
+        }
+        if config.fomc {
+            for date in auxiliary.economic_calendar.dates(EconomicEventKind::Fomc) {
+                calendar.market.push(Window {
+                    start: *date,
+                    end: *date,
+                    details: format!("fomc: {}", date),
+                });
+            }
+        }
+        if config.uses_earnings() {
+            let before = Duration::days(i64::from(config.earnings_days_before.unwrap_or(0)));
+            let after = Duration::days(i64::from(config.earnings_days_after.unwrap_or(0)));
+            for (ticker, dates) in &auxiliary.ticker_earnings {
+                let windows = calendar.by_ticker.entry(ticker.clone()).or_default();
+                windows.extend(dates.iter().map(|date| {
+                    let date = date.date_naive();
+                    Window {
+                        start: date - before,
+                        end: date + after,
+                        details: format!("earnings: {}", date),
+                    }
+                }));
+            }
+        }
+        calendar
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.market.is_empty() && self.by_ticker.values().all(Vec::is_empty)
+    }
+
+    /// Describes the window that blocks entries into `ticker` on `date`, if any.
+    pub fn window_for(&self, ticker: &str, date: DateTime<Utc>) -> Option<&str> {
+        let day = date.date_naive();
+        self.market
+            .iter()
+            .chain(self.by_ticker.get(ticker).into_iter().flatten())
+            .find(|window| window.contains(day))
+            .map(|window| window.details.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EconomicCalendar;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn at(value: &str) -> DateTime<Utc> {
+        day(value).and_hms_opt(0, 0, 0).unwrap().and_utc()
+    }
+
+    #[test]
+    fn calendar_blocks_earnings_fomc_and_custom_ranges() {
+        let config = BlackoutConfig::parse(Some(
+            r#"{"earningsDaysBefore":2,"earningsDaysAfter":1,"fomc":true,
+                "ranges":[{"start":"2024-12-23","end":"2024-12-31","label":"year end"}]}"#,
+        ));
+        let auxiliary = AuxiliaryData {
+            ticker_earnings: HashMap::from([("AAPL".to_string(), vec![at("2024-05-02")])]),
+            economic_calendar: EconomicCalendar::from_events(&[
+                (EconomicEventKind::Fomc, day("2024-06-12")),
+                (EconomicEventKind::Cpi, day("2024-06-13")),
+            ]),
+            ..AuxiliaryData::default()
+        };
+        let calendar = BlackoutCalendar::build(&config, &auxiliary);
+
+        assert_eq!(
+            calendar.window_for("AAPL", at("2024-04-30")),
+            Some("earnings: 2024-05-02")
+        );
+        assert_eq!(
+            calendar.window_for("AAPL", at("2024-05-03")),
+            Some("earnings: 2024-05-02")
+        );
+        assert_eq!(calendar.window_for("AAPL", at("2024-05-06")), None);
+        assert_eq!(calendar.window_for("MSFT", at("2024-04-30")), None);
+        assert_eq!(
+            calendar.window_for("MSFT", at("2024-06-12")),
+            Some("fomc: 2024-06-12")
+        );
+        assert_eq!(calendar.window_for("MSFT", at("2024-06-13")), None);
+        assert_eq!(
+            calendar.window_for("MSFT", at("2024-12-31")),
+            Some("custom: year end")
+        );
+
+        assert!(BlackoutConfig::parse(Some("not json")).is_empty());
+        assert!(BlackoutCalendar::build(&BlackoutConfig::default(), &auxiliary).is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+use crate::models::Candle;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub const EXCHANGE_TIMEZONE_SETTING: &str = "EXCHANGE_TIMEZONE";
+
+/// Exchange clock whose local date names each daily bar's trading session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionCalendar {
+    /// US Eastern time, following its daylight saving switches.
+    #[default]
+    NewYork,
+    /// A fixed offset from UTC, in seconds.
+    Fixed(i32),
+}
+
+impl SessionCalendar {
+    /// Accepts `America/New_York` (or `US/Eastern`), `UTC`, or a fixed offset such as `+09:00`.
+    pub fn parse(raw: &str) -> Result<Self> {
+        let value = raw.trim();
+        if value.eq_ignore_ascii_case("america/new_york")
+            || value.eq_ignore_ascii_case("us/eastern")
+        {
+            return Ok(Self::NewYork);
+        }
+        if value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+            return Ok(Self::Fixed(0));
+        }
+        let invalid = || {
+            anyhow!(
+                "{} must be America/New_York, UTC or an offset like +09:00 (value: {})",
+                EXCHANGE_TIMEZONE_SETTING,
+                raw
+            )
+        };
+        let (sign, rest) = match value.as_bytes().first() {
+            Some(b'+') => (1, &value[1..]),
+            Some(b'-') => (-1, &value[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if !(0..=14).contains(&hours) || !(0..60).contains(&minutes) {
+            return Err(invalid());
+        }
+        Ok(Self::Fixed(sign * (hours * 3600 + minutes * 60)))
+    }
+
+    /// Reads `EXCHANGE_TIMEZONE`, defaulting to New York when it is unset or blank.
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        match settings
+            .get(EXCHANGE_TIMEZONE_SETTING)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        {
+            Some(value) => Self::parse(value),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn utc_offset_seconds(&self, at: DateTime<Utc>) -> i64 {
+        match self {
+            Self::Fixed(seconds) => i64::from(*seconds),
+            Self::NewYork => {
+                // Daylight time runs from 2:00 local on the second Sunday of March to 2:00
+                // local on the first Sunday of November.
+                let year = at.year();
+                let starts = nth_sunday(year, 3, 2).and_hms_opt(7, 0, 0);
+                let ends = nth_sunday(year, 11, 1).and_hms_opt(6, 0, 0);
+                let naive = at.naive_utc();
+                let daylight = matches!((starts, ends), (Some(starts), Some(ends)) if naive >= starts && naive < ends);
+                if daylight {
+                    -4 * 3600
+                } else {
+                    -5 * 3600
+                }
+            }
+        }
+    }
+
+    /// Trading day of a bar stamped `at`. Stamps at exactly midnight UTC are already session
+    /// dates; any other stamp, such as the exchange close or local midnight, is read on the
+    /// exchange clock.
+    pub fn session_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        if at.num_seconds_from_midnight() == 0 && at.nanosecond() == 0 {
+            return at.date_naive();
+        }
+        (at + Duration::seconds(self.utc_offset_seconds(at))).date_naive()
+    }
+
+    /// Midnight UTC of the session date, the form candles, signals and trades are keyed by.
+    pub fn session_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        Utc.from_utc_datetime(
+            &self
+                .session_date(at)
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight should always be valid"),
+        )
+    }
+
+    pub fn same_session(&self, a: DateTime<Utc>, b: DateTime<Utc>) -> bool {
+        self.session_date(a) == self.session_date(b)
+    }
+}
+
+fn nth_sunday(year: i32, month: u32, nth: u32) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month start");
+    let offset = (7 - first.weekday().num_days_from_sunday()) % 7;
+    first + Duration::days(i64::from(offset + (nth - 1) * 7))
+}
+
+/// `count` consecutive weekdays at midnight UTC from `start` on, a holiday-free calendar for
+/// synthetic markets.
+pub fn weekdays_from(start: NaiveDate, count: usize) -> Vec<DateTime<Utc>> {
+    let mut day = start;
+    let mut dates = Vec::with_capacity(count);
+    while dates.len() < count {
+        if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+            dates.push(day.and_hms_opt(0, 0, 0).expect("midnight").and_utc());
+        }
+        day += Duration::days(1);
+    }
+    dates
+}
+
+/// Re-keys every candle to the midnight-UTC start of its session so bars from providers that
+/// stamp the close or exchange midnight line up with database candles. Returns how many moved.
+pub fn normalize_session_dates(candles: &mut [Candle], calendar: SessionCalendar) -> usize {
+    let mut moved = 0;
+    for candle in candles.iter_mut() {
+        let start = calendar.session_start(candle.date);
+        if start != candle.date {
+            candle.date = start;
+            moved += 1;
+        }
+    }
+    moved
+}
+
+/// Groups candles (by reference) keyed by ticker, optionally filtering to a known set.
+pub fn group_candles_for_tickers<'a>(
+    tickers: &[String],
+    candles: &'a [Candle],
+) -> HashMap<String, Vec<&'a Candle>> {
+    group_candles_by_ticker_with(candles, Some(tickers), |c| Some(c.ticker.clone()))
+}
+
+/// Groups every candle in the slice keyed by its native ticker.
+pub fn group_all_candles_by_ticker(candles: &[Candle]) -> HashMap<String, Vec<&Candle>> {
+    group_candles_by_ticker_with(candles, None, |c| Some(c.ticker.clone()))
+}
+
+/// Shared implementation for grouping candles keyed by ticker with optional filtering/custom keys.
+pub fn group_candles_by_ticker_with<'a, F>(
+    candles: &'a [Candle],
+    tickers: Option<&[String]>,
+    mut key_selector: F,
+) -> HashMap<String, Vec<&'a Candle>>
+where
+    F: FnMut(&Candle) -> Option<String>,
+{
+    let mut grouped: HashMap<String, Vec<&Candle>> = if let Some(list) = tickers {
+        list.iter()
+            .map(|ticker| (ticker.clone(), Vec::new()))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    let restrict_to_known = tickers.is_some();
+
+    for candle in candles {
+        let Some(key) = key_selector(candle) else {
+            continue;
+        };
+
+        if let Some(bucket) = grouped.get_mut(&key) {
+            bucket.push(candle);
+            continue;
+        }
+
+        if !restrict_to_known {
+            grouped.entry(key).or_default().push(candle);
+        }
+    }
+
+    grouped.retain(|_, values| !values.is_empty());
+    for values in grouped.values_mut() {
+        values.sort_by_key(|a| a.date);
+    }
+
+    grouped
+}
+
+/// Struct-of-arrays candle store: each column holds every ticker's series back to back, so
+/// one backtest builds it once and the loop and strategies borrow a ticker's slices by range.
+#[derive(Debug, Default)]
+pub struct CandleColumns {
+    ranges: HashMap<String, Range<usize>>,
+    dates: Vec<DateTime<Utc>>,
+    opens: Vec<f64>,
+    highs: Vec<f64>,
+    lows: Vec<f64>,
+    closes: Vec<f64>,
+    volumes: Vec<i64>,
+    rows: Vec<Candle>,
+}
+
+/// One ticker's date-ordered series in a `CandleColumns` store.
+#[derive(Debug, Clone, Copy)]
+pub struct TickerCandles<'a> {
+    pub dates: &'a [DateTime<Utc>],
+    pub opens: &'a [f64],
+    pub highs: &'a [f64],
+    pub lows: &'a [f64],
+    pub closes: &'a [f64],
+    pub volumes: &'a [i64],
+    /// The same series as whole candles, for strategies that take `&[Candle]`.
+    pub rows: &'a [Candle],
+}
+
+impl CandleColumns {
+    /// Lays out grouped, date-sorted candles column by column.
+    pub fn from_grouped(grouped: &HashMap<String, Vec<&Candle>>) -> Self {
+        let total = grouped.values().map(Vec::len).sum();
+        let mut columns = CandleColumns {
+            ranges: HashMap::with_capacity(grouped.len()),
+            dates: Vec::with_capacity(total),
+            opens: Vec::with_capacity(total),
+            highs: Vec::with_capacity(total),
+            lows: Vec::with_capacity(total),
+            closes: Vec::with_capacity(total),
+            volumes: Vec::with_capacity(total),
+            rows: Vec::with_capacity(total),
+        };
+        for (ticker, candles) in grouped {
+            let start = columns.rows.len();
+            for candle in candles {
+                columns.dates.push(candle.date);
+                columns.opens.push(candle.open);
+                columns.highs.push(candle.high);
+                columns.lows.push(candle.low);
+                columns.closes.push(candle.close);
+                columns.volumes.push(candle.volume_shares);
+                columns.rows.push((*candle).clone());
+            }
+            columns
+                .ranges
+                .insert(ticker.clone(), start..columns.rows.len());
+        }
+        columns
+    }
+
+    pub fn ticker(&self, ticker: &str) -> Option<TickerCandles<'_>> {
+        let range = self.ranges.get(ticker)?.clone();
+        Some(TickerCandles {
+            dates: &self.dates[range.clone()],
+            opens: &self.opens[range.clone()],
+            highs: &self.highs[range.clone()],
+            lows: &self.lows[range.clone()],
+            closes: &self.closes[range.clone()],
+            volumes: &self.volumes[range.clone()],
+            rows: &self.rows[range],
+        })
+    }
+}
+
+impl TickerCandles<'_> {
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    /// Index of the first candle at or after `date`, searching from `from`.
+    pub fn seek(&self, from: usize, date: DateTime<Utc>) -> usize {
+        from + self.dates[from..].partition_point(|candle_date| *candle_date < date)
+    }
+}
+
+/// Normalizes a ticker string by trimming whitespace and uppercasing.
+pub fn normalize_ticker_symbol(value: &str) -> Option<String> {
+    let normalized = value.trim().to_uppercase();
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        group_all_candles_by_ticker, group_candles_for_tickers, CandleColumns, SessionCalendar,
+    };
+    use crate::models::Candle;
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+
+    #[test]
+    fn session_dates_follow_the_exchange_clock() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let new_york = SessionCalendar::default();
+        // Midnight UTC stamps are already session dates.
+        let midnight = Utc.with_ymd_and_hms(2024, 3, 8, 0, 0, 0).unwrap();
+        assert_eq!(new_york.session_date(midnight), day(8));
+        // Closes at 16:00 New York, before and after the March 10 switch to daylight time.
+        let winter_close = Utc.with_ymd_and_hms(2024, 3, 8, 21, 0, 0).unwrap();
+        let summer_close = Utc.with_ymd_and_hms(2024, 3, 11, 20, 0, 0).unwrap();
+        assert_eq!(new_york.session_date(winter_close), day(8));
+        assert_eq!(new_york.session_date(summer_close), day(11));
+        // An after-hours fill at 20:30 New York is still that day's session.
+        let after_hours = Utc.with_ymd_and_hms(2024, 3, 12, 0, 30, 0).unwrap();
+        assert_eq!(new_york.session_date(after_hours), day(11));
+        assert_eq!(
+            new_york.session_start(after_hours),
+            Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap()
+        );
+
+        // A Tokyo bar stamped at local midnight falls on the previous UTC day.
+        let tokyo = SessionCalendar::parse("+09:00").unwrap();
+        let tokyo_midnight = Utc.with_ymd_and_hms(2024, 3, 7, 15, 0, 0).unwrap();
+        assert_eq!(tokyo.session_date(tokyo_midnight), day(8));
+        assert!(!new_york.same_session(tokyo_midnight, midnight));
+
+        assert_eq!(
+            SessionCalendar::parse("utc").unwrap(),
+            SessionCalendar::Fixed(0)
+        );
+        assert_eq!(
+            SessionCalendar::parse("US/Eastern").unwrap(),
+            SessionCalendar::NewYork
+        );
+        assert!(SessionCalendar::parse("Europe/Paris").is_err());
+    }
+
+    #[test]
+    fn group_candles_filters_and_sorts() {
+        let tickers = vec!["AAA".to_string(), "BBB".to_string()];
+        let base = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let mut candles = vec![
+            Candle {
+                ticker: "AAA".to_string(),
+                date: base + Duration::days(1),
+                open: 101.0,
+                high: 105.0,
+                low: 99.0,
+                close: 104.0,
+                unadjusted_close: Some(104.0),
+                volume_shares: 1_000,
+            },
+            Candle {
+                ticker: "AAA".to_string(),
+                date: base,
+                open: 100.0,
+                high: 102.0,
+                low: 99.0,
+                close: 101.0,
+                unadjusted_close: Some(101.0),
+                volume_shares: 1_000,
+            },
+            Candle {
+                ticker: "ZZZ".to_string(),
+                date: base,
+                open: 50.0,
+                high: 51.0,
+                low: 49.0,
+                close: 50.5,
+                unadjusted_close: Some(50.5),
+                volume_shares: 500,
+            },
+        ];
+
+        candles.swap(0, 1);
+
+        let grouped = group_candles_for_tickers(&tickers, &candles);
+        assert_eq!(grouped.len(), 1);
+        assert!(!grouped.contains_key("BBB"));
+
+        let aaa = grouped.get("AAA").expect("AAA data missing");
+        assert_eq!(aaa.len(), 2);
+        assert!(aaa[0].date <= aaa[1].date);
+    }
+
+    #[test]
+    fn candle_columns_slice_each_ticker_and_seek_by_date() {
+        let base = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let candle = |ticker: &str, day: i64, close: f64| Candle {
+            ticker: ticker.to_string(),
+            date: base + Duration::days(day),
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            unadjusted_close: None,
+            volume_shares: 100 * day,
+        };
+        let candles = vec![
+            candle("AAA", 2, 12.0),
+            candle("BBB", 1, 50.0),
+            candle("AAA", 0, 10.0),
+            candle("AAA", 3, 13.0),
+        ];
+
+        let columns = CandleColumns::from_grouped(&group_all_candles_by_ticker(&candles));
+        let aaa = columns.ticker("AAA").expect("AAA columns missing");
+        assert_eq!(aaa.len(), 3);
+        assert_eq!(aaa.closes, &[10.0, 12.0, 13.0]);
+        assert_eq!(aaa.highs, &[11.0, 13.0, 14.0]);
+        assert_eq!(aaa.volumes, &[0, 200, 300]);
+        assert_eq!(aaa.rows[1].date, aaa.dates[1]);
+        assert_eq!(columns.ticker("BBB").unwrap().closes, &[50.0]);
+        assert!(columns.ticker("CCC").is_none());
+
+        // Day 1 has no AAA bar, so the cursor lands on the next one; it never moves back.
+        assert_eq!(aaa.seek(0, base + Duration::days(1)), 1);
+        assert_eq!(aaa.seek(1, base), 1);
+        assert_eq!(aaa.seek(1, base + Duration::days(3)), 2);
+        assert_eq!(aaa.seek(2, base + Duration::days(9)), 3);
+    }
+}
@@ -0,0 +1,1510 @@
+use crate::candle_utils::SessionCalendar;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, Weekday};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const BACKTEST_INITIAL_CAPITAL_SETTING: &str = "BACKTEST_INITIAL_CAPITAL";
+const DEFAULT_BACKTEST_INITIAL_CAPITAL: f64 = 100000.0;
+
+pub fn resolve_backtest_initial_capital(settings: &HashMap<String, String>) -> f64 {
+    let raw = settings
+        .get(BACKTEST_INITIAL_CAPITAL_SETTING)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty());
+    let parsed = raw
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0);
+    parsed.unwrap_or(DEFAULT_BACKTEST_INITIAL_CAPITAL)
+}
+
+const LIVE_CAPACITY_MAX_VOLUME_SHARE_SETTING: &str = "LIVE_CAPACITY_MAX_VOLUME_SHARE";
+
+/// Share of a ticker's average dollar volume that one day's planned buys across all accounts
+/// may use; `None` (missing, zero or invalid) disables capacity throttling.
+pub fn resolve_live_capacity_volume_share(settings: &HashMap<String, String>) -> Option<f64> {
+    settings
+        .get(LIVE_CAPACITY_MAX_VOLUME_SHARE_SETTING)
+        .map(|value| value.trim())
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0)
+}
+
+const LIVE_HALT_CHECK_ENABLED_SETTING: &str = "LIVE_HALT_CHECK_ENABLED";
+
+/// Whether plan-operations and reconcile-trades check the broker for halted tickers before
+/// letting operations dispatch; off unless the setting is `true`.
+pub fn resolve_live_halt_check_enabled(settings: &HashMap<String, String>) -> bool {
+    settings
+        .get(LIVE_HALT_CHECK_ENABLED_SETTING)
+        .map(|value| value.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+const VERIFY_REQUIRE_BEAT_BUY_AND_HOLD_SETTING: &str = "VERIFY_REQUIRE_BEAT_BUY_AND_HOLD";
+
+/// Whether verify additionally requires the base cost scenario to beat equal-weight
+/// buy-and-hold before marking a parameter set robust; off unless the setting is `true`.
+pub fn resolve_verify_require_beat_buy_and_hold(settings: &HashMap<String, String>) -> bool {
+    settings
+        .get(VERIFY_REQUIRE_BEAT_BUY_AND_HOLD_SETTING)
+        .map(|value| value.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+const VERIFY_MIN_CAPACITY_SETTING: &str = "VERIFY_MIN_CAPACITY";
+
+/// Minimum capacity estimate (dollars of AUM) verify requires before marking a parameter set
+/// robust; `None` when the setting is missing, zero or invalid.
+pub fn resolve_verify_min_capacity(settings: &HashMap<String, String>) -> Option<f64> {
+    settings
+        .get(VERIFY_MIN_CAPACITY_SETTING)
+        .map(|value| value.trim())
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0)
+}
+
+const VERIFY_MAX_ENTRY_CONCENTRATION_SETTING: &str = "VERIFY_MAX_ENTRY_CONCENTRATION";
+
+/// Largest entry-week P&L concentration verify accepts before marking a parameter set robust;
+/// `None` when the setting is missing, zero or invalid.
+pub fn resolve_verify_max_entry_concentration(settings: &HashMap<String, String>) -> Option<f64> {
+    settings
+        .get(VERIFY_MAX_ENTRY_CONCENTRATION_SETTING)
+        .map(|value| value.trim())
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0)
+}
+
+const SHADOW_TRACKING_ERROR_THRESHOLD_SETTING: &str = "SHADOW_TRACKING_ERROR_THRESHOLD";
+const DEFAULT_SHADOW_TRACKING_ERROR_THRESHOLD: f64 = 0.05;
+
+/// Gap between live and shadow-replay P&L, as a share of strategy capital, above which
+/// shadow-compare raises an alert; missing or invalid values use the default.
+pub fn resolve_shadow_tracking_error_threshold(settings: &HashMap<String, String>) -> f64 {
+    settings
+        .get(SHADOW_TRACKING_ERROR_THRESHOLD_SETTING)
+        .map(|value| value.trim())
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0)
+        .unwrap_or(DEFAULT_SHADOW_TRACKING_ERROR_THRESHOLD)
+}
+
+const VERIFY_MIN_EXPECTANCY_SETTING: &str = "VERIFY_MIN_EXPECTANCY";
+
+/// Smallest mean R-multiple verify accepts before marking a parameter set robust; `None` when
+/// the setting is missing, zero or invalid.
+pub fn resolve_verify_min_expectancy(settings: &HashMap<String, String>) -> Option<f64> {
+    settings
+        .get(VERIFY_MIN_EXPECTANCY_SETTING)
+        .map(|value| value.trim())
+        .and_then(|value| value.parse::<f64>().ok())
+        .filter(|value| value.is_finite() && *value > 0.0)
+}
+
+const CAPACITY_MAX_VOLUME_SHARE_SETTING: &str = "CAPACITY_MAX_VOLUME_SHARE";
+const DEFAULT_CAPACITY_MAX_VOLUME_SHARE: f64 = 0.01;
+const STOP_FILL_AT_STOP_PRICE_SETTING: &str = "STOP_FILL_AT_STOP_PRICE";
+const ORDER_REJECTION_RATE_SETTING: &str = "ORDER_REJECTION_RATE";
+const PARTIAL_FILL_MAX_VOLUME_SHARE_SETTING: &str = "PARTIAL_FILL_MAX_VOLUME_SHARE";
+const PARTIAL_FILL_REMAINDER_SETTING: &str = "PARTIAL_FILL_REMAINDER";
+const BACKTEST_WARMUP_POLICY_SETTING: &str = "BACKTEST_WARMUP_POLICY";
+const MIN_TICKER_HISTORY_CANDLES_SETTING: &str = "MIN_TICKER_HISTORY_CANDLES";
+const HEDGE_TICKER_SETTING: &str = "HEDGE_TICKER";
+const DEFAULT_HEDGE_TICKER: &str = "SPY";
+const VOLATILITY_INDEX_TICKER_SETTING: &str = "VOLATILITY_INDEX_TICKER";
+const DEFAULT_VOLATILITY_INDEX_TICKER: &str = "VIX";
+
+const TICKER_RUNTIME_OVERRIDES_SETTING: &str = "TICKER_RUNTIME_OVERRIDES";
+
+/// Per-ticker replacements for global entry and cost settings, from
+/// `TICKER_RUNTIME_OVERRIDES`: a JSON object keyed by ticker, e.g.
+/// `{"TQQQ": {"TRADE_SLIPPAGE_RATE": 0.002, "MINIMUM_DOLLAR_VOLUME_FOR_ENTRY": 5000000}}`.
+/// Fields left out keep the global value.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", deny_unknown_fields)]
+pub struct TickerRuntimeOverride {
+    pub trade_slippage_rate: Option<f64>,
+    pub minimum_dollar_volume_for_entry: Option<f64>,
+    pub trade_entry_price_min: Option<f64>,
+    pub trade_entry_price_max: Option<f64>,
+}
+
+impl TickerRuntimeOverride {
+    fn values(&self) -> [(&'static str, Option<f64>); 4] {
+        [
+            ("TRADE_SLIPPAGE_RATE", self.trade_slippage_rate),
+            (
+                "MINIMUM_DOLLAR_VOLUME_FOR_ENTRY",
+                self.minimum_dollar_volume_for_entry,
+            ),
+            ("TRADE_ENTRY_PRICE_MIN", self.trade_entry_price_min),
+            ("TRADE_ENTRY_PRICE_MAX", self.trade_entry_price_max),
+        ]
+    }
+}
+
+/// Uppercase ticker stored in `key`, or `default` when the setting is blank or missing.
+fn parse_ticker_setting(
+    settings: &HashMap<String, String>,
+    key: &str,
+    default: &str,
+) -> Result<String> {
+    let ticker = settings
+        .get(key)
+        .map(|value| value.trim().to_ascii_uppercase())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| default.to_string());
+    if ticker.contains(char::is_whitespace) {
+        return Err(anyhow!(
+            "Setting {} must be a single ticker (value: {})",
+            key,
+            ticker
+        ));
+    }
+    Ok(ticker)
+}
+
+/// Auxiliary ticker read as the volatility index (`VOLATILITY_INDEX_TICKER`, default VIX).
+pub fn volatility_index_ticker(settings: &HashMap<String, String>) -> Result<String> {
+    parse_ticker_setting(
+        settings,
+        VOLATILITY_INDEX_TICKER_SETTING,
+        DEFAULT_VOLATILITY_INDEX_TICKER,
+    )
+}
+
+fn parse_ticker_runtime_overrides(
+    settings: &HashMap<String, String>,
+) -> Result<HashMap<String, TickerRuntimeOverride>> {
+    let Some(raw) = settings
+        .get(TICKER_RUNTIME_OVERRIDES_SETTING)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+    else {
+        return Ok(HashMap::new());
+    };
+    let overrides: HashMap<String, TickerRuntimeOverride> =
+        serde_json::from_str(raw).map_err(|err| {
+            anyhow!(
+                "{} must be a JSON object of tickers to setting overrides: {}",
+                TICKER_RUNTIME_OVERRIDES_SETTING,
+                err
+            )
+        })?;
+    for (ticker, ticker_override) in &overrides {
+        for (key, value) in ticker_override.values() {
+            if let Some(value) = value.filter(|value| !value.is_finite() || *value < 0.0) {
+                return Err(anyhow!(
+                    "{} {} for {} must be a non-negative number (value: {})",
+                    TICKER_RUNTIME_OVERRIDES_SETTING,
+                    key,
+                    ticker,
+                    value
+                ));
+            }
+        }
+    }
+    Ok(overrides)
+}
+
+const LIVE_COST_IMPACT_COEFFICIENT_SETTING: &str = "LIVE_COST_IMPACT_COEFFICIENT";
+const LIVE_MONTHLY_COST_BUDGET_SETTING: &str = "LIVE_MONTHLY_COST_BUDGET";
+
+/// Expected-cost settings for planned live operations. Missing, zero or invalid values turn
+/// the impact term and the monthly budget gate off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveCostSettings {
+    pub impact_coefficient: f64,
+    pub monthly_budget: Option<f64>,
+}
+
+impl LiveCostSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Self {
+        let positive = |key: &str| {
+            settings
+                .get(key)
+                .map(|value| value.trim())
+                .and_then(|value| value.parse::<f64>().ok())
+                .filter(|value| value.is_finite() && *value > 0.0)
+        };
+        Self {
+            impact_coefficient: positive(LIVE_COST_IMPACT_COEFFICIENT_SETTING).unwrap_or(0.0),
+            monthly_budget: positive(LIVE_MONTHLY_COST_BUDGET_SETTING),
+        }
+    }
+}
+
+const STUCK_ORDER_TIMEOUT_MINUTES_SETTING: &str = "STUCK_ORDER_TIMEOUT_MINUTES";
+const STUCK_ORDER_POLICY_SETTING: &str = "STUCK_ORDER_POLICY";
+const DEFAULT_STUCK_ORDER_TIMEOUT_MINUTES: i64 = 30;
+
+/// What the order watchdog does with broker orders stuck past the timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StuckOrderPolicy {
+    Flag,
+    Cancel,
+    Resubmit,
+}
+
+impl StuckOrderPolicy {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "flag" => Ok(Self::Flag),
+            "cancel" => Ok(Self::Cancel),
+            "resubmit" => Ok(Self::Resubmit),
+            other => Err(anyhow!(
+                "{} must be flag, cancel or resubmit (value: {})",
+                STUCK_ORDER_POLICY_SETTING,
+                other
+            )),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Flag => "flag",
+            Self::Cancel => "cancel",
+            Self::Resubmit => "resubmit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StuckOrderWatchdogSettings {
+    pub timeout_minutes: i64,
+    pub policy: StuckOrderPolicy,
+}
+
+impl StuckOrderWatchdogSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        let timeout_minutes = match settings
+            .get(STUCK_ORDER_TIMEOUT_MINUTES_SETTING)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        {
+            Some(raw) => raw
+                .parse::<i64>()
+                .ok()
+                .filter(|value| *value > 0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} must be a positive number of minutes (value: {})",
+                        STUCK_ORDER_TIMEOUT_MINUTES_SETTING,
+                        raw
+                    )
+                })?,
+            None => DEFAULT_STUCK_ORDER_TIMEOUT_MINUTES,
+        };
+        let policy = StuckOrderPolicy::parse(
+            settings
+                .get(STUCK_ORDER_POLICY_SETTING)
+                .map(String::as_str)
+                .unwrap_or_default(),
+        )?;
+        Ok(Self {
+            timeout_minutes,
+            policy,
+        })
+    }
+}
+
+const PAPER_BROKER_LATENCY_BARS_SETTING: &str = "PAPER_BROKER_LATENCY_BARS";
+const PAPER_BROKER_PARTIAL_FILL_PROBABILITY_SETTING: &str = "PAPER_BROKER_PARTIAL_FILL_PROBABILITY";
+const DEFAULT_PAPER_BROKER_LATENCY_BARS: usize = 1;
+
+/// How the paper broker simulates fills: orders become fillable on the `latency_bars`-th daily
+/// candle after the submission day (0 allows the submission day's candle), and each order
+/// fills only half its shares with `partial_fill_probability`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaperBrokerSettings {
+    pub latency_bars: usize,
+    pub partial_fill_probability: f64,
+    pub starting_cash: f64,
+}
+
+impl PaperBrokerSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        let raw = |key: &str| {
+            settings
+                .get(key)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let latency_bars = match raw(PAPER_BROKER_LATENCY_BARS_SETTING) {
+            Some(value) => value.parse::<usize>().map_err(|_| {
+                anyhow!(
+                    "{} must be a non-negative number of candles (value: {})",
+                    PAPER_BROKER_LATENCY_BARS_SETTING,
+                    value
+                )
+            })?,
+            None => DEFAULT_PAPER_BROKER_LATENCY_BARS,
+        };
+        let partial_fill_probability = match raw(PAPER_BROKER_PARTIAL_FILL_PROBABILITY_SETTING) {
+            Some(value) => value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| (0.0..=1.0).contains(value))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} must be between 0 and 1 (value: {})",
+                        PAPER_BROKER_PARTIAL_FILL_PROBABILITY_SETTING,
+                        value
+                    )
+                })?,
+            None => 0.0,
+        };
+        Ok(Self {
+            latency_bars,
+            partial_fill_probability,
+            starting_cash: resolve_backtest_initial_capital(settings),
+        })
+    }
+}
+
+const DRIFT_QUANTITY_TOLERANCE_SETTING: &str = "DRIFT_QUANTITY_TOLERANCE";
+const DRIFT_STOP_TOLERANCE_RATIO_SETTING: &str = "DRIFT_STOP_TOLERANCE_RATIO";
+const DEFAULT_DRIFT_STOP_TOLERANCE_RATIO: f64 = 0.005;
+
+/// How far broker positions may diverge from active trades before detect-drift raises a task:
+/// shares of quantity difference per ticker, and the relative gap between a trade's stop and
+/// the closest broker stop order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftToleranceSettings {
+    pub quantity: i32,
+    pub stop_ratio: f64,
+}
+
+impl DriftToleranceSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        let raw = |key: &str| {
+            settings
+                .get(key)
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let quantity = match raw(DRIFT_QUANTITY_TOLERANCE_SETTING) {
+            Some(value) => value
+                .parse::<i32>()
+                .ok()
+                .filter(|value| *value >= 0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} must be a non-negative number of shares (value: {})",
+                        DRIFT_QUANTITY_TOLERANCE_SETTING,
+                        value
+                    )
+                })?,
+            None => 0,
+        };
+        let stop_ratio = match raw(DRIFT_STOP_TOLERANCE_RATIO_SETTING) {
+            Some(value) => value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value >= 0.0)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} must be a non-negative ratio (value: {})",
+                        DRIFT_STOP_TOLERANCE_RATIO_SETTING,
+                        value
+                    )
+                })?,
+            None => DEFAULT_DRIFT_STOP_TOLERANCE_RATIO,
+        };
+        Ok(Self {
+            quantity,
+            stop_ratio,
+        })
+    }
+}
+
+const SKIP_ALERT_RULES_SETTING: &str = "SKIP_ALERT_RULES";
+const SKIP_ALERT_WEBHOOK_URL_SETTING: &str = "SKIP_ALERT_WEBHOOK_URL";
+const DEFAULT_SKIP_ALERT_WINDOW_DAYS: i64 = 7;
+
+/// Alert when `reason` was recorded for the same account and ticker on at least `min_days`
+/// distinct days within the trailing `window_days` calendar days.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipAlertRule {
+    pub reason: String,
+    pub min_days: usize,
+    pub window_days: i64,
+}
+
+impl SkipAlertRule {
+    /// Parses `reason` (once today), `reason:N` (N of the last 7 days) or `reason:N/W` (N of
+    /// the last W days).
+    pub fn parse(raw: &str) -> Result<Self> {
+        let invalid = || {
+            anyhow!(
+                "{} entries must look like reason, reason:N or reason:N/W (value: {})",
+                SKIP_ALERT_RULES_SETTING,
+                raw
+            )
+        };
+        let (reason, threshold) = match raw.split_once(':') {
+            Some((reason, threshold)) => (reason.trim(), Some(threshold.trim())),
+            None => (raw.trim(), None),
+        };
+        if reason.is_empty() || reason.chars().any(char::is_whitespace) {
+            return Err(invalid());
+        }
+        let (min_days, window_days) = match threshold {
+            None => (1, 1),
+            Some(threshold) => {
+                let (count, window) = match threshold.split_once('/') {
+                    Some((count, window)) => (count.trim(), Some(window.trim())),
+                    None => (threshold, None),
+                };
+                let min_days = count
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|value| *value > 0)
+                    .ok_or_else(invalid)?;
+                let window_days = match window {
+                    Some(window) => window
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|value| *value >= min_days as i64)
+                        .ok_or_else(invalid)?,
+                    None => (min_days as i64).max(DEFAULT_SKIP_ALERT_WINDOW_DAYS),
+                };
+                (min_days, window_days)
+            }
+        };
+        Ok(Self {
+            reason: reason.to_string(),
+            min_days,
+            window_days,
+        })
+    }
+}
+
+/// Skip-reason alert rules and the optional webhook they are posted to. No rules (the
+/// default) disables alerting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkipAlertSettings {
+    pub rules: Vec<SkipAlertRule>,
+    pub webhook_url: Option<String>,
+}
+
+impl SkipAlertSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        let rules = settings
+            .get(SKIP_ALERT_RULES_SETTING)
+            .map(String::as_str)
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(SkipAlertRule::parse)
+            .collect::<Result<Vec<_>>>()?;
+        let webhook_url = settings
+            .get(SKIP_ALERT_WEBHOOK_URL_SETTING)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string);
+        Ok(Self { rules, webhook_url })
+    }
+}
+
+const ACCOUNT_NOTIFICATION_CHANNELS_SETTING: &str = "ACCOUNT_NOTIFICATION_CHANNELS";
+const NOTIFICATION_DAILY_SUMMARY_HOUR_UTC_SETTING: &str = "NOTIFICATION_DAILY_SUMMARY_HOUR_UTC";
+const DEFAULT_NOTIFICATION_DAILY_SUMMARY_HOUR_UTC: u32 = 21;
+const NOTIFICATION_ALL_ACCOUNTS_KEY: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Fill,
+    StopTriggered,
+    Mismatch,
+    DailyPnl,
+}
+
+impl NotificationEvent {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fill => "fill",
+            Self::StopTriggered => "stop_triggered",
+            Self::Mismatch => "mismatch",
+            Self::DailyPnl => "daily_pnl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "channel", rename_all = "lowercase")]
+pub enum NotificationChannel {
+    Telegram {
+        #[serde(rename = "botToken")]
+        bot_token: String,
+        #[serde(rename = "chatId")]
+        chat_id: String,
+    },
+    Discord {
+        #[serde(rename = "webhookUrl")]
+        webhook_url: String,
+    },
+    Email {
+        to: String,
+    },
+}
+
+impl NotificationChannel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Telegram { .. } => "telegram",
+            Self::Discord { .. } => "discord",
+            Self::Email { .. } => "email",
+        }
+    }
+}
+
+/// One channel an account notifies, limited to `events` when given.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NotificationSubscription {
+    #[serde(flatten)]
+    pub channel: NotificationChannel,
+    #[serde(default)]
+    pub events: Option<Vec<NotificationEvent>>,
+}
+
+impl NotificationSubscription {
+    pub fn wants(&self, event: NotificationEvent) -> bool {
+        self.events
+            .as_ref()
+            .is_none_or(|events| events.contains(&event))
+    }
+}
+
+/// Per-account notification channels from `ACCOUNT_NOTIFICATION_CHANNELS`, a JSON object keyed
+/// by account id (`*` applies to accounts without their own entry). Empty disables notifications.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationSettings {
+    pub channels_by_account: HashMap<String, Vec<NotificationSubscription>>,
+    pub daily_summary_hour_utc: u32,
+}
+
+impl NotificationSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        let channels_by_account = match settings
+            .get(ACCOUNT_NOTIFICATION_CHANNELS_SETTING)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        {
+            Some(raw) => serde_json::from_str(raw).map_err(|err| {
+                anyhow!(
+                    "{} must be a JSON object of account ids to channel lists: {}",
+                    ACCOUNT_NOTIFICATION_CHANNELS_SETTING,
+                    err
+                )
+            })?,
+            None => HashMap::new(),
+        };
+        let daily_summary_hour_utc = match settings
+            .get(NOTIFICATION_DAILY_SUMMARY_HOUR_UTC_SETTING)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        {
+            Some(raw) => raw
+                .parse::<u32>()
+                .ok()
+                .filter(|hour| *hour < 24)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "{} must be an hour from 0 to 23 (value: {})",
+                        NOTIFICATION_DAILY_SUMMARY_HOUR_UTC_SETTING,
+                        raw
+                    )
+                })?,
+            None => DEFAULT_NOTIFICATION_DAILY_SUMMARY_HOUR_UTC,
+        };
+        Ok(Self {
+            channels_by_account,
+            daily_summary_hour_utc,
+        })
+    }
+
+    pub fn channels_for(&self, account_id: &str) -> &[NotificationSubscription] {
+        self.channels_by_account
+            .get(account_id)
+            .or_else(|| self.channels_by_account.get(NOTIFICATION_ALL_ACCOUNTS_KEY))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// Configuration for position sizing strategies
+#[derive(Debug, Clone)]
+pub struct PositionSizingConfig {
+    pub mode: i32, // 0=fixed, 1=confidence, 2=vol_target, 3=conf+vol
+    pub vol_target_annual: f64,
+    pub vol_lookback: usize,
+}
+
+impl Default for PositionSizingConfig {
+    fn default() -> Self {
+        Self {
+            mode: 0,
+            vol_target_annual: 0.0,
+            vol_lookback: 20,
+        }
+    }
+}
+
+/// When a backtest entry fills relative to its signal candle (`fillTiming` 0-2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillTiming {
+    /// Open of the next candle (0, the default).
+    #[default]
+    NextOpen,
+    /// Close of the signal candle, for signals computed intraday (1).
+    SameClose,
+    /// Close of the next candle (2).
+    NextClose,
+}
+
+impl FillTiming {
+    fn from_param(value: i32) -> Self {
+        match value {
+            1 => Self::SameClose,
+            2 => Self::NextClose,
+            _ => Self::NextOpen,
+        }
+    }
+}
+
+/// Time-decay exit: after each of up to three holding-day thresholds (`scaleOutDays1-3`,
+/// 0 = unused) a further `scaleOutFraction` of the original position is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScaleOutConfig {
+    pub days: [i32; 3],
+    pub fraction: f64,
+}
+
+impl ScaleOutConfig {
+    /// Signed quantity a position opened with `original_quantity` should be down to after
+    /// `days_held` days, or `None` before the first threshold or when the schedule is off.
+    pub fn target_quantity(&self, original_quantity: i32, days_held: i64) -> Option<i32> {
+        if !self.fraction.is_finite() || self.fraction <= 0.0 {
+            return None;
+        }
+        let steps = self
+            .days
+            .iter()
+            .filter(|days| **days > 0 && days_held >= i64::from(**days))
+            .count();
+        if steps == 0 {
+            return None;
+        }
+        let original = original_quantity.abs();
+        let closed =
+            ((original as f64 * self.fraction * steps as f64).round() as i32).min(original);
+        Some((original - closed) * original_quantity.signum())
+    }
+}
+
+/// Daily loss limit: a day's portfolio drop above `max_daily_loss` (`circuitBreakerLoss`,
+/// 0 = off) freezes new entries for `freeze_days` (`circuitBreakerDays`) and, with
+/// `liquidate` (`circuitBreakerLiquidate`), closes every open position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub max_daily_loss: f64,
+    pub freeze_days: i32,
+    pub liquidate: bool,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_daily_loss: 0.0,
+            freeze_days: 5,
+            liquidate: false,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Whether a day that took the portfolio from `previous_value` to `current_value` trips
+    /// the breaker.
+    pub fn trips(&self, previous_value: f64, current_value: f64) -> bool {
+        let enabled = self.max_daily_loss.is_finite() && self.max_daily_loss > 0.0;
+        if !enabled || !previous_value.is_finite() || previous_value <= 0.0 {
+            return false;
+        }
+        (previous_value - current_value) / previous_value > self.max_daily_loss
+    }
+}
+
+/// Hedging overlay: a short position in the hedge ticker (`HEDGE_TICKER`) sized to `beta`
+/// (`hedgeBeta`, 0 = off) times the long exposure, rebalanced once it drifts from that target
+/// by more than `rebalance_threshold` (`hedgeRebalanceThreshold`) of the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HedgeConfig {
+    pub beta: f64,
+    pub rebalance_threshold: f64,
+}
+
+impl Default for HedgeConfig {
+    fn default() -> Self {
+        Self {
+            beta: 0.0,
+            rebalance_threshold: 0.1,
+        }
+    }
+}
+
+impl HedgeConfig {
+    pub fn enabled(&self) -> bool {
+        self.beta.is_finite() && self.beta > 0.0
+    }
+
+    /// Shares to hold short at `price` against `long_exposure` when the `hedged_shares`
+    /// currently short are due for a rebalance, or `None` while they are within the threshold.
+    pub fn rebalance_target(
+        &self,
+        long_exposure: f64,
+        hedged_shares: i32,
+        price: f64,
+    ) -> Option<i32> {
+        if !self.enabled() || !price.is_finite() || price <= 0.0 {
+            return None;
+        }
+        let target = (self.beta * long_exposure.max(0.0) / price).floor() as i32;
+        if target == hedged_shares {
+            return None;
+        }
+        if target == 0 {
+            return Some(0);
+        }
+        let drift = f64::from((hedged_shares - target).abs()) / f64::from(target);
+        (drift > self.rebalance_threshold.max(0.0)).then_some(target)
+    }
+}
+
+/// Configuration for stop loss strategies
+#[derive(Debug, Clone)]
+pub struct StopLossConfig {
+    pub mode: i32, // 0=percent, 1=atr, 2=chandelier, 3=percent-of-peak, 4=breakeven
+    pub ratio: f64,
+    pub atr_period: usize,
+    pub atr_multiplier: f64,
+    pub breakeven_r_multiple: f64,
+}
+
+impl Default for StopLossConfig {
+    fn default() -> Self {
+        Self {
+            mode: 0,
+            ratio: 0.05,
+            atr_period: 20,
+            atr_multiplier: 2.0,
+            breakeven_r_multiple: 1.0,
+        }
+    }
+}
+
+/// Account-level limits the planner applies on top of each strategy's stop configuration.
+/// They only ever tighten: the stop closer to the entry and the smaller quantity win. The
+/// take-profit multiple adds a limit target linked one-cancels-other with the entry's stop.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AccountRiskOverrides {
+    /// Widest initial stop distance as a fraction of the entry price (`accounts.max_stop_ratio`).
+    pub max_stop_ratio: Option<f64>,
+    /// Largest loss in the account currency if a new entry's initial stop fills
+    /// (`accounts.max_risk_per_trade`).
+    pub max_risk_per_trade: Option<f64>,
+    /// Take-profit distance from the entry price in multiples of the initial stop risk
+    /// (`accounts.take_profit_r_multiple`).
+    pub take_profit_r_multiple: Option<f64>,
+}
+
+impl AccountRiskOverrides {
+    /// Drops values outside their valid range so a bad column never blocks planning.
+    pub fn new(
+        max_stop_ratio: Option<f64>,
+        max_risk_per_trade: Option<f64>,
+        take_profit_r_multiple: Option<f64>,
+    ) -> Self {
+        Self {
+            max_stop_ratio: max_stop_ratio
+                .filter(|ratio| ratio.is_finite() && *ratio > 0.0 && *ratio < 1.0),
+            max_risk_per_trade: max_risk_per_trade
+                .filter(|amount| amount.is_finite() && *amount > 0.0),
+            take_profit_r_multiple: take_profit_r_multiple
+                .filter(|multiple| multiple.is_finite() && *multiple > 0.0),
+        }
+    }
+
+    /// Limit price `take_profit_r_multiple` times the risk to `stop` beyond `price`, on the
+    /// profit side of either a long or a short entry. Entries without a stop get no target.
+    pub fn take_profit(&self, price: f64, stop: Option<f64>) -> Option<f64> {
+        let multiple = self.take_profit_r_multiple?;
+        let risk = price - stop?;
+        (risk.is_finite() && risk != 0.0).then_some(price + multiple * risk)
+    }
+
+    /// Raises a long entry's stop to the account's maximum distance below `price`; a strategy
+    /// stop that is already tighter is kept, and an entry without a stop gets the account's.
+    pub fn tighten_stop(&self, price: f64, strategy_stop: Option<f64>) -> Option<f64> {
+        let Some(ratio) = self.max_stop_ratio else {
+            return strategy_stop;
+        };
+        let account_stop = price * (1.0 - ratio);
+        match strategy_stop {
+            Some(stop) if stop >= account_stop => Some(stop),
+            _ => Some(account_stop),
+        }
+    }
+
+    /// Largest long quantity whose loss down to `stop` stays within `max_risk_per_trade`, or
+    /// `None` without a dollar limit. Without a stop the whole position counts as at risk.
+    pub fn max_quantity(&self, price: f64, stop: Option<f64>) -> Option<i32> {
+        let max_risk = self.max_risk_per_trade?;
+        let risk_per_share = match stop {
+            Some(stop) if stop < price => price - stop,
+            Some(_) => return None,
+            None => price,
+        };
+        if !(risk_per_share.is_finite() && risk_per_share > 0.0) {
+            return None;
+        }
+        Some((max_risk / risk_per_share).floor().min(i32::MAX as f64) as i32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalOptimizationObjective {
+    Cagr,
+    Sharpe,
+}
+
+impl LocalOptimizationObjective {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "cagr" => Ok(Self::Cagr),
+            "sharpe" | "sharpe_ratio" => Ok(Self::Sharpe),
+            other => Err(anyhow!(
+                "OPTIMIZATION_OBJECTIVE must be CAGR or SHARPE (value: {})",
+                other
+            )),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Cagr => "CAGR",
+            Self::Sharpe => "Sharpe ratio",
+        }
+    }
+}
+
+/// What a backtest does with the part of an entry order the fill candle's volume could not
+/// absorb (`PARTIAL_FILL_REMAINDER`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartialFillRemainder {
+    /// Drop the unfilled shares.
+    #[default]
+    Cancel,
+    /// Keep working the unfilled shares at the open of the following candles, under the same
+    /// volume cap, until they fill or an opposite signal cancels them.
+    Carry,
+}
+
+impl PartialFillRemainder {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "cancel" => Ok(Self::Cancel),
+            "carry" => Ok(Self::Carry),
+            other => Err(anyhow!(
+                "PARTIAL_FILL_REMAINDER must be cancel or carry (value: {})",
+                other
+            )),
+        }
+    }
+}
+
+/// How strategy backtests treat the strategy's `min_data_points` warm-up
+/// (`BACKTEST_WARMUP_POLICY`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WarmupPolicy {
+    /// Each ticker waits for its own warm-up, and an earlier start date still starts trading
+    /// the tickers that have enough history.
+    #[default]
+    Skip,
+    /// Like `Skip`, but a start date inside the warm-up is moved to the first date after it.
+    Strict,
+    /// Fail the backtest when the start date falls inside the warm-up or a ticker's history is
+    /// too short to ever finish it.
+    Error,
+}
+
+impl WarmupPolicy {
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "" | "skip" => Ok(Self::Skip),
+            "strict" => Ok(Self::Strict),
+            "error" => Ok(Self::Error),
+            other => Err(anyhow!(
+                "BACKTEST_WARMUP_POLICY must be skip, strict or error (value: {})",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineRuntimeSettings {
+    pub trade_close_fee_rate: f64,
+    pub trade_slippage_rate: f64,
+    /// Borrow fee of shorts in tickers without imported borrow fee reports.
+    pub short_borrow_fee_annual_rate: f64,
+    /// Scales imported per-ticker borrow fee rates; `with_cost_multiplier` raises it with the
+    /// other cost rates.
+    pub borrow_fee_multiplier: f64,
+    pub trade_entry_price_min: f64,
+    pub trade_entry_price_max: f64,
+    pub minimum_dollar_volume_for_entry: f64,
+    pub minimum_dollar_volume_lookback: usize,
+    pub local_optimization_version: i32,
+    pub local_optimization_step_multipliers: Vec<f64>,
+    pub local_optimization_objective: LocalOptimizationObjective,
+    pub max_allowed_drawdown_ratio: f64,
+    pub capacity_max_volume_share: f64,
+    /// Fill touched stops at the stop price even when the candle gaps through it
+    /// (`STOP_FILL_AT_STOP_PRICE`), for comparing against gap-aware open fills.
+    pub stop_fill_at_stop_price: bool,
+    /// Share of backtest entry orders the simulated broker rejects (`ORDER_REJECTION_RATE`);
+    /// which orders are rejected depends only on ticker and date, so reruns agree.
+    pub order_rejection_rate: f64,
+    /// Largest share of the fill candle's volume one backtest entry may take
+    /// (`PARTIAL_FILL_MAX_VOLUME_SHARE`, 0 = no cap).
+    pub partial_fill_max_volume_share: f64,
+    pub partial_fill_remainder: PartialFillRemainder,
+    pub warmup_policy: WarmupPolicy,
+    /// Candles a ticker needs up to a date before strategies signal on it
+    /// (`MIN_TICKER_HISTORY_CANDLES`, 0 = no minimum).
+    pub min_ticker_history_candles: usize,
+    /// Per-ticker slippage, minimum dollar volume and entry price bounds; see the `*_for`
+    /// accessors.
+    pub ticker_overrides: HashMap<String, TickerRuntimeOverride>,
+    /// Exchange clock that maps signal, candle and fill timestamps to trading days
+    /// (`EXCHANGE_TIMEZONE`).
+    pub session_calendar: SessionCalendar,
+    /// Instrument the hedging overlay shorts (`HEDGE_TICKER`, default SPY); it takes no
+    /// strategy signals while `hedgeBeta` is set.
+    pub hedge_ticker: String,
+    /// Auxiliary ticker read as the volatility index by model features and the
+    /// `maxVolatilityIndex` entry filter (`VOLATILITY_INDEX_TICKER`, default VIX).
+    pub volatility_index_ticker: String,
+}
+
+impl EngineRuntimeSettings {
+    pub fn from_settings_map(settings: &HashMap<String, String>) -> Result<Self> {
+        let trade_close_fee_rate =
+            require_setting_f64(settings, "TRADE_CLOSE_FEE_RATE", Some(0.0), None)?;
+        let trade_slippage_rate =
+            require_setting_f64(settings, "TRADE_SLIPPAGE_RATE", Some(0.0), None)?;
+        let short_borrow_fee_annual_rate =
+            require_setting_f64(settings, "SHORT_BORROW_FEE_ANNUAL_RATE", Some(0.0), None)?;
+        let trade_entry_price_min =
+            require_setting_f64(settings, "TRADE_ENTRY_PRICE_MIN", Some(0.0), None)?;
+        let trade_entry_price_max =
+            require_setting_f64(settings, "TRADE_ENTRY_PRICE_MAX", Some(0.0), None)?;
+        let minimum_dollar_volume_for_entry =
+            require_setting_f64(settings, "MINIMUM_DOLLAR_VOLUME_FOR_ENTRY", Some(0.0), None)?;
+        let minimum_dollar_volume_lookback =
+            require_setting_usize(settings, "MINIMUM_DOLLAR_VOLUME_LOOKBACK", 0)?;
+        let local_optimization_version =
+            require_setting_i32(settings, "LOCAL_OPTIMIZATION_VERSION", 0)?;
+        let local_optimization_step_multipliers =
+            require_setting_f64_list(settings, "LOCAL_OPTIMIZATION_STEP_MULTIPLIERS")?;
+        let raw_local_optimization_objective = settings
+            .get("OPTIMIZATION_OBJECTIVE")
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .unwrap_or("cagr");
+        let local_optimization_objective =
+            LocalOptimizationObjective::parse(raw_local_optimization_objective)?;
+        let max_allowed_drawdown_ratio =
+            require_setting_f64(settings, "MAX_ALLOWED_DRAWDOWN_RATIO", Some(0.0), Some(1.0))?;
+        let capacity_max_volume_share = if settings.contains_key(CAPACITY_MAX_VOLUME_SHARE_SETTING)
+        {
+            require_setting_f64(
+                settings,
+                CAPACITY_MAX_VOLUME_SHARE_SETTING,
+                Some(0.0),
+                Some(1.0),
+            )?
+        } else {
+            DEFAULT_CAPACITY_MAX_VOLUME_SHARE
+        };
+
+        let stop_fill_at_stop_price = settings
+            .get(STOP_FILL_AT_STOP_PRICE_SETTING)
+            .map(|value| value.trim().eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        let order_rejection_rate = if settings.contains_key(ORDER_REJECTION_RATE_SETTING) {
+            require_setting_f64(settings, ORDER_REJECTION_RATE_SETTING, Some(0.0), Some(1.0))?
+        } else {
+            0.0
+        };
+        let partial_fill_max_volume_share =
+            if settings.contains_key(PARTIAL_FILL_MAX_VOLUME_SHARE_SETTING) {
+                require_setting_f64(
+                    settings,
+                    PARTIAL_FILL_MAX_VOLUME_SHARE_SETTING,
+                    Some(0.0),
+                    Some(1.0),
+                )?
+            } else {
+                0.0
+            };
+        let partial_fill_remainder = settings
+            .get(PARTIAL_FILL_REMAINDER_SETTING)
+            .map(|value| PartialFillRemainder::parse(value))
+            .transpose()?
+            .unwrap_or_default();
+        let warmup_policy = settings
+            .get(BACKTEST_WARMUP_POLICY_SETTING)
+            .map(|value| WarmupPolicy::parse(value))
+            .transpose()?
+            .unwrap_or_default();
+        let min_ticker_history_candles =
+            if settings.contains_key(MIN_TICKER_HISTORY_CANDLES_SETTING) {
+                require_setting_usize(settings, MIN_TICKER_HISTORY_CANDLES_SETTING, 0)?
+            } else {
+                0
+            };
+
+        if trade_entry_price_max < trade_entry_price_min {
+            return Err(anyhow!(
+                "TRADE_ENTRY_PRICE_MAX ({}) must be >= TRADE_ENTRY_PRICE_MIN ({})",
+                trade_entry_price_max,
+                trade_entry_price_min
+            ));
+        }
+
+        let ticker_overrides = parse_ticker_runtime_overrides(settings)?;
+        let session_calendar = SessionCalendar::from_settings_map(settings)?;
+        let hedge_ticker =
+            parse_ticker_setting(settings, HEDGE_TICKER_SETTING, DEFAULT_HEDGE_TICKER)?;
+        let volatility_index_ticker = volatility_index_ticker(settings)?;
+
+        let runtime_settings = Self {
+            trade_close_fee_rate,
+            trade_slippage_rate,
+            short_borrow_fee_annual_rate,
+            borrow_fee_multiplier: 1.0,
+            trade_entry_price_min,
+            trade_entry_price_max,
+            minimum_dollar_volume_for_entry,
+            minimum_dollar_volume_lookback,
+            local_optimization_version,
+            local_optimization_step_multipliers,
+            local_optimization_objective,
+            max_allowed_drawdown_ratio,
+            capacity_max_volume_share,
+            stop_fill_at_stop_price,
+            order_rejection_rate,
+            partial_fill_max_volume_share,
+            partial_fill_remainder,
+            warmup_policy,
+            min_ticker_history_candles,
+            ticker_overrides,
+            session_calendar,
+            hedge_ticker,
+            volatility_index_ticker,
+        };
+        for ticker in runtime_settings.ticker_overrides.keys() {
+            let (min_price, max_price) = runtime_settings.entry_price_range_for(ticker);
+            if max_price < min_price {
+                return Err(anyhow!(
+                    "{} entry price range for {} is empty ({}..{})",
+                    TICKER_RUNTIME_OVERRIDES_SETTING,
+                    ticker,
+                    min_price,
+                    max_price
+                ));
+            }
+        }
+        Ok(runtime_settings)
+    }
+
+    pub fn slippage_rate_for(&self, ticker: &str) -> f64 {
+        self.ticker_overrides
+            .get(ticker)
+            .and_then(|ticker_override| ticker_override.trade_slippage_rate)
+            .unwrap_or(self.trade_slippage_rate)
+    }
+
+    pub fn minimum_dollar_volume_for(&self, ticker: &str) -> f64 {
+        self.ticker_overrides
+            .get(ticker)
+            .and_then(|ticker_override| ticker_override.minimum_dollar_volume_for_entry)
+            .unwrap_or(self.minimum_dollar_volume_for_entry)
+    }
+
+    /// Inclusive entry price bounds for `ticker`.
+    pub fn entry_price_range_for(&self, ticker: &str) -> (f64, f64) {
+        let ticker_override = self.ticker_overrides.get(ticker);
+        (
+            ticker_override
+                .and_then(|ticker_override| ticker_override.trade_entry_price_min)
+                .unwrap_or(self.trade_entry_price_min),
+            ticker_override
+                .and_then(|ticker_override| ticker_override.trade_entry_price_max)
+                .unwrap_or(self.trade_entry_price_max),
+        )
+    }
+
+    /// Returns a copy with slippage and fee rates scaled by `multiplier`.
+    pub fn with_cost_multiplier(&self, multiplier: f64) -> Self {
+        let mut scaled = self.clone();
+        scaled.trade_close_fee_rate *= multiplier;
+        scaled.trade_slippage_rate *= multiplier;
+        scaled.short_borrow_fee_annual_rate *= multiplier;
+        scaled.borrow_fee_multiplier *= multiplier;
+        for ticker_override in scaled.ticker_overrides.values_mut() {
+            if let Some(rate) = ticker_override.trade_slippage_rate.as_mut() {
+                *rate *= multiplier;
+            }
+        }
+        scaled
+    }
+
+    /// Returns a copy with only slippage rates scaled by `multiplier`.
+    pub fn with_slippage_multiplier(&self, multiplier: f64) -> Self {
+        let mut scaled = self.clone();
+        scaled.trade_slippage_rate *= multiplier;
+        for ticker_override in scaled.ticker_overrides.values_mut() {
+            if let Some(rate) = ticker_override.trade_slippage_rate.as_mut() {
+                *rate *= multiplier;
+            }
+        }
+        scaled
+    }
+}
+
+/// Execution cost assumption replayed during verification.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostScenario {
+    pub label: &'static str,
+    pub cost_multiplier: f64,
+}
+
+pub const BASE_COST_SCENARIO_LABEL: &str = "base";
+
+pub const VERIFY_COST_SCENARIOS: [CostScenario; 3] = [
+    CostScenario {
+        label: "low",
+        cost_multiplier: 0.5,
+    },
+    CostScenario {
+        label: BASE_COST_SCENARIO_LABEL,
+        cost_multiplier: 1.0,
+    },
+    CostScenario {
+        label: "high",
+        cost_multiplier: 2.0,
+    },
+];
+
+/// Main engine configuration struct that groups all parameters
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    // Capital and trading parameters
+    pub initial_capital: f64,
+    pub trade_size_ratio: f64,
+    pub sell_fraction: f64,
+    pub minimum_trade_size: f64,
+    pub max_leverage: f64,
+    pub allow_short_selling: bool,
+    // Buy parameters
+    pub buy_discount_ratio: f64,
+
+    // Holding and limits
+    pub max_holding_days: i32,
+    /// Annualized realized volatility above which `max_holding_days` shrinks in proportion,
+    /// measured over `volLookback` sessions (`holdingVolTarget`, 0 = fixed limit).
+    pub holding_vol_target: f64,
+    /// Positions are closed, and no new ones opened, this many calendar days or fewer before
+    /// the ticker's next earnings date (`earningsExitDays`, 0 = off).
+    pub earnings_exit_days: i32,
+    /// Entries are skipped while the volatility index closed above this level on the signal
+    /// date (`maxVolatilityIndex`, 0 = off).
+    pub max_volatility_index: f64,
+    /// Short entries are skipped while the ticker's latest reported borrow fee is above this
+    /// annual rate (`maxBorrowFeeRate`, 0 = off).
+    pub max_borrow_fee_rate: f64,
+    /// Buys are skipped while the ticker's news sentiment on the signal date is below this
+    /// score; a missing score counts as neutral 0 (`minSentimentScore`, -1 = off).
+    pub min_sentiment_score: f64,
+    /// Entries are not opened on FOMC or CPI release days (`pauseOnEconomicEvents`, 0 = off).
+    pub pause_on_economic_events: bool,
+    /// Weekly entry tick: buys are queued and executed at the open of the first trading day
+    /// on or after this weekday each week (`entryWeekday` 1-5 = Monday-Friday, 0 = daily).
+    pub entry_weekday: Option<Weekday>,
+    pub fill_timing: FillTiming,
+    pub scale_out: ScaleOutConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub hedge: HedgeConfig,
+
+    // Grouped configurations
+    pub position_sizing: PositionSizingConfig,
+    pub stop_loss: StopLossConfig,
+
+    // Raw parameters for reference
+    pub raw_parameters: HashMap<String, f64>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: 100000.0,
+            trade_size_ratio: 0.02,
+            sell_fraction: 1.0,
+            minimum_trade_size: 50.0,
+            max_leverage: 1.0,
+            allow_short_selling: false,
+            buy_discount_ratio: 0.0,
+            max_holding_days: 365,
+            holding_vol_target: 0.0,
+            earnings_exit_days: 0,
+            max_volatility_index: 0.0,
+            max_borrow_fee_rate: 0.0,
+            min_sentiment_score: -1.0,
+            pause_on_economic_events: false,
+            entry_weekday: None,
+            fill_timing: FillTiming::NextOpen,
+            scale_out: ScaleOutConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            hedge: HedgeConfig::default(),
+            position_sizing: PositionSizingConfig::default(),
+            stop_loss: StopLossConfig::default(),
+            raw_parameters: HashMap::new(),
+        }
+    }
+}
+
+impl EngineConfig {
+    /// Holding-day limit of a position whose ticker runs at `realized_vol` annualized
+    /// volatility: `max_holding_days` times `holding_vol_target / realized_vol` while
+    /// volatility is above the target, never below one day.
+    pub fn max_holding_days_at(&self, realized_vol: f64) -> i64 {
+        let max = i64::from(self.max_holding_days);
+        let above_target = self.holding_vol_target > 0.0
+            && realized_vol.is_finite()
+            && realized_vol > self.holding_vol_target;
+        if max <= 0 || !above_target {
+            return max;
+        }
+        ((max as f64 * self.holding_vol_target / realized_vol).round() as i64).clamp(1, max)
+    }
+
+    /// Create a new EngineConfig from a parameter map
+    pub fn from_parameters(parameters: &HashMap<String, f64>) -> Self {
+        use crate::param_utils::*;
+
+        let max_leverage_raw = get_param(parameters, "maxLeverage", 1.0);
+        let max_leverage = if max_leverage_raw.is_finite() && max_leverage_raw >= 1.0 {
+            max_leverage_raw
+        } else {
+            1.0
+        };
+
+        Self {
+            initial_capital: get_param(parameters, "initialCapital", 100000.0),
+            trade_size_ratio: get_param(parameters, "tradeSizeRatio", 0.02),
+            sell_fraction: coerce_binary_param(get_param(parameters, "sellFraction", 1.0), 1.0),
+            minimum_trade_size: get_param(parameters, "minimumTradeSize", 50.0),
+            max_leverage,
+            allow_short_selling: get_param(parameters, "allowShortSelling", 0.0) >= 0.5,
+            buy_discount_ratio: get_param(parameters, "buyDiscountRatio", 0.0),
+            max_holding_days: get_rounded_param(parameters, "maxHoldingDays", 365),
+            holding_vol_target: get_param(parameters, "holdingVolTarget", 0.0),
+            earnings_exit_days: get_rounded_param(parameters, "earningsExitDays", 0).max(0),
+            max_volatility_index: get_param(parameters, "maxVolatilityIndex", 0.0),
+            max_borrow_fee_rate: get_param(parameters, "maxBorrowFeeRate", 0.0),
+            min_sentiment_score: get_param(parameters, "minSentimentScore", -1.0),
+            pause_on_economic_events: get_param(parameters, "pauseOnEconomicEvents", 0.0) >= 0.5,
+            entry_weekday: match get_rounded_param(parameters, "entryWeekday", 0) {
+                1 => Some(Weekday::Mon),
+                2 => Some(Weekday::Tue),
+                3 => Some(Weekday::Wed),
+                4 => Some(Weekday::Thu),
+                5 => Some(Weekday::Fri),
+                _ => None,
+            },
+            fill_timing: FillTiming::from_param(get_rounded_param(parameters, "fillTiming", 0)),
+            scale_out: ScaleOutConfig {
+                days: [
+                    get_rounded_param(parameters, "scaleOutDays1", 0),
+                    get_rounded_param(parameters, "scaleOutDays2", 0),
+                    get_rounded_param(parameters, "scaleOutDays3", 0),
+                ],
+                fraction: get_param(parameters, "scaleOutFraction", 0.0),
+            },
+            circuit_breaker: CircuitBreakerConfig {
+                max_daily_loss: get_param(parameters, "circuitBreakerLoss", 0.0),
+                freeze_days: get_rounded_param(parameters, "circuitBreakerDays", 5).max(0),
+                liquidate: get_param(parameters, "circuitBreakerLiquidate", 0.0) >= 0.5,
+            },
+            hedge: HedgeConfig {
+                beta: get_param(parameters, "hedgeBeta", 0.0),
+                rebalance_threshold: get_param(parameters, "hedgeRebalanceThreshold", 0.1),
+            },
+            position_sizing: PositionSizingConfig {
+                mode: get_rounded_param(parameters, "positionSizingMode", 0),
+                vol_target_annual: get_param(parameters, "volTargetAnnual", 0.0),
+                vol_lookback: get_usize_param_min(parameters, "volLookback", 20, 1),
+            },
+            stop_loss: StopLossConfig {
+                mode: get_rounded_param(parameters, "stopLossMode", 0),
+                ratio: get_param(parameters, "stopLossRatio", 0.05),
+                atr_period: get_usize_param_min(parameters, "atrPeriod", 20, 1),
+                atr_multiplier: get_param(parameters, "atrMultiplier", 2.0),
+                breakeven_r_multiple: get_param(parameters, "breakevenRMultiple", 1.0),
+            },
+            raw_parameters: parameters.clone(),
+        }
+    }
+}
+
+fn require_setting<'a>(settings: &'a HashMap<String, String>, key: &str) -> Result<&'a str> {
+    settings
+        .get(key)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("Missing required setting {}", key))
+}
+
+pub fn require_setting_date(settings: &HashMap<String, String>, key: &str) -> Result<NaiveDate> {
+    let raw = require_setting(settings, key)?;
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d").map_err(|_| {
+        anyhow!(
+            "Setting {} must be a date in YYYY-MM-DD format (value: {})",
+            key,
+            raw
+        )
+    })
+}
+
+fn require_setting_f64(
+    settings: &HashMap<String, String>,
+    key: &str,
+    min: Option<f64>,
+    max: Option<f64>,
+) -> Result<f64> {
+    let raw = require_setting(settings, key)?;
+    let value = raw
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Setting {} must be a number (value: {})", key, raw))?;
+    if !value.is_finite() {
+        return Err(anyhow!("Setting {} must be finite (value: {})", key, raw));
+    }
+    if let Some(min_value) = min {
+        if value < min_value {
+            return Err(anyhow!(
+                "Setting {} must be >= {} (value: {})",
+                key,
+                min_value,
+                raw
+            ));
+        }
+    }
+    if let Some(max_value) = max {
+        if value > max_value {
+            return Err(anyhow!(
+                "Setting {} must be <= {} (value: {})",
+                key,
+                max_value,
+                raw
+            ));
+        }
+    }
+    Ok(value)
+}
+
+fn require_setting_usize(
+    settings: &HashMap<String, String>,
+    key: &str,
+    min: usize,
+) -> Result<usize> {
+    let raw = require_setting(settings, key)?;
+    let value = raw
+        .parse::<f64>()
+        .map_err(|_| anyhow!("Setting {} must be a number (value: {})", key, raw))?;
+    if !value.is_finite() {
+        return Err(anyhow!("Setting {} must be finite (value: {})", key, raw));
+    }
+    if value.fract() != 0.0 {
+        return Err(anyhow!(
+            "Setting {} must be an integer (value: {})",
+            key,
+            raw
+        ));
+    }
+    if value < min as f64 {
+        return Err(anyhow!(
+            "Setting {} must be >= {} (value: {})",
+            key,
+            min,
+            raw
+        ));
+    }
+    Ok(value as usize)
+}
+
+fn require_setting_i32(settings: &HashMap<String, String>, key: &str, min: i32) -> Result<i32> {
+    let raw = require_setting(settings, key)?;
+    let value = raw
+        .parse::<i32>()
+        .map_err(|_| anyhow!("Setting {} must be an integer (value: {})", key, raw))?;
+    if value < min {
+        return Err(anyhow!(
+            "Setting {} must be >= {} (value: {})",
+            key,
+            min,
+            raw
+        ));
+    }
+    Ok(value)
+}
+
+fn require_setting_f64_list(settings: &HashMap<String, String>, key: &str) -> Result<Vec<f64>> {
+    let raw = require_setting(settings, key)?;
+    let trimmed = raw.trim().trim_matches(|c| c == '[' || c == ']');
+    let mut values = Vec::new();
+
+    for part in trimmed.split(|c: char| c == ',' || c.is_whitespace()) {
+        let entry = part.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let value = entry
+            .parse::<f64>()
+            .map_err(|_| anyhow!("Setting {} must be a list of numbers (value: {})", key, raw))?;
+        if !value.is_finite() {
+            return Err(anyhow!(
+                "Setting {} must contain only finite numbers (value: {})",
+                key,
+                raw
+            ));
+        }
+        values.push(value);
+    }
+
+    if values.is_empty() {
+        return Err(anyhow!(
+            "Setting {} must contain at least one number (value: {})",
+            key,
+            raw
+        ));
+    }
+
+    Ok(values)
+}
@@ -1,23 +1,34 @@
+use crate::blackout::{BlackoutCalendar, BLACKOUT_SKIP_REASON};
+use crate::cancellation::CancellationToken;
 use crate::candle_utils::{
     group_candles_by_ticker_with, group_candles_for_tickers, normalize_ticker_symbol,
+    CandleColumns, TickerCandles,
 };
-use crate::config::{EngineConfig, EngineRuntimeSettings};
+use crate::config::{
+    AccountRiskOverrides, EngineConfig, EngineRuntimeSettings, FillTiming, PartialFillRemainder,
+    WarmupPolicy,
+};
+use crate::fx::AccountFx;
 use crate::indicators::estimate_annualized_volatility_from_candles;
 use crate::models::*;
 use crate::param_utils::coerce_binary_param;
 use crate::performance::PerformanceCalculator;
-use crate::signals::{
+use crate::signal_generation::{
     generate_signal_with_filters, maybe_create_generated_signal, SignalGenerationParams,
+    INSUFFICIENT_HISTORY_REASON,
 };
+use crate::strategy::lightgbm::CrossSectionalCache;
 use crate::strategy::Strategy;
 use crate::trading_rules::{
     compute_trailing_stop, determine_position_size, has_minimum_dollar_volume, initial_stop_loss,
-    stop_loss_exit_price, PositionSizingOutcome, PositionSizingParams, TrailingStopParams,
+    is_entry_execution_tick, stop_loss_exit_price, volume_capped_quantity, CarriedEntries,
+    CarriedEntry, PositionSizingOutcome, PositionSizingParams, QueuedEntries, TrailingStopParams,
     PRICE_EPSILON,
 };
 use anyhow::{anyhow, ensure, Result};
-use chrono::{DateTime, Duration, Utc};
-use log::warn;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use log::{info, warn};
+use std::borrow::Cow;
 use std::collections::{hash_map::DefaultHasher, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
@@ -27,9 +38,44 @@ use uuid::Uuid;
 const PNL_EPSILON: f64 = 1e-6;
 const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
 
+/// Calendar days of signals replayed when planning a weekly entry tick.
+const ENTRY_TICK_WINDOW_DAYS: i64 = 7;
+/// Planner note and skip reason while a circuit breaker freezes new entries.
+pub const CIRCUIT_BREAKER_REASON: &str = "circuit_breaker_triggered";
+/// Skip reason for entries into tickers outside the point-in-time universe on the signal date.
+pub const NOT_IN_UNIVERSE_REASON: &str = "not_in_universe";
+/// Close reason and skip reason of the pre-earnings rule (`earningsExitDays`).
+pub const EARNINGS_EXIT_REASON: &str = "earnings_upcoming";
+/// Operation reason of hedge overlay orders.
+pub const HEDGE_REASON: &str = "hedge_rebalance";
+/// Skip reason of signals on the hedge ticker while the overlay trades it.
+pub const HEDGE_INSTRUMENT_REASON: &str = "hedge_instrument";
+/// Skip reason for entries while the volatility index is above `maxVolatilityIndex`.
+pub const VOLATILITY_FILTER_REASON: &str = "volatility_filter";
+/// Skip reason for short entries while the ticker's borrow fee is above `maxBorrowFeeRate`.
+pub const BORROW_FEE_REASON: &str = "borrow_fee_too_high";
+/// Skip reason for buys while the ticker's news sentiment is below `minSentimentScore`.
+pub const SENTIMENT_FILTER_REASON: &str = "sentiment_filter";
+/// Skip reason for entries that would open on an FOMC or CPI release day.
+pub const ECONOMIC_EVENT_REASON: &str = "economic_event";
+
+/// Next Monday-Friday date after `date`; exchange holidays are not known when planning.
+fn next_weekday(date: NaiveDate) -> NaiveDate {
+    let mut next = date + Duration::days(1);
+    while matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+        next += Duration::days(1);
+    }
+    next
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum EntrySignalOutcome {
     Executed,
+    /// The fill candle's volume took only part of the order; `unfilled` shares are left.
+    PartiallyFilled {
+        unfilled: i32,
+        fill_date: DateTime<Utc>,
+    },
     Skipped {
         reason: &'static str,
         details: Option<String>,
@@ -71,6 +117,22 @@ pub struct PlannedOperations {
     pub operations: Vec<AccountOperationPlan>,
     pub notes: Vec<String>,
     pub skipped_signals: Vec<AccountSignalSkip>,
+    /// Sizing inputs behind each planned buy, in planning order, for `plan-operations --explain`.
+    pub buy_sizing: Vec<PlannedBuySizing>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedBuySizing {
+    pub ticker: String,
+    pub price: f64,
+    pub available_cash: f64,
+    pub confidence: f64,
+    pub trade_size_ratio: f64,
+    pub position_sizing_mode: i32,
+    pub realized_vol: Option<f64>,
+    pub quantity: i32,
+    pub trade_value: f64,
+    pub stop_loss: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,6 +144,16 @@ pub struct AccountStateSnapshot {
     pub open_sell_orders: HashSet<String>,
     pub positions: Vec<AccountPositionState>,
     pub stop_orders: HashMap<String, Vec<AccountStopOrderState>>,
+    /// Broker-reported equity now and at the previous session's close.
+    pub equity: Option<f64>,
+    pub last_equity: Option<f64>,
+    /// When the account's circuit breaker last tripped, as recorded in `system_logs`.
+    pub circuit_breaker_triggered_at: Option<DateTime<Utc>>,
+    /// The account's stop and risk limits, applied on top of the strategy's stop config.
+    pub risk_overrides: AccountRiskOverrides,
+    /// Cash, buying power and equity are in the account's currency; positions and candles are
+    /// in each ticker's currency.
+    pub fx: AccountFx,
 }
 
 #[derive(Debug, Clone)]
@@ -102,7 +174,10 @@ pub struct AccountStopOrderState {
 pub struct Engine {
     pub config: EngineConfig,
     runtime_settings: EngineRuntimeSettings,
-    ticker_expense_map: Arc<HashMap<String, f64>>,
+    auxiliary: Arc<AuxiliaryData>,
+    cross_sectional_cache: Arc<CrossSectionalCache>,
+    cancellation: CancellationToken,
+    blackout: BlackoutCalendar,
 }
 
 impl Engine {
@@ -111,7 +186,10 @@ impl Engine {
         Self {
             config: EngineConfig::default(),
             runtime_settings,
-            ticker_expense_map: Arc::new(HashMap::new()),
+            auxiliary: Arc::default(),
+            cross_sectional_cache: Arc::new(CrossSectionalCache::default()),
+            cancellation: CancellationToken::default(),
+            blackout: BlackoutCalendar::default(),
         }
     }
 
@@ -123,7 +201,10 @@ impl Engine {
         Self {
             config: EngineConfig::from_parameters(parameters),
             runtime_settings,
-            ticker_expense_map: Arc::new(HashMap::new()),
+            auxiliary: Arc::default(),
+            cross_sectional_cache: Arc::new(CrossSectionalCache::default()),
+            cancellation: CancellationToken::default(),
+            blackout: BlackoutCalendar::default(),
         }
     }
 
@@ -131,16 +212,261 @@ impl Engine {
         self.runtime_settings = settings;
     }
 
-    pub fn set_ticker_expense_map(&mut self, expense_map: Arc<HashMap<String, f64>>) {
-        self.ticker_expense_map = expense_map;
+    pub fn set_auxiliary_data(&mut self, auxiliary: Arc<AuxiliaryData>) {
+        self.auxiliary = auxiliary;
+    }
+
+    /// Blackout windows of the strategy being backtested: they suppress its buy signals and
+    /// refuse short entries, while sells still close longs.
+    pub fn set_blackout(&mut self, blackout: BlackoutCalendar) {
+        self.blackout = blackout;
+    }
+
+    /// Shares cross-sectional contexts with other engines backtesting the same candles.
+    pub fn set_cross_sectional_cache(&mut self, cache: Arc<CrossSectionalCache>) {
+        self.cross_sectional_cache = cache;
+    }
+
+    /// Stops backtests between trading days once `token` is cancelled.
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = token;
+    }
+
+    /// Cash distributions per share of a position opened on `entry_date` and still held at the
+    /// open of each ex-date up to `exit_date`; longs receive them and shorts pay them.
+    fn distributions_per_share(
+        &self,
+        ticker: &str,
+        entry_date: DateTime<Utc>,
+        exit_date: DateTime<Utc>,
+    ) -> f64 {
+        self.auxiliary
+            .ticker_distributions
+            .get(ticker)
+            .map(|schedule| {
+                schedule
+                    .iter()
+                    .filter(|distribution| {
+                        distribution.ex_date > entry_date && distribution.ex_date <= exit_date
+                    })
+                    .map(|distribution| distribution.amount)
+                    .sum()
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Pays the distributions that went ex after `previous_date` and by `current_date` into
+    /// cash on their ex-date: credited to longs and debited from shorts.
+    fn pay_distributions(
+        &self,
+        active_trades: &[Trade],
+        cash: &mut f64,
+        previous_date: Option<DateTime<Utc>>,
+        current_date: DateTime<Utc>,
+    ) {
+        if self.auxiliary.ticker_distributions.is_empty() {
+            return;
+        }
+        for trade in active_trades {
+            let from = previous_date.map_or(trade.date, |previous| previous.max(trade.date));
+            *cash += self.distributions_per_share(&trade.ticker, from, current_date)
+                * trade.quantity as f64;
+        }
+    }
+
+    /// Distributions `pay_distributions` already moved into cash while `trade` was held. The
+    /// close fee nets them into the trade's P&L, so the closing cash flow leaves them out.
+    fn paid_distributions(&self, trade: &Trade) -> f64 {
+        let exit_date = trade.exit_date.unwrap_or(trade.date);
+        self.distributions_per_share(&trade.ticker, trade.date, exit_date) * trade.quantity as f64
+    }
+
+    /// Next earnings date of `ticker` that is at most `earningsExitDays` calendar days after
+    /// `day` (including `day` itself), when the pre-earnings rule is on.
+    fn upcoming_earnings(&self, ticker: &str, day: NaiveDate) -> Option<DateTime<Utc>> {
+        let window = self.config.earnings_exit_days;
+        if window <= 0 {
+            return None;
+        }
+        self.auxiliary
+            .ticker_earnings
+            .get(ticker)?
+            .iter()
+            .copied()
+            .find(|earnings| {
+                let days_until = (earnings.date_naive() - day).num_days();
+                (0..=i64::from(window)).contains(&days_until)
+            })
+    }
+
+    /// Holding-day limit of a position in the ticker whose latest candle is
+    /// `ticker_candles[index]`, shortened by its realized volatility when `holdingVolTarget`
+    /// is on.
+    fn holding_days_limit(&self, ticker_candles: &[&Candle], index: usize) -> i64 {
+        let realized_vol = if self.config.holding_vol_target > 0.0 {
+            estimate_annualized_volatility_from_candles(
+                ticker_candles,
+                index,
+                self.config.position_sizing.vol_lookback,
+            )
+        } else {
+            0.0
+        };
+        self.config.max_holding_days_at(realized_vol)
+    }
+
+    fn in_universe(&self, ticker: &str, date: DateTime<Utc>) -> bool {
+        self.auxiliary.universe_membership.is_empty()
+            || self
+                .auxiliary
+                .universe_membership
+                .get(ticker)
+                .is_some_and(|intervals| intervals.iter().any(|interval| interval.contains(date)))
+    }
+
+    /// Series of the `VOLATILITY_INDEX_TICKER` auxiliary ticker, when it was loaded.
+    pub fn volatility_index(&self) -> Option<&Arc<AuxiliarySeries>> {
+        self.auxiliary
+            .auxiliary_series
+            .get(&self.runtime_settings.volatility_index_ticker)
+    }
+
+    /// Describes the volatility index close that blocks entries signalled on `date`, when
+    /// `maxVolatilityIndex` is set and the index closed above it.
+    fn volatility_filter_details(&self, date: DateTime<Utc>) -> Option<String> {
+        let max_level = self.config.max_volatility_index;
+        if max_level.is_nan() || max_level <= 0.0 {
+            return None;
+        }
+        let level = self.volatility_index()?.close_on_or_before(date)?;
+        (level > max_level).then(|| {
+            format!(
+                "{} at {:.2} above {}",
+                self.runtime_settings.volatility_index_ticker, level, max_level
+            )
+        })
+    }
+
+    /// Describes the borrow fee that blocks a short entry in `ticker` signalled on `date`, when
+    /// `maxBorrowFeeRate` is set and the latest reported fee is above it.
+    fn borrow_fee_details(&self, ticker: &str, date: DateTime<Utc>) -> Option<String> {
+        let max_rate = self.config.max_borrow_fee_rate;
+        if max_rate.is_nan() || max_rate <= 0.0 {
+            return None;
+        }
+        let points = self.auxiliary.ticker_short_interest.get(ticker)?;
+        let rate = short_interest_on_or_before(points, date).1?;
+        (rate > max_rate).then(|| {
+            format!(
+                "borrow fee {:.2}% above {:.2}%",
+                rate * 100.0,
+                max_rate * 100.0
+            )
+        })
+    }
+
+    /// Describes the news sentiment that blocks a buy of `ticker` signalled on `date`, when
+    /// `minSentimentScore` is above -1. A missing or stale score counts as neutral, the same
+    /// value the model features read.
+    fn sentiment_filter_details(&self, ticker: &str, date: DateTime<Utc>) -> Option<String> {
+        let min_score = self.config.min_sentiment_score;
+        if min_score.is_nan() || min_score <= -1.0 {
+            return None;
+        }
+        let score = self
+            .auxiliary
+            .ticker_sentiment
+            .get(ticker)
+            .and_then(|points| sentiment_on_or_before(points, date))
+            .unwrap_or(0.0);
+        (score < min_score).then(|| format!("sentiment {:.2} below {}", score, min_score))
+    }
+
+    /// Borrow fee rate times years of a short in `ticker` held from `entry_date` to
+    /// `exit_date`. Each stretch accrues the latest reported borrow fee, or
+    /// `SHORT_BORROW_FEE_ANNUAL_RATE` before the first report.
+    fn short_borrow_rate_years(
+        &self,
+        ticker: &str,
+        entry_date: DateTime<Utc>,
+        exit_date: DateTime<Utc>,
+    ) -> f64 {
+        let scale = self.runtime_settings.borrow_fee_multiplier;
+        let reports: Vec<(DateTime<Utc>, f64)> = self
+            .auxiliary
+            .ticker_short_interest
+            .get(ticker)
+            .into_iter()
+            .flatten()
+            .filter_map(|point| point.borrow_fee_rate.map(|rate| (point.date, rate * scale)))
+            .collect();
+        let mut index = reports.partition_point(|(date, _)| *date <= entry_date);
+        let mut rate = index.checked_sub(1).map_or(
+            self.runtime_settings.short_borrow_fee_annual_rate,
+            |previous| reports[previous].1,
+        );
+        let mut start = entry_date;
+        let mut rate_years = 0.0;
+        while start < exit_date {
+            let end = reports
+                .get(index)
+                .map_or(exit_date, |(date, _)| (*date).min(exit_date));
+            rate_years += rate * (end - start).num_seconds() as f64 / SECONDS_PER_YEAR;
+            if let Some((_, next_rate)) = reports.get(index) {
+                rate = *next_rate;
+            }
+            start = end;
+            index += 1;
+        }
+        rate_years
+    }
+
+    /// Describes the macro release that blocks entries opening on `date`, when
+    /// `pauseOnEconomicEvents` is set.
+    fn economic_event_details(&self, date: NaiveDate) -> Option<String> {
+        if !self.config.pause_on_economic_events {
+            return None;
+        }
+        self.auxiliary
+            .economic_calendar
+            .event_on(date)
+            .map(|kind| format!("{} on {}", kind.as_str(), date.format("%Y-%m-%d")))
+    }
+
+    /// Ticker the hedging overlay trades, when `hedgeBeta` enables it.
+    pub fn hedge_ticker(&self) -> Option<&str> {
+        self.config
+            .hedge
+            .enabled()
+            .then_some(self.runtime_settings.hedge_ticker.as_str())
+    }
+
+    fn is_hedge_ticker(&self, ticker: &str) -> bool {
+        self.hedge_ticker() == Some(ticker)
+    }
+
+    fn pre_earnings_entry_skip(
+        &self,
+        ticker: &str,
+        trade_date: DateTime<Utc>,
+    ) -> Option<EntrySignalOutcome> {
+        self.upcoming_earnings(ticker, trade_date.date_naive())
+            .map(|earnings| EntrySignalOutcome::Skipped {
+                reason: EARNINGS_EXIT_REASON,
+                details: Some(format!("earnings on {}", earnings.format("%Y-%m-%d"))),
+            })
     }
 
     fn expense_ratio_for(&self, ticker: &str) -> f64 {
-        if let Some(value) = self.ticker_expense_map.get(ticker) {
+        if let Some(value) = self.auxiliary.ticker_expense_map.get(ticker) {
             *value
         } else {
             let upper = ticker.to_ascii_uppercase();
-            self.ticker_expense_map.get(&upper).copied().unwrap_or(0.0)
+            self.auxiliary
+                .ticker_expense_map
+                .get(&upper)
+                .copied()
+                .unwrap_or(0.0)
         }
     }
 
@@ -160,6 +486,70 @@ impl Engine {
         }
     }
 
+    /// Warm-up of each of `tickers` under a strategy that needs `min_data_points` candles
+    /// before it signals.
+    fn ticker_warmups(
+        tickers: &[String],
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        min_data_points: usize,
+    ) -> Vec<TickerWarmup> {
+        let mut warmups: Vec<TickerWarmup> = tickers
+            .iter()
+            .map(|ticker| {
+                let candles = candles_by_ticker.get(ticker).map_or(&[][..], Vec::as_slice);
+                TickerWarmup {
+                    ticker: ticker.clone(),
+                    warmup_dates: min_data_points.min(candles.len()),
+                    ready_date: candles.get(min_data_points).map(|candle| candle.date),
+                }
+            })
+            .collect();
+        warmups.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+        warmups
+    }
+
+    /// First date index a strategy backtest trades on: `start_date_override` when given,
+    /// otherwise the end of the strategy's warm-up, subject to `BACKTEST_WARMUP_POLICY`.
+    fn resolve_warmup_start_index(
+        &self,
+        unique_dates: &[DateTime<Utc>],
+        start_date_override: Option<DateTime<Utc>>,
+        warmup_end_index: usize,
+        warmups: &[TickerWarmup],
+    ) -> Result<usize> {
+        let policy = self.runtime_settings.warmup_policy;
+        if policy == WarmupPolicy::Error {
+            let cold: Vec<&str> = warmups
+                .iter()
+                .filter(|warmup| warmup.ready_date.is_none())
+                .map(|warmup| warmup.ticker.as_str())
+                .collect();
+            if !cold.is_empty() {
+                return Err(anyhow!(
+                    "{} tickers have too little history to finish the warm-up: {}",
+                    cold.len(),
+                    cold.join(", ")
+                ));
+            }
+        }
+        let Some(requested) = start_date_override
+            .map(|target| Self::resolve_trading_start_index(unique_dates, target))
+        else {
+            return Ok(warmup_end_index);
+        };
+        match policy {
+            WarmupPolicy::Skip => Ok(requested),
+            WarmupPolicy::Strict => Ok(requested.max(warmup_end_index)),
+            WarmupPolicy::Error if requested < warmup_end_index => Err(anyhow!(
+                "Backtest start {} falls inside the strategy warm-up, which ends on {}",
+                unique_dates[requested].format("%Y-%m-%d"),
+                unique_dates[warmup_end_index].format("%Y-%m-%d")
+            )),
+            WarmupPolicy::Error => Ok(requested),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn backtest(
         &self,
         strategy: Option<&dyn Strategy>,
@@ -205,13 +595,23 @@ impl Engine {
         }
 
         let candles_by_ticker = group_candles_for_tickers(&tickers_for_run, all_candles);
-        if let Some(strategy_ref) = strategy {
-            if strategy_ref.get_template_id().starts_with("lightgbm") {
-                crate::strategy::lightgbm::prime_cross_sectional_context_from_ref_map(
-                    &candles_by_ticker,
-                );
+        let mut strategy_context = strategy
+            .map(|strategy_ref| {
+                strategy_ref.prepare_context(&candles_by_ticker, &self.cross_sectional_cache)
+            })
+            .unwrap_or_default();
+        strategy_context.volatility_index = self.volatility_index().cloned();
+        strategy_context.auxiliary = Some(Arc::clone(&self.auxiliary));
+        // The hedge ticker trades alongside the strategy's tickers without taking signals, so
+        // its candles are added after the strategy context is prepared.
+        let trading_candles_by_ticker = match self.hedge_ticker() {
+            Some(hedge) if !candles_by_ticker.contains_key(hedge) => {
+                let mut with_hedge = candles_by_ticker.clone();
+                with_hedge.extend(group_candles_for_tickers(&[hedge.to_string()], all_candles));
+                Cow::Owned(with_hedge)
             }
-        }
+            _ => Cow::Borrowed(&candles_by_ticker),
+        };
         let mut resume_state = if let Some(existing) = existing_backtest {
             self.prepare_resume_state(existing, unique_dates)?
         } else {
@@ -223,6 +623,7 @@ impl Engine {
             .map(|state| state.loop_start_index)
             .unwrap_or(0);
 
+        let mut warmup = Vec::new();
         let (loop_result, start_date, template_id) = if let Some(signals) = provided_signals {
             let trading_start_index = start_date_override
                 .map(|target| Self::resolve_trading_start_index(unique_dates, target))
@@ -239,10 +640,10 @@ impl Engine {
             let loop_result = self.run_backtest_loop(
                 &tickers_for_run,
                 unique_dates,
-                &candles_by_ticker,
+                &trading_candles_by_ticker,
                 trading_start_index,
                 loop_start_index,
-                |ticker, _index, current_date, _ticker_candles| {
+                |ticker, _index, current_date, _series| {
                     signal_map
                         .get(&(current_date, ticker.clone()))
                         .map(|signal| SignalDecision {
@@ -259,35 +660,38 @@ impl Engine {
             let min_data_points = strategy.get_min_data_points();
             let default_start_index =
                 std::cmp::min(min_data_points, unique_dates.len().saturating_sub(1));
-            let trading_start_index = start_date_override
-                .map(|target| Self::resolve_trading_start_index(unique_dates, target))
-                .unwrap_or(default_start_index);
+            warmup = Self::ticker_warmups(&tickers_for_run, &candles_by_ticker, min_data_points);
+            let trading_start_index = self.resolve_warmup_start_index(
+                unique_dates,
+                start_date_override,
+                default_start_index,
+                &warmup,
+            )?;
             let start_date = resume_start_date.unwrap_or(unique_dates[trading_start_index]);
 
             // Excluded tickers are deployment-time settings, not optimization parameters
             let empty_excluded: HashSet<String> = HashSet::new();
-
             let loop_result = self.run_backtest_loop(
                 &tickers_for_run,
                 unique_dates,
-                &candles_by_ticker,
+                &trading_candles_by_ticker,
                 trading_start_index,
                 loop_start_index,
-                |ticker, index, current_date, ticker_candles| {
-                    // Convert to owned slice for the shared function
-                    let candles_slice: Vec<Candle> =
-                        ticker_candles.iter().map(|c| (**c).clone()).collect();
-
+                |ticker, index, current_date, series| {
                     // Use the shared signal generation function with optimization parameters
                     if let Some(generated_signal) =
                         generate_signal_with_filters(SignalGenerationParams {
                             strategy,
                             ticker,
-                            candles: &candles_slice,
+                            candles: series.rows,
                             candle_index: index,
                             date: current_date,
                             excluded_tickers: &empty_excluded, // No ticker exclusions during optimization
+                            blackout: (!self.blackout.is_empty()).then_some(&self.blackout),
+                            context: &strategy_context,
+                            min_history_candles: self.runtime_settings.min_ticker_history_candles,
                         })
+                        .into_signal()
                     {
                         Some(SignalDecision {
                             action: generated_signal.action,
@@ -311,6 +715,7 @@ impl Engine {
                 "A strategy or precomputed signals must be provided for backtesting"
             ));
         };
+        self.cancellation.check()?;
 
         let BacktestLoopResult {
             mut cash,
@@ -339,7 +744,7 @@ impl Engine {
         let mut trades = closed_trades;
         trades.extend(active_trades);
 
-        self.validate_trades(&trades, &candles_by_ticker, final_date)?;
+        self.validate_trades(&trades, &trading_candles_by_ticker, final_date)?;
 
         // Use the actual first snapshot date as the start_date to ensure consistency
         let actual_start_date = daily_snapshots
@@ -347,7 +752,7 @@ impl Engine {
             .map(|snapshot| snapshot.date)
             .unwrap_or(start_date);
 
-        let performance = PerformanceCalculator::calculate_performance(
+        let mut performance = PerformanceCalculator::calculate_performance(
             &trades,
             self.config.initial_capital,
             final_portfolio_value,
@@ -355,6 +760,19 @@ impl Engine {
             final_date,
             &daily_snapshots,
         );
+        performance.capacity_aum = PerformanceCalculator::calculate_capacity(
+            &trades,
+            self.config.initial_capital,
+            &daily_snapshots,
+            &trading_candles_by_ticker,
+            self.runtime_settings.capacity_max_volume_share,
+        );
+
+        let baseline_return = PerformanceCalculator::calculate_equal_weight_buy_and_hold_return(
+            &candles_by_ticker,
+            actual_start_date,
+            final_date,
+        );
 
         let strategy_state = strategy.and_then(|strategy_ref| {
             strategy_ref
@@ -365,6 +783,15 @@ impl Engine {
                 })
         });
 
+        let min_history = self.runtime_settings.min_ticker_history_candles;
+        let insufficient_history_tickers: Vec<String> = tickers_for_run
+            .iter()
+            .filter(|ticker| {
+                candles_by_ticker.get(ticker.as_str()).map_or(0, Vec::len) < min_history
+            })
+            .cloned()
+            .collect();
+
         let result = BacktestResult {
             id: Uuid::new_v4().to_string(),
             strategy_id: template_id,
@@ -376,8 +803,10 @@ impl Engine {
             daily_snapshots,
             trades,
             tickers: tickers_for_run.clone(),
+            insufficient_history_tickers,
             ticker_scope: None,
             strategy_state,
+            baseline_return,
             created_at: Utc::now(),
         };
 
@@ -385,14 +814,16 @@ impl Engine {
             result,
             signals: generated_signals,
             signal_skips,
+            warmup,
         })
     }
 
-    fn run_backtest_loop<'a, F>(
+    #[allow(clippy::too_many_arguments)]
+    fn run_backtest_loop<F>(
         &self,
         tickers: &[String],
         unique_dates: &[DateTime<Utc>],
-        candles_by_ticker: &HashMap<String, Vec<&'a Candle>>,
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
         trading_start_index: usize,
         loop_start_index: usize,
         mut signal_provider: F,
@@ -400,7 +831,7 @@ impl Engine {
         track_signal_skips: bool,
     ) -> BacktestLoopResult
     where
-        F: FnMut(&String, usize, DateTime<Utc>, &Vec<&'a Candle>) -> Option<SignalDecision>,
+        F: FnMut(&String, usize, DateTime<Utc>, TickerCandles) -> Option<SignalDecision>,
     {
         let mut active_trades;
         let mut closed_trades;
@@ -411,6 +842,13 @@ impl Engine {
         let mut max_portfolio_value;
         let mut ticker_cursors: HashMap<&String, usize> =
             tickers.iter().map(|ticker| (ticker, 0)).collect();
+        // Built once per run; the daily cursor scan and the signal provider borrow its columns.
+        let candle_columns = CandleColumns::from_grouped(candles_by_ticker);
+        // Queued weekly-tick entries, carried partial-fill remainders and circuit breaker
+        // freezes are not part of the resume state, so a resumed backtest starts without them.
+        let mut queued_entries = QueuedEntries::default();
+        let mut carried_entries = CarriedEntries::default();
+        let mut entries_frozen_until: Option<DateTime<Utc>> = None;
 
         if let Some(state) = resume_state {
             active_trades = state.active_trades;
@@ -428,8 +866,32 @@ impl Engine {
             max_portfolio_value = self.config.initial_capital;
         }
         for (date_index, &current_date) in unique_dates.iter().enumerate().skip(loop_start_index) {
+            // A cancelled run stops between days; `backtest` discards the partial result.
+            if self.cancellation.is_cancelled() {
+                break;
+            }
             let mut missed_trades_due_to_cash_today = 0;
+            let entries_frozen = entries_frozen_until.is_some_and(|until| current_date < until);
+
+            // Remainders fill at the open, before today's stops and marks are applied.
+            if !entries_frozen {
+                self.work_carried_entries(
+                    &mut carried_entries,
+                    &mut active_trades,
+                    &mut cash,
+                    candles_by_ticker,
+                    current_date,
+                );
+            }
 
+            self.pay_distributions(
+                &active_trades,
+                &mut cash,
+                date_index
+                    .checked_sub(1)
+                    .map(|previous| unique_dates[previous]),
+                current_date,
+            );
             self.update_active_trades(
                 &mut active_trades,
                 &mut closed_trades,
@@ -442,21 +904,20 @@ impl Engine {
             if date_index >= trading_start_index {
                 let ordered_tickers = Self::ordered_tickers_for_date(tickers, current_date);
                 for ticker in ordered_tickers {
-                    if let Some(ticker_candles) = candles_by_ticker.get(ticker) {
+                    if self.is_hedge_ticker(ticker) {
+                        continue;
+                    }
+                    if let (Some(ticker_candles), Some(series)) =
+                        (candles_by_ticker.get(ticker), candle_columns.ticker(ticker))
+                    {
                         let cursor = ticker_cursors
                             .get_mut(ticker)
                             .expect("ticker cursor missing");
-                        while *cursor < ticker_candles.len()
-                            && ticker_candles[*cursor].date < current_date
-                        {
-                            *cursor += 1;
-                        }
-                        if *cursor < ticker_candles.len()
-                            && ticker_candles[*cursor].date == current_date
-                        {
+                        *cursor = series.seek(*cursor, current_date);
+                        if series.dates.get(*cursor) == Some(&current_date) {
                             let index = *cursor;
                             if let Some(signal) =
-                                signal_provider(ticker, index, current_date, ticker_candles)
+                                signal_provider(ticker, index, current_date, series)
                             {
                                 let SignalDecision { action, confidence } = signal;
 
@@ -469,28 +930,43 @@ impl Engine {
                                     generated_signals.push(generated);
                                 }
 
+                                if !matches!(action, SignalAction::Hold) {
+                                    carried_entries.cancel_opposite(
+                                        ticker,
+                                        matches!(action, SignalAction::Sell),
+                                    );
+                                }
+
                                 match action {
-                                    SignalAction::Buy => {
-                                        let next_candle = ticker_candles.get(index + 1).copied();
-                                        if self.config.allow_short_selling {
-                                            self.close_short_positions(
-                                                &mut active_trades,
-                                                &mut closed_trades,
-                                                &mut cash,
-                                                ticker,
-                                                next_candle,
-                                            );
+                                    SignalAction::Buy if entries_frozen => {
+                                        if track_signal_skips {
+                                            signal_skips.push(AccountSignalSkip {
+                                                ticker: ticker.clone(),
+                                                signal_date: current_date,
+                                                action: SignalAction::Buy,
+                                                reason: CIRCUIT_BREAKER_REASON.to_string(),
+                                                details: None,
+                                            });
                                         }
-                                        let outcome = self.execute_buy_signal(
+                                    }
+                                    SignalAction::Buy if self.config.entry_weekday.is_some() => {
+                                        queued_entries.queue(ticker, confidence);
+                                    }
+                                    SignalAction::Buy => {
+                                        let outcome = self.enter_long_position(
                                             &mut active_trades,
+                                            &mut closed_trades,
                                             &mut cash,
-                                            ticker,
-                                            ticker_candles[index],
-                                            next_candle,
                                             ticker_candles,
                                             index,
                                             confidence,
                                         );
+                                        self.carry_unfilled_entry(
+                                            &mut carried_entries,
+                                            ticker,
+                                            false,
+                                            &outcome,
+                                        );
                                         if let EntrySignalOutcome::Skipped { reason, details } =
                                             outcome
                                         {
@@ -509,6 +985,7 @@ impl Engine {
                                         }
                                     }
                                     SignalAction::Sell => {
+                                        queued_entries.cancel(ticker);
                                         let sell_outcome = self.execute_sell_signal(
                                             &mut active_trades,
                                             &mut closed_trades,
@@ -530,15 +1007,28 @@ impl Engine {
                                                 ticker,
                                             )
                                         {
-                                            let outcome = self.execute_short_entry(
-                                                &mut active_trades,
-                                                &mut cash,
+                                            let outcome = if entries_frozen {
+                                                EntrySignalOutcome::Skipped {
+                                                    reason: CIRCUIT_BREAKER_REASON,
+                                                    details: None,
+                                                }
+                                            } else {
+                                                self.execute_short_entry(
+                                                    &mut active_trades,
+                                                    &mut cash,
+                                                    ticker,
+                                                    ticker_candles[index],
+                                                    ticker_candles.get(index + 1).copied(),
+                                                    ticker_candles,
+                                                    index,
+                                                    confidence,
+                                                )
+                                            };
+                                            self.carry_unfilled_entry(
+                                                &mut carried_entries,
                                                 ticker,
-                                                ticker_candles[index],
-                                                ticker_candles.get(index + 1).copied(),
-                                                ticker_candles,
-                                                index,
-                                                confidence,
+                                                true,
+                                                &outcome,
                                             );
                                             if let EntrySignalOutcome::Skipped { reason, .. } =
                                                 &outcome
@@ -553,7 +1043,10 @@ impl Engine {
                                         let acted = sell_executed
                                             || matches!(
                                                 short_outcome.as_ref(),
-                                                Some(EntrySignalOutcome::Executed)
+                                                Some(
+                                                    EntrySignalOutcome::Executed
+                                                        | EntrySignalOutcome::PartiallyFilled { .. }
+                                                )
                                             );
                                         if !acted && track_signal_skips {
                                             let reason_details = match short_outcome {
@@ -586,6 +1079,80 @@ impl Engine {
                         }
                     }
                 }
+
+                if let Some(weekday) = self.config.entry_weekday {
+                    let next_is_tick = unique_dates.get(date_index + 1).is_some_and(|next| {
+                        is_entry_execution_tick(
+                            Some(current_date.date_naive()),
+                            next.date_naive(),
+                            weekday,
+                        )
+                    });
+                    if next_is_tick {
+                        let mut entries = queued_entries.drain();
+                        entries.sort_by(|(ticker_a, _), (ticker_b, _)| {
+                            Self::ticker_date_hash(ticker_a, current_date)
+                                .cmp(&Self::ticker_date_hash(ticker_b, current_date))
+                                .then_with(|| ticker_a.cmp(ticker_b))
+                        });
+                        for (ticker, confidence) in entries {
+                            let todays_candle =
+                                candles_by_ticker.get(&ticker).and_then(|candles| {
+                                    let index = *ticker_cursors.get(&ticker)?;
+                                    (candles.get(index)?.date == current_date)
+                                        .then_some((candles, index))
+                                });
+                            let outcome = match todays_candle {
+                                _ if entries_frozen => EntrySignalOutcome::Skipped {
+                                    reason: CIRCUIT_BREAKER_REASON,
+                                    details: None,
+                                },
+                                Some((ticker_candles, index)) => self.enter_long_position(
+                                    &mut active_trades,
+                                    &mut closed_trades,
+                                    &mut cash,
+                                    ticker_candles,
+                                    index,
+                                    confidence,
+                                ),
+                                None => EntrySignalOutcome::Skipped {
+                                    reason: "missing_candle_for_date",
+                                    details: None,
+                                },
+                            };
+                            self.carry_unfilled_entry(
+                                &mut carried_entries,
+                                &ticker,
+                                false,
+                                &outcome,
+                            );
+                            if let EntrySignalOutcome::Skipped { reason, details } = outcome {
+                                if reason == "insufficient_cash" {
+                                    missed_trades_due_to_cash_today += 1;
+                                }
+                                if track_signal_skips {
+                                    signal_skips.push(AccountSignalSkip {
+                                        ticker,
+                                        signal_date: current_date,
+                                        action: SignalAction::Buy,
+                                        reason: reason.to_string(),
+                                        details,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if date_index >= trading_start_index {
+                self.rebalance_hedge(
+                    &mut active_trades,
+                    &mut closed_trades,
+                    &mut cash,
+                    candles_by_ticker,
+                    current_date,
+                );
             }
 
             let mut positions_value = self.calculate_positions_value(&active_trades);
@@ -607,6 +1174,39 @@ impl Engine {
                 portfolio_value = cash + positions_value;
             }
 
+            let previous_value = daily_snapshots
+                .last()
+                .map(|snapshot| snapshot.portfolio_value)
+                .unwrap_or(self.config.initial_capital);
+            let breaker = self.config.circuit_breaker;
+            if date_index >= trading_start_index && breaker.trips(previous_value, portfolio_value) {
+                info!(
+                    "Circuit breaker tripped on {}: portfolio fell {:.2}% to {:.2}; freezing entries for {} day(s){}",
+                    current_date.date_naive(),
+                    (previous_value - portfolio_value) / previous_value * 100.0,
+                    portfolio_value,
+                    breaker.freeze_days,
+                    if breaker.liquidate {
+                        " and liquidating"
+                    } else {
+                        ""
+                    }
+                );
+                entries_frozen_until =
+                    Some(current_date + Duration::days(i64::from(breaker.freeze_days)));
+                if breaker.liquidate && !active_trades.is_empty() {
+                    self.force_liquidation(
+                        &mut active_trades,
+                        &mut closed_trades,
+                        &mut cash,
+                        candles_by_ticker,
+                        current_date,
+                    );
+                    positions_value = self.calculate_positions_value(&active_trades);
+                    portfolio_value = cash + positions_value;
+                }
+            }
+
             if portfolio_value > max_portfolio_value {
                 max_portfolio_value = portfolio_value;
             }
@@ -739,41 +1339,74 @@ impl Engine {
                     let current_price = current_candle.close;
                     let quantity = trade.quantity as f64;
                     trade.pnl = Some((current_price - trade.price) * quantity);
+                    if trade.date == current_date
+                        && self.entry_fill_timing(trade.quantity < 0) == FillTiming::NextClose
+                    {
+                        // Filled at today's close, after the moves this candle could stop out.
+                        continue;
+                    }
+
+                    // Hedge legs only change when the overlay rebalances.
+                    if self.is_hedge_ticker(&trade.ticker) {
+                        continue;
+                    }
 
                     // Check for time-based exit
                     let days_held = (current_date - trade.date).num_days();
-                    if days_held >= self.config.max_holding_days.into() {
-                        let exit_price = self.apply_exit_slippage_with_candle(
+                    let scale_out_target = self
+                        .config
+                        .scale_out
+                        .target_quantity(trade.original_quantity(), days_held);
+                    let holding_days_limit = current_index
+                        .map_or(i64::from(self.config.max_holding_days), |index| {
+                            self.holding_days_limit(ticker_candles, index)
+                        });
+                    if days_held >= holding_days_limit
+                        || scale_out_target == Some(0)
+                        || self
+                            .upcoming_earnings(&trade.ticker, current_date.date_naive())
+                            .is_some()
+                    {
+                        self.close_trade_at_price(
+                            trade,
                             current_price,
-                            trade.quantity < 0,
                             current_candle,
-                        );
-                        trade.set_exit_price(Some(exit_price), current_date);
-                        trade.set_exit_date(Some(current_date), current_date);
-                        let fee = self.calculate_trade_close_fee(
-                            trade.ticker.as_str(),
-                            trade.quantity,
-                            exit_price,
-                            trade.date,
                             current_date,
                         );
-                        trade.pnl = Some((exit_price - trade.price) * trade.quantity as f64 - fee);
-                        trade.set_fee(Some(fee), current_date);
-                        trade.set_status(TradeStatus::Closed, current_date);
                         to_close.push(i);
                         continue;
                     }
+                    if let Some(target) =
+                        scale_out_target.filter(|target| target.abs() < trade.quantity.abs())
+                    {
+                        let mut part = trade.split_off(trade.quantity - target, current_date);
+                        self.close_trade_at_price(
+                            &mut part,
+                            current_price,
+                            current_candle,
+                            current_date,
+                        );
+                        let exit_price = part.exit_price.unwrap_or(current_price);
+                        *cash += exit_price * part.quantity as f64
+                            - part.fee.unwrap_or(0.0)
+                            - self.paid_distributions(&part);
+                        closed_trades.push(part);
+                        trade.pnl = Some((current_price - trade.price) * trade.quantity as f64);
+                    }
 
-                    // Trailing stop update (ATR)
+                    // Trailing stop update
                     if let (Some(curr_stop), Some(idx)) = (trade.stop_loss, current_index) {
                         if trade.date < current_date {
                             if let Some(update) = compute_trailing_stop(TrailingStopParams {
                                 stop_loss_mode: self.config.stop_loss.mode,
+                                stop_loss_ratio: self.config.stop_loss.ratio,
                                 atr_multiplier: self.config.stop_loss.atr_multiplier,
                                 atr_period: self.config.stop_loss.atr_period,
+                                breakeven_r_multiple: self.config.stop_loss.breakeven_r_multiple,
                                 ticker_candles,
                                 candle_index: idx,
                                 current_candle,
+                                entry_price: trade.price,
                                 current_stop: curr_stop,
                                 is_short: trade.quantity < 0,
                                 planning_close: None,
@@ -784,9 +1417,12 @@ impl Engine {
                     }
 
                     if let Some(stop_loss) = trade.stop_loss {
-                        if let Some(raw_exit_price) =
-                            stop_loss_exit_price(current_candle, stop_loss, trade.quantity < 0)
-                        {
+                        if let Some(raw_exit_price) = stop_loss_exit_price(
+                            current_candle,
+                            stop_loss,
+                            trade.quantity < 0,
+                            self.runtime_settings.stop_fill_at_stop_price,
+                        ) {
                             let exit_price = self.apply_exit_slippage_with_candle(
                                 raw_exit_price,
                                 trade.quantity < 0,
@@ -829,68 +1465,427 @@ impl Engine {
                     exit_date,
                 )
             });
-            *cash += trade_value - fee;
+            *cash += trade_value - fee - self.paid_distributions(&trade);
             closed_trades.push(trade);
         }
     }
 
-    fn execute_buy_signal(
+    /// Moves the hedge short toward `hedgeBeta` times the marked long exposure at the hedge
+    /// ticker's close once it drifts past `hedgeRebalanceThreshold`. Increases open a new leg;
+    /// decreases cover the newest legs first.
+    fn rebalance_hedge(
         &self,
         active_trades: &mut Vec<Trade>,
+        closed_trades: &mut Vec<Trade>,
         cash: &mut f64,
-        ticker: &str,
-        candle: &Candle,
-        next_candle_opt: Option<&Candle>,
-        ticker_candles: &Vec<&Candle>,
-        index: usize,
-        confidence: f64,
-    ) -> EntrySignalOutcome {
-        let guard_price = match Self::guard_price_from_candle(candle) {
-            Some(price) if self.entry_price_supported(price) => price,
-            _ => {
-                return EntrySignalOutcome::Skipped {
-                    reason: "price_out_of_range",
-                    details: None,
-                }
-            }
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        current_date: DateTime<Utc>,
+    ) {
+        let Some(hedge_ticker) = self.hedge_ticker() else {
+            return;
         };
-        let Some(next_candle) = next_candle_opt else {
-            return EntrySignalOutcome::Skipped {
-                reason: "missing_next_candle",
-                details: None,
-            };
+        let Some(candle) = candles_by_ticker.get(hedge_ticker).and_then(|candles| {
+            let index = candles.partition_point(|candle| candle.date < current_date);
+            candles
+                .get(index)
+                .filter(|candle| candle.date == current_date)
+                .copied()
+        }) else {
+            return;
         };
-        let Some(next_index) = index.checked_add(1) else {
-            return EntrySignalOutcome::Skipped {
-                reason: "missing_next_candle",
-                details: None,
-            };
+        let long_exposure: f64 = active_trades
+            .iter()
+            .filter(|trade| {
+                trade.quantity > 0 && trade.date <= current_date && trade.ticker != hedge_ticker
+            })
+            .map(|trade| trade.price * trade.quantity as f64 + trade.pnl.unwrap_or(0.0))
+            .sum();
+        let hedged: i32 = -active_trades
+            .iter()
+            .filter(|trade| trade.ticker == hedge_ticker)
+            .map(|trade| trade.quantity)
+            .sum::<i32>();
+        let Some(target) = self
+            .config
+            .hedge
+            .rebalance_target(long_exposure, hedged, candle.close)
+        else {
+            return;
         };
-        if next_index >= ticker_candles.len() {
-            return EntrySignalOutcome::Skipped {
-                reason: "missing_next_candle",
-                details: None,
-            };
-        }
-        if !has_minimum_dollar_volume(
+
+        if target > hedged {
+            let quantity = target - hedged;
+            let price = self.apply_entry_slippage_with_candle(candle.close, true, candle);
+            *cash += quantity as f64 * price;
+            active_trades.push(Trade {
+                id: Uuid::new_v4().to_string(),
+                strategy_id: "backtest".to_string(),
+                ticker: hedge_ticker.to_string(),
+                quantity: -quantity,
+                price,
+                date: current_date,
+                status: TradeStatus::Active,
+                pnl: Some((candle.close - price) * -(quantity as f64)),
+                fee: None,
+                exit_price: None,
+                exit_date: None,
+                stop_loss: None,
+                stop_loss_triggered: Some(false),
+                entry_order_id: None,
+                entry_cancel_after: None,
+                stop_order_id: None,
+                exit_order_id: None,
+                target_order_id: None,
+                changes: Vec::new(),
+            });
+            return;
+        }
+
+        let mut to_cover = hedged - target;
+        let mut index = active_trades.len();
+        while to_cover > 0 && index > 0 {
+            index -= 1;
+            if active_trades[index].ticker != hedge_ticker {
+                continue;
+            }
+            let mut covered = if -active_trades[index].quantity <= to_cover {
+                active_trades.remove(index)
+            } else {
+                let leg = &mut active_trades[index];
+                let part = leg.split_off(-to_cover, current_date);
+                leg.pnl = Some((candle.close - leg.price) * leg.quantity as f64);
+                part
+            };
+            to_cover += covered.quantity;
+            self.close_trade_at_price(&mut covered, candle.close, candle, current_date);
+            let exit_price = covered.exit_price.unwrap_or(candle.close);
+            *cash += exit_price * covered.quantity as f64
+                - covered.fee.unwrap_or(0.0)
+                - self.paid_distributions(&covered);
+            closed_trades.push(covered);
+        }
+    }
+
+    /// Closes `trade` at `price` on `candle` after exit slippage, booking the close fee into
+    /// its P&L.
+    fn close_trade_at_price(
+        &self,
+        trade: &mut Trade,
+        price: f64,
+        candle: &Candle,
+        current_date: DateTime<Utc>,
+    ) {
+        let exit_price = self.apply_exit_slippage_with_candle(price, trade.quantity < 0, candle);
+        trade.set_exit_price(Some(exit_price), current_date);
+        trade.set_exit_date(Some(current_date), current_date);
+        let fee = self.calculate_trade_close_fee(
+            trade.ticker.as_str(),
+            trade.quantity,
+            exit_price,
+            trade.date,
+            current_date,
+        );
+        trade.pnl = Some((exit_price - trade.price) * trade.quantity as f64 - fee);
+        trade.set_fee(Some(fee), current_date);
+        trade.set_status(TradeStatus::Closed, current_date);
+    }
+
+    /// Covers shorts on the ticker when short selling is allowed, then opens a long from a buy
+    /// decision on `ticker_candles[index]`, filled as `fill_timing` dictates.
+    fn enter_long_position(
+        &self,
+        active_trades: &mut Vec<Trade>,
+        closed_trades: &mut Vec<Trade>,
+        cash: &mut f64,
+        ticker_candles: &Vec<&Candle>,
+        index: usize,
+        confidence: f64,
+    ) -> EntrySignalOutcome {
+        let ticker = ticker_candles[index].ticker.as_str();
+        let next_candle = ticker_candles.get(index + 1).copied();
+        if self.config.allow_short_selling {
+            self.close_short_positions(active_trades, closed_trades, cash, ticker, next_candle);
+        }
+        self.execute_buy_signal(
+            active_trades,
+            cash,
+            ticker,
+            ticker_candles[index],
+            next_candle,
+            ticker_candles,
+            index,
+            confidence,
+        )
+    }
+
+    /// Discounted long entries are limit orders for the next session, so they always fill
+    /// from the next candle; every other entry follows `fill_timing`.
+    fn entry_fill_timing(&self, is_short: bool) -> FillTiming {
+        if !is_short && self.config.buy_discount_ratio > 0.0 {
+            FillTiming::NextOpen
+        } else {
+            self.config.fill_timing
+        }
+    }
+
+    /// Shares of an entry the fill candle can absorb under `PARTIAL_FILL_MAX_VOLUME_SHARE`.
+    fn volume_capped_entry(&self, quantity: i32, fill_candle: &Candle) -> i32 {
+        volume_capped_quantity(
+            quantity,
+            fill_candle.volume_shares,
+            self.runtime_settings.partial_fill_max_volume_share,
+        )
+    }
+
+    fn filled_entry_outcome(unfilled: i32, fill_date: DateTime<Utc>) -> EntrySignalOutcome {
+        if unfilled > 0 {
+            EntrySignalOutcome::PartiallyFilled {
+                unfilled,
+                fill_date,
+            }
+        } else {
+            EntrySignalOutcome::Executed
+        }
+    }
+
+    /// An entry the fill candle's volume could not absorb at all keeps working when
+    /// remainders carry over; otherwise the signal is skipped.
+    fn unfilled_entry_outcome(
+        &self,
+        unfilled: i32,
+        fill_date: DateTime<Utc>,
+    ) -> EntrySignalOutcome {
+        if self.runtime_settings.partial_fill_remainder == PartialFillRemainder::Carry {
+            EntrySignalOutcome::PartiallyFilled {
+                unfilled,
+                fill_date,
+            }
+        } else {
+            EntrySignalOutcome::Skipped {
+                reason: "insufficient_fill_volume",
+                details: Some(format!("0 of {} shares filled", unfilled)),
+            }
+        }
+    }
+
+    /// Whether the simulated broker rejects an entry of `ticker` filling on `date`. The draw
+    /// hashes both, so reruns and optimizer workers reject the same orders.
+    fn entry_order_rejected(&self, ticker: &str, date: DateTime<Utc>) -> bool {
+        let rate = self.runtime_settings.order_rejection_rate;
+        if rate <= 0.0 {
+            return false;
+        }
+        let mut hasher = DefaultHasher::new();
+        "order_rejection".hash(&mut hasher);
+        ticker.hash(&mut hasher);
+        date.timestamp().hash(&mut hasher);
+        (hasher.finish() as f64 / u64::MAX as f64) < rate
+    }
+
+    fn carry_unfilled_entry(
+        &self,
+        carried_entries: &mut CarriedEntries,
+        ticker: &str,
+        is_short: bool,
+        outcome: &EntrySignalOutcome,
+    ) {
+        if let EntrySignalOutcome::PartiallyFilled {
+            unfilled,
+            fill_date,
+        } = outcome
+        {
+            if self.runtime_settings.partial_fill_remainder == PartialFillRemainder::Carry {
+                carried_entries.carry(CarriedEntry {
+                    ticker: ticker.to_string(),
+                    quantity: *unfilled,
+                    is_short,
+                    last_fill_date: *fill_date,
+                });
+            }
+        }
+    }
+
+    /// Fills carried entry remainders at today's open, capped by today's volume like the
+    /// original order. Long remainders the cash no longer covers are dropped.
+    fn work_carried_entries(
+        &self,
+        carried_entries: &mut CarriedEntries,
+        active_trades: &mut Vec<Trade>,
+        cash: &mut f64,
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        current_date: DateTime<Utc>,
+    ) {
+        for mut entry in carried_entries.take() {
+            let todays_candle = candles_by_ticker.get(&entry.ticker).and_then(|candles| {
+                let index = candles
+                    .binary_search_by(|candle| candle.date.cmp(&current_date))
+                    .ok()?;
+                Some((candles, index))
+            });
+            let Some((ticker_candles, index)) =
+                todays_candle.filter(|_| current_date > entry.last_fill_date)
+            else {
+                carried_entries.carry(entry);
+                continue;
+            };
+            if self
+                .upcoming_earnings(&entry.ticker, current_date.date_naive())
+                .is_some()
+                || !self.in_universe(&entry.ticker, current_date)
+            {
+                // The rest of the order is cancelled inside the pre-earnings window or once the
+                // ticker leaves the universe.
+                continue;
+            }
+            let candle = ticker_candles[index];
+            let price = self.apply_entry_slippage_with_candle(candle.open, entry.is_short, candle);
+            if !(price.is_finite() && price > 0.0) {
+                carried_entries.carry(entry);
+                continue;
+            }
+            let mut quantity = self.volume_capped_entry(entry.quantity, candle);
+            if !entry.is_short {
+                let affordable = (*cash / price).floor().clamp(0.0, f64::from(i32::MAX)) as i32;
+                if affordable == 0 {
+                    continue;
+                }
+                quantity = quantity.min(affordable);
+            }
+            if quantity > 0 {
+                let signed_quantity = if entry.is_short { -quantity } else { quantity };
+                *cash -= signed_quantity as f64 * price;
+                let stop_loss = initial_stop_loss(
+                    self.config.stop_loss.mode,
+                    self.config.stop_loss.atr_multiplier,
+                    self.config.stop_loss.atr_period,
+                    self.config.stop_loss.ratio,
+                    price,
+                    ticker_candles,
+                    index.saturating_sub(1),
+                    entry.is_short,
+                );
+                active_trades.push(Trade {
+                    id: Uuid::new_v4().to_string(),
+                    strategy_id: "backtest".to_string(),
+                    ticker: entry.ticker.clone(),
+                    quantity: signed_quantity,
+                    price,
+                    date: current_date,
+                    status: TradeStatus::Active,
+                    pnl: None,
+                    fee: None,
+                    exit_price: None,
+                    exit_date: None,
+                    stop_loss,
+                    stop_loss_triggered: Some(false),
+                    entry_order_id: None,
+                    entry_cancel_after: None,
+                    stop_order_id: None,
+                    exit_order_id: None,
+                    target_order_id: None,
+                    changes: Vec::new(),
+                });
+                entry.quantity -= quantity;
+                entry.last_fill_date = current_date;
+            }
+            if entry.quantity > 0 {
+                carried_entries.carry(entry);
+            }
+        }
+    }
+
+    /// Index, candle and pre-slippage price an entry decided on `ticker_candles[index]`
+    /// fills at; `None` when a next-candle fill has no next candle.
+    fn entry_fill<'c>(
+        timing: FillTiming,
+        ticker_candles: &[&'c Candle],
+        index: usize,
+        next_candle: Option<&'c Candle>,
+    ) -> Option<(usize, &'c Candle, f64)> {
+        if timing == FillTiming::SameClose {
+            let candle = *ticker_candles.get(index)?;
+            return Some((index, candle, candle.close));
+        }
+        let next_index = index.checked_add(1)?;
+        if next_index >= ticker_candles.len() {
+            return None;
+        }
+        let next_candle = next_candle?;
+        let price = match timing {
+            FillTiming::NextClose => next_candle.close,
+            _ => next_candle.open,
+        };
+        Some((next_index, next_candle, price))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_buy_signal(
+        &self,
+        active_trades: &mut Vec<Trade>,
+        cash: &mut f64,
+        ticker: &str,
+        candle: &Candle,
+        next_candle_opt: Option<&Candle>,
+        ticker_candles: &Vec<&Candle>,
+        index: usize,
+        confidence: f64,
+    ) -> EntrySignalOutcome {
+        if !self.in_universe(ticker, candle.date) {
+            return EntrySignalOutcome::Skipped {
+                reason: NOT_IN_UNIVERSE_REASON,
+                details: None,
+            };
+        }
+        if let Some(details) = self.volatility_filter_details(candle.date) {
+            return EntrySignalOutcome::Skipped {
+                reason: VOLATILITY_FILTER_REASON,
+                details: Some(details),
+            };
+        }
+        if let Some(details) = self.sentiment_filter_details(ticker, candle.date) {
+            return EntrySignalOutcome::Skipped {
+                reason: SENTIMENT_FILTER_REASON,
+                details: Some(details),
+            };
+        }
+        let guard_price = match Self::guard_price_from_candle(candle) {
+            Some(price) if self.entry_price_supported(ticker, price) => price,
+            _ => {
+                return EntrySignalOutcome::Skipped {
+                    reason: "price_out_of_range",
+                    details: None,
+                }
+            }
+        };
+        let Some((fill_index, fill_candle, mut price)) = Self::entry_fill(
+            self.entry_fill_timing(false),
+            ticker_candles,
+            index,
+            next_candle_opt,
+        ) else {
+            return EntrySignalOutcome::Skipped {
+                reason: "missing_next_candle",
+                details: None,
+            };
+        };
+        if !has_minimum_dollar_volume(
             ticker_candles,
-            next_index,
+            fill_index,
             self.runtime_settings.minimum_dollar_volume_lookback,
-            self.runtime_settings.minimum_dollar_volume_for_entry,
+            self.runtime_settings.minimum_dollar_volume_for(ticker),
         ) {
             return EntrySignalOutcome::Skipped {
                 reason: "insufficient_volume",
                 details: None,
             };
         }
-        let mut price = next_candle.open;
         let mut is_limit_entry = false;
-        let trade_date = next_candle.date;
+        let trade_date = fill_candle.date;
 
         if self.config.buy_discount_ratio > 0.0 {
             let discounted_price = candle.close * (1.0 - self.config.buy_discount_ratio);
-            if next_candle.low <= discounted_price {
-                price = next_candle.open.min(discounted_price);
+            if fill_candle.low <= discounted_price {
+                price = fill_candle.open.min(discounted_price);
                 is_limit_entry = true;
             } else {
                 return EntrySignalOutcome::Skipped {
@@ -900,9 +1895,9 @@ impl Engine {
             }
         }
         if !is_limit_entry {
-            price = self.apply_entry_slippage_with_candle(price, false, next_candle);
+            price = self.apply_entry_slippage_with_candle(price, false, fill_candle);
         }
-        debug_assert!(self.entry_price_supported(guard_price));
+        debug_assert!(self.entry_price_supported(ticker, guard_price));
 
         if active_trades
             .iter()
@@ -913,6 +1908,21 @@ impl Engine {
                 details: None,
             };
         }
+        if let Some(skip) = self.pre_earnings_entry_skip(ticker, trade_date) {
+            return skip;
+        }
+        if let Some(details) = self.economic_event_details(trade_date.date_naive()) {
+            return EntrySignalOutcome::Skipped {
+                reason: ECONOMIC_EVENT_REASON,
+                details: Some(details),
+            };
+        }
+        if self.entry_order_rejected(ticker, trade_date) {
+            return EntrySignalOutcome::Skipped {
+                reason: "order_rejected",
+                details: None,
+            };
+        }
 
         let realized_vol = if (self.config.position_sizing.mode == 2
             || self.config.position_sizing.mode == 3)
@@ -952,7 +1962,12 @@ impl Engine {
             }
         };
 
-        *cash -= allocation.trade_value;
+        let quantity = self.volume_capped_entry(allocation.quantity, fill_candle);
+        let unfilled = allocation.quantity - quantity;
+        if quantity == 0 {
+            return self.unfilled_entry_outcome(unfilled, trade_date);
+        }
+        *cash -= quantity as f64 * price;
 
         let stop_loss = initial_stop_loss(
             self.config.stop_loss.mode,
@@ -965,15 +1980,19 @@ impl Engine {
             false,
         );
 
+        // Same-close fills are marked now; the day's mark-to-market has already run.
+        let same_close_mark =
+            (trade_date == candle.date).then_some((fill_candle.close - price) * quantity as f64);
+
         let trade = Trade {
             id: Uuid::new_v4().to_string(),
             strategy_id: "backtest".to_string(),
             ticker: ticker.to_string(),
-            quantity: allocation.quantity,
+            quantity,
             price,
             date: trade_date,
             status: TradeStatus::Active,
-            pnl: None,
+            pnl: same_close_mark,
             fee: None,
             exit_price: None,
             exit_date: None,
@@ -983,13 +2002,15 @@ impl Engine {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
         active_trades.push(trade);
 
-        EntrySignalOutcome::Executed
+        Self::filled_entry_outcome(unfilled, trade_date)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn execute_short_entry(
         &self,
         active_trades: &mut Vec<Trade>,
@@ -1001,8 +2022,20 @@ impl Engine {
         index: usize,
         confidence: f64,
     ) -> EntrySignalOutcome {
+        if !self.in_universe(ticker, candle.date) {
+            return EntrySignalOutcome::Skipped {
+                reason: NOT_IN_UNIVERSE_REASON,
+                details: None,
+            };
+        }
+        if let Some(details) = self.volatility_filter_details(candle.date) {
+            return EntrySignalOutcome::Skipped {
+                reason: VOLATILITY_FILTER_REASON,
+                details: Some(details),
+            };
+        }
         let guard_price = match Self::guard_price_from_candle(candle) {
-            Some(price) if self.entry_price_supported(price) => price,
+            Some(price) if self.entry_price_supported(ticker, price) => price,
             _ => {
                 return EntrySignalOutcome::Skipped {
                     reason: "price_out_of_range",
@@ -1016,37 +2049,41 @@ impl Engine {
                 details: None,
             };
         }
-        let Some(next_candle) = next_candle_opt else {
+        if let Some(window) = self.blackout.window_for(ticker, candle.date) {
             return EntrySignalOutcome::Skipped {
-                reason: "missing_next_candle",
-                details: None,
+                reason: BLACKOUT_SKIP_REASON,
+                details: Some(window.to_string()),
             };
-        };
-        let Some(next_index) = index.checked_add(1) else {
+        }
+        if let Some(details) = self.borrow_fee_details(ticker, candle.date) {
             return EntrySignalOutcome::Skipped {
-                reason: "missing_next_candle",
-                details: None,
+                reason: BORROW_FEE_REASON,
+                details: Some(details),
             };
-        };
-        if next_index >= ticker_candles.len() {
+        }
+        let Some((fill_index, fill_candle, mut price)) = Self::entry_fill(
+            self.entry_fill_timing(true),
+            ticker_candles,
+            index,
+            next_candle_opt,
+        ) else {
             return EntrySignalOutcome::Skipped {
                 reason: "missing_next_candle",
                 details: None,
             };
-        }
+        };
         if !has_minimum_dollar_volume(
             ticker_candles,
-            next_index,
+            fill_index,
             self.runtime_settings.minimum_dollar_volume_lookback,
-            self.runtime_settings.minimum_dollar_volume_for_entry,
+            self.runtime_settings.minimum_dollar_volume_for(ticker),
         ) {
             return EntrySignalOutcome::Skipped {
                 reason: "insufficient_volume",
                 details: None,
             };
         }
-        let mut price = next_candle.open;
-        let trade_date = next_candle.date;
+        let trade_date = fill_candle.date;
 
         if Self::has_active_long_position(active_trades, ticker)
             || Self::has_active_short_position(active_trades, ticker)
@@ -1066,8 +2103,23 @@ impl Engine {
                 details: None,
             };
         }
-        price = self.apply_entry_slippage_with_candle(price, true, next_candle);
-        debug_assert!(self.entry_price_supported(guard_price));
+        if let Some(skip) = self.pre_earnings_entry_skip(ticker, trade_date) {
+            return skip;
+        }
+        if let Some(details) = self.economic_event_details(trade_date.date_naive()) {
+            return EntrySignalOutcome::Skipped {
+                reason: ECONOMIC_EVENT_REASON,
+                details: Some(details),
+            };
+        }
+        if self.entry_order_rejected(ticker, trade_date) {
+            return EntrySignalOutcome::Skipped {
+                reason: "order_rejected",
+                details: None,
+            };
+        }
+        price = self.apply_entry_slippage_with_candle(price, true, fill_candle);
+        debug_assert!(self.entry_price_supported(ticker, guard_price));
 
         let realized_vol = if (self.config.position_sizing.mode == 2
             || self.config.position_sizing.mode == 3)
@@ -1107,7 +2159,12 @@ impl Engine {
             }
         };
 
-        *cash += allocation.trade_value;
+        let quantity = self.volume_capped_entry(allocation.quantity, fill_candle);
+        let unfilled = allocation.quantity - quantity;
+        if quantity == 0 {
+            return self.unfilled_entry_outcome(unfilled, trade_date);
+        }
+        *cash += quantity as f64 * price;
 
         let stop_loss = initial_stop_loss(
             self.config.stop_loss.mode,
@@ -1120,15 +2177,19 @@ impl Engine {
             true,
         );
 
+        // Same-close fills are marked now; the day's mark-to-market has already run.
+        let same_close_mark =
+            (trade_date == candle.date).then_some((fill_candle.close - price) * -(quantity as f64));
+
         let trade = Trade {
             id: Uuid::new_v4().to_string(),
             strategy_id: "backtest".to_string(),
             ticker: ticker.to_string(),
-            quantity: -(allocation.quantity),
+            quantity: -quantity,
             price,
             date: trade_date,
             status: TradeStatus::Active,
-            pnl: None,
+            pnl: same_close_mark,
             fee: None,
             exit_price: None,
             exit_date: None,
@@ -1138,11 +2199,12 @@ impl Engine {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
         active_trades.push(trade);
 
-        EntrySignalOutcome::Executed
+        Self::filled_entry_outcome(unfilled, trade_date)
     }
 
     fn execute_sell_signal(
@@ -1194,7 +2256,7 @@ impl Engine {
                 trade.set_status(TradeStatus::Closed, exit_date);
                 trade.set_fee(Some(fee), exit_date);
 
-                *cash += trade_value - fee;
+                *cash += trade_value - fee - self.paid_distributions(trade);
                 to_close.push(i);
             }
         }
@@ -1258,7 +2320,7 @@ impl Engine {
             trade.set_stop_loss_triggered(Some(false), exit_date);
 
             let trade_value = exit_price * trade.quantity as f64;
-            *cash += trade_value - fee;
+            *cash += trade_value - fee - self.paid_distributions(trade);
 
             to_close.push(i);
         }
@@ -1326,7 +2388,7 @@ impl Engine {
                     exit_candle,
                 )
             } else {
-                self.apply_exit_slippage(exit_price_raw, trade.quantity < 0)
+                self.apply_exit_slippage(&trade.ticker, exit_price_raw, trade.quantity < 0)
             };
             let exit_date = current_date;
             let fee = self.calculate_trade_close_fee(
@@ -1346,7 +2408,7 @@ impl Engine {
             trade.set_stop_loss_triggered(Some(false), exit_date);
 
             let trade_value = exit_price * trade.quantity as f64;
-            *cash += trade_value - fee;
+            *cash += trade_value - fee - self.paid_distributions(trade);
 
             to_close.push(i);
         }
@@ -1405,7 +2467,7 @@ impl Engine {
         };
 
         if quantity < 0 && years_held.is_finite() && years_held > 0.0 {
-            fee += notional * self.runtime_settings.short_borrow_fee_annual_rate * years_held;
+            fee += notional * self.short_borrow_rate_years(ticker, entry_date, exit_date);
         }
 
         if quantity > 0 {
@@ -1415,11 +2477,15 @@ impl Engine {
             }
         }
 
+        // Distributions net against holding costs, so an ETF long can close with a negative fee
+        // and a short pays the distributions it owed the lender.
+        fee -= self.distributions_per_share(ticker, entry_date, exit_date) * quantity as f64;
+
         fee
     }
 
-    fn apply_entry_slippage(&self, price: f64, is_short: bool) -> f64 {
-        let slippage_rate = self.runtime_settings.trade_slippage_rate;
+    fn apply_entry_slippage(&self, ticker: &str, price: f64, is_short: bool) -> f64 {
+        let slippage_rate = self.runtime_settings.slippage_rate_for(ticker);
         if is_short {
             price * (1.0 - slippage_rate)
         } else {
@@ -1427,8 +2493,8 @@ impl Engine {
         }
     }
 
-    fn apply_exit_slippage(&self, price: f64, is_short: bool) -> f64 {
-        let slippage_rate = self.runtime_settings.trade_slippage_rate;
+    fn apply_exit_slippage(&self, ticker: &str, price: f64, is_short: bool) -> f64 {
+        let slippage_rate = self.runtime_settings.slippage_rate_for(ticker);
         if is_short {
             price * (1.0 + slippage_rate)
         } else {
@@ -1437,12 +2503,12 @@ impl Engine {
     }
 
     fn apply_entry_slippage_with_candle(&self, price: f64, is_short: bool, candle: &Candle) -> f64 {
-        let slipped = self.apply_entry_slippage(price, is_short);
+        let slipped = self.apply_entry_slippage(&candle.ticker, price, is_short);
         Self::clamp_price_to_candle_bounds(slipped, candle)
     }
 
     fn apply_exit_slippage_with_candle(&self, price: f64, is_short: bool, candle: &Candle) -> f64 {
-        let slipped = self.apply_exit_slippage(price, is_short);
+        let slipped = self.apply_exit_slippage(&candle.ticker, price, is_short);
         Self::clamp_price_to_candle_bounds(slipped, candle)
     }
 
@@ -1474,10 +2540,9 @@ impl Engine {
         }
     }
 
-    fn entry_price_supported(&self, price: f64) -> bool {
-        price.is_finite()
-            && price >= self.runtime_settings.trade_entry_price_min
-            && price <= self.runtime_settings.trade_entry_price_max
+    fn entry_price_supported(&self, ticker: &str, price: f64) -> bool {
+        let (min_price, max_price) = self.runtime_settings.entry_price_range_for(ticker);
+        price.is_finite() && price >= min_price && price <= max_price
     }
 
     fn guard_price_from_candle(candle: &Candle) -> Option<f64> {
@@ -1533,7 +2598,7 @@ impl Engine {
                 })?;
 
             ensure!(
-                self.price_within_bounds(trade.price, entry_min, entry_max),
+                self.price_within_bounds(&trade.ticker, trade.price, entry_min, entry_max),
                 "Trade {} entry price {:.4} outside {} range [{:.4}, {:.4}] on {}",
                 trade.id,
                 trade.price,
@@ -1634,7 +2699,7 @@ impl Engine {
                         })?;
 
                     ensure!(
-                        self.price_within_bounds(exit_price, exit_min, exit_max),
+                        self.price_within_bounds(&trade.ticker, exit_price, exit_min, exit_max),
                         "Trade {} exit price {:.4} outside {} range [{:.4}, {:.4}] on/ before {}",
                         trade.id,
                         exit_price,
@@ -1704,14 +2769,20 @@ impl Engine {
         }
     }
 
-    fn price_within_bounds(&self, price: f64, min_price: f64, max_price: f64) -> bool {
+    fn price_within_bounds(
+        &self,
+        ticker: &str,
+        price: f64,
+        min_price: f64,
+        max_price: f64,
+    ) -> bool {
         if !price.is_finite() || !min_price.is_finite() || !max_price.is_finite() {
             return false;
         }
         let lower = min_price.min(max_price);
         let upper = max_price.max(min_price);
         let magnitude = lower.abs().max(upper.abs()).max(price.abs()).max(1.0);
-        let tolerance = magnitude * self.runtime_settings.trade_slippage_rate + PRICE_EPSILON;
+        let tolerance = magnitude * self.runtime_settings.slippage_rate_for(ticker) + PRICE_EPSILON;
         price + tolerance >= lower && price <= upper + tolerance
     }
 
@@ -1741,7 +2812,9 @@ impl Engine {
             if !price.is_finite() || price <= 0.0 {
                 continue;
             }
-            let value = position.quantity as f64 * price;
+            let value = account_state
+                .fx
+                .to_account(position.quantity as f64 * price, &position.ticker);
             position_value += value;
             exposure += value.abs();
         }
@@ -1766,6 +2839,25 @@ impl Engine {
         self.resolve_account_buying_power(account_state)
     }
 
+    /// Whether the account's last session loss, from broker-reported equity, trips the
+    /// circuit breaker.
+    pub fn circuit_breaker_tripped(&self, account_state: &AccountStateSnapshot) -> bool {
+        match (account_state.last_equity, account_state.equity) {
+            (Some(previous), Some(current)) => self.config.circuit_breaker.trips(previous, current),
+            _ => false,
+        }
+    }
+
+    /// Whether a breaker tripped at `triggered_at` still freezes entries on `date`; the freeze
+    /// covers `freeze_days` calendar days starting with the trigger day.
+    fn circuit_breaker_freezes(&self, triggered_at: DateTime<Utc>, date: DateTime<Utc>) -> bool {
+        let breaker = self.config.circuit_breaker;
+        breaker.max_daily_loss > 0.0
+            && date.date_naive()
+                < triggered_at.date_naive() + Duration::days(i64::from(breaker.freeze_days))
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn plan_account_operations(
         &self,
         strategy_id: &str,
@@ -1787,6 +2879,7 @@ impl Engine {
                 operations: Vec::new(),
                 notes,
                 skipped_signals,
+                buy_sizing: Vec::new(),
             };
         }
 
@@ -1799,15 +2892,28 @@ impl Engine {
                 operations: Vec::new(),
                 notes,
                 skipped_signals,
+                buy_sizing: Vec::new(),
             };
         }
 
+        // Signals, candles and fills can be stamped at different times of day, so they are
+        // matched to the target by exchange session rather than by exact timestamp.
+        let calendar = self.runtime_settings.session_calendar;
         let mut available_cash = self.resolve_account_buying_power(account_state);
         if available_cash <= 0.0 {
             notes.push("account_cash_unavailable".to_string());
         }
+        let breaker_tripped = self.circuit_breaker_tripped(account_state);
+        let entries_frozen = breaker_tripped
+            || account_state
+                .circuit_breaker_triggered_at
+                .is_some_and(|at| self.circuit_breaker_freezes(at, target_date));
+        if entries_frozen {
+            notes.push(CIRCUIT_BREAKER_REASON.to_string());
+        }
 
         let mut operations = Vec::new();
+        let mut buy_sizing = Vec::new();
         let mut record_skip =
             |ticker: &str, action: SignalAction, reason: &str, details: Option<String>| {
                 skipped_signals.push(AccountSignalSkip {
@@ -1840,13 +2946,18 @@ impl Engine {
 
         let mut sell_signals: HashMap<String, &GeneratedSignal> = HashMap::new();
         for signal in signals.iter().filter(|signal| {
-            matches!(signal.action, SignalAction::Sell) && signal.date == target_date
+            matches!(signal.action, SignalAction::Sell)
+                && calendar.same_session(signal.date, target_date)
         }) {
             let ticker = signal.ticker.trim().to_uppercase();
             if ticker.is_empty() {
                 notes.push("signal_missing_ticker".to_string());
                 continue;
             }
+            if self.is_hedge_ticker(&ticker) {
+                record_skip(&ticker, SignalAction::Sell, HEDGE_INSTRUMENT_REASON, None);
+                continue;
+            }
             sell_signals.entry(ticker).or_insert(signal);
         }
         let sell_fraction = coerce_binary_param(self.config.sell_fraction, 1.0);
@@ -1857,11 +2968,42 @@ impl Engine {
             sell_signals.clear();
         }
 
-        let mut actionable_signals: Vec<(u64, String, &GeneratedSignal)> = signals
-            .iter()
-            .filter(|signal| {
-                matches!(signal.action, SignalAction::Buy) && signal.date == target_date
-            })
+        let batched_signals: Vec<GeneratedSignal>;
+        let buy_signals: Vec<&GeneratedSignal> = match self.config.entry_weekday {
+            None => signals
+                .iter()
+                .filter(|signal| {
+                    matches!(signal.action, SignalAction::Buy)
+                        && calendar.same_session(signal.date, target_date)
+                })
+                .collect(),
+            Some(weekday)
+                if is_entry_execution_tick(
+                    Some(calendar.session_date(target_date)),
+                    next_weekday(calendar.session_date(target_date)),
+                    weekday,
+                ) =>
+            {
+                batched_signals = self.batch_queued_buy_signals(signals, target_date);
+                batched_signals.iter().collect()
+            }
+            Some(_) => {
+                for signal in signals.iter().filter(|signal| {
+                    matches!(signal.action, SignalAction::Buy)
+                        && calendar.same_session(signal.date, target_date)
+                }) {
+                    record_skip(
+                        &signal.ticker.trim().to_uppercase(),
+                        SignalAction::Buy,
+                        "awaiting_entry_tick",
+                        None,
+                    );
+                }
+                Vec::new()
+            }
+        };
+        let mut actionable_signals: Vec<(u64, String, &GeneratedSignal)> = buy_signals
+            .into_iter()
             .map(|signal| {
                 let ticker = signal.ticker.trim().to_uppercase();
                 let hash = Self::ticker_date_hash(ticker.as_str(), target_date);
@@ -1871,8 +3013,18 @@ impl Engine {
         actionable_signals.sort_by(|(hash_a, ticker_a, _), (hash_b, ticker_b, _)| {
             hash_a.cmp(hash_b).then_with(|| ticker_a.cmp(ticker_b))
         });
+        let volatility_block = self.volatility_filter_details(target_date);
+        // Buys fill next session, so that is the day checked against release days.
+        let economic_event_block =
+            self.economic_event_details(next_weekday(calendar.session_date(target_date)));
         let existing_buy_ops = existing_buy_operations_today > 0;
-        if existing_buy_ops {
+        if entries_frozen {
+            for (_, ticker, _signal) in actionable_signals {
+                if !ticker.is_empty() {
+                    record_skip(&ticker, SignalAction::Buy, CIRCUIT_BREAKER_REASON, None);
+                }
+            }
+        } else if existing_buy_ops {
             notes.push("buy_operations_already_planned_for_day".to_string());
             for (_, ticker, _signal) in actionable_signals {
                 if ticker.is_empty() {
@@ -1894,6 +3046,59 @@ impl Engine {
                     continue;
                 }
 
+                if self.is_hedge_ticker(&ticker) {
+                    notes.push(format!("signal_{}_{}", ticker, HEDGE_INSTRUMENT_REASON));
+                    record_skip(&ticker, SignalAction::Buy, HEDGE_INSTRUMENT_REASON, None);
+                    continue;
+                }
+
+                if let Some(details) = &volatility_block {
+                    notes.push(format!("signal_{}_{}", ticker, VOLATILITY_FILTER_REASON));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        VOLATILITY_FILTER_REASON,
+                        Some(details.clone()),
+                    );
+                    continue;
+                }
+
+                if let Some(details) = self.sentiment_filter_details(&ticker, target_date) {
+                    notes.push(format!("signal_{}_{}", ticker, SENTIMENT_FILTER_REASON));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        SENTIMENT_FILTER_REASON,
+                        Some(details),
+                    );
+                    continue;
+                }
+
+                if let Some(details) = &economic_event_block {
+                    notes.push(format!("signal_{}_{}", ticker, ECONOMIC_EVENT_REASON));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        ECONOMIC_EVENT_REASON,
+                        Some(details.clone()),
+                    );
+                    continue;
+                }
+
+                // The order fills next session, so that is the day checked against earnings.
+                if let Some(earnings) = self
+                    .upcoming_earnings(&ticker, next_weekday(calendar.session_date(target_date)))
+                {
+                    notes.push(format!("signal_{}_{}", ticker, EARNINGS_EXIT_REASON));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        EARNINGS_EXIT_REASON,
+                        Some(format!("earnings on {}", earnings.format("%Y-%m-%d"))),
+                    );
+                    continue;
+                }
+
                 if let Some(metadata) = ticker_metadata.get(&ticker) {
                     if !metadata.tradable {
                         notes.push(format!("signal_{}_not_tradable", ticker));
@@ -1925,28 +3130,64 @@ impl Engine {
                     .iter()
                     .enumerate()
                     .rev()
-                    .find(|(_, candle)| candle.date == target_date)
+                    .find(|(_, candle)| calendar.same_session(candle.date, target_date))
                     .map(|(index, candle)| (index, *candle))
                 else {
                     notes.push(format!("no_candle_for_signal_{}_on_date", ticker));
                     record_skip(&ticker, SignalAction::Buy, "missing_candle_for_date", None);
                     continue;
                 };
-                let planning_close = Self::planning_reference_price(current_candle);
-                if !self.entry_price_supported(planning_close) {
-                    notes.push(format!("signal_{}_price_out_of_range", ticker));
-                    record_skip(&ticker, SignalAction::Buy, "price_out_of_range", None);
+                let min_history = self.runtime_settings.min_ticker_history_candles;
+                if candle_index + 1 < min_history {
+                    notes.push(format!("signal_{}_{}", ticker, INSUFFICIENT_HISTORY_REASON));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        INSUFFICIENT_HISTORY_REASON,
+                        Some(format!(
+                            "{} candles, need {}",
+                            candle_index + 1,
+                            min_history
+                        )),
+                    );
+                    continue;
+                }
+                let planning_close = Self::planning_reference_price(current_candle);
+                if !self.entry_price_supported(&ticker, planning_close) {
+                    let (min_price, max_price) =
+                        self.runtime_settings.entry_price_range_for(&ticker);
+                    notes.push(format!("signal_{}_price_out_of_range", ticker));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        "price_out_of_range",
+                        Some(format!(
+                            "price {:.2} outside {:.2}..{:.2}",
+                            planning_close, min_price, max_price
+                        )),
+                    );
                     continue;
                 }
 
+                let minimum_dollar_volume =
+                    self.runtime_settings.minimum_dollar_volume_for(&ticker);
                 if !has_minimum_dollar_volume(
                     ticker_candles,
                     candle_index,
                     self.runtime_settings.minimum_dollar_volume_lookback,
-                    self.runtime_settings.minimum_dollar_volume_for_entry,
+                    minimum_dollar_volume,
                 ) {
                     notes.push(format!("signal_{}_insufficient_volume", ticker));
-                    record_skip(&ticker, SignalAction::Buy, "insufficient_volume", None);
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        "insufficient_volume",
+                        Some(format!(
+                            "needs {:.0} dollar volume over {} days",
+                            minimum_dollar_volume,
+                            self.runtime_settings.minimum_dollar_volume_lookback
+                        )),
+                    );
                     continue;
                 }
 
@@ -1970,6 +3211,26 @@ impl Engine {
                     continue;
                 };
 
+                // Sizing runs in the ticker's currency and the spent cash is converted back.
+                let Some(fx_rate) = account_state
+                    .fx
+                    .ticker_rate_on(&ticker, calendar.session_date(target_date))
+                else {
+                    notes.push(format!("fx_rate_unavailable_for_{}", ticker));
+                    record_skip(
+                        &ticker,
+                        SignalAction::Buy,
+                        "fx_rate_unavailable",
+                        Some(format!(
+                            "no {}/{} rate",
+                            account_state.fx.currency(),
+                            account_state.fx.ticker_currency(&ticker)
+                        )),
+                    );
+                    continue;
+                };
+                let sizing_cash = available_cash * fx_rate;
+
                 let signal_confidence = signal.confidence.unwrap_or(1.0);
                 let realized_vol = if (self.config.position_sizing.mode == 2
                     || self.config.position_sizing.mode == 3)
@@ -1984,9 +3245,9 @@ impl Engine {
                     None
                 };
 
-                let allocation = match determine_position_size(PositionSizingParams {
+                let mut allocation = match determine_position_size(PositionSizingParams {
                     price,
-                    available_cash,
+                    available_cash: sizing_cash,
                     trade_size_ratio: self.config.trade_size_ratio,
                     minimum_trade_size: self.config.minimum_trade_size,
                     position_sizing_mode: self.config.position_sizing.mode,
@@ -1997,41 +3258,101 @@ impl Engine {
                     PositionSizingOutcome::Sized(allocation) => allocation,
                     PositionSizingOutcome::TooSmall => {
                         notes.push(format!("signal_{}_insufficient_size", ticker));
-                        record_skip(&ticker, SignalAction::Buy, "insufficient_size", None);
+                        record_skip(
+                            &ticker,
+                            SignalAction::Buy,
+                            "insufficient_size",
+                            Some(format!(
+                                "price {:.2}, cash {:.2}, size ratio {}, minimum trade {:.2}",
+                                price,
+                                sizing_cash,
+                                self.config.trade_size_ratio,
+                                self.config.minimum_trade_size
+                            )),
+                        );
                         continue;
                     }
                     PositionSizingOutcome::InsufficientCash { required } => {
                         notes.push(format!(
                             "insufficient_cash_for_signal_{} (need {:.2}, have {:.2})",
-                            ticker, required, available_cash
+                            ticker, required, sizing_cash
                         ));
                         record_skip(
                             &ticker,
                             SignalAction::Buy,
                             "insufficient_cash",
-                            Some(format!("need {:.2}, have {:.2}", required, available_cash)),
+                            Some(format!("need {:.2}, have {:.2}", required, sizing_cash)),
                         );
                         continue;
                     }
                 };
 
-                let stop_loss = initial_stop_loss(
-                    self.config.stop_loss.mode,
-                    self.config.stop_loss.atr_multiplier,
-                    self.config.stop_loss.atr_period,
-                    self.config.stop_loss.ratio,
+                let risk_overrides = AccountRiskOverrides {
+                    max_risk_per_trade: account_state
+                        .risk_overrides
+                        .max_risk_per_trade
+                        .map(|max_risk| max_risk * fx_rate),
+                    ..account_state.risk_overrides
+                };
+                let stop_loss = risk_overrides.tighten_stop(
                     price,
-                    ticker_candles,
-                    candle_index,
-                    false,
+                    initial_stop_loss(
+                        self.config.stop_loss.mode,
+                        self.config.stop_loss.atr_multiplier,
+                        self.config.stop_loss.atr_period,
+                        self.config.stop_loss.ratio,
+                        price,
+                        ticker_candles,
+                        candle_index,
+                        false,
+                    ),
                 );
+                if let Some(max_quantity) = risk_overrides
+                    .max_quantity(price, stop_loss)
+                    .filter(|max_quantity| *max_quantity < allocation.quantity)
+                {
+                    let max_risk = risk_overrides.max_risk_per_trade.unwrap_or_default();
+                    if max_quantity <= 0 {
+                        notes.push(format!("signal_{}_exceeds_account_risk", ticker));
+                        record_skip(
+                            &ticker,
+                            SignalAction::Buy,
+                            "account_risk_limit",
+                            Some(format!(
+                                "one share risks {:.2} to stop {:.2}, limit {:.2}",
+                                price - stop_loss.unwrap_or(0.0),
+                                stop_loss.unwrap_or(0.0),
+                                max_risk
+                            )),
+                        );
+                        continue;
+                    }
+                    notes.push(format!(
+                        "signal_{}_capped_by_account_risk ({} -> {} shares)",
+                        ticker, allocation.quantity, max_quantity
+                    ));
+                    allocation.quantity = max_quantity;
+                    allocation.trade_value = max_quantity as f64 * price;
+                }
 
                 let trade_id = format!(
                     "{}-plan",
                     generate_trade_id(strategy_id, account_id, &ticker, target_date)
                 );
 
-                available_cash -= allocation.trade_value;
+                buy_sizing.push(PlannedBuySizing {
+                    ticker: ticker.clone(),
+                    price,
+                    available_cash: sizing_cash,
+                    confidence: signal_confidence,
+                    trade_size_ratio: self.config.trade_size_ratio,
+                    position_sizing_mode: self.config.position_sizing.mode,
+                    realized_vol,
+                    quantity: allocation.quantity,
+                    trade_value: allocation.trade_value,
+                    stop_loss,
+                });
+                available_cash -= allocation.trade_value / fx_rate;
                 operations.push(AccountOperationPlan {
                     trade_id,
                     ticker: ticker.clone(),
@@ -2047,6 +3368,9 @@ impl Engine {
                     signal_confidence: signal.confidence,
                     account_cash_at_plan: Some(account_state.available_cash),
                     days_held: None,
+                    expected_cost: None,
+                    replaces_order_id: None,
+                    take_profit: risk_overrides.take_profit(price, stop_loss),
                 });
             }
         }
@@ -2119,7 +3443,7 @@ impl Engine {
                 .iter()
                 .enumerate()
                 .rev()
-                .find(|(_, candle)| candle.date == target_date)
+                .find(|(_, candle)| calendar.same_session(candle.date, target_date))
                 .map(|(index, candle)| (index, *candle))
             else {
                 notes.push(format!("no_candle_for_{}_on_latest_date", trade.ticker));
@@ -2154,6 +3478,35 @@ impl Engine {
             let days_held = current_date.signed_duration_since(trade.date).num_days();
             let days_held_i32 = i32::try_from(days_held).unwrap_or(i32::MAX);
 
+            if breaker_tripped && self.config.circuit_breaker.liquidate {
+                operations.push(AccountOperationPlan {
+                    trade_id: trade.id.clone(),
+                    ticker: trade.ticker.clone(),
+                    quantity: Some(trade.quantity),
+                    price: Some(planning_close),
+                    stop_loss: trade.stop_loss,
+                    previous_stop_loss: None,
+                    triggered_at: current_date,
+                    operation_type: AccountOperationType::ClosePosition,
+                    reason: Some(CIRCUIT_BREAKER_REASON.to_string()),
+                    order_type: Some("market".to_string()),
+                    discount_applied: None,
+                    signal_confidence: None,
+                    account_cash_at_plan: None,
+                    days_held: Some(days_held_i32),
+                    expected_cost: None,
+                    replaces_order_id: None,
+                    take_profit: None,
+                });
+                pending_sell_signals.remove(&trade.ticker);
+                continue;
+            }
+
+            // Hedge legs are planned by the overlay below.
+            if self.is_hedge_ticker(&trade.ticker) {
+                continue;
+            }
+
             if let Some(signal) = sell_signals.get(&trade.ticker) {
                 operations.push(AccountOperationPlan {
                     trade_id: trade.id.clone(),
@@ -2170,12 +3523,16 @@ impl Engine {
                     signal_confidence: signal.confidence,
                     account_cash_at_plan: None,
                     days_held: Some(days_held_i32),
+                    expected_cost: None,
+                    replaces_order_id: None,
+                    take_profit: None,
                 });
                 pending_sell_signals.remove(&trade.ticker);
                 continue;
             }
 
-            if self.config.max_holding_days > 0 && days_held >= self.config.max_holding_days as i64
+            if self.config.max_holding_days > 0
+                && days_held >= self.holding_days_limit(ticker_candles, candle_index)
             {
                 operations.push(AccountOperationPlan {
                     trade_id: trade.id.clone(),
@@ -2192,6 +3549,63 @@ impl Engine {
                     signal_confidence: None,
                     account_cash_at_plan: None,
                     days_held: Some(days_held_i32),
+                    expected_cost: None,
+                    replaces_order_id: None,
+                    take_profit: None,
+                });
+                continue;
+            }
+
+            if self
+                .upcoming_earnings(&trade.ticker, calendar.session_date(current_date))
+                .is_some()
+            {
+                operations.push(AccountOperationPlan {
+                    trade_id: trade.id.clone(),
+                    ticker: trade.ticker.clone(),
+                    quantity: Some(trade.quantity),
+                    price: Some(planning_close),
+                    stop_loss: trade.stop_loss,
+                    previous_stop_loss: None,
+                    triggered_at: current_date,
+                    operation_type: AccountOperationType::ClosePosition,
+                    reason: Some(EARNINGS_EXIT_REASON.to_string()),
+                    order_type: None,
+                    discount_applied: None,
+                    signal_confidence: None,
+                    account_cash_at_plan: None,
+                    days_held: Some(days_held_i32),
+                    expected_cost: None,
+                    replaces_order_id: None,
+                    take_profit: None,
+                });
+                continue;
+            }
+
+            if let Some(target) = self
+                .config
+                .scale_out
+                .target_quantity(trade.original_quantity(), days_held)
+                .filter(|target| target.abs() < trade.quantity.abs())
+            {
+                operations.push(AccountOperationPlan {
+                    trade_id: trade.id.clone(),
+                    ticker: trade.ticker.clone(),
+                    quantity: Some(trade.quantity - target),
+                    price: Some(planning_close),
+                    stop_loss: trade.stop_loss,
+                    previous_stop_loss: None,
+                    triggered_at: current_date,
+                    operation_type: AccountOperationType::ClosePosition,
+                    reason: Some("time_decay_exit".to_string()),
+                    order_type: None,
+                    discount_applied: None,
+                    signal_confidence: None,
+                    account_cash_at_plan: None,
+                    days_held: Some(days_held_i32),
+                    expected_cost: None,
+                    replaces_order_id: None,
+                    take_profit: None,
                 });
                 continue;
             }
@@ -2214,17 +3628,23 @@ impl Engine {
                             signal_confidence: None,
                             account_cash_at_plan: None,
                             days_held: None,
+                            expected_cost: None,
+                            replaces_order_id: None,
+                            take_profit: None,
                         });
                         continue;
                     }
 
                     if let Some(update) = compute_trailing_stop(TrailingStopParams {
                         stop_loss_mode: self.config.stop_loss.mode,
+                        stop_loss_ratio: self.config.stop_loss.ratio,
                         atr_multiplier: self.config.stop_loss.atr_multiplier,
                         atr_period: self.config.stop_loss.atr_period,
+                        breakeven_r_multiple: self.config.stop_loss.breakeven_r_multiple,
                         ticker_candles,
                         candle_index,
                         current_candle,
+                        entry_price: trade.price,
                         current_stop: curr_stop,
                         is_short: trade.quantity < 0,
                         planning_close: Some(planning_close),
@@ -2245,6 +3665,9 @@ impl Engine {
                             signal_confidence: None,
                             account_cash_at_plan: None,
                             days_held: None,
+                            expected_cost: None,
+                            replaces_order_id: trade.stop_order_id.clone(),
+                            take_profit: None,
                         });
                     }
                 }
@@ -2255,11 +3678,154 @@ impl Engine {
             record_skip(&ticker, SignalAction::Sell, "sell_no_active_position", None);
         }
 
+        // A liquidating circuit breaker has already closed the hedge with everything else.
+        if !(breaker_tripped && self.config.circuit_breaker.liquidate) {
+            let (hedge_operations, hedge_note) = self.plan_hedge_operations(
+                strategy_id,
+                account_id,
+                existing_trades,
+                &operations,
+                &candles_by_ticker,
+                target_date,
+            );
+            operations.extend(hedge_operations);
+            notes.extend(hedge_note);
+        }
+
         PlannedOperations {
             operations,
             notes,
             skipped_signals,
+            buy_sizing,
+        }
+    }
+
+    /// Orders that move the hedge short toward `hedgeBeta` times the long exposure of the open
+    /// trades and `planned` buys, at the hedge ticker's planning price. Increases open a new
+    /// leg; decreases cover the newest legs first. Also returns the planning note, if any.
+    fn plan_hedge_operations(
+        &self,
+        strategy_id: &str,
+        account_id: &str,
+        existing_trades: &[Trade],
+        planned: &[AccountOperationPlan],
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        target_date: DateTime<Utc>,
+    ) -> (Vec<AccountOperationPlan>, Option<String>) {
+        let Some(hedge_ticker) = self.hedge_ticker() else {
+            return (Vec::new(), None);
+        };
+        let calendar = self.runtime_settings.session_calendar;
+        let session_price = |ticker: &str| {
+            candles_by_ticker
+                .get(ticker)?
+                .iter()
+                .rev()
+                .find(|candle| calendar.same_session(candle.date, target_date))
+                .map(|candle| Self::planning_reference_price(candle))
+        };
+        let Some(price) = session_price(hedge_ticker) else {
+            let note = format!("missing_candles_for_hedge_{}", hedge_ticker);
+            return (Vec::new(), Some(note));
+        };
+
+        let mut hedge_legs: Vec<&Trade> = existing_trades
+            .iter()
+            .filter(|trade| {
+                trade.ticker == hedge_ticker
+                    && matches!(trade.status, TradeStatus::Pending | TradeStatus::Active)
+            })
+            .collect();
+        if hedge_legs.iter().any(|trade| {
+            trade.status == TradeStatus::Pending
+                || trade
+                    .exit_order_id
+                    .as_deref()
+                    .is_some_and(|value| !value.trim().is_empty())
+        }) {
+            let note = format!("hedge_{}_order_pending", hedge_ticker);
+            return (Vec::new(), Some(note));
+        }
+
+        let open_exposure: f64 = existing_trades
+            .iter()
+            .filter(|trade| {
+                trade.status == TradeStatus::Active
+                    && trade.quantity > 0
+                    && trade.ticker != hedge_ticker
+            })
+            .map(|trade| {
+                trade.quantity as f64 * session_price(&trade.ticker).unwrap_or(trade.price)
+            })
+            .sum();
+        let planned_exposure: f64 = planned
+            .iter()
+            .filter(|operation| operation.operation_type == AccountOperationType::OpenPosition)
+            .map(|operation| {
+                operation.quantity.unwrap_or(0).max(0) as f64 * operation.price.unwrap_or(0.0)
+            })
+            .sum();
+        let hedged: i32 = -hedge_legs.iter().map(|trade| trade.quantity).sum::<i32>();
+        let Some(target) =
+            self.config
+                .hedge
+                .rebalance_target(open_exposure + planned_exposure, hedged, price)
+        else {
+            return (Vec::new(), None);
+        };
+        let note = Some(format!(
+            "hedge_{}_rebalance ({} -> {} shares short)",
+            hedge_ticker, hedged, target
+        ));
+
+        let operation = |trade_id: String, quantity: i32, operation_type| AccountOperationPlan {
+            trade_id,
+            ticker: hedge_ticker.to_string(),
+            quantity: Some(quantity),
+            price: Some(price),
+            stop_loss: None,
+            previous_stop_loss: None,
+            triggered_at: target_date,
+            operation_type,
+            reason: Some(HEDGE_REASON.to_string()),
+            order_type: Some("market".to_string()),
+            discount_applied: None,
+            signal_confidence: None,
+            account_cash_at_plan: None,
+            days_held: None,
+            expected_cost: None,
+            replaces_order_id: None,
+            take_profit: None,
+        };
+        if target > hedged {
+            let trade_id = format!(
+                "{}-hedge",
+                generate_trade_id(strategy_id, account_id, hedge_ticker, target_date)
+            );
+            let open = operation(
+                trade_id,
+                -(target - hedged),
+                AccountOperationType::OpenPosition,
+            );
+            return (vec![open], note);
+        }
+
+        hedge_legs.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.id.cmp(&b.id)));
+        let mut to_cover = hedged - target;
+        let mut operations = Vec::new();
+        for leg in hedge_legs {
+            if to_cover <= 0 {
+                break;
+            }
+            let covered = (-leg.quantity).min(to_cover);
+            to_cover -= covered;
+            operations.push(operation(
+                leg.id.clone(),
+                -covered,
+                AccountOperationType::ClosePosition,
+            ));
         }
+        (operations, note)
     }
 
     fn ordered_tickers_for_date<'a>(tickers: &'a [String], date: DateTime<Utc>) -> Vec<&'a String> {
@@ -2273,6 +3839,50 @@ impl Engine {
         ordered.into_iter().map(|(_, ticker)| ticker).collect()
     }
 
+    /// First signal date plan-operations must load for `target_date`: with a weekly entry
+    /// tick the whole week of queued buys is needed, otherwise only the target date.
+    pub fn planning_signal_window_start(&self, target_date: DateTime<Utc>) -> DateTime<Utc> {
+        if self.config.entry_weekday.is_some() {
+            target_date - Duration::days(ENTRY_TICK_WINDOW_DAYS - 1)
+        } else {
+            target_date
+        }
+    }
+
+    /// Replays the week's signals through the same queue the backtest uses and returns the
+    /// surviving buys re-dated to `target_date`, the eve of the entry tick.
+    fn batch_queued_buy_signals(
+        &self,
+        signals: &[GeneratedSignal],
+        target_date: DateTime<Utc>,
+    ) -> Vec<GeneratedSignal> {
+        let window_start = self.planning_signal_window_start(target_date);
+        let mut ordered: Vec<&GeneratedSignal> = signals
+            .iter()
+            .filter(|signal| signal.date >= window_start && signal.date <= target_date)
+            .collect();
+        ordered.sort_by_key(|signal| signal.date);
+        let mut queue = QueuedEntries::default();
+        for signal in ordered {
+            let ticker = signal.ticker.trim().to_uppercase();
+            match signal.action {
+                SignalAction::Buy => queue.queue(&ticker, signal.confidence.unwrap_or(0.0)),
+                SignalAction::Sell => queue.cancel(&ticker),
+                SignalAction::Hold => {}
+            }
+        }
+        queue
+            .drain()
+            .into_iter()
+            .map(|(ticker, confidence)| GeneratedSignal {
+                date: target_date,
+                ticker,
+                action: SignalAction::Buy,
+                confidence: Some(confidence),
+            })
+            .collect()
+    }
+
     fn ticker_date_hash(ticker: &str, date: DateTime<Utc>) -> u64 {
         let mut hasher = DefaultHasher::new();
         ticker.hash(&mut hasher);
@@ -2331,7 +3941,12 @@ impl Engine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::LocalOptimizationObjective;
+    use crate::blackout::{BlackoutConfig, BlackoutRange};
+    use crate::config::{
+        CircuitBreakerConfig, HedgeConfig, LocalOptimizationObjective, ScaleOutConfig,
+        TickerRuntimeOverride,
+    };
+    use crate::fx::FxRates;
     use crate::models::{AccountOperationType, SignalAction, StrategySignal, Trade, TradeStatus};
     use crate::trading_rules::PRICE_EPSILON;
     use chrono::{Duration, Utc};
@@ -2342,6 +3957,7 @@ mod tests {
             trade_close_fee_rate: 0.0005,
             trade_slippage_rate: 0.003,
             short_borrow_fee_annual_rate: 0.003,
+            borrow_fee_multiplier: 1.0,
             trade_entry_price_min: 0.10,
             trade_entry_price_max: 1000.0,
             minimum_dollar_volume_for_entry: 150_000.0,
@@ -2352,6 +3968,17 @@ mod tests {
             ],
             local_optimization_objective: LocalOptimizationObjective::Cagr,
             max_allowed_drawdown_ratio: 0.40,
+            capacity_max_volume_share: 0.01,
+            stop_fill_at_stop_price: false,
+            order_rejection_rate: 0.0,
+            partial_fill_max_volume_share: 0.0,
+            partial_fill_remainder: PartialFillRemainder::Cancel,
+            warmup_policy: WarmupPolicy::Skip,
+            min_ticker_history_candles: 0,
+            ticker_overrides: HashMap::new(),
+            session_calendar: Default::default(),
+            hedge_ticker: "SPY".to_string(),
+            volatility_index_ticker: "VIX".to_string(),
         }
     }
 
@@ -2397,7 +4024,7 @@ mod tests {
         }
         let first_price = prices[0];
         let mut extended = Vec::with_capacity(required_history + prices.len());
-        extended.extend(std::iter::repeat(first_price).take(required_history));
+        extended.extend(std::iter::repeat_n(first_price, required_history));
         extended.append(&mut prices);
         let (candles, dates) = generate_candles(ticker, extended);
         (candles, dates, required_history)
@@ -2438,6 +4065,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
 
@@ -2474,80 +4102,800 @@ mod tests {
             .unwrap_or(false));
     }
 
-    fn with_spy_reference(candles: &[Candle]) -> Vec<Candle> {
-        let mut combined = candles.to_vec();
-        combined.extend(generate_spy_candles(candles.len()));
-        combined
+    fn generate_weekday_candles(
+        ticker: &str,
+        count: usize,
+        price: f64,
+    ) -> (Vec<Candle>, Vec<DateTime<Utc>>) {
+        // 2024-01-01 is a Monday.
+        let mut day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut candles = Vec::new();
+        let mut dates = Vec::new();
+        while dates.len() < count {
+            if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                let date = day.and_hms_opt(0, 0, 0).unwrap().and_utc();
+                dates.push(date);
+                candles.push(Candle {
+                    ticker: ticker.to_string(),
+                    date,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    unadjusted_close: Some(price),
+                    volume_shares: 10_000_000,
+                });
+            }
+            day += Duration::days(1);
+        }
+        (candles, dates)
     }
 
-    #[test]
-    fn test_resolve_trading_start_index_rounds_down_within_same_day() {
-        let day_zero = create_date(0);
-        let day_one = create_date(1);
-        let unique_dates = vec![day_zero, day_one];
-        let intraday_start = day_zero + Duration::hours(15);
+    fn run_single_buy_backtest(engine: &Engine, signal_day: u32) -> Vec<DateTime<Utc>> {
+        let (candles, dates) = generate_weekday_candles("WEEK", 15, 50.0);
+        let mut candles_by_ticker = HashMap::new();
+        candles_by_ticker.insert("WEEK".to_string(), candles.iter().collect::<Vec<_>>());
+        let signal_date = NaiveDate::from_ymd_opt(2024, 1, signal_day).unwrap();
 
-        assert_eq!(
-            Engine::resolve_trading_start_index(&unique_dates, intraday_start),
-            0
+        let tickers = vec!["WEEK".to_string()];
+        let result = engine.run_backtest_loop(
+            &tickers,
+            &dates,
+            &candles_by_ticker,
+            0,
+            0,
+            |_, _, date, _| {
+                (date.date_naive() == signal_date).then_some(SignalDecision {
+                    action: SignalAction::Buy,
+                    confidence: 0.9,
+                })
+            },
+            None,
+            false,
         );
+        result
+            .active_trades
+            .iter()
+            .chain(result.closed_trades.iter())
+            .map(|trade| trade.date)
+            .collect()
     }
 
     #[test]
-    fn test_resolve_trading_start_index_clamps_to_last_available_day() {
-        let day_zero = create_date(0);
-        let day_one = create_date(1);
-        let unique_dates = vec![day_zero, day_one];
-        let future_start = day_one + Duration::days(5);
+    fn ticker_overrides_replace_slippage_and_entry_bounds() {
+        let mut settings = test_runtime_settings();
+        settings.ticker_overrides.insert(
+            "TQQQ".to_string(),
+            TickerRuntimeOverride {
+                trade_slippage_rate: Some(0.01),
+                trade_entry_price_max: Some(50.0),
+                ..Default::default()
+            },
+        );
+        let engine = Engine::new(settings);
 
+        assert!((engine.apply_entry_slippage("TQQQ", 100.0, false) - 101.0).abs() < 1e-9);
+        assert!((engine.apply_entry_slippage("SPY", 100.0, false) - 100.3).abs() < 1e-9);
+        assert!(!engine.entry_price_supported("TQQQ", 60.0));
+        assert!(engine.entry_price_supported("SPY", 60.0));
         assert_eq!(
-            Engine::resolve_trading_start_index(&unique_dates, future_start),
-            1
+            engine.runtime_settings.minimum_dollar_volume_for("TQQQ"),
+            150_000.0
         );
+        let scaled = engine.runtime_settings.with_cost_multiplier(2.0);
+        assert!((scaled.slippage_rate_for("TQQQ") - 0.02).abs() < 1e-12);
     }
 
-    struct MockStrategy {
-        signals: HashMap<(String, DateTime<Utc>), StrategySignal>,
-    }
+    #[test]
+    fn run_loop_defers_buys_to_entry_weekday() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        let day = |d| {
+            NaiveDate::from_ymd_opt(2024, 1, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
 
-    impl Strategy for MockStrategy {
-        fn generate_signal(
-            &self,
-            ticker: &str,
-            candles: &[Candle],
-            index: usize,
-        ) -> StrategySignal {
-            let current_date = candles[index].date;
-            self.signals
-                .get(&(ticker.to_string(), current_date))
-                .cloned()
-                .unwrap_or(StrategySignal {
-                    action: SignalAction::Hold,
-                    confidence: 0.0,
-                })
-        }
+        assert_eq!(run_single_buy_backtest(&engine, 10), vec![day(11)]);
 
-        fn get_min_data_points(&self) -> usize {
-            0
-        }
+        engine.config.entry_weekday = Some(Weekday::Mon);
+        assert_eq!(run_single_buy_backtest(&engine, 10), vec![day(15)]);
+        assert_eq!(run_single_buy_backtest(&engine, 12), vec![day(15)]);
+    }
 
-        fn get_template_id(&self) -> &str {
-            "mock_strategy"
+    #[test]
+    fn run_loop_fills_entries_per_fill_timing() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        let (mut candles, dates) = generate_weekday_candles("FILL", 15, 50.0);
+        for (offset, candle) in candles.iter_mut().enumerate() {
+            candle.open = 50.0 + offset as f64;
+            candle.close = candle.open + 0.5;
+            candle.high = candle.close + 1.0;
+            candle.low = candle.open - 1.0;
+        }
+        // The day after the signal dips through any stop: entries held at that open are
+        // stopped out, while a next-close entry only fills after the dip.
+        candles[8].low = 40.0;
+        let mut candles_by_ticker = HashMap::new();
+        candles_by_ticker.insert("FILL".to_string(), candles.iter().collect::<Vec<_>>());
+        let tickers = vec!["FILL".to_string()];
+        let signal_date = dates[7];
+
+        let cases = [
+            (FillTiming::NextOpen, 8, 58.0, false),
+            (FillTiming::SameClose, 7, 57.5, false),
+            (FillTiming::NextClose, 8, 58.5, true),
+        ];
+        for (timing, fill_index, raw_price, still_open) in cases {
+            engine.config.fill_timing = timing;
+            let result = engine.run_backtest_loop(
+                &tickers,
+                &dates,
+                &candles_by_ticker,
+                0,
+                0,
+                |_, _, date, _| {
+                    (date == signal_date).then_some(SignalDecision {
+                        action: SignalAction::Buy,
+                        confidence: 0.9,
+                    })
+                },
+                None,
+                false,
+            );
+            assert_eq!(
+                result.active_trades.len(),
+                still_open as usize,
+                "{:?}",
+                timing
+            );
+            let trades: Vec<&Trade> = result
+                .active_trades
+                .iter()
+                .chain(result.closed_trades.iter())
+                .collect();
+            assert_eq!(trades.len(), 1, "{:?}", timing);
+            let trade = trades[0];
+            assert_eq!(trade.date, dates[fill_index], "{:?}", timing);
+            let expected =
+                engine.apply_entry_slippage_with_candle(raw_price, false, &candles[fill_index]);
+            assert!((trade.price - expected).abs() < 1e-9, "{:?}", timing);
         }
     }
 
     #[test]
-    fn test_backtest_constant_price() {
-        let engine = Engine::new(test_runtime_settings());
-        let ticker = "CONST".to_string();
-        let spy = "SPY".to_string();
-        let (candles, unique_dates, history_offset) =
-            generate_candles_with_history(&ticker, vec![100.0, 100.0, 100.0, 100.0]);
-        let all_candles = with_spy_reference(&candles);
-
-        let mut signals = HashMap::new();
-        signals.insert(
-            (ticker.clone(), unique_dates[history_offset]),
+    fn run_loop_scales_out_on_schedule_before_max_holding() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        // Entry fills Thursday 2024-01-11: one step on Friday, the next on Monday.
+        engine.config.scale_out = ScaleOutConfig {
+            days: [1, 4, 0],
+            fraction: 0.25,
+        };
+        let (mut candles, dates) = generate_weekday_candles("DECAY", 15, 50.0);
+        for (offset, candle) in candles.iter_mut().enumerate() {
+            candle.open = 50.0 + offset as f64;
+            candle.close = candle.open + 0.5;
+            candle.high = candle.close + 1.0;
+            candle.low = candle.open - 1.0;
+        }
+        let mut candles_by_ticker = HashMap::new();
+        candles_by_ticker.insert("DECAY".to_string(), candles.iter().collect::<Vec<_>>());
+        let tickers = vec!["DECAY".to_string()];
+        let signal_date = dates[7];
+
+        let result = engine.run_backtest_loop(
+            &tickers,
+            &dates,
+            &candles_by_ticker,
+            0,
+            0,
+            |_, _, date, _| {
+                (date == signal_date).then_some(SignalDecision {
+                    action: SignalAction::Buy,
+                    confidence: 0.9,
+                })
+            },
+            None,
+            false,
+        );
+
+        assert_eq!(result.active_trades.len(), 1);
+        let open = &result.active_trades[0];
+        let original = open.original_quantity();
+        // Each step rounds the cumulative share of the original position, not the step alone.
+        let first = (original as f64 * 0.25).round() as i32;
+        let both = (original as f64 * 0.5).round() as i32;
+        assert_eq!(open.quantity, original - both);
+        let parts = &result.closed_trades;
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].id, format!("{}-partial-1", open.id));
+        assert_eq!(parts[1].id, format!("{}-partial-2", open.id));
+        assert_eq!(
+            (parts[0].quantity, parts[1].quantity),
+            (first, both - first)
+        );
+        assert!(parts
+            .iter()
+            .all(|part| part.status == TradeStatus::Closed && part.exit_price.is_some()));
+    }
+
+    #[test]
+    fn test_plan_account_operations_batches_buys_for_entry_weekday() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        let (candles, dates) = generate_weekday_candles("WEEK", 15, 100.0);
+        // Signal on Wednesday 2024-01-10; the next entry tick is Monday 2024-01-15.
+        let signal_date = dates[7];
+        let signals = vec![GeneratedSignal {
+            date: signal_date,
+            ticker: "WEEK".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(0.9),
+        }];
+        let state = sample_account_state(50_000.0);
+        let plan_for = |engine: &Engine, target_date: DateTime<Utc>| {
+            engine.plan_account_operations(
+                "strategy",
+                "acct",
+                &signals,
+                &candles,
+                target_date,
+                &state,
+                &HashSet::new(),
+                &[],
+                0,
+                &HashMap::new(),
+            )
+        };
+
+        engine.config.entry_weekday = Some(Weekday::Mon);
+        let waiting = plan_for(&engine, signal_date);
+        assert!(waiting.operations.is_empty());
+        assert!(waiting
+            .skipped_signals
+            .iter()
+            .any(|skip| skip.ticker == "WEEK" && skip.reason == "awaiting_entry_tick"));
+
+        let friday = dates[9];
+        assert_eq!(
+            engine.planning_signal_window_start(friday),
+            friday - Duration::days(6)
+        );
+        let batched = plan_for(&engine, friday);
+        assert_eq!(batched.operations.len(), 1);
+        assert_eq!(batched.operations[0].ticker, "WEEK");
+        assert_eq!(
+            batched.operations[0].operation_type,
+            AccountOperationType::OpenPosition
+        );
+    }
+
+    #[test]
+    fn run_loop_circuit_breaker_liquidates_and_freezes_entries() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        engine.config.stop_loss.ratio = 0.9;
+        engine.config.circuit_breaker = CircuitBreakerConfig {
+            max_daily_loss: 0.005,
+            freeze_days: 3,
+            liquidate: true,
+        };
+        let ramp = |ticker: &str| {
+            let (mut candles, dates) = generate_weekday_candles(ticker, 16, 50.0);
+            for (offset, candle) in candles.iter_mut().enumerate() {
+                candle.open = 50.0 + offset as f64;
+                candle.close = candle.open + 0.5;
+                candle.high = candle.close + 1.0;
+                candle.low = candle.open - 1.0;
+            }
+            (candles, dates)
+        };
+        let (mut crash, dates) = ramp("CRASH");
+        // Monday 2024-01-15 halves the held position, freezing entries through Wednesday.
+        crash[10].open = 30.0;
+        crash[10].high = 30.5;
+        crash[10].low = 29.5;
+        crash[10].close = 30.0;
+        let (later, _) = ramp("LATER");
+        let mut candles_by_ticker = HashMap::new();
+        candles_by_ticker.insert("CRASH".to_string(), crash.iter().collect::<Vec<_>>());
+        candles_by_ticker.insert("LATER".to_string(), later.iter().collect::<Vec<_>>());
+        let tickers = vec!["CRASH".to_string(), "LATER".to_string()];
+
+        let result = engine.run_backtest_loop(
+            &tickers,
+            &dates,
+            &candles_by_ticker,
+            0,
+            0,
+            |ticker, _, date, _| {
+                let buy = match ticker.as_str() {
+                    "CRASH" => date == dates[7],
+                    _ => date == dates[11] || date == dates[13],
+                };
+                buy.then_some(SignalDecision {
+                    action: SignalAction::Buy,
+                    confidence: 0.9,
+                })
+            },
+            None,
+            true,
+        );
+
+        assert_eq!(result.closed_trades.len(), 1);
+        assert_eq!(result.closed_trades[0].ticker, "CRASH");
+        assert_eq!(result.closed_trades[0].exit_date, Some(dates[10]));
+        assert!(result.signal_skips.iter().any(|skip| skip.ticker == "LATER"
+            && skip.signal_date == dates[11]
+            && skip.reason == CIRCUIT_BREAKER_REASON));
+        assert_eq!(result.active_trades.len(), 1);
+        assert_eq!(result.active_trades[0].ticker, "LATER");
+        assert_eq!(result.active_trades[0].date, dates[14]);
+    }
+
+    #[test]
+    fn test_plan_account_operations_circuit_breaker_liquidates_then_freezes() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        engine.config.circuit_breaker = CircuitBreakerConfig {
+            max_daily_loss: 0.05,
+            freeze_days: 3,
+            liquidate: true,
+        };
+        let (mut candles, dates) = generate_weekday_candles("NEW", 5, 100.0);
+        let (held_candles, _) = generate_weekday_candles("HELD", 5, 100.0);
+        candles.extend(held_candles);
+        let target_date = dates[4];
+        let signals = vec![GeneratedSignal {
+            date: target_date,
+            ticker: "NEW".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(0.9),
+        }];
+        let trade = sample_active_trade("held", "strategy", "HELD", 10, 100.0, dates[0], None);
+        let mut state = sample_account_state_with_holdings(50_000.0, &[("HELD", 10, 100.0)], None);
+        state.last_equity = Some(100_000.0);
+        state.equity = Some(90_000.0);
+        let plan_for = |state: &AccountStateSnapshot| {
+            engine.plan_account_operations(
+                "strategy",
+                "acct",
+                &signals,
+                &candles,
+                target_date,
+                state,
+                &HashSet::new(),
+                std::slice::from_ref(&trade),
+                0,
+                &HashMap::new(),
+            )
+        };
+
+        let tripped = plan_for(&state);
+        assert!(tripped
+            .notes
+            .iter()
+            .any(|note| note == CIRCUIT_BREAKER_REASON));
+        assert_eq!(tripped.operations.len(), 1);
+        assert_eq!(
+            tripped.operations[0].operation_type,
+            AccountOperationType::ClosePosition
+        );
+        assert_eq!(
+            tripped.operations[0].reason.as_deref(),
+            Some(CIRCUIT_BREAKER_REASON)
+        );
+        assert!(tripped
+            .skipped_signals
+            .iter()
+            .any(|skip| skip.ticker == "NEW" && skip.reason == CIRCUIT_BREAKER_REASON));
+
+        state.equity = state.last_equity;
+        state.circuit_breaker_triggered_at = Some(target_date - Duration::days(2));
+        let frozen = plan_for(&state);
+        assert!(frozen
+            .operations
+            .iter()
+            .all(|op| op.operation_type != AccountOperationType::OpenPosition));
+        assert!(frozen
+            .skipped_signals
+            .iter()
+            .any(|skip| skip.reason == CIRCUIT_BREAKER_REASON));
+
+        state.circuit_breaker_triggered_at = Some(target_date - Duration::days(3));
+        let resumed = plan_for(&state);
+        assert!(resumed.operations.iter().any(|op| op.operation_type
+            == AccountOperationType::OpenPosition
+            && op.ticker == "NEW"));
+    }
+
+    #[test]
+    fn close_fee_nets_distributions_with_ex_date_inside_holding_period() {
+        let mut engine = Engine::new(test_runtime_settings());
+        let entry_date = create_date(0);
+        let exit_date = create_date(30);
+        let baseline_fee =
+            engine.calculate_trade_close_fee("ETF", 100, 50.0, entry_date, exit_date);
+
+        let mut distributions = TickerDistributionMap::new();
+        distributions.insert(
+            "ETF".to_string(),
+            vec![
+                TickerDistribution {
+                    ex_date: entry_date,
+                    amount: 1.0,
+                },
+                TickerDistribution {
+                    ex_date: create_date(10),
+                    amount: 0.25,
+                },
+                TickerDistribution {
+                    ex_date: exit_date,
+                    amount: 0.5,
+                },
+                TickerDistribution {
+                    ex_date: create_date(31),
+                    amount: 2.0,
+                },
+            ],
+        );
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_distributions: distributions,
+            ..AuxiliaryData::default()
+        }));
+
+        let long_fee = engine.calculate_trade_close_fee("ETF", 100, 50.0, entry_date, exit_date);
+        assert!((baseline_fee - long_fee - 75.0).abs() < 1e-9);
+
+        let short_fee = engine.calculate_trade_close_fee("ETF", -100, 50.0, entry_date, exit_date);
+        let short_baseline = Engine::new(test_runtime_settings())
+            .calculate_trade_close_fee("ETF", -100, 50.0, entry_date, exit_date);
+        assert!((short_fee - short_baseline - 75.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn distributions_move_cash_on_the_ex_date() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_distributions: TickerDistributionMap::from([(
+                "ETF".to_string(),
+                vec![TickerDistribution {
+                    ex_date: create_date(2),
+                    amount: 0.5,
+                }],
+            )]),
+            ..AuxiliaryData::default()
+        }));
+        let trades = vec![
+            sample_active_trade("long", "strategy", "ETF", 100, 50.0, create_date(0), None),
+            sample_active_trade("short", "strategy", "ETF", -40, 50.0, create_date(0), None),
+        ];
+
+        let mut cash = 0.0;
+        let mut cash_by_day = Vec::new();
+        for day in 1..=3 {
+            engine.pay_distributions(
+                &trades,
+                &mut cash,
+                Some(create_date(day - 1)),
+                create_date(day),
+            );
+            cash_by_day.push(cash);
+        }
+        assert_eq!(cash_by_day, vec![0.0, 30.0, 30.0]);
+
+        let mut closed = trades[1].clone();
+        closed.exit_date = Some(create_date(3));
+        assert!((engine.paid_distributions(&closed) + 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn shorts_accrue_reported_borrow_fees_and_skip_expensive_borrows() {
+        let mut engine = Engine::new(test_runtime_settings());
+        let entry_date = create_date(0);
+        let exit_date = create_date(20);
+        let baseline_fee =
+            engine.calculate_trade_close_fee("HTB", -100, 50.0, entry_date, exit_date);
+        let report = |day, borrow_fee_rate| ShortInterestPoint {
+            date: create_date(day),
+            short_interest_ratio: Some(0.3),
+            borrow_fee_rate: Some(borrow_fee_rate),
+        };
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_short_interest: HashMap::from([("HTB".to_string(), vec![report(10, 0.5)])]),
+            ..AuxiliaryData::default()
+        }));
+
+        // The default rate applies until the first report, then the reported one.
+        let fee = engine.calculate_trade_close_fee("HTB", -100, 50.0, entry_date, exit_date);
+        let extra = 5_000.0 * (0.5 - 0.003) * (10.0 * 86_400.0 / SECONDS_PER_YEAR);
+        assert!((fee - baseline_fee - extra).abs() < 1e-9);
+
+        engine.config.allow_short_selling = true;
+        engine.config.max_borrow_fee_rate = 0.2;
+        let (candles, _) = generate_candles("HTB", vec![50.0; 12]);
+        let refs: Vec<&Candle> = candles.iter().collect();
+        let mut active_trades = Vec::new();
+        let mut cash = engine.config.initial_capital;
+        let mut short_on = |index: usize| {
+            engine.execute_short_entry(
+                &mut active_trades,
+                &mut cash,
+                "HTB",
+                refs[index],
+                refs.get(index + 1).copied(),
+                &refs,
+                index,
+                1.0,
+            )
+        };
+        assert!(matches!(
+            short_on(10),
+            EntrySignalOutcome::Skipped {
+                reason: BORROW_FEE_REASON,
+                ..
+            }
+        ));
+        assert!(!matches!(short_on(5), EntrySignalOutcome::Skipped { .. }));
+    }
+
+    #[test]
+    fn blackouts_refuse_short_entries() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.allow_short_selling = true;
+        let (candles, _) = generate_candles("XYZ", vec![50.0; 12]);
+        let refs: Vec<&Candle> = candles.iter().collect();
+        let day = refs[5].date.date_naive();
+        let config = BlackoutConfig {
+            ranges: vec![BlackoutRange {
+                start: day,
+                end: day,
+                label: None,
+            }],
+            ..BlackoutConfig::default()
+        };
+        engine.set_blackout(BlackoutCalendar::build(&config, &AuxiliaryData::default()));
+        let mut active_trades = Vec::new();
+        let mut cash = engine.config.initial_capital;
+        let mut short_on = |index: usize| {
+            engine.execute_short_entry(
+                &mut active_trades,
+                &mut cash,
+                "XYZ",
+                refs[index],
+                refs.get(index + 1).copied(),
+                &refs,
+                index,
+                1.0,
+            )
+        };
+        assert!(matches!(
+            short_on(5),
+            EntrySignalOutcome::Skipped {
+                reason: BLACKOUT_SKIP_REASON,
+                ..
+            }
+        ));
+        assert!(!matches!(short_on(7), EntrySignalOutcome::Skipped { .. }));
+    }
+
+    fn with_spy_reference(candles: &[Candle]) -> Vec<Candle> {
+        let mut combined = candles.to_vec();
+        combined.extend(generate_spy_candles(candles.len()));
+        combined
+    }
+
+    #[test]
+    fn test_resolve_trading_start_index_rounds_down_within_same_day() {
+        let day_zero = create_date(0);
+        let day_one = create_date(1);
+        let unique_dates = vec![day_zero, day_one];
+        let intraday_start = day_zero + Duration::hours(15);
+
+        assert_eq!(
+            Engine::resolve_trading_start_index(&unique_dates, intraday_start),
+            0
+        );
+    }
+
+    #[test]
+    fn test_resolve_trading_start_index_clamps_to_last_available_day() {
+        let day_zero = create_date(0);
+        let day_one = create_date(1);
+        let unique_dates = vec![day_zero, day_one];
+        let future_start = day_one + Duration::days(5);
+
+        assert_eq!(
+            Engine::resolve_trading_start_index(&unique_dates, future_start),
+            1
+        );
+    }
+
+    struct MockStrategy {
+        signals: HashMap<(String, DateTime<Utc>), StrategySignal>,
+    }
+
+    impl Strategy for MockStrategy {
+        fn generate_signal(
+            &self,
+            ticker: &str,
+            candles: &[Candle],
+            index: usize,
+        ) -> StrategySignal {
+            let current_date = candles[index].date;
+            self.signals
+                .get(&(ticker.to_string(), current_date))
+                .cloned()
+                .unwrap_or(StrategySignal {
+                    action: SignalAction::Hold,
+                    confidence: 0.0,
+                })
+        }
+
+        fn get_min_data_points(&self) -> usize {
+            0
+        }
+
+        fn get_template_id(&self) -> &str {
+            "mock_strategy"
+        }
+    }
+
+    #[test]
+    fn cancelled_backtest_returns_cancelled_error() {
+        let mut engine = Engine::new(test_runtime_settings());
+        let token = CancellationToken::new();
+        engine.set_cancellation(token.clone());
+        token.cancel();
+        let ticker = "CONST".to_string();
+        let (candles, unique_dates, _) =
+            generate_candles_with_history(&ticker, vec![100.0, 100.0, 100.0, 100.0]);
+        let all_candles = with_spy_reference(&candles);
+        let strategy = MockStrategy {
+            signals: HashMap::new(),
+        };
+
+        let err = engine
+            .backtest(
+                Some(&strategy),
+                strategy.get_template_id(),
+                &[ticker, "SPY".to_string()],
+                &all_candles,
+                &unique_dates,
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
+        assert!(crate::cancellation::is_cancellation(&err));
+    }
+
+    #[test]
+    fn tickers_below_min_history_never_signal_and_are_reported() {
+        let (mut candles, dates) = generate_candles("OLD", vec![10.0; 10]);
+        let (mut young, _) = generate_candles("NEW", vec![20.0; 10]);
+        candles.extend(young.split_off(3));
+        let (mut tiny, _) = generate_candles("TINY", vec![30.0; 10]);
+        candles.extend(tiny.split_off(8));
+        let buy = StrategySignal {
+            action: SignalAction::Buy,
+            confidence: 1.0,
+        };
+        let strategy = MockStrategy {
+            signals: HashMap::from([
+                (("OLD".to_string(), dates[7]), buy.clone()),
+                (("NEW".to_string(), dates[8]), buy),
+            ]),
+        };
+        let tickers = ["NEW".to_string(), "OLD".to_string(), "TINY".to_string()];
+        let run_with = |min_history| {
+            let mut runtime_settings = test_runtime_settings();
+            runtime_settings.min_ticker_history_candles = min_history;
+            Engine::new(runtime_settings)
+                .backtest(
+                    Some(&strategy),
+                    strategy.get_template_id(),
+                    &tickers,
+                    &candles,
+                    &dates,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap()
+        };
+
+        let unfiltered = run_with(0);
+        assert_eq!(unfiltered.result.trades.len(), 2);
+        assert!(unfiltered.result.insufficient_history_tickers.is_empty());
+
+        // NEW has 6 candles on its signal date and TINY never reaches 7.
+        let run = run_with(7);
+        assert_eq!(run.result.trades.len(), 1);
+        assert_eq!(run.result.trades[0].ticker, "OLD");
+        assert_eq!(run.result.insufficient_history_tickers, vec!["TINY"]);
+    }
+
+    #[test]
+    fn warmup_policy_governs_start_dates_inside_the_warmup() {
+        struct WarmupStrategy;
+        impl Strategy for WarmupStrategy {
+            fn generate_signal(&self, _: &str, _: &[Candle], _: usize) -> StrategySignal {
+                StrategySignal {
+                    action: SignalAction::Hold,
+                    confidence: 0.0,
+                }
+            }
+            fn get_min_data_points(&self) -> usize {
+                3
+            }
+            fn get_template_id(&self) -> &str {
+                "warmup_strategy"
+            }
+        }
+
+        let (mut candles, dates) = generate_candles("LONG", vec![10.0; 6]);
+        let (mut late, _) = generate_candles("LATE", vec![20.0; 6]);
+        candles.extend(late.split_off(4));
+        let both = ["LATE".to_string(), "LONG".to_string()];
+        let run_with = |policy, tickers: &[String], start| {
+            let mut runtime_settings = test_runtime_settings();
+            runtime_settings.warmup_policy = policy;
+            Engine::new(runtime_settings).backtest(
+                Some(&WarmupStrategy),
+                "warmup_strategy",
+                tickers,
+                &candles,
+                &dates,
+                None,
+                start,
+                None,
+            )
+        };
+
+        let skip = run_with(WarmupPolicy::Skip, &both, Some(dates[1])).unwrap();
+        assert_eq!(skip.result.start_date, dates[1]);
+        assert_eq!(
+            skip.warmup,
+            vec![
+                TickerWarmup {
+                    ticker: "LATE".to_string(),
+                    warmup_dates: 2,
+                    ready_date: None,
+                },
+                TickerWarmup {
+                    ticker: "LONG".to_string(),
+                    warmup_dates: 3,
+                    ready_date: Some(dates[3]),
+                },
+            ]
+        );
+
+        let strict = run_with(WarmupPolicy::Strict, &both, Some(dates[1])).unwrap();
+        assert_eq!(strict.result.start_date, dates[3]);
+
+        let long = ["LONG".to_string()];
+        assert!(run_with(WarmupPolicy::Error, &both, None).is_err());
+        assert!(run_with(WarmupPolicy::Error, &long, Some(dates[1])).is_err());
+        assert!(run_with(WarmupPolicy::Error, &long, Some(dates[4])).is_ok());
+    }
+
+    #[test]
+    fn test_backtest_constant_price() {
+        let engine = Engine::new(test_runtime_settings());
+        let ticker = "CONST".to_string();
+        let spy = "SPY".to_string();
+        let (candles, unique_dates, history_offset) =
+            generate_candles_with_history(&ticker, vec![100.0, 100.0, 100.0, 100.0]);
+        let all_candles = with_spy_reference(&candles);
+
+        let mut signals = HashMap::new();
+        signals.insert(
+            (ticker.clone(), unique_dates[history_offset]),
             StrategySignal {
                 action: SignalAction::Buy,
                 confidence: 1.0,
@@ -2771,7 +5119,7 @@ mod tests {
         let entry_index = signal_index + 1;
         let make_candles = |volumes: Vec<i64>| -> Vec<Candle> {
             assert!(
-                volumes.len() >= entry_index + 1,
+                volumes.len() > entry_index,
                 "need at least signal and entry candles"
             );
             volumes
@@ -3070,6 +5418,11 @@ mod tests {
             open_sell_orders: HashSet::new(),
             positions: Vec::new(),
             stop_orders: HashMap::new(),
+            equity: None,
+            last_equity: None,
+            circuit_breaker_triggered_at: None,
+            risk_overrides: Default::default(),
+            fx: Default::default(),
         }
     }
 
@@ -3115,6 +5468,11 @@ mod tests {
             open_sell_orders: HashSet::new(),
             positions,
             stop_orders,
+            equity: None,
+            last_equity: None,
+            circuit_breaker_triggered_at: None,
+            risk_overrides: Default::default(),
+            fx: Default::default(),
         }
     }
 
@@ -3145,6 +5503,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         }
     }
@@ -3186,6 +5545,12 @@ mod tests {
         assert_eq!(buy.triggered_at, signal_date);
         assert_eq!(buy.price, Some(candles[history_offset + 1].close));
         assert_eq!(buy.order_type.as_deref(), Some("market"));
+
+        let sizing = plan.buy_sizing.first().expect("expected sizing trace");
+        assert_eq!(sizing.ticker, "BUY");
+        assert_eq!(Some(sizing.quantity), buy.quantity);
+        assert_eq!(sizing.available_cash, 50_000.0);
+        assert_eq!(sizing.stop_loss, buy.stop_loss);
     }
 
     #[test]
@@ -3262,6 +5627,141 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_plan_account_operations_applies_account_risk_overrides() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        engine.config.stop_loss.mode = 0;
+        engine.config.stop_loss.ratio = 0.05;
+        engine.config.trade_size_ratio = 0.5;
+
+        let (candles, dates, history_offset) =
+            generate_candles_with_history("RISK", vec![100.0, 110.0]);
+        let signal_date = dates[history_offset + 1];
+        let signals = vec![GeneratedSignal {
+            date: signal_date,
+            ticker: "RISK".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        }];
+        let plan_with = |max_stop_ratio: Option<f64>,
+                         max_risk_per_trade: Option<f64>,
+                         take_profit_r_multiple: Option<f64>| {
+            let mut state = sample_account_state(50_000.0);
+            state.risk_overrides = AccountRiskOverrides::new(
+                max_stop_ratio,
+                max_risk_per_trade,
+                take_profit_r_multiple,
+            );
+            engine.plan_account_operations(
+                "strategy",
+                "acct",
+                &signals,
+                &candles,
+                signal_date,
+                &state,
+                &HashSet::new(),
+                &[],
+                0,
+                &HashMap::new(),
+            )
+        };
+        let open = |plan: &PlannedOperations| {
+            plan.operations
+                .iter()
+                .find(|op| op.operation_type == AccountOperationType::OpenPosition)
+                .cloned()
+        };
+
+        let price = candles[history_offset + 1].close;
+        let strategy_only = open(&plan_with(None, None, None)).expect("expected buy op");
+        let strategy_stop = strategy_only.stop_loss.unwrap();
+        assert!((strategy_stop - price * 0.95).abs() < 1e-6);
+        assert!(strategy_only.quantity.unwrap() > 10);
+        assert_eq!(strategy_only.take_profit, None);
+
+        // The account's tighter stop wins; a looser account limit leaves the strategy stop.
+        let tighter = open(&plan_with(Some(0.02), None, None)).unwrap();
+        assert!((tighter.stop_loss.unwrap() - price * 0.98).abs() < 1e-6);
+        assert_eq!(tighter.quantity, strategy_only.quantity);
+        let looser = open(&plan_with(Some(0.10), None, None)).unwrap();
+        assert_eq!(looser.stop_loss, Some(strategy_stop));
+
+        // The dollar risk limit caps the quantity at the stop that actually applies.
+        let capped = open(&plan_with(None, Some(price * 0.05 * 10.5), None)).unwrap();
+        assert_eq!(capped.quantity, Some(10));
+        let capped_tighter = open(&plan_with(Some(0.02), Some(price * 0.05 * 10.5), None)).unwrap();
+        assert_eq!(capped_tighter.quantity, Some(26));
+
+        let blocked = plan_with(None, Some(1.0), None);
+        assert!(open(&blocked).is_none());
+        assert!(blocked
+            .skipped_signals
+            .iter()
+            .any(|skip| skip.reason == "account_risk_limit"));
+
+        // The take-profit target sits twice the stop risk above the entry.
+        let targeted = open(&plan_with(Some(0.02), None, Some(2.0))).unwrap();
+        assert!((targeted.take_profit.unwrap() - price * 1.04).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_plan_account_operations_sizes_in_the_ticker_currency() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.buy_discount_ratio = 0.0;
+        engine.config.trade_size_ratio = 0.5;
+
+        let (candles, dates, history_offset) =
+            generate_candles_with_history("FXUS", vec![100.0, 110.0]);
+        let signal_date = dates[history_offset + 1];
+        let signals = vec![GeneratedSignal {
+            date: signal_date,
+            ticker: "FXUS".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        }];
+        let plan_with = |fx: AccountFx| {
+            let mut state = sample_account_state(50_000.0);
+            state.fx = fx;
+            engine.plan_account_operations(
+                "strategy",
+                "acct",
+                &signals,
+                &candles,
+                signal_date,
+                &state,
+                &HashSet::new(),
+                &[],
+                0,
+                &HashMap::new(),
+            )
+        };
+        let open_quantity = |plan: &PlannedOperations| {
+            plan.operations
+                .iter()
+                .find(|op| op.operation_type == AccountOperationType::OpenPosition)
+                .and_then(|op| op.quantity)
+        };
+
+        let usd_quantity = open_quantity(&plan_with(AccountFx::default())).unwrap();
+        let mut rates = FxRates::default();
+        rates.insert("EUR", "USD", signal_date.date_naive(), 1.2);
+        let eur_plan = plan_with(AccountFx::new(Some("EUR"), rates, HashMap::new()));
+        let eur_quantity = open_quantity(&eur_plan).unwrap();
+        assert!((eur_quantity as f64 / usd_quantity as f64 - 1.2).abs() < 0.02);
+
+        let unpriced = plan_with(AccountFx::new(
+            Some("EUR"),
+            FxRates::default(),
+            HashMap::new(),
+        ));
+        assert_eq!(open_quantity(&unpriced), None);
+        assert!(unpriced
+            .skipped_signals
+            .iter()
+            .any(|skip| skip.reason == "fx_rate_unavailable"));
+    }
+
     #[test]
     fn test_plan_account_operations_skips_when_price_out_of_range() {
         let engine = Engine::new(test_runtime_settings());
@@ -3298,6 +5798,45 @@ mod tests {
             .any(|note| note == "signal_XRNG_price_out_of_range"));
     }
 
+    #[test]
+    fn test_plan_account_operations_skips_tickers_with_short_history() {
+        let mut runtime_settings = test_runtime_settings();
+        runtime_settings.min_ticker_history_candles = 250;
+        let engine = Engine::new(runtime_settings);
+        let (candles, dates, history_offset) = generate_candles_with_history("IPO", vec![20.0]);
+        let signal_date = dates[history_offset];
+        let signals = vec![GeneratedSignal {
+            date: signal_date,
+            ticker: "IPO".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        }];
+
+        let plan = engine.plan_account_operations(
+            "strategy",
+            "acct",
+            &signals,
+            &candles,
+            signal_date,
+            &sample_account_state(100_000.0),
+            &HashSet::new(),
+            &[],
+            0,
+            &HashMap::new(),
+        );
+
+        assert!(plan.operations.is_empty());
+        assert!(plan
+            .notes
+            .iter()
+            .any(|note| note == "signal_IPO_insufficient_history"));
+        assert_eq!(plan.skipped_signals[0].reason, INSUFFICIENT_HISTORY_REASON);
+        assert_eq!(
+            plan.skipped_signals[0].details.as_deref(),
+            Some(format!("{} candles, need 250", candles.len()).as_str())
+        );
+    }
+
     #[test]
     fn test_plan_account_operations_skips_when_volume_insufficient() {
         let mut engine = Engine::new(test_runtime_settings());
@@ -3694,6 +6233,7 @@ mod tests {
             op.stop_loss.unwrap() > 90.0,
             "trailing stop should have increased"
         );
+        assert_eq!(op.replaces_order_id.as_deref(), Some("alpaca-stop"));
     }
 
     #[test]
@@ -3734,22 +6274,137 @@ mod tests {
             .expect("expected stop repair operation");
         assert_eq!(op.reason.as_deref(), Some("stop_missing"));
         assert_eq!(op.stop_loss, Some(90.0));
+        assert_eq!(op.replaces_order_id, None);
+    }
+
+    #[test]
+    fn test_plan_account_operations_uses_existing_trade_history() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.max_holding_days = 1;
+
+        let (candles, dates) = generate_candles("OLD", vec![100.0, 101.0, 102.0]);
+        let signals = Vec::<GeneratedSignal>::new();
+        let state = sample_account_state_with_holdings(0.0, &[("OLD", 5, 95.0)], Some(90.0));
+
+        let existing_trade = Trade {
+            id: "existing-trade".to_string(),
+            strategy_id: "strategy".to_string(),
+            ticker: "OLD".to_string(),
+            quantity: 5,
+            price: 95.0,
+            date: dates[0],
+            status: TradeStatus::Active,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: Some(90.0),
+            stop_loss_triggered: Some(false),
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+
+        let plan = engine.plan_account_operations(
+            "strategy",
+            "acct",
+            &signals,
+            &candles,
+            dates[2],
+            &state,
+            &HashSet::new(),
+            std::slice::from_ref(&existing_trade),
+            0,
+            &HashMap::new(),
+        );
+
+        let close = plan
+            .operations
+            .iter()
+            .find(|op| op.operation_type == AccountOperationType::ClosePosition)
+            .expect("expected close operation");
+        assert_eq!(close.reason.as_deref(), Some("max_holding_days"));
+        assert_eq!(close.trade_id, existing_trade.id);
+        assert_eq!(close.days_held, Some(2));
+    }
+
+    #[test]
+    fn test_plan_account_operations_shortens_holding_period_in_high_volatility() {
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.max_holding_days = 10;
+        engine.config.position_sizing.vol_lookback = 3;
+        assert_eq!(engine.config.max_holding_days_at(2.0), 10);
+
+        let (candles, dates) = generate_candles("VOL", vec![100.0, 125.0, 95.0]);
+        let state = sample_account_state_with_holdings(0.0, &[("VOL", 5, 100.0)], Some(50.0));
+        let trade = Trade {
+            id: "vol-trade".to_string(),
+            strategy_id: "strategy".to_string(),
+            ticker: "VOL".to_string(),
+            quantity: 5,
+            price: 100.0,
+            date: dates[0],
+            status: TradeStatus::Active,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: Some(50.0),
+            stop_loss_triggered: Some(false),
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+        let plan_close = |engine: &Engine| {
+            engine
+                .plan_account_operations(
+                    "strategy",
+                    "acct",
+                    &[],
+                    &candles,
+                    dates[2],
+                    &state,
+                    &HashSet::new(),
+                    std::slice::from_ref(&trade),
+                    0,
+                    &HashMap::new(),
+                )
+                .operations
+                .into_iter()
+                .find(|op| op.operation_type == AccountOperationType::ClosePosition)
+        };
+        assert!(plan_close(&engine).is_none());
+
+        engine.config.holding_vol_target = 0.4;
+        assert_eq!(engine.config.max_holding_days_at(0.3), 10);
+        assert_eq!(engine.config.max_holding_days_at(0.8), 5);
+        assert_eq!(engine.config.max_holding_days_at(100.0), 1);
+        let close = plan_close(&engine).expect("expected volatility-shortened close");
+        assert_eq!(close.reason.as_deref(), Some("max_holding_days"));
+        assert_eq!(close.days_held, Some(2));
     }
 
     #[test]
-    fn test_plan_account_operations_uses_existing_trade_history() {
+    fn test_plan_account_operations_plans_partial_time_decay_close() {
         let mut engine = Engine::new(test_runtime_settings());
-        engine.config.max_holding_days = 1;
-
-        let (candles, dates) = generate_candles("OLD", vec![100.0, 101.0, 102.0]);
-        let signals = Vec::<GeneratedSignal>::new();
-        let state = sample_account_state_with_holdings(0.0, &[("OLD", 5, 95.0)], Some(90.0));
+        engine.config.scale_out = ScaleOutConfig {
+            days: [1, 2, 3],
+            fraction: 0.3,
+        };
 
-        let existing_trade = Trade {
-            id: "existing-trade".to_string(),
+        let (candles, dates) = generate_candles("DECAY", vec![100.0, 101.0, 102.0]);
+        let state = sample_account_state_with_holdings(0.0, &[("DECAY", 7, 95.0)], Some(90.0));
+        let mut trade = Trade {
+            id: "decay-trade".to_string(),
             strategy_id: "strategy".to_string(),
-            ticker: "OLD".to_string(),
-            quantity: 5,
+            ticker: "DECAY".to_string(),
+            quantity: 10,
             price: 95.0,
             date: dates[0],
             status: TradeStatus::Active,
@@ -3763,18 +6418,21 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
+        // The day-1 step already sold 3 of the original 10 shares.
+        trade.set_quantity(7, dates[1]);
 
         let plan = engine.plan_account_operations(
             "strategy",
             "acct",
-            &signals,
+            &[],
             &candles,
             dates[2],
             &state,
             &HashSet::new(),
-            &[existing_trade.clone()],
+            std::slice::from_ref(&trade),
             0,
             &HashMap::new(),
         );
@@ -3783,9 +6441,9 @@ mod tests {
             .operations
             .iter()
             .find(|op| op.operation_type == AccountOperationType::ClosePosition)
-            .expect("expected close operation");
-        assert_eq!(close.reason.as_deref(), Some("max_holding_days"));
-        assert_eq!(close.trade_id, existing_trade.id);
+            .expect("expected partial close operation");
+        assert_eq!(close.reason.as_deref(), Some("time_decay_exit"));
+        assert_eq!(close.quantity, Some(3));
         assert_eq!(close.days_held, Some(2));
     }
 
@@ -3956,6 +6614,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         }];
         let mut closed_trades = Vec::new();
@@ -3998,7 +6657,7 @@ mod tests {
             .minimum_dollar_volume_lookback
             .saturating_sub(1);
         let mut definitions: Vec<(f64, f64, f64, f64)> = Vec::new();
-        definitions.extend(std::iter::repeat(base_definitions[0]).take(history_offset));
+        definitions.extend(std::iter::repeat_n(base_definitions[0], history_offset));
         definitions.extend(base_definitions);
 
         for (i, (open, high, low, close)) in definitions.iter().enumerate() {
@@ -4030,7 +6689,7 @@ mod tests {
             .backtest(
                 Some(&strategy),
                 strategy.get_template_id(),
-                &[ticker.clone()],
+                std::slice::from_ref(&ticker),
                 &candles,
                 &unique_dates,
                 None,
@@ -4084,6 +6743,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         }];
         let mut closed_trades = Vec::new();
@@ -4131,6 +6791,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
 
@@ -4165,6 +6826,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
 
@@ -4199,6 +6861,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
 
@@ -4233,6 +6896,7 @@ mod tests {
             entry_cancel_after: None,
             stop_order_id: None,
             exit_order_id: None,
+            target_order_id: None,
             changes: Vec::new(),
         };
 
@@ -4240,4 +6904,633 @@ mod tests {
             .validate_trades(&[trade], &candles_by_ticker, unique_dates[1])
             .is_err());
     }
+
+    #[test]
+    fn partial_fills_cap_entries_by_volume_and_carry_or_cancel_the_rest() {
+        let ticker = "THIN".to_string();
+        let (mut candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 6]);
+        for candle in &mut candles {
+            candle.volume_shares = 20_000;
+        }
+        let signals = vec![GeneratedSignal {
+            date: dates[history],
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        }];
+        let fills = |remainder| {
+            let mut settings = test_runtime_settings();
+            settings.partial_fill_max_volume_share = 0.004;
+            settings.partial_fill_remainder = remainder;
+            let run = Engine::new(settings)
+                .backtest(
+                    None,
+                    "test",
+                    std::slice::from_ref(&ticker),
+                    &candles,
+                    &dates,
+                    Some(&signals),
+                    None,
+                    None,
+                )
+                .unwrap();
+            run.result
+                .trades
+                .iter()
+                .map(|trade| (trade.date, trade.quantity))
+                .collect::<Vec<_>>()
+        };
+
+        // 2% of 100k at 10 is 200 shares; each candle fills at most 0.4% of 20k = 80.
+        assert_eq!(
+            fills(PartialFillRemainder::Cancel),
+            vec![(dates[history + 1], 80)]
+        );
+        assert_eq!(
+            fills(PartialFillRemainder::Carry),
+            vec![
+                (dates[history + 1], 80),
+                (dates[history + 2], 80),
+                (dates[history + 3], 40)
+            ]
+        );
+    }
+
+    #[test]
+    fn pre_earnings_rule_closes_positions_and_refuses_entries_near_earnings() {
+        let ticker = "ERN".to_string();
+        let (candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 6]);
+        let buy = |date| GeneratedSignal {
+            date,
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        };
+        let signals = vec![buy(dates[history]), buy(dates[history + 2])];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.earnings_exit_days = 1;
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_earnings: HashMap::from([(ticker.clone(), vec![dates[history + 3]])]),
+            ..AuxiliaryData::default()
+        }));
+
+        let run = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        let trades: Vec<_> = run
+            .result
+            .trades
+            .iter()
+            .map(|trade| (trade.date, trade.exit_date))
+            .collect();
+        assert_eq!(trades, vec![(dates[history + 1], Some(dates[history + 2]))]);
+        assert_eq!(run.signal_skips.len(), 1);
+        assert_eq!(run.signal_skips[0].reason, EARNINGS_EXIT_REASON);
+        assert_eq!(run.signal_skips[0].signal_date, dates[history + 2]);
+
+        let (mut candles, dates) = generate_candles("OLD", vec![100.0, 101.0, 102.0]);
+        candles.extend(generate_candles("NEW", vec![50.0, 51.0, 52.0]).0);
+        let state = sample_account_state_with_holdings(10_000.0, &[("OLD", 5, 95.0)], Some(90.0));
+        let existing_trade = sample_active_trade(
+            "existing-trade",
+            "strategy",
+            "OLD",
+            5,
+            95.0,
+            dates[0],
+            Some(90.0),
+        );
+        let signals = vec![GeneratedSignal {
+            date: dates[2],
+            ticker: "NEW".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        }];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.earnings_exit_days = 2;
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_earnings: HashMap::from([
+                ("OLD".to_string(), vec![dates[2] + Duration::days(2)]),
+                ("NEW".to_string(), vec![dates[2] + Duration::days(3)]),
+            ]),
+            ..AuxiliaryData::default()
+        }));
+
+        let plan = engine.plan_account_operations(
+            "strategy",
+            "acct",
+            &signals,
+            &candles,
+            dates[2],
+            &state,
+            &HashSet::new(),
+            std::slice::from_ref(&existing_trade),
+            0,
+            &HashMap::new(),
+        );
+        let close = plan
+            .operations
+            .iter()
+            .find(|op| op.operation_type == AccountOperationType::ClosePosition)
+            .expect("expected pre-earnings close");
+        assert_eq!(close.reason.as_deref(), Some(EARNINGS_EXIT_REASON));
+        assert!(!plan
+            .operations
+            .iter()
+            .any(|op| op.operation_type == AccountOperationType::OpenPosition));
+        assert_eq!(plan.skipped_signals.len(), 1);
+        assert_eq!(plan.skipped_signals[0].ticker, "NEW");
+        assert_eq!(plan.skipped_signals[0].reason, EARNINGS_EXIT_REASON);
+    }
+
+    #[test]
+    fn hedge_overlay_tracks_long_exposure_in_backtests_and_plans() {
+        let ticker = "AAA".to_string();
+        let (mut candles, dates, history) =
+            generate_candles_with_history(&ticker, vec![10.0, 10.0, 20.0, 20.0, 20.0, 20.0]);
+        candles.extend(generate_spy_candles(dates.len()));
+        let signal = |date, action| GeneratedSignal {
+            date,
+            ticker: ticker.clone(),
+            action,
+            confidence: Some(1.0),
+        };
+        let signals = vec![
+            signal(dates[history], SignalAction::Buy),
+            signal(dates[history + 4], SignalAction::Sell),
+        ];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.trade_size_ratio = 0.5;
+        engine.config.hedge = HedgeConfig {
+            beta: 1.0,
+            rebalance_threshold: 0.1,
+        };
+
+        let run = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        let mut legs: Vec<&Trade> = run
+            .result
+            .trades
+            .iter()
+            .filter(|trade| trade.ticker == "SPY")
+            .collect();
+        legs.sort_by_key(|leg| leg.date);
+        assert_eq!(legs.len(), 2);
+        assert_eq!(legs[0].date, dates[history + 1]);
+        assert_eq!(legs[1].date, dates[history + 2]);
+        assert!(legs[0].quantity < -100);
+        assert!((legs[1].quantity - legs[0].quantity).abs() <= 1);
+        assert!(legs
+            .iter()
+            .all(|leg| leg.status == TradeStatus::Closed
+                && leg.exit_date == Some(dates[history + 4])));
+
+        let (mut candles, dates) = generate_candles("OLD", vec![100.0, 100.0]);
+        candles.extend(generate_candles("SPY", vec![500.0, 500.0]).0);
+        let long = sample_active_trade("long", "strategy", "OLD", 100, 100.0, dates[0], None);
+        let spy_buy = GeneratedSignal {
+            date: dates[1],
+            ticker: "SPY".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        };
+        let plan = |existing: &[Trade]| {
+            engine.plan_account_operations(
+                "strategy",
+                "acct",
+                std::slice::from_ref(&spy_buy),
+                &candles,
+                dates[1],
+                &sample_account_state(10_000.0),
+                &HashSet::new(),
+                existing,
+                0,
+                &HashMap::new(),
+            )
+        };
+
+        let opened = plan(std::slice::from_ref(&long));
+        assert_eq!(opened.operations.len(), 1);
+        let open = &opened.operations[0];
+        assert_eq!(open.operation_type, AccountOperationType::OpenPosition);
+        assert_eq!((open.ticker.as_str(), open.quantity), ("SPY", Some(-20)));
+        assert_eq!(open.reason.as_deref(), Some(HEDGE_REASON));
+        assert_eq!(opened.skipped_signals.len(), 1);
+        assert_eq!(opened.skipped_signals[0].reason, HEDGE_INSTRUMENT_REASON);
+
+        let hedge = sample_active_trade("hedge", "strategy", "SPY", -30, 500.0, dates[0], None);
+        let covered = plan(&[long, hedge]);
+        assert_eq!(covered.operations.len(), 1);
+        let close = &covered.operations[0];
+        assert_eq!(close.operation_type, AccountOperationType::ClosePosition);
+        assert_eq!(
+            (close.trade_id.as_str(), close.quantity),
+            ("hedge", Some(-10))
+        );
+    }
+
+    #[test]
+    fn universe_membership_refuses_entries_before_a_ticker_joins() {
+        let ticker = "JOIN".to_string();
+        let (candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 6]);
+        let buy = |date| GeneratedSignal {
+            date,
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        };
+        let signals = vec![buy(dates[history]), buy(dates[history + 2])];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            universe_membership: HashMap::from([(
+                ticker.clone(),
+                vec![MembershipInterval {
+                    start: dates[history + 2],
+                    end: None,
+                }],
+            )]),
+            ..AuxiliaryData::default()
+        }));
+
+        let run = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(run.result.trades.len(), 1);
+        assert_eq!(run.result.trades[0].date, dates[history + 3]);
+        assert_eq!(run.signal_skips.len(), 1);
+        assert_eq!(run.signal_skips[0].reason, NOT_IN_UNIVERSE_REASON);
+        assert_eq!(run.signal_skips[0].signal_date, dates[history]);
+    }
+
+    #[test]
+    fn volatility_filter_skips_entries_while_the_index_is_high() {
+        let ticker = "CALM".to_string();
+        let (candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 6]);
+        let (vix, _, _) =
+            generate_candles_with_history("VIX", vec![35.0, 35.0, 15.0, 15.0, 15.0, 15.0]);
+        let buy = |date| GeneratedSignal {
+            date,
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        };
+        let signals = vec![buy(dates[history]), buy(dates[history + 2])];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.max_volatility_index = 30.0;
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            auxiliary_series: HashMap::from([(
+                "VIX".to_string(),
+                Arc::new(AuxiliarySeries::from_candles(&vix)),
+            )]),
+            ..AuxiliaryData::default()
+        }));
+
+        let run = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(run.result.trades.len(), 1);
+        assert_eq!(run.result.trades[0].date, dates[history + 3]);
+        assert_eq!(run.signal_skips.len(), 1);
+        assert_eq!(run.signal_skips[0].reason, VOLATILITY_FILTER_REASON);
+        assert_eq!(
+            run.signal_skips[0].details.as_deref(),
+            Some("VIX at 35.00 above 30")
+        );
+    }
+
+    #[test]
+    fn sentiment_filter_skips_entries_with_low_or_stale_sentiment() {
+        let ticker = "MOOD".to_string();
+        let (candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 12]);
+        let buy = |date| GeneratedSignal {
+            date,
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        };
+        let signals = vec![buy(dates[history]), buy(dates[history + 2])];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.min_sentiment_score = 0.1;
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_sentiment: HashMap::from([(
+                ticker.clone(),
+                vec![
+                    SentimentPoint {
+                        date: dates[history],
+                        score: -0.5,
+                    },
+                    SentimentPoint {
+                        date: dates[history + 2],
+                        score: 0.6,
+                    },
+                ],
+            )]),
+            ..AuxiliaryData::default()
+        }));
+
+        let run = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(run.result.trades.len(), 1);
+        assert_eq!(run.result.trades[0].date, dates[history + 3]);
+        assert_eq!(run.signal_skips.len(), 1);
+        assert_eq!(run.signal_skips[0].reason, SENTIMENT_FILTER_REASON);
+        assert_eq!(
+            run.signal_skips[0].details.as_deref(),
+            Some("sentiment -0.50 below 0.1")
+        );
+
+        // Scores older than SENTIMENT_MAX_AGE_DAYS count as neutral.
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            ticker_sentiment: HashMap::from([(
+                ticker.clone(),
+                vec![SentimentPoint {
+                    date: dates[history] - Duration::days(SENTIMENT_MAX_AGE_DAYS + 1),
+                    score: 0.9,
+                }],
+            )]),
+            ..AuxiliaryData::default()
+        }));
+        let stale = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals[..1]),
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(stale.result.trades.is_empty());
+        assert_eq!(
+            stale.signal_skips[0].details.as_deref(),
+            Some("sentiment 0.00 below 0.1")
+        );
+    }
+
+    #[test]
+    fn economic_event_days_pause_entries_that_would_open_on_them() {
+        let ticker = "MACRO".to_string();
+        let (candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 5]);
+        let buy = |date| GeneratedSignal {
+            date,
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        };
+        let signals = vec![buy(dates[history]), buy(dates[history + 2])];
+        let mut engine = Engine::new(test_runtime_settings());
+        engine.config.pause_on_economic_events = true;
+        engine.set_auxiliary_data(Arc::new(AuxiliaryData {
+            economic_calendar: EconomicCalendar::from_events(&[(
+                EconomicEventKind::Fomc,
+                dates[history + 1].date_naive(),
+            )]),
+            ..AuxiliaryData::default()
+        }));
+
+        let run = engine
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(run.result.trades.len(), 1);
+        assert_eq!(run.result.trades[0].date, dates[history + 3]);
+        assert_eq!(run.signal_skips.len(), 1);
+        assert_eq!(run.signal_skips[0].reason, ECONOMIC_EVENT_REASON);
+        assert_eq!(
+            run.signal_skips[0].details,
+            Some(format!(
+                "FOMC on {}",
+                dates[history + 1].date_naive().format("%Y-%m-%d")
+            ))
+        );
+    }
+
+    #[test]
+    fn order_rejections_skip_entries_the_same_way_on_every_run() {
+        let ticker = "REJ".to_string();
+        let (candles, dates, history) = generate_candles_with_history(&ticker, vec![10.0; 3]);
+        let signals = vec![GeneratedSignal {
+            date: dates[history],
+            ticker: ticker.clone(),
+            action: SignalAction::Buy,
+            confidence: Some(1.0),
+        }];
+        let mut settings = test_runtime_settings();
+        settings.order_rejection_rate = 1.0;
+        let run = Engine::new(settings.clone())
+            .backtest(
+                None,
+                "test",
+                std::slice::from_ref(&ticker),
+                &candles,
+                &dates,
+                Some(&signals),
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(run.result.trades.is_empty());
+        assert_eq!(run.signal_skips.len(), 1);
+        assert_eq!(run.signal_skips[0].reason, "order_rejected");
+
+        settings.order_rejection_rate = 0.5;
+        let engine = Engine::new(settings);
+        let rejected: Vec<bool> = (0..400)
+            .map(|day| engine.entry_order_rejected("REJ", create_date(day)))
+            .collect();
+        let rejected_count = rejected.iter().filter(|rejected| **rejected).count();
+        assert!((140..260).contains(&rejected_count), "{}", rejected_count);
+        assert!((0..400).all(|day| {
+            engine.entry_order_rejected("REJ", create_date(day)) == rejected[day as usize]
+        }));
+    }
+
+    mod invariants {
+        use super::*;
+        use crate::generators::market_with_signals;
+        use proptest::prelude::{
+            any, prop_assert, prop_assert_eq, prop_oneof, proptest, Just, ProptestConfig,
+        };
+        use proptest::strategy::Strategy as GeneratorStrategy;
+
+        /// Sizing, shorting, stop, holding-period, fill-timing and circuit-breaker settings.
+        fn engine_parameters() -> impl GeneratorStrategy<Value = HashMap<String, f64>> {
+            (
+                (0.01..0.5f64, any::<bool>(), 0.01..0.2f64, 1..40i32),
+                (0..3i32, 0.0..0.03f64, 0..10i32, 0.0..0.1f64),
+            )
+                .prop_map(
+                    |(
+                        (trade_size_ratio, allow_short, stop_loss_ratio, max_holding_days),
+                        (fill_timing, buy_discount_ratio, scale_out_days, circuit_breaker_loss),
+                    )| {
+                        HashMap::from([
+                            ("initialCapital".to_string(), 100_000.0),
+                            ("tradeSizeRatio".to_string(), trade_size_ratio),
+                            (
+                                "allowShortSelling".to_string(),
+                                if allow_short { 1.0 } else { 0.0 },
+                            ),
+                            ("stopLossRatio".to_string(), stop_loss_ratio),
+                            ("maxHoldingDays".to_string(), f64::from(max_holding_days)),
+                            ("fillTiming".to_string(), f64::from(fill_timing)),
+                            ("buyDiscountRatio".to_string(), buy_discount_ratio),
+                            ("scaleOutDays1".to_string(), f64::from(scale_out_days)),
+                            ("scaleOutFraction".to_string(), 0.5),
+                            ("circuitBreakerLoss".to_string(), circuit_breaker_loss),
+                        ])
+                    },
+                )
+        }
+
+        /// Order rejections and volume-capped fills with cancelled or carried remainders.
+        fn broker_settings() -> impl GeneratorStrategy<Value = EngineRuntimeSettings> {
+            (
+                prop_oneof![Just(0.0), 0.0..0.5f64],
+                prop_oneof![Just(0.0), 0.0001..0.01f64],
+                any::<bool>(),
+            )
+                .prop_map(|(rejection_rate, max_volume_share, carry)| {
+                    let mut settings = test_runtime_settings();
+                    settings.order_rejection_rate = rejection_rate;
+                    settings.partial_fill_max_volume_share = max_volume_share;
+                    settings.partial_fill_remainder = if carry {
+                        PartialFillRemainder::Carry
+                    } else {
+                        PartialFillRemainder::Cancel
+                    };
+                    settings
+                })
+        }
+
+        fn close_to(actual: f64, expected: f64) -> bool {
+            (actual - expected).abs() <= 1e-6 * expected.abs().max(1.0)
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn engine_output_keeps_trade_invariants(
+                (market, signals) in market_with_signals(4, 10, 80, 120),
+                parameters in engine_parameters(),
+                settings in broker_settings(),
+            ) {
+                let engine = Engine::from_parameters(&parameters, settings);
+                // backtest runs validate_trades on its own output before returning.
+                let run = engine.backtest(
+                    None,
+                    "fuzz",
+                    &market.tickers,
+                    &market.candles,
+                    &market.dates,
+                    Some(&signals),
+                    None,
+                    None,
+                );
+                prop_assert!(run.is_ok(), "backtest failed: {:#}", run.unwrap_err());
+                let result = run.unwrap().result;
+
+                // Cash plus positions is the portfolio value on every day.
+                for snapshot in &result.daily_snapshots {
+                    prop_assert!(snapshot.portfolio_value.is_finite());
+                    prop_assert!(
+                        close_to(snapshot.portfolio_value, snapshot.cash + snapshot.positions_value),
+                        "{} != {} + {} on {}",
+                        snapshot.portfolio_value,
+                        snapshot.cash,
+                        snapshot.positions_value,
+                        snapshot.date
+                    );
+                }
+                let last = result.daily_snapshots.last().expect("at least one snapshot");
+                prop_assert!(close_to(result.final_portfolio_value, last.portfolio_value));
+
+                // Nothing is created or lost: the final value is the capital plus every
+                // trade's realized or marked pnl (net of fees).
+                let total_pnl: f64 = result
+                    .trades
+                    .iter()
+                    .filter(|trade| matches!(trade.status, TradeStatus::Active | TradeStatus::Closed))
+                    .map(|trade| trade.pnl.unwrap_or(0.0))
+                    .sum();
+                prop_assert!(
+                    close_to(result.final_portfolio_value, result.initial_capital + total_pnl),
+                    "final {} != capital {} + pnl {}",
+                    result.final_portfolio_value,
+                    result.initial_capital,
+                    total_pnl
+                );
+
+                let long_only = parameters["allowShortSelling"] < 0.5;
+                for trade in &result.trades {
+                    prop_assert!(trade.quantity != 0, "trade {} has zero quantity", trade.id);
+                    if long_only {
+                        prop_assert!(trade.quantity > 0, "long-only trade {} is short", trade.id);
+                    }
+                    if trade.status == TradeStatus::Closed {
+                        prop_assert!(trade.exit_date.is_some_and(|exit| exit >= trade.date));
+                    }
+                }
+                prop_assert_eq!(result.initial_capital, parameters["initialCapital"]);
+            }
+        }
+    }
 }
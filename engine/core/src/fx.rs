@@ -0,0 +1,160 @@
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashMap};
+
+/// Currency of accounts and tickers that do not set one.
+pub const BASE_CURRENCY: &str = "USD";
+
+/// Upper-cased ISO code, or `BASE_CURRENCY` when missing or blank.
+pub fn normalize_currency(code: Option<&str>) -> String {
+    code.map(|value| value.trim().to_uppercase())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| BASE_CURRENCY.to_string())
+}
+
+/// Daily exchange rates from the `fx_rates` table: one unit of `base` buys `rate` units of
+/// `quote`.
+#[derive(Debug, Clone, Default)]
+pub struct FxRates {
+    rates: HashMap<(String, String), BTreeMap<NaiveDate, f64>>,
+}
+
+impl FxRates {
+    pub fn insert(&mut self, base: &str, quote: &str, date: NaiveDate, rate: f64) {
+        if !rate.is_finite() || rate <= 0.0 {
+            return;
+        }
+        self.rates
+            .entry((
+                normalize_currency(Some(base)),
+                normalize_currency(Some(quote)),
+            ))
+            .or_default()
+            .insert(date, rate);
+    }
+
+    fn stored_rate(&self, base: &str, quote: &str, date: NaiveDate) -> Option<f64> {
+        let direct = |base: &str, quote: &str| {
+            self.rates
+                .get(&(base.to_string(), quote.to_string()))
+                .and_then(|history| history.range(..=date).next_back())
+                .map(|(_, rate)| *rate)
+        };
+        direct(base, quote).or_else(|| direct(quote, base).map(|rate| 1.0 / rate))
+    }
+
+    /// Units of `quote` per unit of `base` from the latest rate on or before `date`, using the
+    /// inverse pair or a cross through `BASE_CURRENCY` when the pair itself is not stored.
+    pub fn rate_on(&self, base: &str, quote: &str, date: NaiveDate) -> Option<f64> {
+        let base = normalize_currency(Some(base));
+        let quote = normalize_currency(Some(quote));
+        if base == quote {
+            return Some(1.0);
+        }
+        self.stored_rate(&base, &quote, date).or_else(|| {
+            Some(
+                self.stored_rate(&base, BASE_CURRENCY, date)?
+                    * self.stored_rate(BASE_CURRENCY, &quote, date)?,
+            )
+        })
+    }
+
+    pub fn latest_rate(&self, base: &str, quote: &str) -> Option<f64> {
+        self.rate_on(base, quote, NaiveDate::MAX)
+    }
+}
+
+/// An account's currency with the rates and ticker currencies needed to move amounts between
+/// the account's cash and the prices of the tickers it trades. The default is an account in
+/// `BASE_CURRENCY` trading only `BASE_CURRENCY` tickers.
+#[derive(Debug, Clone, Default)]
+pub struct AccountFx {
+    currency: Option<String>,
+    rates: FxRates,
+    ticker_currencies: HashMap<String, String>,
+}
+
+impl AccountFx {
+    pub fn new(
+        currency: Option<&str>,
+        rates: FxRates,
+        ticker_currencies: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            currency: Some(normalize_currency(currency)),
+            rates,
+            ticker_currencies,
+        }
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(BASE_CURRENCY)
+    }
+
+    pub fn ticker_currency(&self, ticker: &str) -> &str {
+        self.ticker_currencies
+            .get(ticker)
+            .map(String::as_str)
+            .unwrap_or(BASE_CURRENCY)
+    }
+
+    /// Units of the ticker's currency per unit of account currency on `date`.
+    pub fn ticker_rate_on(&self, ticker: &str, date: NaiveDate) -> Option<f64> {
+        self.rates
+            .rate_on(self.currency(), self.ticker_currency(ticker), date)
+    }
+
+    pub fn ticker_rate(&self, ticker: &str) -> Option<f64> {
+        self.ticker_rate_on(ticker, NaiveDate::MAX)
+    }
+
+    /// `amount` in the ticker's currency expressed in the account currency as of `date`;
+    /// amounts without a known rate are taken at par.
+    pub fn to_account_on(&self, amount: f64, ticker: &str, date: NaiveDate) -> f64 {
+        match self.ticker_rate_on(ticker, date) {
+            Some(rate) => amount / rate,
+            None => amount,
+        }
+    }
+
+    pub fn to_account(&self, amount: f64, ticker: &str) -> f64 {
+        self.to_account_on(amount, ticker, NaiveDate::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountFx, FxRates};
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    #[test]
+    fn resolves_inverse_cross_and_dated_rates() {
+        let mut rates = FxRates::default();
+        rates.insert("eur", "usd", date(1), 1.10);
+        rates.insert("EUR", "USD", date(10), 1.20);
+        rates.insert("USD", "JPY", date(1), 150.0);
+
+        assert_eq!(rates.rate_on("EUR", "USD", date(5)), Some(1.10));
+        assert_eq!(rates.latest_rate("EUR", "USD"), Some(1.20));
+        assert_eq!(rates.rate_on("EUR", "USD", NaiveDate::MIN), None);
+        assert!((rates.latest_rate("USD", "EUR").unwrap() - 1.0 / 1.20).abs() < 1e-12);
+        assert!((rates.latest_rate("EUR", "JPY").unwrap() - 180.0).abs() < 1e-9);
+        assert_eq!(rates.latest_rate("GBP", "USD"), None);
+        assert_eq!(rates.latest_rate("GBP", "gbp"), Some(1.0));
+
+        let fx = AccountFx::new(
+            Some("eur"),
+            rates,
+            HashMap::from([("SAP".to_string(), "EUR".to_string())]),
+        );
+        assert_eq!(fx.currency(), "EUR");
+        assert_eq!(fx.ticker_rate("AAPL"), Some(1.20));
+        assert_eq!(fx.ticker_rate("SAP"), Some(1.0));
+        assert!((fx.to_account_on(1_100.0, "AAPL", date(5)) - 1_000.0).abs() < 1e-9);
+        assert_eq!(AccountFx::default().to_account(50.0, "AAPL"), 50.0);
+    }
+}
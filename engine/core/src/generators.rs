@@ -0,0 +1,144 @@
+//! Proptest generators for candle series, markets and signal streams, shared by the
+//! property-based tests of the engine and other modules.
+
+use crate::candle_utils::weekdays_from;
+use crate::models::{Candle, GeneratedSignal, SignalAction};
+use chrono::{DateTime, NaiveDate, Utc};
+use proptest::prelude::*;
+
+/// Candles of several tickers on shared trading days, as the engine expects them.
+#[derive(Debug, Clone)]
+pub struct GeneratedMarket {
+    pub tickers: Vec<String>,
+    /// Sorted by date, then ticker.
+    pub candles: Vec<Candle>,
+    pub dates: Vec<DateTime<Utc>>,
+}
+
+/// `count` consecutive weekdays at midnight UTC starting 2021-01-04.
+pub fn trading_days(count: usize) -> Vec<DateTime<Utc>> {
+    weekdays_from(
+        NaiveDate::from_ymd_opt(2021, 1, 4).expect("valid start date"),
+        count,
+    )
+}
+
+/// Daily candles for `ticker` on `dates`: a random walk with overnight gaps, intraday ranges
+/// around open and close, and liquid volume. Prices stay above 1.
+pub fn candle_series(
+    ticker: String,
+    dates: Vec<DateTime<Utc>>,
+) -> impl Strategy<Value = Vec<Candle>> {
+    let days = dates.len();
+    (
+        5.0..300.0f64,
+        prop::collection::vec(
+            (
+                -0.05..0.05f64,
+                -0.08..0.08f64,
+                0.0..0.03f64,
+                0.0..0.03f64,
+                100_000i64..5_000_000,
+            ),
+            days,
+        ),
+    )
+        .prop_map(move |(start, moves)| {
+            let mut previous_close: f64 = start;
+            dates
+                .iter()
+                .zip(moves)
+                .map(|(date, (gap, change, wick_up, wick_down, volume))| {
+                    let open = (previous_close * (1.0 + gap)).max(1.0);
+                    let close = (open * (1.0 + change)).max(1.0);
+                    previous_close = close;
+                    Candle {
+                        ticker: ticker.clone(),
+                        date: *date,
+                        open,
+                        high: open.max(close) * (1.0 + wick_up),
+                        low: (open.min(close) * (1.0 - wick_down)).max(0.5),
+                        close,
+                        unadjusted_close: None,
+                        volume_shares: volume,
+                    }
+                })
+                .collect()
+        })
+}
+
+/// Up to `max_tickers` tickers with `min_days..=max_days` candles each on the same dates.
+pub fn market(
+    max_tickers: usize,
+    min_days: usize,
+    max_days: usize,
+) -> impl Strategy<Value = GeneratedMarket> {
+    (1..=max_tickers, min_days..=max_days).prop_flat_map(|(ticker_count, days)| {
+        let tickers: Vec<String> = (0..ticker_count)
+            .map(|index| format!("TICK{}", index))
+            .collect();
+        let dates = trading_days(days);
+        let series: Vec<_> = tickers
+            .iter()
+            .map(|ticker| candle_series(ticker.clone(), dates.clone()))
+            .collect();
+        (Just(tickers), Just(dates), series).prop_map(|(tickers, dates, series)| {
+            let mut candles: Vec<Candle> = series.into_iter().flatten().collect();
+            candles.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.ticker.cmp(&b.ticker)));
+            GeneratedMarket {
+                tickers,
+                candles,
+                dates,
+            }
+        })
+    })
+}
+
+/// Buy, sell or hold signals with confidences for random (date, ticker) pairs of `market`.
+pub fn signal_stream(
+    market: &GeneratedMarket,
+    max_signals: usize,
+) -> impl Strategy<Value = Vec<GeneratedSignal>> {
+    let tickers = market.tickers.clone();
+    let dates = market.dates.clone();
+    let action = prop_oneof![
+        3 => Just(SignalAction::Buy),
+        2 => Just(SignalAction::Sell),
+        1 => Just(SignalAction::Hold),
+    ];
+    prop::collection::vec(
+        (
+            0..dates.len(),
+            0..tickers.len(),
+            action,
+            prop::option::of(0.0..1.0f64),
+        ),
+        0..=max_signals,
+    )
+    .prop_map(move |picks| {
+        picks
+            .into_iter()
+            .map(
+                |(date_index, ticker_index, action, confidence)| GeneratedSignal {
+                    date: dates[date_index],
+                    ticker: tickers[ticker_index].clone(),
+                    action,
+                    confidence,
+                },
+            )
+            .collect()
+    })
+}
+
+/// A market together with a signal stream over it.
+pub fn market_with_signals(
+    max_tickers: usize,
+    min_days: usize,
+    max_days: usize,
+    max_signals: usize,
+) -> impl Strategy<Value = (GeneratedMarket, Vec<GeneratedSignal>)> {
+    market(max_tickers, min_days, max_days).prop_flat_map(move |market| {
+        let signals = signal_stream(&market, max_signals);
+        (Just(market), signals)
+    })
+}
@@ -685,11 +685,10 @@ pub fn compute_atr_series(candles: &[Candle], period: usize) -> AtrCacheEntry {
         }
     }
 
-    let entry = AtrCacheEntry {
+    AtrCacheEntry {
         period,
         length: n,
         atr,
         atr_sma5,
-    };
-    entry
+    }
 }
@@ -0,0 +1,71 @@
+//! Backtesting core of the StratCraft engine.
+//!
+//! The crate holds everything needed to simulate strategies on daily candles: the candle and
+//! trade models, indicators, the strategy registry, trading rules, the portfolio simulation and
+//! performance metrics. It has no database, HTTP or async runtime dependencies; loading candles
+//! and storing results is left to the embedding application (the `engine` CLI keeps its
+//! PostgreSQL, SQLite and broker layers in its own crate).
+//!
+//! A backtest needs a strategy, the runtime settings (the same keys as the engine's `settings`
+//! table, such as `TRADE_SLIPPAGE_RATE`), and the candles sorted by date together with the
+//! distinct candle dates:
+//!
+//! ```no_run
+//! use std::collections::HashMap;
+//! use stratcraft_core::config::EngineRuntimeSettings;
+//! use stratcraft_core::engine::Engine;
+//! use stratcraft_core::models::Candle;
+//! use stratcraft_core::strategy::create_strategy;
+//!
+//! # fn load_candles() -> Vec<Candle> { Vec::new() }
+//! # fn load_settings() -> HashMap<String, String> { HashMap::new() }
+//! # fn main() -> anyhow::Result<()> {
+//! let candles = load_candles();
+//! let mut dates: Vec<_> = candles.iter().map(|candle| candle.date).collect();
+//! dates.sort();
+//! dates.dedup();
+//! let mut tickers: Vec<String> = candles.iter().map(|candle| candle.ticker.clone()).collect();
+//! tickers.sort();
+//! tickers.dedup();
+//!
+//! let parameters = HashMap::from([("initialCapital".to_string(), 100_000.0)]);
+//! let settings = EngineRuntimeSettings::from_settings_map(&load_settings())?;
+//! let strategy = create_strategy("buy_and_hold", parameters.clone())?;
+//! let mut engine = Engine::from_parameters(&parameters, settings);
+//! let run = engine.backtest(
+//!     Some(strategy.as_ref()),
+//!     "buy_and_hold",
+//!     &tickers,
+//!     &candles,
+//!     &dates,
+//!     None,
+//!     None,
+//!     None,
+//! )?;
+//! println!("CAGR {:.2}%", run.result.performance.cagr * 100.0);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The public modules below are the supported interface; items marked `pub(crate)` inside
+//! them are implementation details.
+
+pub mod benchmark;
+pub mod blackout;
+pub mod cancellation;
+pub mod candle_utils;
+pub mod config;
+pub mod engine;
+pub mod fx;
+pub mod indicators;
+pub mod models;
+pub mod param_utils;
+pub mod performance;
+pub mod signal_generation;
+pub mod strategy;
+pub mod strategy_utils;
+pub mod trading_rules;
+
+/// Proptest generators for property tests of the core, the engine crate and benches.
+#[cfg(any(test, feature = "test-utils"))]
+pub mod generators;
@@ -0,0 +1,1145 @@
+use crate::blackout::BlackoutConfig;
+use anyhow::{anyhow, Result as AnyResult};
+use chrono::{DateTime, NaiveDate, Utc};
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub ticker: String,
+    pub date: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub unadjusted_close: Option<f64>,
+    pub volume_shares: i64,
+}
+
+/// Per-share cash distribution paid to holders of record before `ex_date`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerDistribution {
+    pub ex_date: DateTime<Utc>,
+    pub amount: f64,
+}
+
+/// Distribution schedules keyed by ticker, sorted by ex-date.
+pub type TickerDistributionMap = HashMap<String, Vec<TickerDistribution>>;
+
+/// Earnings report dates keyed by ticker, sorted ascending.
+pub type TickerEarningsMap = HashMap<String, Vec<DateTime<Utc>>>;
+
+/// Short-side data of a ticker reported on `date`; either value may be missing from a report.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShortInterestPoint {
+    pub date: DateTime<Utc>,
+    /// Shares sold short as a fraction of the float.
+    pub short_interest_ratio: Option<f64>,
+    /// Annualized cost of borrowing the shares, e.g. 0.05 = 5% per year.
+    pub borrow_fee_rate: Option<f64>,
+}
+
+/// Short interest and borrow fee reports keyed by ticker, sorted by date.
+pub type TickerShortInterestMap = HashMap<String, Vec<ShortInterestPoint>>;
+
+/// Latest reported short interest ratio and borrow fee rate on or before `date`, each taken
+/// from the newest report that has it.
+pub fn short_interest_on_or_before(
+    points: &[ShortInterestPoint],
+    date: DateTime<Utc>,
+) -> (Option<f64>, Option<f64>) {
+    let known = &points[..points.partition_point(|point| point.date <= date)];
+    (
+        known
+            .iter()
+            .rev()
+            .find_map(|point| point.short_interest_ratio),
+        known.iter().rev().find_map(|point| point.borrow_fee_rate),
+    )
+}
+
+/// Scores older than this many calendar days count as missing, alike in training, inference
+/// and the sentiment entry filter.
+pub const SENTIMENT_MAX_AGE_DAYS: i64 = 5;
+
+/// News sentiment of a ticker on `date`, from -1 (negative) to 1 (positive).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SentimentPoint {
+    pub date: DateTime<Utc>,
+    pub score: f64,
+}
+
+/// Daily sentiment scores keyed by ticker, sorted by date.
+pub type TickerSentimentMap = HashMap<String, Vec<SentimentPoint>>;
+
+/// Latest sentiment score on or before `date`, unless it is older than
+/// `SENTIMENT_MAX_AGE_DAYS`.
+pub fn sentiment_on_or_before(points: &[SentimentPoint], date: DateTime<Utc>) -> Option<f64> {
+    let latest = points[..points.partition_point(|point| point.date <= date)].last()?;
+    ((date - latest.date).num_days() <= SENTIMENT_MAX_AGE_DAYS).then_some(latest.score)
+}
+
+/// Period in which a ticker belonged to a universe, from `start` up to but excluding `end`;
+/// `end` is `None` while the ticker is still a member.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MembershipInterval {
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl MembershipInterval {
+    pub fn contains(&self, date: DateTime<Utc>) -> bool {
+        self.start <= date && self.end.is_none_or(|end| date < end)
+    }
+}
+
+/// Membership intervals of a point-in-time universe keyed by ticker.
+pub type UniverseMembershipMap = HashMap<String, Vec<MembershipInterval>>;
+
+/// Daily closes of a non-tradable auxiliary ticker such as a volatility index, sorted by date.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuxiliarySeries {
+    dates: Vec<DateTime<Utc>>,
+    closes: Vec<f64>,
+}
+
+impl AuxiliarySeries {
+    /// Builds the series from one ticker's candles in any order.
+    pub fn from_candles(candles: &[Candle]) -> Self {
+        let mut points: Vec<(DateTime<Utc>, f64)> = candles
+            .iter()
+            .filter(|candle| candle.close.is_finite())
+            .map(|candle| (candle.date, candle.close))
+            .collect();
+        points.sort_by_key(|(date, _)| *date);
+        points.dedup_by_key(|(date, _)| *date);
+        let (dates, closes) = points.into_iter().unzip();
+        Self { dates, closes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dates.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dates.is_empty()
+    }
+
+    pub fn dates(&self) -> &[DateTime<Utc>] {
+        &self.dates
+    }
+
+    pub fn closes(&self) -> &[f64] {
+        &self.closes
+    }
+
+    fn index_on_or_before(&self, date: DateTime<Utc>) -> Option<usize> {
+        self.dates
+            .partition_point(|day| *day <= date)
+            .checked_sub(1)
+    }
+
+    /// Latest close on or before `date`, as known at that day's close.
+    pub fn close_on_or_before(&self, date: DateTime<Utc>) -> Option<f64> {
+        self.index_on_or_before(date)
+            .map(|index| self.closes[index])
+    }
+
+    /// Return of the series over the `bars` closes ending at the latest close on or before
+    /// `date`.
+    pub fn change_on_or_before(&self, date: DateTime<Utc>, bars: usize) -> Option<f64> {
+        let index = self.index_on_or_before(date)?;
+        let past = self.closes[index.checked_sub(bars)?];
+        (past.abs() > f64::EPSILON).then(|| self.closes[index] / past - 1.0)
+    }
+
+    /// Cheap key of the series: its length and first and last points.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.dates.len().hash(&mut hasher);
+        for index in [0, self.dates.len().saturating_sub(1)] {
+            if let (Some(date), Some(close)) = (self.dates.get(index), self.closes.get(index)) {
+                date.hash(&mut hasher);
+                close.to_bits().hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+/// Auxiliary series keyed by ticker.
+pub type AuxiliarySeriesMap = HashMap<String, Arc<AuxiliarySeries>>;
+
+/// Scheduled macro releases tracked by the economic calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EconomicEventKind {
+    Fomc,
+    Cpi,
+}
+
+impl EconomicEventKind {
+    pub const ALL: [Self; 2] = [Self::Fomc, Self::Cpi];
+
+    /// Value stored in `economic_events.event_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fomc => "FOMC",
+            Self::Cpi => "CPI",
+        }
+    }
+}
+
+impl FromStr for EconomicEventKind {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> AnyResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| anyhow!("Unknown economic event type: {}", value))
+    }
+}
+
+/// Release days of scheduled macro events, sorted per kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EconomicCalendar {
+    fomc: Vec<NaiveDate>,
+    cpi: Vec<NaiveDate>,
+}
+
+impl EconomicCalendar {
+    pub fn from_events(events: &[(EconomicEventKind, NaiveDate)]) -> Self {
+        let mut calendar = Self::default();
+        for (kind, date) in events {
+            calendar.dates_mut(*kind).push(*date);
+        }
+        for kind in EconomicEventKind::ALL {
+            let dates = calendar.dates_mut(kind);
+            dates.sort_unstable();
+            dates.dedup();
+        }
+        calendar
+    }
+
+    pub fn dates(&self, kind: EconomicEventKind) -> &[NaiveDate] {
+        match kind {
+            EconomicEventKind::Fomc => &self.fomc,
+            EconomicEventKind::Cpi => &self.cpi,
+        }
+    }
+
+    fn dates_mut(&mut self, kind: EconomicEventKind) -> &mut Vec<NaiveDate> {
+        match kind {
+            EconomicEventKind::Fomc => &mut self.fomc,
+            EconomicEventKind::Cpi => &mut self.cpi,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fomc.len() + self.cpi.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fomc.is_empty() && self.cpi.is_empty()
+    }
+
+    /// Calendar days from `date` to the next `kind` release on or after it; 0 on a release day.
+    pub fn days_until(&self, kind: EconomicEventKind, date: NaiveDate) -> Option<i64> {
+        let dates = self.dates(kind);
+        dates
+            .get(dates.partition_point(|day| *day < date))
+            .map(|day| (*day - date).num_days())
+    }
+
+    /// First kind of event released on `date`.
+    pub fn event_on(&self, date: NaiveDate) -> Option<EconomicEventKind> {
+        EconomicEventKind::ALL
+            .into_iter()
+            .find(|kind| self.dates(*kind).binary_search(&date).is_ok())
+    }
+
+    /// Hash of every release day.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Data a backtest reads besides candles, shared by every engine over the same market data.
+#[derive(Debug, Clone, Default)]
+pub struct AuxiliaryData {
+    pub ticker_expense_map: HashMap<String, f64>,
+    pub ticker_distributions: TickerDistributionMap,
+    pub ticker_earnings: TickerEarningsMap,
+    /// Short interest and borrow fee reports; shorts accrue the reported borrow fees.
+    pub ticker_short_interest: TickerShortInterestMap,
+    /// Daily news sentiment scores, for the `minSentimentScore` buy filter.
+    pub ticker_sentiment: TickerSentimentMap,
+    /// Point-in-time universe; when not empty, only its members on the signal date are entered.
+    pub universe_membership: UniverseMembershipMap,
+    /// Non-tradable series such as the volatility index, keyed by ticker.
+    pub auxiliary_series: AuxiliarySeriesMap,
+    pub economic_calendar: EconomicCalendar,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeChange {
+    pub field: String,
+    pub old_value: Value,
+    pub new_value: Value,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub id: String,
+    pub strategy_id: String,
+    pub ticker: String,
+    pub quantity: i32,
+    pub price: f64,
+    pub date: DateTime<Utc>,
+    pub status: TradeStatus,
+    pub pnl: Option<f64>,
+    #[serde(default)]
+    pub fee: Option<f64>,
+    pub exit_price: Option<f64>,
+    pub exit_date: Option<DateTime<Utc>>,
+    pub stop_loss: Option<f64>,
+    pub stop_loss_triggered: Option<bool>,
+    #[serde(default)]
+    pub entry_order_id: Option<String>,
+    #[serde(default)]
+    pub entry_cancel_after: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub stop_order_id: Option<String>,
+    #[serde(default)]
+    pub exit_order_id: Option<String>,
+    /// Limit take-profit order linked one-cancels-other with `stop_order_id`: once either leg
+    /// fills, the broker cancels the other.
+    #[serde(default)]
+    pub target_order_id: Option<String>,
+    pub changes: Vec<TradeChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AccountOperationType {
+    OpenPosition,
+    ClosePosition,
+    UpdateStopLoss,
+}
+
+impl AccountOperationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountOperationType::OpenPosition => "open_position",
+            AccountOperationType::ClosePosition => "close_position",
+            AccountOperationType::UpdateStopLoss => "update_stop_loss",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountOperationPlan {
+    pub trade_id: String,
+    pub ticker: String,
+    pub quantity: Option<i32>,
+    pub price: Option<f64>,
+    pub stop_loss: Option<f64>,
+    pub previous_stop_loss: Option<f64>,
+    pub triggered_at: DateTime<Utc>,
+    pub operation_type: AccountOperationType,
+    pub reason: Option<String>,
+    pub order_type: Option<String>,
+    pub discount_applied: Option<bool>,
+    pub signal_confidence: Option<f64>,
+    pub account_cash_at_plan: Option<f64>,
+    pub days_held: Option<i32>,
+    /// Estimated spread, impact and fee cost in dollars; set by plan-operations for orders.
+    pub expected_cost: Option<f64>,
+    /// Broker stop order an `UpdateStopLoss` modifies in place, so the position stays protected
+    /// instead of going without a stop between a cancel and a new order.
+    #[serde(default)]
+    pub replaces_order_id: Option<String>,
+    /// Limit price of the take-profit order an `OpenPosition` places alongside its stop.
+    #[serde(default)]
+    pub take_profit: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TradeStatus {
+    Pending,
+    Active,
+    Closed,
+    Cancelled,
+}
+
+impl TradeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeStatus::Pending => "pending",
+            TradeStatus::Active => "active",
+            TradeStatus::Closed => "closed",
+            TradeStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl Trade {
+    pub fn record_change<V>(
+        &mut self,
+        field: &str,
+        old_value: &V,
+        new_value: &V,
+        changed_at: DateTime<Utc>,
+    ) where
+        V: Serialize,
+    {
+        if let (Ok(old_json), Ok(new_json)) = (
+            serde_json::to_value(old_value),
+            serde_json::to_value(new_value),
+        ) {
+            if old_json == new_json {
+                return;
+            }
+
+            self.changes.push(TradeChange {
+                field: field.to_string(),
+                old_value: old_json,
+                new_value: new_json,
+                changed_at,
+            });
+        }
+    }
+
+    pub fn set_exit_price(&mut self, value: Option<f64>, changed_at: DateTime<Utc>) {
+        let old = self.exit_price;
+        self.record_change("exitPrice", &old, &value, changed_at);
+        self.exit_price = value;
+    }
+
+    pub fn set_price(&mut self, value: f64, changed_at: DateTime<Utc>) {
+        let old = self.price;
+        self.record_change("price", &old, &value, changed_at);
+        self.price = value;
+    }
+
+    pub fn set_date(&mut self, value: DateTime<Utc>, changed_at: DateTime<Utc>) {
+        let old = self.date;
+        self.record_change("date", &old, &value, changed_at);
+        self.date = value;
+    }
+
+    pub fn set_exit_date(&mut self, value: Option<DateTime<Utc>>, changed_at: DateTime<Utc>) {
+        let old = self.exit_date;
+        self.record_change("exitDate", &old, &value, changed_at);
+        self.exit_date = value;
+    }
+
+    pub fn set_status(&mut self, status: TradeStatus, changed_at: DateTime<Utc>) {
+        let old = self.status.clone();
+        self.record_change("status", &old, &status, changed_at);
+        self.status = status;
+    }
+
+    pub fn set_stop_loss(&mut self, value: Option<f64>, changed_at: DateTime<Utc>) {
+        let old = self.stop_loss;
+        self.record_change("stopLoss", &old, &value, changed_at);
+        self.stop_loss = value;
+    }
+
+    pub fn set_stop_loss_triggered(&mut self, value: Option<bool>, changed_at: DateTime<Utc>) {
+        let old = self.stop_loss_triggered;
+        self.record_change("stopLossTriggered", &old, &value, changed_at);
+        self.stop_loss_triggered = value;
+    }
+
+    /// Stop-loss the trade was opened with, before any later update moved it.
+    pub fn initial_stop_loss(&self) -> Option<f64> {
+        match self
+            .changes
+            .iter()
+            .find(|change| change.field == "stopLoss")
+        {
+            Some(change) => change.old_value.as_f64().or(change.new_value.as_f64()),
+            None => self.stop_loss,
+        }
+    }
+
+    /// Net P&L in multiples of the initial risk R, the loss at the initial stop-loss; `None`
+    /// without a stop-loss on the losing side of the entry price.
+    pub fn r_multiple(&self) -> Option<f64> {
+        let stop = self.initial_stop_loss()?;
+        let risk = (self.price - stop) * self.quantity as f64;
+        let pnl = self.pnl.filter(|pnl| pnl.is_finite())?;
+        (risk.is_finite() && risk > 0.0).then(|| pnl / risk)
+    }
+
+    /// Quantity the trade was opened with, before any partial exits reduced it.
+    pub fn original_quantity(&self) -> i32 {
+        self.changes
+            .iter()
+            .find(|change| change.field == "quantity")
+            .and_then(|change| change.old_value.as_i64())
+            .and_then(|value| i32::try_from(value).ok())
+            .unwrap_or(self.quantity)
+    }
+
+    /// Adverse slippage of the entry fill over the planned entry price (the price before a
+    /// fill first replaced it), as a share of the planned price; negative when the fill was
+    /// better than planned.
+    pub fn entry_slippage_rate(&self) -> Option<f64> {
+        let planned = self
+            .changes
+            .iter()
+            .find(|change| change.field == "price")
+            .and_then(|change| change.old_value.as_f64())
+            .unwrap_or(self.price);
+        if !(planned.is_finite() && planned > 0.0) {
+            return None;
+        }
+        Some((self.price - planned) / planned * self.quantity.signum() as f64)
+    }
+
+    /// Adverse slippage of a triggered stop's fill past its stop price, as a share of the
+    /// stop price; `None` unless the trade was closed by its stop.
+    pub fn stop_slippage_rate(&self) -> Option<f64> {
+        if self.status != TradeStatus::Closed || self.stop_loss_triggered != Some(true) {
+            return None;
+        }
+        let stop = self
+            .stop_loss
+            .filter(|stop| stop.is_finite() && *stop > 0.0)?;
+        let fill = self.exit_price?;
+        Some((stop - fill) / stop * self.quantity.signum() as f64)
+    }
+
+    /// Moves `quantity` shares (same sign as the trade) into a new `<id>-partial-<n>` trade
+    /// for the caller to close, leaving the rest open on `self`.
+    pub fn split_off(&mut self, quantity: i32, changed_at: DateTime<Utc>) -> Trade {
+        let splits = self
+            .changes
+            .iter()
+            .filter(|change| change.field == "quantity")
+            .count();
+        let mut part = self.clone();
+        part.id = format!("{}-partial-{}", self.id, splits + 1);
+        part.quantity = quantity;
+        part.entry_order_id = None;
+        part.stop_order_id = None;
+        part.exit_order_id = None;
+        part.target_order_id = None;
+        part.changes = Vec::new();
+        self.set_quantity(self.quantity - quantity, changed_at);
+        part
+    }
+
+    /// Shrinks a trade whose entry stopped after filling `filled` shares (same sign as the
+    /// trade) to that size and returns the unfilled rest as a cancelled `<id>-unfilled`
+    /// trade. The change is recorded as `entryQuantity` so the filled size stays the trade's
+    /// original quantity.
+    pub fn split_unfilled_remainder(&mut self, filled: i32, changed_at: DateTime<Utc>) -> Trade {
+        let mut remainder = self.clone();
+        remainder.id = format!("{}-unfilled", self.id);
+        remainder.quantity = self.quantity - filled;
+        remainder.status = TradeStatus::Cancelled;
+        remainder.pnl = None;
+        remainder.entry_order_id = None;
+        remainder.stop_order_id = None;
+        remainder.exit_order_id = None;
+        remainder.target_order_id = None;
+        remainder.changes = Vec::new();
+        let old = self.quantity;
+        self.record_change("entryQuantity", &old, &filled, changed_at);
+        self.quantity = filled;
+        remainder
+    }
+
+    pub fn set_quantity(&mut self, quantity: i32, changed_at: DateTime<Utc>) {
+        let old = self.quantity;
+        self.record_change("quantity", &old, &quantity, changed_at);
+        self.quantity = quantity;
+    }
+
+    pub fn set_fee(&mut self, value: Option<f64>, changed_at: DateTime<Utc>) {
+        let old = self.fee;
+        self.record_change("fee", &old, &value, changed_at);
+        self.fee = value;
+    }
+
+    pub fn set_pnl(&mut self, value: Option<f64>, changed_at: DateTime<Utc>) {
+        let old = self.pnl;
+        self.record_change("pnl", &old, &value, changed_at);
+        self.pnl = value;
+    }
+
+    pub fn set_ticker(&mut self, value: String, changed_at: DateTime<Utc>) {
+        let old = self.ticker.clone();
+        self.record_change("ticker", &old, &value, changed_at);
+        self.ticker = value;
+    }
+
+    pub fn set_stop_order_id(&mut self, value: Option<String>, changed_at: DateTime<Utc>) {
+        let old = self.stop_order_id.clone();
+        self.record_change("stopOrderId", &old, &value, changed_at);
+        self.stop_order_id = value;
+    }
+
+    pub fn set_exit_order_id(&mut self, value: Option<String>, changed_at: DateTime<Utc>) {
+        let old = self.exit_order_id.clone();
+        self.record_change("exitOrderId", &old, &value, changed_at);
+        self.exit_order_id = value;
+    }
+
+    pub fn set_target_order_id(&mut self, value: Option<String>, changed_at: DateTime<Utc>) {
+        let old = self.target_order_id.clone();
+        self.record_change("targetOrderId", &old, &value, changed_at);
+        self.target_order_id = value;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyPerformance {
+    pub total_trades: i32,
+    pub winning_trades: i32,
+    pub losing_trades: i32,
+    pub win_rate: f64,
+    pub total_return: f64,
+    pub cagr: f64,
+    pub sharpe_ratio: f64,
+    pub calmar_ratio: f64,
+    pub max_drawdown: f64,
+    pub max_drawdown_percent: f64,
+    pub avg_trade_return: f64,
+    pub best_trade: f64,
+    pub worst_trade: f64,
+    pub total_tickers: i32,
+    pub median_trade_duration: f64,
+    pub median_trade_pnl: f64,
+    pub median_trade_pnl_percent: f64,
+    pub median_concurrent_trades: f64,
+    pub avg_trade_duration: f64,
+    pub avg_trade_pnl: f64,
+    pub avg_trade_pnl_percent: f64,
+    pub avg_concurrent_trades: f64,
+    pub avg_losing_pnl: f64,
+    pub avg_losing_pnl_percent: f64,
+    pub avg_winning_pnl: f64,
+    pub avg_winning_pnl_percent: f64,
+    pub last_updated: DateTime<Utc>,
+    #[serde(default)]
+    pub monthly_returns: Vec<MonthlyReturns>,
+    /// Traded notional (entries and exits) per year as a multiple of average portfolio value.
+    #[serde(default)]
+    pub annual_turnover: f64,
+    #[serde(default)]
+    pub avg_daily_traded_notional: f64,
+    /// Largest AUM at which the average entry stays within `CAPACITY_MAX_VOLUME_SHARE` of
+    /// its ticker's 20-day dollar volume.
+    #[serde(default)]
+    pub capacity_aum: Option<f64>,
+    /// Net P&L of the trades entered in the most profitable ISO week as a share of total net
+    /// P&L; above 1 when the other weeks lose money together. `None` without net profit.
+    #[serde(default)]
+    pub entry_week_concentration: Option<f64>,
+    /// Mean R-multiple of the trades with an initial stop-loss.
+    #[serde(default)]
+    pub r_expectancy: Option<f64>,
+    #[serde(default)]
+    pub r_multiple_histogram: Vec<RMultipleBucket>,
+    /// Van Tharp's system quality number: the square root of the trade count (capped at 100)
+    /// times mean R over the standard deviation of R.
+    #[serde(default)]
+    pub system_quality_number: Option<f64>,
+    /// Closed trades grouped by calendar days held; buckets without trades are left out.
+    #[serde(default)]
+    pub holding_period_returns: Vec<HoldingPeriodBucket>,
+}
+
+/// Closed trades held `[min_days, max_days)` calendar days; the last bucket is open-ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingPeriodBucket {
+    pub min_days: i64,
+    pub max_days: Option<i64>,
+    pub trades: i32,
+    /// Mean net P&L as a percentage of entry notional.
+    pub avg_return_percent: f64,
+    pub win_rate: f64,
+}
+
+/// Trades whose R-multiple falls in `[min, max)`; the outermost buckets are open-ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RMultipleBucket {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub count: i32,
+}
+
+/// One calendar year of month-over-month portfolio returns (ratios), indexed January to
+/// December; months outside the backtest window are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthlyReturns {
+    pub year: i32,
+    pub months: [Option<f64>; 12],
+    pub ytd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestDataPoint {
+    pub date: DateTime<Utc>,
+    pub portfolio_value: f64,
+    pub cash: f64,
+    pub positions_value: f64,
+    pub concurrent_trades: i32,
+    pub missed_trades_due_to_cash: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyStateSnapshot {
+    pub template_id: String,
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub id: String,
+    pub strategy_id: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub initial_capital: f64,
+    pub final_portfolio_value: f64,
+    pub performance: StrategyPerformance,
+    pub daily_snapshots: Vec<BacktestDataPoint>,
+    pub trades: Vec<Trade>,
+    pub tickers: Vec<String>,
+    /// Tickers with fewer candles than `MIN_TICKER_HISTORY_CANDLES`, which never traded.
+    #[serde(default)]
+    pub insufficient_history_tickers: Vec<String>,
+    pub ticker_scope: Option<String>,
+    pub strategy_state: Option<StrategyStateSnapshot>,
+    /// Equal-weight buy-and-hold return ratio over the same tickers and window.
+    #[serde(default)]
+    pub baseline_return: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BacktestResult {
+    /// Strategy return ratio minus the buy-and-hold baseline.
+    pub fn excess_return(&self) -> Option<f64> {
+        let baseline = self.baseline_return?;
+        if self.initial_capital <= 0.0 {
+            return None;
+        }
+        Some(self.final_portfolio_value / self.initial_capital - 1.0 - baseline)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestRun {
+    pub result: BacktestResult,
+    #[allow(dead_code)]
+    pub signals: Vec<GeneratedSignal>,
+    #[allow(dead_code)]
+    pub signal_skips: Vec<AccountSignalSkip>,
+    /// Per-ticker warm-up of strategy backtests, sorted by ticker; empty for precomputed
+    /// signals.
+    pub warmup: Vec<TickerWarmup>,
+}
+
+/// Leading candles a ticker spent warming up before its strategy could signal on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickerWarmup {
+    pub ticker: String,
+    /// Candles consumed by the strategy's `min_data_points`.
+    pub warmup_dates: usize,
+    /// First date the strategy can signal on the ticker; `None` when the ticker's history is
+    /// too short to finish the warm-up, so it never trades.
+    pub ready_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub local_optimization_version: i32,
+    pub parameters: Vec<StrategyParameter>,
+    pub example_usage: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyParameter {
+    pub name: String,
+    pub r#type: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub step: Option<f64>,
+    pub default: Option<serde_json::Value>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyConfig {
+    pub id: String,
+    pub name: String,
+    pub template_id: String,
+    pub account_id: Option<String>,
+    pub excluded_tickers: Vec<String>,
+    pub excluded_keywords: Vec<String>,
+    pub parameters: HashMap<String, f64>,
+    pub backtest_start_date: Option<DateTime<Utc>>,
+    pub blackout: BlackoutConfig,
+}
+
+/// Decrypted secret whose `Debug` output is redacted so it cannot end up in logs.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// Plaintext value; only pass it straight to the broker request that needs it.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString([redacted])")
+    }
+}
+
+/// Broker credentials for one account, decrypted from `accounts.api_key` and
+/// `accounts.api_secret` (each stored as its own `enc:` envelope under `DATABASE_KEY`).
+#[derive(Debug, Clone)]
+pub struct AccountCredentials {
+    pub id: String,
+    pub provider: String,
+    pub environment: String,
+    pub api_key: SecretString,
+    pub api_secret: SecretString,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationResult {
+    #[serde(deserialize_with = "deserialize_parameters_map")]
+    pub parameters: HashMap<String, f64>,
+    #[serde(alias = "cagr", default)]
+    pub cagr: f64,
+    #[serde(alias = "sharpeRatio")]
+    pub sharpe_ratio: f64,
+    #[serde(alias = "totalReturn")]
+    pub total_return: f64,
+    #[serde(alias = "maxDrawdown")]
+    pub max_drawdown: f64,
+    #[serde(alias = "maxDrawdownRatio", default)]
+    pub max_drawdown_ratio: f64,
+    #[serde(alias = "winRate")]
+    pub win_rate: f64,
+    #[serde(alias = "totalTrades")]
+    pub total_trades: i32,
+    #[serde(alias = "calmarRatio", default)]
+    pub calmar_ratio: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub monthly_returns: Vec<MonthlyReturns>,
+    /// Return ratio minus equal-weight buy-and-hold of the same tickers and window.
+    #[serde(
+        alias = "excessReturn",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub excess_return: Option<f64>,
+    #[serde(
+        alias = "capacityAum",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub capacity_aum: Option<f64>,
+    #[serde(
+        alias = "entryWeekConcentration",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub entry_week_concentration: Option<f64>,
+    #[serde(
+        alias = "rExpectancy",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub r_expectancy: Option<f64>,
+}
+
+const STRING_PARAM_NAN_TAG: u64 = 0x7ff8_0000_0000_0000;
+const STRING_PARAM_NAN_MASK: u64 = 0x0007_ffff_ffff_ffff;
+
+static STRING_PARAM_REGISTRY: OnceLock<Mutex<HashMap<u64, String>>> = OnceLock::new();
+static STRING_PARAM_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+fn string_param_registry() -> &'static Mutex<HashMap<u64, String>> {
+    STRING_PARAM_REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn encode_string_parameter(value: &str) -> f64 {
+    let normalized = value.trim();
+    let mut id = STRING_PARAM_COUNTER.fetch_add(1, Ordering::Relaxed) & STRING_PARAM_NAN_MASK;
+    if id == 0 {
+        id = 1;
+    }
+    if let Ok(mut registry) = string_param_registry().lock() {
+        registry.insert(id, normalized.to_string());
+    }
+    f64::from_bits(STRING_PARAM_NAN_TAG | id)
+}
+
+fn decode_string_parameter_value(value: f64) -> Option<String> {
+    let bits = value.to_bits();
+    if bits & STRING_PARAM_NAN_TAG != STRING_PARAM_NAN_TAG {
+        return None;
+    }
+    let id = bits & STRING_PARAM_NAN_MASK;
+    string_param_registry()
+        .lock()
+        .ok()
+        .and_then(|registry| registry.get(&id).cloned())
+}
+
+fn normalize_parameter_map(raw: HashMap<String, Value>) -> HashMap<String, f64> {
+    let mut cleaned = HashMap::with_capacity(raw.len());
+
+    for (key, value) in raw.into_iter() {
+        if let Some(num) = value.as_f64() {
+            if num.is_finite() {
+                cleaned.insert(key, num);
+            } else {
+                warn!(
+                    "Skipping parameter `{}` due to non-finite numeric value {}",
+                    key, value
+                );
+            }
+            continue;
+        }
+
+        if let Some(text) = value.as_str() {
+            let trimmed = text.trim();
+            if trimmed.is_empty() {
+                warn!("Skipping parameter `{}` due to empty string value", key);
+                continue;
+            }
+            match trimmed.parse::<f64>() {
+                Ok(parsed) if parsed.is_finite() => {
+                    cleaned.insert(key, parsed);
+                }
+                Ok(parsed) => {
+                    warn!(
+                        "Skipping parameter `{}` due to non-finite parsed value {}",
+                        key, parsed
+                    );
+                }
+                Err(_) => {
+                    let encoded = encode_string_parameter(trimmed);
+                    cleaned.insert(key, encoded);
+                }
+            }
+            continue;
+        }
+
+        if let Some(boolean) = value.as_bool() {
+            cleaned.insert(key, if boolean { 1.0 } else { 0.0 });
+            continue;
+        }
+
+        if value.is_null() {
+            warn!("Skipping parameter `{}` due to null value", key);
+        } else if value.is_array() || value.is_object() {
+            warn!(
+                "Skipping parameter `{}` due to unsupported composite value {}",
+                key, value
+            );
+        } else {
+            warn!(
+                "Skipping parameter `{}` due to unexpected JSON value {}",
+                key, value
+            );
+        }
+    }
+
+    cleaned
+}
+
+pub fn get_string_parameter(parameters: &HashMap<String, f64>, key: &str) -> Option<String> {
+    parameters
+        .get(key)
+        .and_then(|value| decode_string_parameter_value(*value))
+}
+
+fn deserialize_parameters_map<'de, D>(deserializer: D) -> Result<HashMap<String, f64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = HashMap::<String, Value>::deserialize(deserializer)?;
+    Ok(normalize_parameter_map(raw))
+}
+
+pub fn parse_parameter_map_from_json(json: &str) -> AnyResult<HashMap<String, f64>> {
+    let raw: HashMap<String, Value> =
+        serde_json::from_str(json).map_err(|error| anyhow!("Invalid parameter JSON: {}", error))?;
+    Ok(normalize_parameter_map(raw))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySignal {
+    pub action: SignalAction,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratedSignal {
+    pub date: DateTime<Utc>,
+    pub ticker: String,
+    pub action: SignalAction,
+    pub confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountSignalSkip {
+    pub ticker: String,
+    pub signal_date: DateTime<Utc>,
+    pub action: SignalAction,
+    pub reason: String,
+    pub details: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SignalAction {
+    Buy,
+    Sell,
+    Hold,
+}
+
+impl SignalAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalAction::Buy => "buy",
+            SignalAction::Sell => "sell",
+            SignalAction::Hold => "hold",
+        }
+    }
+}
+
+impl FromStr for SignalAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "buy" => Ok(SignalAction::Buy),
+            "sell" => Ok(SignalAction::Sell),
+            "hold" => Ok(SignalAction::Hold),
+            other => Err(anyhow!("Unknown signal action '{}'", other)),
+        }
+    }
+}
+
+pub fn generate_trade_id(
+    strategy_id: &str,
+    backtest_id: &str,
+    ticker: &str,
+    date: DateTime<Utc>,
+) -> String {
+    format!(
+        "{}_{}_{}_{}",
+        strategy_id,
+        backtest_id,
+        ticker,
+        date.format("%Y-%m-%d")
+    )
+}
+
+pub fn generate_signal_id(strategy_id: &str, ticker: &str, date: DateTime<Utc>) -> String {
+    format!("{}_{}_{}", strategy_id, ticker, date.format("%Y-%m-%d"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerInfo {
+    pub symbol: String,
+    pub name: Option<String>,
+    pub tradable: bool,
+    pub shortable: bool,
+    pub easy_to_borrow: bool,
+    #[serde(default)]
+    pub asset_type: Option<String>,
+    #[serde(default)]
+    pub expense_ratio: Option<f64>,
+    pub market_cap: Option<f64>,
+    pub volume_usd: Option<f64>,
+    pub max_fluctuation_ratio: Option<f64>,
+    pub last_updated: Option<DateTime<Utc>>,
+    pub candle_count: Option<i64>,
+    pub training: bool,
+    /// Series such as a volatility index that is read by features and filters, never traded.
+    #[serde(default)]
+    pub auxiliary: bool,
+}
+
+// API response structures for caching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct ApiCheckResponse {
+    pub exists: bool,
+    pub result: Option<OptimizationResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct ApiStoreResponse {
+    pub success: bool,
+    pub message: Option<String>,
+}
+
+// Performance calculation structures
+#[derive(Debug, Clone)]
+pub struct DrawdownInfo {
+    pub max_drawdown: f64,
+    pub max_drawdown_percent: f64,
+}
+
+// Worker communication structures
+#[derive(Debug, Clone)]
+pub struct BacktestTask {
+    pub id: String,
+    pub template_id: String,
+    pub parameters: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestTaskResult {
+    pub _task_id: String,
+    pub result: Option<OptimizationResult>,
+    pub _error: Option<String>,
+}
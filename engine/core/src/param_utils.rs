@@ -146,18 +146,38 @@ pub fn parameter_is_inactive(param_name: &str, params: &HashMap<String, f64>) ->
         "initialCapital" | "maxLeverage" => true,
         "stopLossRatio" => {
             if let Some(mode) = rounded_param(params, "stopLossMode") {
-                mode == 1
+                mode == 1 || mode == 2
             } else {
                 false
             }
         }
         "atrPeriod" | "atrMultiplier" => {
             if let Some(mode) = rounded_param(params, "stopLossMode") {
-                mode != 1
+                mode != 1 && mode != 2
             } else {
                 false
             }
         }
+        "breakevenRMultiple" => {
+            if let Some(mode) = rounded_param(params, "stopLossMode") {
+                mode != 4
+            } else {
+                false
+            }
+        }
+        "scaleOutDays1" | "scaleOutDays2" | "scaleOutDays3" => {
+            finite_param(params, "scaleOutFraction")
+                .map(|fraction| fraction <= 0.0)
+                .unwrap_or(false)
+        }
+        "circuitBreakerDays" | "circuitBreakerLiquidate" => {
+            finite_param(params, "circuitBreakerLoss")
+                .map(|loss| loss <= 0.0)
+                .unwrap_or(false)
+        }
+        "hedgeRebalanceThreshold" => finite_param(params, "hedgeBeta")
+            .map(|beta| beta <= 0.0)
+            .unwrap_or(false),
         "volTargetAnnual" => {
             if let (Some(mode), Some(vol_target)) = (
                 rounded_param(params, "positionSizingMode"),
@@ -169,11 +189,14 @@ pub fn parameter_is_inactive(param_name: &str, params: &HashMap<String, f64>) ->
             }
         }
         "volLookback" => {
+            let holding_vol_off = finite_param(params, "holdingVolTarget")
+                .map(|target| target <= 0.0)
+                .unwrap_or(true);
             if let (Some(mode), Some(vol_target)) = (
                 rounded_param(params, "positionSizingMode"),
                 finite_param(params, "volTargetAnnual"),
             ) {
-                (mode != 2 && mode != 3) || vol_target <= 0.0
+                ((mode != 2 && mode != 3) || vol_target <= 0.0) && holding_vol_off
             } else {
                 false
             }
@@ -0,0 +1,1112 @@
+use crate::models::*;
+use crate::trading_rules::average_dollar_volume;
+use chrono::{DateTime, Datelike, Utc};
+use statrs::statistics::Statistics;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+pub struct PerformanceCalculator;
+
+const CAPACITY_DOLLAR_VOLUME_LOOKBACK: usize = 20;
+/// Bucket boundaries of the R-multiple histogram; values at an edge fall in the upper bucket.
+const R_MULTIPLE_HISTOGRAM_EDGES: [f64; 6] = [-2.0, -1.0, 0.0, 1.0, 2.0, 3.0];
+/// Trade count beyond which the system quality number stops growing with sample size.
+const SQN_MAX_TRADES: f64 = 100.0;
+/// Calendar-day boundaries of the holding period buckets.
+const HOLDING_PERIOD_EDGES: [i64; 8] = [1, 3, 7, 14, 30, 60, 90, 180];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotResolution {
+    Weekly,
+    Monthly,
+}
+
+impl SnapshotResolution {
+    fn period_key(self, date: DateTime<Utc>) -> (i32, u32) {
+        match self {
+            SnapshotResolution::Weekly => {
+                let week = date.iso_week();
+                (week.year(), week.week())
+            }
+            SnapshotResolution::Monthly => (date.year(), date.month()),
+        }
+    }
+}
+
+impl PerformanceCalculator {
+    pub fn calculate_performance(
+        trades: &[Trade],
+        initial_capital: f64,
+        final_portfolio_value: f64,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        daily_snapshots: &[BacktestDataPoint],
+    ) -> StrategyPerformance {
+        let executed_trades: Vec<&Trade> = trades.iter().collect();
+        let total_trades = executed_trades.len() as i32;
+
+        let mut trade_pnls = Vec::with_capacity(executed_trades.len());
+        let mut trade_pnl_percents = Vec::with_capacity(executed_trades.len());
+        let mut winning_trade_pnls = Vec::new();
+        let mut winning_trade_percents = Vec::new();
+        let mut losing_trade_pnls = Vec::new();
+        let mut losing_trade_percents = Vec::new();
+
+        for trade in executed_trades.iter().copied() {
+            let pnl = trade.pnl.unwrap_or(0.0);
+            let exposure = (trade.price * trade.quantity as f64).abs();
+            let pnl_percent = if exposure > 0.0 {
+                (pnl / exposure) * 100.0
+            } else {
+                0.0
+            };
+
+            trade_pnls.push(pnl);
+            trade_pnl_percents.push(pnl_percent);
+
+            if pnl > 0.0 {
+                winning_trade_pnls.push(pnl);
+                winning_trade_percents.push(pnl_percent);
+            } else if pnl < 0.0 {
+                losing_trade_pnls.push(pnl);
+                losing_trade_percents.push(pnl_percent);
+            }
+        }
+        let winning_trades = winning_trade_pnls.len() as i32;
+        let losing_trades = losing_trade_pnls.len() as i32;
+        let win_rate = if total_trades > 0 {
+            winning_trades as f64 / total_trades as f64
+        } else {
+            0.0
+        };
+
+        let final_portfolio_value = if final_portfolio_value.is_finite() {
+            final_portfolio_value
+        } else {
+            daily_snapshots
+                .last()
+                .map(|s| s.portfolio_value)
+                .unwrap_or(initial_capital)
+        };
+
+        let total_return = if final_portfolio_value.is_finite() {
+            final_portfolio_value - initial_capital
+        } else {
+            0.0
+        };
+
+        let cagr =
+            Self::calculate_cagr(initial_capital, final_portfolio_value, start_date, end_date);
+        let sharpe_ratio = Self::calculate_sharpe_ratio(daily_snapshots);
+        let drawdown_info = Self::calculate_max_drawdown(daily_snapshots);
+        let calmar_ratio = Self::calculate_calmar_ratio(cagr, drawdown_info.max_drawdown_percent);
+
+        let avg_trade_pnl = Self::average(&trade_pnls);
+        let best_trade = if trade_pnls.is_empty() {
+            0.0
+        } else {
+            trade_pnls.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        };
+        let best_trade = if best_trade.is_finite() {
+            best_trade
+        } else {
+            0.0
+        };
+
+        let worst_trade = if trade_pnls.is_empty() {
+            0.0
+        } else {
+            trade_pnls.iter().copied().fold(f64::INFINITY, f64::min)
+        };
+        let worst_trade = if worst_trade.is_finite() {
+            worst_trade
+        } else {
+            0.0
+        };
+
+        let total_tickers = executed_trades
+            .iter()
+            .map(|t| &t.ticker)
+            .collect::<std::collections::HashSet<_>>()
+            .len() as i32;
+
+        let trade_durations: Vec<f64> = executed_trades
+            .iter()
+            .filter_map(|t| {
+                if let (Some(entry_date), Some(exit_date)) = (Some(t.date), t.exit_date) {
+                    Some((exit_date - entry_date).num_days() as f64)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let median_trade_duration = Self::median(&trade_durations);
+        let avg_trade_duration = Self::average(&trade_durations);
+
+        let median_trade_pnl = Self::median(&trade_pnls);
+        let median_trade_pnl_percent = Self::median(&trade_pnl_percents);
+        let avg_trade_pnl_percent = Self::average(&trade_pnl_percents);
+
+        let concurrent_trades: Vec<i32> = daily_snapshots
+            .iter()
+            .map(|s| s.concurrent_trades)
+            .collect();
+        let median_concurrent_trades = if !concurrent_trades.is_empty() {
+            let mut sorted = concurrent_trades.clone();
+            sorted.sort();
+            sorted[sorted.len() / 2] as f64
+        } else {
+            0.0
+        };
+
+        let avg_concurrent_trades = if !concurrent_trades.is_empty() {
+            concurrent_trades.iter().sum::<i32>() as f64 / concurrent_trades.len() as f64
+        } else {
+            0.0
+        };
+
+        let avg_losing_pnl = Self::average(&losing_trade_pnls);
+        let avg_losing_pnl_percent = Self::average(&losing_trade_percents);
+
+        let avg_winning_pnl = Self::average(&winning_trade_pnls);
+        let avg_winning_pnl_percent = Self::average(&winning_trade_percents);
+
+        let r_multiples: Vec<f64> = trades.iter().filter_map(Trade::r_multiple).collect();
+        let (r_expectancy, system_quality_number) = Self::calculate_r_statistics(&r_multiples);
+
+        let (annual_turnover, avg_daily_traded_notional) = Self::calculate_turnover(
+            trades,
+            initial_capital,
+            start_date,
+            end_date,
+            daily_snapshots,
+        );
+
+        StrategyPerformance {
+            total_trades,
+            winning_trades,
+            losing_trades,
+            win_rate,
+            total_return,
+            cagr,
+            sharpe_ratio,
+            calmar_ratio,
+            max_drawdown: drawdown_info.max_drawdown,
+            max_drawdown_percent: drawdown_info.max_drawdown_percent,
+            avg_trade_return: avg_trade_pnl,
+            best_trade,
+            worst_trade,
+            total_tickers,
+            median_trade_duration,
+            median_trade_pnl,
+            median_trade_pnl_percent,
+            median_concurrent_trades,
+            avg_trade_duration,
+            avg_trade_pnl,
+            avg_trade_pnl_percent,
+            avg_concurrent_trades,
+            avg_losing_pnl,
+            avg_losing_pnl_percent,
+            avg_winning_pnl,
+            avg_winning_pnl_percent,
+            last_updated: Utc::now(),
+            monthly_returns: Self::calculate_monthly_returns(initial_capital, daily_snapshots),
+            annual_turnover,
+            avg_daily_traded_notional,
+            capacity_aum: None,
+            entry_week_concentration: Self::calculate_entry_week_concentration(trades),
+            r_expectancy,
+            r_multiple_histogram: Self::calculate_r_multiple_histogram(&r_multiples),
+            system_quality_number,
+            holding_period_returns: Self::calculate_holding_period_returns(trades),
+        }
+    }
+
+    /// Groups closed trades by calendar days held into HOLDING_PERIOD_EDGES buckets with the
+    /// average return and win rate of each, skipping empty buckets.
+    pub fn calculate_holding_period_returns(trades: &[Trade]) -> Vec<HoldingPeriodBucket> {
+        let mut returns_by_bucket: Vec<Vec<f64>> = vec![Vec::new(); HOLDING_PERIOD_EDGES.len() + 1];
+        for trade in trades {
+            if trade.status != TradeStatus::Closed {
+                continue;
+            }
+            let Some(exit_date) = trade.exit_date else {
+                continue;
+            };
+            let exposure = (trade.price * trade.quantity as f64).abs();
+            let pnl = trade.pnl.unwrap_or(0.0);
+            if !(exposure > 0.0 && pnl.is_finite()) {
+                continue;
+            }
+            let days_held = (exit_date - trade.date).num_days();
+            let index = HOLDING_PERIOD_EDGES.partition_point(|edge| *edge <= days_held);
+            returns_by_bucket[index].push(pnl / exposure * 100.0);
+        }
+        returns_by_bucket
+            .into_iter()
+            .enumerate()
+            .filter(|(_, returns)| !returns.is_empty())
+            .map(|(index, returns)| {
+                let wins = returns.iter().filter(|value| **value > 0.0).count();
+                HoldingPeriodBucket {
+                    min_days: index
+                        .checked_sub(1)
+                        .map_or(0, |lower| HOLDING_PERIOD_EDGES[lower]),
+                    max_days: HOLDING_PERIOD_EDGES.get(index).copied(),
+                    trades: returns.len() as i32,
+                    avg_return_percent: Self::average(&returns),
+                    win_rate: wins as f64 / returns.len() as f64,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the mean R-multiple and the system quality number of `r_multiples`; the latter
+    /// needs at least two trades with differing R.
+    pub fn calculate_r_statistics(r_multiples: &[f64]) -> (Option<f64>, Option<f64>) {
+        if r_multiples.is_empty() {
+            return (None, None);
+        }
+        let count = r_multiples.len() as f64;
+        let mean = r_multiples.iter().sum::<f64>() / count;
+        if r_multiples.len() < 2 {
+            return (Some(mean), None);
+        }
+        let variance = r_multiples.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (count - 1.0);
+        let std_dev = variance.sqrt();
+        let sqn = (std_dev > 0.0).then(|| count.min(SQN_MAX_TRADES).sqrt() * mean / std_dev);
+        (Some(mean), sqn)
+    }
+
+    /// Counts `r_multiples` into buckets split at R_MULTIPLE_HISTOGRAM_EDGES; empty without
+    /// R-multiples.
+    pub fn calculate_r_multiple_histogram(r_multiples: &[f64]) -> Vec<RMultipleBucket> {
+        if r_multiples.is_empty() {
+            return Vec::new();
+        }
+        let mut buckets: Vec<RMultipleBucket> = (0..=R_MULTIPLE_HISTOGRAM_EDGES.len())
+            .map(|index| RMultipleBucket {
+                min: index
+                    .checked_sub(1)
+                    .map(|lower| R_MULTIPLE_HISTOGRAM_EDGES[lower]),
+                max: R_MULTIPLE_HISTOGRAM_EDGES.get(index).copied(),
+                count: 0,
+            })
+            .collect();
+        for r in r_multiples {
+            let index = R_MULTIPLE_HISTOGRAM_EDGES.partition_point(|edge| edge <= r);
+            buckets[index].count += 1;
+        }
+        buckets
+    }
+
+    /// Share of total net P&L earned by the trades entered in the single most profitable ISO
+    /// week, which flags results carried by one event. `None` when total P&L is not positive.
+    pub fn calculate_entry_week_concentration(trades: &[Trade]) -> Option<f64> {
+        let mut pnl_by_week: HashMap<(i32, u32), f64> = HashMap::new();
+        for trade in trades {
+            let Some(pnl) = trade.pnl.filter(|pnl| pnl.is_finite()) else {
+                continue;
+            };
+            let week = trade.date.iso_week();
+            *pnl_by_week.entry((week.year(), week.week())).or_default() += pnl;
+        }
+        let total: f64 = pnl_by_week.values().sum();
+        let best_week = pnl_by_week.values().copied().fold(f64::MIN, f64::max);
+        (total > 0.0).then(|| best_week / total)
+    }
+
+    /// Returns annual turnover (entry plus exit notional per year over average portfolio
+    /// value) and the average traded notional per snapshot day.
+    fn calculate_turnover(
+        trades: &[Trade],
+        initial_capital: f64,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        daily_snapshots: &[BacktestDataPoint],
+    ) -> (f64, f64) {
+        let traded_notional: f64 = trades
+            .iter()
+            .map(|trade| {
+                let quantity = trade.quantity.abs() as f64;
+                let exit = trade.exit_price.map(|price| price.abs()).unwrap_or(0.0);
+                (trade.price.abs() + exit) * quantity
+            })
+            .filter(|notional| notional.is_finite())
+            .sum();
+
+        let portfolio_values: Vec<f64> = daily_snapshots
+            .iter()
+            .map(|snapshot| snapshot.portfolio_value)
+            .filter(|value| value.is_finite())
+            .collect();
+        let average_value = if portfolio_values.is_empty() {
+            initial_capital
+        } else {
+            Self::average(&portfolio_values)
+        };
+        let years = (end_date - start_date).num_seconds() as f64 / (365.25 * 24.0 * 60.0 * 60.0);
+        let annual_turnover = if average_value > 0.0 && years > 0.0 {
+            traded_notional / average_value / years
+        } else {
+            0.0
+        };
+        let avg_daily_traded_notional = if daily_snapshots.is_empty() {
+            0.0
+        } else {
+            traded_notional / daily_snapshots.len() as f64
+        };
+        (annual_turnover, avg_daily_traded_notional)
+    }
+
+    /// Estimates the largest AUM at which the average entry stays within `max_volume_share`
+    /// of its ticker's average dollar volume over the 20 candles before entry. Entry
+    /// sizes scale with AUM, so each trade contributes its share of the portfolio at entry
+    /// divided by that dollar volume. `None` when no entry has a priced history.
+    pub fn calculate_capacity(
+        trades: &[Trade],
+        initial_capital: f64,
+        daily_snapshots: &[BacktestDataPoint],
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        max_volume_share: f64,
+    ) -> Option<f64> {
+        if max_volume_share <= 0.0 {
+            return None;
+        }
+        let ratios: Vec<f64> = trades
+            .iter()
+            .filter_map(|trade| {
+                let candles = candles_by_ticker.get(&trade.ticker)?;
+                let history =
+                    &candles[..candles.partition_point(|candle| candle.date < trade.date)];
+                let dollar_volume =
+                    average_dollar_volume(history, CAPACITY_DOLLAR_VOLUME_LOOKBACK)?;
+                let snapshot_index =
+                    daily_snapshots.partition_point(|snapshot| snapshot.date <= trade.date);
+                let portfolio_value = snapshot_index
+                    .checked_sub(1)
+                    .map(|index| daily_snapshots[index].portfolio_value)
+                    .unwrap_or(initial_capital);
+                let notional = (trade.price * trade.quantity as f64).abs();
+                (portfolio_value > 0.0 && notional.is_finite())
+                    .then(|| notional / portfolio_value / dollar_volume)
+            })
+            .collect();
+        let average_ratio = Self::average(&ratios);
+        (average_ratio > 0.0).then(|| max_volume_share / average_ratio)
+    }
+
+    /// Builds a year × month return calendar from month-end portfolio values. Each month is
+    /// measured against the previous month end (initial capital for the first month) and
+    /// YTD compounds the months of that year present in the window.
+    pub fn calculate_monthly_returns(
+        initial_capital: f64,
+        daily_snapshots: &[BacktestDataPoint],
+    ) -> Vec<MonthlyReturns> {
+        let month_ends = Self::rollup_snapshots(daily_snapshots, SnapshotResolution::Monthly);
+        let mut previous_value = match month_ends.first() {
+            Some(_) if initial_capital > 0.0 => initial_capital,
+            Some(first) => first.portfolio_value,
+            None => return Vec::new(),
+        };
+
+        let mut rows: Vec<MonthlyReturns> = Vec::new();
+        for snapshot in &month_ends {
+            let value = snapshot.portfolio_value;
+            let month_return = if previous_value > 0.0 && value.is_finite() {
+                value / previous_value - 1.0
+            } else {
+                0.0
+            };
+            previous_value = value;
+
+            let year = snapshot.date.year();
+            if rows.last().map(|row| row.year) != Some(year) {
+                rows.push(MonthlyReturns {
+                    year,
+                    months: [None; 12],
+                    ytd: 0.0,
+                });
+            }
+            if let Some(row) = rows.last_mut() {
+                row.months[snapshot.date.month0() as usize] = Some(month_return);
+                row.ytd = (1.0 + row.ytd) * (1.0 + month_return) - 1.0;
+            }
+        }
+        rows
+    }
+
+    fn calculate_cagr(
+        initial_capital: f64,
+        final_portfolio_value: f64,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> f64 {
+        if initial_capital <= 0.0 || !final_portfolio_value.is_finite() {
+            return 0.0;
+        }
+
+        if end_date <= start_date {
+            return 0.0;
+        }
+
+        let duration = end_date - start_date;
+        let years = duration.num_seconds() as f64 / (365.25_f64 * 24.0 * 60.0 * 60.0);
+
+        if years <= 0.0 {
+            return 0.0;
+        }
+
+        let total_return_ratio = final_portfolio_value / initial_capital;
+        if total_return_ratio <= 0.0 {
+            return -1.0;
+        }
+
+        total_return_ratio.powf(1.0 / years) - 1.0
+    }
+
+    fn average(values: &[f64]) -> f64 {
+        let mut sum = 0.0;
+        let mut count = 0usize;
+
+        for value in values.iter().copied() {
+            if value.is_finite() {
+                sum += value;
+                count += 1;
+            }
+        }
+
+        if count == 0 {
+            0.0
+        } else {
+            sum / count as f64
+        }
+    }
+
+    fn median(values: &[f64]) -> f64 {
+        let mut filtered: Vec<f64> = values
+            .iter()
+            .copied()
+            .filter(|value| value.is_finite())
+            .collect();
+
+        if filtered.is_empty() {
+            return 0.0;
+        }
+
+        filtered.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let mid = filtered.len() / 2;
+
+        if filtered.len().is_multiple_of(2) {
+            (filtered[mid - 1] + filtered[mid]) / 2.0
+        } else {
+            filtered[mid]
+        }
+    }
+
+    pub fn calculate_sharpe_ratio(daily_snapshots: &[BacktestDataPoint]) -> f64 {
+        if daily_snapshots.len() < 2 {
+            return 0.0;
+        }
+
+        let returns: Vec<f64> = daily_snapshots
+            .windows(2)
+            .map(|window| {
+                let prev_value = window[0].portfolio_value;
+                let curr_value = window[1].portfolio_value;
+                if prev_value > 0.0 {
+                    (curr_value - prev_value) / prev_value
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        if returns.is_empty() {
+            return 0.0;
+        }
+
+        let mean_return = returns.clone().mean();
+        let std_dev = returns.std_dev();
+
+        if std_dev == 0.0 {
+            return 0.0;
+        }
+
+        // Annualize the Sharpe ratio (assuming daily returns)
+        let annualized_return = mean_return * 252.0;
+        let annualized_volatility = std_dev * (252.0_f64).sqrt();
+        let risk_free_rate = 0.02; // 2% risk-free rate
+
+        (annualized_return - risk_free_rate) / annualized_volatility
+    }
+
+    fn calculate_calmar_ratio(cagr: f64, max_drawdown_percent: f64) -> f64 {
+        if !cagr.is_finite() || !max_drawdown_percent.is_finite() {
+            return 0.0;
+        }
+
+        let drawdown_ratio = (max_drawdown_percent / 100.0).abs();
+        if drawdown_ratio <= f64::EPSILON {
+            return 0.0;
+        }
+
+        cagr / drawdown_ratio
+    }
+
+    fn calculate_max_drawdown(daily_snapshots: &[BacktestDataPoint]) -> DrawdownInfo {
+        if daily_snapshots.is_empty() {
+            return DrawdownInfo {
+                max_drawdown: 0.0,
+                max_drawdown_percent: 0.0,
+            };
+        }
+
+        let mut max_drawdown = 0.0;
+        let mut max_drawdown_percent = 0.0;
+        let mut peak_value = daily_snapshots[0].portfolio_value;
+
+        for snapshot in daily_snapshots {
+            if snapshot.portfolio_value > peak_value {
+                peak_value = snapshot.portfolio_value;
+            } else {
+                let drawdown = peak_value - snapshot.portfolio_value;
+                let drawdown_percent = if peak_value > 0.0 {
+                    (drawdown / peak_value) * 100.0
+                } else {
+                    0.0
+                };
+
+                if drawdown > max_drawdown {
+                    max_drawdown = drawdown;
+                }
+                if drawdown_percent > max_drawdown_percent {
+                    max_drawdown_percent = drawdown_percent;
+                }
+            }
+        }
+
+        DrawdownInfo {
+            max_drawdown,
+            max_drawdown_percent,
+        }
+    }
+
+    /// Keeps the last daily snapshot of each ISO week or calendar month, so long-horizon charts
+    /// can plot period closes without loading every day.
+    pub fn rollup_snapshots(
+        daily_snapshots: &[BacktestDataPoint],
+        resolution: SnapshotResolution,
+    ) -> Vec<BacktestDataPoint> {
+        let mut rollup: Vec<BacktestDataPoint> = Vec::new();
+        let mut current_key = None;
+        for snapshot in daily_snapshots {
+            let key = resolution.period_key(snapshot.date);
+            if current_key == Some(key) {
+                if let Some(last) = rollup.last_mut() {
+                    *last = snapshot.clone();
+                }
+            } else {
+                rollup.push(snapshot.clone());
+                current_key = Some(key);
+            }
+        }
+        rollup
+    }
+
+    /// Return ratio of splitting capital equally across the tickers on the first trading day
+    /// in the window and holding to the end. Tickers without two priced candles in the window
+    /// are ignored; `None` when no ticker qualifies.
+    pub fn calculate_equal_weight_buy_and_hold_return(
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Option<f64> {
+        let ticker_returns: Vec<f64> = candles_by_ticker
+            .values()
+            .filter_map(|candles| {
+                let mut window = candles
+                    .iter()
+                    .filter(|candle| candle.date >= start_date && candle.date <= end_date);
+                let first = window.next()?;
+                let last = window.next_back()?;
+                if first.close > 0.0 && last.close.is_finite() {
+                    Some(last.close / first.close - 1.0)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if ticker_returns.is_empty() {
+            return None;
+        }
+        Some(ticker_returns.iter().sum::<f64>() / ticker_returns.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(dead_code)]
+
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn calculates_total_return_and_cagr_using_backtest_dates() {
+        let start_date = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let end_date = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+
+        let daily_snapshots = vec![
+            BacktestDataPoint {
+                date: start_date,
+                portfolio_value: 100_000.0,
+                cash: 100_000.0,
+                positions_value: 0.0,
+                concurrent_trades: 0,
+                missed_trades_due_to_cash: 0,
+            },
+            BacktestDataPoint {
+                date: end_date,
+                portfolio_value: 121_000.0,
+                cash: 121_000.0,
+                positions_value: 0.0,
+                concurrent_trades: 0,
+                missed_trades_due_to_cash: 0,
+            },
+        ];
+
+        let performance = PerformanceCalculator::calculate_performance(
+            &[],
+            100_000.0,
+            121_000.0,
+            start_date,
+            end_date,
+            &daily_snapshots,
+        );
+
+        assert!((performance.total_return - 21_000.0).abs() < 1e-9);
+        let duration_seconds = (end_date - start_date).num_seconds() as f64;
+        let years = duration_seconds / (365.25_f64 * 24.0 * 60.0 * 60.0);
+        let total_return_ratio = 121_000.0_f64 / 100_000.0_f64;
+        let expected_cagr = total_return_ratio.powf(1.0_f64 / years) - 1.0_f64;
+        assert!((performance.cagr - expected_cagr).abs() < 1e-9);
+    }
+
+    #[test]
+    fn computes_trade_percentages_relative_to_exposure() {
+        let start_date = Utc.with_ymd_and_hms(2022, 6, 1, 0, 0, 0).unwrap();
+        let mid_date = Utc.with_ymd_and_hms(2022, 6, 10, 0, 0, 0).unwrap();
+        let end_date = Utc.with_ymd_and_hms(2022, 6, 30, 0, 0, 0).unwrap();
+
+        let trades = vec![
+            Trade {
+                id: "t1".to_string(),
+                strategy_id: "s1".to_string(),
+                ticker: "AAA".to_string(),
+                quantity: 10,
+                price: 100.0,
+                date: start_date,
+                status: TradeStatus::Closed,
+                pnl: Some(100.0),
+                fee: None,
+                exit_price: Some(110.0),
+                exit_date: Some(mid_date),
+                stop_loss: None,
+                stop_loss_triggered: Some(false),
+                entry_order_id: None,
+                entry_cancel_after: None,
+                stop_order_id: None,
+                exit_order_id: None,
+                target_order_id: None,
+                changes: Vec::new(),
+            },
+            Trade {
+                id: "t2".to_string(),
+                strategy_id: "s1".to_string(),
+                ticker: "BBB".to_string(),
+                quantity: 10,
+                price: 50.0,
+                date: start_date,
+                status: TradeStatus::Closed,
+                pnl: Some(-25.0),
+                fee: None,
+                exit_price: Some(47.5),
+                exit_date: Some(end_date),
+                stop_loss: None,
+                stop_loss_triggered: Some(false),
+                entry_order_id: None,
+                entry_cancel_after: None,
+                stop_order_id: None,
+                exit_order_id: None,
+                target_order_id: None,
+                changes: Vec::new(),
+            },
+        ];
+
+        let daily_snapshots = vec![
+            BacktestDataPoint {
+                date: start_date,
+                portfolio_value: 10_000.0,
+                cash: 10_000.0,
+                positions_value: 0.0,
+                concurrent_trades: 0,
+                missed_trades_due_to_cash: 0,
+            },
+            BacktestDataPoint {
+                date: end_date,
+                portfolio_value: 10_050.0,
+                cash: 10_050.0,
+                positions_value: 0.0,
+                concurrent_trades: 0,
+                missed_trades_due_to_cash: 0,
+            },
+        ];
+
+        let performance = PerformanceCalculator::calculate_performance(
+            &trades,
+            10_000.0,
+            10_050.0,
+            start_date,
+            end_date,
+            &daily_snapshots,
+        );
+
+        assert!((performance.avg_trade_pnl_percent - 2.5).abs() < 1e-9);
+        assert!((performance.median_trade_pnl_percent - 2.5).abs() < 1e-9);
+        assert!((performance.avg_winning_pnl_percent - 10.0).abs() < 1e-9);
+        assert!((performance.avg_losing_pnl_percent + 5.0).abs() < 1e-9);
+        assert!((performance.total_return - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn equal_weight_baseline_averages_ticker_returns_inside_window() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+        let candle = |ticker: &str, d: u32, close: f64| Candle {
+            ticker: ticker.to_string(),
+            date: day(d),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            unadjusted_close: None,
+            volume_shares: 1_000,
+        };
+        let candles = vec![
+            candle("AAA", 1, 50.0),
+            candle("AAA", 2, 100.0),
+            candle("AAA", 5, 150.0),
+            candle("AAA", 9, 400.0),
+            candle("BBB", 2, 20.0),
+            candle("BBB", 5, 18.0),
+            candle("CCC", 3, 10.0),
+        ];
+        let mut candles_by_ticker: HashMap<String, Vec<&Candle>> = HashMap::new();
+        for candle in &candles {
+            candles_by_ticker
+                .entry(candle.ticker.clone())
+                .or_default()
+                .push(candle);
+        }
+
+        let baseline = PerformanceCalculator::calculate_equal_weight_buy_and_hold_return(
+            &candles_by_ticker,
+            day(2),
+            day(5),
+        )
+        .unwrap();
+        assert!((baseline - 0.2).abs() < 1e-9);
+
+        assert!(
+            PerformanceCalculator::calculate_equal_weight_buy_and_hold_return(
+                &candles_by_ticker,
+                day(3),
+                day(4),
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn rollups_keep_last_snapshot_of_each_period() {
+        let snapshot = |month: u32, day: u32, value: f64| BacktestDataPoint {
+            date: Utc.with_ymd_and_hms(2024, month, day, 0, 0, 0).unwrap(),
+            portfolio_value: value,
+            cash: value,
+            positions_value: 0.0,
+            concurrent_trades: 0,
+            missed_trades_due_to_cash: 0,
+        };
+        // 2024-01-29 (Mon) through 2024-02-06 (Tue) spans two months and two ISO weeks.
+        let daily = vec![
+            snapshot(1, 29, 100.0),
+            snapshot(1, 31, 101.0),
+            snapshot(2, 2, 102.0),
+            snapshot(2, 5, 103.0),
+            snapshot(2, 6, 104.0),
+        ];
+
+        let values = |points: Vec<BacktestDataPoint>| -> Vec<f64> {
+            points.iter().map(|point| point.portfolio_value).collect()
+        };
+        assert_eq!(
+            values(PerformanceCalculator::rollup_snapshots(
+                &daily,
+                SnapshotResolution::Weekly
+            )),
+            vec![102.0, 104.0]
+        );
+        assert_eq!(
+            values(PerformanceCalculator::rollup_snapshots(
+                &daily,
+                SnapshotResolution::Monthly
+            )),
+            vec![101.0, 104.0]
+        );
+    }
+
+    #[test]
+    fn monthly_returns_compound_into_ytd_per_year() {
+        let snapshot = |year: i32, month: u32, day: u32, value: f64| BacktestDataPoint {
+            date: Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap(),
+            portfolio_value: value,
+            cash: value,
+            positions_value: 0.0,
+            concurrent_trades: 0,
+            missed_trades_due_to_cash: 0,
+        };
+        let daily = vec![
+            snapshot(2023, 11, 15, 1_050.0),
+            snapshot(2023, 11, 30, 1_100.0),
+            snapshot(2023, 12, 29, 990.0),
+            snapshot(2024, 1, 31, 1_089.0),
+        ];
+
+        let rows = PerformanceCalculator::calculate_monthly_returns(1_000.0, &daily);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].year, 2023);
+        assert!(rows[0].months[..10].iter().all(Option::is_none));
+        assert!((rows[0].months[10].unwrap() - 0.1).abs() < 1e-9);
+        assert!((rows[0].months[11].unwrap() + 0.1).abs() < 1e-9);
+        assert!((rows[0].ytd + 0.01).abs() < 1e-9);
+        assert_eq!(rows[1].year, 2024);
+        assert!((rows[1].months[0].unwrap() - 0.1).abs() < 1e-9);
+        assert!((rows[1].ytd - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn capacity_scales_entry_share_against_dollar_volume() {
+        let day = |d: u32| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+        let candles: Vec<Candle> = (1..=25)
+            .map(|d| Candle {
+                ticker: "AAA".to_string(),
+                date: day(d),
+                open: 100.0,
+                high: 100.0,
+                low: 100.0,
+                close: 100.0,
+                unadjusted_close: None,
+                // Volume after the entry day must not count toward its dollar volume.
+                volume_shares: if d < 21 { 1_000 } else { 1_000_000 },
+            })
+            .collect();
+        let candles_by_ticker = HashMap::from([("AAA".to_string(), candles.iter().collect())]);
+        let snapshots: Vec<BacktestDataPoint> = (1..=25)
+            .map(|d| BacktestDataPoint {
+                date: day(d),
+                portfolio_value: 10_000.0,
+                cash: 10_000.0,
+                positions_value: 0.0,
+                concurrent_trades: 0,
+                missed_trades_due_to_cash: 0,
+            })
+            .collect();
+        let trade = Trade {
+            id: "t1".to_string(),
+            strategy_id: "s1".to_string(),
+            ticker: "AAA".to_string(),
+            quantity: 10,
+            price: 100.0,
+            date: day(21),
+            status: TradeStatus::Closed,
+            pnl: Some(100.0),
+            fee: None,
+            exit_price: Some(110.0),
+            exit_date: Some(day(24)),
+            stop_loss: None,
+            stop_loss_triggered: Some(false),
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+
+        // A 10% position against $100k of daily dollar volume stays within 1% up to $10k AUM.
+        let capacity = PerformanceCalculator::calculate_capacity(
+            std::slice::from_ref(&trade),
+            10_000.0,
+            &snapshots,
+            &candles_by_ticker,
+            0.01,
+        )
+        .unwrap();
+        assert!((capacity - 10_000.0).abs() < 1e-6);
+
+        let (annual_turnover, avg_daily) = PerformanceCalculator::calculate_turnover(
+            &[trade],
+            10_000.0,
+            day(1),
+            day(25),
+            &snapshots,
+        );
+        assert!((avg_daily - 2_100.0 / 25.0).abs() < 1e-9);
+        let years = (day(25) - day(1)).num_seconds() as f64 / (365.25 * 24.0 * 60.0 * 60.0);
+        assert!((annual_turnover - 0.21 / years).abs() < 1e-9);
+    }
+
+    #[test]
+    fn entry_week_concentration_measures_the_best_week_against_total_pnl() {
+        // 2024-01-01 is a Monday, so days 1-7, 8-14 and 15-21 are separate ISO weeks.
+        let trade = |day: u32, pnl: f64| Trade {
+            id: format!("t{}", day),
+            strategy_id: "s1".to_string(),
+            ticker: "AAA".to_string(),
+            quantity: 10,
+            price: 100.0,
+            date: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            status: TradeStatus::Closed,
+            pnl: Some(pnl),
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: None,
+            stop_loss_triggered: Some(false),
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+        let mut trades = vec![
+            trade(1, 200.0),
+            trade(5, 100.0),
+            trade(8, 100.0),
+            trade(15, 100.0),
+        ];
+        let concentration = PerformanceCalculator::calculate_entry_week_concentration(&trades);
+        assert!((concentration.unwrap() - 0.6).abs() < 1e-9);
+
+        trades.push(trade(16, -300.0));
+        let concentration = PerformanceCalculator::calculate_entry_week_concentration(&trades);
+        assert!((concentration.unwrap() - 1.5).abs() < 1e-9);
+
+        assert_eq!(
+            PerformanceCalculator::calculate_entry_week_concentration(&[trade(1, -10.0)]),
+            None
+        );
+    }
+
+    #[test]
+    fn r_statistics_measure_pnl_against_the_initial_stop() {
+        let trade = |quantity: i32, stop: Option<f64>, pnl: f64| Trade {
+            id: "t1".to_string(),
+            strategy_id: "s1".to_string(),
+            ticker: "AAA".to_string(),
+            quantity,
+            price: 100.0,
+            date: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            status: TradeStatus::Closed,
+            pnl: Some(pnl),
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: stop,
+            stop_loss_triggered: Some(false),
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+        // Risking $5 a share on 10 shares makes R $50.
+        let mut trailed = trade(10, Some(95.0), 150.0);
+        trailed.set_stop_loss(
+            Some(110.0),
+            Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap(),
+        );
+        assert_eq!(trailed.r_multiple(), Some(3.0));
+        assert_eq!(trade(-10, Some(105.0), -50.0).r_multiple(), Some(-1.0));
+        assert_eq!(trade(10, None, 50.0).r_multiple(), None);
+        assert_eq!(trade(10, Some(105.0), 50.0).r_multiple(), None);
+
+        let (expectancy, sqn) = PerformanceCalculator::calculate_r_statistics(&[3.0, -1.0]);
+        assert_eq!(expectancy, Some(1.0));
+        let std_dev = 8.0_f64.sqrt();
+        assert!((sqn.unwrap() - 2.0_f64.sqrt() / std_dev).abs() < 1e-12);
+        assert_eq!(
+            PerformanceCalculator::calculate_r_statistics(&[0.5]),
+            (Some(0.5), None)
+        );
+        assert_eq!(
+            PerformanceCalculator::calculate_r_statistics(&[]),
+            (None, None)
+        );
+
+        let histogram =
+            PerformanceCalculator::calculate_r_multiple_histogram(&[-2.5, -1.0, -0.5, 3.0, 7.0]);
+        let counts: Vec<i32> = histogram.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, vec![1, 0, 2, 0, 0, 0, 2]);
+        assert_eq!(histogram[0].min, None);
+        assert_eq!(histogram[0].max, Some(-2.0));
+        assert_eq!(histogram[6].min, Some(3.0));
+        assert_eq!(histogram[6].max, None);
+        assert!(PerformanceCalculator::calculate_r_multiple_histogram(&[]).is_empty());
+    }
+
+    #[test]
+    fn holding_period_returns_bucket_closed_trades_by_days_held() {
+        let entry = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        let trade = |days: i64, pnl: f64, status: TradeStatus| Trade {
+            id: format!("t{}", days),
+            strategy_id: "s1".to_string(),
+            ticker: "AAA".to_string(),
+            quantity: 10,
+            price: 100.0,
+            date: entry,
+            status,
+            pnl: Some(pnl),
+            fee: None,
+            exit_price: None,
+            exit_date: Some(entry + chrono::Duration::days(days)),
+            stop_loss: None,
+            stop_loss_triggered: Some(false),
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+        let trades = vec![
+            trade(0, 10.0, TradeStatus::Closed),
+            trade(3, 50.0, TradeStatus::Closed),
+            trade(6, -30.0, TradeStatus::Closed),
+            trade(200, 100.0, TradeStatus::Closed),
+            trade(5, 500.0, TradeStatus::Active),
+        ];
+        let buckets = PerformanceCalculator::calculate_holding_period_returns(&trades);
+        assert_eq!(buckets.len(), 3);
+        assert_eq!((buckets[0].min_days, buckets[0].max_days), (0, Some(1)));
+        assert_eq!((buckets[1].min_days, buckets[1].max_days), (3, Some(7)));
+        assert_eq!(buckets[1].trades, 2);
+        assert!((buckets[1].avg_return_percent - 1.0).abs() < 1e-9);
+        assert!((buckets[1].win_rate - 0.5).abs() < 1e-9);
+        assert_eq!((buckets[2].min_days, buckets[2].max_days), (180, None));
+        assert!((buckets[2].avg_return_percent - 10.0).abs() < 1e-9);
+    }
+}
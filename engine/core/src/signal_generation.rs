@@ -0,0 +1,142 @@
+use crate::blackout::{BlackoutCalendar, BLACKOUT_SKIP_REASON};
+use crate::models::{AccountSignalSkip, Candle, GeneratedSignal, SignalAction};
+use crate::strategy::{Strategy, StrategyContext};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+
+/// Skip reason of tickers with fewer candles than `MIN_TICKER_HISTORY_CANDLES`.
+pub const INSUFFICIENT_HISTORY_REASON: &str = "insufficient_history";
+
+/// Builds a `GeneratedSignal` when the action is tradable and confidence is usable.
+/// Returns `None` for non-trading actions or invalid confidence values.
+pub fn maybe_create_generated_signal(
+    date: DateTime<Utc>,
+    ticker: &str,
+    action: &SignalAction,
+    confidence: f64,
+) -> Option<GeneratedSignal> {
+    if !matches!(action, SignalAction::Buy | SignalAction::Sell) {
+        return None;
+    }
+
+    let confidence = if confidence.is_finite() {
+        Some(confidence)
+    } else {
+        None
+    };
+
+    Some(GeneratedSignal {
+        date,
+        ticker: ticker.to_string(),
+        action: action.clone(),
+        confidence,
+    })
+}
+
+/// Parameters for generating a signal with filtering
+pub struct SignalGenerationParams<'a> {
+    pub strategy: &'a dyn Strategy,
+    pub ticker: &'a str,
+    pub candles: &'a [Candle],
+    pub candle_index: usize,
+    pub date: DateTime<Utc>,
+    pub excluded_tickers: &'a HashSet<String>,
+    /// Blackout windows of the strategy; Buy signals inside one are suppressed. Sells pass, as
+    /// they may close longs; `Engine::set_blackout` refuses the shorts they would open.
+    pub blackout: Option<&'a BlackoutCalendar>,
+    /// The strategy's `prepare_context` result for the run `candles` belongs to.
+    pub context: &'a StrategyContext,
+    /// Candles the ticker needs up to `date` before the strategy runs on it; younger tickers
+    /// are recorded as skipped instead of holding.
+    pub min_history_candles: usize,
+}
+
+/// Result of `generate_signal_with_filters`.
+#[derive(Debug, Clone)]
+pub enum SignalFilterOutcome {
+    Generated(GeneratedSignal),
+    /// A Buy entry the strategy produced inside a blackout window, or a ticker with too little
+    /// history for the strategy to run, recorded as a skip.
+    Suppressed(AccountSignalSkip),
+    Filtered,
+}
+
+impl SignalFilterOutcome {
+    pub fn into_signal(self) -> Option<GeneratedSignal> {
+        match self {
+            SignalFilterOutcome::Generated(signal) => Some(signal),
+            _ => None,
+        }
+    }
+}
+
+pub fn generate_signal_with_filters(params: SignalGenerationParams) -> SignalFilterOutcome {
+    let SignalGenerationParams {
+        strategy,
+        ticker,
+        candles,
+        candle_index,
+        date,
+        excluded_tickers,
+        blackout,
+        context,
+        min_history_candles,
+    } = params;
+
+    // Check if ticker is excluded
+    if !excluded_tickers.is_empty() {
+        let ticker_upper = ticker.to_ascii_uppercase();
+        if excluded_tickers.contains(&ticker_upper) {
+            return SignalFilterOutcome::Filtered;
+        }
+    }
+
+    // Check minimum data requirements
+    let min_data_points = strategy.get_min_data_points();
+    if candle_index < min_data_points || candle_index >= candles.len() {
+        return SignalFilterOutcome::Filtered;
+    }
+    if candle_index + 1 < min_history_candles {
+        return SignalFilterOutcome::Suppressed(AccountSignalSkip {
+            ticker: ticker.to_string(),
+            signal_date: date,
+            action: SignalAction::Hold,
+            reason: INSUFFICIENT_HISTORY_REASON.to_string(),
+            details: Some(format!(
+                "{} candles, need {}",
+                candle_index + 1,
+                min_history_candles
+            )),
+        });
+    }
+    // Generate the signal
+    let signal = strategy.generate_signal_with_context(
+        ticker,
+        &candles[..=candle_index],
+        candle_index,
+        context,
+    );
+
+    // Convert to GeneratedSignal if it's a tradable action
+    let Some(generated) =
+        maybe_create_generated_signal(date, ticker, &signal.action, signal.confidence)
+    else {
+        return SignalFilterOutcome::Filtered;
+    };
+
+    // Blackouts suppress Buy entries here. Sell signals still close longs, so the engine refuses
+    // the short entries they would open inside a window instead.
+    if matches!(generated.action, SignalAction::Buy) {
+        if let Some(window) = blackout.and_then(|calendar| calendar.window_for(ticker, date)) {
+            return SignalFilterOutcome::Suppressed(AccountSignalSkip {
+                ticker: generated.ticker,
+                signal_date: date,
+                action: generated.action,
+                reason: BLACKOUT_SKIP_REASON.to_string(),
+                details: Some(window.to_string()),
+            });
+        }
+    }
+
+    SignalFilterOutcome::Generated(generated)
+}
@@ -1,6 +1,7 @@
 use crate::indicators;
 use crate::models::*;
 use crate::param_utils::{clamp_f64, get_param_f64_clamped, get_param_usize_rounded_clamped};
+use crate::strategy::ParameterSpec;
 use std::collections::HashMap;
 
 struct IndicatorSnapshot {
@@ -10,6 +11,16 @@ struct IndicatorSnapshot {
     weakness_triggered: bool,
 }
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::integer("period", 5.0, 50.0, 1.0, 6.0),
+    ParameterSpec::integer("emaPeriod", 10.0, 200.0, 5.0, 10.0),
+    ParameterSpec::number("trendStrength", 15.0, 50.0, 1.0, 25.0),
+    ParameterSpec::number("diDiffMin", 0.0, 30.0, 1.0, 5.0),
+    ParameterSpec::integer("adxSlopeLookback", 1.0, 10.0, 1.0, 2.0),
+    ParameterSpec::integer("weaknessExitDays", 0.0, 20.0, 1.0, 15.0),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.05, 0.5),
+];
+
 pub struct ADXStrategy {
     template_id: String,
     period: usize,
@@ -23,8 +34,8 @@ pub struct ADXStrategy {
 
 impl ADXStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
-        let period = get_param_usize_rounded_clamped(&parameters, "period", 14, 5, 50);
-        let ema_period = get_param_usize_rounded_clamped(&parameters, "emaPeriod", 60, 10, 200);
+        let period = get_param_usize_rounded_clamped(&parameters, "period", 6, 5, 50);
+        let ema_period = get_param_usize_rounded_clamped(&parameters, "emaPeriod", 10, 10, 200);
         let trend_raw = parameters
             .get("trendStrength")
             .copied()
@@ -33,10 +44,10 @@ impl ADXStrategy {
         let trend_strength = clamp_f64(trend_raw, 24.0, 15.0, 50.0);
         let di_diff_min = get_param_f64_clamped(&parameters, "diDiffMin", 5.0, 0.0, 30.0);
         let adx_slope_lookback =
-            get_param_usize_rounded_clamped(&parameters, "adxSlopeLookback", 9, 1, 10);
+            get_param_usize_rounded_clamped(&parameters, "adxSlopeLookback", 2, 1, 10);
         let weakness_exit_days =
             get_param_usize_rounded_clamped(&parameters, "weaknessExitDays", 15, 0, 20);
-        let min_confidence = get_param_f64_clamped(&parameters, "minConfidence", 0.3, 0.0, 1.0);
+        let min_confidence = get_param_f64_clamped(&parameters, "minConfidence", 0.5, 0.0, 1.0);
         Self {
             template_id: "adx".to_string(),
             period,
@@ -49,6 +60,7 @@ impl ADXStrategy {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn collect_indicator_snapshot(
         &self,
         candles: &[Candle],
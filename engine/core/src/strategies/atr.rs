@@ -1,8 +1,15 @@
 use crate::indicators::compute_atr_series;
 use crate::models::*;
 use crate::param_utils::get_param_f64;
+use crate::strategy::ParameterSpec;
 use std::collections::HashMap;
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::integer("period", 5.0, 50.0, 1.0, 5.0),
+    ParameterSpec::number("volatilityThreshold", 1.0, 3.0, 0.1, 2.0),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.03, 0.54),
+];
+
 pub struct ATRStrategy {
     template_id: String,
     period: usize,
@@ -13,11 +20,11 @@ pub struct ATRStrategy {
 
 impl ATRStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
-        let raw_period = get_param_f64(&parameters, "period", 14.0);
+        let raw_period = get_param_f64(&parameters, "period", 5.0);
         let period = raw_period.round().clamp(5.0, 50.0) as usize;
         let min_period_points = raw_period.round().max(50.0) as usize;
-        let volatility_threshold = get_param_f64(&parameters, "volatilityThreshold", 1.5);
-        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.3);
+        let volatility_threshold = get_param_f64(&parameters, "volatilityThreshold", 2.0);
+        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.54);
         Self {
             template_id: "atr".to_string(),
             period,
@@ -1,6 +1,9 @@
 use crate::models::*;
+use crate::strategy::ParameterSpec;
 use std::collections::HashMap;
 
+pub const PARAMETERS: &[ParameterSpec] = &[ParameterSpec::text("ticker")];
+
 pub struct BuyAndHoldStrategy {
     template_id: String,
     target_ticker: Option<String>,
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+
+const PROBABILITY_EPSILON: f64 = 1e-6;
+const PLATT_ITERATIONS: usize = 100;
+const MIN_CALIBRATION_SAMPLES: usize = 200;
+
+/// Maps a model's raw probability to the hit rate observed on validation data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "camelCase")]
+pub enum Calibration {
+    /// Logistic fit on the raw logit: `sigmoid(a * logit(p) + b)`.
+    Platt { a: f64, b: f64 },
+    /// Non-decreasing step function: raw probabilities from `thresholds[i]` up map to `values[i]`.
+    Isotonic {
+        thresholds: Vec<f64>,
+        values: Vec<f64>,
+    },
+}
+
+impl Calibration {
+    pub fn method(&self) -> &'static str {
+        match self {
+            Calibration::Platt { .. } => "platt",
+            Calibration::Isotonic { .. } => "isotonic",
+        }
+    }
+
+    pub fn apply(&self, probability: f64) -> f64 {
+        if !probability.is_finite() {
+            return probability;
+        }
+        match self {
+            Calibration::Platt { a, b } => sigmoid(a * logit(probability) + b),
+            Calibration::Isotonic { thresholds, values } => {
+                let idx = thresholds.partition_point(|threshold| *threshold <= probability);
+                values
+                    .get(idx.saturating_sub(1))
+                    .copied()
+                    .unwrap_or(probability)
+            }
+        }
+        .clamp(0.0, 1.0)
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            Calibration::Platt { a, b } => a.is_finite() && b.is_finite(),
+            Calibration::Isotonic { thresholds, values } => {
+                !thresholds.is_empty()
+                    && thresholds.len() == values.len()
+                    && thresholds
+                        .iter()
+                        .chain(values)
+                        .all(|value| value.is_finite())
+            }
+        }
+    }
+
+    /// Parses a stored calibration, dropping malformed ones so the model falls back to raw scores.
+    pub fn from_json(text: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(text)
+            .ok()
+            .filter(Calibration::is_valid)
+    }
+}
+
+fn logit(probability: f64) -> f64 {
+    let clipped = probability.clamp(PROBABILITY_EPSILON, 1.0 - PROBABILITY_EPSILON);
+    (clipped / (1.0 - clipped)).ln()
+}
+
+fn sigmoid(value: f64) -> f64 {
+    1.0 / (1.0 + (-value).exp())
+}
+
+/// Platt scaling with the smoothed targets from Platt (1999), fitted by Newton's method.
+pub fn fit_platt(probabilities: &[f64], labels: &[bool]) -> Calibration {
+    let positives = labels.iter().filter(|label| **label).count() as f64;
+    let negatives = labels.len() as f64 - positives;
+    let high = (positives + 1.0) / (positives + 2.0);
+    let low = 1.0 / (negatives + 2.0);
+    let inputs: Vec<(f64, f64)> = probabilities
+        .iter()
+        .zip(labels)
+        .map(|(probability, label)| (logit(*probability), if *label { high } else { low }))
+        .collect();
+
+    let (mut a, mut b) = (1.0, 0.0);
+    for _ in 0..PLATT_ITERATIONS {
+        let (mut ga, mut gb, mut haa, mut hab, mut hbb) = (0.0, 0.0, 1e-9, 0.0, 1e-9);
+        for (x, target) in &inputs {
+            let p = sigmoid(a * x + b);
+            let error = p - target;
+            let curvature = p * (1.0 - p);
+            ga += error * x;
+            gb += error;
+            haa += curvature * x * x;
+            hab += curvature * x;
+            hbb += curvature;
+        }
+        let determinant = haa * hbb - hab * hab;
+        if determinant.abs() < 1e-12 {
+            break;
+        }
+        let step_a = (hbb * ga - hab * gb) / determinant;
+        let step_b = (haa * gb - hab * ga) / determinant;
+        a -= step_a;
+        b -= step_b;
+        if step_a.abs() < 1e-10 && step_b.abs() < 1e-10 {
+            break;
+        }
+    }
+    Calibration::Platt { a, b }
+}
+
+/// Isotonic regression by pool-adjacent-violators over raw probabilities sorted ascending.
+pub fn fit_isotonic(probabilities: &[f64], labels: &[bool]) -> Calibration {
+    let mut pairs: Vec<(f64, f64)> = probabilities
+        .iter()
+        .zip(labels)
+        .map(|(probability, label)| (*probability, if *label { 1.0 } else { 0.0 }))
+        .collect();
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Each block: (lowest raw probability, sum of labels, count).
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+    for (probability, label) in pairs {
+        blocks.push((probability, label, 1.0));
+        while blocks.len() > 1 {
+            let last = blocks[blocks.len() - 1];
+            let previous = blocks[blocks.len() - 2];
+            if previous.1 / previous.2 < last.1 / last.2 {
+                break;
+            }
+            blocks.pop();
+            let merged = blocks.last_mut().expect("checked two blocks");
+            merged.1 += last.1;
+            merged.2 += last.2;
+        }
+    }
+    Calibration::Isotonic {
+        thresholds: blocks.iter().map(|block| block.0).collect(),
+        values: blocks.iter().map(|block| block.1 / block.2).collect(),
+    }
+}
+
+pub fn brier_score(probabilities: &[f64], labels: &[bool]) -> f64 {
+    if probabilities.is_empty() {
+        return f64::NAN;
+    }
+    probabilities
+        .iter()
+        .zip(labels)
+        .map(|(probability, label)| (probability - if *label { 1.0 } else { 0.0 }).powi(2))
+        .sum::<f64>()
+        / probabilities.len() as f64
+}
+
+/// Fits Platt and isotonic calibrations on the first half of the rows (callers pass them in date
+/// order), keeps the one with the lower Brier score on the second half and refits it on all rows.
+/// Returns `None` with too few rows or a single class.
+pub fn fit_calibration(probabilities: &[f64], labels: &[bool]) -> Option<Calibration> {
+    let positives = labels.iter().filter(|label| **label).count();
+    if probabilities.len() < MIN_CALIBRATION_SAMPLES || positives == 0 || positives == labels.len()
+    {
+        return None;
+    }
+    let split = probabilities.len() / 2;
+    let holdout_brier = |calibration: Calibration| {
+        let calibrated: Vec<f64> = probabilities[split..]
+            .iter()
+            .map(|probability| calibration.apply(*probability))
+            .collect();
+        brier_score(&calibrated, &labels[split..])
+    };
+    let platt_brier = holdout_brier(fit_platt(&probabilities[..split], &labels[..split]));
+    let isotonic_brier = holdout_brier(fit_isotonic(&probabilities[..split], &labels[..split]));
+    let calibration = if isotonic_brier < platt_brier {
+        fit_isotonic(probabilities, labels)
+    } else {
+        fit_platt(probabilities, labels)
+    };
+    calibration.is_valid().then_some(calibration)
+}
+
+/// Rows whose raw probability falls in `[lower, upper)`, with their mean prediction and hit rate.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReliabilityBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_predicted: f64,
+    pub observed_rate: f64,
+}
+
+pub fn reliability_curve(
+    probabilities: &[f64],
+    labels: &[bool],
+    bins: usize,
+) -> Vec<ReliabilityBin> {
+    let bins = bins.max(1);
+    let mut sums = vec![(0usize, 0.0, 0usize); bins];
+    for (probability, label) in probabilities.iter().zip(labels) {
+        if !probability.is_finite() {
+            continue;
+        }
+        let idx = ((probability.clamp(0.0, 1.0) * bins as f64) as usize).min(bins - 1);
+        let slot = &mut sums[idx];
+        slot.0 += 1;
+        slot.1 += probability;
+        slot.2 += usize::from(*label);
+    }
+    sums.into_iter()
+        .enumerate()
+        .filter(|(_, (count, _, _))| *count > 0)
+        .map(|(idx, (count, predicted, hits))| ReliabilityBin {
+            lower: idx as f64 / bins as f64,
+            upper: (idx + 1) as f64 / bins as f64,
+            count,
+            mean_predicted: predicted / count as f64,
+            observed_rate: hits as f64 / count as f64,
+        })
+        .collect()
+}
+
+/// Count-weighted mean gap between predicted and observed rates across `curve`.
+pub fn expected_calibration_error(curve: &[ReliabilityBin]) -> f64 {
+    let total: usize = curve.iter().map(|bin| bin.count).sum();
+    if total == 0 {
+        return f64::NAN;
+    }
+    curve
+        .iter()
+        .map(|bin| (bin.mean_predicted - bin.observed_rate).abs() * bin.count as f64)
+        .sum::<f64>()
+        / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        brier_score, expected_calibration_error, fit_calibration, fit_isotonic, fit_platt,
+        reliability_curve, Calibration,
+    };
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Raw scores that overstate the true hit rate, which is `raw^2`.
+    fn overconfident_sample(count: usize) -> (Vec<f64>, Vec<bool>) {
+        let mut rng = StdRng::seed_from_u64(11);
+        (0..count)
+            .map(|_| {
+                let raw: f64 = rng.gen_range(0.01..0.99);
+                (raw, rng.gen::<f64>() < raw * raw)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn calibration_reduces_the_reliability_gap() {
+        let (raw, labels) = overconfident_sample(4_000);
+        let calibration = fit_calibration(&raw, &labels).unwrap();
+        let calibrated: Vec<f64> = raw.iter().map(|p| calibration.apply(*p)).collect();
+        assert!(brier_score(&calibrated, &labels) < brier_score(&raw, &labels));
+        let before = expected_calibration_error(&reliability_curve(&raw, &labels, 10));
+        let after = expected_calibration_error(&reliability_curve(&calibrated, &labels, 10));
+        assert!(after < before / 2.0, "ECE {before} -> {after}");
+
+        let restored = Calibration::from_json(&serde_json::to_string(&calibration).unwrap());
+        assert_eq!(restored, Some(calibration));
+        assert!(
+            Calibration::from_json(r#"{"method":"isotonic","thresholds":[],"values":[]}"#)
+                .is_none()
+        );
+        assert!(fit_calibration(&raw[..50], &labels[..50]).is_none());
+    }
+
+    #[test]
+    fn fitted_mappings_are_monotonic() {
+        let (raw, labels) = overconfident_sample(1_000);
+        for calibration in [fit_platt(&raw, &labels), fit_isotonic(&raw, &labels)] {
+            let mut previous = 0.0;
+            for step in 0..=100 {
+                let value = calibration.apply(step as f64 / 100.0);
+                assert!(value >= previous, "{} not monotonic", calibration.method());
+                previous = value;
+            }
+        }
+    }
+}
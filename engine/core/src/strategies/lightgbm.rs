@@ -1,19 +1,31 @@
 use crate::indicators;
 use crate::models::*;
 use crate::param_utils::{get_param_f64_clamped, get_param_usize_rounded_clamped};
+use crate::strategy::calibration::Calibration;
+use crate::strategy::{neural_net, ParameterSpec, StrategyContext};
 use crate::strategy_utils::{buy_signal, hold_signal, meets_confidence_threshold, sell_signal};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use dashmap::{mapref::entry::Entry, DashMap, DashSet};
 use log::{debug, info, warn};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex, OnceLock};
 
+#[path = "lightgbm_features.rs"]
+pub mod features;
+
+use features::{FeatureContext, FeatureSet, FEATURES};
+
 pub const SIGNAL_BUCKET_COUNT: usize = 2;
 pub const SIGNAL_BUCKET_NAMES: [&str; SIGNAL_BUCKET_COUNT] = ["no_hit", "hit"];
 pub const SIGNAL_BUCKET_NEGATIVE: usize = 0;
@@ -41,7 +53,36 @@ pub struct FeatureConfig {
 }
 
 impl FeatureConfig {
-    pub fn default() -> Self {
+    fn cache_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (
+            self.rsi_period,
+            self.atr_period,
+            self.stochastic_period,
+            self.stochastic_smooth,
+            self.cci_period,
+            self.bollinger_period,
+            self.bollinger_std.to_bits(),
+            self.momentum_short,
+            self.momentum_long,
+            self.volatility_short,
+            self.volatility_long,
+        )
+            .hash(&mut hasher);
+        (
+            self.ma_fast,
+            self.ma_slow,
+            self.ma_trend,
+            self.ma_trend_slow,
+            self.correlation_window,
+        )
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
         Self {
             rsi_period: 14,
             atr_period: 14,
@@ -251,7 +292,7 @@ fn std_dev(values: &[f64]) -> Option<f64> {
     }
 }
 
-fn rolling_slice<'a>(data: &'a [f64], end_idx: usize, window: usize) -> Option<&'a [f64]> {
+fn rolling_slice(data: &[f64], end_idx: usize, window: usize) -> Option<&[f64]> {
     if window == 0 || end_idx + 1 < window || end_idx >= data.len() {
         None
     } else {
@@ -346,8 +387,8 @@ fn percentile_ranks(entries: &[(String, f64)], default: f64) -> HashMap<String,
             end += 1;
         }
         let mid_rank = ((idx + end - 1) as f64 / 2.0) / last_index;
-        for pos in idx..end {
-            ranks.insert(sorted[pos].0.clone(), mid_rank);
+        for (ticker, _) in &sorted[idx..end] {
+            ranks.insert(ticker.clone(), mid_rank);
         }
         idx = end;
     }
@@ -400,7 +441,156 @@ struct CrossSectionalFeatures {
     momentum_zscore: f64,
 }
 
-#[derive(Clone)]
+/// Inputs from outside the ticker's candles shared by every ticker's features.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MarketInputs<'a> {
+    pub volatility_index: Option<&'a AuxiliarySeries>,
+    pub economic_calendar: Option<&'a EconomicCalendar>,
+    pub short_interest: Option<&'a TickerShortInterestMap>,
+    pub sentiment: Option<&'a TickerSentimentMap>,
+}
+
+impl<'a> MarketInputs<'a> {
+    pub fn from_context(context: &'a StrategyContext) -> Self {
+        let auxiliary = context.auxiliary.as_deref();
+        Self {
+            volatility_index: context.volatility_index.as_deref(),
+            ..auxiliary.map(Self::from_auxiliary).unwrap_or_default()
+        }
+    }
+
+    /// Non-empty economic calendar, short interest and sentiment of `auxiliary`.
+    pub fn from_auxiliary(auxiliary: &'a AuxiliaryData) -> Self {
+        Self {
+            volatility_index: None,
+            economic_calendar: Some(&auxiliary.economic_calendar)
+                .filter(|calendar| !calendar.is_empty()),
+            short_interest: Some(&auxiliary.ticker_short_interest)
+                .filter(|short_interest| !short_interest.is_empty()),
+            sentiment: Some(&auxiliary.ticker_sentiment).filter(|sentiment| !sentiment.is_empty()),
+        }
+    }
+
+    fn sentiment_for(&self, ticker: &str) -> &'a [SentimentPoint] {
+        self.sentiment
+            .and_then(|sentiment| sentiment.get(ticker))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn short_interest_for(&self, ticker: &str) -> &'a [ShortInterestPoint] {
+        self.short_interest
+            .and_then(|short_interest| short_interest.get(ticker))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Cache key of the inputs read for `ticker`; 0 when there are none.
+    fn fingerprint(&self, ticker: &str) -> u64 {
+        let short_interest = self.short_interest_for(ticker);
+        let sentiment = self.sentiment_for(ticker);
+        if self.volatility_index.is_none()
+            && self.economic_calendar.is_none()
+            && short_interest.is_empty()
+            && sentiment.is_empty()
+        {
+            return 0;
+        }
+        let mut hasher = DefaultHasher::new();
+        self.volatility_index
+            .map(AuxiliarySeries::fingerprint)
+            .hash(&mut hasher);
+        self.economic_calendar
+            .map(EconomicCalendar::fingerprint)
+            .hash(&mut hasher);
+        short_interest.len().hash(&mut hasher);
+        if let Some(last) = short_interest.last() {
+            last.date.hash(&mut hasher);
+            for value in [last.short_interest_ratio, last.borrow_fee_rate] {
+                value.map(f64::to_bits).hash(&mut hasher);
+            }
+        }
+        sentiment.len().hash(&mut hasher);
+        if let Some(last) = sentiment.last() {
+            last.date.hash(&mut hasher);
+            last.score.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+/// Macro release distances are capped at this many calendar days and scaled to 0..=1.
+const ECONOMIC_EVENT_HORIZON_DAYS: i64 = 30;
+
+/// Features of one date read from outside the ticker's candles: auxiliary series such as the
+/// volatility index, the economic calendar and the ticker's short interest and sentiment.
+#[derive(Clone, Copy, Debug)]
+struct MarketFeatures {
+    volatility_index_level: f64,
+    volatility_index_change_5d: f64,
+    days_until_fomc: f64,
+    days_until_cpi: f64,
+    economic_event_day: f64,
+    short_interest_ratio: f64,
+    borrow_fee_rate: f64,
+    sentiment_score: f64,
+    sentiment_missing: f64,
+}
+
+impl Default for MarketFeatures {
+    fn default() -> Self {
+        Self {
+            volatility_index_level: 0.0,
+            volatility_index_change_5d: 0.0,
+            days_until_fomc: 1.0,
+            days_until_cpi: 1.0,
+            economic_event_day: 0.0,
+            short_interest_ratio: 0.0,
+            borrow_fee_rate: 0.0,
+            sentiment_score: 0.0,
+            sentiment_missing: 1.0,
+        }
+    }
+}
+
+impl MarketFeatures {
+    fn at(market: MarketInputs<'_>, ticker: &str, date: DateTime<Utc>) -> Self {
+        let mut features = Self::default();
+        if let Some(series) = market.volatility_index {
+            // Index points are annualized volatility in percent.
+            features.volatility_index_level =
+                series.close_on_or_before(date).unwrap_or(0.0) / 100.0;
+            features.volatility_index_change_5d =
+                series.change_on_or_before(date, 5).unwrap_or(0.0);
+        }
+        if let Some(calendar) = market.economic_calendar {
+            let day = date.date_naive();
+            let scaled_days_until = |kind| {
+                calendar.days_until(kind, day).map_or(1.0, |days| {
+                    days.min(ECONOMIC_EVENT_HORIZON_DAYS) as f64
+                        / ECONOMIC_EVENT_HORIZON_DAYS as f64
+                })
+            };
+            features.days_until_fomc = scaled_days_until(EconomicEventKind::Fomc);
+            features.days_until_cpi = scaled_days_until(EconomicEventKind::Cpi);
+            features.economic_event_day = if calendar.event_on(day).is_some() {
+                1.0
+            } else {
+                0.0
+            };
+        }
+        let (short_interest_ratio, borrow_fee_rate) =
+            short_interest_on_or_before(market.short_interest_for(ticker), date);
+        features.short_interest_ratio = short_interest_ratio.unwrap_or(0.0);
+        features.borrow_fee_rate = borrow_fee_rate.unwrap_or(0.0);
+        // A missing or stale score reads as neutral, flagged so models can tell it apart.
+        if let Some(score) = sentiment_on_or_before(market.sentiment_for(ticker), date) {
+            features.sentiment_score = score;
+            features.sentiment_missing = 0.0;
+        }
+        features
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct TickerCrossSeries {
     return_1d: Vec<f64>,
     momentum_20: Vec<f64>,
@@ -442,9 +632,9 @@ impl TickerCrossSeries {
         }
 
         let mut volatility_20 = vec![f64::NAN; closes.len()];
-        for i in 1..closes.len() {
+        for (i, slot) in volatility_20.iter_mut().enumerate().skip(1) {
             if let Some(std) = rolling_std_at(&return_1d, i, 20) {
-                volatility_20[i] = std;
+                *slot = std;
             }
         }
 
@@ -467,9 +657,84 @@ impl TickerCrossSeries {
     }
 }
 
+/// Lazily filled per-ticker feature rows for one FeatureConfig, indexed like the candle series.
+struct FeatureMatrix {
+    first_date: DateTime<Utc>,
+    rows: Vec<OnceLock<Option<FeatureVector>>>,
+}
+
+impl FeatureMatrix {
+    fn new(candles: &[Candle]) -> Option<Self> {
+        let first_date = candles.first()?.date;
+        Some(Self {
+            first_date,
+            rows: (0..candles.len()).map(|_| OnceLock::new()).collect(),
+        })
+    }
+
+    fn matches(&self, candles: &[Candle]) -> bool {
+        self.rows.len() == candles.len()
+            && candles
+                .first()
+                .is_some_and(|candle| candle.date == self.first_date)
+    }
+}
+
+const MAX_CACHED_FEATURE_CONFIGS: usize = 16;
+
+const CROSS_SECTIONAL_SNAPSHOT_VERSION: u32 = 1;
+const MAX_CACHED_CROSS_SECTIONAL_CONTEXTS: usize = 4;
+
+/// Per-date cross-sectional ranks of one candle set. Built once per candle set and shared by
+/// every backtest over it through `CrossSectionalCache`.
 pub struct CrossSectionalContext {
     per_ticker: HashMap<String, TickerCrossSeries>,
     per_date_cache: DashMap<DateTime<Utc>, Arc<HashMap<String, CrossSectionalFeatures>>>,
+    // Feature rows only depend on the candles and the FeatureConfig, so parameter sets that
+    // share a config (e.g. during optimize) reuse them instead of recomputing every candle.
+    feature_matrices: DashMap<u64, DashMap<String, Arc<FeatureMatrix>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CrossSectionalSnapshot {
+    version: u32,
+    content_hash: u64,
+    per_ticker: HashMap<String, TickerCrossSeries>,
+}
+
+/// Cheap in-memory key: ticker names, lengths and the first/last candle of every series.
+fn candle_map_fingerprint(candles_by_ticker: &HashMap<String, Vec<&Candle>>) -> u64 {
+    let mut tickers: Vec<&String> = candles_by_ticker.keys().collect();
+    tickers.sort();
+    let mut hasher = DefaultHasher::new();
+    for ticker in tickers {
+        let candles = &candles_by_ticker[ticker];
+        ticker.hash(&mut hasher);
+        candles.len().hash(&mut hasher);
+        for candle in [candles.first(), candles.last()].into_iter().flatten() {
+            candle.date.hash(&mut hasher);
+            candle.close.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Hash of every candle, naming persisted contexts so an edited history never reuses a file.
+fn candle_map_content_hash(candles_by_ticker: &HashMap<String, Vec<&Candle>>) -> u64 {
+    let mut tickers: Vec<&String> = candles_by_ticker.keys().collect();
+    tickers.sort();
+    let mut hasher = DefaultHasher::new();
+    for ticker in tickers {
+        let candles = &candles_by_ticker[ticker];
+        ticker.hash(&mut hasher);
+        candles.len().hash(&mut hasher);
+        for candle in candles {
+            candle.date.hash(&mut hasher);
+            candle.close.to_bits().hash(&mut hasher);
+            candle.volume_shares.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
 }
 
 impl CrossSectionalContext {
@@ -480,17 +745,59 @@ impl CrossSectionalContext {
                 per_ticker.insert(ticker.clone(), series);
             }
         }
+        Self::from_series(per_ticker)
+    }
 
+    fn from_series(per_ticker: HashMap<String, TickerCrossSeries>) -> Option<Self> {
         if per_ticker.is_empty() {
             None
         } else {
             Some(Self {
                 per_ticker,
                 per_date_cache: DashMap::new(),
+                feature_matrices: DashMap::new(),
             })
         }
     }
 
+    /// Writes the per-ticker series (not the lazily filled caches) tagged with `content_hash`.
+    pub fn save_to_file(&self, path: &Path, content_hash: u64) -> Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        let snapshot = CrossSectionalSnapshot {
+            version: CROSS_SECTIONAL_SNAPSHOT_VERSION,
+            content_hash,
+            per_ticker: self.per_ticker.clone(),
+        };
+        bincode::serialize_into(&mut writer, &snapshot)
+            .context("Failed to serialize cross-sectional context")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a context saved for `content_hash`; `None` when the file belongs to another
+    /// candle set or an older format.
+    pub fn load_from_file(path: &Path, content_hash: u64) -> Result<Option<Self>> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let snapshot: CrossSectionalSnapshot = bincode::deserialize_from(BufReader::new(file))
+            .context("Cross-sectional context decode failed")?;
+        if snapshot.version != CROSS_SECTIONAL_SNAPSHOT_VERSION
+            || snapshot.content_hash != content_hash
+        {
+            return Ok(None);
+        }
+        Ok(Self::from_series(snapshot.per_ticker))
+    }
+
     fn build_snapshot_for_date(
         &self,
         date: DateTime<Utc>,
@@ -595,49 +902,106 @@ impl CrossSectionalContext {
         }
     }
 
+    fn feature_matrix(
+        &self,
+        config_key: u64,
+        ticker: &str,
+        candles: &[Candle],
+    ) -> Option<Arc<FeatureMatrix>> {
+        if let Some(per_ticker) = self.feature_matrices.get(&config_key) {
+            if let Some(existing) = per_ticker.get(ticker) {
+                if existing.matches(candles) {
+                    return Some(existing.clone());
+                }
+            }
+        } else if self.feature_matrices.len() >= MAX_CACHED_FEATURE_CONFIGS {
+            self.feature_matrices.clear();
+        }
+        let per_ticker = self.feature_matrices.entry(config_key).or_default();
+        let matrix = Arc::new(FeatureMatrix::new(candles)?);
+        per_ticker.insert(ticker.to_string(), matrix.clone());
+        Some(matrix)
+    }
+
     fn get_features(&self, ticker: &str, date: DateTime<Utc>) -> Option<CrossSectionalFeatures> {
         self.snapshot_for_date(date)
             .and_then(|snapshot| snapshot.get(ticker).copied())
     }
 }
 
-static CROSS_SECTIONAL_CONTEXT: OnceLock<Mutex<Option<Arc<CrossSectionalContext>>>> =
-    OnceLock::new();
-
-fn cross_context_slot() -> &'static Mutex<Option<Arc<CrossSectionalContext>>> {
-    CROSS_SECTIONAL_CONTEXT.get_or_init(|| Mutex::new(None))
+/// Cross-sectional contexts keyed by candle set. Engines sharing one cache (every backtest of
+/// an optimize run, every strategy of a signal run) build each context once; with a directory
+/// the contexts are also persisted so later runs over the same candles skip the rebuild.
+#[derive(Default)]
+pub struct CrossSectionalCache {
+    contexts: DashMap<u64, Arc<CrossSectionalContext>>,
+    directory: Option<PathBuf>,
 }
 
-fn set_global_cross_sectional_context(context: Option<Arc<CrossSectionalContext>>) {
-    if let Ok(mut slot) = cross_context_slot().lock() {
-        *slot = context;
+impl CrossSectionalCache {
+    pub fn with_directory(directory: PathBuf) -> Self {
+        Self {
+            contexts: DashMap::new(),
+            directory: Some(directory),
+        }
     }
-}
 
-fn get_global_cross_sectional_context() -> Option<Arc<CrossSectionalContext>> {
-    cross_context_slot()
-        .lock()
-        .ok()
-        .and_then(|slot| slot.clone())
-}
+    pub fn context_for(
+        &self,
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+    ) -> Option<Arc<CrossSectionalContext>> {
+        let fingerprint = candle_map_fingerprint(candles_by_ticker);
+        if let Some(existing) = self.contexts.get(&fingerprint) {
+            return Some(existing.clone());
+        }
+        let context = Arc::new(self.load_or_build(candles_by_ticker)?);
+        if self.contexts.len() >= MAX_CACHED_CROSS_SECTIONAL_CONTEXTS {
+            self.contexts.clear();
+        }
+        Some(
+            self.contexts
+                .entry(fingerprint)
+                .or_insert(context)
+                .value()
+                .clone(),
+        )
+    }
 
-pub fn prime_cross_sectional_context_from_ref_map(
-    candles_by_ticker: &HashMap<String, Vec<&Candle>>,
-) -> Option<()> {
-    let context = CrossSectionalContext::new(candles_by_ticker).map(Arc::new);
-    set_global_cross_sectional_context(context.clone());
-    context.map(|_| ())
-}
+    /// Drops the in-memory contexts. Backtests holding one keep it, and persisted contexts are
+    /// reloaded from disk on next use.
+    pub fn clear(&self) {
+        self.contexts.clear();
+    }
 
-#[allow(dead_code)]
-pub fn prime_cross_sectional_context_from_owned_map(
-    candles_by_ticker: &HashMap<String, Vec<Candle>>,
-) -> Option<()> {
-    let mut ref_map: HashMap<String, Vec<&Candle>> = HashMap::new();
-    for (ticker, candles) in candles_by_ticker {
-        ref_map.insert(ticker.clone(), candles.iter().collect());
+    fn load_or_build(
+        &self,
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+    ) -> Option<CrossSectionalContext> {
+        let Some(directory) = &self.directory else {
+            return CrossSectionalContext::new(candles_by_ticker);
+        };
+        let content_hash = candle_map_content_hash(candles_by_ticker);
+        let path = directory.join(format!("cross_sectional_{content_hash:016x}.bin"));
+        if path.exists() {
+            match CrossSectionalContext::load_from_file(&path, content_hash) {
+                Ok(Some(context)) => {
+                    debug!("Loaded cross-sectional context from {}", path.display());
+                    return Some(context);
+                }
+                Ok(None) => {}
+                Err(err) => warn!(
+                    "Ignoring cross-sectional context {}: {:#}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+        let context = CrossSectionalContext::new(candles_by_ticker)?;
+        if let Err(err) = context.save_to_file(&path, content_hash) {
+            warn!("Failed to persist cross-sectional context: {:#}", err);
+        }
+        Some(context)
     }
-    prime_cross_sectional_context_from_ref_map(&ref_map)
 }
 
 #[derive(Debug)]
@@ -648,15 +1012,96 @@ enum FeatureStatus {
     Failed,
 }
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.01, 0.1),
+    ParameterSpec::number("modelBias", -2.0, 2.0, 0.01, 0.01),
+    ParameterSpec::integer("rsiPeriod", 5.0, 60.0, 1.0, 14.0),
+    ParameterSpec::integer("stochPeriod", 5.0, 60.0, 1.0, 14.0),
+    ParameterSpec::integer("stochSmooth", 1.0, 10.0, 1.0, 3.0),
+    ParameterSpec::integer("cciPeriod", 10.0, 90.0, 1.0, 20.0),
+    ParameterSpec::integer("bollingerPeriod", 10.0, 90.0, 1.0, 20.0),
+    ParameterSpec::number("bollingerStd", 1.0, 4.0, 0.1, 2.0),
+    ParameterSpec::integer("momentumShort", 5.0, 120.0, 1.0, 20.0),
+    ParameterSpec::integer("momentumLong", 20.0, 240.0, 5.0, 60.0),
+    ParameterSpec::integer("returnVolShort", 2.0, 90.0, 1.0, 5.0),
+    ParameterSpec::integer("returnVolLong", 5.0, 180.0, 5.0, 20.0),
+    ParameterSpec::integer("maFast", 5.0, 60.0, 1.0, 10.0),
+    ParameterSpec::integer("maSlow", 20.0, 200.0, 5.0, 50.0),
+    ParameterSpec::integer("maTrend", 10.0, 90.0, 1.0, 20.0),
+    ParameterSpec::integer("maTrendSlow", 100.0, 320.0, 10.0, 200.0),
+    ParameterSpec::integer("correlationWindow", 5.0, 180.0, 5.0, 20.0),
+];
+
 pub struct LightGBMStrategy {
     template_id: String,
     feature_config: FeatureConfig,
+    feature_cache_key: u64,
     min_confidence: f64,
     model_bias: f64,
     model_id: Option<String>,
+    backend: ModelBackend,
     decision_summary: Mutex<LightGBMSummary>,
 }
 
+/// Model scoring the feature vector: a LightGBM booster for `lightgbm`/`lightgbm_<id>` templates
+/// or a neural network trained by `train-nn` for `nn_<id>` templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelBackend {
+    Booster,
+    NeuralNet,
+}
+
+/// Model handle resolved from the registries when a run starts, so re-registering a model id
+/// mid-run does not change the scores of that run.
+#[derive(Debug, Clone)]
+pub enum ScoringModel {
+    Booster(Arc<LightGBMBooster>),
+    NeuralNet(Arc<neural_net::MlpModel>),
+}
+
+impl ScoringModel {
+    /// Registry columns the model reads, in input order.
+    pub fn features(&self) -> &FeatureSet {
+        match self {
+            ScoringModel::Booster(booster) => &booster.features,
+            ScoringModel::NeuralNet(model) => model.features(),
+        }
+    }
+
+    pub fn calibration(&self) -> Option<&Calibration> {
+        match self {
+            ScoringModel::Booster(booster) => booster.calibration.as_ref(),
+            ScoringModel::NeuralNet(model) => model.calibration.as_ref(),
+        }
+    }
+
+    /// Uncalibrated probability of a hit for an already selected feature row.
+    pub fn raw_probability(&self, selected: &[f64]) -> Option<f64> {
+        match self {
+            ScoringModel::Booster(booster) => {
+                if selected.len() != booster.feature_count {
+                    return None;
+                }
+                let distribution = booster.predict_distribution(selected)?;
+                Some(probability_from_distribution(&distribution))
+            }
+            ScoringModel::NeuralNet(model) => model.predict_probability(selected),
+        }
+    }
+}
+
+/// Resolves a registered model by id: LightGBM models first, then neural networks. Without an
+/// id, the default LightGBM model.
+pub fn resolve_scoring_model(model_id: Option<&str>) -> Option<ScoringModel> {
+    if let Ok(Some(booster)) = load_lightgbm_booster_for_model(model_id) {
+        return Some(ScoringModel::Booster(booster));
+    }
+    neural_net::registered_model(model_id?).map(ScoringModel::NeuralNet)
+}
+
+/// Prefix of the model text line carrying the fitted [`Calibration`] as JSON.
+pub const CALIBRATION_LINE_PREFIX: &str = "stratcraft_calibration=";
+
 static LIGHTGBM_MODELS: OnceLock<DashMap<String, Arc<LightGBMBooster>>> = OnceLock::new();
 static LIGHTGBM_DEFAULT_MODEL_ID: OnceLock<Mutex<Option<String>>> = OnceLock::new();
 static LIGHTGBM_MISSING_LOGGED: OnceLock<DashSet<String>> = OnceLock::new();
@@ -746,7 +1191,7 @@ impl LightGBMTree {
             ));
         }
 
-        let declared_leaves = num_leaves.unwrap_or_else(|| leaf_values.len());
+        let declared_leaves = num_leaves.unwrap_or(leaf_values.len());
         if declared_leaves != leaf_values.len() {
             return Err(anyhow!(
                 "LightGBM tree leaf count mismatch: expected {declared_leaves}, found {}",
@@ -798,13 +1243,15 @@ enum LightGBMObjectiveKind {
 }
 
 #[derive(Debug)]
-struct LightGBMBooster {
+pub struct LightGBMBooster {
     trees: Vec<LightGBMTree>,
     feature_count: usize,
     sigmoid: f64,
     num_classes: usize,
     num_tree_per_iteration: usize,
     objective: LightGBMObjectiveKind,
+    features: FeatureSet,
+    calibration: Option<Calibration>,
 }
 
 impl LightGBMBooster {
@@ -816,6 +1263,7 @@ impl LightGBMBooster {
         let mut num_classes: usize = 1;
         let mut num_tree_per_iteration: usize = 1;
         let mut objective = LightGBMObjectiveKind::Binary;
+        let mut feature_names: Vec<&str> = Vec::new();
 
         while let Some(line) = lines.next() {
             let trimmed = line.trim();
@@ -836,6 +1284,8 @@ impl LightGBMBooster {
                 num_tree_per_iteration = parse_value(trimmed, "num_tree_per_iteration=")?;
             } else if trimmed.starts_with("max_feature_idx=") {
                 max_feature_idx = Some(parse_value(trimmed, "max_feature_idx=")?);
+            } else if let Some(names) = trimmed.strip_prefix("feature_names=") {
+                feature_names = names.split_whitespace().collect();
             }
 
             if trimmed.starts_with("Tree=") {
@@ -863,6 +1313,27 @@ impl LightGBMBooster {
         if num_tree_per_iteration == 0 {
             num_tree_per_iteration = 1;
         }
+        // Models trained before the feature registry carry LightGBM's generic Column_<n> names
+        // and use the full feature vector; a column count mismatch then holds at prediction.
+        let features = if feature_names.iter().all(|name| name.starts_with("Column_")) {
+            FeatureSet::default()
+        } else {
+            let features = FeatureSet::from_names(&feature_names)?;
+            features.check_column_count(feature_count)?;
+            features
+        };
+
+        // Written after the trees, where the tree parser has already consumed it.
+        let calibration = text
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(CALIBRATION_LINE_PREFIX))
+            .and_then(|json| {
+                let calibration = Calibration::from_json(json);
+                if calibration.is_none() {
+                    warn!("Ignoring malformed LightGBM model calibration");
+                }
+                calibration
+            });
 
         Ok(Self {
             trees,
@@ -871,6 +1342,8 @@ impl LightGBMBooster {
             num_classes,
             num_tree_per_iteration,
             objective,
+            features,
+            calibration,
         })
     }
 
@@ -922,10 +1395,6 @@ impl LightGBMBooster {
             }
         }
     }
-
-    fn num_features(&self) -> usize {
-        self.feature_count
-    }
 }
 
 fn parse_value<T>(line: &str, prefix: &str) -> Result<T>
@@ -1006,6 +1475,14 @@ fn load_lightgbm_booster_for_model(model_id: Option<&str>) -> Result<Option<Arc<
     Ok(None)
 }
 
+fn probability_from_distribution(distribution: &[f64]) -> f64 {
+    if distribution.len() >= SIGNAL_BUCKET_COUNT {
+        distribution[SIGNAL_BUCKET_POSITIVE].clamp(0.0, 1.0)
+    } else {
+        distribution.last().copied().unwrap_or(0.5).clamp(0.0, 1.0)
+    }
+}
+
 fn minimum_history_needed(config: &FeatureConfig) -> usize {
     config
         .ma_trend_slow
@@ -1116,11 +1593,11 @@ pub fn precompute_inputs_for_ticker(
         config.bollinger_std,
     );
 
-    let mut atr_series = vec![0.0; candles.len()];
-    for i in 0..candles.len() {
-        atr_series[i] =
-            indicators::calculate_atr_from_candles(candles, i, config.atr_period).unwrap_or(0.0);
-    }
+    let atr_series: Vec<f64> = (0..candles.len())
+        .map(|i| {
+            indicators::calculate_atr_from_candles(candles, i, config.atr_period).unwrap_or(0.0)
+        })
+        .collect();
 
     Some(PrecomputedInputs {
         closes,
@@ -1152,6 +1629,7 @@ pub fn compute_features_from_precomputed(
     config: FeatureConfig,
     pre: &PrecomputedInputs,
     cross_context: Option<Arc<CrossSectionalContext>>,
+    market: MarketInputs<'_>,
 ) -> Option<FeatureVector> {
     if candles.len() <= candle_index {
         return None;
@@ -1162,713 +1640,49 @@ pub fn compute_features_from_precomputed(
         return None;
     }
 
-    let close_now = pre.closes[candle_index];
-    let open_now = pre.opens[candle_index];
-    let high_now = pre.highs[candle_index];
-    let low_now = pre.lows[candle_index];
-    let prev_close = if candle_index > 0 {
-        pre.closes[candle_index - 1]
-    } else {
-        close_now
-    };
-
-    let overnight_return = if prev_close.abs() > EPSILON {
-        (open_now / prev_close) - 1.0
-    } else {
-        0.0
-    };
-    let intraday_range = (high_now - low_now).abs();
-    let high_low_range = safe_div(intraday_range, close_now.abs());
-    let close_open_return = safe_div(close_now - open_now, open_now.abs());
-    let true_range = intraday_range
-        .max((high_now - prev_close).abs())
-        .max((low_now - prev_close).abs());
-    let true_range_pct = safe_div(true_range, close_now.abs());
-    let close_range_position = if intraday_range > EPSILON {
-        (close_now - low_now) / intraday_range
-    } else {
-        0.5
-    };
-
-    let rolling_mean_short =
-        rolling_mean_at(&pre.returns, candle_index, config.volatility_short).unwrap_or(0.0);
-    let rolling_mean_long =
-        rolling_mean_at(&pre.returns, candle_index, config.volatility_long).unwrap_or(0.0);
-    let rolling_std_short =
-        rolling_std_at(&pre.returns, candle_index, config.volatility_short).unwrap_or(0.0);
-    let rolling_std_long =
-        rolling_std_at(&pre.returns, candle_index, config.volatility_long).unwrap_or(0.0);
-    let rolling_sharpe_short = if rolling_std_short.abs() <= EPSILON {
-        0.0
-    } else {
-        rolling_mean_short / rolling_std_short
-    };
-    let rolling_sharpe_long = if rolling_std_long.abs() <= EPSILON {
-        0.0
-    } else {
-        rolling_mean_long / rolling_std_long
-    };
-    let rolling_max_return =
-        rolling_max_at(&pre.returns, candle_index, config.volatility_long).unwrap_or(0.0);
-    let rolling_min_return =
-        rolling_min_at(&pre.returns, candle_index, config.volatility_long).unwrap_or(0.0);
-
-    let momentum_short = if candle_index + 1 > config.momentum_short {
-        let past = pre.closes[candle_index + 1 - config.momentum_short];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-    let momentum_long = if candle_index + 1 > config.momentum_long {
-        let past = pre.closes[candle_index + 1 - config.momentum_long];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-
-    let lagged_return_2d = if candle_index >= 2 {
-        let past = pre.closes[candle_index - 2];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-    let lagged_return_5d = if candle_index >= 5 {
-        let past = pre.closes[candle_index - 5];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-
-    let volume_change_1d = pre.volume_changes[candle_index];
-    let volume_mean_ratio_10 = rolling_mean_at(&pre.volumes, candle_index, 10)
-        .map_or(0.0, |avg| safe_div(pre.volumes[candle_index], avg) - 1.0);
-    let volume_mean_ratio_20 = rolling_mean_at(&pre.volumes, candle_index, 20)
-        .map_or(0.0, |avg| safe_div(pre.volumes[candle_index], avg) - 1.0);
-    let volume_volatility_20 = rolling_std_at(&pre.volume_changes, candle_index, 20).unwrap_or(0.0);
-    let dollar_volume_log = (close_now.abs() * pre.volumes[candle_index].abs()).ln_1p();
-
-    let ma_fast = pre
-        .ma_fast_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-    let ma_slow = pre
-        .ma_slow_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-    let ma_trend = pre
-        .ma_trend_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-    let ma_trend_slow = pre
-        .ma_trend_slow_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-
-    let ma_fast_over_slow = safe_div(ma_fast, ma_slow);
-    let ma_trend_over_slow = safe_div(ma_trend, ma_trend_slow);
-    let price_rel_ma_trend = safe_div(close_now, ma_trend) - 1.0;
-    let price_rel_ma_slow = safe_div(close_now, ma_slow) - 1.0;
-    let trend_strength = safe_div(close_now - ma_slow, ma_trend_slow);
-    let ma_trend_slope_5 = if candle_index >= 5 {
-        let past = pre
-            .ma_trend_series
-            .get(candle_index - 5)
-            .copied()
-            .unwrap_or(ma_trend);
-        safe_div(ma_trend - past, past.abs().max(EPSILON))
-    } else {
-        0.0
-    };
-
-    let rsi_value = pre
-        .rsi_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(50.0)
-        .clamp(0.0, 100.0);
-
-    let percent_k = pre.percent_k_series[candle_index].clamp(0.0, 100.0);
-    let percent_d = if candle_index + 1 >= config.stochastic_smooth {
-        let start = candle_index + 1 - config.stochastic_smooth;
-        let smooth_slice: Vec<f64> = pre.percent_k_series[start..=candle_index]
-            .iter()
-            .copied()
-            .filter(|value| value.is_finite())
-            .collect();
-        if smooth_slice.is_empty() {
-            percent_k
-        } else {
-            mean(&smooth_slice).unwrap_or(percent_k)
-        }
-    } else {
-        percent_k
-    };
-
-    let macd_hist = pre
-        .macd_hist_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(0.0);
-
-    let typical_price_now = *pre
-        .typical_prices
-        .get(candle_index)
-        .unwrap_or(&((close_now + high_now + low_now) / 3.0));
-    let cci_value = if candle_index + 1 >= config.cci_period {
-        let start = candle_index + 1 - config.cci_period;
-        let window = &pre.typical_prices[start..=candle_index];
-        let tp_mean = mean(window).unwrap_or(0.0);
-        let mean_dev = window
-            .iter()
-            .map(|value| (*value - tp_mean).abs())
-            .sum::<f64>()
-            / window.len() as f64;
-        if mean_dev <= EPSILON {
-            0.0
-        } else {
-            (typical_price_now - tp_mean) / (0.015 * mean_dev)
-        }
-    } else {
-        0.0
-    };
-
-    let williams_r = if candle_index + 1 >= config.stochastic_period {
-        let start = candle_index + 1 - config.stochastic_period;
-        let highest_high = pre.highs[start..=candle_index]
-            .iter()
-            .cloned()
-            .fold(f64::NEG_INFINITY, f64::max);
-        let lowest_low = pre.lows[start..=candle_index]
-            .iter()
-            .cloned()
-            .fold(f64::INFINITY, f64::min);
-        let range = highest_high - lowest_low;
-        if range.abs() > EPSILON {
-            -100.0 * (highest_high - close_now) / range
-        } else {
-            -50.0
-        }
-    } else {
-        -50.0
-    };
-
-    let atr = pre.atr_series.get(candle_index).copied().unwrap_or(0.0);
-    let atr_normalized = safe_div(atr, close_now.abs());
-    let range_to_atr = safe_div(intraday_range, atr);
-
-    let bb_index = candle_index + 1 - config.bollinger_period;
-    let (bollinger_pct_b, bollinger_bandwidth) =
-        if candle_index + 1 >= config.bollinger_period && bb_index < pre.bb_upper.len() {
-            let upper = pre.bb_upper[bb_index];
-            let lower = pre.bb_lower[bb_index];
-            let middle = pre
-                .bb_middle
-                .get(candle_index)
-                .copied()
-                .unwrap_or(close_now);
-            let span = upper - lower;
-            let pct_b = if span.abs() > EPSILON {
-                (close_now - lower) / span
-            } else {
-                0.5
-            };
-            let bandwidth = if middle.abs() > EPSILON {
-                span / middle
-            } else {
-                0.0
-            };
-            (pct_b, bandwidth)
-        } else {
-            (0.5, 0.0)
-        };
-
-    let breakout_high_ratio = rolling_max_at(&pre.closes, candle_index, config.bollinger_period)
-        .map_or(1.0, |max_c| safe_div(close_now, max_c));
-    let breakout_low_ratio = rolling_min_at(&pre.closes, candle_index, config.bollinger_period)
-        .map_or(1.0, |min_c| safe_div(close_now, min_c));
-
-    let upper_shadow = (high_now - open_now.max(close_now)).max(0.0);
-    let lower_shadow = (open_now.min(close_now) - low_now).max(0.0);
-    let body = (close_now - open_now).abs().max(EPSILON);
-    let upper_shadow_ratio = safe_div(upper_shadow, close_now.abs().max(EPSILON));
-    let lower_shadow_ratio = safe_div(lower_shadow, close_now.abs().max(EPSILON));
-    let shadow_to_body_upper = safe_div(upper_shadow, body);
-    let shadow_to_body_lower = safe_div(lower_shadow, body);
-
-    let return_volume_corr = rolling_corr_at(
-        &pre.returns,
-        &pre.volume_changes,
-        candle_index,
-        config.correlation_window,
-    )
-    .unwrap_or(0.0);
-
-    let cross_sectional = cross_context
-        .or_else(get_global_cross_sectional_context)
+    let cross = cross_context
         .as_ref()
         .and_then(|ctx| ctx.get_features(ticker, candles[candle_index].date))
         .unwrap_or_default();
-
-    let mut values = Vec::new();
-    values.extend_from_slice(&[
-        overnight_return,
-        high_low_range,
-        close_open_return,
-        true_range_pct,
-        close_range_position,
-        rolling_mean_short,
-        rolling_mean_long,
-        rolling_std_short,
-        rolling_std_long,
-        rolling_sharpe_short,
-        rolling_sharpe_long,
-        rolling_max_return,
-        rolling_min_return,
-        momentum_short,
-        momentum_long,
-        lagged_return_2d,
-        lagged_return_5d,
-        volume_change_1d,
-        volume_mean_ratio_10,
-        volume_mean_ratio_20,
-        volume_volatility_20,
-        dollar_volume_log,
-        ma_fast_over_slow,
-        ma_trend_over_slow,
-        price_rel_ma_trend,
-        price_rel_ma_slow,
-        trend_strength,
-        ma_trend_slope_5,
-        rsi_value,
-        percent_k,
-        percent_d,
-        macd_hist,
-        cci_value,
-        williams_r,
-        atr_normalized,
-        range_to_atr,
-        bollinger_pct_b,
-        bollinger_bandwidth,
-        breakout_high_ratio,
-        breakout_low_ratio,
-        upper_shadow_ratio,
-        lower_shadow_ratio,
-        shadow_to_body_upper,
-        shadow_to_body_lower,
-        return_volume_corr,
-        cross_sectional.return_rank,
-        cross_sectional.momentum_rank,
-        cross_sectional.volatility_rank,
-        cross_sectional.volume_rank,
-        cross_sectional.return_zscore,
-        cross_sectional.momentum_zscore,
-    ]);
+    let context = FeatureContext {
+        pre,
+        config: &config,
+        index: candle_index,
+        cross,
+        market: MarketFeatures::at(market, ticker, candles[candle_index].date),
+    };
+    let values = FEATURES
+        .iter()
+        .map(|feature| feature.compute(&context))
+        .collect();
 
     Some(FeatureVector { values })
 }
 
-#[allow(dead_code)]
+/// Features at `candle_index` computed from the candles up to it. Every input series is causal,
+/// so this matches `compute_features_from_precomputed` over the whole history.
 pub fn compute_features_from_refs(
     ticker: &str,
     candles: &[&Candle],
     candle_index: usize,
     config: FeatureConfig,
     cross_context: Option<Arc<CrossSectionalContext>>,
+    market: MarketInputs<'_>,
 ) -> Option<FeatureVector> {
-    if candles.len() <= candle_index {
-        return None;
-    }
-
-    let required_history = minimum_history_needed(&config);
-    if candle_index + 1 < required_history {
+    if candles.len() <= candle_index || candle_index + 1 < minimum_history_needed(&config) {
         return None;
     }
-
-    let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
-    let highs: Vec<f64> = candles.iter().map(|c| c.high).collect();
-    let lows: Vec<f64> = candles.iter().map(|c| c.low).collect();
-    let opens: Vec<f64> = candles.iter().map(|c| c.open).collect();
-    let volumes: Vec<f64> = candles.iter().map(|c| c.volume_shares as f64).collect();
-
-    let close_now = closes[candle_index];
-    let open_now = opens[candle_index];
-    let high_now = highs[candle_index];
-    let low_now = lows[candle_index];
-    let prev_close = if candle_index > 0 {
-        closes[candle_index - 1]
-    } else {
-        close_now
-    };
-
-    let mut returns = vec![0.0; closes.len()];
-    for i in 1..closes.len() {
-        let prev = closes[i - 1];
-        if prev.abs() > EPSILON {
-            returns[i] = (closes[i] - prev) / prev;
-        }
-    }
-
-    let mut volume_changes = vec![0.0; volumes.len()];
-    for i in 1..volumes.len() {
-        let prev = volumes[i - 1];
-        if prev.abs() > EPSILON {
-            volume_changes[i] = (volumes[i] / prev) - 1.0;
-        }
-    }
-
-    let overnight_return = if prev_close.abs() > EPSILON {
-        (open_now / prev_close) - 1.0
-    } else {
-        0.0
-    };
-    let intraday_range = (high_now - low_now).abs();
-    let high_low_range = safe_div(intraday_range, close_now.abs());
-    let close_open_return = safe_div(close_now - open_now, open_now.abs());
-    let true_range = intraday_range
-        .max((high_now - prev_close).abs())
-        .max((low_now - prev_close).abs());
-    let true_range_pct = safe_div(true_range, close_now.abs());
-    let close_range_position = if intraday_range > EPSILON {
-        (close_now - low_now) / intraday_range
-    } else {
-        0.5
-    };
-
-    let rolling_mean_short =
-        rolling_mean_at(&returns, candle_index, config.volatility_short).unwrap_or(0.0);
-    let rolling_mean_long =
-        rolling_mean_at(&returns, candle_index, config.volatility_long).unwrap_or(0.0);
-    let rolling_std_short =
-        rolling_std_at(&returns, candle_index, config.volatility_short).unwrap_or(0.0);
-    let rolling_std_long =
-        rolling_std_at(&returns, candle_index, config.volatility_long).unwrap_or(0.0);
-    let rolling_sharpe_short = if rolling_std_short.abs() <= EPSILON {
-        0.0
-    } else {
-        rolling_mean_short / rolling_std_short
-    };
-    let rolling_sharpe_long = if rolling_std_long.abs() <= EPSILON {
-        0.0
-    } else {
-        rolling_mean_long / rolling_std_long
-    };
-    let rolling_max_return =
-        rolling_max_at(&returns, candle_index, config.volatility_long).unwrap_or(0.0);
-    let rolling_min_return =
-        rolling_min_at(&returns, candle_index, config.volatility_long).unwrap_or(0.0);
-
-    let momentum_short = if candle_index + 1 > config.momentum_short {
-        let past = closes[candle_index + 1 - config.momentum_short];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-    let momentum_long = if candle_index + 1 > config.momentum_long {
-        let past = closes[candle_index + 1 - config.momentum_long];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-
-    let lagged_return_2d = if candle_index >= 2 {
-        let past = closes[candle_index - 2];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-    let lagged_return_5d = if candle_index >= 5 {
-        let past = closes[candle_index - 5];
-        if past.abs() > EPSILON {
-            (close_now / past) - 1.0
-        } else {
-            0.0
-        }
-    } else {
-        0.0
-    };
-
-    let volume_change_1d = volume_changes[candle_index];
-    let volume_mean_ratio_10 = rolling_mean_at(&volumes, candle_index, 10)
-        .map_or(0.0, |avg| safe_div(volumes[candle_index], avg) - 1.0);
-    let volume_mean_ratio_20 = rolling_mean_at(&volumes, candle_index, 20)
-        .map_or(0.0, |avg| safe_div(volumes[candle_index], avg) - 1.0);
-    let volume_volatility_20 = rolling_std_at(&volume_changes, candle_index, 20).unwrap_or(0.0);
-    let dollar_volume_log = (close_now.abs() * volumes[candle_index].abs()).ln_1p();
-
-    let ma_fast_series = indicators::calculate_sma(&closes, config.ma_fast);
-    let ma_slow_series = indicators::calculate_sma(&closes, config.ma_slow);
-    let ma_trend_series = indicators::calculate_sma(&closes, config.ma_trend);
-    let ma_trend_slow_series = indicators::calculate_sma(&closes, config.ma_trend_slow);
-
-    let ma_fast = ma_fast_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-    let ma_slow = ma_slow_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-    let ma_trend = ma_trend_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-    let ma_trend_slow = ma_trend_slow_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(close_now);
-
-    let ma_fast_over_slow = safe_div(ma_fast, ma_slow);
-    let ma_trend_over_slow = safe_div(ma_trend, ma_trend_slow);
-    let price_rel_ma_trend = safe_div(close_now, ma_trend) - 1.0;
-    let price_rel_ma_slow = safe_div(close_now, ma_slow) - 1.0;
-    let trend_strength = safe_div(close_now - ma_slow, ma_trend_slow);
-    let ma_trend_slope_5 = if candle_index >= 5 {
-        let past = ma_trend_series
-            .get(candle_index - 5)
-            .copied()
-            .unwrap_or(ma_trend);
-        safe_div(ma_trend - past, past.abs().max(EPSILON))
-    } else {
-        0.0
-    };
-
-    let rsi_series = indicators::calculate_rsi(&closes, config.rsi_period);
-    let rsi_value = rsi_series
-        .get(candle_index)
-        .copied()
-        .unwrap_or(50.0)
-        .clamp(0.0, 100.0);
-
-    let stoch_period = config.stochastic_period.max(2);
-    let mut percent_k_series = vec![f64::NAN; candle_index + 1];
-    for i in (stoch_period - 1)..=candle_index {
-        let start = i + 1 - stoch_period;
-        let highest_high = highs[start..=i]
-            .iter()
-            .cloned()
-            .fold(f64::NEG_INFINITY, f64::max);
-        let lowest_low = lows[start..=i]
-            .iter()
-            .cloned()
-            .fold(f64::INFINITY, f64::min);
-        let range = highest_high - lowest_low;
-        percent_k_series[i] = if range.abs() > EPSILON {
-            safe_div(closes[i] - lowest_low, range) * 100.0
-        } else {
-            50.0
-        };
-    }
-    let percent_k = percent_k_series[candle_index].clamp(0.0, 100.0);
-    let percent_d = if candle_index + 1 >= config.stochastic_smooth {
-        let start = candle_index + 1 - config.stochastic_smooth;
-        let smooth_slice: Vec<f64> = percent_k_series[start..=candle_index]
-            .iter()
-            .cloned()
-            .filter(|value| value.is_finite())
-            .collect();
-        if smooth_slice.is_empty() {
-            percent_k
-        } else {
-            mean(&smooth_slice).unwrap_or(percent_k)
-        }
-    } else {
-        percent_k
-    };
-
-    let (_, _, macd_hist_series) = indicators::calculate_macd(&closes, 12, 26, 9);
-    let macd_hist = macd_hist_series.get(candle_index).copied().unwrap_or(0.0);
-
-    let typical_prices: Vec<f64> = closes
-        .iter()
-        .zip(highs.iter())
-        .zip(lows.iter())
-        .map(|((c, h), l)| (c + h + l) / 3.0)
-        .collect();
-    let cci_value = if candle_index + 1 >= config.cci_period {
-        let start = candle_index + 1 - config.cci_period;
-        let window = &typical_prices[start..=candle_index];
-        let tp_mean = mean(window).unwrap_or(0.0);
-        let mean_dev = window
-            .iter()
-            .map(|value| (*value - tp_mean).abs())
-            .sum::<f64>()
-            / window.len() as f64;
-        if mean_dev <= EPSILON {
-            0.0
-        } else {
-            (typical_prices[candle_index] - tp_mean) / (0.015 * mean_dev)
-        }
-    } else {
-        0.0
-    };
-
-    let williams_r = if candle_index + 1 >= config.stochastic_period {
-        let start = candle_index + 1 - config.stochastic_period;
-        let highest_high = highs[start..=candle_index]
-            .iter()
-            .cloned()
-            .fold(f64::NEG_INFINITY, f64::max);
-        let lowest_low = lows[start..=candle_index]
-            .iter()
-            .cloned()
-            .fold(f64::INFINITY, f64::min);
-        let range = highest_high - lowest_low;
-        if range.abs() > EPSILON {
-            -100.0 * (highest_high - close_now) / range
-        } else {
-            -50.0
-        }
-    } else {
-        -50.0
-    };
-
-    let atr = indicators::calculate_atr_from_candles(candles, candle_index, config.atr_period)
-        .unwrap_or(0.0);
-    let atr_normalized = safe_div(atr, close_now.abs());
-    let range_to_atr = safe_div(intraday_range, atr);
-
-    let (bb_upper, bb_middle, bb_lower) = indicators::calculate_bollinger_bands(
-        &closes,
-        config.bollinger_period,
-        config.bollinger_std,
-    );
-    let bb_index = candle_index + 1 - config.bollinger_period;
-    let (bollinger_pct_b, bollinger_bandwidth) =
-        if candle_index + 1 >= config.bollinger_period && bb_index < bb_upper.len() {
-            let upper = bb_upper[bb_index];
-            let lower = bb_lower[bb_index];
-            let middle = bb_middle.get(candle_index).copied().unwrap_or(close_now);
-            let span = upper - lower;
-            let pct_b = if span.abs() > EPSILON {
-                (close_now - lower) / span
-            } else {
-                0.5
-            };
-            let bandwidth = if middle.abs() > EPSILON {
-                span / middle
-            } else {
-                0.0
-            };
-            (pct_b, bandwidth)
-        } else {
-            (0.5, 0.0)
-        };
-
-    let breakout_high_ratio = rolling_max_at(&closes, candle_index, config.bollinger_period)
-        .map_or(1.0, |max_c| safe_div(close_now, max_c));
-    let breakout_low_ratio = rolling_min_at(&closes, candle_index, config.bollinger_period)
-        .map_or(1.0, |min_c| safe_div(close_now, min_c));
-
-    let upper_shadow = (high_now - open_now.max(close_now)).max(0.0);
-    let lower_shadow = (open_now.min(close_now) - low_now).max(0.0);
-    let body = (close_now - open_now).abs().max(EPSILON);
-    let upper_shadow_ratio = safe_div(upper_shadow, close_now.abs().max(EPSILON));
-    let lower_shadow_ratio = safe_div(lower_shadow, close_now.abs().max(EPSILON));
-    let shadow_to_body_upper = safe_div(upper_shadow, body);
-    let shadow_to_body_lower = safe_div(lower_shadow, body);
-
-    let return_volume_corr = rolling_corr_at(
-        &returns,
-        &volume_changes,
+    let history = &candles[..=candle_index];
+    let pre = precompute_inputs_for_ticker(history, config)?;
+    compute_features_from_precomputed(
+        ticker,
+        history,
         candle_index,
-        config.correlation_window,
+        config,
+        &pre,
+        cross_context,
+        market,
     )
-    .unwrap_or(0.0);
-
-    let cross_sectional = cross_context
-        .or_else(get_global_cross_sectional_context)
-        .as_ref()
-        .and_then(|ctx| ctx.get_features(ticker, candles[candle_index].date))
-        .unwrap_or_default();
-
-    let mut values = Vec::new();
-    values.extend_from_slice(&[
-        overnight_return,
-        high_low_range,
-        close_open_return,
-        true_range_pct,
-        close_range_position,
-        rolling_mean_short,
-        rolling_mean_long,
-        rolling_std_short,
-        rolling_std_long,
-        rolling_sharpe_short,
-        rolling_sharpe_long,
-        rolling_max_return,
-        rolling_min_return,
-        momentum_short,
-        momentum_long,
-        lagged_return_2d,
-        lagged_return_5d,
-        volume_change_1d,
-        volume_mean_ratio_10,
-        volume_mean_ratio_20,
-        volume_volatility_20,
-        dollar_volume_log,
-        ma_fast_over_slow,
-        ma_trend_over_slow,
-        price_rel_ma_trend,
-        price_rel_ma_slow,
-        trend_strength,
-        ma_trend_slope_5,
-        rsi_value,
-        percent_k,
-        percent_d,
-        macd_hist,
-        cci_value,
-        williams_r,
-        atr_normalized,
-        range_to_atr,
-        bollinger_pct_b,
-        bollinger_bandwidth,
-        breakout_high_ratio,
-        breakout_low_ratio,
-        upper_shadow_ratio,
-        lower_shadow_ratio,
-        shadow_to_body_upper,
-        shadow_to_body_lower,
-        return_volume_corr,
-        cross_sectional.return_rank,
-        cross_sectional.momentum_rank,
-        cross_sectional.volatility_rank,
-        cross_sectional.volume_rank,
-        cross_sectional.return_zscore,
-        cross_sectional.momentum_zscore,
-    ]);
-
-    Some(FeatureVector { values })
 }
 
 impl LightGBMStrategy {
@@ -1947,16 +1761,21 @@ impl LightGBMStrategy {
         };
         let min_confidence = get_param_f64_clamped(&parameters, "minConfidence", 0.1, 0.0, 1.0);
         let model_bias = get_param_f64_clamped(&parameters, "modelBias", 0.01, -2.0, 2.0);
-        let model_id = template_id
-            .strip_prefix("lightgbm_")
+        let (backend, model_id) = match template_id.strip_prefix("nn_") {
+            Some(id) => (ModelBackend::NeuralNet, Some(id)),
+            None => (ModelBackend::Booster, template_id.strip_prefix("lightgbm_")),
+        };
+        let model_id = model_id
             .map(|value| value.trim().to_string())
             .filter(|value| !value.is_empty());
         Self {
             template_id,
+            feature_cache_key: feature_config.cache_key(),
             feature_config,
             min_confidence,
             model_bias,
             model_id,
+            backend,
             decision_summary: Mutex::new(LightGBMSummary::default()),
         }
     }
@@ -1988,6 +1807,15 @@ impl LightGBMStrategy {
         self.feature_config
     }
 
+    /// Calibrated models map the raw probability to their validation hit rate; uncalibrated
+    /// ones fall back to the `modelBias` logit shift.
+    fn calibrate_probability(&self, probability: f64, model: &ScoringModel) -> f64 {
+        match model.calibration() {
+            Some(calibration) => calibration.apply(probability),
+            None => self.apply_model_bias(probability),
+        }
+    }
+
     fn apply_model_bias(&self, probability: f64) -> f64 {
         if !self.model_bias.is_finite() || self.model_bias.abs() <= EPSILON {
             return probability.clamp(0.0, 1.0);
@@ -2000,19 +1828,12 @@ impl LightGBMStrategy {
         adjusted.clamp(0.0, 1.0)
     }
 
-    fn probability_from_distribution(&self, distribution: &[f64]) -> f64 {
-        if distribution.len() >= SIGNAL_BUCKET_COUNT {
-            distribution[SIGNAL_BUCKET_POSITIVE].clamp(0.0, 1.0)
-        } else {
-            distribution.last().copied().unwrap_or(0.5).clamp(0.0, 1.0)
-        }
-    }
-
     fn collect_features(
         &self,
         ticker: &str,
         candles: &[Candle],
         candle_index: usize,
+        context: &StrategyContext,
     ) -> FeatureStatus {
         let config = self.feature_config();
         let required = minimum_history_needed(&config);
@@ -2025,10 +1846,35 @@ impl LightGBMStrategy {
             return FeatureStatus::InsufficientHistory;
         }
 
-        let candle_refs: Vec<&Candle> = candles.iter().collect();
-        let cross_context = get_global_cross_sectional_context();
-        match compute_features_from_refs(ticker, &candle_refs, candle_index, config, cross_context)
-        {
+        let cross_context = context.cross_sectional.as_ref();
+        let market = MarketInputs::from_context(context);
+        let compute = || {
+            let candle_refs: Vec<&Candle> = candles.iter().collect();
+            compute_features_from_refs(
+                ticker,
+                &candle_refs,
+                candle_index,
+                config,
+                cross_context.cloned(),
+                market,
+            )
+        };
+        // Cached rows hold the market features too, so the key covers the inputs read.
+        let cache_key = match market.fingerprint(ticker) {
+            0 => self.feature_cache_key,
+            market_key => {
+                let mut hasher = DefaultHasher::new();
+                (self.feature_cache_key, market_key).hash(&mut hasher);
+                hasher.finish()
+            }
+        };
+        let matrix =
+            cross_context.and_then(|context| context.feature_matrix(cache_key, ticker, candles));
+        let features = match matrix {
+            Some(matrix) => matrix.rows[candle_index].get_or_init(compute).clone(),
+            None => compute(),
+        };
+        match features {
             Some(features) => {
                 debug!(
                     "LightGBM features @{} for {} => {} values",
@@ -2048,26 +1894,62 @@ impl LightGBMStrategy {
         }
     }
 
-    fn predict_distribution(&self, features: &FeatureVector) -> Option<Vec<f64>> {
-        let booster = match load_lightgbm_booster_for_model(self.model_id.as_deref()) {
-            Ok(Some(model)) => model,
-            Ok(None) => return None,
-            Err(err) => {
-                warn!("LightGBM model unavailable: {err}");
-                return None;
+    /// The registered model for this template, or `None` (logged once) when it is missing.
+    fn resolve_model(&self) -> Option<ScoringModel> {
+        match self.backend {
+            ModelBackend::NeuralNet => {
+                let model_id = self.model_id.as_deref()?;
+                let model = neural_net::registered_model(model_id);
+                if model.is_none() {
+                    log_missing_model_once(model_id);
+                }
+                model.map(ScoringModel::NeuralNet)
             }
-        };
+            ModelBackend::Booster => {
+                match load_lightgbm_booster_for_model(self.model_id.as_deref()) {
+                    Ok(booster) => booster.map(ScoringModel::Booster),
+                    Err(err) => {
+                        warn!("LightGBM model unavailable: {err}");
+                        None
+                    }
+                }
+            }
+        }
+    }
 
-        if booster.num_features() != features.values.len() {
+    fn predict_distribution(
+        &self,
+        features: &FeatureVector,
+        model: &ScoringModel,
+    ) -> Option<Vec<f64>> {
+        if features.values.len() != FEATURES.len() {
             warn!(
-                "LightGBM model expects {} feature(s), but {} were computed",
-                booster.num_features(),
-                features.values.len()
+                "LightGBM features have {} value(s), but the registry declares {}",
+                features.values.len(),
+                FEATURES.len()
             );
             return None;
         }
 
-        booster.predict_distribution(&features.values)
+        match model {
+            ScoringModel::Booster(booster) => {
+                let selected = booster.features.select(&features.values);
+                if selected.len() != booster.feature_count {
+                    warn!(
+                        "LightGBM model expects {} feature(s), but {} were computed",
+                        booster.feature_count,
+                        selected.len()
+                    );
+                    return None;
+                }
+                booster.predict_distribution(&selected)
+            }
+            ScoringModel::NeuralNet(model) => {
+                let probability =
+                    model.predict_probability(&model.features().select(&features.values))?;
+                Some(vec![1.0 - probability, probability])
+            }
+        }
     }
 
     fn log_summary(&self) {
@@ -2097,10 +1979,37 @@ impl super::Strategy for LightGBMStrategy {
         ticker: &str,
         candles: &[Candle],
         candle_index: usize,
+    ) -> StrategySignal {
+        let context = StrategyContext {
+            model: self.resolve_model(),
+            ..StrategyContext::default()
+        };
+        self.generate_signal_with_context(ticker, candles, candle_index, &context)
+    }
+
+    fn prepare_context(
+        &self,
+        candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        cross_sectional: &CrossSectionalCache,
+    ) -> StrategyContext {
+        StrategyContext {
+            model: self.resolve_model(),
+            cross_sectional: cross_sectional.context_for(candles_by_ticker),
+            volatility_index: None,
+            auxiliary: None,
+        }
+    }
+
+    fn generate_signal_with_context(
+        &self,
+        ticker: &str,
+        candles: &[Candle],
+        candle_index: usize,
+        context: &StrategyContext,
     ) -> StrategySignal {
         self.update_summary(|summary| summary.record_invocation());
 
-        let snapshot = match self.collect_features(ticker, candles, candle_index) {
+        let snapshot = match self.collect_features(ticker, candles, candle_index, context) {
             FeatureStatus::Vector(features) => features,
             FeatureStatus::OutOfBounds => {
                 self.update_summary(|summary| summary.record_out_of_bounds());
@@ -2129,7 +2038,12 @@ impl super::Strategy for LightGBMStrategy {
             }
         };
 
-        let distribution = match self.predict_distribution(&snapshot) {
+        let Some(model) = context.model.as_ref() else {
+            self.update_summary(|summary| summary.record_probability_missing());
+            debug!("LightGBM holding: model missing @{}", candle_index);
+            return hold_signal();
+        };
+        let distribution = match self.predict_distribution(&snapshot, model) {
             Some(values) if !values.is_empty() => values,
             _ => {
                 self.update_summary(|summary| summary.record_probability_missing());
@@ -2150,8 +2064,8 @@ impl super::Strategy for LightGBMStrategy {
             return hold_signal();
         }
 
-        let raw_probability = self.probability_from_distribution(&distribution);
-        let probability = self.apply_model_bias(raw_probability);
+        let raw_probability = probability_from_distribution(&distribution);
+        let probability = self.calibrate_probability(raw_probability, model);
 
         if let Some((bucket_idx, bucket_prob)) = distribution
             .iter()
@@ -2202,17 +2116,188 @@ pub fn load_model_from_path(path: impl AsRef<Path>) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::LightGBMStrategy;
+    use super::features::{FeatureSet, FEATURES};
+    use super::{
+        compute_features_from_precomputed, compute_features_from_refs,
+        precompute_inputs_for_ticker, probability_from_distribution, CrossSectionalCache,
+        CrossSectionalContext, FeatureConfig, MarketFeatures, MarketInputs,
+    };
+    use crate::models::{Candle, EconomicCalendar, EconomicEventKind};
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
     use std::collections::HashMap;
+    use std::fs;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn build_candles(count: usize) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2021, 1, 4, 0, 0, 0).unwrap();
+        (0..count)
+            .map(|idx| {
+                let close = 100.0 + (idx as f64 / 7.0).sin();
+                Candle {
+                    ticker: "AAA".to_string(),
+                    date: start + Duration::days(idx as i64),
+                    open: close,
+                    high: close * 1.01,
+                    low: close * 0.99,
+                    close,
+                    unadjusted_close: None,
+                    volume_shares: 1_000_000,
+                }
+            })
+            .collect()
+    }
 
     #[test]
     fn probability_from_distribution_binary_uses_positive_class() {
-        let strat = LightGBMStrategy::new("lightgbm_test".to_string(), HashMap::new());
-
-        let p_hit = strat.probability_from_distribution(&[0.15, 0.85]);
+        let p_hit = probability_from_distribution(&[0.15, 0.85]);
         assert!(
             (p_hit - 0.85).abs() < 1e-6,
             "expected hit prob 0.85, got {p_hit}"
         );
     }
+
+    #[test]
+    fn feature_matrix_is_shared_per_config_and_rebuilt_for_other_series() {
+        let candles = build_candles(40);
+        let mut candles_by_ticker = HashMap::new();
+        candles_by_ticker.insert("AAA".to_string(), candles.iter().collect::<Vec<_>>());
+        let context = CrossSectionalContext::new(&candles_by_ticker).expect("context");
+        let key = FeatureConfig::default().cache_key();
+
+        let first = context.feature_matrix(key, "AAA", &candles).unwrap();
+        let second = context.feature_matrix(key, "AAA", &candles).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let shorter = context.feature_matrix(key, "AAA", &candles[..20]).unwrap();
+        assert!(!Arc::ptr_eq(&first, &shorter));
+        assert_eq!(shorter.rows.len(), 20);
+    }
+
+    fn by_ticker(candles: &[Candle]) -> HashMap<String, Vec<&Candle>> {
+        let mut map = HashMap::new();
+        map.insert("AAA".to_string(), candles.iter().collect());
+        map
+    }
+
+    #[test]
+    fn cross_sectional_cache_reuses_and_persists_contexts_per_candle_set() {
+        let candles = build_candles(60);
+        let other = build_candles(80);
+        let directory = std::env::temp_dir().join(format!("cross_sectional_{}", Uuid::new_v4()));
+
+        let cache = CrossSectionalCache::with_directory(directory.clone());
+        let first = cache.context_for(&by_ticker(&candles)).unwrap();
+        let again = cache.context_for(&by_ticker(&candles)).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        let longer = cache.context_for(&by_ticker(&other)).unwrap();
+        assert!(!Arc::ptr_eq(&first, &longer));
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 2);
+
+        let date = candles[40].date;
+        let restored = CrossSectionalCache::with_directory(directory.clone())
+            .context_for(&by_ticker(&candles))
+            .unwrap();
+        assert_eq!(
+            restored
+                .get_features("AAA", date)
+                .map(|features| features.momentum_rank),
+            first
+                .get_features("AAA", date)
+                .map(|features| features.momentum_rank)
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn registry_vector_matches_between_history_and_precomputed_inputs() {
+        let candles = build_candles(320);
+        let refs: Vec<&Candle> = candles.iter().collect();
+        let config = FeatureConfig::default();
+        let pre = precompute_inputs_for_ticker(&refs, config).unwrap();
+        let mut computed = 0;
+        for idx in 0..refs.len() {
+            let precomputed = compute_features_from_precomputed(
+                "AAA",
+                &refs,
+                idx,
+                config,
+                &pre,
+                None,
+                MarketInputs::default(),
+            );
+            let from_history = compute_features_from_refs(
+                "AAA",
+                &refs,
+                idx,
+                config,
+                None,
+                MarketInputs::default(),
+            );
+            assert_eq!(
+                precomputed.as_ref().map(|vector| &vector.values),
+                from_history.as_ref().map(|vector| &vector.values)
+            );
+            if let Some(vector) = precomputed {
+                assert_eq!(vector.values.len(), FEATURES.len());
+                computed += 1;
+            }
+        }
+        assert!(computed > 0);
+    }
+
+    #[test]
+    fn market_features_read_the_economic_calendar() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        let calendar = EconomicCalendar::from_events(&[
+            (EconomicEventKind::Fomc, day(12)),
+            (EconomicEventKind::Cpi, day(12)),
+        ]);
+        let market = MarketInputs {
+            volatility_index: None,
+            economic_calendar: Some(&calendar),
+            short_interest: None,
+            sentiment: None,
+        };
+        let at = |d| {
+            MarketFeatures::at(
+                market,
+                "AAA",
+                day(d).and_hms_opt(0, 0, 0).unwrap().and_utc(),
+            )
+        };
+
+        let before = at(6);
+        assert!((before.days_until_fomc - 0.2).abs() < 1e-12);
+        assert_eq!(before.economic_event_day, 0.0);
+        let release = at(12);
+        assert_eq!(release.days_until_cpi, 0.0);
+        assert_eq!(release.economic_event_day, 1.0);
+        assert_eq!(at(13).days_until_fomc, 1.0);
+    }
+
+    #[test]
+    fn feature_sets_select_named_columns() {
+        let mut names: Vec<&str> = FEATURES.iter().map(|feature| feature.name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), FEATURES.len());
+
+        let enabled = vec!["rsi".to_string(), "overnight_return".to_string()];
+        let set = FeatureSet::from_lists(&enabled, &[]).unwrap();
+        assert_eq!(set.names(), vec!["overnight_return", "rsi"]);
+        let full: Vec<f64> = (0..FEATURES.len()).map(|idx| idx as f64).collect();
+        assert_eq!(set.select(&full), vec![0.0, 28.0]);
+        assert!(set.check_column_count(2).is_ok());
+        assert!(set.check_column_count(FEATURES.len()).is_err());
+
+        let without_rsi = FeatureSet::from_lists(&[], &["rsi".to_string()]).unwrap();
+        assert_eq!(without_rsi.len(), FEATURES.len() - 1);
+        assert!(FeatureSet::from_lists(&["unknown".to_string()], &[]).is_err());
+        assert!(FeatureSet::from_lists(&enabled, &enabled).is_err());
+        assert_eq!(
+            FeatureSet::from_names::<&str>(&[]).unwrap(),
+            FeatureSet::default()
+        );
+    }
 }
@@ -0,0 +1,488 @@
+//! Declarative registry of the model feature vector. Every feature is a named computation over
+//! the precomputed per-ticker series at one candle; training rows and inference both build
+//! their vectors from `FEATURES`, so a feature added here reaches both in the same column.
+
+use super::{
+    mean, rolling_corr_at, rolling_max_at, rolling_mean_at, rolling_min_at, rolling_std_at,
+    safe_div, CrossSectionalFeatures, FeatureConfig, MarketFeatures, PrecomputedInputs, EPSILON,
+};
+use anyhow::{anyhow, Result};
+
+/// Everything a feature may read at `index`.
+pub(super) struct FeatureContext<'a> {
+    pub(super) pre: &'a PrecomputedInputs,
+    pub(super) config: &'a FeatureConfig,
+    pub(super) index: usize,
+    pub(super) cross: CrossSectionalFeatures,
+    pub(super) market: MarketFeatures,
+}
+
+impl FeatureContext<'_> {
+    fn close(&self) -> f64 {
+        self.pre.closes[self.index]
+    }
+
+    fn open(&self) -> f64 {
+        self.pre.opens[self.index]
+    }
+
+    fn high(&self) -> f64 {
+        self.pre.highs[self.index]
+    }
+
+    fn low(&self) -> f64 {
+        self.pre.lows[self.index]
+    }
+
+    fn prev_close(&self) -> f64 {
+        if self.index > 0 {
+            self.pre.closes[self.index - 1]
+        } else {
+            self.close()
+        }
+    }
+
+    fn intraday_range(&self) -> f64 {
+        (self.high() - self.low()).abs()
+    }
+
+    /// Return from the close at `past_index` to the current close.
+    fn return_since(&self, past_index: Option<usize>) -> f64 {
+        match past_index.map(|idx| self.pre.closes[idx]) {
+            Some(past) if past.abs() > EPSILON => (self.close() / past) - 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Return over a window of `period` closes ending at the current one.
+    fn momentum(&self, period: usize) -> f64 {
+        self.return_since((self.index + 1 > period).then(|| self.index + 1 - period))
+    }
+
+    fn lagged_return(&self, bars: usize) -> f64 {
+        self.return_since(self.index.checked_sub(bars))
+    }
+
+    fn returns_mean(&self, window: usize) -> f64 {
+        rolling_mean_at(&self.pre.returns, self.index, window).unwrap_or(0.0)
+    }
+
+    fn returns_std(&self, window: usize) -> f64 {
+        rolling_std_at(&self.pre.returns, self.index, window).unwrap_or(0.0)
+    }
+
+    fn returns_sharpe(&self, window: usize) -> f64 {
+        let std = self.returns_std(window);
+        if std.abs() <= EPSILON {
+            0.0
+        } else {
+            self.returns_mean(window) / std
+        }
+    }
+
+    fn volume_mean_ratio(&self, window: usize) -> f64 {
+        rolling_mean_at(&self.pre.volumes, self.index, window)
+            .map_or(0.0, |avg| safe_div(self.pre.volumes[self.index], avg) - 1.0)
+    }
+
+    fn series_or_close(&self, series: &[f64]) -> f64 {
+        series.get(self.index).copied().unwrap_or(self.close())
+    }
+
+    fn ma_fast(&self) -> f64 {
+        self.series_or_close(&self.pre.ma_fast_series)
+    }
+
+    fn ma_slow(&self) -> f64 {
+        self.series_or_close(&self.pre.ma_slow_series)
+    }
+
+    fn ma_trend(&self) -> f64 {
+        self.series_or_close(&self.pre.ma_trend_series)
+    }
+
+    fn ma_trend_slow(&self) -> f64 {
+        self.series_or_close(&self.pre.ma_trend_slow_series)
+    }
+
+    fn percent_k(&self) -> f64 {
+        self.pre.percent_k_series[self.index].clamp(0.0, 100.0)
+    }
+
+    fn atr(&self) -> f64 {
+        self.pre.atr_series.get(self.index).copied().unwrap_or(0.0)
+    }
+
+    /// Bollinger %B and bandwidth, or the neutral (0.5, 0.0) before the bands exist.
+    fn bollinger(&self) -> (f64, f64) {
+        let period = self.config.bollinger_period;
+        if self.index + 1 < period || self.index + 1 - period >= self.pre.bb_upper.len() {
+            return (0.5, 0.0);
+        }
+        let bb_index = self.index + 1 - period;
+        let upper = self.pre.bb_upper[bb_index];
+        let lower = self.pre.bb_lower[bb_index];
+        let middle = self.series_or_close(&self.pre.bb_middle);
+        let span = upper - lower;
+        let pct_b = if span.abs() > EPSILON {
+            (self.close() - lower) / span
+        } else {
+            0.5
+        };
+        let bandwidth = if middle.abs() > EPSILON {
+            span / middle
+        } else {
+            0.0
+        };
+        (pct_b, bandwidth)
+    }
+
+    fn upper_shadow(&self) -> f64 {
+        (self.high() - self.open().max(self.close())).max(0.0)
+    }
+
+    fn lower_shadow(&self) -> f64 {
+        (self.open().min(self.close()) - self.low()).max(0.0)
+    }
+
+    fn body(&self) -> f64 {
+        (self.close() - self.open()).abs().max(EPSILON)
+    }
+}
+
+/// One named column of the feature vector.
+pub struct FeatureDefinition {
+    pub name: &'static str,
+    compute: fn(&FeatureContext) -> f64,
+}
+
+impl FeatureDefinition {
+    const fn new(name: &'static str, compute: fn(&FeatureContext) -> f64) -> Self {
+        Self { name, compute }
+    }
+
+    pub(super) fn compute(&self, context: &FeatureContext) -> f64 {
+        (self.compute)(context)
+    }
+}
+
+/// Every feature the engine can compute, in the column order of the full vector.
+pub const FEATURES: &[FeatureDefinition] = &[
+    FeatureDefinition::new("overnight_return", |ctx| {
+        let prev_close = ctx.prev_close();
+        if prev_close.abs() > EPSILON {
+            (ctx.open() / prev_close) - 1.0
+        } else {
+            0.0
+        }
+    }),
+    FeatureDefinition::new("high_low_range", |ctx| {
+        safe_div(ctx.intraday_range(), ctx.close().abs())
+    }),
+    FeatureDefinition::new("close_open_return", |ctx| {
+        safe_div(ctx.close() - ctx.open(), ctx.open().abs())
+    }),
+    FeatureDefinition::new("true_range_pct", |ctx| {
+        let prev_close = ctx.prev_close();
+        let true_range = ctx
+            .intraday_range()
+            .max((ctx.high() - prev_close).abs())
+            .max((ctx.low() - prev_close).abs());
+        safe_div(true_range, ctx.close().abs())
+    }),
+    FeatureDefinition::new("close_range_position", |ctx| {
+        let range = ctx.intraday_range();
+        if range > EPSILON {
+            (ctx.close() - ctx.low()) / range
+        } else {
+            0.5
+        }
+    }),
+    FeatureDefinition::new("rolling_mean_short", |ctx| {
+        ctx.returns_mean(ctx.config.volatility_short)
+    }),
+    FeatureDefinition::new("rolling_mean_long", |ctx| {
+        ctx.returns_mean(ctx.config.volatility_long)
+    }),
+    FeatureDefinition::new("rolling_std_short", |ctx| {
+        ctx.returns_std(ctx.config.volatility_short)
+    }),
+    FeatureDefinition::new("rolling_std_long", |ctx| {
+        ctx.returns_std(ctx.config.volatility_long)
+    }),
+    FeatureDefinition::new("rolling_sharpe_short", |ctx| {
+        ctx.returns_sharpe(ctx.config.volatility_short)
+    }),
+    FeatureDefinition::new("rolling_sharpe_long", |ctx| {
+        ctx.returns_sharpe(ctx.config.volatility_long)
+    }),
+    FeatureDefinition::new("rolling_max_return", |ctx| {
+        rolling_max_at(&ctx.pre.returns, ctx.index, ctx.config.volatility_long).unwrap_or(0.0)
+    }),
+    FeatureDefinition::new("rolling_min_return", |ctx| {
+        rolling_min_at(&ctx.pre.returns, ctx.index, ctx.config.volatility_long).unwrap_or(0.0)
+    }),
+    FeatureDefinition::new("momentum_short", |ctx| {
+        ctx.momentum(ctx.config.momentum_short)
+    }),
+    FeatureDefinition::new("momentum_long", |ctx| {
+        ctx.momentum(ctx.config.momentum_long)
+    }),
+    FeatureDefinition::new("lagged_return_2d", |ctx| ctx.lagged_return(2)),
+    FeatureDefinition::new("lagged_return_5d", |ctx| ctx.lagged_return(5)),
+    FeatureDefinition::new("volume_change_1d", |ctx| ctx.pre.volume_changes[ctx.index]),
+    FeatureDefinition::new("volume_mean_ratio_10", |ctx| ctx.volume_mean_ratio(10)),
+    FeatureDefinition::new("volume_mean_ratio_20", |ctx| ctx.volume_mean_ratio(20)),
+    FeatureDefinition::new("volume_volatility_20", |ctx| {
+        rolling_std_at(&ctx.pre.volume_changes, ctx.index, 20).unwrap_or(0.0)
+    }),
+    FeatureDefinition::new("dollar_volume_log", |ctx| {
+        (ctx.close().abs() * ctx.pre.volumes[ctx.index].abs()).ln_1p()
+    }),
+    FeatureDefinition::new("ma_fast_over_slow", |ctx| {
+        safe_div(ctx.ma_fast(), ctx.ma_slow())
+    }),
+    FeatureDefinition::new("ma_trend_over_slow", |ctx| {
+        safe_div(ctx.ma_trend(), ctx.ma_trend_slow())
+    }),
+    FeatureDefinition::new("price_rel_ma_trend", |ctx| {
+        safe_div(ctx.close(), ctx.ma_trend()) - 1.0
+    }),
+    FeatureDefinition::new("price_rel_ma_slow", |ctx| {
+        safe_div(ctx.close(), ctx.ma_slow()) - 1.0
+    }),
+    FeatureDefinition::new("trend_strength", |ctx| {
+        safe_div(ctx.close() - ctx.ma_slow(), ctx.ma_trend_slow())
+    }),
+    FeatureDefinition::new("ma_trend_slope_5", |ctx| {
+        if ctx.index < 5 {
+            return 0.0;
+        }
+        let ma_trend = ctx.ma_trend();
+        let past = ctx
+            .pre
+            .ma_trend_series
+            .get(ctx.index - 5)
+            .copied()
+            .unwrap_or(ma_trend);
+        safe_div(ma_trend - past, past.abs().max(EPSILON))
+    }),
+    FeatureDefinition::new("rsi", |ctx| {
+        ctx.pre
+            .rsi_series
+            .get(ctx.index)
+            .copied()
+            .unwrap_or(50.0)
+            .clamp(0.0, 100.0)
+    }),
+    FeatureDefinition::new("stochastic_k", |ctx| ctx.percent_k()),
+    FeatureDefinition::new("stochastic_d", |ctx| {
+        let percent_k = ctx.percent_k();
+        if ctx.index + 1 < ctx.config.stochastic_smooth {
+            return percent_k;
+        }
+        let start = ctx.index + 1 - ctx.config.stochastic_smooth;
+        let smooth_slice: Vec<f64> = ctx.pre.percent_k_series[start..=ctx.index]
+            .iter()
+            .copied()
+            .filter(|value| value.is_finite())
+            .collect();
+        mean(&smooth_slice).unwrap_or(percent_k)
+    }),
+    FeatureDefinition::new("macd_histogram", |ctx| {
+        ctx.pre
+            .macd_hist_series
+            .get(ctx.index)
+            .copied()
+            .unwrap_or(0.0)
+    }),
+    FeatureDefinition::new("cci", |ctx| {
+        let period = ctx.config.cci_period;
+        if ctx.index + 1 < period {
+            return 0.0;
+        }
+        let typical_price_now = ctx
+            .pre
+            .typical_prices
+            .get(ctx.index)
+            .copied()
+            .unwrap_or((ctx.close() + ctx.high() + ctx.low()) / 3.0);
+        let window = &ctx.pre.typical_prices[ctx.index + 1 - period..=ctx.index];
+        let tp_mean = mean(window).unwrap_or(0.0);
+        let mean_dev = window
+            .iter()
+            .map(|value| (*value - tp_mean).abs())
+            .sum::<f64>()
+            / window.len() as f64;
+        if mean_dev <= EPSILON {
+            0.0
+        } else {
+            (typical_price_now - tp_mean) / (0.015 * mean_dev)
+        }
+    }),
+    FeatureDefinition::new("williams_r", |ctx| {
+        let period = ctx.config.stochastic_period;
+        if ctx.index + 1 < period {
+            return -50.0;
+        }
+        let start = ctx.index + 1 - period;
+        let highest_high = ctx.pre.highs[start..=ctx.index]
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let lowest_low = ctx.pre.lows[start..=ctx.index]
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let range = highest_high - lowest_low;
+        if range.abs() > EPSILON {
+            -100.0 * (highest_high - ctx.close()) / range
+        } else {
+            -50.0
+        }
+    }),
+    FeatureDefinition::new("atr_normalized", |ctx| {
+        safe_div(ctx.atr(), ctx.close().abs())
+    }),
+    FeatureDefinition::new("range_to_atr", |ctx| {
+        safe_div(ctx.intraday_range(), ctx.atr())
+    }),
+    FeatureDefinition::new("bollinger_pct_b", |ctx| ctx.bollinger().0),
+    FeatureDefinition::new("bollinger_bandwidth", |ctx| ctx.bollinger().1),
+    FeatureDefinition::new("breakout_high_ratio", |ctx| {
+        rolling_max_at(&ctx.pre.closes, ctx.index, ctx.config.bollinger_period)
+            .map_or(1.0, |max_close| safe_div(ctx.close(), max_close))
+    }),
+    FeatureDefinition::new("breakout_low_ratio", |ctx| {
+        rolling_min_at(&ctx.pre.closes, ctx.index, ctx.config.bollinger_period)
+            .map_or(1.0, |min_close| safe_div(ctx.close(), min_close))
+    }),
+    FeatureDefinition::new("upper_shadow_ratio", |ctx| {
+        safe_div(ctx.upper_shadow(), ctx.close().abs().max(EPSILON))
+    }),
+    FeatureDefinition::new("lower_shadow_ratio", |ctx| {
+        safe_div(ctx.lower_shadow(), ctx.close().abs().max(EPSILON))
+    }),
+    FeatureDefinition::new("shadow_to_body_upper", |ctx| {
+        safe_div(ctx.upper_shadow(), ctx.body())
+    }),
+    FeatureDefinition::new("shadow_to_body_lower", |ctx| {
+        safe_div(ctx.lower_shadow(), ctx.body())
+    }),
+    FeatureDefinition::new("return_volume_corr", |ctx| {
+        rolling_corr_at(
+            &ctx.pre.returns,
+            &ctx.pre.volume_changes,
+            ctx.index,
+            ctx.config.correlation_window,
+        )
+        .unwrap_or(0.0)
+    }),
+    FeatureDefinition::new("cross_return_rank", |ctx| ctx.cross.return_rank),
+    FeatureDefinition::new("cross_momentum_rank", |ctx| ctx.cross.momentum_rank),
+    FeatureDefinition::new("cross_volatility_rank", |ctx| ctx.cross.volatility_rank),
+    FeatureDefinition::new("cross_volume_rank", |ctx| ctx.cross.volume_rank),
+    FeatureDefinition::new("cross_return_zscore", |ctx| ctx.cross.return_zscore),
+    FeatureDefinition::new("cross_momentum_zscore", |ctx| ctx.cross.momentum_zscore),
+    FeatureDefinition::new("volatility_index_level", |ctx| {
+        ctx.market.volatility_index_level
+    }),
+    FeatureDefinition::new("volatility_index_change_5d", |ctx| {
+        ctx.market.volatility_index_change_5d
+    }),
+    FeatureDefinition::new("days_until_fomc", |ctx| ctx.market.days_until_fomc),
+    FeatureDefinition::new("days_until_cpi", |ctx| ctx.market.days_until_cpi),
+    FeatureDefinition::new("economic_event_day", |ctx| ctx.market.economic_event_day),
+    FeatureDefinition::new("short_interest_ratio", |ctx| {
+        ctx.market.short_interest_ratio
+    }),
+    FeatureDefinition::new("borrow_fee_rate", |ctx| ctx.market.borrow_fee_rate),
+    FeatureDefinition::new("sentiment_score", |ctx| ctx.market.sentiment_score),
+    FeatureDefinition::new("sentiment_missing", |ctx| ctx.market.sentiment_missing),
+];
+
+/// Ordered subset of `FEATURES` used by one model. Models store the names of their columns, so
+/// a model trained on a subset is always scored with the same columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureSet {
+    indices: Vec<usize>,
+}
+
+impl Default for FeatureSet {
+    fn default() -> Self {
+        Self {
+            indices: (0..FEATURES.len()).collect(),
+        }
+    }
+}
+
+impl FeatureSet {
+    /// `enabled` features (all when empty) in registry order, minus the `disabled` ones.
+    /// Unknown names are an error.
+    pub fn from_lists(enabled: &[String], disabled: &[String]) -> Result<Self> {
+        for name in enabled.iter().chain(disabled) {
+            feature_index(name)?;
+        }
+        let indices: Vec<usize> = FEATURES
+            .iter()
+            .enumerate()
+            .filter(|(_, feature)| {
+                (enabled.is_empty() || enabled.iter().any(|name| name == feature.name))
+                    && !disabled.iter().any(|name| name == feature.name)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        if indices.is_empty() {
+            return Err(anyhow!("Feature selection leaves no features enabled"));
+        }
+        Ok(Self { indices })
+    }
+
+    /// Feature set stored with a model, in the stored column order.
+    pub fn from_names<S: AsRef<str>>(names: &[S]) -> Result<Self> {
+        if names.is_empty() {
+            return Ok(Self::default());
+        }
+        let indices = names
+            .iter()
+            .map(|name| feature_index(name.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { indices })
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.indices.iter().map(|&idx| FEATURES[idx].name).collect()
+    }
+
+    /// Columns of this set taken from a full feature vector.
+    pub fn select(&self, full: &[f64]) -> Vec<f64> {
+        self.indices.iter().map(|&idx| full[idx]).collect()
+    }
+
+    /// Errors unless a model expecting `feature_count` columns matches this set.
+    pub fn check_column_count(&self, feature_count: usize) -> Result<()> {
+        if feature_count != self.len() {
+            return Err(anyhow!(
+                "Model expects {} feature column(s) but its feature set has {}",
+                feature_count,
+                self.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn feature_index(name: &str) -> Result<usize> {
+    FEATURES
+        .iter()
+        .position(|feature| feature.name == name)
+        .ok_or_else(|| anyhow!("Unknown feature: {}", name))
+}
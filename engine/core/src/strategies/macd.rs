@@ -1,9 +1,17 @@
 use crate::indicators;
 use crate::models::*;
 use crate::param_utils::{get_param_f64, get_param_usize};
+use crate::strategy::ParameterSpec;
 use crate::strategy_utils::{buy_signal, hold_signal, meets_confidence_threshold, sell_signal};
 use std::collections::HashMap;
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::integer("fastPeriod", 5.0, 50.0, 1.0, 5.0),
+    ParameterSpec::integer("slowPeriod", 10.0, 100.0, 1.0, 27.0),
+    ParameterSpec::integer("signalPeriod", 3.0, 20.0, 1.0, 9.0),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.05, 0.8),
+];
+
 pub struct MACDStrategy {
     pub template_id: String,
     min_confidence: f64,
@@ -15,11 +23,11 @@ pub struct MACDStrategy {
 
 impl MACDStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
-        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.6);
-        let fast_period = get_param_usize(&parameters, "fastPeriod", 12);
-        let slow_period = get_param_usize(&parameters, "slowPeriod", 26);
+        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.8);
+        let fast_period = get_param_usize(&parameters, "fastPeriod", 5);
+        let slow_period = get_param_usize(&parameters, "slowPeriod", 27);
         let signal_period = get_param_usize(&parameters, "signalPeriod", 9);
-        let slow_for_min = get_param_f64(&parameters, "slowPeriod", 26.0);
+        let slow_for_min = get_param_f64(&parameters, "slowPeriod", 27.0);
         let signal_for_min = get_param_f64(&parameters, "signalPeriod", 9.0);
         let min_data_points = (slow_for_min.max(50.0) + signal_for_min) as usize;
         Self {
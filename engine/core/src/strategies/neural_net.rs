@@ -0,0 +1,240 @@
+use super::calibration::Calibration;
+use super::lightgbm::features::FeatureSet;
+use anyhow::{anyhow, Result};
+use dashmap::DashMap;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+/// Fully connected layer with `weights` stored row-major, one row of `inputs` values per output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DenseLayer {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub weights: Vec<f64>,
+    pub biases: Vec<f64>,
+}
+
+impl DenseLayer {
+    /// He-initialized layer, suited to the ReLU activations between hidden layers.
+    fn random<R: Rng>(inputs: usize, outputs: usize, rng: &mut R) -> Self {
+        let bound = (6.0 / inputs.max(1) as f64).sqrt();
+        let weights = (0..inputs * outputs)
+            .map(|_| rng.gen_range(-bound..bound))
+            .collect();
+        Self {
+            inputs,
+            outputs,
+            weights,
+            biases: vec![0.0; outputs],
+        }
+    }
+
+    pub fn forward(&self, input: &[f64], output: &mut Vec<f64>) {
+        output.clear();
+        for (row, bias) in self.weights.chunks_exact(self.inputs).zip(&self.biases) {
+            let sum: f64 = row.iter().zip(input).map(|(w, x)| w * x).sum();
+            output.push(sum + bias);
+        }
+    }
+}
+
+/// Multilayer perceptron scoring the LightGBM feature vector: features are standardized with
+/// the training means and scales, hidden layers use ReLU and the single output is a logit.
+/// `feature_names` lists the registry columns it was trained on (all of them when empty).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MlpModel {
+    #[serde(default)]
+    pub feature_names: Vec<String>,
+    pub feature_means: Vec<f64>,
+    pub feature_scales: Vec<f64>,
+    pub layers: Vec<DenseLayer>,
+    /// Mapping from the output probability to the validation hit rate, when one was fitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<Calibration>,
+    #[serde(skip)]
+    features: FeatureSet,
+}
+
+impl MlpModel {
+    pub fn random<R: Rng>(
+        features: FeatureSet,
+        feature_means: Vec<f64>,
+        feature_scales: Vec<f64>,
+        hidden_units: &[usize],
+        rng: &mut R,
+    ) -> Self {
+        let mut layers = Vec::with_capacity(hidden_units.len() + 1);
+        let mut inputs = feature_means.len();
+        for &units in hidden_units.iter().chain(std::iter::once(&1)) {
+            layers.push(DenseLayer::random(inputs, units, rng));
+            inputs = units;
+        }
+        Self {
+            feature_names: features
+                .names()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            feature_means,
+            feature_scales,
+            layers,
+            calibration: None,
+            features,
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        let mut model: Self = serde_json::from_str(text)
+            .map_err(|err| anyhow!("Failed to parse neural network weights: {err}"))?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.feature_means.len()
+    }
+
+    /// Registry columns this model reads, in input order.
+    pub fn features(&self) -> &FeatureSet {
+        &self.features
+    }
+
+    fn validate(&mut self) -> Result<()> {
+        self.features = FeatureSet::from_names(&self.feature_names)?;
+        self.features.check_column_count(self.feature_count())?;
+        if self.feature_scales.len() != self.feature_count() {
+            return Err(anyhow!(
+                "Neural network declares {} feature means but {} scales",
+                self.feature_count(),
+                self.feature_scales.len()
+            ));
+        }
+        let mut inputs = self.feature_count();
+        for (idx, layer) in self.layers.iter().enumerate() {
+            if layer.inputs != inputs
+                || layer.weights.len() != layer.inputs * layer.outputs
+                || layer.biases.len() != layer.outputs
+            {
+                return Err(anyhow!(
+                    "Neural network layer {} has inconsistent shape",
+                    idx
+                ));
+            }
+            inputs = layer.outputs;
+        }
+        if self.layers.is_empty() || inputs != 1 {
+            return Err(anyhow!("Neural network must end in a single output"));
+        }
+        if self
+            .calibration
+            .as_ref()
+            .is_some_and(|calibration| !calibration.is_valid())
+        {
+            return Err(anyhow!("Neural network calibration is malformed"));
+        }
+        Ok(())
+    }
+
+    /// Standardized copy of `features`; non-finite values map to the training mean.
+    pub fn standardize(&self, features: &[f64]) -> Vec<f64> {
+        features
+            .iter()
+            .zip(self.feature_means.iter().zip(&self.feature_scales))
+            .map(|(value, (mean, scale))| {
+                if value.is_finite() {
+                    (value - mean) / scale
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    /// Raw output logit for already standardized `input`.
+    pub fn logit(&self, input: &[f64]) -> f64 {
+        let mut current = input.to_vec();
+        let mut next = Vec::new();
+        let last = self.layers.len() - 1;
+        for (idx, layer) in self.layers.iter().enumerate() {
+            layer.forward(&current, &mut next);
+            if idx < last {
+                next.iter_mut().for_each(|value| *value = value.max(0.0));
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        current[0]
+    }
+
+    pub fn predict_probability(&self, features: &[f64]) -> Option<f64> {
+        if features.len() != self.feature_count() {
+            return None;
+        }
+        let logit = self.logit(&self.standardize(features));
+        logit.is_finite().then(|| 1.0 / (1.0 + (-logit).exp()))
+    }
+}
+
+static NEURAL_NET_MODELS: OnceLock<DashMap<String, Arc<MlpModel>>> = OnceLock::new();
+
+fn neural_net_models() -> &'static DashMap<String, Arc<MlpModel>> {
+    NEURAL_NET_MODELS.get_or_init(DashMap::new)
+}
+
+pub fn register_model(model_id: &str, mut model: MlpModel) -> Result<()> {
+    if model_id.trim().is_empty() {
+        return Err(anyhow!("Neural network model id cannot be empty"));
+    }
+    model.validate()?;
+    neural_net_models().insert(model_id.to_string(), Arc::new(model));
+    Ok(())
+}
+
+pub fn register_model_json(model_id: &str, text: &str) -> Result<()> {
+    register_model(model_id, MlpModel::from_json(text)?)
+}
+
+pub(crate) fn registered_model(model_id: &str) -> Option<Arc<MlpModel>> {
+    neural_net_models()
+        .get(model_id)
+        .map(|entry| entry.value().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MlpModel;
+    use crate::strategy::lightgbm::features::FeatureSet;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn weights_round_trip_through_json() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let features = FeatureSet::from_names(&["rsi", "cci", "williams_r"]).unwrap();
+        let model = MlpModel::random(
+            features,
+            vec![1.0, 2.0, 3.0],
+            vec![1.0, 0.5, 2.0],
+            &[4],
+            &mut rng,
+        );
+        let text = serde_json::to_string(&model).unwrap();
+        let restored = MlpModel::from_json(&text).unwrap();
+        let features = [0.5, f64::NAN, 4.0];
+        assert_eq!(
+            model.predict_probability(&features),
+            restored.predict_probability(&features)
+        );
+        assert!(model.predict_probability(&features[..2]).is_none());
+        assert_eq!(restored.features(), model.features());
+
+        let mut renamed = model.clone();
+        renamed.feature_names.pop();
+        assert!(MlpModel::from_json(&serde_json::to_string(&renamed).unwrap()).is_err());
+
+        let mut broken = model.clone();
+        broken.layers[0].biases.pop();
+        assert!(MlpModel::from_json(&serde_json::to_string(&broken).unwrap()).is_err());
+    }
+}
@@ -2,6 +2,13 @@ use std::collections::HashMap;
 
 use crate::models::{Candle, SignalAction, StrategySignal};
 use crate::param_utils::get_param_f64;
+use crate::strategy::ParameterSpec;
+
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::number("step", 0.01, 0.1, 0.005, 0.035),
+    ParameterSpec::number("max", 0.1, 0.5, 0.01, 0.2),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.1, 0.0),
+];
 
 pub struct PSARStrategy {
     template_id: String,
@@ -12,9 +19,9 @@ pub struct PSARStrategy {
 
 impl PSARStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
-        let step = get_param_f64(&parameters, "step", 0.02).max(0.0001);
+        let step = get_param_f64(&parameters, "step", 0.035).max(0.0001);
         let max_acceleration = get_param_f64(&parameters, "max", 0.2).max(step);
-        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.3);
+        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.0);
         Self {
             template_id: "psar".to_string(),
             step,
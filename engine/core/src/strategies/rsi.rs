@@ -1,9 +1,17 @@
 use crate::indicators;
 use crate::models::*;
 use crate::param_utils::{get_param_f64, get_param_usize};
+use crate::strategy::ParameterSpec;
 use crate::strategy_utils::{buy_signal, hold_signal, meets_confidence_threshold, sell_signal};
 use std::collections::HashMap;
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::integer("period", 5.0, 50.0, 1.0, 10.0),
+    ParameterSpec::number("oversoldLevel", 10.0, 40.0, 1.0, 36.0),
+    ParameterSpec::number("overboughtLevel", 60.0, 90.0, 1.0, 88.0),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.05, 0.45),
+];
+
 pub struct RSIStrategy {
     pub template_id: String,
     period: usize,
@@ -14,10 +22,10 @@ pub struct RSIStrategy {
 
 impl RSIStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
-        let period = get_param_usize(&parameters, "period", 14);
-        let oversold_level = get_param_f64(&parameters, "oversoldLevel", 30.0);
-        let overbought_level = get_param_f64(&parameters, "overboughtLevel", 70.0);
-        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.6);
+        let period = get_param_usize(&parameters, "period", 10);
+        let oversold_level = get_param_f64(&parameters, "oversoldLevel", 36.0);
+        let overbought_level = get_param_f64(&parameters, "overboughtLevel", 88.0);
+        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.45);
         Self {
             template_id: "rsi".to_string(),
             period,
@@ -1,7 +1,18 @@
 use crate::models::*;
 use crate::param_utils::{get_param_f64, get_param_usize_at_least};
+use crate::strategy::ParameterSpec;
 use std::collections::HashMap;
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::integer("rocPeriod1", 5.0, 50.0, 1.0, 21.0),
+    ParameterSpec::integer("smaPeriod1", 5.0, 50.0, 1.0, 21.0),
+    ParameterSpec::integer("rocPeriod2", 5.0, 50.0, 1.0, 20.0),
+    ParameterSpec::integer("smaPeriod2", 5.0, 50.0, 1.0, 25.0),
+    ParameterSpec::number("weight1", 0.1, 5.0, 0.1, 1.2),
+    ParameterSpec::number("weight2", 0.1, 5.0, 0.1, 1.4),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.05, 0.4),
+];
+
 pub struct WeightedMomentumStrategy {
     template_id: String,
     roc_period1: usize,
@@ -17,11 +28,11 @@ impl WeightedMomentumStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
         let roc_period1 = get_param_usize_at_least(&parameters, "rocPeriod1", 21, 1);
         let sma_period1 = get_param_usize_at_least(&parameters, "smaPeriod1", 21, 1);
-        let roc_period2 = get_param_usize_at_least(&parameters, "rocPeriod2", 21, 1);
-        let sma_period2 = get_param_usize_at_least(&parameters, "smaPeriod2", 21, 1);
-        let weight1 = get_param_f64(&parameters, "weight1", 1.0);
-        let weight2 = get_param_f64(&parameters, "weight2", 1.0);
-        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.5);
+        let roc_period2 = get_param_usize_at_least(&parameters, "rocPeriod2", 20, 1);
+        let sma_period2 = get_param_usize_at_least(&parameters, "smaPeriod2", 25, 1);
+        let weight1 = get_param_f64(&parameters, "weight1", 1.2);
+        let weight2 = get_param_f64(&parameters, "weight2", 1.4);
+        let min_confidence = get_param_f64(&parameters, "minConfidence", 0.4);
         Self {
             template_id: "weighted_momentum".to_string(),
             roc_period1,
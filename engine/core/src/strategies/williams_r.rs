@@ -1,7 +1,15 @@
 use crate::models::*;
 use crate::param_utils::{get_param_f64, get_param_usize_at_least};
+use crate::strategy::ParameterSpec;
 use std::collections::HashMap;
 
+pub const PARAMETERS: &[ParameterSpec] = &[
+    ParameterSpec::integer("period", 5.0, 50.0, 1.0, 21.0),
+    ParameterSpec::number("oversoldLevel", -99.0, -80.0, 1.0, -80.0),
+    ParameterSpec::number("overboughtLevel", -20.0, -1.0, 1.0, -3.0),
+    ParameterSpec::number("minConfidence", 0.0, 1.0, 0.05, 0.5),
+];
+
 pub struct WilliamsRStrategy {
     template_id: String,
     period: usize,
@@ -12,9 +20,9 @@ pub struct WilliamsRStrategy {
 
 impl WilliamsRStrategy {
     pub fn new(parameters: HashMap<String, f64>) -> Self {
-        let period = get_param_usize_at_least(&parameters, "period", 14, 1);
-        let oversold_level = get_param_f64(&parameters, "oversoldLevel", -90.0);
-        let overbought_level = get_param_f64(&parameters, "overboughtLevel", -10.0);
+        let period = get_param_usize_at_least(&parameters, "period", 21, 1);
+        let oversold_level = get_param_f64(&parameters, "oversoldLevel", -80.0);
+        let overbought_level = get_param_f64(&parameters, "overboughtLevel", -3.0);
         let min_confidence = get_param_f64(&parameters, "minConfidence", 0.5);
         Self {
             template_id: "williams_r".to_string(),
@@ -0,0 +1,431 @@
+use crate::models::*;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub trait Strategy {
+    fn get_template_id(&self) -> &str;
+    fn generate_signal(
+        &self,
+        ticker: &str,
+        candles: &[Candle],
+        candle_index: usize,
+    ) -> StrategySignal;
+    fn target_ticker(&self) -> Option<String> {
+        None
+    }
+    #[allow(dead_code)]
+    fn get_min_data_points(&self) -> usize;
+    fn snapshot_state(&self) -> Option<Value> {
+        None
+    }
+    fn restore_state(&self, _state: &Value) -> Result<()> {
+        Ok(())
+    }
+    /// Resolves the inputs shared by every signal of one run over `candles_by_ticker`, such as
+    /// the scoring model and the cross-sectional ranks. Called once per backtest or signal run.
+    fn prepare_context(
+        &self,
+        _candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+        _cross_sectional: &lightgbm::CrossSectionalCache,
+    ) -> StrategyContext {
+        StrategyContext::default()
+    }
+    /// `generate_signal` with the run's `prepare_context` result.
+    fn generate_signal_with_context(
+        &self,
+        ticker: &str,
+        candles: &[Candle],
+        candle_index: usize,
+        _context: &StrategyContext,
+    ) -> StrategySignal {
+        self.generate_signal(ticker, candles, candle_index)
+    }
+}
+
+/// Per-run strategy inputs. Each backtest or signal run owns its context, so concurrent runs
+/// over different models or ticker universes never observe each other's state.
+#[derive(Clone, Default)]
+pub struct StrategyContext {
+    pub model: Option<lightgbm::ScoringModel>,
+    pub cross_sectional: Option<Arc<lightgbm::CrossSectionalContext>>,
+    /// Volatility index closes read by market-wide model features.
+    pub volatility_index: Option<Arc<AuxiliarySeries>>,
+    /// Economic calendar, short interest and sentiment read by model features.
+    pub auxiliary: Option<Arc<AuxiliaryData>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    Integer,
+    Number,
+    /// String value encoded with `encode_string_parameter`; not range-checked.
+    Text,
+}
+
+/// A strategy parameter's name, type, bounds, optimizer step and default. Each strategy module
+/// lists its parameters in a `PARAMETERS` constant, kept identical to the server template JSON;
+/// `default` mirrors the fallback the strategy uses when the value is missing. Text parameters
+/// (such as `buy_and_hold`'s `ticker`) carry no bounds, step or default.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParameterSpec {
+    pub name: &'static str,
+    pub kind: ParameterKind,
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+    pub default: f64,
+}
+
+impl ParameterSpec {
+    pub const fn integer(name: &'static str, min: f64, max: f64, step: f64, default: f64) -> Self {
+        Self {
+            name,
+            kind: ParameterKind::Integer,
+            min,
+            max,
+            step,
+            default,
+        }
+    }
+
+    pub const fn number(name: &'static str, min: f64, max: f64, step: f64, default: f64) -> Self {
+        Self {
+            name,
+            kind: ParameterKind::Number,
+            min,
+            max,
+            step,
+            default,
+        }
+    }
+
+    pub const fn text(name: &'static str) -> Self {
+        Self {
+            name,
+            kind: ParameterKind::Text,
+            min: 0.0,
+            max: 0.0,
+            step: 0.0,
+            default: 0.0,
+        }
+    }
+
+    fn to_template_parameter(self) -> StrategyParameter {
+        let (r#type, bounds, default) = match self.kind {
+            ParameterKind::Text => ("string", None, None),
+            _ => (
+                "number",
+                Some((self.min, self.max, self.step)),
+                Some(Value::from(self.default)),
+            ),
+        };
+        StrategyParameter {
+            name: self.name.to_string(),
+            r#type: r#type.to_string(),
+            min: bounds.map(|(min, _, _)| min),
+            max: bounds.map(|(_, max, _)| max),
+            step: bounds.map(|(_, _, step)| step),
+            default,
+            description: None,
+        }
+    }
+
+    pub fn is_tunable(&self) -> bool {
+        self.kind != ParameterKind::Text && self.step > 0.0 && self.max > self.min
+    }
+
+    /// Rounds integers; non-finite values and values outside `min..=max` are rejected.
+    fn resolve(&self, template_id: &str, value: f64) -> Result<f64> {
+        if self.kind == ParameterKind::Text {
+            return Ok(value);
+        }
+        let value = match self.kind {
+            ParameterKind::Integer => value.round(),
+            _ => value,
+        };
+        if !value.is_finite() || !(self.min..=self.max).contains(&value) {
+            return Err(anyhow!(
+                "Parameter {} of strategy {} must be a number between {} and {} (value: {})",
+                self.name,
+                template_id,
+                self.min,
+                self.max,
+                value
+            ));
+        }
+        Ok(value)
+    }
+}
+
+/// Declared parameters of `template_id`, or `None` for unknown templates.
+pub fn parameter_specs(template_id: &str) -> Option<&'static [ParameterSpec]> {
+    if template_id.starts_with("lightgbm_") || template_id.starts_with("nn_") {
+        return Some(lightgbm::PARAMETERS);
+    }
+    match template_id {
+        "rsi" => Some(rsi::PARAMETERS),
+        "macd" => Some(macd::PARAMETERS),
+        "williams_r" => Some(williams_r::PARAMETERS),
+        "adx" => Some(adx::PARAMETERS),
+        "atr" => Some(atr::PARAMETERS),
+        "buy_and_hold" => Some(buy_and_hold::PARAMETERS),
+        "lightgbm" => Some(lightgbm::PARAMETERS),
+        "psar" => Some(psar::PARAMETERS),
+        "weighted_momentum" => Some(weighted_momentum::PARAMETERS),
+        _ => None,
+    }
+}
+
+/// Template holding only the declared parameters of `template_id`, for optimizing templates
+/// missing from the market data snapshot and database.
+pub fn registry_template(template_id: &str) -> Option<StrategyTemplate> {
+    let specs = parameter_specs(template_id)?;
+    Some(StrategyTemplate {
+        id: template_id.to_string(),
+        name: template_id.to_string(),
+        description: None,
+        category: None,
+        author: None,
+        version: None,
+        local_optimization_version: 0,
+        parameters: specs
+            .iter()
+            .map(|spec| spec.to_template_parameter())
+            .collect(),
+        example_usage: None,
+        created_at: Utc::now(),
+    })
+}
+
+/// Checks the declared parameters of `template_id` in `parameters`: integers are rounded,
+/// and non-finite or out-of-bounds values are an error naming the parameter, the value and
+/// its bounds. Missing parameters keep the strategy default; other keys (engine settings such
+/// as `stopLossRatio`) pass through unchanged.
+pub fn validate_parameters(
+    template_id: &str,
+    mut parameters: HashMap<String, f64>,
+) -> Result<HashMap<String, f64>> {
+    let specs = parameter_specs(template_id)
+        .ok_or_else(|| anyhow!("Unknown strategy template: {}", template_id))?;
+    for spec in specs {
+        if let Some(value) = parameters.get_mut(spec.name) {
+            *value = spec.resolve(template_id, *value)?;
+        }
+    }
+    Ok(parameters)
+}
+
+#[path = "strategies/rsi.rs"]
+pub mod rsi;
+
+pub use rsi::RSIStrategy;
+
+#[path = "strategies/macd.rs"]
+pub mod macd;
+
+pub use macd::MACDStrategy;
+
+#[path = "strategies/williams_r.rs"]
+pub mod williams_r;
+
+pub use williams_r::WilliamsRStrategy;
+
+#[path = "strategies/adx.rs"]
+pub mod adx;
+
+pub use adx::ADXStrategy;
+
+#[path = "strategies/atr.rs"]
+pub mod atr;
+
+pub use atr::ATRStrategy;
+
+#[path = "strategies/lightgbm.rs"]
+pub mod lightgbm;
+
+pub use lightgbm::LightGBMStrategy;
+
+#[path = "strategies/neural_net.rs"]
+pub mod neural_net;
+
+#[path = "strategies/calibration.rs"]
+pub mod calibration;
+
+#[path = "strategies/weighted_momentum.rs"]
+pub mod weighted_momentum;
+
+pub use weighted_momentum::WeightedMomentumStrategy;
+
+#[path = "strategies/psar.rs"]
+pub mod psar;
+
+pub use psar::PSARStrategy;
+
+#[path = "strategies/buy_and_hold.rs"]
+pub mod buy_and_hold;
+
+pub use buy_and_hold::BuyAndHoldStrategy;
+
+pub fn create_strategy(
+    template_id: &str,
+    parameters: HashMap<String, f64>,
+) -> Result<Box<dyn Strategy + Send + Sync>> {
+    let parameters = validate_parameters(template_id, parameters)?;
+    if template_id.starts_with("lightgbm_") || template_id.starts_with("nn_") {
+        return Ok(Box::new(LightGBMStrategy::new(
+            template_id.to_string(),
+            parameters,
+        )));
+    }
+
+    match template_id {
+        "rsi" => Ok(Box::new(RSIStrategy::new(parameters))),
+        "macd" => Ok(Box::new(MACDStrategy::new(parameters))),
+        "williams_r" => Ok(Box::new(WilliamsRStrategy::new(parameters))),
+        "adx" => Ok(Box::new(ADXStrategy::new(parameters))),
+        "atr" => Ok(Box::new(ATRStrategy::new(parameters))),
+        "buy_and_hold" => Ok(Box::new(BuyAndHoldStrategy::new(parameters))),
+        "lightgbm" => Ok(Box::new(LightGBMStrategy::new(
+            template_id.to_string(),
+            parameters,
+        ))),
+        "psar" => Ok(Box::new(PSARStrategy::new(parameters))),
+        "weighted_momentum" => Ok(Box::new(WeightedMomentumStrategy::new(parameters))),
+        _ => Err(anyhow::anyhow!(
+            "Unknown strategy template: {}",
+            template_id
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_defaults_lie_within_bounds() {
+        for template_id in [
+            "rsi",
+            "macd",
+            "williams_r",
+            "adx",
+            "atr",
+            "buy_and_hold",
+            "lightgbm",
+            "psar",
+            "weighted_momentum",
+        ] {
+            let specs = parameter_specs(template_id).unwrap();
+            for spec in specs.iter().filter(|spec| spec.is_tunable()) {
+                assert!(
+                    (spec.min..=spec.max).contains(&spec.default),
+                    "{} {}",
+                    template_id,
+                    spec.name
+                );
+            }
+            assert!(create_strategy(template_id, HashMap::new()).is_ok());
+        }
+        assert!(parameter_specs("unknown").is_none());
+    }
+
+    #[test]
+    fn declared_parameters_match_server_templates() {
+        let templates = [
+            (
+                "rsi",
+                include_str!("../../../src/server/strategies/rsi.json"),
+            ),
+            (
+                "macd",
+                include_str!("../../../src/server/strategies/macd.json"),
+            ),
+            (
+                "williams_r",
+                include_str!("../../../src/server/strategies/williams_r.json"),
+            ),
+            (
+                "adx",
+                include_str!("../../../src/server/strategies/adx.json"),
+            ),
+            (
+                "atr",
+                include_str!("../../../src/server/strategies/atr.json"),
+            ),
+            (
+                "buy_and_hold",
+                include_str!("../../../src/server/strategies/buy_and_hold.json"),
+            ),
+            (
+                "lightgbm",
+                include_str!("../../../src/server/strategies/lightgbm.json"),
+            ),
+            (
+                "psar",
+                include_str!("../../../src/server/strategies/psar.json"),
+            ),
+            (
+                "weighted_momentum",
+                include_str!("../../../src/server/strategies/weighted_momentum.json"),
+            ),
+        ];
+        for (template_id, json) in templates {
+            let template: Value = serde_json::from_str(json).unwrap();
+            let parameters = template["parameters"].as_array().unwrap();
+            for spec in parameter_specs(template_id).unwrap() {
+                let parameter = parameters
+                    .iter()
+                    .find(|parameter| parameter["name"] == spec.name)
+                    .unwrap_or_else(|| panic!("{} {} missing", template_id, spec.name));
+                let label = format!("{} {}", template_id, spec.name);
+                if spec.kind == ParameterKind::Text {
+                    assert_eq!(parameter["type"], "string", "{}", label);
+                    continue;
+                }
+                assert_eq!(parameter["min"].as_f64(), Some(spec.min), "{}", label);
+                assert_eq!(parameter["max"].as_f64(), Some(spec.max), "{}", label);
+                assert_eq!(parameter["step"].as_f64(), Some(spec.step), "{}", label);
+                assert_eq!(
+                    parameter["default"].as_f64(),
+                    Some(spec.default),
+                    "{}",
+                    label
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn validation_rounds_and_rejects_out_of_bounds_values() {
+        let parameters = HashMap::from([
+            ("period".to_string(), 7.6),
+            ("stopLossRatio".to_string(), 0.07),
+        ]);
+        let validated = validate_parameters("rsi", parameters).unwrap();
+        assert_eq!(validated["period"], 8.0);
+        assert_eq!(validated["stopLossRatio"], 0.07);
+        assert!(!validated.contains_key("minConfidence"));
+
+        let error = validate_parameters("rsi", HashMap::from([("oversoldLevel".to_string(), 5.0)]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("oversoldLevel"), "{}", error);
+        assert!(error.contains("between 10 and 40"), "{}", error);
+        assert!(error.contains("value: 5"), "{}", error);
+
+        let error = validate_parameters(
+            "rsi",
+            HashMap::from([("minConfidence".to_string(), f64::NAN)]),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("minConfidence"));
+
+        let template = registry_template("lightgbm_abc").unwrap();
+        assert_eq!(template.parameters.len(), lightgbm::PARAMETERS.len());
+        let template = registry_template("nn_abc").unwrap();
+        assert_eq!(template.parameters.len(), lightgbm::PARAMETERS.len());
+    }
+}
@@ -0,0 +1,976 @@
+use crate::indicators::calculate_atr_from_candles;
+use crate::models::Candle;
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use std::collections::HashMap;
+
+pub const PRICE_EPSILON: f64 = 1e-6;
+
+pub fn has_minimum_dollar_volume(
+    candles: &[&Candle],
+    end_index: usize,
+    lookback: usize,
+    minimum_dollar_volume: f64,
+) -> bool {
+    if minimum_dollar_volume <= 0.0 || lookback == 0 {
+        return true;
+    }
+    if candles.is_empty() || end_index >= candles.len() {
+        return false;
+    }
+    if end_index + 1 < lookback {
+        return false;
+    }
+    let start_index = end_index + 1 - lookback;
+    for candle in &candles[start_index..=end_index] {
+        let usd_volume = candle.high * candle.volume_shares as f64;
+        if usd_volume + PRICE_EPSILON < minimum_dollar_volume {
+            return false;
+        }
+    }
+    true
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionAllocation {
+    pub quantity: i32,
+    pub trade_value: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PositionSizingOutcome {
+    Sized(PositionAllocation),
+    TooSmall,
+    InsufficientCash { required: f64 },
+}
+
+pub struct PositionSizingParams {
+    pub price: f64,
+    pub available_cash: f64,
+    pub trade_size_ratio: f64,
+    pub minimum_trade_size: f64,
+    pub position_sizing_mode: i32,
+    pub confidence: f64,
+    pub vol_target_annual: f64,
+    pub realized_vol: Option<f64>,
+}
+
+pub fn determine_position_size(params: PositionSizingParams) -> PositionSizingOutcome {
+    let PositionSizingParams {
+        price,
+        available_cash,
+        trade_size_ratio,
+        minimum_trade_size,
+        position_sizing_mode,
+        confidence,
+        vol_target_annual,
+        realized_vol,
+    } = params;
+
+    if price <= 0.0 || !price.is_finite() || !available_cash.is_finite() {
+        return PositionSizingOutcome::TooSmall;
+    }
+
+    let mut sizing_multiplier = 1.0;
+    if position_sizing_mode == 1 || position_sizing_mode == 3 {
+        let conf = confidence.clamp(0.0, 1.0);
+        sizing_multiplier *= conf.max(0.3);
+    }
+
+    if (position_sizing_mode == 2 || position_sizing_mode == 3)
+        && vol_target_annual > 0.0
+        && vol_target_annual.is_finite()
+    {
+        if let Some(vol) = realized_vol {
+            if vol > 0.0 && vol.is_finite() {
+                let vol_scale = (vol_target_annual / vol).clamp(0.0, 1.0);
+                sizing_multiplier *= if vol_scale.is_finite() {
+                    vol_scale
+                } else {
+                    1.0
+                };
+            }
+        }
+    }
+
+    let trade_allocation = available_cash.max(0.0) * trade_size_ratio.max(0.0) * sizing_multiplier;
+    let desired_shares = if trade_allocation <= 0.0 {
+        0.0
+    } else {
+        trade_allocation / price
+    };
+    let mut quantity = desired_shares.floor().max(0.0) as i32;
+
+    let mut trade_value = quantity as f64 * price;
+
+    if quantity > 0 && trade_value < minimum_trade_size {
+        quantity = (minimum_trade_size / price).ceil() as i32;
+        trade_value = quantity as f64 * price;
+    }
+
+    if quantity <= 0 {
+        if available_cash + PRICE_EPSILON < price {
+            return PositionSizingOutcome::InsufficientCash { required: price };
+        }
+        return PositionSizingOutcome::TooSmall;
+    }
+
+    if trade_value > available_cash + PRICE_EPSILON {
+        return PositionSizingOutcome::InsufficientCash {
+            required: trade_value,
+        };
+    }
+
+    PositionSizingOutcome::Sized(PositionAllocation {
+        quantity,
+        trade_value,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initial_stop_loss(
+    stop_loss_mode: i32,
+    atr_multiplier: f64,
+    atr_period: usize,
+    stop_loss_ratio: f64,
+    price: f64,
+    ticker_candles: &Vec<&Candle>,
+    index: usize,
+    is_short: bool,
+) -> Option<f64> {
+    if (stop_loss_mode == 1 || stop_loss_mode == 2) && atr_multiplier > 0.0 {
+        return calculate_atr_from_candles(ticker_candles, index, atr_period).and_then(|atr| {
+            if atr > 0.0 && atr.is_finite() {
+                if is_short {
+                    Some(price + atr_multiplier * atr)
+                } else {
+                    Some(price - atr_multiplier * atr)
+                }
+            } else {
+                None
+            }
+        });
+    }
+
+    if stop_loss_ratio.is_finite() && stop_loss_ratio > 0.0 && stop_loss_ratio < 1.0 {
+        return if is_short {
+            Some(price * (1.0 + stop_loss_ratio))
+        } else {
+            Some(price * (1.0 - stop_loss_ratio))
+        };
+    }
+
+    None
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TrailingStopUpdate {
+    Atr(f64),
+    Chandelier(f64),
+    PercentOfPeak(f64),
+    Breakeven(f64),
+}
+
+impl TrailingStopUpdate {
+    pub fn value(&self) -> f64 {
+        match self {
+            TrailingStopUpdate::Atr(value)
+            | TrailingStopUpdate::Chandelier(value)
+            | TrailingStopUpdate::PercentOfPeak(value)
+            | TrailingStopUpdate::Breakeven(value) => *value,
+        }
+    }
+
+    pub fn reason(&self) -> &'static str {
+        match self {
+            TrailingStopUpdate::Atr(_) => "atr_trailing",
+            TrailingStopUpdate::Chandelier(_) => "chandelier_trailing",
+            TrailingStopUpdate::PercentOfPeak(_) => "percent_trailing",
+            TrailingStopUpdate::Breakeven(_) => "breakeven",
+        }
+    }
+}
+
+pub struct TrailingStopParams<'a> {
+    /// 0=fixed percent, 1=ATR trail, 2=chandelier, 3=percent-of-peak trail,
+    /// 4=breakeven after `breakeven_r_multiple`.
+    pub stop_loss_mode: i32,
+    pub stop_loss_ratio: f64,
+    pub atr_multiplier: f64,
+    pub atr_period: usize,
+    pub breakeven_r_multiple: f64,
+    pub ticker_candles: &'a [&'a Candle],
+    pub candle_index: usize,
+    pub current_candle: &'a Candle,
+    pub entry_price: f64,
+    pub current_stop: f64,
+    pub is_short: bool,
+    pub planning_close: Option<f64>,
+}
+
+pub fn compute_trailing_stop(params: TrailingStopParams) -> Option<TrailingStopUpdate> {
+    let TrailingStopParams {
+        stop_loss_mode,
+        stop_loss_ratio,
+        atr_multiplier,
+        atr_period,
+        breakeven_r_multiple,
+        ticker_candles,
+        candle_index,
+        current_candle,
+        entry_price,
+        current_stop,
+        is_short,
+        planning_close,
+    } = params;
+
+    let reference_close = planning_close.unwrap_or(current_candle.close);
+    let tightens = |potential: f64| {
+        (!is_short && potential > current_stop) || (is_short && potential < current_stop)
+    };
+    let atr = || {
+        calculate_atr_from_candles(ticker_candles, candle_index, atr_period)
+            .filter(|atr| *atr > 0.0 && atr.is_finite())
+    };
+
+    let potential = match stop_loss_mode {
+        1 if atr_multiplier > 0.0 => atr().map(|atr| {
+            let update = if is_short {
+                reference_close + atr_multiplier * atr
+            } else {
+                reference_close - atr_multiplier * atr
+            };
+            TrailingStopUpdate::Atr(update)
+        }),
+        2 if atr_multiplier > 0.0 => atr().and_then(|atr| {
+            let start = (candle_index + 1).saturating_sub(atr_period);
+            let window = ticker_candles.get(start..=candle_index)?;
+            let update = if is_short {
+                let lowest = window
+                    .iter()
+                    .map(|candle| candle.low)
+                    .fold(f64::MAX, f64::min);
+                lowest.min(reference_close) + atr_multiplier * atr
+            } else {
+                let highest = window
+                    .iter()
+                    .map(|candle| candle.high)
+                    .fold(f64::MIN, f64::max);
+                highest.max(reference_close) - atr_multiplier * atr
+            };
+            Some(TrailingStopUpdate::Chandelier(update))
+        }),
+        // Ratcheting off each close keeps the stop at the best close since entry.
+        3 if stop_loss_ratio.is_finite() && stop_loss_ratio > 0.0 && stop_loss_ratio < 1.0 => {
+            Some(TrailingStopUpdate::PercentOfPeak(if is_short {
+                reference_close * (1.0 + stop_loss_ratio)
+            } else {
+                reference_close * (1.0 - stop_loss_ratio)
+            }))
+        }
+        // The stop moves once; until then the distance to entry is the initial risk.
+        4 if breakeven_r_multiple > 0.0 && entry_price > 0.0 => {
+            let risk = if is_short {
+                current_stop - entry_price
+            } else {
+                entry_price - current_stop
+            };
+            let target = if is_short {
+                entry_price - breakeven_r_multiple * risk
+            } else {
+                entry_price + breakeven_r_multiple * risk
+            };
+            let reached = if is_short {
+                reference_close <= target
+            } else {
+                reference_close >= target
+            };
+            (risk > 0.0 && reached).then_some(TrailingStopUpdate::Breakeven(entry_price))
+        }
+        _ => None,
+    }?;
+
+    tightens(potential.value()).then_some(potential)
+}
+
+/// Price a stop touched on `current_candle` fills at before slippage: the open when the
+/// candle gaps through the stop, unless `fill_at_stop_price` keeps the optimistic stop price.
+pub fn stop_loss_exit_price(
+    current_candle: &Candle,
+    stop_loss: f64,
+    is_short: bool,
+    fill_at_stop_price: bool,
+) -> Option<f64> {
+    if !is_short {
+        if current_candle.low <= stop_loss {
+            if current_candle.open <= stop_loss && !fill_at_stop_price {
+                Some(current_candle.open)
+            } else {
+                Some(stop_loss)
+            }
+        } else {
+            None
+        }
+    } else if current_candle.high >= stop_loss {
+        if current_candle.open >= stop_loss && !fill_at_stop_price {
+            Some(current_candle.open)
+        } else {
+            Some(stop_loss)
+        }
+    } else {
+        None
+    }
+}
+
+/// Average `close * volume` over the last `lookback` candles.
+pub fn average_dollar_volume(candles: &[&Candle], lookback: usize) -> Option<f64> {
+    if lookback == 0 || candles.is_empty() {
+        return None;
+    }
+    let window = &candles[candles.len().saturating_sub(lookback)..];
+    let total: f64 = window
+        .iter()
+        .map(|candle| candle.close * candle.volume_shares as f64)
+        .sum();
+    let average = total / window.len() as f64;
+    (average.is_finite() && average > 0.0).then_some(average)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CapacityOrder {
+    pub quantity: i32,
+    pub price: f64,
+    pub confidence: f64,
+}
+
+/// Fits same-ticker buy orders into `capacity` dollars of notional and returns the new
+/// quantity of each order (0 means deferred).
+///
+/// The lowest-confidence orders are deferred first while more than one order remains; any
+/// remaining excess is removed by scaling the surviving orders down proportionally.
+pub fn throttle_orders_to_capacity(orders: &[CapacityOrder], capacity: f64) -> Vec<i32> {
+    let mut quantities: Vec<i32> = orders.iter().map(|order| order.quantity).collect();
+    let notional = |quantities: &[i32]| -> f64 {
+        orders
+            .iter()
+            .zip(quantities)
+            .map(|(order, quantity)| *quantity as f64 * order.price)
+            .sum()
+    };
+    let mut total = notional(&quantities);
+    if total <= capacity + PRICE_EPSILON {
+        return quantities;
+    }
+
+    let mut by_confidence: Vec<usize> = (0..orders.len()).collect();
+    by_confidence.sort_by(|a, b| orders[*a].confidence.total_cmp(&orders[*b].confidence));
+    let mut remaining = orders.len();
+    for index in by_confidence {
+        if total <= capacity + PRICE_EPSILON || remaining <= 1 {
+            break;
+        }
+        total -= quantities[index] as f64 * orders[index].price;
+        quantities[index] = 0;
+        remaining -= 1;
+    }
+
+    if total > capacity + PRICE_EPSILON {
+        let scale = (capacity / total).max(0.0);
+        for quantity in quantities.iter_mut() {
+            *quantity = (*quantity as f64 * scale).floor() as i32;
+        }
+    }
+    quantities
+}
+
+/// Whether buys queued for a weekly entry tick execute at the open of `date`: the first
+/// trading day of its ISO week that falls on or after `weekday`.
+pub fn is_entry_execution_tick(
+    previous_trading_day: Option<NaiveDate>,
+    date: NaiveDate,
+    weekday: Weekday,
+) -> bool {
+    let target = weekday.num_days_from_monday();
+    if date.weekday().num_days_from_monday() < target {
+        return false;
+    }
+    match previous_trading_day {
+        Some(previous) => {
+            previous.iso_week() != date.iso_week()
+                || previous.weekday().num_days_from_monday() < target
+        }
+        None => true,
+    }
+}
+
+/// Buy signals waiting for the next weekly entry tick, one per ticker at its highest
+/// confidence. A sell signal drops the ticker's queued buy.
+#[derive(Debug, Default, Clone)]
+pub struct QueuedEntries {
+    confidences: HashMap<String, f64>,
+}
+
+impl QueuedEntries {
+    pub fn queue(&mut self, ticker: &str, confidence: f64) {
+        self.confidences
+            .entry(ticker.to_string())
+            .and_modify(|existing| *existing = existing.max(confidence))
+            .or_insert(confidence);
+    }
+
+    pub fn cancel(&mut self, ticker: &str) {
+        self.confidences.remove(ticker);
+    }
+
+    /// Empties the queue, returning entries sorted by ticker.
+    pub fn drain(&mut self) -> Vec<(String, f64)> {
+        let mut entries: Vec<(String, f64)> = self.confidences.drain().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+}
+
+/// Shares of a `quantity`-share order a candle trading `volume_shares` fills when one order
+/// may take at most `max_volume_share` of the volume (0 = no cap).
+pub fn volume_capped_quantity(quantity: i32, volume_shares: i64, max_volume_share: f64) -> i32 {
+    if max_volume_share <= 0.0 {
+        return quantity;
+    }
+    let cap = (volume_shares.max(0) as f64 * max_volume_share).floor();
+    if cap >= f64::from(quantity) {
+        quantity
+    } else {
+        cap as i32
+    }
+}
+
+/// Unfilled shares of a volume-capped backtest entry, worked at the open of later candles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarriedEntry {
+    pub ticker: String,
+    /// Shares still to fill, always positive.
+    pub quantity: i32,
+    pub is_short: bool,
+    /// Candle of the last fill; the remainder works from the next candle on.
+    pub last_fill_date: DateTime<Utc>,
+}
+
+/// Carried entry remainders, one per ticker and side. A signal on the other side cancels them.
+#[derive(Debug, Default, Clone)]
+pub struct CarriedEntries {
+    entries: Vec<CarriedEntry>,
+}
+
+impl CarriedEntries {
+    pub fn carry(&mut self, entry: CarriedEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.ticker == entry.ticker && existing.is_short == entry.is_short)
+        {
+            Some(existing) => {
+                existing.quantity += entry.quantity;
+                existing.last_fill_date = existing.last_fill_date.max(entry.last_fill_date);
+            }
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Drops the remainders of `ticker` on the side a new `is_short` signal closes.
+    pub fn cancel_opposite(&mut self, ticker: &str, is_short: bool) {
+        self.entries
+            .retain(|entry| entry.ticker != ticker || entry.is_short == is_short);
+    }
+
+    pub fn take(&mut self) -> Vec<CarriedEntry> {
+        std::mem::take(&mut self.entries)
+    }
+}
+
+/// Parameters of the expected cost of a live order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransactionCostModel {
+    /// Share of notional lost to the bid/ask spread and slippage.
+    pub spread_rate: f64,
+    /// Square-root impact coefficient: impact = coefficient * sqrt(notional / ADV) * notional.
+    pub impact_coefficient: f64,
+    /// Broker fee rate, charged on closing orders only as in backtests.
+    pub close_fee_rate: f64,
+}
+
+/// Expected dollar cost (spread + market impact + fees) of trading `notional`.
+pub fn estimate_transaction_cost(
+    model: &TransactionCostModel,
+    notional: f64,
+    average_dollar_volume: Option<f64>,
+    is_close: bool,
+) -> f64 {
+    if !notional.is_finite() || notional <= 0.0 {
+        return 0.0;
+    }
+    let spread = notional * model.spread_rate.max(0.0);
+    let impact = match average_dollar_volume {
+        Some(volume) if volume > 0.0 && model.impact_coefficient > 0.0 => {
+            model.impact_coefficient * (notional / volume).sqrt() * notional
+        }
+        _ => 0.0,
+    };
+    let fees = if is_close {
+        notional * model.close_fee_rate.max(0.0)
+    } else {
+        0.0
+    };
+    spread + impact + fees
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetedOrder {
+    pub confidence: f64,
+    pub cost: f64,
+}
+
+/// Spends `remaining_budget` on orders from the highest confidence down and returns which
+/// orders must be deferred: the first order that no longer fits and every lower-confidence
+/// order after it.
+pub fn defer_orders_over_budget(orders: &[BudgetedOrder], remaining_budget: f64) -> Vec<bool> {
+    let mut by_confidence: Vec<usize> = (0..orders.len()).collect();
+    by_confidence.sort_by(|a, b| orders[*b].confidence.total_cmp(&orders[*a].confidence));
+    let mut deferred = vec![false; orders.len()];
+    let mut remaining = remaining_budget;
+    let mut exhausted = false;
+    for index in by_confidence {
+        if !exhausted && orders[index].cost <= remaining + PRICE_EPSILON {
+            remaining -= orders[index].cost;
+        } else {
+            exhausted = true;
+            deferred[index] = true;
+        }
+    }
+    deferred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use chrono::Utc;
+
+    const TEST_MIN_DOLLAR_VOLUME_FOR_ENTRY: f64 = 150_000.0;
+    const TEST_MIN_DOLLAR_VOLUME_LOOKBACK: usize = 5;
+
+    fn candle(date_offset: i64, open: f64, high: f64, low: f64, close: f64, volume: i64) -> Candle {
+        Candle {
+            ticker: "T".to_string(),
+            date: Utc::now() + Duration::days(date_offset),
+            open,
+            high,
+            low,
+            close,
+            unadjusted_close: Some(close),
+            volume_shares: volume,
+        }
+    }
+
+    #[test]
+    fn test_minimum_dollar_volume_check() {
+        let liquid: Vec<Candle> = (0..TEST_MIN_DOLLAR_VOLUME_LOOKBACK as i64)
+            .map(|offset| candle(offset, 10.0, 10.0, 10.0, 10.0, 20_000))
+            .collect();
+        let liquid_refs: Vec<&Candle> = liquid.iter().collect();
+        assert!(has_minimum_dollar_volume(
+            &liquid_refs,
+            liquid_refs.len() - 1,
+            TEST_MIN_DOLLAR_VOLUME_LOOKBACK,
+            TEST_MIN_DOLLAR_VOLUME_FOR_ENTRY
+        ));
+
+        let mut weak: Vec<Candle> = (0..TEST_MIN_DOLLAR_VOLUME_LOOKBACK as i64)
+            .map(|offset| candle(offset, 10.0, 10.0, 10.0, 10.0, 20_000))
+            .collect();
+        weak[2].volume_shares = 5_000; // Drops below $150k
+        let weak_refs: Vec<&Candle> = weak.iter().collect();
+        assert!(!has_minimum_dollar_volume(
+            &weak_refs,
+            weak_refs.len() - 1,
+            TEST_MIN_DOLLAR_VOLUME_LOOKBACK,
+            TEST_MIN_DOLLAR_VOLUME_FOR_ENTRY
+        ));
+
+        let short_history: Vec<Candle> = (0..4)
+            .map(|offset| candle(offset, 10.0, 10.0, 10.0, 10.0, 20_000))
+            .collect();
+        let short_refs: Vec<&Candle> = short_history.iter().collect();
+        assert!(!has_minimum_dollar_volume(
+            &short_refs,
+            short_refs.len() - 1,
+            TEST_MIN_DOLLAR_VOLUME_LOOKBACK,
+            TEST_MIN_DOLLAR_VOLUME_FOR_ENTRY
+        ));
+    }
+
+    #[test]
+    fn test_position_size_detects_cash_and_size() {
+        let outcome = determine_position_size(PositionSizingParams {
+            price: 10.0,
+            available_cash: 1000.0,
+            trade_size_ratio: 0.5,
+            minimum_trade_size: 100.0,
+            position_sizing_mode: 0,
+            confidence: 1.0,
+            vol_target_annual: 0.0,
+            realized_vol: None,
+        });
+        match outcome {
+            PositionSizingOutcome::Sized(allocation) => {
+                assert_eq!(allocation.quantity, 50);
+                assert!((allocation.trade_value - 500.0).abs() < 1e-9);
+            }
+            _ => panic!("unexpected outcome"),
+        }
+
+        let too_small = determine_position_size(PositionSizingParams {
+            price: 100.0,
+            available_cash: 50.0,
+            trade_size_ratio: 0.1,
+            minimum_trade_size: 100.0,
+            position_sizing_mode: 0,
+            confidence: 1.0,
+            vol_target_annual: 0.0,
+            realized_vol: None,
+        });
+        assert_eq!(
+            too_small,
+            PositionSizingOutcome::InsufficientCash { required: 100.0 }
+        );
+
+        let insufficient = determine_position_size(PositionSizingParams {
+            price: 100.0,
+            available_cash: 100.0,
+            trade_size_ratio: 1.0,
+            minimum_trade_size: 1000.0,
+            position_sizing_mode: 0,
+            confidence: 1.0,
+            vol_target_annual: 0.0,
+            realized_vol: None,
+        });
+        assert!(matches!(
+            insufficient,
+            PositionSizingOutcome::InsufficientCash { .. }
+        ));
+    }
+
+    #[test]
+    fn test_position_size_too_small_when_allocation_below_one_share() {
+        let too_small = determine_position_size(PositionSizingParams {
+            price: 100.0,
+            available_cash: 1000.0,
+            trade_size_ratio: 0.001,
+            minimum_trade_size: 10.0,
+            position_sizing_mode: 0,
+            confidence: 1.0,
+            vol_target_annual: 0.0,
+            realized_vol: None,
+        });
+        assert_eq!(too_small, PositionSizingOutcome::TooSmall);
+    }
+
+    #[test]
+    fn test_position_size_insufficient_cash_for_allocation() {
+        let sized = determine_position_size(PositionSizingParams {
+            price: 105.0,
+            available_cash: 1000.0,
+            trade_size_ratio: 2.0,
+            minimum_trade_size: 100.0,
+            position_sizing_mode: 0,
+            confidence: 1.0,
+            vol_target_annual: 0.0,
+            realized_vol: None,
+        });
+
+        assert_eq!(
+            sized,
+            PositionSizingOutcome::InsufficientCash {
+                required: 19.0 * 105.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_position_size_applies_minimum_trade_size() {
+        let sized = determine_position_size(PositionSizingParams {
+            price: 20.0,
+            available_cash: 1000.0,
+            trade_size_ratio: 0.05,
+            minimum_trade_size: 100.0,
+            position_sizing_mode: 0,
+            confidence: 1.0,
+            vol_target_annual: 0.0,
+            realized_vol: None,
+        });
+
+        match sized {
+            PositionSizingOutcome::Sized(allocation) => {
+                assert_eq!(allocation.quantity, 5);
+                assert!((allocation.trade_value - 100.0).abs() < 1e-9);
+            }
+            _ => panic!("expected sized allocation"),
+        }
+    }
+
+    #[test]
+    fn test_initial_stop_loss_percent() {
+        let candles = [candle(0, 10.0, 12.0, 8.0, 11.0, 1000)];
+        assert_eq!(
+            initial_stop_loss(0, 0.0, 14, 0.1, 10.0, &vec![&candles[0]], 0, false).unwrap(),
+            9.0
+        );
+        assert_eq!(
+            initial_stop_loss(0, 0.0, 14, 0.1, 10.0, &vec![&candles[0]], 0, true).unwrap(),
+            11.0
+        );
+    }
+
+    #[test]
+    fn test_trailing_stop_atr() {
+        let candles = [
+            candle(0, 10.0, 12.0, 9.0, 11.0, 1000),
+            candle(1, 12.0, 16.0, 11.0, 15.0, 1000),
+        ];
+        let candle_refs = vec![&candles[0], &candles[1]];
+        let update = compute_trailing_stop(TrailingStopParams {
+            stop_loss_mode: 1,
+            stop_loss_ratio: 0.05,
+            atr_multiplier: 1.0,
+            atr_period: 2,
+            breakeven_r_multiple: 1.0,
+            ticker_candles: &candle_refs,
+            candle_index: 1,
+            current_candle: &candles[1],
+            entry_price: 10.0,
+            current_stop: 10.0,
+            is_short: false,
+            planning_close: None,
+        })
+        .unwrap();
+        assert!(matches!(update, TrailingStopUpdate::Atr(_)));
+        assert!(update.value() > 10.0);
+
+        let short_update = compute_trailing_stop(TrailingStopParams {
+            stop_loss_mode: 1,
+            stop_loss_ratio: 0.05,
+            atr_multiplier: 1.0,
+            atr_period: 2,
+            breakeven_r_multiple: 1.0,
+            ticker_candles: &candle_refs,
+            candle_index: 1,
+            current_candle: candle_refs[1],
+            entry_price: 15.0,
+            current_stop: 20.0,
+            is_short: true,
+            planning_close: None,
+        })
+        .unwrap();
+        assert!(short_update.value() < 20.0);
+    }
+
+    #[test]
+    fn test_trailing_stop_chandelier_peak_and_breakeven_modes() {
+        let candles = [
+            candle(0, 10.0, 12.0, 9.0, 11.0, 1000),
+            candle(1, 11.0, 16.0, 11.0, 13.0, 1000),
+        ];
+        let candle_refs = vec![&candles[0], &candles[1]];
+        let params = |mode: i32, current_stop: f64, planning_close: Option<f64>| {
+            compute_trailing_stop(TrailingStopParams {
+                stop_loss_mode: mode,
+                stop_loss_ratio: 0.1,
+                atr_multiplier: 1.0,
+                atr_period: 2,
+                breakeven_r_multiple: 2.0,
+                ticker_candles: &candle_refs,
+                candle_index: 1,
+                current_candle: &candles[1],
+                entry_price: 12.0,
+                current_stop,
+                is_short: false,
+                planning_close,
+            })
+        };
+
+        // ATR(2) averages true ranges 3 and 5; the chandelier hangs off the 16 high, not the close.
+        let chandelier = params(2, 10.0, None).unwrap();
+        assert_eq!(chandelier, TrailingStopUpdate::Chandelier(12.0));
+        assert_eq!(chandelier.reason(), "chandelier_trailing");
+        assert_eq!(params(0, 10.0, None), None);
+
+        let peak = params(3, 10.0, None).unwrap();
+        assert!((peak.value() - 11.7).abs() < 1e-9);
+        assert!(params(3, 12.0, None).is_none());
+
+        // Risk is 12 - 11 = 1, so breakeven needs a close of 14.
+        assert!(params(4, 11.0, None).is_none());
+        assert_eq!(
+            params(4, 11.0, Some(14.0)),
+            Some(TrailingStopUpdate::Breakeven(12.0))
+        );
+        assert!(params(4, 12.0, Some(20.0)).is_none());
+    }
+
+    #[test]
+    fn test_stop_loss_exit_price_prefers_open_gap() {
+        let mut base = candle(0, 10.0, 12.0, 8.0, 11.0, 1000);
+        base.open = 9.0;
+        base.close = 10.0;
+        base.low = 8.5;
+        base.high = 12.0;
+        let candle = base;
+        assert_eq!(stop_loss_exit_price(&candle, 9.5, false, false), Some(9.0));
+        assert!(stop_loss_exit_price(&candle, 8.0, false, false).is_none());
+        assert_eq!(stop_loss_exit_price(&candle, 9.5, false, true), Some(9.5));
+
+        let mut short_candle = candle;
+        short_candle.open = 12.5;
+        short_candle.high = 12.5;
+        short_candle.low = 9.0;
+        assert_eq!(
+            stop_loss_exit_price(&short_candle, 12.0, true, false),
+            Some(12.5)
+        );
+        short_candle.open = 11.5;
+        assert_eq!(
+            stop_loss_exit_price(&short_candle, 12.0, true, false),
+            Some(12.0)
+        );
+        assert!(stop_loss_exit_price(&short_candle, 13.0, true, false).is_none());
+        short_candle.open = 12.5;
+        assert_eq!(
+            stop_loss_exit_price(&short_candle, 12.0, true, true),
+            Some(12.0)
+        );
+    }
+
+    #[test]
+    fn capacity_throttle_defers_low_confidence_then_scales() {
+        let order = |quantity, confidence| CapacityOrder {
+            quantity,
+            price: 10.0,
+            confidence,
+        };
+        let orders = [order(100, 0.9), order(50, 0.2), order(80, 0.5)];
+
+        assert_eq!(
+            throttle_orders_to_capacity(&orders, 5_000.0),
+            vec![100, 50, 80]
+        );
+        assert_eq!(
+            throttle_orders_to_capacity(&orders, 1_800.0),
+            vec![100, 0, 80]
+        );
+        assert_eq!(throttle_orders_to_capacity(&orders, 500.0), vec![50, 0, 0]);
+    }
+
+    #[test]
+    fn transaction_cost_adds_spread_impact_and_close_fees() {
+        let model = TransactionCostModel {
+            spread_rate: 0.001,
+            impact_coefficient: 0.1,
+            close_fee_rate: 0.0005,
+        };
+        let open = estimate_transaction_cost(&model, 10_000.0, Some(1_000_000.0), false);
+        assert!((open - (10.0 + 100.0)).abs() < 1e-9);
+        let close = estimate_transaction_cost(&model, 10_000.0, None, true);
+        assert!((close - (10.0 + 5.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn budget_gate_defers_from_first_order_that_does_not_fit() {
+        let order = |confidence, cost| BudgetedOrder { confidence, cost };
+        let orders = [order(0.4, 5.0), order(0.9, 30.0), order(0.6, 40.0)];
+
+        assert_eq!(
+            defer_orders_over_budget(&orders, 100.0),
+            vec![false, false, false]
+        );
+        assert_eq!(
+            defer_orders_over_budget(&orders, 50.0),
+            vec![true, false, true]
+        );
+        assert_eq!(
+            defer_orders_over_budget(&orders, 0.0),
+            vec![true, true, true]
+        );
+    }
+
+    #[test]
+    fn entry_tick_fires_on_first_trading_day_at_or_after_weekday() {
+        let date = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        // 2024-01-01 is a Monday.
+        assert!(is_entry_execution_tick(
+            Some(date(5)),
+            date(8),
+            Weekday::Mon
+        ));
+        assert!(!is_entry_execution_tick(
+            Some(date(8)),
+            date(9),
+            Weekday::Mon
+        ));
+        assert!(is_entry_execution_tick(
+            Some(date(2)),
+            date(4),
+            Weekday::Wed
+        ));
+        assert!(!is_entry_execution_tick(
+            Some(date(3)),
+            date(4),
+            Weekday::Wed
+        ));
+        assert!(!is_entry_execution_tick(None, date(2), Weekday::Wed));
+        assert!(is_entry_execution_tick(None, date(3), Weekday::Wed));
+    }
+
+    #[test]
+    fn queued_entries_keep_strongest_signal_until_cancelled() {
+        let mut queue = QueuedEntries::default();
+        queue.queue("BBB", 0.4);
+        queue.queue("AAA", 0.7);
+        queue.queue("BBB", 0.9);
+        queue.queue("CCC", 0.5);
+        queue.queue("BBB", 0.2);
+        queue.cancel("CCC");
+
+        assert_eq!(
+            queue.drain(),
+            vec![("AAA".to_string(), 0.7), ("BBB".to_string(), 0.9)]
+        );
+        assert!(queue.drain().is_empty());
+    }
+
+    #[test]
+    fn volume_cap_limits_fills_and_carried_entries_merge_per_side() {
+        assert_eq!(volume_capped_quantity(500, 10_000, 0.0), 500);
+        assert_eq!(volume_capped_quantity(500, 10_000, 0.1), 500);
+        assert_eq!(volume_capped_quantity(500, 4_999, 0.1), 499);
+        assert_eq!(volume_capped_quantity(500, 5, 0.1), 0);
+
+        let day = |offset| Utc::now() + Duration::days(offset);
+        let entry = |ticker: &str, quantity, is_short, offset| CarriedEntry {
+            ticker: ticker.to_string(),
+            quantity,
+            is_short,
+            last_fill_date: day(offset),
+        };
+        let mut carried = CarriedEntries::default();
+        carried.carry(entry("AAA", 100, false, 0));
+        carried.carry(entry("AAA", 50, false, 1));
+        carried.carry(entry("AAA", 70, true, 1));
+        carried.carry(entry("BBB", 30, false, 0));
+        carried.cancel_opposite("AAA", true);
+
+        let remaining = carried.take();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].quantity, 70);
+        assert!(remaining[0].is_short);
+        assert_eq!(remaining[1].ticker, "BBB");
+        assert!(carried.take().is_empty());
+    }
+}
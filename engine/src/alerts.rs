@@ -0,0 +1,135 @@
+use crate::config::SkipAlertRule;
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One recorded planner outcome: a signal skip reason or a planned operation reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkipOccurrence {
+    pub account_id: String,
+    pub ticker: String,
+    pub reason: String,
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkipAlert {
+    pub account_id: String,
+    pub ticker: String,
+    pub reason: String,
+    pub days: usize,
+    pub min_days: usize,
+    pub window_days: i64,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+}
+
+/// Earliest date any rule looks at when evaluating on `today`.
+pub fn skip_alert_lookback_start(rules: &[SkipAlertRule], today: NaiveDate) -> NaiveDate {
+    let window = rules.iter().map(|rule| rule.window_days).max().unwrap_or(1);
+    today - Duration::days(window - 1)
+}
+
+/// Returns an alert for every account/ticker whose occurrences of a rule's reason fall on at
+/// least `min_days` distinct dates within the rule window ending on `today`.
+pub fn evaluate_skip_alerts(
+    rules: &[SkipAlertRule],
+    occurrences: &[SkipOccurrence],
+    today: NaiveDate,
+) -> Vec<SkipAlert> {
+    let mut alerts = Vec::new();
+    for rule in rules {
+        let window_start = today - Duration::days(rule.window_days - 1);
+        let mut days_by_key: BTreeMap<(&str, &str), BTreeSet<NaiveDate>> = BTreeMap::new();
+        for occurrence in occurrences.iter().filter(|occurrence| {
+            occurrence.reason == rule.reason
+                && occurrence.date >= window_start
+                && occurrence.date <= today
+        }) {
+            days_by_key
+                .entry((&occurrence.account_id, &occurrence.ticker))
+                .or_default()
+                .insert(occurrence.date);
+        }
+        for ((account_id, ticker), days) in days_by_key {
+            if days.len() < rule.min_days {
+                continue;
+            }
+            let (Some(first_date), Some(last_date)) = (days.first(), days.last()) else {
+                continue;
+            };
+            alerts.push(SkipAlert {
+                account_id: account_id.to_string(),
+                ticker: ticker.to_string(),
+                reason: rule.reason.clone(),
+                days: days.len(),
+                min_days: rule.min_days,
+                window_days: rule.window_days,
+                first_date: *first_date,
+                last_date: *last_date,
+            });
+        }
+    }
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn occurrence(ticker: &str, reason: &str, day: u32) -> SkipOccurrence {
+        SkipOccurrence {
+            account_id: "acct".to_string(),
+            ticker: ticker.to_string(),
+            reason: reason.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+        }
+    }
+
+    #[test]
+    fn parses_rule_shorthands() {
+        let rule = |raw| SkipAlertRule::parse(raw).unwrap();
+        let once = rule("stop_missing");
+        assert_eq!((once.min_days, once.window_days), (1, 1));
+        assert_eq!(rule("insufficient_cash:3").window_days, 7);
+        assert_eq!(rule("sell_exit_order_pending:4/6").window_days, 6);
+        assert!(SkipAlertRule::parse("insufficient_cash:3/2").is_err());
+        assert!(SkipAlertRule::parse("insufficient cash").is_err());
+        assert!(SkipAlertRule::parse("stop_missing:0").is_err());
+    }
+
+    #[test]
+    fn alerts_when_reason_repeats_on_enough_days_in_window() {
+        let rules = vec![SkipAlertRule::parse("insufficient_cash:3/5").unwrap()];
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let occurrences = vec![
+            occurrence("AAA", "insufficient_cash", 5),
+            occurrence("AAA", "insufficient_cash", 8),
+            occurrence("AAA", "insufficient_cash", 8),
+            occurrence("AAA", "insufficient_cash", 10),
+            occurrence("BBB", "insufficient_cash", 2),
+            occurrence("BBB", "insufficient_cash", 9),
+            occurrence("BBB", "insufficient_cash", 10),
+            occurrence("CCC", "insufficient_volume", 10),
+        ];
+
+        let alerts = evaluate_skip_alerts(&rules, &occurrences, today);
+        assert_eq!(alerts.len(), 0);
+
+        let mut occurrences = occurrences;
+        occurrences.push(occurrence("AAA", "insufficient_cash", 6));
+        let alerts = evaluate_skip_alerts(&rules, &occurrences, today);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].ticker, "AAA");
+        assert_eq!(alerts[0].days, 3);
+        assert_eq!(
+            alerts[0].first_date,
+            NaiveDate::from_ymd_opt(2024, 1, 6).unwrap()
+        );
+        assert_eq!(
+            skip_alert_lookback_start(&rules, today),
+            NaiveDate::from_ymd_opt(2024, 1, 6).unwrap()
+        );
+    }
+}
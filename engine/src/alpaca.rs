@@ -1,5 +1,5 @@
 use crate::engine::{AccountPositionState, AccountStateSnapshot, AccountStopOrderState};
-use crate::models::AccountCredentials;
+use crate::models::{AccountCredentials, SecretString};
 use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Utc};
 use log::{info, warn};
@@ -36,11 +36,11 @@ impl<'a> AlpacaClient<'a> {
         let mut headers = HeaderMap::new();
         headers.insert(
             "APCA-API-KEY-ID",
-            HeaderValue::from_str(&creds.api_key).context("invalid Alpaca API key")?,
+            sensitive_header(&creds.api_key).context("invalid Alpaca API key")?,
         );
         headers.insert(
             "APCA-API-SECRET-KEY",
-            HeaderValue::from_str(&creds.api_secret).context("invalid Alpaca API secret")?,
+            sensitive_header(&creds.api_secret).context("invalid Alpaca API secret")?,
         );
 
         Ok(Self {
@@ -106,14 +106,13 @@ impl<'a> AlpacaClient<'a> {
                 if order_type == "stop" || order_type == "stop_limit" {
                     if let Some(stop_price) = entry.stop_price {
                         let qty = entry.qty.unwrap_or(0.0).round() as i32;
-                        stop_orders
-                            .entry(symbol.clone())
-                            .or_insert_with(Vec::new)
-                            .push(AccountStopOrderState {
+                        stop_orders.entry(symbol.clone()).or_default().push(
+                            AccountStopOrderState {
                                 quantity: qty,
                                 stop_price,
                                 side: order_side.clone().unwrap_or_default(),
-                            });
+                            },
+                        );
                     }
                 }
             }
@@ -130,6 +129,11 @@ impl<'a> AlpacaClient<'a> {
             open_sell_orders,
             positions: account_positions,
             stop_orders,
+            equity: account.equity,
+            last_equity: account.last_equity,
+            circuit_breaker_triggered_at: None,
+            risk_overrides: Default::default(),
+            fx: Default::default(),
         })
     }
 
@@ -148,8 +152,12 @@ impl<'a> AlpacaClient<'a> {
         let filled_price = order.filled_price();
         let filled_at = order.filled_timestamp();
 
+        let mut filled_quantity = None;
         let state = match status.as_str() {
-            "filled" | "done_for_day" => OrderState::Filled,
+            "filled" | "done_for_day" => {
+                filled_quantity = order.filled_quantity();
+                OrderState::Filled
+            }
             "partially_filled" => {
                 if order.filled_quantity().unwrap_or(0.0) > 0.0 {
                     OrderState::Filled
@@ -157,13 +165,18 @@ impl<'a> AlpacaClient<'a> {
                     OrderState::Pending
                 }
             }
-            value if is_cancel_status(value) => OrderState::Cancelled,
+            value if is_cancel_status(value) => {
+                // An order cancelled after a partial fill keeps the shares that filled.
+                filled_quantity = order.filled_quantity().filter(|quantity| *quantity > 0.0);
+                OrderState::Cancelled
+            }
             _ => OrderState::Pending,
         };
 
         Ok(Some(OrderEvaluation {
             state,
             filled_price,
+            filled_quantity,
             timestamp: filled_at,
         }))
     }
@@ -183,6 +196,236 @@ impl<'a> AlpacaClient<'a> {
         self.delete_order(trimmed, &client_path).await
     }
 
+    /// Replaces open order `order_id` with the same order for `quantity` shares and returns
+    /// the id of the replacement.
+    pub async fn resize_order(&self, order_id: &str, quantity: i32) -> Result<String> {
+        let path = format!("/orders/{}", order_id.trim());
+        let payload = serde_json::json!({ "qty": quantity.abs().to_string() });
+        let replaced: AlpacaOrder = self.patch(&path, &payload).await?;
+        replaced
+            .id
+            .or(replaced.client_order_id)
+            .ok_or_else(|| anyhow!("Alpaca did not return an id for the replaced order"))
+    }
+
+    /// Open orders still waiting for the broker to route them (accepted, pending new,
+    /// pending cancel or replace) that were submitted before `submitted_before`.
+    pub async fn fetch_stuck_orders(
+        &self,
+        submitted_before: DateTime<Utc>,
+    ) -> Result<Vec<StuckOrder>> {
+        let orders = self.fetch_open_orders().await?;
+        Ok(orders
+            .into_iter()
+            .filter_map(|order| {
+                let status = order.normalized_status();
+                if !is_stuck_status(&status) {
+                    return None;
+                }
+                let submitted_at = parse_timestamp(order.submitted_at.as_deref())?;
+                if submitted_at >= submitted_before {
+                    return None;
+                }
+                Some(StuckOrder {
+                    order_id: extract_order_id(&order)?,
+                    client_order_id: order.client_order_id.clone(),
+                    symbol: normalize_symbol(order.symbol.as_deref())?,
+                    side: normalize_side(order.side.as_deref())?,
+                    status,
+                    submitted_at,
+                    quantity: order.qty,
+                    order_type: order.order_type.clone(),
+                    time_in_force: order.time_in_force.clone(),
+                    limit_price: order.limit_price,
+                    stop_price: order.stop_price,
+                    is_simple: order
+                        .order_class
+                        .as_deref()
+                        .map(|class| {
+                            class.trim().is_empty() || class.eq_ignore_ascii_case("simple")
+                        })
+                        .unwrap_or(true),
+                })
+            })
+            .collect())
+    }
+
+    /// Places a copy of a simple stuck order under a new client order id and returns the new
+    /// broker order id.
+    pub async fn resubmit_order(&self, order: &StuckOrder) -> Result<String> {
+        if !order.is_simple {
+            return Err(anyhow!(
+                "Order {} is part of a multi-leg order and cannot be resubmitted",
+                order.order_id
+            ));
+        }
+        let quantity = order
+            .quantity
+            .filter(|qty| qty.is_finite() && *qty > 0.0)
+            .ok_or_else(|| anyhow!("Order {} has no quantity to resubmit", order.order_id))?;
+        let base_client_id = order
+            .client_order_id
+            .as_deref()
+            .unwrap_or(order.order_id.as_str());
+        let mut payload = serde_json::json!({
+            "symbol": order.symbol,
+            "qty": quantity.to_string(),
+            "side": order.side,
+            "type": order.order_type.as_deref().unwrap_or("market"),
+            "time_in_force": order.time_in_force.as_deref().unwrap_or("day"),
+            "client_order_id": format!("{}-resubmit-{}", base_client_id, Utc::now().timestamp()),
+        });
+        if let Some(limit_price) = order.limit_price {
+            payload["limit_price"] = serde_json::json!(limit_price.to_string());
+        }
+        if let Some(stop_price) = order.stop_price {
+            payload["stop_price"] = serde_json::json!(stop_price.to_string());
+        }
+
+        let created: AlpacaOrder = self.post("/orders", &payload).await?;
+        created
+            .id
+            .clone()
+            .or(created.client_order_id.clone())
+            .ok_or_else(|| anyhow!("Alpaca did not return an id for the resubmitted order"))
+    }
+
+    /// Symbols the broker currently reports as not tradable (halted or inactive). Lookups
+    /// that fail are logged and treated as tradable so a flaky asset endpoint cannot pause
+    /// every operation.
+    pub async fn fetch_halted_symbols(&self, symbols: &[String]) -> HashSet<String> {
+        let mut halted = HashSet::new();
+        for symbol in symbols {
+            let path = format!("/assets/{}", symbol);
+            match self.get::<AlpacaAsset>(&path).await {
+                Ok(asset) => {
+                    let active = asset
+                        .status
+                        .as_deref()
+                        .map(|status| status.eq_ignore_ascii_case("active"))
+                        .unwrap_or(true);
+                    if !active || asset.tradable == Some(false) {
+                        halted.insert(symbol.clone());
+                    }
+                }
+                Err(err) => {
+                    warn!(
+                        "Failed to fetch Alpaca asset status for {}: {}",
+                        symbol, err
+                    );
+                }
+            }
+        }
+        halted
+    }
+
+    /// Orders that filled at least one share, oldest first, optionally only those submitted
+    /// after `after`.
+    pub async fn fetch_filled_orders(
+        &self,
+        after: Option<DateTime<Utc>>,
+    ) -> Result<Vec<FilledOrder>> {
+        let mut filled = Vec::new();
+        let mut seen = HashSet::new();
+        let mut submitted_after = after.map(|value| value.to_rfc3339());
+        let mut pages = 0usize;
+
+        loop {
+            if pages >= ORDER_MAX_PAGES {
+                break;
+            }
+            pages += 1;
+
+            let mut query_params = vec![
+                ("status", "closed"),
+                ("direction", "asc"),
+                ("limit", ORDER_QUERY_LIMIT),
+                ("nested", "true"),
+            ];
+            if let Some(after) = submitted_after.as_deref() {
+                query_params.push(("after", after));
+            }
+
+            let entries: Vec<AlpacaOrder> = self.get_with_query("/orders", &query_params).await?;
+            if entries.is_empty() {
+                break;
+            }
+
+            let is_last_page = entries.len() < ORDER_QUERY_LIMIT_NUM;
+            let last_submitted = entries
+                .iter()
+                .rev()
+                .find_map(|order| order.submitted_at.clone());
+            for order in entries
+                .iter()
+                .chain(entries.iter().flat_map(|order| order.legs.iter().flatten()))
+            {
+                let Some(order_id) = extract_order_id(order) else {
+                    continue;
+                };
+                if !seen.insert(order_id.clone()) {
+                    continue;
+                }
+                let quantity = order.filled_quantity().unwrap_or(0.0);
+                let (Some(symbol), Some(side), Some(price), Some(filled_at)) = (
+                    normalize_symbol(order.symbol.as_deref()),
+                    normalize_side(order.side.as_deref()),
+                    order.filled_avg_price,
+                    parse_timestamp(order.filled_at.as_deref()),
+                ) else {
+                    continue;
+                };
+                if quantity <= 0.0 {
+                    continue;
+                }
+                filled.push(FilledOrder {
+                    order_id,
+                    symbol,
+                    side,
+                    quantity,
+                    price,
+                    filled_at,
+                });
+            }
+
+            if is_last_page || last_submitted == submitted_after {
+                break;
+            }
+            submitted_after = last_submitted;
+            if submitted_after.is_none() {
+                break;
+            }
+        }
+
+        filled.sort_by(|a, b| {
+            a.filled_at
+                .cmp(&b.filled_at)
+                .then_with(|| a.order_id.cmp(&b.order_id))
+        });
+        Ok(filled)
+    }
+
+    /// Resting stop and stop-limit orders.
+    pub async fn fetch_open_stop_orders(&self) -> Result<Vec<OpenStopOrder>> {
+        let orders = self.fetch_open_orders().await?;
+        Ok(orders
+            .into_iter()
+            .filter_map(|order| {
+                let order_type = order.order_type.as_deref()?.trim().to_lowercase();
+                if order_type != "stop" && order_type != "stop_limit" {
+                    return None;
+                }
+                Some(OpenStopOrder {
+                    order_id: extract_order_id(&order)?,
+                    symbol: normalize_symbol(order.symbol.as_deref())?,
+                    side: normalize_side(order.side.as_deref())?,
+                    quantity: order.qty?,
+                    stop_price: order.stop_price?,
+                })
+            })
+            .collect())
+    }
+
     async fn fetch_open_orders(&self) -> Result<Vec<AlpacaOrder>> {
         let mut all_orders = Vec::new();
         let mut after_order_id: Option<String> = None;
@@ -286,6 +529,38 @@ impl<'a> AlpacaClient<'a> {
         parse_alpaca_json(response, path).await
     }
 
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: &serde_json::Value) -> Result<T> {
+        sleep(REQUEST_DELAY).await;
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .http
+            .post(url)
+            .headers(self.headers.clone())
+            .json(body)
+            .send()
+            .await
+            .with_context(|| format!("POST {}{} failed", self.base_url, path))?
+            .error_for_status()
+            .with_context(|| format!("POST {}{} returned error", self.base_url, path))?;
+        parse_alpaca_json(response, path).await
+    }
+
+    async fn patch<T: DeserializeOwned>(&self, path: &str, body: &serde_json::Value) -> Result<T> {
+        sleep(REQUEST_DELAY).await;
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .http
+            .patch(url)
+            .headers(self.headers.clone())
+            .json(body)
+            .send()
+            .await
+            .with_context(|| format!("PATCH {}{} failed", self.base_url, path))?
+            .error_for_status()
+            .with_context(|| format!("PATCH {}{} returned error", self.base_url, path))?;
+        parse_alpaca_json(response, path).await
+    }
+
     async fn get_optional<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
         sleep(REQUEST_DELAY).await;
         let url = format!("{}{}", self.base_url, path);
@@ -347,9 +622,19 @@ fn resolve_alpaca_base_url(
     }
 }
 
+/// Builds a header value marked sensitive so reqwest and hyper never print it.
+fn sensitive_header(secret: &SecretString) -> Result<HeaderValue> {
+    let mut value = HeaderValue::from_str(secret.expose())?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+#[derive(Clone)]
 pub struct OrderEvaluation {
     pub state: OrderState,
     pub filled_price: Option<f64>,
+    /// Shares filled, set once the order is done filling.
+    pub filled_quantity: Option<f64>,
     pub timestamp: Option<DateTime<Utc>>,
 }
 
@@ -359,6 +644,44 @@ impl OrderEvaluation {
     }
 }
 
+/// Broker order that has not progressed past acceptance within the watchdog timeout.
+#[derive(Debug, Clone)]
+pub struct StuckOrder {
+    pub order_id: String,
+    pub client_order_id: Option<String>,
+    pub symbol: String,
+    pub side: String,
+    pub status: String,
+    pub submitted_at: DateTime<Utc>,
+    pub quantity: Option<f64>,
+    pub order_type: Option<String>,
+    pub time_in_force: Option<String>,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub is_simple: bool,
+}
+
+/// Broker order that filled at least one share.
+#[derive(Debug, Clone)]
+pub struct FilledOrder {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub filled_at: DateTime<Utc>,
+}
+
+/// Stop order still resting at the broker.
+#[derive(Debug, Clone)]
+pub struct OpenStopOrder {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: String,
+    pub quantity: f64,
+    pub stop_price: f64,
+}
+
 #[derive(Clone, Copy)]
 pub enum OrderState {
     Pending,
@@ -374,6 +697,10 @@ struct AlpacaAccount {
     buying_power: Option<f64>,
     #[serde(default, deserialize_with = "deserialize_f64_opt")]
     regt_buying_power: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_f64_opt")]
+    equity: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_f64_opt")]
+    last_equity: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -390,6 +717,14 @@ struct AlpacaPosition {
     current_price: Option<f64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct AlpacaAsset {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    tradable: Option<bool>,
+}
+
 #[derive(Debug, Deserialize)]
 struct AlpacaOrder {
     #[serde(default)]
@@ -422,6 +757,12 @@ struct AlpacaOrder {
     updated_at: Option<String>,
     #[serde(default)]
     submitted_at: Option<String>,
+    #[serde(default)]
+    time_in_force: Option<String>,
+    #[serde(default)]
+    order_class: Option<String>,
+    #[serde(default)]
+    legs: Option<Vec<AlpacaOrder>>,
 }
 
 impl AlpacaOrder {
@@ -538,7 +879,7 @@ fn extract_order_id(value: &AlpacaOrder) -> Option<String> {
     value
         .id
         .as_deref()
-        .or_else(|| value.client_order_id.as_deref())
+        .or(value.client_order_id.as_deref())
         .map(|id| id.trim())
         .filter(|id| !id.is_empty())
         .map(|id| id.to_string())
@@ -552,6 +893,13 @@ fn parse_timestamp(raw: Option<&str>) -> Option<DateTime<Utc>> {
     })
 }
 
+fn is_stuck_status(status: &str) -> bool {
+    matches!(
+        status,
+        "accepted" | "pending_new" | "accepted_for_bidding" | "pending_cancel" | "pending_replace"
+    )
+}
+
 fn is_cancel_status(status: &str) -> bool {
     matches!(
         status,
@@ -1,3 +1,5 @@
+use crate::blackout::BlackoutCalendar;
+use crate::cancellation::{is_cancellation, CancellationToken, Cancelled};
 use crate::config::{resolve_backtest_initial_capital, EngineRuntimeSettings};
 use crate::data_context::{MarketData, TickerScope};
 use crate::database::Database;
@@ -22,6 +24,7 @@ struct StrategyBacktestTask {
     name: String,
     template_id: String,
     parameters: HashMap<String, f64>,
+    blackout: BlackoutCalendar,
     signals: Vec<GeneratedSignal>,
     start_date_override: chrono::DateTime<chrono::Utc>,
     months_filter: Option<i64>,
@@ -55,18 +58,21 @@ struct StrategyBacktestSuccess {
 }
 
 #[derive(Clone, Copy)]
-pub enum StrategySelection {
+pub enum StrategySelection<'a> {
     All,
     AccountLinkedOnly,
     WithoutAccounts,
+    /// Strategies linked to one account.
+    Account(&'a str),
 }
 
-impl StrategySelection {
+impl StrategySelection<'_> {
     fn description(self) -> &'static str {
         match self {
             StrategySelection::All => "active",
             StrategySelection::AccountLinkedOnly => "account-linked",
             StrategySelection::WithoutAccounts => "unlinked active",
+            StrategySelection::Account(_) => "active account",
         }
     }
 
@@ -75,6 +81,9 @@ impl StrategySelection {
             StrategySelection::All => true,
             StrategySelection::AccountLinkedOnly => strategy_has_linked_account(strategy),
             StrategySelection::WithoutAccounts => !strategy_has_linked_account(strategy),
+            StrategySelection::Account(account_id) => {
+                strategy.account_id.as_deref().map(str::trim) == Some(account_id)
+            }
         }
     }
 }
@@ -93,6 +102,7 @@ pub struct ActiveStrategyBacktester<'a> {
     data: &'a MarketData,
     backtested_strategy_ids: &'a mut HashSet<String>,
     ticker_scope: TickerScope,
+    cancellation: &'a CancellationToken,
 }
 
 impl<'a> ActiveStrategyBacktester<'a> {
@@ -102,6 +112,7 @@ impl<'a> ActiveStrategyBacktester<'a> {
         data: &'a MarketData,
         backtested_strategy_ids: &'a mut HashSet<String>,
         ticker_scope: TickerScope,
+        cancellation: &'a CancellationToken,
     ) -> Self {
         Self {
             db,
@@ -109,13 +120,14 @@ impl<'a> ActiveStrategyBacktester<'a> {
             data,
             backtested_strategy_ids,
             ticker_scope,
+            cancellation,
         }
     }
 
     pub async fn run_with_selection(
         &mut self,
         months: Option<u32>,
-        selection: StrategySelection,
+        selection: StrategySelection<'_>,
     ) -> Result<()> {
         if !self.data.has_data() {
             warn!("No market data available to run backtests.");
@@ -441,7 +453,7 @@ impl<'a> ActiveStrategyBacktester<'a> {
         ));
         self.status.set_progress(total, 0, 0, None);
 
-        let num_workers = std::cmp::min(total, std::cmp::max(1, num_cpus::get()));
+        let num_workers = std::cmp::min(total, std::cmp::max(1, rayon::current_num_threads()));
         info!(
             "Using {} worker threads for active strategy backtests",
             num_workers
@@ -455,7 +467,7 @@ impl<'a> ActiveStrategyBacktester<'a> {
         ) = bounded(total);
 
         let ticker_universe = self.data.tickers_arc();
-        let ticker_expense_map = self.data.ticker_expense_map_arc();
+        let auxiliary_data = self.data.auxiliary_data_arc();
         let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
         let backtest_initial_capital = resolve_backtest_initial_capital(self.data.settings());
         let mut handles = Vec::new();
@@ -465,16 +477,21 @@ impl<'a> ActiveStrategyBacktester<'a> {
             let all_candles = all_candles_window.clone();
             let unique_dates = unique_dates_window.clone();
             let tickers = ticker_universe.clone();
-            let expense_map = ticker_expense_map.clone();
+            let auxiliary = auxiliary_data.clone();
             let runtime_settings = runtime_settings.clone();
+            let cancellation = self.cancellation.clone();
 
             let handle = thread::spawn(move || {
                 while let Ok(task) = rx.recv() {
+                    if cancellation.is_cancelled() {
+                        break;
+                    }
                     let StrategyBacktestTask {
                         id,
                         name,
                         template_id,
                         parameters,
+                        blackout,
                         signals,
                         start_date_override,
                         months_filter,
@@ -485,7 +502,9 @@ impl<'a> ActiveStrategyBacktester<'a> {
                     let run_result: StdResult<CompletedBacktestPayload, String> = {
                         let mut engine =
                             Engine::from_parameters(&parameters, runtime_settings.clone());
-                        engine.set_ticker_expense_map(expense_map.clone());
+                        engine.set_auxiliary_data(auxiliary.clone());
+                        engine.set_cancellation(cancellation.clone());
+                        engine.set_blackout(blackout);
                         let filtered_tickers = if signals.is_empty() {
                             None
                         } else {
@@ -516,6 +535,8 @@ impl<'a> ActiveStrategyBacktester<'a> {
                                 result: run.result,
                                 signal_skips: run.signal_skips,
                             }),
+                            // Cancelled runs are not failures of the strategy.
+                            Err(e) if is_cancellation(&e) => break,
                             Err(e) => Err(e.to_string()),
                         }
                     };
@@ -538,6 +559,8 @@ impl<'a> ActiveStrategyBacktester<'a> {
             });
             handles.push(handle);
         }
+        // Workers hold the remaining senders, so the results channel closes once they all stop.
+        drop(result_tx);
 
         for (strategy, effective_start, months_filter, existing_backtest) in runnable_strategies {
             let has_linked_account = strategy_has_linked_account(&strategy);
@@ -559,6 +582,7 @@ impl<'a> ActiveStrategyBacktester<'a> {
                 name: strategy.name.clone(),
                 template_id: strategy.template_id.clone(),
                 parameters,
+                blackout: BlackoutCalendar::build(&strategy.blackout, &auxiliary_data),
                 signals,
                 start_date_override: effective_start,
                 months_filter,
@@ -570,9 +594,9 @@ impl<'a> ActiveStrategyBacktester<'a> {
 
         let mut completed_runs = 0usize;
         let mut failures: Vec<String> = Vec::new();
-        let mut pending_persistence: Vec<StrategyBacktestSuccess> = Vec::new();
+        let mut persisted_successes = 0usize;
 
-        while completed_runs < total {
+        while completed_runs < total && !self.cancellation.is_cancelled() {
             match result_rx.recv() {
                 Ok(message) => {
                     completed_runs += 1;
@@ -585,9 +609,10 @@ impl<'a> ActiveStrategyBacktester<'a> {
                                 message.name, calmar_ratio, sharpe, message.duration_minutes
                             );
                             self.status.set_phase(format!(
-                                "Completed {}/{} strategies (awaiting persistence; last: {})",
+                                "Completed {}/{} strategies (persisting {})",
                                 completed_runs, total, message.name
                             ));
+                            let strategy_name = message.name.clone();
                             let success = StrategyBacktestSuccess {
                                 id: message.id,
                                 name: message.name,
@@ -597,13 +622,33 @@ impl<'a> ActiveStrategyBacktester<'a> {
                                 account_id: message.account_id.clone(),
                                 signal_skips: payload.signal_skips,
                             };
-                            pending_persistence.push(success);
-                            self.status.set_progress(
-                                total,
-                                completed_runs,
-                                failures.len(),
-                                Some(calmar_ratio),
-                            );
+                            // Persist each result as it arrives so finished strategies are
+                            // stored even if a later one fails, and results don't pile up.
+                            if let Some(error) = self.persist_backtest_success(success).await {
+                                failures.push(error);
+                                self.status.set_phase(format!(
+                                    "Completed {}/{} strategies (last failure: {})",
+                                    completed_runs, total, strategy_name
+                                ));
+                                self.status.set_progress(
+                                    total,
+                                    completed_runs,
+                                    failures.len(),
+                                    None,
+                                );
+                            } else {
+                                persisted_successes += 1;
+                                self.status.set_phase(format!(
+                                    "Completed {}/{} strategies ({} persisted; last: {})",
+                                    completed_runs, total, persisted_successes, strategy_name
+                                ));
+                                self.status.set_progress(
+                                    total,
+                                    completed_runs,
+                                    failures.len(),
+                                    Some(calmar_ratio),
+                                );
+                            }
                         }
                         Err(error) => {
                             warn!(
@@ -644,49 +689,14 @@ impl<'a> ActiveStrategyBacktester<'a> {
             let _ = handle.join();
         }
 
-        let total_successes = pending_persistence.len();
-        if total_successes > 0 {
-            info!(
-                "Persisting {} backtest result{} sequentially",
-                total_successes,
-                if total_successes == 1 { "" } else { "s" }
+        if self.cancellation.is_cancelled() {
+            warn!(
+                "Backtesting cancelled after {}/{} strategies",
+                completed_runs, total
             );
-            self.status.set_phase(format!(
-                "Persisting {} backtest result{} sequentially",
-                total_successes,
-                if total_successes == 1 { "" } else { "s" }
-            ));
-        }
-        let mut persisted_successes = 0usize;
-        for success in pending_persistence {
-            let strategy_name = success.name.clone();
-            let calmar_ratio = success.run.performance.calmar_ratio;
-            match self.persist_backtest_success(success).await {
-                Some(error) => {
-                    failures.push(error);
-                    self.status.set_phase(format!(
-                        "Persisted {}/{} results (last failure: {})",
-                        persisted_successes, total_successes, strategy_name
-                    ));
-                    self.status
-                        .set_progress(total, completed_runs, failures.len(), None);
-                }
-                None => {
-                    persisted_successes += 1;
-                    self.status.set_phase(format!(
-                        "Persisted {}/{} results (last success: {})",
-                        persisted_successes, total_successes, strategy_name
-                    ));
-                    self.status.set_progress(
-                        total,
-                        completed_runs,
-                        failures.len(),
-                        Some(calmar_ratio),
-                    );
-                }
-            }
+            self.status.set_phase("Backtesting cancelled");
+            return Err(Cancelled.into());
         }
-
         if failures.is_empty() {
             self.status.set_phase("Backtesting completed successfully");
         } else {
@@ -747,6 +757,7 @@ impl<'a> ActiveStrategyBacktester<'a> {
                     &run,
                     months_filter,
                     self.ticker_scope.result_label(),
+                    Some(self.data.content_hash()),
                 )
                 .await
         }) {
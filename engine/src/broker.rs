@@ -0,0 +1,111 @@
+use crate::alpaca::{AlpacaClient, OrderEvaluation};
+use crate::config::PaperBrokerSettings;
+use crate::database::Database;
+use crate::engine::AccountStateSnapshot;
+use crate::models::AccountCredentials;
+use crate::paper_broker::PaperBroker;
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use std::collections::{HashMap, HashSet};
+
+/// Account access that plan-operations and reconcile-trades need from a brokerage.
+// Only used through static dispatch, so the futures' `Send` bounds never matter.
+#[allow(async_fn_in_trait)]
+pub trait Broker {
+    async fn fetch_account_state(&self) -> Result<AccountStateSnapshot>;
+    async fn evaluate_order(&self, order_id: &str) -> Result<Option<OrderEvaluation>>;
+    async fn cancel_order(&self, order_id: &str) -> Result<bool>;
+    /// Changes open order `order_id` to `quantity` shares, returning the id that now carries it.
+    async fn resize_order(&self, order_id: &str, quantity: i32) -> Result<String>;
+    async fn fetch_halted_symbols(&self, symbols: &[String]) -> HashSet<String>;
+}
+
+impl Broker for AlpacaClient<'_> {
+    async fn fetch_account_state(&self) -> Result<AccountStateSnapshot> {
+        AlpacaClient::fetch_account_state(self).await
+    }
+
+    async fn evaluate_order(&self, order_id: &str) -> Result<Option<OrderEvaluation>> {
+        AlpacaClient::evaluate_order(self, order_id).await
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        AlpacaClient::cancel_order(self, order_id).await
+    }
+
+    async fn resize_order(&self, order_id: &str, quantity: i32) -> Result<String> {
+        AlpacaClient::resize_order(self, order_id, quantity).await
+    }
+
+    async fn fetch_halted_symbols(&self, symbols: &[String]) -> HashSet<String> {
+        AlpacaClient::fetch_halted_symbols(self, symbols).await
+    }
+}
+
+/// The broker behind an account, picked by its provider: `alpaca` or `paper`.
+pub enum AccountBroker<'a> {
+    Alpaca(AlpacaClient<'a>),
+    Paper(PaperBroker<'a>),
+}
+
+impl<'a> AccountBroker<'a> {
+    pub fn supports(provider: &str) -> bool {
+        provider.eq_ignore_ascii_case("alpaca") || provider.eq_ignore_ascii_case("paper")
+    }
+
+    pub fn connect(
+        http: &'a Client,
+        db: &'a Database,
+        creds: &AccountCredentials,
+        settings: &HashMap<String, String>,
+    ) -> Result<Self> {
+        if creds.provider.eq_ignore_ascii_case("alpaca") {
+            Ok(Self::Alpaca(AlpacaClient::new(http, creds, settings)?))
+        } else if creds.provider.eq_ignore_ascii_case("paper") {
+            Ok(Self::Paper(PaperBroker::new(
+                db,
+                &creds.id,
+                PaperBrokerSettings::from_settings_map(settings)?,
+            )))
+        } else {
+            Err(anyhow!("unsupported account provider {}", creds.provider))
+        }
+    }
+}
+
+impl Broker for AccountBroker<'_> {
+    async fn fetch_account_state(&self) -> Result<AccountStateSnapshot> {
+        match self {
+            Self::Alpaca(client) => Broker::fetch_account_state(client).await,
+            Self::Paper(broker) => broker.fetch_account_state().await,
+        }
+    }
+
+    async fn evaluate_order(&self, order_id: &str) -> Result<Option<OrderEvaluation>> {
+        match self {
+            Self::Alpaca(client) => Broker::evaluate_order(client, order_id).await,
+            Self::Paper(broker) => broker.evaluate_order(order_id).await,
+        }
+    }
+
+    async fn cancel_order(&self, order_id: &str) -> Result<bool> {
+        match self {
+            Self::Alpaca(client) => Broker::cancel_order(client, order_id).await,
+            Self::Paper(broker) => broker.cancel_order(order_id).await,
+        }
+    }
+
+    async fn resize_order(&self, order_id: &str, quantity: i32) -> Result<String> {
+        match self {
+            Self::Alpaca(client) => Broker::resize_order(client, order_id, quantity).await,
+            Self::Paper(broker) => broker.resize_order(order_id, quantity).await,
+        }
+    }
+
+    async fn fetch_halted_symbols(&self, symbols: &[String]) -> HashSet<String> {
+        match self {
+            Self::Alpaca(client) => Broker::fetch_halted_symbols(client, symbols).await,
+            Self::Paper(broker) => broker.fetch_halted_symbols(symbols).await,
+        }
+    }
+}
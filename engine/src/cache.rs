@@ -1,5 +1,7 @@
 use crate::backtest_api_client::build_blocking_client;
 use crate::models::*;
+use crate::storage::{LocalCacheRecord, SqliteStorage};
+use crate::strategy::lightgbm::CrossSectionalCache;
 use anyhow::{anyhow, Result};
 use dashmap::DashMap;
 use std::collections::HashMap;
@@ -18,6 +20,9 @@ pub struct CacheManager {
     remote_request_gate: Arc<Mutex<Option<Instant>>>,
     remote_api_secret: Option<String>,
     remote_api_base_url: Option<String>,
+    local_store: Option<SqliteStorage>,
+    cross_sectional: Arc<CrossSectionalCache>,
+    data_hash: Option<String>,
 }
 
 pub struct CacheStoreParams {
@@ -30,6 +35,8 @@ pub struct CacheStoreParams {
     pub duration_minutes: f64,
     pub top_absolute_gain_ticker: Option<String>,
     pub top_relative_gain_ticker: Option<String>,
+    /// Set on the final result of an optimization run to record why the search stopped.
+    pub stop_reason: Option<String>,
 }
 
 impl CacheManager {
@@ -51,9 +58,35 @@ impl CacheManager {
             remote_request_gate: Arc::new(Mutex::new(None)),
             remote_api_secret,
             remote_api_base_url,
+            local_store: None,
+            cross_sectional: Arc::new(CrossSectionalCache::default()),
+            data_hash: None,
         }
     }
 
+    /// Tags stored results with the market data content hash and ignores cached results
+    /// computed from different data. Results stored without a hash still match.
+    pub fn with_data_hash(mut self, data_hash: &str) -> Self {
+        self.data_hash = Some(data_hash.to_string());
+        self
+    }
+
+    /// Reads and writes cached results through a local SQLite store before the cache API.
+    pub fn with_local_store(mut self, store: SqliteStorage) -> Self {
+        self.local_store = Some(store);
+        self
+    }
+
+    /// Shares `cache` (typically the run-wide one from `AppContext`) for cross-sectional contexts.
+    pub fn with_cross_sectional_cache(mut self, cache: Arc<CrossSectionalCache>) -> Self {
+        self.cross_sectional = cache;
+        self
+    }
+
+    pub fn cross_sectional_cache(&self) -> &Arc<CrossSectionalCache> {
+        &self.cross_sectional
+    }
+
     fn throttle_remote_request(&self) {
         const MIN_GAP: Duration = Duration::from_secs(1);
         if let Ok(mut guard) = self.remote_request_gate.lock() {
@@ -119,14 +152,22 @@ impl CacheManager {
             return Some(result.clone());
         }
 
+        if let Some(store) = &self.local_store {
+            match store.cached_result(template_id, parameters, self.data_hash.as_deref()) {
+                Ok(Some(result)) => {
+                    self.local_cache.insert(cache_key, result.clone());
+                    return Some(result);
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to check local SQLite cache: {:?}", e),
+            }
+        }
+
         let use_local_api = self.has_db && std::env::var("SERVER_PORT").is_ok();
         let api_base_url = if use_local_api {
             resolve_local_api_base_url()
         } else {
-            let Some(api_base_url) = self.remote_api_base_url.clone() else {
-                return None;
-            };
-            api_base_url
+            self.remote_api_base_url.clone()?
         };
 
         match self.check_remote_cache(&api_base_url, template_id, parameters, !use_local_api) {
@@ -143,6 +184,23 @@ impl CacheManager {
     }
 
     pub fn store_cache(&self, params: CacheStoreParams) {
+        if let Some(store) = &self.local_store {
+            if let Err(e) = store.store_result(LocalCacheRecord {
+                template_id: &params.template_id,
+                result: &params.result,
+                ticker_count: params.ticker_count,
+                start_date: params.start_date,
+                end_date: params.end_date,
+                duration_minutes: params.duration_minutes,
+                top_absolute_gain_ticker: params.top_absolute_gain_ticker.as_deref(),
+                top_relative_gain_ticker: params.top_relative_gain_ticker.as_deref(),
+                data_hash: self.data_hash.as_deref(),
+                stop_reason: params.stop_reason.as_deref(),
+            }) {
+                log::warn!("Failed to store result in local SQLite cache: {:?}", e);
+            }
+        }
+
         let use_local_api = self.has_db && std::env::var("SERVER_PORT").is_ok();
         let api_base_url = if use_local_api {
             resolve_local_api_base_url()
@@ -154,11 +212,16 @@ impl CacheManager {
         };
         let timeout = self.remote_timeout;
         let api_secret = self.remote_api_secret.clone();
+        let data_hash = self.data_hash.clone();
 
         thread::spawn(move || {
-            if let Err(e) =
-                Self::store_remote_cache_static(&api_base_url, timeout, params, api_secret)
-            {
+            if let Err(e) = Self::store_remote_cache_static(
+                &api_base_url,
+                timeout,
+                params,
+                api_secret,
+                data_hash,
+            ) {
                 log::warn!("Failed to store cache: {:?}", e);
             }
         });
@@ -175,6 +238,7 @@ impl CacheManager {
         let parameters = parameters.clone();
         let timeout = self.remote_timeout;
         let api_secret = self.remote_api_secret.clone();
+        let data_hash = self.data_hash.clone();
 
         Self::retry_with_backoff(|| {
             if throttle {
@@ -186,6 +250,7 @@ impl CacheManager {
             let request_body = serde_json::json!({
                 "templateId": template_id,
                 "parameters": parameters,
+                "dataHash": data_hash,
             });
 
             let mut request = client.post(url.as_str()).json(&request_body);
@@ -218,6 +283,7 @@ impl CacheManager {
         timeout: Duration,
         params: CacheStoreParams,
         api_secret: Option<String>,
+        data_hash: Option<String>,
     ) -> Result<()> {
         let CacheStoreParams {
             template_id,
@@ -229,6 +295,7 @@ impl CacheManager {
             duration_minutes,
             top_absolute_gain_ticker,
             top_relative_gain_ticker,
+            stop_reason,
         } = params;
 
         Self::retry_with_backoff(move || {
@@ -253,6 +320,8 @@ impl CacheManager {
                 "tool": "rust-cli",
                 "topAbsoluteGainTicker": top_absolute_gain_ticker,
                 "topRelativeGainTicker": top_relative_gain_ticker,
+                "dataHash": data_hash,
+                "stopReason": stop_reason,
             });
 
             let mut request = client.post(&url).json(&request_body);
@@ -0,0 +1,155 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CLI_CONFIG_FILE: &str = "stratcraft.toml";
+pub const CLI_ENVIRONMENT_ENV_VAR: &str = "STRATCRAFT_ENV";
+const ENGINE_THREADS_ENV_VAR: &str = "ENGINE_THREADS";
+
+/// Defaults for CLI options loaded from `stratcraft.toml`.
+///
+/// Top-level keys apply to every run; `[environments.<name>]` tables override them when the
+/// environment is selected with `--env` or `STRATCRAFT_ENV`. CLI flags and environment
+/// variables take precedence over file values.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CliDefaults {
+    pub database_url: Option<String>,
+    pub threads: Option<usize>,
+    pub data_file: Option<PathBuf>,
+    pub sqlite_file: Option<PathBuf>,
+    pub cross_sectional_cache_dir: Option<PathBuf>,
+}
+
+impl CliDefaults {
+    fn overlay(self, overrides: CliDefaults) -> Self {
+        Self {
+            database_url: overrides.database_url.or(self.database_url),
+            threads: overrides.threads.or(self.threads),
+            data_file: overrides.data_file.or(self.data_file),
+            sqlite_file: overrides.sqlite_file.or(self.sqlite_file),
+            cross_sectional_cache_dir: overrides
+                .cross_sectional_cache_dir
+                .or(self.cross_sectional_cache_dir),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CliConfigFile {
+    database_url: Option<String>,
+    threads: Option<usize>,
+    data_file: Option<PathBuf>,
+    sqlite_file: Option<PathBuf>,
+    cross_sectional_cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    environments: HashMap<String, CliDefaults>,
+}
+
+/// Loads CLI defaults from `path`, or from `stratcraft.toml` in the working directory when it
+/// exists. A missing default file yields empty defaults; a missing explicit file is an error.
+pub fn load_cli_defaults(path: Option<&Path>, environment: Option<&str>) -> Result<CliDefaults> {
+    let environment = environment
+        .map(str::to_string)
+        .or_else(|| std::env::var(CLI_ENVIRONMENT_ENV_VAR).ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let default_path = Path::new(DEFAULT_CLI_CONFIG_FILE);
+    let path = match path {
+        Some(path) => path,
+        None if default_path.exists() => default_path,
+        None => {
+            if let Some(environment) = environment {
+                return Err(anyhow!(
+                    "Environment '{}' requested but {} was not found",
+                    environment,
+                    DEFAULT_CLI_CONFIG_FILE
+                ));
+            }
+            return Ok(CliDefaults::default());
+        }
+    };
+
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read CLI config {}", path.display()))?;
+    parse_cli_defaults(&text, environment.as_deref())
+        .with_context(|| format!("Invalid CLI config {}", path.display()))
+}
+
+fn parse_cli_defaults(text: &str, environment: Option<&str>) -> Result<CliDefaults> {
+    let mut file: CliConfigFile = toml::from_str(text)?;
+    let defaults = CliDefaults {
+        database_url: file.database_url,
+        threads: file.threads,
+        data_file: file.data_file,
+        sqlite_file: file.sqlite_file,
+        cross_sectional_cache_dir: file.cross_sectional_cache_dir,
+    };
+    let Some(environment) = environment else {
+        return Ok(defaults);
+    };
+    let overrides = file
+        .environments
+        .remove(environment)
+        .ok_or_else(|| anyhow!("Environment '{}' is not defined", environment))?;
+    Ok(defaults.overlay(overrides))
+}
+
+/// Resolves the worker thread count from the CLI flag, then ENGINE_THREADS, then the file.
+pub fn resolve_thread_count(
+    cli_value: Option<usize>,
+    defaults: &CliDefaults,
+) -> Result<Option<usize>> {
+    if cli_value.is_some() {
+        return Ok(cli_value);
+    }
+    match std::env::var(ENGINE_THREADS_ENV_VAR) {
+        Ok(raw) if !raw.trim().is_empty() => raw.trim().parse::<usize>().map(Some).map_err(|_| {
+            anyhow!(
+                "{} must be a positive integer (value: {})",
+                ENGINE_THREADS_ENV_VAR,
+                raw
+            )
+        }),
+        _ => Ok(defaults.threads),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+database_url = "postgres://localhost/stratcraft"
+threads = 8
+data_file = "../data/market-data.bin"
+
+[environments.production]
+database_url = "postgres://db.internal/stratcraft"
+threads = 32
+"#;
+
+    #[test]
+    fn environment_overrides_top_level_defaults() {
+        let defaults = parse_cli_defaults(SAMPLE, Some("production")).unwrap();
+        assert_eq!(
+            defaults.database_url.as_deref(),
+            Some("postgres://db.internal/stratcraft")
+        );
+        assert_eq!(defaults.threads, Some(32));
+        assert_eq!(
+            defaults.data_file,
+            Some(PathBuf::from("../data/market-data.bin"))
+        );
+    }
+
+    #[test]
+    fn unknown_environment_and_keys_are_rejected() {
+        assert!(parse_cli_defaults(SAMPLE, Some("staging")).is_err());
+        assert!(parse_cli_defaults("thread = 4", None).is_err());
+    }
+}
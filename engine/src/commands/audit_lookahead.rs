@@ -0,0 +1,106 @@
+use crate::commands::market_data_snapshot::ensure_market_data_file;
+use crate::commands::verify::{best_cached_parameter_set, objective_score, verify_window_context};
+use crate::config::EngineRuntimeSettings;
+use crate::context::AppContext;
+use crate::models::BacktestRun;
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use std::path::Path;
+
+/// Trading sessions every signal is delayed by.
+const DELAY_SESSIONS: usize = 1;
+/// Share of the objective the delayed replay must keep; a strategy whose edge vanishes when
+/// it acts a day late likely sees data it could not have had.
+const MIN_RETAINED: f64 = 0.5;
+
+/// True when a positive `base` objective drops below MIN_RETAINED of itself once signals are
+/// delayed. Strategies without an edge have nothing to leak.
+pub fn collapses_when_delayed(base: f64, delayed: f64) -> bool {
+    base.is_finite() && base > 0.0 && (delayed.is_nan() || delayed < MIN_RETAINED * base)
+}
+
+fn print_run(label: &str, run: &BacktestRun) {
+    let performance = &run.result.performance;
+    println!(
+        "  {:<8} {:>8.2}% {:>8.2} {:>8.2}% {:>7}",
+        label,
+        performance.cagr * 100.0,
+        performance.sharpe_ratio,
+        performance.max_drawdown_percent,
+        performance.total_trades
+    );
+}
+
+/// Backtests the best cached parameter set of `template_id` over the verification window, then
+/// replays its signals one session late. Fails when the objective collapses under the delay.
+pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -> Result<()> {
+    ensure_market_data_file(market_data_file).await?;
+    let (settings, parameters) =
+        best_cached_parameter_set(app, template_id, market_data_file).await?;
+    let objective =
+        EngineRuntimeSettings::from_settings_map(&settings)?.local_optimization_objective;
+    let mut context = verify_window_context(app, &settings, market_data_file).await?;
+    info!(
+        "Auditing template {} for lookahead by delaying its signals {} session(s)",
+        template_id, DELAY_SESSIONS
+    );
+    let (base, delayed) = context
+        .optimizer()
+        .backtest_with_delayed_signals(template_id, &parameters, DELAY_SESSIONS)
+        .await?;
+
+    println!(
+        "  {:<8} {:>9} {:>8} {:>9} {:>7}",
+        "run", "cagr", "sharpe", "drawdown", "trades"
+    );
+    print_run("base", &base);
+    print_run("delayed", &delayed);
+    let score = |run: &BacktestRun| {
+        objective_score(
+            objective,
+            run.result.performance.cagr,
+            run.result.performance.sharpe_ratio,
+        )
+    };
+    let (base_score, delayed_score) = (score(&base), score(&delayed));
+    if collapses_when_delayed(base_score, delayed_score) {
+        warn!(
+            "Template {} keeps {:.4} of its {} {:.4} when signals are delayed",
+            template_id,
+            delayed_score,
+            objective.label(),
+            base_score
+        );
+        return Err(anyhow!(
+            "Template {} likely leaks future data: its {} collapses from {:.4} to {:.4} with signals delayed by {} session(s)",
+            template_id,
+            objective.label(),
+            base_score,
+            delayed_score,
+            DELAY_SESSIONS
+        ));
+    }
+    info!(
+        "Template {} passed the lookahead audit: {} {:.4} with signals on time, {:.4} delayed",
+        template_id,
+        objective.label(),
+        base_score,
+        delayed_score
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_only_edges_that_collapse() {
+        assert!(!collapses_when_delayed(0.2, 0.15));
+        assert!(!collapses_when_delayed(0.2, 0.1));
+        assert!(collapses_when_delayed(0.2, 0.05));
+        assert!(collapses_when_delayed(0.2, f64::NAN));
+        assert!(!collapses_when_delayed(-0.1, -0.4));
+        assert!(!collapses_when_delayed(0.0, -0.1));
+    }
+}
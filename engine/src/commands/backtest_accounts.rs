@@ -3,7 +3,9 @@ use crate::context::AppContext;
 use anyhow::Result;
 use log::{info, warn};
 
-pub async fn run(app: &AppContext) -> Result<()> {
+/// Backtests the account-linked strategies, or with `account_id` only that account's, on one
+/// shared all-ticker data load.
+pub async fn run(app: &AppContext, account_id: Option<&str>) -> Result<()> {
     let mut context = app.engine_context_all_tickers().await.map_err(|error| {
         warn!(
             "Unable to initialize all ticker backtest context for account strategies: {}",
@@ -12,10 +14,22 @@ pub async fn run(app: &AppContext) -> Result<()> {
         error
     })?;
 
-    info!("Running backtests for account-linked strategies using all tickers");
+    let selection = match account_id {
+        Some(account_id) => {
+            info!(
+                "Running backtests for strategies of account {} using all tickers",
+                account_id
+            );
+            StrategySelection::Account(account_id)
+        }
+        None => {
+            info!("Running backtests for account-linked strategies using all tickers");
+            StrategySelection::AccountLinkedOnly
+        }
+    };
     context
         .backtester()
-        .run_with_selection(None, StrategySelection::AccountLinkedOnly)
+        .run_with_selection(None, selection)
         .await?;
     info!("Completed backtests for account-linked strategies");
 
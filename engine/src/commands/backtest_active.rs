@@ -1,22 +1,30 @@
 use crate::backtester::StrategySelection;
 use crate::candle_utils::{group_candles_for_tickers, normalize_ticker_symbol};
 use crate::config::EngineConfig;
-use crate::context::{AppContext, EngineContext};
+use crate::context::AppContext;
+use crate::data_context::TickerScope;
 use crate::database::Database;
 use crate::models::{
     BacktestDataPoint, BacktestResult, Candle, StrategyStateSnapshot, Trade, TradeStatus,
 };
 use crate::performance::PerformanceCalculator;
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use crate::strategy::create_strategy;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use clap::ValueEnum;
 use log::{info, warn};
+use serde::Deserialize;
 use serde_json::json;
 use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 const LIVE_TICKER_SCOPE: &str = "live";
+const CALENDAR_DAYS_PER_TRADING_DAY: f64 = 1.5;
+const HISTORY_BUFFER_DAYS: i64 = 30;
 
-#[derive(Clone, Copy, Debug, ValueEnum)]
+#[derive(Clone, Copy, Debug, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BacktestScope {
     Validation,
     Training,
@@ -24,48 +32,131 @@ pub enum BacktestScope {
 }
 
 impl BacktestScope {
-    fn label(self) -> &'static str {
+    fn ticker_scope(self) -> TickerScope {
         match self {
-            BacktestScope::Validation => "validation",
-            BacktestScope::Training => "training",
-            BacktestScope::All => "all",
+            BacktestScope::Validation => TickerScope::ValidationOnly,
+            BacktestScope::Training => TickerScope::TrainingOnly,
+            BacktestScope::All => TickerScope::AllTickers,
         }
     }
+}
 
-    async fn build_context(self, app: &AppContext) -> Result<EngineContext> {
-        match self {
-            BacktestScope::Validation => app.engine_context_validation_tickers().await,
-            BacktestScope::Training => app.engine_context_training_tickers().await,
-            BacktestScope::All => app.engine_context_all_tickers().await,
+/// Tickers named on the command line or in files. Listing tickers replaces the scope's universe;
+/// exclusions remove tickers from whichever universe is used. Either way the results are stored
+/// under the `custom` scope so they never replace the database-universe backtests.
+#[derive(Clone, Debug, Default)]
+pub struct TickerSelection {
+    pub tickers: Vec<String>,
+    pub tickers_file: Option<PathBuf>,
+    pub exclude_file: Option<PathBuf>,
+}
+
+impl TickerSelection {
+    fn is_empty(&self) -> bool {
+        self.tickers.is_empty() && self.tickers_file.is_none() && self.exclude_file.is_none()
+    }
+
+    /// Tickers to keep (`None` keeps the whole scope) and tickers to drop.
+    fn resolve(&self) -> Result<(Option<HashSet<String>>, HashSet<String>)> {
+        let mut included: HashSet<String> = self
+            .tickers
+            .iter()
+            .filter_map(|ticker| normalize_ticker_symbol(ticker))
+            .collect();
+        if let Some(path) = &self.tickers_file {
+            included.extend(read_ticker_file(path)?);
         }
+        let excluded = match &self.exclude_file {
+            Some(path) => read_ticker_file(path)?,
+            None => HashSet::new(),
+        };
+        let listed = !self.tickers.is_empty() || self.tickers_file.is_some();
+        if listed && included.is_empty() {
+            return Err(anyhow!("The ticker selection does not name any ticker"));
+        }
+        Ok((listed.then_some(included), excluded))
     }
 }
 
-pub async fn run(app: &AppContext, scope: BacktestScope, months: &[u32]) -> Result<()> {
-    let mut context = scope.build_context(app).await.map_err(|error| {
-        warn!(
-            "Unable to initialize {} ticker backtest context: {}",
-            scope.label(),
+/// Tickers separated by commas, whitespace or newlines; `#` starts a comment.
+fn read_ticker_file(path: &Path) -> Result<HashSet<String>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read ticker file {}", path.display()))?;
+    Ok(parse_ticker_list(&text))
+}
+
+fn parse_ticker_list(text: &str) -> HashSet<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter_map(normalize_ticker_symbol)
+        .collect()
+}
+
+pub async fn run(
+    app: &AppContext,
+    scope: BacktestScope,
+    months: &[u32],
+    selection: &TickerSelection,
+) -> Result<()> {
+    let (included, excluded) = selection.resolve()?;
+    let ticker_scope = if included.is_some() {
+        TickerScope::Custom
+    } else {
+        scope.ticker_scope()
+    };
+    let candles_since = resolve_history_start(app, months).await?;
+    let mut context = app
+        .engine_context_since(ticker_scope, candles_since)
+        .await
+        .map_err(|error| {
+            warn!(
+                "Unable to initialize {} ticker backtest context: {}",
+                ticker_scope.result_label(),
+                error
+            );
             error
-        );
-        error
-    })?;
+        })?;
+    if !selection.is_empty() {
+        if let Some(included) = &included {
+            let available: HashSet<&str> = context.tickers().iter().map(String::as_str).collect();
+            let mut missing: Vec<&str> = included
+                .iter()
+                .map(String::as_str)
+                .filter(|ticker| !available.contains(ticker))
+                .collect();
+            if !missing.is_empty() {
+                missing.sort_unstable();
+                warn!(
+                    "Selected tickers without market data: {}",
+                    missing.join(", ")
+                );
+            }
+        }
+        context = context.restrict_to_custom_tickers(|ticker| {
+            included
+                .as_ref()
+                .is_none_or(|included| included.contains(ticker))
+                && !excluded.contains(ticker)
+        })?;
+    }
+    let label = if selection.is_empty() {
+        ticker_scope.result_label()
+    } else {
+        TickerScope::Custom.result_label()
+    };
 
     for month in months {
         info!(
             "Running {} backtest for active strategies (window: {}m)",
-            scope.label(),
-            month,
+            label, month,
         );
         context
             .backtester()
             .run_with_selection(Some(*month), StrategySelection::WithoutAccounts)
             .await?;
     }
-    info!(
-        "Completed {} backtesting run for active strategies",
-        scope.label()
-    );
+    info!("Completed {} backtesting run for active strategies", label);
 
     if let Err(err) = refresh_live_backtests(app).await {
         warn!("Failed to refresh live backtests: {}", err);
@@ -74,6 +165,47 @@ pub async fn run(app: &AppContext, scope: BacktestScope, months: &[u32]) -> Resu
     Ok(())
 }
 
+/// Earliest candle date any active strategy needs for the longest requested window
+/// (window + strategy min_data_points + buffer), or `None` to load the full history.
+async fn resolve_history_start(app: &AppContext, months: &[u32]) -> Result<Option<NaiveDate>> {
+    let Some(max_months) = months.iter().copied().max() else {
+        return Ok(None);
+    };
+    let db = app.database().await?;
+    let Some(latest) = db.get_latest_candle_date().await? else {
+        return Ok(None);
+    };
+    let strategies = db.get_active_strategies().await?;
+    let window_hours = ((max_months as f64) * 30.4 * 24.0).ceil() as i64;
+
+    let mut earliest: Option<DateTime<Utc>> = None;
+    for strategy in strategies {
+        let min_data_points =
+            match create_strategy(&strategy.template_id, strategy.parameters.clone()) {
+                Ok(instance) => instance.get_min_data_points(),
+                Err(error) => {
+                    warn!(
+                        "Loading full history because strategy {} could not be created: {}",
+                        strategy.name, error
+                    );
+                    return Ok(None);
+                }
+            };
+        let history = Duration::days(
+            (min_data_points as f64 * CALENDAR_DAYS_PER_TRADING_DAY).ceil() as i64
+                + HISTORY_BUFFER_DAYS,
+        );
+        let window_start = match strategy.backtest_start_date {
+            Some(custom_start) => custom_start.min(latest - Duration::hours(window_hours)),
+            None => latest - Duration::hours(window_hours),
+        };
+        let start = window_start - history;
+        earliest = Some(earliest.map_or(start, |current| current.min(start)));
+    }
+
+    Ok(earliest.map(|date| date.date_naive()))
+}
+
 async fn refresh_live_backtests(app: &AppContext) -> Result<()> {
     let mut db = app.database().await?;
     let candidates = db.get_live_trades_with_accounts().await?;
@@ -155,6 +287,12 @@ pub async fn update_live_backtests(
             &snapshots,
         );
 
+        let baseline_return = PerformanceCalculator::calculate_equal_weight_buy_and_hold_return(
+            &candles_by_ticker,
+            actual_start_date,
+            end_date,
+        );
+
         let (active_count, closed_count) = count_trade_statuses(&evaluated);
         let strategy_state = StrategyStateSnapshot {
             template_id: "live_trades".to_string(),
@@ -177,12 +315,14 @@ pub async fn update_live_backtests(
             daily_snapshots: snapshots,
             trades: Vec::new(),
             tickers,
+            insufficient_history_tickers: Vec::new(),
             ticker_scope: Some(LIVE_TICKER_SCOPE.to_string()),
             strategy_state: Some(strategy_state),
+            baseline_return,
             created_at: now,
         };
 
-        db.replace_strategy_backtest_data(strategy_id, &result, None, LIVE_TICKER_SCOPE)
+        db.replace_strategy_backtest_data(strategy_id, &result, None, LIVE_TICKER_SCOPE, None)
             .await?;
         db.link_live_trades_to_backtest(strategy_id, &result.id)
             .await?;
@@ -445,3 +585,31 @@ fn normalize_trade_date(date: DateTime<Utc>) -> DateTime<Utc> {
         .expect("midnight should always be valid")
         .and_utc()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ticker_list, TickerSelection};
+
+    #[test]
+    fn ticker_lists_accept_commas_whitespace_and_comments() {
+        let parsed = parse_ticker_list("aapl, msft\n# held elsewhere\nNVDA  amd # semis\n,,\n");
+        let mut parsed: Vec<String> = parsed.into_iter().collect();
+        parsed.sort();
+        assert_eq!(parsed, ["AAPL", "AMD", "MSFT", "NVDA"]);
+
+        let selection = TickerSelection {
+            tickers: vec!["spy".into(), " ".into()],
+            ..TickerSelection::default()
+        };
+        let (included, excluded) = selection.resolve().unwrap();
+        assert_eq!(included.unwrap().into_iter().collect::<Vec<_>>(), ["SPY"]);
+        assert!(excluded.is_empty());
+
+        let blank = TickerSelection {
+            tickers: vec![" ".into()],
+            ..TickerSelection::default()
+        };
+        assert!(blank.resolve().is_err());
+        assert!(TickerSelection::default().resolve().unwrap().0.is_none());
+    }
+}
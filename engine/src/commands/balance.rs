@@ -48,7 +48,7 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
         return Ok(());
     }
 
-    let settings = db.get_all_settings().await?;
+    let settings = app.database_settings(&db).await?;
     let balance_start = require_setting_date(&settings, "BALANCE_WINDOW_START_DATE")?;
     let balance_end = require_setting_date(&settings, "BALANCE_WINDOW_END_DATE")?;
     let start_label = balance_start.format("%Y-%m-%d").to_string();
@@ -172,6 +172,7 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_balance_scope(
     app: &AppContext,
     db: &Database,
@@ -217,9 +218,9 @@ async fn run_balance_scope(
         end_label,
         scope_label
     );
-    let cpu_count = num_cpus::get();
+    let cpu_count = rayon::current_num_threads();
     let batch_size = std::cmp::max(1, cpu_count.saturating_mul(10));
-    let total_batches = (parameter_sets.len() + batch_size - 1) / batch_size;
+    let total_batches = parameter_sets.len().div_ceil(batch_size);
     if total_batches > 1 {
         info!(
             "Processing balance {} in {} batch(es) of up to {} parameter set(s) ({} cores * 10)",
@@ -0,0 +1,64 @@
+use crate::benchmark::{run_strategy, SyntheticMarket, BENCHMARK_STRATEGIES, DEFAULT_SEED};
+use anyhow::{anyhow, Result};
+use log::info;
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub tickers: usize,
+    pub years: usize,
+    /// Number of strategies from `BENCHMARK_STRATEGIES` to run, in order.
+    pub strategies: usize,
+}
+
+/// Backtests the first `strategies` benchmark strategies on a synthetic market of `tickers` ×
+/// `years` daily candles and prints each run's throughput in candles per second.
+pub fn run(options: &BenchOptions) -> Result<()> {
+    if options.tickers == 0 || options.years == 0 {
+        return Err(anyhow!("--tickers and --years must be at least 1"));
+    }
+    if options.strategies == 0 || options.strategies > BENCHMARK_STRATEGIES.len() {
+        return Err(anyhow!(
+            "--strategies must be between 1 and {}",
+            BENCHMARK_STRATEGIES.len()
+        ));
+    }
+
+    let market = SyntheticMarket::generate(options.tickers, options.years, DEFAULT_SEED);
+    info!(
+        "Benchmarking {} strategies on {} tickers x {} years ({} candles)",
+        options.strategies,
+        options.tickers,
+        options.years,
+        market.candles.len()
+    );
+
+    println!(
+        "{:<20} {:>10} {:>8} {:>12} {:>16}",
+        "strategy", "candles", "trades", "seconds", "candles/sec"
+    );
+    let mut total_candles = 0;
+    let mut total_seconds = 0.0;
+    for strategy_id in &BENCHMARK_STRATEGIES[..options.strategies] {
+        let result = run_strategy(&market, strategy_id)?;
+        let seconds = result.elapsed.as_secs_f64();
+        println!(
+            "{:<20} {:>10} {:>8} {:>12.3} {:>16.0}",
+            result.strategy_id,
+            result.candles,
+            result.trades,
+            seconds,
+            result.candles_per_second()
+        );
+        total_candles += result.candles;
+        total_seconds += seconds;
+    }
+    println!(
+        "{:<20} {:>10} {:>8} {:>12.3} {:>16.0}",
+        "total",
+        total_candles,
+        "",
+        total_seconds,
+        total_candles as f64 / total_seconds.max(f64::EPSILON)
+    );
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use crate::commands::train_lightgbm::{
+    build_training_datasets, LabelingOptions, EXTREME_LABEL_POSITIVE,
+};
+use crate::context::AppContext;
+use crate::strategy::calibration::{
+    brier_score, expected_calibration_error, fit_calibration, reliability_curve,
+};
+use crate::strategy::lightgbm::resolve_scoring_model;
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+
+fn print_curve(title: &str, probabilities: &[f64], labels: &[bool], bins: usize) {
+    let curve = reliability_curve(probabilities, labels, bins);
+    println!(
+        "{}: Brier {:.5}, ECE {:.4}",
+        title,
+        brier_score(probabilities, labels),
+        expected_calibration_error(&curve)
+    );
+    println!(
+        "  {:>11} {:>8} {:>10} {:>10} {:>8}",
+        "bin", "rows", "predicted", "observed", "gap"
+    );
+    for bin in &curve {
+        println!(
+            "  {:.2}-{:.2}   {:>8} {:>10.4} {:>10.4} {:>+8.4}",
+            bin.lower,
+            bin.upper,
+            bin.count,
+            bin.mean_predicted,
+            bin.observed_rate,
+            bin.observed_rate - bin.mean_predicted
+        );
+    }
+}
+
+/// Prints reliability curves of a model's raw and calibrated probabilities on the validation
+/// rows. Uncalibrated models get a calibration fitted on the spot to preview its effect.
+pub async fn run(app: &AppContext, model_id: Option<&str>, bins: usize) -> Result<()> {
+    if bins == 0 {
+        return Err(anyhow!("--bins must be at least 1"));
+    }
+    let model = resolve_scoring_model(model_id).ok_or_else(|| match model_id {
+        Some(id) => anyhow!("Model {} is not registered", id),
+        None => anyhow!("No default LightGBM model is registered"),
+    })?;
+    let db = app.database().await?;
+    let datasets =
+        build_training_datasets(app, &db, model.features(), &LabelingOptions::default()).await?;
+
+    let mut probabilities = Vec::with_capacity(datasets.validation_rows.len());
+    let mut labels = Vec::with_capacity(datasets.validation_rows.len());
+    for row in &datasets.validation_rows {
+        if let Some(probability) = model.raw_probability(&row.features) {
+            probabilities.push(probability);
+            labels.push(row.label == EXTREME_LABEL_POSITIVE);
+        }
+    }
+    if probabilities.is_empty() {
+        return Err(anyhow!("The model scored none of the validation rows"));
+    }
+    info!(
+        "Scored {} of {} validation rows",
+        probabilities.len(),
+        datasets.validation_rows.len()
+    );
+
+    print_curve("Raw probabilities", &probabilities, &labels, bins);
+    let (calibration, stored) = match model.calibration() {
+        Some(calibration) => (Some(calibration.clone()), true),
+        None => (fit_calibration(&probabilities, &labels), false),
+    };
+    let Some(calibration) = calibration else {
+        warn!("The model has no calibration and too few validation rows to fit one");
+        return Ok(());
+    };
+    let calibrated: Vec<f64> = probabilities
+        .iter()
+        .map(|probability| calibration.apply(*probability))
+        .collect();
+    let title = if stored {
+        format!("Calibrated probabilities (stored {})", calibration.method())
+    } else {
+        format!(
+            "Calibrated probabilities (not stored; {} fitted on these rows, retrain to store one)",
+            calibration.method()
+        )
+    };
+    print_curve(&title, &calibrated, &labels, bins);
+    Ok(())
+}
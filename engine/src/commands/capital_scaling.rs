@@ -0,0 +1,227 @@
+use crate::commands::market_data_snapshot::ensure_market_data_file;
+use crate::commands::verify::{best_cached_parameter_set, verify_window_context};
+use crate::context::AppContext;
+use crate::models::{AccountSignalSkip, BacktestRun, GeneratedSignal, SignalAction};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::info;
+use std::path::Path;
+
+/// Initial capitals backtested when `--capitals` is not given.
+pub const DEFAULT_CAPITAL_LADDER: &[f64] = &[
+    1_000.0,
+    2_500.0,
+    5_000.0,
+    10_000.0,
+    25_000.0,
+    50_000.0,
+    100_000.0,
+    250_000.0,
+    500_000.0,
+    1_000_000.0,
+];
+/// CAGR gap to the largest capital, in absolute terms, below which a smaller capital still
+/// trades the strategy as intended.
+const CAGR_TOLERANCE: f64 = 0.01;
+
+/// Backtest of one parameter set at one initial capital. Skip rates are shares of the buy
+/// signals between the backtest's start and end dates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapitalScalingPoint {
+    pub initial_capital: f64,
+    pub cagr: f64,
+    pub trades: i32,
+    pub buy_signals: usize,
+    pub insufficient_size_rate: f64,
+    pub insufficient_cash_rate: f64,
+}
+
+impl CapitalScalingPoint {
+    pub fn from_run(initial_capital: f64, run: &BacktestRun) -> Self {
+        let (buy_signals, insufficient_size_rate, insufficient_cash_rate) = buy_skip_rates(
+            &run.signals,
+            &run.signal_skips,
+            run.result.start_date,
+            run.result.end_date,
+        );
+        Self {
+            initial_capital,
+            cagr: run.result.performance.cagr,
+            trades: run.result.performance.total_trades,
+            buy_signals,
+            insufficient_size_rate,
+            insufficient_cash_rate,
+        }
+    }
+}
+
+/// Buy signals dated `start..=end` and the shares of them skipped as `insufficient_size` and
+/// `insufficient_cash`.
+fn buy_skip_rates(
+    signals: &[GeneratedSignal],
+    skips: &[AccountSignalSkip],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> (usize, f64, f64) {
+    let buys = signals
+        .iter()
+        .filter(|signal| {
+            matches!(signal.action, SignalAction::Buy) && signal.date >= start && signal.date <= end
+        })
+        .count();
+    let rate = |reason: &str| {
+        let skipped = skips
+            .iter()
+            .filter(|skip| matches!(skip.action, SignalAction::Buy) && skip.reason == reason)
+            .count();
+        if buys > 0 {
+            skipped as f64 / buys as f64
+        } else {
+            0.0
+        }
+    };
+    (buys, rate("insufficient_size"), rate("insufficient_cash"))
+}
+
+/// Smallest capital from which every larger one stays within CAGR_TOLERANCE of the largest
+/// capital's CAGR. `points` are sorted by capital.
+pub fn minimum_realistic_capital(points: &[CapitalScalingPoint]) -> Option<f64> {
+    let target = points.last()?.cagr - CAGR_TOLERANCE;
+    let first_unrealistic = points.iter().rposition(|point| point.cagr < target);
+    points
+        .get(first_unrealistic.map_or(0, |index| index + 1))
+        .map(|point| point.initial_capital)
+}
+
+/// Replays the best cached parameter set of `template_id` over the verification window at each
+/// of `capitals` (default: DEFAULT_CAPITAL_LADDER) and prints how CAGR and the
+/// insufficient size and cash skip rates change with the account size.
+pub async fn run(
+    app: &AppContext,
+    template_id: &str,
+    market_data_file: &Path,
+    capitals: &[f64],
+) -> Result<()> {
+    let mut capitals = if capitals.is_empty() {
+        DEFAULT_CAPITAL_LADDER.to_vec()
+    } else {
+        capitals.to_vec()
+    };
+    if capitals
+        .iter()
+        .any(|capital| !capital.is_finite() || *capital <= 0.0)
+    {
+        return Err(anyhow!("--capitals must all be positive"));
+    }
+    capitals.sort_by(f64::total_cmp);
+    capitals.dedup();
+
+    ensure_market_data_file(market_data_file).await?;
+    let (settings, parameters) =
+        best_cached_parameter_set(app, template_id, market_data_file).await?;
+    let mut context = verify_window_context(app, &settings, market_data_file).await?;
+    info!(
+        "Backtesting the best cached parameter set of template {} at {} initial capitals",
+        template_id,
+        capitals.len()
+    );
+    let runs = context
+        .optimizer()
+        .backtest_capital_ladder(template_id, &parameters, &capitals)
+        .await?;
+    let points: Vec<CapitalScalingPoint> = capitals
+        .iter()
+        .zip(&runs)
+        .map(|(capital, run)| CapitalScalingPoint::from_run(*capital, run))
+        .collect();
+
+    println!(
+        "  {:>12} {:>9} {:>7} {:>6} {:>10} {:>10}",
+        "capital", "cagr", "trades", "buys", "size skip", "cash skip"
+    );
+    for point in &points {
+        println!(
+            "  {:>12.0} {:>8.2}% {:>7} {:>6} {:>9.1}% {:>9.1}%",
+            point.initial_capital,
+            point.cagr * 100.0,
+            point.trades,
+            point.buy_signals,
+            point.insufficient_size_rate * 100.0,
+            point.insufficient_cash_rate * 100.0
+        );
+    }
+    match minimum_realistic_capital(&points) {
+        Some(capital) => println!(
+            "Minimum realistic capital for {}: {:.0} (CAGR within {:.0} points of the largest capital)",
+            template_id,
+            capital,
+            CAGR_TOLERANCE * 100.0
+        ),
+        None => println!("No capital was backtested for {}", template_id),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn point(initial_capital: f64, cagr: f64) -> CapitalScalingPoint {
+        CapitalScalingPoint {
+            initial_capital,
+            cagr,
+            trades: 0,
+            buy_signals: 0,
+            insufficient_size_rate: 0.0,
+            insufficient_cash_rate: 0.0,
+        }
+    }
+
+    fn buy(day: u32) -> GeneratedSignal {
+        GeneratedSignal {
+            date: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+            ticker: "AAA".to_string(),
+            action: SignalAction::Buy,
+            confidence: None,
+        }
+    }
+
+    fn skip(day: u32, reason: &str) -> AccountSignalSkip {
+        AccountSignalSkip {
+            ticker: "AAA".to_string(),
+            signal_date: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+            action: SignalAction::Buy,
+            reason: reason.to_string(),
+            details: None,
+        }
+    }
+
+    #[test]
+    fn skip_rates_count_buys_inside_the_backtest_window() {
+        let signals = vec![buy(1), buy(4), buy(5), buy(6), buy(7)];
+        let skips = vec![
+            skip(4, "insufficient_size"),
+            skip(5, "insufficient_size"),
+            skip(6, "insufficient_cash"),
+        ];
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 3, 7, 0, 0, 0).unwrap();
+        assert_eq!(buy_skip_rates(&signals, &skips, start, end), (4, 0.5, 0.25));
+        assert_eq!(buy_skip_rates(&[], &skips, start, end), (0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn minimum_capital_is_where_cagr_converges_for_good() {
+        let points = vec![
+            point(1_000.0, -0.05),
+            point(5_000.0, 0.115),
+            point(10_000.0, 0.08),
+            point(50_000.0, 0.118),
+            point(100_000.0, 0.12),
+        ];
+        assert_eq!(minimum_realistic_capital(&points), Some(50_000.0));
+        assert_eq!(minimum_realistic_capital(&points[3..]), Some(50_000.0));
+        assert_eq!(minimum_realistic_capital(&[]), None);
+    }
+}
@@ -0,0 +1,458 @@
+use crate::candle_utils::group_all_candles_by_ticker;
+use crate::commands::market_data_snapshot::ensure_market_data_file;
+use crate::context::AppContext;
+use crate::data_context::MarketData;
+use crate::models::{Candle, TickerDistributionMap};
+use crate::optimizer_status::OptimizerStatus;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+/// Split ratios a jump is compared against to hint at an unadjusted split.
+const SPLIT_RATIOS: [f64; 7] = [2.0, 3.0, 4.0, 5.0, 8.0, 10.0, 20.0];
+const SPLIT_RATIO_TOLERANCE: f64 = 0.05;
+
+/// `data_file` reads a market data snapshot instead of the database; `quarantine` moves rows
+/// with impossible prices into `candle_quarantine` and needs the database.
+pub struct CheckDataOptions {
+    pub data_file: Option<PathBuf>,
+    pub output: PathBuf,
+    pub jump_threshold: f64,
+    pub stale_sessions: usize,
+    pub quarantine: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataIssueKind {
+    /// Sessions most listed tickers traded that this ticker has no candle for.
+    Gap,
+    /// A zero, negative or non-finite open, high, low or close.
+    NonPositivePrice,
+    /// A high below the low, or an open or close outside the high-low range.
+    InconsistentRange,
+    /// A close-to-close move beyond the jump threshold that no distribution explains.
+    PriceJump,
+    /// A ticker whose last candle is older than the latest sessions.
+    StaleTicker,
+}
+
+impl DataIssueKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            DataIssueKind::Gap => "gap",
+            DataIssueKind::NonPositivePrice => "non_positive_price",
+            DataIssueKind::InconsistentRange => "inconsistent_range",
+            DataIssueKind::PriceJump => "price_jump",
+            DataIssueKind::StaleTicker => "stale_ticker",
+        }
+    }
+
+    /// Rows with these issues cannot be traded on and are moved out by `--quarantine`.
+    pub fn is_bad_row(self) -> bool {
+        matches!(
+            self,
+            DataIssueKind::NonPositivePrice | DataIssueKind::InconsistentRange
+        )
+    }
+}
+
+/// One finding; `end_date` closes the range of a gap.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIssue {
+    pub ticker: String,
+    pub kind: DataIssueKind,
+    pub date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataQualityReport {
+    generated_at: DateTime<Utc>,
+    source: String,
+    tickers: usize,
+    candles: usize,
+    sessions: usize,
+    jump_threshold: f64,
+    stale_sessions: usize,
+    issue_counts: BTreeMap<DataIssueKind, usize>,
+    quarantined: usize,
+    issues: Vec<DataIssue>,
+}
+
+/// Sessions in which at least half of the tickers listed at the time (between their first
+/// and last candle) have a candle, so a stray candle of one ticker does not open a gap in
+/// every other.
+pub fn trading_calendar(candles: &[Candle]) -> Vec<NaiveDate> {
+    let mut counts: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut listings: HashMap<&str, (NaiveDate, NaiveDate)> = HashMap::new();
+    for candle in candles {
+        let date = candle.date.date_naive();
+        *counts.entry(date).or_default() += 1;
+        listings
+            .entry(candle.ticker.as_str())
+            .and_modify(|(first, last)| {
+                *first = (*first).min(date);
+                *last = (*last).max(date);
+            })
+            .or_insert((date, date));
+    }
+    let dates: Vec<NaiveDate> = counts.keys().copied().collect();
+    let mut listed_delta = vec![0i64; dates.len() + 1];
+    for (first, last) in listings.values() {
+        let start = dates.partition_point(|date| date < first);
+        let end = dates.partition_point(|date| date <= last);
+        listed_delta[start] += 1;
+        listed_delta[end] -= 1;
+    }
+    let mut listed = 0i64;
+    dates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, date)| {
+            listed += listed_delta[index];
+            (counts[date] as i64 * 2 >= listed).then_some(*date)
+        })
+        .collect()
+}
+
+fn split_ratio_hint(ratio: f64) -> Option<String> {
+    SPLIT_RATIOS.iter().find_map(|split| {
+        if (ratio / split - 1.0).abs() <= SPLIT_RATIO_TOLERANCE {
+            Some(format!("1:{} reverse split", split))
+        } else if (ratio * split - 1.0).abs() <= SPLIT_RATIO_TOLERANCE {
+            Some(format!("{}:1 split", split))
+        } else {
+            None
+        }
+    })
+}
+
+/// Every issue found in `candles`, ordered by ticker and date. A jump is explained when adding
+/// back a distribution going ex on that day brings it under `jump_threshold`.
+pub fn check_candles(
+    candles: &[Candle],
+    distributions: &TickerDistributionMap,
+    jump_threshold: f64,
+    stale_sessions: usize,
+) -> Vec<DataIssue> {
+    let calendar = trading_calendar(candles);
+    let latest_session = calendar.last().copied();
+    let mut issues = Vec::new();
+    let mut by_ticker: Vec<_> = group_all_candles_by_ticker(candles).into_iter().collect();
+    by_ticker.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (ticker, mut ticker_candles) in by_ticker {
+        ticker_candles.sort_by_key(|candle| candle.date);
+        let mut issue =
+            |kind: DataIssueKind, date: NaiveDate, end_date: Option<NaiveDate>, detail: String| {
+                issues.push(DataIssue {
+                    ticker: ticker.clone(),
+                    kind,
+                    date,
+                    end_date,
+                    detail,
+                })
+            };
+
+        let mut previous_close: Option<f64> = None;
+        for candle in &ticker_candles {
+            let date = candle.date.date_naive();
+            let prices = [candle.open, candle.high, candle.low, candle.close];
+            if prices
+                .iter()
+                .any(|price| !price.is_finite() || *price <= 0.0)
+            {
+                issue(
+                    DataIssueKind::NonPositivePrice,
+                    date,
+                    None,
+                    format!(
+                        "open {} high {} low {} close {}",
+                        candle.open, candle.high, candle.low, candle.close
+                    ),
+                );
+                continue;
+            }
+            if candle.high < candle.low
+                || [candle.open, candle.close]
+                    .iter()
+                    .any(|price| *price > candle.high || *price < candle.low)
+            {
+                issue(
+                    DataIssueKind::InconsistentRange,
+                    date,
+                    None,
+                    format!(
+                        "open {} high {} low {} close {}",
+                        candle.open, candle.high, candle.low, candle.close
+                    ),
+                );
+                continue;
+            }
+            if let Some(previous) = previous_close {
+                let distribution: f64 = distributions
+                    .get(&ticker)
+                    .map(|entries| {
+                        entries
+                            .iter()
+                            .filter(|entry| entry.ex_date.date_naive() == date)
+                            .map(|entry| entry.amount)
+                            .sum()
+                    })
+                    .unwrap_or(0.0);
+                let ratio = candle.close / previous;
+                let explained_ratio = (candle.close + distribution) / previous;
+                if (ratio - 1.0).abs() > jump_threshold
+                    && (explained_ratio - 1.0).abs() > jump_threshold
+                {
+                    let hint = split_ratio_hint(ratio)
+                        .map(|hint| format!(", near a {}", hint))
+                        .unwrap_or_default();
+                    issue(
+                        DataIssueKind::PriceJump,
+                        date,
+                        None,
+                        format!(
+                            "close {} after {} ({:+.1}%){}",
+                            candle.close,
+                            previous,
+                            (ratio - 1.0) * 100.0,
+                            hint
+                        ),
+                    );
+                }
+            }
+            previous_close = Some(candle.close);
+        }
+
+        let (Some(first), Some(last)) = (ticker_candles.first(), ticker_candles.last()) else {
+            continue;
+        };
+        let first = first.date.date_naive();
+        let last = last.date.date_naive();
+        let mut present = ticker_candles
+            .iter()
+            .map(|candle| candle.date.date_naive())
+            .peekable();
+        let mut run: Option<(NaiveDate, NaiveDate, usize)> = None;
+        let mut close_run = |run: &mut Option<(NaiveDate, NaiveDate, usize)>| {
+            if let Some((start, end, sessions)) = run.take() {
+                issue(
+                    DataIssueKind::Gap,
+                    start,
+                    Some(end),
+                    format!(
+                        "{} missing session{}",
+                        sessions,
+                        if sessions == 1 { "" } else { "s" }
+                    ),
+                );
+            }
+        };
+        for session in calendar
+            .iter()
+            .copied()
+            .filter(|session| *session >= first && *session <= last)
+        {
+            while present.next_if(|date| *date < session).is_some() {}
+            if present.next_if_eq(&session).is_some() {
+                close_run(&mut run);
+                continue;
+            }
+            run = Some(match run {
+                Some((start, _, sessions)) => (start, session, sessions + 1),
+                None => (session, session, 1),
+            });
+        }
+        close_run(&mut run);
+
+        if let Some(latest) = latest_session {
+            let behind = calendar.len() - calendar.partition_point(|session| *session <= last);
+            if behind > stale_sessions {
+                issue(
+                    DataIssueKind::StaleTicker,
+                    last,
+                    None,
+                    format!("last candle {} sessions before {}", behind, latest),
+                );
+            }
+        }
+    }
+    issues
+}
+
+pub async fn run(app: &AppContext, options: CheckDataOptions) -> Result<()> {
+    if !options.jump_threshold.is_finite() || options.jump_threshold <= 0.0 {
+        return Err(anyhow!("--jump-threshold must be a positive fraction"));
+    }
+    if options.quarantine && options.data_file.is_some() {
+        return Err(anyhow!(
+            "--quarantine moves rows out of the candles table and cannot be used with --data-file"
+        ));
+    }
+
+    let (source, candles, distributions) = match &options.data_file {
+        Some(path) => {
+            ensure_market_data_file(path).await?;
+            let status = OptimizerStatus::new();
+            let market_data = MarketData::load_from_file(path, &status)?;
+            (
+                path.display().to_string(),
+                market_data.all_candles().to_vec(),
+                market_data.auxiliary_data().ticker_distributions.clone(),
+            )
+        }
+        None => {
+            let db = app.database().await?;
+            info!("Loading candle data from database...");
+            (
+                "database".to_string(),
+                db.get_all_candles(None).await?,
+                db.get_etf_distributions().await?,
+            )
+        }
+    };
+    if candles.is_empty() {
+        return Err(anyhow!("No candle data to check"));
+    }
+
+    let issues = check_candles(
+        &candles,
+        &distributions,
+        options.jump_threshold,
+        options.stale_sessions,
+    );
+    let mut issue_counts: BTreeMap<DataIssueKind, usize> = BTreeMap::new();
+    for issue in &issues {
+        *issue_counts.entry(issue.kind).or_default() += 1;
+    }
+
+    let mut quarantined = 0;
+    if options.quarantine {
+        let bad_rows: Vec<(String, NaiveDate, &str)> = issues
+            .iter()
+            .filter(|issue| issue.kind.is_bad_row())
+            .map(|issue| (issue.ticker.clone(), issue.date, issue.kind.label()))
+            .collect();
+        if !bad_rows.is_empty() {
+            let db = app.database().await?;
+            quarantined = db.quarantine_candles(&bad_rows).await?;
+        }
+    }
+
+    let tickers = group_all_candles_by_ticker(&candles).len();
+    let report = DataQualityReport {
+        generated_at: Utc::now(),
+        source,
+        tickers,
+        candles: candles.len(),
+        sessions: trading_calendar(&candles).len(),
+        jump_threshold: options.jump_threshold,
+        stale_sessions: options.stale_sessions,
+        issue_counts,
+        quarantined,
+        issues,
+    };
+    std::fs::write(&options.output, serde_json::to_string_pretty(&report)?)
+        .with_context(|| format!("failed to write {}", options.output.display()))?;
+
+    info!(
+        "Checked {} candles of {} tickers across {} sessions from {}",
+        report.candles, report.tickers, report.sessions, report.source
+    );
+    for (kind, count) in &report.issue_counts {
+        info!("  {:<18} {}", kind.label(), count);
+    }
+    if quarantined > 0 {
+        warn!(
+            "Moved {} candle rows with impossible prices to candle_quarantine",
+            quarantined
+        );
+    }
+    info!(
+        "Wrote {} issues to {}",
+        report.issues.len(),
+        options.output.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_candles, DataIssueKind};
+    use crate::models::{Candle, TickerDistribution, TickerDistributionMap};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn candle(ticker: &str, day: i64, close: f64) -> Candle {
+        Candle {
+            ticker: ticker.to_string(),
+            date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day),
+            open: close,
+            high: close * 1.01,
+            low: close * 0.99,
+            close,
+            unadjusted_close: None,
+            volume_shares: 1_000,
+        }
+    }
+
+    #[test]
+    fn flags_gaps_bad_rows_jumps_and_stale_tickers() {
+        let mut candles = Vec::new();
+        for day in 0..10 {
+            candles.push(candle("AAA", day, 100.0));
+            candles.push(candle("CCC", day, 50.0));
+            if day != 3 && day != 4 && day < 8 {
+                let close = if day >= 6 { 10.0 } else { 50.0 };
+                candles.push(candle("BBB", day, close));
+            }
+        }
+        // Only DDD trades on day 20, so it does not become a session for the others.
+        candles.push(candle("DDD", 20, 10.0));
+        candles[0].close = 0.0;
+        candles[3].high = 90.0;
+        let distributions: TickerDistributionMap = [(
+            "CCC".to_string(),
+            vec![TickerDistribution {
+                ex_date: candles[1].date + Duration::days(5),
+                amount: 30.0,
+            }],
+        )]
+        .into_iter()
+        .collect();
+        for candle in candles.iter_mut().filter(|c| c.ticker == "CCC") {
+            if candle.date >= distributions["CCC"][0].ex_date {
+                candle.close = 20.0;
+                candle.open = 20.0;
+                candle.high = 20.2;
+                candle.low = 19.8;
+            }
+        }
+
+        let issues = check_candles(&candles, &distributions, 0.5, 1);
+        let kinds: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.ticker.as_str(), issue.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("AAA", DataIssueKind::NonPositivePrice),
+                ("AAA", DataIssueKind::InconsistentRange),
+                ("BBB", DataIssueKind::PriceJump),
+                ("BBB", DataIssueKind::Gap),
+                ("BBB", DataIssueKind::StaleTicker),
+            ]
+        );
+        assert!(issues[2].detail.contains("5:1 split"));
+        assert_eq!(issues[3].detail, "2 missing sessions");
+        assert_eq!(issues[4].detail, "last candle 3 sessions before 2024-01-21");
+    }
+}
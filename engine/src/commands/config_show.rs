@@ -0,0 +1,67 @@
+use crate::context::AppContext;
+use crate::data_context::MarketData;
+use crate::optimizer_status::OptimizerStatus;
+use crate::storage::Storage;
+use anyhow::Result;
+use log::{info, warn};
+use std::path::Path;
+
+const REDACTED_KEY_MARKERS: &[&str] = &["SECRET", "PASSWORD", "TOKEN", "API_KEY"];
+
+/// Prints every effective runtime setting with the layer it came from. The database layer is
+/// used when reachable; otherwise the local store or the market data snapshot sits beneath
+/// `engine.toml` and the environment.
+pub async fn run(app: &AppContext, market_data_file: &Path) -> Result<()> {
+    let mut database = None;
+    let mut snapshot = None;
+    if app.has_database() {
+        match app.database().await {
+            Ok(db) => database = Some(db.get_all_settings().await?),
+            Err(error) => warn!("Database unavailable ({}). Showing offline layers.", error),
+        }
+    }
+    if database.is_none() {
+        if let Some(store) = app.local_store()? {
+            info!("Using settings from {}", store.path().display());
+            snapshot = Some(store.get_all_settings().await?);
+        } else if market_data_file.exists() {
+            info!(
+                "Using settings from market data snapshot {}",
+                market_data_file.display()
+            );
+            let status = OptimizerStatus::new();
+            let market_data = MarketData::load_from_file(market_data_file, &status)?;
+            snapshot = Some(market_data.settings().clone());
+        }
+    }
+
+    let resolved = app
+        .setting_layers()
+        .resolve(snapshot.as_ref(), database.as_ref());
+    if resolved.is_empty() {
+        println!("No engine settings found.");
+        return Ok(());
+    }
+    let width = resolved.keys().map(String::len).max().unwrap_or(0);
+    for (key, (value, source)) in &resolved {
+        println!(
+            "{:<width$}  {:<11}  {}",
+            key,
+            source.label(),
+            display_value(key, value),
+            width = width
+        );
+    }
+    Ok(())
+}
+
+fn display_value<'a>(key: &str, value: &'a str) -> &'a str {
+    if REDACTED_KEY_MARKERS
+        .iter()
+        .any(|marker| key.contains(marker))
+    {
+        "[redacted]"
+    } else {
+        value
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::NaiveDate;
+use log::{info, warn};
+use std::fs;
+use std::path::Path;
+
+/// Parses a YYYY-MM-DD date.
+pub(super) fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Maps the trimmed comma-separated fields of every non-blank line with `parse_row`. A first
+/// line whose `date_column` field is not a date is a header and skipped; any other line that
+/// `parse_row` rejects is an error naming the line and the `expected` row shape.
+pub(super) fn parse_rows<T>(
+    contents: &str,
+    date_column: usize,
+    expected: &str,
+    mut parse_row: impl FnMut(&[&str]) -> Option<T>,
+) -> Result<Vec<T>> {
+    let mut rows = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        match parse_row(&fields) {
+            Some(row) => rows.push(row),
+            None if index == 0
+                && parse_date(fields.get(date_column).copied().unwrap_or_default()).is_none() =>
+            {
+                continue
+            }
+            None => {
+                return Err(anyhow!(
+                    "Line {} is not a {}: {}",
+                    index + 1,
+                    expected,
+                    line
+                ))
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Reads the `what` file at `path` and parses it with `parse`, refusing a file without rows.
+pub(super) fn read_rows<T>(
+    path: &Path,
+    what: &str,
+    parse: impl FnOnce(&str) -> Result<Vec<T>>,
+) -> Result<Vec<T>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {} file {}", what, path.display()))?;
+    let rows = parse(&contents)?;
+    if rows.is_empty() {
+        return Err(anyhow!("{} has no {} rows", path.display(), what));
+    }
+    Ok(rows)
+}
+
+/// Logs how many of the `rows` read from `path` were stored as `stored_what`; the rest belong
+/// to tickers that are not in the tickers table.
+pub(super) fn report_stored(path: &Path, stored_what: &str, rows: usize, stored: usize) {
+    if stored < rows {
+        warn!(
+            "Skipped {} rows of tickers that are not in the tickers table",
+            rows - stored
+        );
+    }
+    info!("Stored {} {} from {}", stored, stored_what, path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_pairs(contents: &str) -> Result<Vec<(String, NaiveDate)>> {
+        parse_rows(
+            contents,
+            1,
+            "ticker,YYYY-MM-DD row",
+            |fields| match fields {
+                [ticker, date] if !ticker.is_empty() => {
+                    Some((ticker.to_string(), parse_date(date)?))
+                }
+                _ => None,
+            },
+        )
+    }
+
+    #[test]
+    fn skips_a_header_and_blank_lines_and_names_the_bad_line() {
+        let day = NaiveDate::from_ymd_opt(2025, 1, 30).unwrap();
+        assert_eq!(
+            parse_pairs("ticker,date\n AAPL , 2025-01-30\n\nMSFT,2025-01-30\n").unwrap(),
+            vec![("AAPL".to_string(), day), ("MSFT".to_string(), day)]
+        );
+        let error = parse_pairs("AAPL,2025-01-30\nMSFT,next week\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2 is not a ticker,YYYY-MM-DD row: MSFT,next week"
+        );
+        assert!(parse_pairs("AAPL,2025-01-30,extra\n").is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::broker::{AccountBroker, Broker};
+use crate::config::DriftToleranceSettings;
+use crate::context::AppContext;
+use crate::drift::detect_position_drift;
+use crate::models::Trade;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use log::{info, warn};
+use reqwest::Client;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration as StdDuration;
+
+const SYSTEM_LOG_SOURCE: &str = "drift-detector";
+const DRIFT_EVENT: &str = "position_drift";
+
+/// Snapshots every account's broker state for today and raises a reconciliation task (an
+/// `error` log) for each quantity or stop divergence from the active trades, once per UTC day.
+pub async fn run(app: &AppContext) -> Result<()> {
+    let db = app.database().await?;
+    let settings = app.database_settings(&db).await?;
+    let tolerance = DriftToleranceSettings::from_settings_map(&settings)?;
+    let now = Utc::now();
+    let today = now.date_naive();
+    let day_start = today
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight should always be valid")
+        .and_utc();
+
+    let http_client = Client::builder()
+        .timeout(StdDuration::from_secs(30))
+        .build()
+        .context("failed to construct HTTP client")?;
+
+    let mut trades_by_account: HashMap<String, Vec<Trade>> = HashMap::new();
+    for candidate in db.get_live_trades_with_accounts().await? {
+        trades_by_account
+            .entry(candidate.account_id)
+            .or_default()
+            .push(candidate.trade);
+    }
+
+    let mut snapshots = 0usize;
+    let mut raised = 0usize;
+    for account_id in db.get_account_ids().await? {
+        let Some(credentials) = db.get_account_credentials(&account_id).await? else {
+            continue;
+        };
+        if !AccountBroker::supports(&credentials.provider) {
+            continue;
+        }
+        let client = match AccountBroker::connect(&http_client, &db, &credentials, &settings) {
+            Ok(client) => client,
+            Err(err) => {
+                warn!(
+                    "Skipping account {}: {} broker init failed: {}",
+                    account_id, credentials.provider, err
+                );
+                continue;
+            }
+        };
+        let state = match client.fetch_account_state().await {
+            Ok(state) => state,
+            Err(err) => {
+                warn!(
+                    "Failed to fetch account state for account {}: {}",
+                    account_id, err
+                );
+                continue;
+            }
+        };
+        db.upsert_account_state_snapshot(&account_id, today, &state)
+            .await?;
+        snapshots += 1;
+
+        let trades = trades_by_account
+            .get(&account_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for drift in detect_position_drift(trades, &state, &tolerance) {
+            let message = format!("Reconcile account {}: {}", account_id, drift.describe());
+            if db
+                .has_account_event_log_since(
+                    SYSTEM_LOG_SOURCE,
+                    &account_id,
+                    DRIFT_EVENT,
+                    Some(&message),
+                    day_start,
+                )
+                .await?
+            {
+                continue;
+            }
+            warn!("{}", message);
+            db.insert_system_log(
+                SYSTEM_LOG_SOURCE,
+                "error",
+                &message,
+                Some(json!({
+                    "accountId": account_id,
+                    "event": DRIFT_EVENT,
+                    "drift": drift,
+                    "quantityTolerance": tolerance.quantity,
+                    "stopToleranceRatio": tolerance.stop_ratio,
+                })),
+            )
+            .await?;
+            raised += 1;
+        }
+    }
+
+    info!(
+        "Drift detection finished: {} account snapshot{} stored, {} reconciliation task{} raised",
+        snapshots,
+        if snapshots == 1 { "" } else { "s" },
+        raised,
+        if raised == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
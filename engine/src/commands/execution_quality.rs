@@ -0,0 +1,238 @@
+use crate::config::EngineRuntimeSettings;
+use crate::context::AppContext;
+use crate::models::{Trade, TradeStatus};
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use log::info;
+
+/// Realized against assumed costs of one kind of fill. Rates are shares of the planned price
+/// (or notional, for fees), averaged over the fills.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostComparison {
+    pub fills: usize,
+    pub live_rate: f64,
+    pub assumed_rate: f64,
+}
+
+impl CostComparison {
+    fn from_pairs(pairs: &[(f64, f64)]) -> Self {
+        if pairs.is_empty() {
+            return Self::default();
+        }
+        let count = pairs.len() as f64;
+        Self {
+            fills: pairs.len(),
+            live_rate: pairs.iter().map(|(live, _)| live).sum::<f64>() / count,
+            assumed_rate: pairs.iter().map(|(_, assumed)| assumed).sum::<f64>() / count,
+        }
+    }
+}
+
+/// Live execution of an account's trades next to the backtest's cost assumptions. Entry
+/// slippage compares fills with the planned entry price, stop slippage compares triggered
+/// stops with their stop price, and fees are recorded fees over the exit notional of closed
+/// trades.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecutionQuality {
+    pub entries: CostComparison,
+    pub stops: CostComparison,
+    pub fees: CostComparison,
+}
+
+impl ExecutionQuality {
+    /// Average adverse slippage over every entry and stop fill, the live counterpart of
+    /// TRADE_SLIPPAGE_RATE.
+    pub fn live_slippage_rate(&self) -> Option<f64> {
+        let fills = self.entries.fills + self.stops.fills;
+        (fills > 0).then(|| {
+            (self.entries.live_rate * self.entries.fills as f64
+                + self.stops.live_rate * self.stops.fills as f64)
+                / fills as f64
+        })
+    }
+}
+
+/// Compares the filled live trades with `runtime_settings`. Parts split off by partial exits
+/// carry no entry order, so only their exits count.
+pub fn execution_quality(
+    trades: &[Trade],
+    runtime_settings: &EngineRuntimeSettings,
+) -> ExecutionQuality {
+    let mut entries = Vec::new();
+    let mut stops = Vec::new();
+    let mut fees = (0.0, 0.0, 0usize);
+    for trade in trades {
+        let assumed = runtime_settings.slippage_rate_for(&trade.ticker);
+        if trade.entry_order_id.is_some() {
+            if let Some(rate) = trade.entry_slippage_rate() {
+                entries.push((rate, assumed));
+            }
+        }
+        if let Some(rate) = trade.stop_slippage_rate() {
+            stops.push((rate, assumed));
+        }
+        if let Some(exit_price) = trade
+            .exit_price
+            .filter(|price| trade.status == TradeStatus::Closed && *price > 0.0)
+        {
+            fees.0 += trade.fee.unwrap_or(0.0);
+            fees.1 += exit_price * trade.quantity.abs() as f64;
+            fees.2 += 1;
+        }
+    }
+    ExecutionQuality {
+        entries: CostComparison::from_pairs(&entries),
+        stops: CostComparison::from_pairs(&stops),
+        fees: CostComparison {
+            fills: fees.2,
+            live_rate: if fees.1 > 0.0 { fees.0 / fees.1 } else { 0.0 },
+            assumed_rate: runtime_settings.trade_close_fee_rate,
+        },
+    }
+}
+
+fn print_row(label: &str, comparison: &CostComparison) {
+    println!(
+        "  {:<16} {:>6} {:>10.4}% {:>10.4}% {:>+10.4}%",
+        label,
+        comparison.fills,
+        comparison.live_rate * 100.0,
+        comparison.assumed_rate * 100.0,
+        (comparison.live_rate - comparison.assumed_rate) * 100.0
+    );
+}
+
+/// Prints live entry slippage, stop slippage and fees of `account_id`'s trades entered or
+/// exited between `from` (default: one year before `to`) and `to` (default: today) against
+/// TRADE_SLIPPAGE_RATE and TRADE_CLOSE_FEE_RATE.
+pub async fn run(
+    app: &AppContext,
+    account_id: &str,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<()> {
+    let to = to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = from.unwrap_or(to - Duration::days(365));
+    if from > to {
+        return Err(anyhow!("--from {} is after --to {}", from, to));
+    }
+
+    let db = app.database().await?;
+    if db.get_account_credentials(account_id).await?.is_none() {
+        return Err(anyhow!("Account {} not found", account_id));
+    }
+    let settings = app.database_settings(&db).await?;
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(&settings)?;
+    let trades: Vec<Trade> = db
+        .get_account_journal_trades(account_id, from, to)
+        .await?
+        .into_iter()
+        .map(|journal_trade| journal_trade.trade)
+        .collect();
+    info!(
+        "Comparing {} filled trades of account {} from {} to {}",
+        trades.len(),
+        account_id,
+        from,
+        to
+    );
+
+    let quality = execution_quality(&trades, &runtime_settings);
+    println!(
+        "  {:<16} {:>6} {:>11} {:>11} {:>11}",
+        "cost", "fills", "live", "assumed", "gap"
+    );
+    print_row("entry slippage", &quality.entries);
+    print_row("stop slippage", &quality.stops);
+    print_row("close fees", &quality.fees);
+    match quality.live_slippage_rate() {
+        Some(rate) => println!(
+            "Live slippage across {} fills: {:.4}% (TRADE_SLIPPAGE_RATE {:.4}%)",
+            quality.entries.fills + quality.stops.fills,
+            rate * 100.0,
+            runtime_settings.trade_slippage_rate * 100.0
+        ),
+        None => println!("No entry or stop fills to measure slippage from"),
+    }
+    if quality.fees.fills > 0 {
+        println!(
+            "Live close fees across {} exits: {:.4}% (TRADE_CLOSE_FEE_RATE {:.4}%)",
+            quality.fees.fills,
+            quality.fees.live_rate * 100.0,
+            runtime_settings.trade_close_fee_rate * 100.0
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::benchmark_runtime_settings;
+    use crate::models::TradeChange;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn trade(id: &str, quantity: i32, price: f64) -> Trade {
+        Trade {
+            id: id.to_string(),
+            strategy_id: "strategy".to_string(),
+            ticker: "AAA".to_string(),
+            quantity,
+            price,
+            date: Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap(),
+            status: TradeStatus::Active,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: None,
+            stop_loss_triggered: None,
+            entry_order_id: Some(format!("{}-entry", id)),
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn compares_entry_and_stop_fills_with_assumptions() {
+        let changed_at = Utc.with_ymd_and_hms(2024, 3, 5, 15, 0, 0).unwrap();
+        // Long planned at 100 filled at 100.5; short planned at 50 filled at 50.5 (favourable).
+        let mut long = trade("long", 10, 100.5);
+        long.changes.push(TradeChange {
+            field: "price".to_string(),
+            old_value: json!(100.0),
+            new_value: json!(100.5),
+            changed_at,
+        });
+        long.status = TradeStatus::Closed;
+        long.stop_loss = Some(90.0);
+        long.stop_loss_triggered = Some(true);
+        long.exit_price = Some(89.1);
+        long.fee = Some(0.891);
+        let mut short = trade("short", -4, 50.5);
+        short.changes.push(TradeChange {
+            field: "price".to_string(),
+            old_value: json!(50.0),
+            new_value: json!(50.5),
+            changed_at,
+        });
+        let mut part = trade("long-partial-1", 5, 100.0);
+        part.entry_order_id = None;
+
+        let settings = benchmark_runtime_settings();
+        let quality = execution_quality(&[long, short, part], &settings);
+        assert_eq!(quality.entries.fills, 2);
+        assert!((quality.entries.live_rate - (0.005 - 0.01) / 2.0).abs() < 1e-12);
+        assert_eq!(quality.entries.assumed_rate, settings.trade_slippage_rate);
+        assert_eq!(quality.stops.fills, 1);
+        assert!((quality.stops.live_rate - 0.01).abs() < 1e-12);
+        assert_eq!(quality.fees.fills, 1);
+        assert!((quality.fees.live_rate - 0.001).abs() < 1e-12);
+        let slippage = quality.live_slippage_rate().unwrap();
+        assert!((slippage - (0.005 - 0.01 + 0.01) / 3.0).abs() < 1e-12);
+    }
+}
@@ -1,10 +1,25 @@
 use crate::context::AppContext;
 use crate::data_context::{MarketData, TickerScope};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use log::info;
 use std::path::Path;
 
-pub async fn run(app: &AppContext, output_path: &Path) -> Result<()> {
+/// Writes the snapshot, zstd-compressed at `compression_level` unless it is `None`.
+pub async fn run(
+    app: &AppContext,
+    output_path: &Path,
+    compression_level: Option<i32>,
+) -> Result<()> {
+    if let Some(level) = compression_level {
+        let range = zstd::compression_level_range();
+        if !range.contains(&level) {
+            return Err(anyhow!(
+                "--compression-level must be between {} and {}",
+                range.start(),
+                range.end()
+            ));
+        }
+    }
     info!(
         "Generating market data snapshot at {}",
         output_path.display()
@@ -13,10 +28,19 @@ pub async fn run(app: &AppContext, output_path: &Path) -> Result<()> {
     let db = app.database().await?;
     let market_data = MarketData::load(&db, TickerScope::AllTickers).await?;
 
-    market_data.save_to_file(output_path)?;
+    market_data.save_to_file(output_path, compression_level)?;
+    let size_mb = std::fs::metadata(output_path)
+        .map(|metadata| metadata.len() as f64 / 1_048_576.0)
+        .unwrap_or(0.0);
     info!(
-        "Market data snapshot successfully written to {}",
-        output_path.display()
+        "Market data snapshot successfully written to {} ({:.1} MB, {}, content hash {})",
+        output_path.display(),
+        size_mb,
+        match compression_level {
+            Some(level) => format!("zstd level {}", level),
+            None => "uncompressed".to_string(),
+        },
+        market_data.content_hash()
     );
 
     Ok(())
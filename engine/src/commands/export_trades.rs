@@ -0,0 +1,512 @@
+use crate::alpaca::{OrderEvaluation, OrderState};
+use crate::broker::{AccountBroker, Broker};
+use crate::context::AppContext;
+use crate::database::JournalTrade;
+use crate::models::TradeStatus;
+use crate::xlsx::{write_xlsx, XlsxCell};
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, NaiveDate, Utc};
+use clap::ValueEnum;
+use log::{info, warn};
+use reqwest::Client;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const PRICE_EPSILON: f64 = 1e-6;
+const JOURNAL_COLUMNS: [&str; 19] = [
+    "trade_id",
+    "strategy",
+    "ticker",
+    "side",
+    "quantity",
+    "status",
+    "entry_date",
+    "entry_price",
+    "exit_date",
+    "exit_price",
+    "holding_days",
+    "fees",
+    "gross_pnl",
+    "realized_pnl",
+    "return_pct",
+    "exit_reason",
+    "entry_order_id",
+    "exit_order_id",
+    "broker_check",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum JournalFormat {
+    Csv,
+    Json,
+    Xlsx,
+}
+
+impl JournalFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            JournalFormat::Csv => "csv",
+            JournalFormat::Json => "json",
+            JournalFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// `from`/`to` default to the start of the current year and today; `offline` skips the broker
+/// check; `output` defaults to `trade-journal-<account>.<format>`.
+pub struct ExportTradesOptions {
+    pub account_id: String,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub format: JournalFormat,
+    pub output: Option<PathBuf>,
+    pub offline: bool,
+}
+
+/// How a journal row compares with the broker's fills of its orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokerCheck {
+    /// Every order of the trade was found filled at the recorded prices.
+    Confirmed,
+    /// The broker filled at a different price; the row uses the broker's price.
+    Adjusted,
+    /// The broker was not asked or did not report a fill.
+    Unverified,
+}
+
+impl BrokerCheck {
+    fn label(self) -> &'static str {
+        match self {
+            BrokerCheck::Confirmed => "confirmed",
+            BrokerCheck::Adjusted => "adjusted",
+            BrokerCheck::Unverified => "unverified",
+        }
+    }
+}
+
+/// One filled live trade. P&L and holding days are only set once the trade is closed; fees
+/// are subtracted from the gross P&L, and `return_pct` is realized P&L over the entry cost.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub trade_id: String,
+    pub strategy: String,
+    pub ticker: String,
+    pub side: &'static str,
+    pub quantity: i32,
+    pub status: &'static str,
+    pub entry_date: NaiveDate,
+    pub entry_price: f64,
+    pub exit_date: Option<NaiveDate>,
+    pub exit_price: Option<f64>,
+    pub holding_days: Option<i64>,
+    pub fees: f64,
+    pub gross_pnl: Option<f64>,
+    pub realized_pnl: Option<f64>,
+    pub return_pct: Option<f64>,
+    pub exit_reason: Option<String>,
+    pub entry_order_id: Option<String>,
+    pub exit_order_id: Option<String>,
+    pub broker_check: BrokerCheck,
+}
+
+/// Broker fills of a trade's entry and exit orders, when the broker reported them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BrokerFills {
+    pub checked: bool,
+    pub entry_price: Option<f64>,
+    pub exit_price: Option<f64>,
+}
+
+fn filled_price(evaluation: Option<OrderEvaluation>) -> Option<f64> {
+    evaluation
+        .filter(|evaluation| matches!(evaluation.state, OrderState::Filled))
+        .and_then(|evaluation| evaluation.filled_price)
+        .filter(|price| price.is_finite() && *price > 0.0)
+}
+
+pub fn journal_entry(journal_trade: &JournalTrade, fills: BrokerFills) -> JournalEntry {
+    let trade = &journal_trade.trade;
+    let closed = trade.status == TradeStatus::Closed;
+    let mut adjusted = false;
+    let mut confirmed = fills.checked;
+    let mut reconcile = |recorded: Option<f64>, broker: Option<f64>| match (recorded, broker) {
+        (_, None) => {
+            confirmed = false;
+            recorded
+        }
+        (Some(recorded), Some(broker)) if (recorded - broker).abs() <= PRICE_EPSILON => {
+            Some(recorded)
+        }
+        (_, Some(broker)) => {
+            adjusted = true;
+            Some(broker)
+        }
+    };
+    let entry_price = reconcile(Some(trade.price), fills.entry_price).unwrap_or(trade.price);
+    let exit_price = if closed {
+        reconcile(trade.exit_price, fills.exit_price)
+    } else {
+        None
+    };
+    let broker_check = if adjusted {
+        BrokerCheck::Adjusted
+    } else if confirmed {
+        BrokerCheck::Confirmed
+    } else {
+        BrokerCheck::Unverified
+    };
+
+    let quantity = trade.quantity;
+    let fees = trade.fee.unwrap_or(0.0);
+    let gross_pnl = if closed {
+        exit_price
+            .map(|exit| (exit - entry_price) * quantity as f64)
+            .or(trade.pnl)
+    } else {
+        None
+    };
+    let realized_pnl = gross_pnl.map(|pnl| pnl - fees);
+    let cost = entry_price * quantity.abs() as f64;
+    let return_pct = realized_pnl
+        .filter(|_| cost > 0.0)
+        .map(|pnl| pnl / cost * 100.0);
+    let exit_date = if closed {
+        trade.exit_date.map(|date| date.date_naive())
+    } else {
+        None
+    };
+    let exit_reason = if !closed {
+        None
+    } else if trade.stop_loss_triggered == Some(true) {
+        Some("stop_loss".to_string())
+    } else {
+        Some(
+            journal_trade
+                .close_reason
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        )
+    };
+    let exit_order_id = if trade.stop_loss_triggered == Some(true) {
+        trade.stop_order_id.clone()
+    } else {
+        trade
+            .exit_order_id
+            .clone()
+            .or_else(|| trade.target_order_id.clone())
+    };
+
+    JournalEntry {
+        trade_id: trade.id.clone(),
+        strategy: journal_trade.strategy_name.clone(),
+        ticker: trade.ticker.clone(),
+        side: if quantity < 0 { "short" } else { "long" },
+        quantity: quantity.abs(),
+        status: if closed { "closed" } else { "open" },
+        entry_date: trade.date.date_naive(),
+        entry_price,
+        exit_date,
+        exit_price,
+        holding_days: exit_date.map(|exit| (exit - trade.date.date_naive()).num_days()),
+        fees,
+        gross_pnl,
+        realized_pnl,
+        return_pct,
+        exit_reason,
+        entry_order_id: trade.entry_order_id.clone(),
+        exit_order_id: if closed { exit_order_id } else { None },
+        broker_check,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+pub fn render_csv(entries: &[JournalEntry]) -> String {
+    let mut out = JOURNAL_COLUMNS.join(",");
+    out.push('\n');
+    for entry in entries {
+        let fields = [
+            csv_field(&entry.trade_id),
+            csv_field(&entry.strategy),
+            csv_field(&entry.ticker),
+            entry.side.to_string(),
+            entry.quantity.to_string(),
+            entry.status.to_string(),
+            entry.entry_date.to_string(),
+            entry.entry_price.to_string(),
+            optional(&entry.exit_date),
+            optional(&entry.exit_price),
+            optional(&entry.holding_days),
+            entry.fees.to_string(),
+            optional(&entry.gross_pnl),
+            optional(&entry.realized_pnl),
+            optional(&entry.return_pct),
+            csv_field(&optional(&entry.exit_reason)),
+            csv_field(&optional(&entry.entry_order_id)),
+            csv_field(&optional(&entry.exit_order_id)),
+            entry.broker_check.label().to_string(),
+        ];
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn xlsx_row(entry: &JournalEntry) -> Vec<XlsxCell> {
+    let text = |value: &str| XlsxCell::Text(value.to_string());
+    vec![
+        text(&entry.trade_id),
+        text(&entry.strategy),
+        text(&entry.ticker),
+        text(entry.side),
+        XlsxCell::Number(entry.quantity as f64),
+        text(entry.status),
+        text(&entry.entry_date.to_string()),
+        XlsxCell::Number(entry.entry_price),
+        entry.exit_date.map(|date| date.to_string()).into(),
+        entry.exit_price.into(),
+        entry.holding_days.map(|days| days as f64).into(),
+        XlsxCell::Number(entry.fees),
+        entry.gross_pnl.into(),
+        entry.realized_pnl.into(),
+        entry.return_pct.into(),
+        entry.exit_reason.clone().into(),
+        entry.entry_order_id.clone().into(),
+        entry.exit_order_id.clone().into(),
+        text(entry.broker_check.label()),
+    ]
+}
+
+async fn broker_fill_price(broker: &AccountBroker<'_>, order_id: Option<&String>) -> Option<f64> {
+    let order_id = order_id.filter(|id| !id.trim().is_empty())?;
+    match broker.evaluate_order(order_id).await {
+        Ok(evaluation) => filled_price(evaluation),
+        Err(err) => {
+            warn!("Failed to look up order {}: {}", order_id, err);
+            None
+        }
+    }
+}
+
+async fn fetch_fills(broker: &AccountBroker<'_>, journal_trade: &JournalTrade) -> BrokerFills {
+    let trade = &journal_trade.trade;
+    let exit_order_id = if trade.stop_loss_triggered == Some(true) {
+        trade.stop_order_id.as_ref()
+    } else {
+        trade
+            .exit_order_id
+            .as_ref()
+            .or(trade.target_order_id.as_ref())
+    };
+    BrokerFills {
+        checked: true,
+        entry_price: broker_fill_price(broker, trade.entry_order_id.as_ref()).await,
+        exit_price: if trade.status == TradeStatus::Closed {
+            broker_fill_price(broker, exit_order_id).await
+        } else {
+            None
+        },
+    }
+}
+
+fn write_output(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Writes the account's trade journal: filled live trades entered or exited in the window, with
+/// prices checked against the broker's order fills unless `offline` is set.
+pub async fn run(app: &AppContext, options: ExportTradesOptions) -> Result<()> {
+    let today = Utc::now().date_naive();
+    let to = options.to.unwrap_or(today);
+    let from = options
+        .from
+        .or_else(|| NaiveDate::from_ymd_opt(to.year(), 1, 1))
+        .unwrap_or(to);
+    if from > to {
+        return Err(anyhow!("--from {} is after --to {}", from, to));
+    }
+
+    let db = app.database().await?;
+    let creds = db
+        .get_account_credentials(&options.account_id)
+        .await?
+        .ok_or_else(|| anyhow!("Account {} not found", options.account_id))?;
+    let journal_trades = db
+        .get_account_journal_trades(&options.account_id, from, to)
+        .await?;
+
+    let http_client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("failed to create HTTP client for broker order lookups")?;
+    let settings = app.database_settings(&db).await?;
+    let broker = if options.offline || journal_trades.is_empty() {
+        None
+    } else if !AccountBroker::supports(&creds.provider) {
+        warn!(
+            "Account provider {} is not supported; exporting without a broker check",
+            creds.provider
+        );
+        None
+    } else {
+        match AccountBroker::connect(&http_client, &db, &creds, &settings) {
+            Ok(broker) => Some(broker),
+            Err(err) => {
+                warn!(
+                    "Failed to connect to the {} broker, exporting without a broker check: {}",
+                    creds.provider, err
+                );
+                None
+            }
+        }
+    };
+
+    let mut entries = Vec::with_capacity(journal_trades.len());
+    for journal_trade in &journal_trades {
+        let fills = match &broker {
+            Some(broker) => fetch_fills(broker, journal_trade).await,
+            None => BrokerFills::default(),
+        };
+        entries.push(journal_entry(journal_trade, fills));
+    }
+
+    let output = options.output.clone().unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "trade-journal-{}.{}",
+            options.account_id,
+            options.format.extension()
+        ))
+    });
+    match options.format {
+        JournalFormat::Csv => write_output(&output, &render_csv(&entries))?,
+        JournalFormat::Json => write_output(&output, &serde_json::to_string_pretty(&entries)?)?,
+        JournalFormat::Xlsx => {
+            let rows: Vec<Vec<XlsxCell>> = entries.iter().map(xlsx_row).collect();
+            write_xlsx(&output, "Trades", &JOURNAL_COLUMNS, &rows)?;
+        }
+    }
+
+    let realized: f64 = entries
+        .iter()
+        .filter(|entry| {
+            entry
+                .exit_date
+                .is_some_and(|date| date >= from && date <= to)
+        })
+        .filter_map(|entry| entry.realized_pnl)
+        .sum();
+    let count = |check: BrokerCheck| {
+        entries
+            .iter()
+            .filter(|entry| entry.broker_check == check)
+            .count()
+    };
+    info!(
+        "Wrote {} trades of account {} from {} to {} to {}; realized P&L {:.2} on exits in the window; broker check: {} confirmed, {} adjusted, {} unverified",
+        entries.len(),
+        options.account_id,
+        from,
+        to,
+        output.display(),
+        realized,
+        count(BrokerCheck::Confirmed),
+        count(BrokerCheck::Adjusted),
+        count(BrokerCheck::Unverified)
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{journal_entry, render_csv, BrokerCheck, BrokerFills};
+    use crate::database::JournalTrade;
+    use crate::models::{Trade, TradeStatus};
+    use chrono::{TimeZone, Utc};
+
+    fn closed_trade() -> JournalTrade {
+        JournalTrade {
+            trade: Trade {
+                id: "t1".to_string(),
+                strategy_id: "s1".to_string(),
+                ticker: "AAPL".to_string(),
+                quantity: 10,
+                price: 100.0,
+                date: Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+                status: TradeStatus::Closed,
+                pnl: Some(100.0),
+                fee: Some(2.0),
+                exit_price: Some(110.0),
+                exit_date: Some(Utc.with_ymd_and_hms(2024, 3, 11, 0, 0, 0).unwrap()),
+                stop_loss: Some(95.0),
+                stop_loss_triggered: Some(false),
+                entry_order_id: Some("entry-1".to_string()),
+                entry_cancel_after: None,
+                stop_order_id: Some("stop-1".to_string()),
+                exit_order_id: Some("exit-1".to_string()),
+                target_order_id: None,
+                changes: Vec::new(),
+            },
+            strategy_name: "Momentum, v2".to_string(),
+            close_reason: Some("max_holding_days".to_string()),
+        }
+    }
+
+    #[test]
+    fn builds_entries_and_prefers_broker_fills() {
+        let trade = closed_trade();
+        let unchecked = journal_entry(&trade, BrokerFills::default());
+        assert_eq!(unchecked.broker_check, BrokerCheck::Unverified);
+        assert_eq!(unchecked.holding_days, Some(10));
+        assert_eq!(unchecked.gross_pnl, Some(100.0));
+        assert_eq!(unchecked.realized_pnl, Some(98.0));
+        assert!((unchecked.return_pct.unwrap() - 9.8).abs() < 1e-9);
+        assert_eq!(unchecked.exit_reason.as_deref(), Some("max_holding_days"));
+        assert_eq!(unchecked.exit_order_id.as_deref(), Some("exit-1"));
+
+        let confirmed = journal_entry(
+            &trade,
+            BrokerFills {
+                checked: true,
+                entry_price: Some(100.0),
+                exit_price: Some(110.0),
+            },
+        );
+        assert_eq!(confirmed.broker_check, BrokerCheck::Confirmed);
+
+        let adjusted = journal_entry(
+            &trade,
+            BrokerFills {
+                checked: true,
+                entry_price: Some(100.0),
+                exit_price: Some(108.0),
+            },
+        );
+        assert_eq!(adjusted.broker_check, BrokerCheck::Adjusted);
+        assert_eq!(adjusted.exit_price, Some(108.0));
+        assert_eq!(adjusted.realized_pnl, Some(78.0));
+
+        let mut stopped = closed_trade();
+        stopped.trade.stop_loss_triggered = Some(true);
+        let stopped = journal_entry(&stopped, BrokerFills::default());
+        assert_eq!(stopped.exit_reason.as_deref(), Some("stop_loss"));
+        assert_eq!(stopped.exit_order_id.as_deref(), Some("stop-1"));
+
+        let csv = render_csv(&[unchecked]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.starts_with("t1,\"Momentum, v2\",AAPL,long,10,closed,2024-03-01,100,"));
+        assert!(row.ends_with(",max_holding_days,entry-1,exit-1,unverified"));
+    }
+}
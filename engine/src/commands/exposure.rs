@@ -0,0 +1,384 @@
+use crate::broker::{AccountBroker, Broker};
+use crate::config::EngineConfig;
+use crate::context::AppContext;
+use crate::engine::AccountStateSnapshot;
+use crate::models::TickerInfo;
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+pub const EXPOSURE_LOG_SOURCE: &str = "exposure";
+const UNCLASSIFIED_ASSET_TYPE: &str = "unclassified";
+
+pub struct ExposureOptions<'a> {
+    pub account_id: &'a str,
+    /// Positions weighing more than this share of equity are flagged as concentrated.
+    pub max_position_weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionExposure {
+    pub ticker: String,
+    pub asset_type: String,
+    pub quantity: i32,
+    pub price: f64,
+    pub market_value: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupExposure {
+    pub asset_type: String,
+    pub net_value: f64,
+    pub gross_value: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExposureReport {
+    pub account_id: String,
+    pub equity: f64,
+    pub cash: f64,
+    pub long_value: f64,
+    pub short_value: f64,
+    pub net_exposure: f64,
+    pub gross_exposure: f64,
+    /// Gross exposure over equity, against the highest `maxLeverage` of the account's strategies.
+    pub leverage_used: f64,
+    pub leverage_allowed: f64,
+    /// Share of equity sitting in cash rather than positions.
+    pub cash_drag: f64,
+    pub positions: Vec<PositionExposure>,
+    pub asset_types: Vec<GroupExposure>,
+    pub warnings: Vec<String>,
+}
+
+pub async fn run(app: &AppContext, options: ExposureOptions<'_>) -> Result<()> {
+    if !(options.max_position_weight > 0.0 && options.max_position_weight <= 1.0) {
+        return Err(anyhow!("--max-weight must be in (0, 1]"));
+    }
+    let db = app.database().await?;
+    let settings = app.database_settings(&db).await?;
+    let creds = db
+        .get_account_credentials(options.account_id)
+        .await?
+        .ok_or_else(|| anyhow!("Account {} not found", options.account_id))?;
+    if !AccountBroker::supports(&creds.provider) {
+        return Err(anyhow!(
+            "Account {} uses unsupported provider {}",
+            options.account_id,
+            creds.provider
+        ));
+    }
+
+    let http_client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("failed to create HTTP client for account state fetches")?;
+    let broker = AccountBroker::connect(&http_client, &db, &creds, &settings)?;
+    let mut state = broker
+        .fetch_account_state()
+        .await
+        .context("failed to fetch account state")?;
+    state.fx = db.get_account_fx(options.account_id).await?;
+
+    let leverage_allowed = db
+        .get_active_strategies()
+        .await?
+        .iter()
+        .filter(|strategy| strategy.account_id.as_deref() == Some(options.account_id))
+        .map(|strategy| EngineConfig::from_parameters(&strategy.parameters).max_leverage)
+        .fold(1.0, f64::max);
+    let symbols: Vec<String> = state
+        .positions
+        .iter()
+        .map(|position| position.ticker.clone())
+        .collect();
+    let metadata = db.get_ticker_metadata(&symbols).await?;
+    // Candles come back ordered by date, so the last close per ticker wins.
+    let latest_closes: HashMap<String, f64> = db
+        .get_candles_for_tickers(&symbols)
+        .await?
+        .into_iter()
+        .map(|candle| (candle.ticker, candle.close))
+        .collect();
+
+    let report = build_exposure_report(
+        options.account_id,
+        &state,
+        &metadata,
+        &latest_closes,
+        leverage_allowed,
+        options.max_position_weight,
+    );
+
+    info!(
+        "Account {}: equity {:.2}, net {:+.2} ({:.1}%), gross {:.2}, leverage {:.2}x of {:.2}x allowed, cash drag {:.1}%",
+        report.account_id,
+        report.equity,
+        report.net_exposure,
+        ratio(report.net_exposure, report.equity) * 100.0,
+        report.gross_exposure,
+        report.leverage_used,
+        report.leverage_allowed,
+        report.cash_drag * 100.0
+    );
+    for group in &report.asset_types {
+        info!(
+            "  {:<14} net {:>+12.2} gross {:>12.2} {:>6.1}%",
+            group.asset_type,
+            group.net_value,
+            group.gross_value,
+            group.weight * 100.0
+        );
+    }
+    for position in &report.positions {
+        info!(
+            "  {:<8} {:>8} @ {:>10.2} {:>+12.2} {:>6.1}%",
+            position.ticker,
+            position.quantity,
+            position.price,
+            position.market_value,
+            position.weight * 100.0
+        );
+    }
+    for warning in &report.warnings {
+        info!("  warning: {}", warning);
+    }
+
+    db.insert_system_log(
+        EXPOSURE_LOG_SOURCE,
+        if report.warnings.is_empty() {
+            "info"
+        } else {
+            "warn"
+        },
+        &format!(
+            "Exposure for account {}: net {:+.2}, gross {:.2}, leverage {:.2}x",
+            report.account_id, report.net_exposure, report.gross_exposure, report.leverage_used
+        ),
+        Some(json!({
+            "accountId": report.account_id,
+            "event": "exposure_report",
+            "report": report,
+        })),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Values each position at the broker's current price, falling back to the latest candle close
+/// and then the entry price, converted into the account currency. Weights are shares of equity
+/// (broker equity, or cash plus net exposure when the broker does not report it); sectors are not tracked, so positions group
+/// by ticker asset type.
+pub fn build_exposure_report(
+    account_id: &str,
+    state: &AccountStateSnapshot,
+    metadata: &HashMap<String, TickerInfo>,
+    latest_closes: &HashMap<String, f64>,
+    leverage_allowed: f64,
+    max_position_weight: f64,
+) -> ExposureReport {
+    let mut positions: Vec<PositionExposure> = state
+        .positions
+        .iter()
+        .filter(|position| position.quantity != 0)
+        .map(|position| {
+            let price = position
+                .current_price
+                .or_else(|| latest_closes.get(&position.ticker).copied())
+                .unwrap_or(position.avg_entry_price);
+            PositionExposure {
+                ticker: position.ticker.clone(),
+                asset_type: metadata
+                    .get(&position.ticker)
+                    .and_then(|info| info.asset_type.clone())
+                    .unwrap_or_else(|| UNCLASSIFIED_ASSET_TYPE.to_string()),
+                quantity: position.quantity,
+                price,
+                market_value: state
+                    .fx
+                    .to_account(position.quantity as f64 * price, &position.ticker),
+                weight: 0.0,
+            }
+        })
+        .collect();
+
+    let long_value: f64 = positions
+        .iter()
+        .map(|position| position.market_value.max(0.0))
+        .sum();
+    let short_value: f64 = positions
+        .iter()
+        .map(|position| (-position.market_value).max(0.0))
+        .sum();
+    let net_exposure = long_value - short_value;
+    let gross_exposure = long_value + short_value;
+    let cash = state.available_cash;
+    let equity = state.equity.unwrap_or(cash + net_exposure);
+
+    for position in &mut positions {
+        position.weight = ratio(position.market_value, equity);
+    }
+    positions.sort_by(|a, b| {
+        b.weight
+            .abs()
+            .total_cmp(&a.weight.abs())
+            .then_with(|| a.ticker.cmp(&b.ticker))
+    });
+
+    let mut groups: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for position in &positions {
+        let group = groups.entry(&position.asset_type).or_default();
+        group.0 += position.market_value;
+        group.1 += position.market_value.abs();
+    }
+    let asset_types = groups
+        .into_iter()
+        .map(|(asset_type, (net_value, gross_value))| GroupExposure {
+            asset_type: asset_type.to_string(),
+            net_value,
+            gross_value,
+            weight: ratio(gross_value, equity),
+        })
+        .collect();
+
+    let leverage_used = ratio(gross_exposure, equity);
+    let mut warnings = Vec::new();
+    if equity <= 0.0 {
+        warnings.push(format!(
+            "Equity is {:.2}; weights are not meaningful",
+            equity
+        ));
+    }
+    if leverage_used > leverage_allowed {
+        warnings.push(format!(
+            "Leverage {:.2}x exceeds the {:.2}x allowed by the account's strategies",
+            leverage_used, leverage_allowed
+        ));
+    }
+    for position in &positions {
+        if position.weight.abs() > max_position_weight {
+            warnings.push(format!(
+                "{} is {:.1}% of equity, above the {:.1}% concentration limit",
+                position.ticker,
+                position.weight * 100.0,
+                max_position_weight * 100.0
+            ));
+        }
+    }
+
+    ExposureReport {
+        account_id: account_id.to_string(),
+        equity,
+        cash,
+        long_value,
+        short_value,
+        net_exposure,
+        gross_exposure,
+        leverage_used,
+        leverage_allowed,
+        cash_drag: ratio(cash.max(0.0), equity),
+        positions,
+        asset_types,
+        warnings,
+    }
+}
+
+fn ratio(value: f64, equity: f64) -> f64 {
+    if equity > 0.0 {
+        value / equity
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::AccountPositionState;
+
+    fn position(ticker: &str, quantity: i32, current_price: Option<f64>) -> AccountPositionState {
+        AccountPositionState {
+            ticker: ticker.to_string(),
+            quantity,
+            avg_entry_price: 50.0,
+            current_price,
+        }
+    }
+
+    fn info(symbol: &str, asset_type: &str) -> TickerInfo {
+        TickerInfo {
+            symbol: symbol.to_string(),
+            name: None,
+            tradable: true,
+            shortable: true,
+            easy_to_borrow: true,
+            asset_type: Some(asset_type.to_string()),
+            expense_ratio: None,
+            market_cap: None,
+            volume_usd: None,
+            max_fluctuation_ratio: None,
+            last_updated: None,
+            candle_count: None,
+            training: false,
+            auxiliary: false,
+        }
+    }
+
+    #[test]
+    fn reports_exposure_weights_and_warnings() {
+        let state = AccountStateSnapshot {
+            available_cash: 2_000.0,
+            buying_power: None,
+            held_tickers: Default::default(),
+            open_buy_orders: Default::default(),
+            open_sell_orders: Default::default(),
+            positions: vec![
+                position("AAA", 60, Some(100.0)),
+                position("BBB", 20, None),
+                position("CCC", -10, None),
+            ],
+            stop_orders: HashMap::new(),
+            equity: Some(10_000.0),
+            last_equity: None,
+            circuit_breaker_triggered_at: None,
+            risk_overrides: Default::default(),
+            fx: Default::default(),
+        };
+        let metadata = HashMap::from([
+            ("AAA".to_string(), info("AAA", "stock")),
+            ("BBB".to_string(), info("BBB", "etf")),
+        ]);
+        let latest_closes = HashMap::from([("BBB".to_string(), 100.0)]);
+
+        let report = build_exposure_report("acct", &state, &metadata, &latest_closes, 1.0, 0.5);
+        assert_eq!(report.long_value, 8_000.0);
+        // CCC has no current price or candle, so it is valued at its entry price.
+        assert_eq!(report.short_value, 500.0);
+        assert_eq!(report.net_exposure, 7_500.0);
+        assert!((report.leverage_used - 0.85).abs() < 1e-9);
+        assert!((report.cash_drag - 0.2).abs() < 1e-9);
+        assert_eq!(report.positions[0].ticker, "AAA");
+        assert!((report.positions[2].weight + 0.05).abs() < 1e-9);
+        let types: Vec<&str> = report
+            .asset_types
+            .iter()
+            .map(|group| group.asset_type.as_str())
+            .collect();
+        assert_eq!(types, vec!["etf", "stock", "unclassified"]);
+        assert_eq!(report.warnings.len(), 1, "{:?}", report.warnings);
+        assert!(report.warnings[0].starts_with("AAA"));
+
+        let tight = build_exposure_report("acct", &state, &metadata, &latest_closes, 0.5, 1.0);
+        assert_eq!(tight.warnings.len(), 1);
+        assert!(tight.warnings[0].starts_with("Leverage"));
+    }
+}
@@ -0,0 +1,53 @@
+use crate::context::AppContext;
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use log::{info, warn};
+
+pub async fn run(app: &AppContext, months: u32) -> Result<()> {
+    if months == 0 {
+        return Err(anyhow!("--months must be at least 1"));
+    }
+    let db = app.database().await?;
+    let active_strategies = db.count_active_strategies().await?;
+    if active_strategies == 0 {
+        warn!("No active strategies; every ticker would be reported as idle");
+        return Ok(());
+    }
+
+    let window_hours = ((months as f64) * 30.4 * 24.0).ceil() as i64;
+    let since = (Utc::now() - Duration::hours(window_hours)).date_naive();
+    let activity = db.get_ticker_activity(since).await?;
+
+    let total_candles: i64 = activity.iter().map(|ticker| ticker.candle_count).sum();
+    let mut idle: Vec<_> = activity
+        .iter()
+        .filter(|ticker| !ticker.has_signals && !ticker.has_trades)
+        .collect();
+    idle.sort_by(|a, b| {
+        b.candle_count
+            .cmp(&a.candle_count)
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+    let idle_candles: i64 = idle.iter().map(|ticker| ticker.candle_count).sum();
+
+    info!(
+        "{} of {} tickers had no signals or trades from {} active strateg{} since {}",
+        idle.len(),
+        activity.len(),
+        active_strategies,
+        if active_strategies == 1 { "y" } else { "ies" },
+        since
+    );
+    for ticker in &idle {
+        info!("  {:<8} {:>8} candles", ticker.symbol, ticker.candle_count);
+    }
+    if total_candles > 0 {
+        info!(
+            "Idle tickers hold {} of {} candles ({:.1}%); pruning them shrinks every backtest and signal run accordingly",
+            idle_candles,
+            total_candles,
+            idle_candles as f64 / total_candles as f64 * 100.0
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use super::csv_import::{parse_date, parse_rows, read_rows};
+use crate::context::AppContext;
+use crate::models::Candle;
+use anyhow::{anyhow, Result};
+use log::info;
+use std::path::Path;
+
+/// Reads `date,close` or `date,open,high,low,close[,volume]` rows (YYYY-MM-DD) of `ticker`. A
+/// close-only row uses the close as its open, high and low.
+fn parse_auxiliary_csv(ticker: &str, contents: &str) -> Result<Vec<Candle>> {
+    parse_rows(
+        contents,
+        0,
+        "date,close or date,open,high,low,close[,volume] row",
+        |fields| {
+            let date = parse_date(fields.first()?)?;
+            let prices: Vec<f64> = fields[1..]
+                .iter()
+                .map(|value| value.parse().ok().filter(|price: &f64| price.is_finite()))
+                .collect::<Option<_>>()?;
+            let (open, high, low, close) = match prices[..] {
+                [close] => (close, close, close, close),
+                [open, high, low, close] | [open, high, low, close, _] => (open, high, low, close),
+                _ => return None,
+            };
+            Some(Candle {
+                ticker: ticker.to_string(),
+                date: date.and_hms_opt(0, 0, 0).expect("midnight").and_utc(),
+                open,
+                high,
+                low,
+                close,
+                unadjusted_close: None,
+                volume_shares: prices.get(4).map_or(0, |volume| *volume as i64),
+            })
+        },
+    )
+}
+
+/// Stores the daily values in `path` as candles of the auxiliary ticker `ticker`, which is
+/// marked non-tradable so it feeds features and filters without being traded.
+pub async fn run(app: &AppContext, ticker: &str, path: &Path) -> Result<()> {
+    let ticker = ticker.trim().to_ascii_uppercase();
+    if ticker.is_empty() || ticker.contains(char::is_whitespace) {
+        return Err(anyhow!("Auxiliary ticker must be a single symbol"));
+    }
+    let candles = read_rows(path, "auxiliary series", |contents| {
+        parse_auxiliary_csv(&ticker, contents)
+    })?;
+
+    let db = app.database().await?;
+    db.upsert_auxiliary_candles(&ticker, &candles).await?;
+    info!(
+        "Stored {} daily values of auxiliary series {} from {}",
+        candles.len(),
+        ticker,
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_close_only_and_ohlc_rows() {
+        let candles = parse_auxiliary_csv(
+            "VIX",
+            "date,close\n2024-01-02,13.2\n2024-01-03,14.0,15.5,13.9,14.8\n",
+        )
+        .unwrap();
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].ticker, "VIX");
+        assert_eq!((candles[0].open, candles[0].close), (13.2, 13.2));
+        assert_eq!((candles[1].high, candles[1].close), (15.5, 14.8));
+        assert!(parse_auxiliary_csv("VIX", "2024-01-02,13.2\n2024-01-03,high\n").is_err());
+        assert!(parse_auxiliary_csv("VIX", "2024-01-02,13.2\n2024-01-03,1,2\n").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::alpaca::{AlpacaClient, FilledOrder, OpenStopOrder};
+use crate::context::AppContext;
+use crate::models::{Trade, TradeStatus};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use log::{info, warn};
+use reqwest::Client;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+const IMPORTED_TRADE_PREFIX: &str = "import";
+
+/// `since` limits the history to orders submitted on or after that date; `dry_run` only logs
+/// the trades that would be written.
+pub struct ImportBrokerHistoryOptions {
+    pub account_id: String,
+    pub strategy_id: String,
+    pub since: Option<NaiveDate>,
+    pub dry_run: bool,
+}
+
+/// Shares of one entry fill that no later fill has closed yet.
+struct OpenLot {
+    order_id: String,
+    quantity: i32,
+    price: f64,
+    filled_at: DateTime<Utc>,
+}
+
+fn trade_date(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .map(|value| value.and_utc())
+        .unwrap_or(at)
+}
+
+fn lot_trade(strategy_id: &str, ticker: &str, lot: &OpenLot, quantity: i32) -> Trade {
+    Trade {
+        id: format!("{}-{}", IMPORTED_TRADE_PREFIX, lot.order_id),
+        strategy_id: strategy_id.to_string(),
+        ticker: ticker.to_string(),
+        quantity,
+        price: lot.price,
+        date: trade_date(lot.filled_at),
+        status: TradeStatus::Active,
+        pnl: None,
+        fee: Some(0.0),
+        exit_price: None,
+        exit_date: None,
+        stop_loss: None,
+        stop_loss_triggered: Some(false),
+        entry_order_id: Some(lot.order_id.clone()),
+        entry_cancel_after: None,
+        stop_order_id: None,
+        exit_order_id: None,
+        target_order_id: None,
+        changes: Vec::new(),
+    }
+}
+
+/// Replays filled orders first in, first out per ticker: a fill opens a lot (negative
+/// quantity for a short) unless it closes shares of the oldest opposite lots, and every closed
+/// slice becomes a closed trade `import-<entry order>-<exit order>`. Lots still open become
+/// active trades `import-<entry order>`, linked to a resting stop of the exact quantity when
+/// there is one, otherwise given the tightest remaining stop price without an order id.
+pub fn build_imported_trades(
+    strategy_id: &str,
+    fills: &[FilledOrder],
+    stops: &[OpenStopOrder],
+) -> Vec<Trade> {
+    let mut lots: BTreeMap<&str, VecDeque<OpenLot>> = BTreeMap::new();
+    let mut trades = Vec::new();
+
+    for fill in fills {
+        let shares = fill.quantity.round() as i32;
+        if shares <= 0 || fill.symbol.trim().is_empty() {
+            continue;
+        }
+        let mut remaining = if fill.side == "sell" { -shares } else { shares };
+        let ticker_lots = lots.entry(fill.symbol.as_str()).or_default();
+        while remaining != 0 {
+            let Some(lot) = ticker_lots
+                .front_mut()
+                .filter(|lot| lot.quantity.signum() != remaining.signum())
+            else {
+                break;
+            };
+            let closed = remaining.abs().min(lot.quantity.abs()) * lot.quantity.signum();
+            let mut trade = lot_trade(strategy_id, &fill.symbol, lot, closed);
+            trade.id = format!("{}-{}", trade.id, fill.order_id);
+            trade.status = TradeStatus::Closed;
+            trade.exit_price = Some(fill.price);
+            trade.exit_date = Some(trade_date(fill.filled_at));
+            trade.exit_order_id = Some(fill.order_id.clone());
+            trade.pnl = Some((fill.price - lot.price) * closed as f64);
+            trades.push(trade);
+
+            lot.quantity -= closed;
+            remaining += closed;
+            if lot.quantity == 0 {
+                ticker_lots.pop_front();
+            }
+        }
+        if remaining != 0 {
+            ticker_lots.push_back(OpenLot {
+                order_id: fill.order_id.clone(),
+                quantity: remaining,
+                price: fill.price,
+                filled_at: fill.filled_at,
+            });
+        }
+    }
+
+    for (ticker, ticker_lots) in lots {
+        let mut unused: Vec<&OpenStopOrder> =
+            stops.iter().filter(|stop| stop.symbol == ticker).collect();
+        let mut pending = Vec::new();
+        for lot in &ticker_lots {
+            let mut trade = lot_trade(strategy_id, ticker, lot, lot.quantity);
+            let stop_side = if lot.quantity > 0 { "sell" } else { "buy" };
+            if let Some(index) = unused.iter().position(|stop| {
+                stop.side == stop_side && stop.quantity.round() as i32 == lot.quantity.abs()
+            }) {
+                let stop = unused.remove(index);
+                trade.stop_loss = Some(stop.stop_price);
+                trade.stop_order_id = Some(stop.order_id.clone());
+            } else {
+                pending.push(trades.len());
+            }
+            trades.push(trade);
+        }
+        for index in pending {
+            let trade = &mut trades[index];
+            let stop_side = if trade.quantity > 0 { "sell" } else { "buy" };
+            let candidates = unused
+                .iter()
+                .filter(|stop| stop.side == stop_side)
+                .map(|stop| stop.stop_price);
+            trade.stop_loss = if trade.quantity > 0 {
+                candidates.reduce(f64::max)
+            } else {
+                candidates.reduce(f64::min)
+            };
+        }
+    }
+
+    trades
+}
+
+/// Open shares per ticker across `trades`.
+fn open_quantities(trades: &[Trade]) -> HashMap<&str, i32> {
+    let mut quantities: HashMap<&str, i32> = HashMap::new();
+    for trade in trades
+        .iter()
+        .filter(|trade| trade.status == TradeStatus::Active)
+    {
+        *quantities.entry(trade.ticker.as_str()).or_default() += trade.quantity;
+    }
+    quantities
+}
+
+/// Rebuilds the account's pre-existing broker trades as live trades of `strategy_id` so the
+/// planner manages them. Orders the engine already knows about are left out of the replay.
+pub async fn run(app: &AppContext, options: ImportBrokerHistoryOptions) -> Result<()> {
+    let db = app.database().await?;
+    let creds = db
+        .get_account_credentials(&options.account_id)
+        .await?
+        .ok_or_else(|| anyhow!("Account {} not found", options.account_id))?;
+    if !creds.provider.eq_ignore_ascii_case("alpaca") {
+        return Err(anyhow!(
+            "Account {} uses provider {}; broker history can only be imported from Alpaca",
+            options.account_id,
+            creds.provider
+        ));
+    }
+    let strategy = db
+        .get_strategy_config(&options.strategy_id)
+        .await?
+        .ok_or_else(|| anyhow!("Strategy {} not found", options.strategy_id))?;
+    if strategy.account_id.as_deref() != Some(options.account_id.as_str()) {
+        return Err(anyhow!(
+            "Strategy {} is not linked to account {}",
+            options.strategy_id,
+            options.account_id
+        ));
+    }
+
+    let settings = app.database_settings(&db).await?;
+    let http_client = Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .context("failed to create HTTP client for broker history")?;
+    let client = AlpacaClient::new(&http_client, &creds, &settings)?;
+
+    let known_order_ids = db.get_account_order_ids(&options.account_id).await?;
+    let since = options
+        .since
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|value| value.and_utc());
+    let fills: Vec<FilledOrder> = client
+        .fetch_filled_orders(since)
+        .await?
+        .into_iter()
+        .filter(|fill| !known_order_ids.contains(&fill.order_id))
+        .collect();
+    let stops: Vec<OpenStopOrder> = client
+        .fetch_open_stop_orders()
+        .await?
+        .into_iter()
+        .filter(|stop| !known_order_ids.contains(&stop.order_id))
+        .collect();
+    let trades = build_imported_trades(&options.strategy_id, &fills, &stops);
+
+    // The replay should account for every broker share the engine does not already track.
+    let account_state = client.fetch_account_state().await?;
+    let mut managed: HashMap<String, i32> = HashMap::new();
+    for candidate in db.get_live_trades_with_accounts().await? {
+        if candidate.account_id == options.account_id
+            && candidate.trade.status == TradeStatus::Active
+        {
+            *managed.entry(candidate.trade.ticker).or_default() += candidate.trade.quantity;
+        }
+    }
+    let imported = open_quantities(&trades);
+    let mut tickers: HashSet<&str> = imported.keys().copied().collect();
+    tickers.extend(
+        account_state
+            .positions
+            .iter()
+            .map(|position| position.ticker.as_str()),
+    );
+    for ticker in tickers {
+        let held = account_state
+            .positions
+            .iter()
+            .find(|position| position.ticker == ticker)
+            .map_or(0, |position| position.quantity);
+        let expected = held - managed.get(ticker).copied().unwrap_or(0);
+        let rebuilt = imported.get(ticker).copied().unwrap_or(0);
+        if rebuilt != expected {
+            warn!(
+                "{}: broker holds {} untracked shares but the order history rebuilds {}; the history may start after the position was opened (try an earlier --since)",
+                ticker, expected, rebuilt
+            );
+        }
+    }
+    for trade in trades
+        .iter()
+        .filter(|trade| trade.stop_loss.is_some() && trade.stop_order_id.is_none())
+    {
+        warn!(
+            "{}: no resting stop matches the {} shares of {}; the planner will place a new stop at {:.4}, so cancel any broker stop covering these shares",
+            trade.ticker,
+            trade.quantity,
+            trade.id,
+            trade.stop_loss.unwrap_or_default()
+        );
+    }
+
+    let open_count = trades
+        .iter()
+        .filter(|trade| trade.status == TradeStatus::Active)
+        .count();
+    if options.dry_run {
+        for trade in &trades {
+            info!(
+                "[dry-run] {} {} {} x{} @ {:.4} on {} ({}){}",
+                trade.id,
+                trade.status.as_str(),
+                trade.ticker,
+                trade.quantity,
+                trade.price,
+                trade.date.date_naive(),
+                trade
+                    .exit_price
+                    .map(|price| format!("exit {:.4}", price))
+                    .unwrap_or_else(|| "open".to_string()),
+                trade
+                    .stop_loss
+                    .map(|stop| format!(", stop {:.4}", stop))
+                    .unwrap_or_default()
+            );
+        }
+        info!(
+            "[dry-run] Would import {} trades ({} open) from {} filled broker orders into strategy {}",
+            trades.len(),
+            open_count,
+            fills.len(),
+            options.strategy_id
+        );
+        return Ok(());
+    }
+
+    let inserted = db
+        .insert_imported_trades(&options.strategy_id, &trades)
+        .await?;
+    info!(
+        "Imported {} of {} trades ({} open) from {} filled broker orders into strategy {} of account {}; the rest already existed or trade unknown tickers",
+        inserted,
+        trades.len(),
+        open_count,
+        fills.len(),
+        options.strategy_id,
+        options.account_id
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fill(order_id: &str, side: &str, quantity: f64, price: f64, day: u32) -> FilledOrder {
+        FilledOrder {
+            order_id: order_id.to_string(),
+            symbol: "AAPL".to_string(),
+            side: side.to_string(),
+            quantity,
+            price,
+            filled_at: Utc.with_ymd_and_hms(2025, 3, day, 15, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn replays_fills_into_fifo_lots_with_matching_stops() {
+        let fills = vec![
+            fill("b1", "buy", 10.0, 100.0, 3),
+            fill("b2", "buy", 5.0, 110.0, 4),
+            fill("s1", "sell", 12.0, 120.0, 5),
+        ];
+        let stops = vec![OpenStopOrder {
+            order_id: "stop-1".to_string(),
+            symbol: "AAPL".to_string(),
+            side: "sell".to_string(),
+            quantity: 3.0,
+            stop_price: 105.0,
+        }];
+
+        let trades = build_imported_trades("strategy-1", &fills, &stops);
+        let ids: Vec<&str> = trades.iter().map(|trade| trade.id.as_str()).collect();
+        assert_eq!(ids, vec!["import-b1-s1", "import-b2-s1", "import-b2"]);
+
+        assert_eq!(trades[0].quantity, 10);
+        assert_eq!(trades[0].status, TradeStatus::Closed);
+        assert_eq!(trades[0].pnl, Some(200.0));
+        assert_eq!(trades[1].quantity, 2);
+        assert_eq!(trades[1].pnl, Some(20.0));
+        assert_eq!(trades[1].entry_order_id.as_deref(), Some("b2"));
+        assert_eq!(trades[1].exit_order_id.as_deref(), Some("s1"));
+
+        let open = &trades[2];
+        assert_eq!(open.status, TradeStatus::Active);
+        assert_eq!(open.quantity, 3);
+        assert_eq!(open.price, 110.0);
+        assert_eq!(open.date.date_naive().to_string(), "2025-03-04");
+        assert_eq!(open.stop_loss, Some(105.0));
+        assert_eq!(open.stop_order_id.as_deref(), Some("stop-1"));
+    }
+}
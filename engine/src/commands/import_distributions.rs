@@ -0,0 +1,56 @@
+use super::csv_import::{parse_date, parse_rows, read_rows, report_stored};
+use crate::context::AppContext;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::path::Path;
+
+/// Reads `ticker,ex_date,amount` rows (YYYY-MM-DD, a positive cash amount per share).
+fn parse_distributions_csv(contents: &str) -> Result<Vec<(String, NaiveDate, f64)>> {
+    parse_rows(
+        contents,
+        1,
+        "ticker,YYYY-MM-DD,amount row with a positive amount",
+        |fields| match fields {
+            [ticker, ex_date, amount] if !ticker.is_empty() => {
+                let amount = amount.parse::<f64>().ok()?;
+                (amount.is_finite() && amount > 0.0).then_some((
+                    ticker.to_string(),
+                    parse_date(ex_date)?,
+                    amount,
+                ))
+            }
+            _ => None,
+        },
+    )
+}
+
+/// Stores the ETF cash distributions in `path`. Rows of tickers not in `tickers` are skipped.
+pub async fn run(app: &AppContext, path: &Path) -> Result<()> {
+    let rows = read_rows(path, "distribution", parse_distributions_csv)?;
+    let stored = app
+        .database()
+        .await?
+        .upsert_ticker_distributions(&rows)
+        .await?;
+    report_stored(path, "distributions", rows.len(), stored);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rows_and_rejects_non_positive_amounts() {
+        assert_eq!(
+            parse_distributions_csv("ticker,ex_date,amount\nSPY,2024-06-21,1.76\n").unwrap(),
+            vec![(
+                "SPY".to_string(),
+                NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+                1.76
+            )]
+        );
+        assert!(parse_distributions_csv("SPY,2024-06-21,0\n").is_err());
+        assert!(parse_distributions_csv("SPY,2024-06-21,1.76\nQQQ,2024-06-24,\n").is_err());
+    }
+}
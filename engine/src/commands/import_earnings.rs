@@ -0,0 +1,43 @@
+use super::csv_import::{parse_date, parse_rows, read_rows, report_stored};
+use crate::context::AppContext;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::path::Path;
+
+/// Reads `ticker,report_date` rows (YYYY-MM-DD).
+fn parse_earnings_csv(contents: &str) -> Result<Vec<(String, NaiveDate)>> {
+    parse_rows(
+        contents,
+        1,
+        "ticker,YYYY-MM-DD row",
+        |fields| match fields {
+            [ticker, date] if !ticker.is_empty() => Some((ticker.to_string(), parse_date(date)?)),
+            _ => None,
+        },
+    )
+}
+
+/// Stores the earnings report dates in `path`. Rows of tickers not in `tickers` are skipped.
+pub async fn run(app: &AppContext, path: &Path) -> Result<()> {
+    let rows = read_rows(path, "earnings", parse_earnings_csv)?;
+    let stored = app.database().await?.upsert_earnings_dates(&rows).await?;
+    report_stored(path, "earnings dates", rows.len(), stored);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ticker_and_report_date() {
+        assert_eq!(
+            parse_earnings_csv("ticker,report_date\nAAPL,2025-01-30\n").unwrap(),
+            vec![(
+                "AAPL".to_string(),
+                NaiveDate::from_ymd_opt(2025, 1, 30).unwrap()
+            )]
+        );
+        assert!(parse_earnings_csv("AAPL,2025-01-30\n,2025-01-29\n").is_err());
+    }
+}
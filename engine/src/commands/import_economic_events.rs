@@ -0,0 +1,57 @@
+use super::csv_import::{parse_date, parse_rows, read_rows};
+use crate::context::AppContext;
+use crate::models::EconomicEventKind;
+use anyhow::Result;
+use chrono::NaiveDate;
+use log::info;
+use std::path::Path;
+
+/// Reads `event_type,event_date` rows (FOMC or CPI, YYYY-MM-DD).
+fn parse_economic_events_csv(contents: &str) -> Result<Vec<(EconomicEventKind, NaiveDate)>> {
+    parse_rows(
+        contents,
+        1,
+        "FOMC|CPI,YYYY-MM-DD row",
+        |fields| match fields {
+            [kind, date] => Some((kind.parse().ok()?, parse_date(date)?)),
+            _ => None,
+        },
+    )
+}
+
+/// Stores the FOMC and CPI release days in `path`.
+pub async fn run(app: &AppContext, path: &Path) -> Result<()> {
+    let rows = read_rows(path, "economic event", parse_economic_events_csv)?;
+    let stored = app.database().await?.upsert_economic_events(&rows).await?;
+    info!(
+        "Stored {} new economic events ({} rows) from {}",
+        stored,
+        rows.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_event_types_only() {
+        assert_eq!(
+            parse_economic_events_csv("event_type,event_date\nfomc,2025-01-29\nCPI,2025-02-12\n")
+                .unwrap(),
+            vec![
+                (
+                    EconomicEventKind::Fomc,
+                    NaiveDate::from_ymd_opt(2025, 1, 29).unwrap()
+                ),
+                (
+                    EconomicEventKind::Cpi,
+                    NaiveDate::from_ymd_opt(2025, 2, 12).unwrap()
+                ),
+            ]
+        );
+        assert!(parse_economic_events_csv("FOMC,2025-01-29\nNFP,2025-02-07\n").is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use super::csv_import::{parse_date, parse_rows, read_rows, report_stored};
+use crate::context::AppContext;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::path::Path;
+
+/// Reads `ticker,date,score` rows (YYYY-MM-DD, scores from -1 to 1).
+fn parse_sentiment_csv(contents: &str) -> Result<Vec<(String, NaiveDate, f64)>> {
+    parse_rows(
+        contents,
+        1,
+        "ticker,YYYY-MM-DD,score row with a score from -1 to 1",
+        |fields| match fields {
+            [ticker, date, score] if !ticker.is_empty() => {
+                let score = score.parse::<f64>().ok()?;
+                (-1.0..=1.0).contains(&score).then_some((
+                    ticker.to_string(),
+                    parse_date(date)?,
+                    score,
+                ))
+            }
+            _ => None,
+        },
+    )
+}
+
+/// Stores the daily news sentiment scores in `path`. Rows of tickers not in `tickers` are
+/// skipped.
+pub async fn run(app: &AppContext, path: &Path) -> Result<()> {
+    let rows = read_rows(path, "sentiment", parse_sentiment_csv)?;
+    let stored = app.database().await?.upsert_ticker_sentiment(&rows).await?;
+    report_stored(path, "sentiment scores", rows.len(), stored);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_scores_and_rejects_out_of_range_ones() {
+        let day = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        assert_eq!(
+            parse_sentiment_csv("ticker,date,score\nAAPL,2024-05-15,0.42\nMSFT,2024-05-15,-1\n")
+                .unwrap(),
+            vec![
+                ("AAPL".to_string(), day, 0.42),
+                ("MSFT".to_string(), day, -1.0),
+            ]
+        );
+        assert!(parse_sentiment_csv("AAPL,2024-05-15,1.5\n").is_err());
+        assert!(parse_sentiment_csv("AAPL,2024-05-15,0.2\nMSFT,2024-05-15,\n").is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use super::csv_import::{parse_date, parse_rows, read_rows, report_stored};
+use crate::context::AppContext;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::path::Path;
+
+type ShortInterestRow = (String, NaiveDate, Option<f64>, Option<f64>);
+
+/// Parses a non-negative ratio; an empty field is `Some(None)`.
+fn parse_optional_ratio(value: &str) -> Option<Option<f64>> {
+    if value.is_empty() {
+        return Some(None);
+    }
+    let ratio = value.parse::<f64>().ok()?;
+    (ratio.is_finite() && ratio >= 0.0).then_some(Some(ratio))
+}
+
+/// Reads `ticker,report_date,short_interest_ratio,borrow_fee_rate` rows (YYYY-MM-DD, ratios
+/// such as 0.12 for 12%); either ratio may be left empty but not both.
+fn parse_short_interest_csv(contents: &str) -> Result<Vec<ShortInterestRow>> {
+    parse_rows(
+        contents,
+        1,
+        "ticker,YYYY-MM-DD,short_interest_ratio,borrow_fee_rate row",
+        |fields| {
+            let (ticker, date, short_interest_ratio, borrow_fee_rate) = match fields {
+                [ticker, date, ratio] => (ticker, date, ratio, &""),
+                [ticker, date, ratio, fee] => (ticker, date, ratio, fee),
+                _ => return None,
+            };
+            let short_interest_ratio = parse_optional_ratio(short_interest_ratio)?;
+            let borrow_fee_rate = parse_optional_ratio(borrow_fee_rate)?;
+            if ticker.is_empty() || (short_interest_ratio.is_none() && borrow_fee_rate.is_none()) {
+                return None;
+            }
+            Some((
+                ticker.to_string(),
+                parse_date(date)?,
+                short_interest_ratio,
+                borrow_fee_rate,
+            ))
+        },
+    )
+}
+
+/// Stores the short interest and borrow fee reports in `path`. Rows of tickers not in
+/// `tickers` are skipped.
+pub async fn run(app: &AppContext, path: &Path) -> Result<()> {
+    let rows = read_rows(path, "short interest", parse_short_interest_csv)?;
+    let stored = app.database().await?.upsert_short_interest(&rows).await?;
+    report_stored(path, "short interest reports", rows.len(), stored);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rows_with_either_ratio() {
+        let day = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        assert_eq!(
+            parse_short_interest_csv(
+                "ticker,report_date,short_interest_ratio,borrow_fee_rate\nGME,2024-05-15,0.24,\nAMC,2024-05-15,,0.35\n",
+            )
+            .unwrap(),
+            vec![
+                ("GME".to_string(), day, Some(0.24), None),
+                ("AMC".to_string(), day, None, Some(0.35)),
+            ]
+        );
+        assert!(parse_short_interest_csv("GME,2024-05-15,,\n").is_err());
+        assert!(parse_short_interest_csv("GME,2024-05-15,0.2,0.1\nAMC,2024-05-15,-1,\n").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use super::csv_import::{parse_date, parse_rows, read_rows, report_stored};
+use crate::context::AppContext;
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use std::path::Path;
+
+type MembershipRow = (String, NaiveDate, Option<NaiveDate>);
+
+/// Reads `ticker,start_date,end_date` rows (YYYY-MM-DD); a blank end date means the ticker is
+/// still a member.
+fn parse_universe_csv(contents: &str) -> Result<Vec<MembershipRow>> {
+    parse_rows(
+        contents,
+        1,
+        "ticker,YYYY-MM-DD[,YYYY-MM-DD] row ending after it starts",
+        |fields| {
+            let (ticker, start, end) = match fields {
+                [ticker, start] => (ticker, start, &""),
+                [ticker, start, end] => (ticker, start, end),
+                _ => return None,
+            };
+            let start = parse_date(start)?;
+            let end = match *end {
+                "" => None,
+                end => Some(parse_date(end)?),
+            };
+            (!ticker.is_empty() && end.is_none_or(|end| end > start))
+                .then(|| (ticker.to_string(), start, end))
+        },
+    )
+}
+
+/// Replaces the memberships of universe `name` with the intervals in `path`. Rows of tickers
+/// not in `tickers` are skipped.
+pub async fn run(app: &AppContext, name: &str, path: &Path) -> Result<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Universe name must not be blank"));
+    }
+    let rows = read_rows(path, "membership", parse_universe_csv)?;
+    let db = app.database().await?;
+    let stored = db.replace_universe_memberships(name, &rows).await?;
+    report_stored(
+        path,
+        &format!("membership intervals of universe {}", name),
+        rows.len(),
+        stored,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_open_and_closed_intervals() {
+        let rows = parse_universe_csv(
+            "ticker,start_date,end_date\nAAPL,1982-11-30,\nENRN,1990-01-02,2001-11-29\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("AAPL".to_string(), day("1982-11-30"), None),
+                (
+                    "ENRN".to_string(),
+                    day("1990-01-02"),
+                    Some(day("2001-11-29"))
+                ),
+            ]
+        );
+        assert!(parse_universe_csv("AAPL,2020-01-02,2019-01-02\n").is_err());
+        assert!(parse_universe_csv("AAPL,2020-01-02\nMSFT,someday\n").is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::fs;
+use uuid::Uuid;
+
+use crate::commands::train_lightgbm::{
+    build_training_datasets, resolve_lightgbm_executable, score_validation_rows, train_and_predict,
+    LabelingOptions, LightgbmHyperparameters, TrainingDatasets, TrainingRow,
+};
+use crate::context::AppContext;
+use crate::strategy::lightgbm::features::FeatureSet;
+
+/// Seed of the first label shuffle; permutation `i` uses `seed + i`.
+pub const DEFAULT_PERMUTATION_SEED: u64 = 1729;
+
+/// Validation ndcg of the model trained on real labels against models trained on shuffled
+/// labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermutationTest {
+    pub real_score: f64,
+    pub null_scores: Vec<f64>,
+}
+
+impl PermutationTest {
+    /// Share of label shuffles (counting the real labels as one) scoring at least as well as
+    /// the real labels.
+    pub fn p_value(&self) -> f64 {
+        let at_least_as_good = self
+            .null_scores
+            .iter()
+            .filter(|score| **score >= self.real_score)
+            .count();
+        (at_least_as_good + 1) as f64 / (self.null_scores.len() + 1) as f64
+    }
+
+    fn null_mean_and_std(&self) -> (f64, f64) {
+        let count = self.null_scores.len().max(1) as f64;
+        let mean = self.null_scores.iter().sum::<f64>() / count;
+        let variance = self
+            .null_scores
+            .iter()
+            .map(|score| (score - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        (mean, variance.sqrt())
+    }
+}
+
+/// Permutes the labels of `rows` among themselves, keeping each row's features, date and
+/// weight, so the features can no longer explain the labels.
+fn shuffle_labels(rows: &mut [TrainingRow], rng: &mut StdRng) {
+    let mut labels: Vec<(u8, u8, f64)> = rows
+        .iter()
+        .map(|row| (row.label, row.rank_label, row.max_multiple))
+        .collect();
+    labels.shuffle(rng);
+    for (row, (label, rank_label, max_multiple)) in rows.iter_mut().zip(labels) {
+        row.label = label;
+        row.rank_label = rank_label;
+        row.max_multiple = max_multiple;
+    }
+}
+
+/// Trains into a temporary model file and returns the validation ndcg, or None when LightGBM
+/// could not score the validation rows.
+fn train_and_score(
+    hyperparameters: &LightgbmHyperparameters,
+    train_rows: &[TrainingRow],
+    validation_rows: &[TrainingRow],
+) -> Result<Option<f64>> {
+    let exe_path = resolve_lightgbm_executable()?;
+    let model_path =
+        std::env::temp_dir().join(format!("lightgbm_permutation_{}.txt", Uuid::new_v4()));
+    let scores = train_and_predict(
+        &exe_path,
+        hyperparameters,
+        train_rows,
+        validation_rows,
+        &model_path,
+    );
+    let _ = fs::remove_file(&model_path);
+    Ok(scores?.and_then(|scores| score_validation_rows(validation_rows, &scores).ndcg_at_k))
+}
+
+/// Trains the LightGBM pipeline on the real training labels and on `permutations` shuffles of
+/// them, scores every model on the unshuffled validation rows and prints the p-value of the
+/// real validation ndcg under the shuffled-label null distribution. No model is saved.
+pub async fn run(
+    app: &AppContext,
+    hyperparameters: LightgbmHyperparameters,
+    features: FeatureSet,
+    labeling: LabelingOptions,
+    permutations: usize,
+    seed: u64,
+) -> Result<()> {
+    labeling.validate()?;
+    if permutations == 0 {
+        return Err(anyhow!("--permutations must be at least 1"));
+    }
+    let db = app.database().await?;
+    let TrainingDatasets {
+        train_rows,
+        validation_rows,
+    } = build_training_datasets(app, &db, &features, &labeling).await?;
+
+    info!("Training LightGBM on the real labels");
+    let real_score = train_and_score(&hyperparameters, &train_rows, &validation_rows)?
+        .ok_or_else(|| anyhow!("The model trained on real labels has no validation ndcg"))?;
+
+    let mut null_scores = Vec::with_capacity(permutations);
+    for permutation in 0..permutations {
+        app.cancellation().check()?;
+        info!(
+            "Training LightGBM on shuffled labels {}/{}",
+            permutation + 1,
+            permutations
+        );
+        let mut shuffled = train_rows.clone();
+        shuffle_labels(
+            &mut shuffled,
+            &mut StdRng::seed_from_u64(seed + permutation as u64),
+        );
+        match train_and_score(&hyperparameters, &shuffled, &validation_rows)? {
+            Some(score) => null_scores.push(score),
+            None => warn!(
+                "Shuffled labels {}/{} produced no validation ndcg",
+                permutation + 1,
+                permutations
+            ),
+        }
+    }
+    if null_scores.is_empty() {
+        return Err(anyhow!("No shuffled-label model could be scored"));
+    }
+
+    let test = PermutationTest {
+        real_score,
+        null_scores,
+    };
+    let (null_mean, null_std) = test.null_mean_and_std();
+    println!(
+        "Validation ndcg: real labels {:.4}, shuffled labels {:.4} ± {:.4} over {} permutations",
+        test.real_score,
+        null_mean,
+        null_std,
+        test.null_scores.len()
+    );
+    println!("Permutation p-value: {:.4}", test.p_value());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn p_value_counts_the_real_labels_as_one_permutation() {
+        let test = PermutationTest {
+            real_score: 0.3,
+            null_scores: vec![0.1, 0.2, 0.3, 0.25],
+        };
+        assert!((test.p_value() - 2.0 / 5.0).abs() < 1e-12);
+        let (mean, std) = test.null_mean_and_std();
+        assert!((mean - 0.2125).abs() < 1e-12);
+        assert!(std > 0.0);
+
+        let beaten = PermutationTest {
+            real_score: 0.5,
+            null_scores: vec![0.1; 19],
+        };
+        assert!((beaten.p_value() - 0.05).abs() < 1e-12);
+    }
+}
@@ -1,4 +1,9 @@
+use crate::data_context::MarketData;
+use crate::optimizer_status::OptimizerStatus;
+use crate::storage::{SqliteStorage, Storage};
 use anyhow::{anyhow, Result};
+use log::info;
+use std::collections::HashMap;
 use std::path::Path;
 use tokio::fs;
 
@@ -12,3 +17,22 @@ pub async fn ensure_market_data_file(path: &Path) -> Result<()> {
         path.display()
     ))
 }
+
+/// Seeds the local store with the snapshot settings it does not have yet and returns the
+/// stored settings, so values edited in the SQLite file take precedence.
+pub async fn local_store_settings(
+    store: &SqliteStorage,
+    path: &Path,
+) -> Result<HashMap<String, String>> {
+    let status = OptimizerStatus::new();
+    let snapshot = MarketData::load_from_file(path, &status)?;
+    let seeded = store.seed_settings(snapshot.settings())?;
+    if seeded > 0 {
+        info!(
+            "Copied {} setting(s) from the market data snapshot into {}",
+            seeded,
+            store.path().display()
+        );
+    }
+    store.get_all_settings().await
+}
@@ -1,11 +1,45 @@
+pub mod audit_lookahead;
 pub mod backtest_accounts;
 pub mod backtest_active;
 pub mod balance;
+pub mod bench;
+pub mod calibration_report;
+pub mod capital_scaling;
+pub mod check_data;
+pub mod config_show;
+mod csv_import;
+pub mod detect_drift;
+pub mod execution_quality;
 pub mod export_market_data;
+pub mod export_trades;
+pub mod exposure;
 pub mod generate_signals;
+pub mod idle_tickers;
+pub mod import_auxiliary;
+pub mod import_broker_history;
+pub mod import_distributions;
+pub mod import_earnings;
+pub mod import_economic_events;
+pub mod import_sentiment;
+pub mod import_short_interest;
+pub mod import_universe;
+pub mod lightgbm_permutation;
 pub mod market_data_snapshot;
 pub mod optimize;
+pub mod pipeline;
 pub mod plan_operations;
+pub mod prune_backtests;
 pub mod reconcile_trades;
+pub mod rotate_key;
+pub mod sensitivity;
+pub mod serve;
+pub mod set_fx_rate;
+pub mod shadow_compare;
+pub mod signal_stats;
 pub mod train_lightgbm;
+pub mod train_nn;
+pub mod try_parameters;
 pub mod verify;
+pub mod watch_orders;
+pub mod what_if;
+pub mod worker;
@@ -1,13 +1,84 @@
-use crate::commands::market_data_snapshot::ensure_market_data_file;
+use crate::commands::market_data_snapshot::{ensure_market_data_file, local_store_settings};
 use crate::config::{require_setting_date, EngineRuntimeSettings};
 use crate::context::{AppContext, MarketDataFilters};
 use crate::data_context::{MarketData, TickerScope};
+use crate::memory_guard::{self, MemoryGuard};
+use crate::optimizer::OptimizationBudget;
 use crate::optimizer_status::OptimizerStatus;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use log::{info, warn};
 use std::path::Path;
+use std::time::Duration;
 
-pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -> Result<()> {
+/// Smallest objective gain that resets the plateau counter.
+pub const DEFAULT_PLATEAU_EPSILON: f64 = 1e-4;
+
+#[derive(Debug, Clone)]
+pub struct OptimizeOptions {
+    pub max_evals: Option<usize>,
+    pub max_hours: Option<f64>,
+    pub plateau_evals: Option<usize>,
+    pub plateau_epsilon: f64,
+    pub max_memory_gb: Option<f64>,
+}
+
+impl Default for OptimizeOptions {
+    fn default() -> Self {
+        Self {
+            max_evals: None,
+            max_hours: None,
+            plateau_evals: None,
+            plateau_epsilon: DEFAULT_PLATEAU_EPSILON,
+            max_memory_gb: None,
+        }
+    }
+}
+
+impl OptimizeOptions {
+    fn budget(&self) -> Result<OptimizationBudget> {
+        if self.max_evals == Some(0) || self.plateau_evals == Some(0) {
+            return Err(anyhow!("--max-evals and --plateau-evals must be positive"));
+        }
+        let max_duration = match self.max_hours {
+            Some(hours) if !hours.is_finite() || hours <= 0.0 => {
+                return Err(anyhow!("--max-hours must be a positive number"));
+            }
+            Some(hours) => Some(Duration::from_secs_f64(hours * 3600.0)),
+            None => None,
+        };
+        if !self.plateau_epsilon.is_finite() || self.plateau_epsilon < 0.0 {
+            return Err(anyhow!("--plateau-epsilon must be zero or positive"));
+        }
+        Ok(OptimizationBudget {
+            max_evals: self.max_evals,
+            max_duration,
+            plateau_evals: self.plateau_evals,
+            plateau_epsilon: self.plateau_epsilon,
+        })
+    }
+
+    fn memory_guard(&self) -> Result<Option<MemoryGuard>> {
+        match self.max_memory_gb {
+            Some(limit) if !limit.is_finite() || limit <= 0.0 => {
+                Err(anyhow!("--max-memory-gb must be a positive number"))
+            }
+            Some(limit) => Ok(Some(MemoryGuard::from_gigabytes(limit))),
+            None => Ok(None),
+        }
+    }
+}
+
+pub async fn run(
+    app: &AppContext,
+    template_id: &str,
+    market_data_file: &Path,
+    options: &OptimizeOptions,
+) -> Result<()> {
+    let budget = options.budget()?;
+    let memory_guard = options.memory_guard()?;
+    if memory_guard.is_some() && memory_guard::resident_bytes().is_none() {
+        warn!("--max-memory-gb is not enforced: resident memory is unavailable on this platform");
+    }
     info!(
         "Received optimize command for template_id={} (auto parameter detection)",
         template_id
@@ -18,16 +89,20 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
         market_data_file.display()
     );
 
-    let settings = match app.database().await {
-        Ok(db) => db.get_all_settings().await?,
-        Err(error) => {
-            warn!(
-                "Database unavailable ({}). Using settings from market data snapshot.",
-                error
-            );
-            let status = OptimizerStatus::new();
-            let snapshot = MarketData::load_from_file(market_data_file, &status)?;
-            snapshot.settings().clone()
+    let settings = if let Some(store) = app.local_store()? {
+        app.snapshot_settings(&local_store_settings(&store, market_data_file).await?)
+    } else {
+        match app.database().await {
+            Ok(db) => app.database_settings(&db).await?,
+            Err(error) => {
+                warn!(
+                    "Database unavailable ({}). Using settings from market data snapshot.",
+                    error
+                );
+                let status = OptimizerStatus::new();
+                let snapshot = MarketData::load_from_file(market_data_file, &status)?;
+                app.snapshot_settings(snapshot.settings())
+            }
         }
     };
     let training_start = require_setting_date(&settings, "OPTIMIZER_TRAINING_START_DATE")?;
@@ -54,9 +129,9 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
             }),
         )
         .await?;
-    let mut optimizer = context.optimizer();
+    let mut optimizer = context.optimizer().with_memory_guard(memory_guard);
     let (param_names, param_ranges) = optimizer.detect_optimizable_parameters(template_id).await?;
     optimizer
-        .optimize_local_search(template_id, &param_names, &param_ranges)
+        .optimize_local_search(template_id, &param_names, &param_ranges, &budget)
         .await
 }
@@ -0,0 +1,319 @@
+use crate::commands::{
+    backtest_accounts, backtest_active, balance, export_market_data, generate_signals, optimize,
+    plan_operations::{self, PlanOperationsOptions},
+    reconcile_trades, verify,
+};
+use crate::context::AppContext;
+use crate::data_context::DEFAULT_SNAPSHOT_COMPRESSION_LEVEL;
+use anyhow::{anyhow, Context, Result};
+use log::{error, info};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PipelineConfig {
+    steps: Vec<PipelineStep>,
+}
+
+/// One engine command in a pipeline file, named like the CLI subcommand.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) enum PipelineStep {
+    Optimize {
+        template_id: String,
+        data_file: Option<PathBuf>,
+        max_evals: Option<usize>,
+        max_hours: Option<f64>,
+        plateau_evals: Option<usize>,
+        plateau_epsilon: Option<f64>,
+        max_memory_gb: Option<f64>,
+    },
+    Verify {
+        template_id: String,
+        data_file: Option<PathBuf>,
+        ticker_folds: Option<usize>,
+        #[serde(default)]
+        perturb: bool,
+        anchored_windows: Option<usize>,
+    },
+    Balance {
+        template_id: String,
+        data_file: Option<PathBuf>,
+    },
+    GenerateSignals,
+    BacktestActive {
+        #[serde(default = "default_backtest_scope")]
+        scope: backtest_active::BacktestScope,
+        #[serde(default)]
+        months: Vec<u32>,
+        #[serde(default)]
+        tickers: Vec<String>,
+        tickers_file: Option<PathBuf>,
+        exclude_file: Option<PathBuf>,
+    },
+    BacktestAccounts,
+    PlanOperations,
+    ReconcileTrades,
+    ExportMarketData {
+        output: Option<PathBuf>,
+        compression_level: Option<i32>,
+        #[serde(default)]
+        no_compress: bool,
+    },
+}
+
+fn default_backtest_scope() -> backtest_active::BacktestScope {
+    backtest_active::BacktestScope::Validation
+}
+
+impl PipelineStep {
+    pub(crate) fn label(&self) -> String {
+        match self {
+            PipelineStep::Optimize { template_id, .. } => format!("optimize {}", template_id),
+            PipelineStep::Verify { template_id, .. } => format!("verify {}", template_id),
+            PipelineStep::Balance { template_id, .. } => format!("balance {}", template_id),
+            PipelineStep::GenerateSignals => "generate-signals".to_string(),
+            PipelineStep::BacktestActive { .. } => "backtest-active".to_string(),
+            PipelineStep::BacktestAccounts => "backtest-accounts".to_string(),
+            PipelineStep::PlanOperations => "plan-operations".to_string(),
+            PipelineStep::ReconcileTrades => "reconcile-trades".to_string(),
+            PipelineStep::ExportMarketData { .. } => "export-market-data".to_string(),
+        }
+    }
+
+    pub(crate) async fn execute(&self, app: &AppContext, default_data_file: &Path) -> Result<()> {
+        let data_path = |value: &Option<PathBuf>| {
+            value
+                .clone()
+                .unwrap_or_else(|| default_data_file.to_path_buf())
+        };
+        match self {
+            PipelineStep::Optimize {
+                template_id,
+                data_file,
+                max_evals,
+                max_hours,
+                plateau_evals,
+                plateau_epsilon,
+                max_memory_gb,
+            } => {
+                let options = optimize::OptimizeOptions {
+                    max_evals: *max_evals,
+                    max_hours: *max_hours,
+                    plateau_evals: *plateau_evals,
+                    plateau_epsilon: plateau_epsilon.unwrap_or(optimize::DEFAULT_PLATEAU_EPSILON),
+                    max_memory_gb: *max_memory_gb,
+                };
+                optimize::run(app, template_id, &data_path(data_file), &options).await
+            }
+            PipelineStep::Verify {
+                template_id,
+                data_file,
+                ticker_folds,
+                perturb,
+                anchored_windows,
+            } => {
+                let options = verify::VerifyOptions {
+                    ticker_folds: *ticker_folds,
+                    perturb: *perturb,
+                    anchored_windows: *anchored_windows,
+                };
+                verify::run(app, template_id, &data_path(data_file), options).await
+            }
+            PipelineStep::Balance {
+                template_id,
+                data_file,
+            } => balance::run(app, template_id, &data_path(data_file)).await,
+            PipelineStep::GenerateSignals => generate_signals::run(app).await,
+            PipelineStep::BacktestActive {
+                scope,
+                months,
+                tickers,
+                tickers_file,
+                exclude_file,
+            } => {
+                let selection = backtest_active::TickerSelection {
+                    tickers: tickers.clone(),
+                    tickers_file: tickers_file.clone(),
+                    exclude_file: exclude_file.clone(),
+                };
+                backtest_active::run(app, *scope, months, &selection).await
+            }
+            PipelineStep::BacktestAccounts => backtest_accounts::run(app, None).await,
+            PipelineStep::PlanOperations => {
+                plan_operations::run(app, PlanOperationsOptions::default(), None).await
+            }
+            PipelineStep::ReconcileTrades => reconcile_trades::run(app).await,
+            PipelineStep::ExportMarketData {
+                output,
+                compression_level,
+                no_compress,
+            } => {
+                let level = compression_level.unwrap_or(DEFAULT_SNAPSHOT_COMPRESSION_LEVEL);
+                export_market_data::run(app, &data_path(output), (!no_compress).then_some(level))
+                    .await
+            }
+        }
+    }
+}
+
+enum StepOutcome {
+    Succeeded(Duration),
+    Failed(Duration),
+    Skipped,
+}
+
+/// Runs the steps of a pipeline file in order, stopping at the first failure.
+pub async fn run(app: &AppContext, config_path: &Path, default_data_file: &Path) -> Result<()> {
+    let text = fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read pipeline {}", config_path.display()))?;
+    let config = parse_pipeline(&text)
+        .with_context(|| format!("Invalid pipeline {}", config_path.display()))?;
+
+    let total_start = Instant::now();
+    let mut outcomes = Vec::with_capacity(config.steps.len());
+    let mut failure = None;
+    for (idx, step) in config.steps.iter().enumerate() {
+        if failure.is_some() {
+            outcomes.push(StepOutcome::Skipped);
+            continue;
+        }
+        info!(
+            "Pipeline step {}/{}: {}",
+            idx + 1,
+            config.steps.len(),
+            step.label()
+        );
+        let start = Instant::now();
+        match step.execute(app, default_data_file).await {
+            Ok(()) => outcomes.push(StepOutcome::Succeeded(start.elapsed())),
+            Err(err) => {
+                outcomes.push(StepOutcome::Failed(start.elapsed()));
+                failure = Some((step.label(), err));
+            }
+        }
+    }
+
+    info!("Pipeline report:");
+    for (idx, (step, outcome)) in config.steps.iter().zip(&outcomes).enumerate() {
+        let status = match outcome {
+            StepOutcome::Succeeded(duration) => format!("ok ({:.1}s)", duration.as_secs_f64()),
+            StepOutcome::Failed(duration) => {
+                format!("FAILED ({:.1}s)", duration.as_secs_f64())
+            }
+            StepOutcome::Skipped => "skipped".to_string(),
+        };
+        info!("  {}. {:<24} {}", idx + 1, step.label(), status);
+    }
+    info!(
+        "Pipeline finished in {:.1}s",
+        total_start.elapsed().as_secs_f64()
+    );
+
+    match failure {
+        Some((label, err)) => {
+            error!("Pipeline step '{}' failed: {:#}", label, err);
+            Err(err.context(format!("Pipeline step '{}' failed", label)))
+        }
+        None => Ok(()),
+    }
+}
+
+fn parse_pipeline(text: &str) -> Result<PipelineConfig> {
+    let config: PipelineConfig = toml::from_str(text)?;
+    if config.steps.is_empty() {
+        return Err(anyhow!("Pipeline must define at least one step"));
+    }
+    Ok(config)
+}
+
+/// Reads a queued job as a pipeline step: `command` names the step and `payload` is a JSON
+/// object of its options.
+pub(crate) fn parse_job_step(command: &str, payload: &str) -> Result<PipelineStep> {
+    let mut options: serde_json::Value = if payload.trim().is_empty() {
+        serde_json::json!({})
+    } else {
+        serde_json::from_str(payload).context("Job payload is not valid JSON")?
+    };
+    let Some(fields) = options.as_object_mut() else {
+        return Err(anyhow!("Job payload must be a JSON object"));
+    };
+    fields.insert("command".to_string(), command.into());
+    serde_json::from_value(options)
+        .with_context(|| format!("Invalid options for job command {}", command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_steps_in_order_with_defaults() {
+        let config = parse_pipeline(
+            r#"
+[[steps]]
+command = "generate-signals"
+
+[[steps]]
+command = "backtest-active"
+months = [12, 24]
+
+[[steps]]
+command = "plan-operations"
+"#,
+        )
+        .unwrap();
+        let labels: Vec<String> = config.steps.iter().map(PipelineStep::label).collect();
+        assert_eq!(
+            labels,
+            vec!["generate-signals", "backtest-active", "plan-operations"]
+        );
+        match &config.steps[1] {
+            PipelineStep::BacktestActive {
+                scope,
+                months,
+                tickers,
+                ..
+            } => {
+                assert!(matches!(scope, backtest_active::BacktestScope::Validation));
+                assert_eq!(months, &vec![12, 24]);
+                assert!(tickers.is_empty());
+            }
+            other => panic!("unexpected step {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_empty_and_unknown_steps() {
+        assert!(parse_pipeline("steps = []").is_err());
+        assert!(parse_pipeline("[[steps]]\ncommand = \"ingest\"").is_err());
+    }
+
+    #[test]
+    fn parses_job_payloads_as_steps() {
+        let step =
+            parse_job_step("optimize", r#"{"template_id": "atr", "max_evals": 50}"#).unwrap();
+        match step {
+            PipelineStep::Optimize {
+                template_id,
+                max_evals,
+                data_file,
+                ..
+            } => {
+                assert_eq!(template_id, "atr");
+                assert_eq!(max_evals, Some(50));
+                assert!(data_file.is_none());
+            }
+            other => panic!("unexpected step {:?}", other),
+        }
+        assert_eq!(
+            parse_job_step("plan-operations", "").unwrap().label(),
+            "plan-operations"
+        );
+        assert!(parse_job_step("optimize", "[]").is_err());
+        assert!(parse_job_step("optimize", r#"{"template_id": "atr", "budget": 1}"#).is_err());
+    }
+}
@@ -1,89 +1,279 @@
-use crate::alpaca::AlpacaClient;
-use crate::config::EngineRuntimeSettings;
+use crate::alerts::{evaluate_skip_alerts, skip_alert_lookback_start, SkipOccurrence};
+use crate::broker::{AccountBroker, Broker};
+use crate::candle_utils::group_all_candles_by_ticker;
+use crate::commands::what_if::WhatIfScenario;
+use crate::config::{
+    resolve_live_capacity_volume_share, resolve_live_halt_check_enabled, EngineRuntimeSettings,
+    LiveCostSettings, SkipAlertSettings,
+};
 use crate::context::AppContext;
-use crate::engine::Engine;
-use anyhow::{Context, Result};
+use crate::database::Database;
+use crate::engine::{AccountStateSnapshot, Engine, PlannedOperations, CIRCUIT_BREAKER_REASON};
+use crate::ensemble::EnsembleVotes;
+use crate::models::{
+    AccountOperationType, AccountSignalSkip, AuxiliaryData, AuxiliarySeries, AuxiliarySeriesMap,
+    Candle, EconomicCalendar, GeneratedSignal, SignalAction, StrategyConfig, Trade,
+};
+use crate::trading_rules::{
+    average_dollar_volume, defer_orders_over_budget, estimate_transaction_cost,
+    throttle_orders_to_capacity, BudgetedOrder, CapacityOrder, TransactionCostModel,
+};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, Months, NaiveDate, NaiveTime, Utc};
 use log::{info, warn};
+use rayon::prelude::*;
 use reqwest::Client;
-use serde_json::json;
-use std::collections::HashSet;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use std::time::Duration;
 
-pub async fn run(app: &AppContext) -> Result<()> {
+const DOLLAR_VOLUME_LOOKBACK_DAYS: usize = 20;
+const TRADING_HALTED_REASON: &str = "trading_halted";
+const SKIP_ALERT_LOG_SOURCE: &str = "skip-alerts";
+const PLAN_LOG_SOURCE: &str = "plan-operations-job";
+
+/// `--dry-run` skips every database write; `--explain` prints a per-signal decision trace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlanOperationsOptions {
+    pub dry_run: bool,
+    pub explain: bool,
+}
+
+struct StrategyPlan {
+    strategy_id: String,
+    strategy_name: String,
+    account_id: String,
+    target_date: DateTime<Utc>,
+    plan: PlannedOperations,
+}
+
+/// A strategy's account state, signals and tickers, gathered before the shared candle load.
+struct PlanningInput {
+    strategy: StrategyConfig,
+    account_id: String,
+    engine: Engine,
+    account_state: AccountStateSnapshot,
+    latest_signal_date: Option<DateTime<Utc>>,
+    signals: Vec<GeneratedSignal>,
+    symbol_list: Vec<String>,
+    existing_trades: Vec<Trade>,
+}
+
+/// Everything `Engine::plan_account_operations` reads for one strategy.
+struct PreparedPlan {
+    strategy: StrategyConfig,
+    account_id: String,
+    engine: Engine,
+    account_state: AccountStateSnapshot,
+    target_date: DateTime<Utc>,
+    signals: Vec<GeneratedSignal>,
+    candles: Vec<Candle>,
+    excluded_tickers: HashSet<String>,
+    existing_trades: Vec<Trade>,
+    existing_buy_operations_today: usize,
+    ensemble_skips: Vec<AccountSignalSkip>,
+}
+
+/// Plans every active account strategy, or with `account_id` only that account's.
+pub async fn run(
+    app: &AppContext,
+    options: PlanOperationsOptions,
+    account_id: Option<&str>,
+) -> Result<()> {
+    plan_accounts(app, options, account_id, None).await
+}
+
+/// Plans every active strategy, or only those of `account_id`, with the hypothetical signals
+/// and cash of `what_if` applied.
+pub(crate) async fn plan_accounts(
+    app: &AppContext,
+    options: PlanOperationsOptions,
+    account_id: Option<&str>,
+    what_if: Option<&WhatIfScenario>,
+) -> Result<()> {
     let mut db = app.database().await?;
-    let settings = db.get_all_settings().await?;
+    let settings = app.database_settings(&db).await?;
     let runtime_settings = EngineRuntimeSettings::from_settings_map(&settings)?;
-    let strategies = db.get_active_strategies().await?;
+    let mut strategies = db.get_active_strategies().await?;
+    if let Some(account_id) = account_id {
+        strategies.retain(|strategy| strategy.account_id.as_deref() == Some(account_id));
+        if strategies.is_empty() {
+            return Err(anyhow!(
+                "No active strategy trades on account {}",
+                account_id
+            ));
+        }
+    }
     if strategies.is_empty() {
         info!("No active strategies found");
         return Ok(());
     }
+    let hypothetical_signals = what_if
+        .map(|scenario| scenario.signals.as_slice())
+        .unwrap_or_default();
 
     let http_client = Client::builder()
         .timeout(Duration::from_secs(30))
         .build()
         .context("failed to create HTTP client for account state fetches")?;
 
+    let capacity_share = resolve_live_capacity_volume_share(&settings);
+    let cost_settings = LiveCostSettings::from_settings_map(&settings);
+    let cost_model = TransactionCostModel {
+        spread_rate: runtime_settings.trade_slippage_rate,
+        impact_coefficient: cost_settings.impact_coefficient,
+        close_fee_rate: runtime_settings.trade_close_fee_rate,
+    };
+    let alert_settings = SkipAlertSettings::from_settings_map(&settings)?;
     let mut processed = 0usize;
     let mut skipped = 0usize;
+    let mut strategy_plans = Vec::new();
+    let mut dollar_volumes: HashMap<String, f64> = HashMap::new();
+    let halt_check_enabled = resolve_live_halt_check_enabled(&settings);
+    let volatility_index_ticker = runtime_settings.volatility_index_ticker.clone();
+    let volatility_index_candles = db
+        .get_candles_for_tickers(std::slice::from_ref(&volatility_index_ticker))
+        .await?;
+    let mut auxiliary_series = AuxiliarySeriesMap::new();
+    if !volatility_index_candles.is_empty() {
+        auxiliary_series.insert(
+            volatility_index_ticker,
+            Arc::new(AuxiliarySeries::from_candles(&volatility_index_candles)),
+        );
+    }
+    let auxiliary_data = Arc::new(AuxiliaryData {
+        ticker_earnings: db.get_ticker_earnings().await?,
+        ticker_sentiment: db.get_ticker_sentiment().await?,
+        auxiliary_series,
+        economic_calendar: EconomicCalendar::from_events(&db.get_economic_events().await?),
+        ..AuxiliaryData::default()
+    });
+    let mut halt_checked: HashSet<String> = HashSet::new();
+    let mut halted_tickers: HashSet<String> = HashSet::new();
+    let mut tripped_accounts: HashSet<String> = HashSet::new();
+
+    // Ensemble accounts need every member's buy votes before any member plans.
+    let ensemble_quorums = db.get_account_ensemble_quorums().await?;
+    let mut ensembles: HashMap<String, EnsembleVotes> = HashMap::new();
+    let mut preloaded_signals = HashMap::new();
+    for strategy in &strategies {
+        let Some(account_id) = strategy.account_id.as_deref() else {
+            continue;
+        };
+        let Some(quorum) = ensemble_quorums.get(account_id) else {
+            continue;
+        };
+        let engine = Engine::from_parameters(&strategy.parameters, runtime_settings.clone());
+        let loaded = load_planning_signals(&db, &strategy.id, &engine).await?;
+        ensembles
+            .entry(account_id.to_string())
+            .or_insert_with(|| EnsembleVotes::new(*quorum))
+            .record(&strategy.id, &loaded.1);
+        preloaded_signals.insert(strategy.id.clone(), loaded);
+    }
+    for (account_id, votes) in &ensembles {
+        if votes.quorum() > votes.member_count() {
+            warn!(
+                "Ensemble account {} needs {} votes but has {} active strateg{}; no buys can open",
+                account_id,
+                votes.quorum(),
+                votes.member_count(),
+                if votes.member_count() == 1 {
+                    "y"
+                } else {
+                    "ies"
+                }
+            );
+        } else {
+            info!(
+                "Ensemble account {}: {} strategies, quorum {}, {} agreed buy{}",
+                account_id,
+                votes.member_count(),
+                votes.quorum(),
+                votes.agreed_count(),
+                if votes.agreed_count() == 1 { "" } else { "s" }
+            );
+        }
+    }
 
+    // Each account is connected and its state fetched once, however many strategies trade it.
+    let mut brokers: HashMap<String, AccountBroker> = HashMap::new();
+    let mut account_states: HashMap<String, AccountStateSnapshot> = HashMap::new();
+    let mut unavailable_accounts: HashMap<String, String> = HashMap::new();
+    let mut inputs = Vec::new();
     for strategy in strategies.into_iter().filter(|s| s.account_id.is_some()) {
         let Some(account_id) = strategy.account_id.clone() else {
             continue;
         };
 
-        let creds = match db.get_account_credentials(&account_id).await? {
-            Some(creds) => creds,
-            None => {
-                skipped += 1;
-                warn!(
-                    "Skipping strategy {} - account {} not found",
-                    strategy.name, account_id
-                );
-                continue;
+        if !account_states.contains_key(&account_id)
+            && !unavailable_accounts.contains_key(&account_id)
+        {
+            match connect_account(&http_client, &db, &settings, &account_id).await? {
+                Ok((broker, mut state)) => {
+                    if let Some(cash) = what_if.and_then(|scenario| scenario.cash) {
+                        state.available_cash = cash;
+                        state.buying_power = Some(cash);
+                    }
+                    brokers.insert(account_id.clone(), broker);
+                    account_states.insert(account_id.clone(), state);
+                }
+                Err(reason) => {
+                    unavailable_accounts.insert(account_id.clone(), reason);
+                }
             }
-        };
-
-        if !creds.provider.eq_ignore_ascii_case("alpaca") {
+        }
+        let Some(mut account_state) = account_states.get(&account_id).cloned() else {
             skipped += 1;
             warn!(
-                "Skipping strategy {} - unsupported account provider {}",
-                strategy.name, creds.provider
+                "Skipping strategy {} - {}",
+                strategy.name, unavailable_accounts[&account_id]
             );
             continue;
-        }
-
-        let alpaca_client = match AlpacaClient::new(&http_client, &creds, &settings) {
-            Ok(client) => client,
-            Err(err) => {
-                skipped += 1;
-                warn!(
-                    "Skipping strategy {} - failed to initialize Alpaca client: {}",
-                    strategy.name, err
-                );
-                continue;
-            }
-        };
-        let account_state = match alpaca_client.fetch_account_state().await {
-            Ok(state) => state,
-            Err(err) => {
-                skipped += 1;
-                warn!(
-                    "Skipping strategy {} - failed to fetch account state: {}",
-                    strategy.name, err
-                );
-                continue;
-            }
         };
 
-        let latest_signal_date = db.get_latest_signal_date(&strategy.id).await?;
-        let signals = if let Some(date) = latest_signal_date {
-            db.get_signals_for_strategy_in_range(&strategy.id, date, date)
-                .await?
-        } else {
-            Vec::new()
+        let mut engine = Engine::from_parameters(&strategy.parameters, runtime_settings.clone());
+        engine.set_auxiliary_data(Arc::clone(&auxiliary_data));
+        if engine.circuit_breaker_tripped(&account_state)
+            && !options.dry_run
+            && tripped_accounts.insert(account_id.clone())
+        {
+            let message = format!(
+                "Circuit breaker tripped for account {}: equity {:.2} vs {:.2} at the previous close; freezing new entries for {} day(s)",
+                account_id,
+                account_state.equity.unwrap_or(0.0),
+                account_state.last_equity.unwrap_or(0.0),
+                engine.config.circuit_breaker.freeze_days
+            );
+            warn!("{}", message);
+            db.insert_system_log(
+                PLAN_LOG_SOURCE,
+                "warn",
+                &message,
+                Some(json!({
+                    "accountId": account_id,
+                    "event": CIRCUIT_BREAKER_REASON,
+                    "strategyId": strategy.id,
+                    "equity": account_state.equity,
+                    "lastEquity": account_state.last_equity,
+                    "maxDailyLoss": engine.config.circuit_breaker.max_daily_loss,
+                    "liquidate": engine.config.circuit_breaker.liquidate,
+                })),
+            )
+            .await?;
+        }
+        account_state.circuit_breaker_triggered_at = db
+            .latest_account_event_log_at(PLAN_LOG_SOURCE, &account_id, CIRCUIT_BREAKER_REASON)
+            .await?;
+        let (latest_signal_date, signals) = match preloaded_signals.remove(&strategy.id) {
+            Some(loaded) => loaded,
+            None => load_planning_signals(&db, &strategy.id, &engine).await?,
         };
-        if signals.is_empty() && account_state.positions.is_empty() {
+        if signals.is_empty()
+            && hypothetical_signals.is_empty()
+            && account_state.positions.is_empty()
+        {
             skipped += 1;
             warn!(
                 "Skipping strategy {} - no recent signals or open trades",
@@ -95,6 +285,11 @@ pub async fn run(app: &AppContext) -> Result<()> {
         let mut candle_symbols: HashSet<String> = signals
             .iter()
             .map(|signal| signal.ticker.trim().to_uppercase())
+            .chain(
+                hypothetical_signals
+                    .iter()
+                    .map(|(_, ticker)| ticker.clone()),
+            )
             .filter(|ticker| !ticker.is_empty())
             .collect();
         for position in &account_state.positions {
@@ -103,6 +298,9 @@ pub async fn run(app: &AppContext) -> Result<()> {
                 candle_symbols.insert(ticker);
             }
         }
+        if let Some(hedge_ticker) = engine.hedge_ticker() {
+            candle_symbols.insert(hedge_ticker.to_string());
+        }
         if candle_symbols.is_empty() {
             skipped += 1;
             warn!(
@@ -114,8 +312,48 @@ pub async fn run(app: &AppContext) -> Result<()> {
 
         let mut symbol_list: Vec<String> = candle_symbols.drain().collect();
         symbol_list.sort();
-        let ticker_metadata = db.get_ticker_metadata(&symbol_list).await?;
-        let candles = db.get_candles_for_tickers(&symbol_list).await?;
+        let existing_trades = db.get_strategy_live_trades(&strategy.id).await?;
+        inputs.push(PlanningInput {
+            strategy,
+            account_id,
+            engine,
+            account_state,
+            latest_signal_date,
+            signals,
+            symbol_list,
+            existing_trades,
+        });
+    }
+
+    // One candle and metadata load covers the tickers of every strategy.
+    let mut all_symbols: Vec<String> = inputs
+        .iter()
+        .flat_map(|input| input.symbol_list.iter().cloned())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    all_symbols.sort();
+    let ticker_metadata = db.get_ticker_metadata(&all_symbols).await?;
+    let all_candles = db.get_candles_for_tickers(&all_symbols).await?;
+
+    let mut prepared_plans = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let PlanningInput {
+            strategy,
+            account_id,
+            engine,
+            account_state,
+            latest_signal_date,
+            mut signals,
+            symbol_list,
+            existing_trades,
+        } = input;
+        let symbols: HashSet<&str> = symbol_list.iter().map(String::as_str).collect();
+        let candles: Vec<Candle> = all_candles
+            .iter()
+            .filter(|candle| symbols.contains(candle.ticker.as_str()))
+            .cloned()
+            .collect();
         if candles.is_empty() {
             skipped += 1;
             warn!(
@@ -135,12 +373,24 @@ pub async fn run(app: &AppContext) -> Result<()> {
             );
             continue;
         };
+        signals.extend(
+            hypothetical_signals
+                .iter()
+                .map(|(action, ticker)| GeneratedSignal {
+                    date: target_date,
+                    ticker: ticker.clone(),
+                    action: action.clone(),
+                    confidence: None,
+                }),
+        );
 
-        let engine = Engine::from_parameters(&strategy.parameters, runtime_settings.clone());
         let effective_buying_power = engine.effective_buying_power_for_account(&account_state);
         info!(
-            "Strategy {} (account {}) effective buying power for sizing: {:.2}",
-            strategy.name, account_id, effective_buying_power
+            "Strategy {} (account {}) effective buying power for sizing: {:.2} {}",
+            strategy.name,
+            account_id,
+            effective_buying_power,
+            account_state.fx.currency()
         );
 
         let excluded_keywords: Vec<String> = strategy
@@ -175,29 +425,160 @@ pub async fn run(app: &AppContext) -> Result<()> {
             }
         }
 
-        let existing_trades = db.get_strategy_live_trades(&strategy.id).await?;
         let existing_buy_operations_today = db
             .count_buy_operations_for_day(&strategy.id, target_date)
             .await?
             .max(0) as usize;
 
-        let plan = engine.plan_account_operations(
-            &strategy.id,
-            &account_id,
-            &signals,
-            &candles,
+        let mut ensemble_skips = Vec::new();
+        if let Some(votes) = ensembles.get(&account_id) {
+            (signals, ensemble_skips) = votes.filter_signals(&strategy.id, signals);
+        }
+
+        prepared_plans.push(PreparedPlan {
+            strategy,
+            account_id,
+            engine,
+            account_state,
             target_date,
-            &account_state,
-            &excluded_tickers,
-            &existing_trades,
+            signals,
+            candles,
+            excluded_tickers,
+            existing_trades,
             existing_buy_operations_today,
-            &ticker_metadata,
-        );
+            ensemble_skips,
+        });
+    }
+
+    // Planning only reads the prepared inputs, so strategies plan in parallel.
+    let planned: Vec<(StrategyPlan, HashMap<String, f64>)> = prepared_plans
+        .into_par_iter()
+        .map(|prepared| {
+            let mut plan = prepared.engine.plan_account_operations(
+                &prepared.strategy.id,
+                &prepared.account_id,
+                &prepared.signals,
+                &prepared.candles,
+                prepared.target_date,
+                &prepared.account_state,
+                &prepared.excluded_tickers,
+                &prepared.existing_trades,
+                prepared.existing_buy_operations_today,
+                &ticker_metadata,
+            );
+            plan.skipped_signals.extend(prepared.ensemble_skips);
+            let volumes = operation_dollar_volumes(&plan, &prepared.candles);
+            let strategy_plan = StrategyPlan {
+                strategy_id: prepared.strategy.id,
+                strategy_name: prepared.strategy.name,
+                account_id: prepared.account_id,
+                target_date: prepared.target_date,
+                plan,
+            };
+            (strategy_plan, volumes)
+        })
+        .collect();
+
+    for (strategy_plan, volumes) in planned {
+        for (ticker, volume) in volumes {
+            dollar_volumes.entry(ticker).or_insert(volume);
+        }
+        if halt_check_enabled {
+            if let Some(broker) = brokers.get(&strategy_plan.account_id) {
+                let mut unchecked: Vec<String> = strategy_plan
+                    .plan
+                    .operations
+                    .iter()
+                    .map(|operation| operation.ticker.clone())
+                    .filter(|ticker| halt_checked.insert(ticker.clone()))
+                    .collect();
+                unchecked.sort();
+                halted_tickers.extend(broker.fetch_halted_symbols(&unchecked).await);
+            }
+        }
+        strategy_plans.push(strategy_plan);
+    }
+
+    if let Some(share) = capacity_share {
+        let decisions = throttle_buy_operations(&mut strategy_plans, &dollar_volumes, share);
+        if !decisions.is_empty() && !options.dry_run {
+            let metadata = json!({
+                "maxVolumeShare": share,
+                "lookbackDays": DOLLAR_VOLUME_LOOKBACK_DAYS,
+                "decisions": decisions,
+            });
+            db.insert_system_log(
+                PLAN_LOG_SOURCE,
+                "warn",
+                &format!(
+                    "Capacity throttling adjusted {} planned buy order{}",
+                    decisions.len(),
+                    if decisions.len() == 1 { "" } else { "s" }
+                ),
+                Some(metadata),
+            )
+            .await?;
+        }
+    }
+
+    attach_expected_costs(
+        &mut strategy_plans,
+        &dollar_volumes,
+        &cost_model,
+        &runtime_settings,
+    );
+    if let Some(budget) = cost_settings.monthly_budget {
+        let mut decisions = Vec::new();
+        for strategy_plan in strategy_plans.iter_mut() {
+            let (month_start, month_end) = calendar_month_bounds(strategy_plan.target_date);
+            let spent = db
+                .sum_sent_operation_costs(&strategy_plan.strategy_id, month_start, month_end)
+                .await?;
+            decisions.extend(defer_operations_over_budget(strategy_plan, budget, spent));
+        }
+        if !decisions.is_empty() && !options.dry_run {
+            db.insert_system_log(
+                PLAN_LOG_SOURCE,
+                "warn",
+                &format!(
+                    "Monthly cost budget deferred {} planned buy order{}",
+                    decisions.len(),
+                    if decisions.len() == 1 { "" } else { "s" }
+                ),
+                Some(json!({ "monthlyBudget": budget, "decisions": decisions })),
+            )
+            .await?;
+        }
+    }
+
+    let latest_target_date = strategy_plans
+        .iter()
+        .map(|strategy_plan| strategy_plan.target_date.date_naive())
+        .max();
+    let mut operation_reasons = Vec::new();
+    for StrategyPlan {
+        strategy_id,
+        strategy_name,
+        account_id,
+        target_date,
+        plan,
+    } in strategy_plans
+    {
+        if options.explain {
+            for line in explain_plan(&strategy_name, &account_id, target_date, &plan) {
+                println!("{}", line);
+            }
+        }
+        if options.dry_run {
+            processed += usize::from(!plan.operations.is_empty());
+            skipped += usize::from(plan.operations.is_empty());
+            continue;
+        }
 
         if !plan.skipped_signals.is_empty() {
             if let Err(err) = db
                 .insert_account_signal_skips(
-                    &strategy.id,
+                    &strategy_id,
                     Some(&account_id),
                     "plan_operations",
                     &plan.skipped_signals,
@@ -206,7 +587,7 @@ pub async fn run(app: &AppContext) -> Result<()> {
             {
                 warn!(
                     "Failed to record signal skip reasons for strategy {}: {}",
-                    strategy.name, err
+                    strategy_name, err
                 );
             }
         }
@@ -214,16 +595,16 @@ pub async fn run(app: &AppContext) -> Result<()> {
         if plan.operations.is_empty() {
             skipped += 1;
             let metadata = json!({
-                "strategyId": strategy.id,
+                "strategyId": strategy_id,
                 "latestDate": target_date,
                 "notes": plan.notes,
             });
             db.insert_system_log(
-                "plan-operations-job",
+                PLAN_LOG_SOURCE,
                 "info",
                 &format!(
                     "No account operations generated for strategy {}",
-                    strategy.name
+                    strategy_name
                 ),
                 Some(metadata),
             )
@@ -231,7 +612,15 @@ pub async fn run(app: &AppContext) -> Result<()> {
             continue;
         }
 
-        db.replace_account_operations_for_strategy(&account_id, &strategy.id, &plan.operations)
+        operation_reasons.extend(plan.operations.iter().filter_map(|operation| {
+            Some(SkipOccurrence {
+                account_id: account_id.clone(),
+                ticker: operation.ticker.clone(),
+                reason: operation.reason.clone()?,
+                date: target_date.date_naive(),
+            })
+        }));
+        db.replace_account_operations_for_strategy(&account_id, &strategy_id, &plan.operations)
             .await?;
 
         processed += 1;
@@ -239,16 +628,511 @@ pub async fn run(app: &AppContext) -> Result<()> {
             "Planned {} operation{} for {} as of {}",
             plan.operations.len(),
             if plan.operations.len() == 1 { "" } else { "s" },
-            strategy.name,
+            strategy_name,
             target_date.format("%Y-%m-%d")
         );
     }
 
+    if options.dry_run {
+        if !halted_tickers.is_empty() {
+            let mut tickers: Vec<&String> = halted_tickers.iter().collect();
+            tickers.sort();
+            info!(
+                "Dry run: would pause pending operations on halted tickers {:?}",
+                tickers
+            );
+        }
+    } else {
+        pause_halted_operations(&db, &halted_tickers, PLAN_LOG_SOURCE).await?;
+        if let Some(today) = latest_target_date {
+            raise_skip_alerts(&db, &http_client, &alert_settings, operation_reasons, today).await?;
+        }
+    }
+
     info!(
-        "Completed operation planning for {} strateg{} ({} skipped)",
+        "Completed operation planning for {} strateg{} ({} skipped){}",
         processed,
         if processed == 1 { "y" } else { "ies" },
-        skipped
+        skipped,
+        if options.dry_run {
+            "; dry run, nothing was written"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+/// Connects to `account_id`'s broker and fetches its state with the account's risk overrides
+/// and FX rates, or returns why the account's strategies cannot be planned.
+async fn connect_account<'a>(
+    http_client: &'a Client,
+    db: &'a Database,
+    settings: &HashMap<String, String>,
+    account_id: &str,
+) -> Result<std::result::Result<(AccountBroker<'a>, AccountStateSnapshot), String>> {
+    let Some(creds) = db.get_account_credentials(account_id).await? else {
+        return Ok(Err(format!("account {} not found", account_id)));
+    };
+    if !AccountBroker::supports(&creds.provider) {
+        return Ok(Err(format!(
+            "unsupported account provider {}",
+            creds.provider
+        )));
+    }
+    let broker = match AccountBroker::connect(http_client, db, &creds, settings) {
+        Ok(broker) => broker,
+        Err(err) => {
+            return Ok(Err(format!(
+                "failed to initialize {} broker: {}",
+                creds.provider, err
+            )))
+        }
+    };
+    let mut state = match broker.fetch_account_state().await {
+        Ok(state) => state,
+        Err(err) => return Ok(Err(format!("failed to fetch account state: {}", err))),
+    };
+    state.risk_overrides = db.get_account_risk_overrides(account_id).await?;
+    state.fx = db.get_account_fx(account_id).await?;
+    Ok(Ok((broker, state)))
+}
+
+/// Average dollar volume of each ticker `plan` opens or closes, from `candles`.
+fn operation_dollar_volumes(plan: &PlannedOperations, candles: &[Candle]) -> HashMap<String, f64> {
+    let candles_by_ticker = group_all_candles_by_ticker(candles);
+    plan.operations
+        .iter()
+        .filter(|operation| operation.operation_type != AccountOperationType::UpdateStopLoss)
+        .filter_map(|operation| {
+            let ticker_candles = candles_by_ticker.get(&operation.ticker)?;
+            let volume = average_dollar_volume(ticker_candles, DOLLAR_VOLUME_LOOKBACK_DAYS)?;
+            Some((operation.ticker.clone(), volume))
+        })
+        .collect()
+}
+
+/// The latest signal date of `strategy_id` and its signals within the engine's planning window.
+async fn load_planning_signals(
+    db: &Database,
+    strategy_id: &str,
+    engine: &Engine,
+) -> Result<(Option<DateTime<Utc>>, Vec<GeneratedSignal>)> {
+    let latest_signal_date = db.get_latest_signal_date(strategy_id).await?;
+    let signals = match latest_signal_date {
+        Some(date) => {
+            let window_start = engine.planning_signal_window_start(date);
+            db.get_signals_for_strategy_in_range(strategy_id, window_start, date)
+                .await?
+        }
+        None => Vec::new(),
+    };
+    Ok((latest_signal_date, signals))
+}
+
+/// Evaluates the skip-reason alert rules over recorded plan-operations skips plus this run's
+/// operation reasons (such as `stop_missing`), logs any alerts at `error` level and posts them
+/// to the configured webhook.
+async fn raise_skip_alerts(
+    db: &Database,
+    http_client: &Client,
+    alert_settings: &SkipAlertSettings,
+    mut occurrences: Vec<SkipOccurrence>,
+    today: NaiveDate,
+) -> Result<()> {
+    if alert_settings.rules.is_empty() {
+        return Ok(());
+    }
+    let reasons: Vec<String> = alert_settings
+        .rules
+        .iter()
+        .map(|rule| rule.reason.clone())
+        .collect();
+    occurrences.extend(
+        db.get_signal_skip_occurrences(
+            "plan_operations",
+            &reasons,
+            skip_alert_lookback_start(&alert_settings.rules, today),
+        )
+        .await?,
     );
+    let alerts = evaluate_skip_alerts(&alert_settings.rules, &occurrences, today);
+    if alerts.is_empty() {
+        return Ok(());
+    }
+
+    let summary = alerts
+        .iter()
+        .map(|alert| {
+            format!(
+                "{} {} on account {} ({} of the last {} days)",
+                alert.ticker, alert.reason, alert.account_id, alert.days, alert.window_days
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    let message = format!(
+        "{} skip alert{}: {}",
+        alerts.len(),
+        if alerts.len() == 1 { "" } else { "s" },
+        summary
+    );
+    info!("{}", message);
+    db.insert_system_log(
+        SKIP_ALERT_LOG_SOURCE,
+        "error",
+        &message,
+        Some(json!({ "alerts": alerts })),
+    )
+    .await?;
+
+    if let Some(url) = &alert_settings.webhook_url {
+        let delivery = http_client
+            .post(url)
+            .json(&json!({ "text": message, "alerts": alerts }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(err) = delivery {
+            warn!("Failed to post skip alerts to webhook: {}", err);
+        }
+    }
     Ok(())
 }
+
+/// Human-readable decision trace for one strategy's plan: every planned operation with the
+/// inputs behind it, then every skipped signal with its reason, then planner notes.
+fn explain_plan(
+    strategy_name: &str,
+    account_id: &str,
+    target_date: DateTime<Utc>,
+    plan: &PlannedOperations,
+) -> Vec<String> {
+    let mut lines = vec![format!(
+        "Strategy {} (account {}) as of {}: {} operation{}, {} skipped signal{}",
+        strategy_name,
+        account_id,
+        target_date.format("%Y-%m-%d"),
+        plan.operations.len(),
+        if plan.operations.len() == 1 { "" } else { "s" },
+        plan.skipped_signals.len(),
+        if plan.skipped_signals.len() == 1 {
+            ""
+        } else {
+            "s"
+        }
+    )];
+    let format_price = |value: Option<f64>| {
+        value
+            .map(|price| format!("{:.2}", price))
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+
+    for operation in &plan.operations {
+        let mut line = format!(
+            "  {:<16} {:<8} qty {} @ {} {}, stop {}",
+            operation.operation_type.as_str(),
+            operation.ticker,
+            operation
+                .quantity
+                .map(|quantity| quantity.to_string())
+                .unwrap_or_else(|| "n/a".to_string()),
+            format_price(operation.price),
+            operation.order_type.as_deref().unwrap_or("-"),
+            format_price(operation.stop_loss)
+        );
+        if let Some(previous) = operation.previous_stop_loss {
+            line.push_str(&format!(" (was {:.2})", previous));
+        }
+        if let Some(order_id) = operation.replaces_order_id.as_deref() {
+            line.push_str(&format!(", replaces order {}", order_id));
+        }
+        if let Some(cost) = operation.expected_cost {
+            line.push_str(&format!(", expected cost {:.2}", cost));
+        }
+        if let Some(reason) = operation.reason.as_deref() {
+            line.push_str(&format!(" [{}]", reason));
+        }
+        if operation.operation_type == AccountOperationType::OpenPosition {
+            if let Some(sizing) = plan
+                .buy_sizing
+                .iter()
+                .find(|sizing| sizing.ticker == operation.ticker)
+            {
+                line.push_str(&format!(
+                    "; sized from cash {:.2}, confidence {:.2}, size ratio {}, sizing mode {}, realized vol {}, value {:.2}",
+                    sizing.available_cash,
+                    sizing.confidence,
+                    sizing.trade_size_ratio,
+                    sizing.position_sizing_mode,
+                    sizing
+                        .realized_vol
+                        .map(|vol| format!("{:.4}", vol))
+                        .unwrap_or_else(|| "n/a".to_string()),
+                    sizing.trade_value
+                ));
+                if Some(sizing.quantity) != operation.quantity {
+                    line.push_str(&format!(" (sized qty {})", sizing.quantity));
+                }
+            }
+        }
+        lines.push(line);
+    }
+
+    for skip in &plan.skipped_signals {
+        let mut line = format!(
+            "  skip {:<4} {:<8} {}",
+            skip.action.as_str(),
+            skip.ticker,
+            skip.reason
+        );
+        if let Some(details) = skip.details.as_deref() {
+            line.push_str(&format!(" ({})", details));
+        }
+        lines.push(line);
+    }
+
+    if !plan.notes.is_empty() {
+        lines.push(format!("  notes: {}", plan.notes.join(", ")));
+    }
+    lines
+}
+
+/// Sets `expected_cost` on every order-placing operation, using the ticker's slippage rate as
+/// the spread; stop updates carry no cost.
+fn attach_expected_costs(
+    strategy_plans: &mut [StrategyPlan],
+    dollar_volumes: &HashMap<String, f64>,
+    cost_model: &TransactionCostModel,
+    runtime_settings: &EngineRuntimeSettings,
+) {
+    for strategy_plan in strategy_plans.iter_mut() {
+        for operation in strategy_plan.plan.operations.iter_mut() {
+            if operation.operation_type == AccountOperationType::UpdateStopLoss {
+                continue;
+            }
+            let notional = operation.quantity.unwrap_or(0) as f64 * operation.price.unwrap_or(0.0);
+            let ticker_cost_model = TransactionCostModel {
+                spread_rate: runtime_settings.slippage_rate_for(&operation.ticker),
+                ..*cost_model
+            };
+            operation.expected_cost = Some(estimate_transaction_cost(
+                &ticker_cost_model,
+                notional,
+                dollar_volumes.get(&operation.ticker).copied(),
+                operation.operation_type == AccountOperationType::ClosePosition,
+            ));
+        }
+    }
+}
+
+fn calendar_month_bounds(date: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let day = date.date_naive();
+    let start = day
+        .with_day(1)
+        .unwrap_or(day)
+        .and_time(NaiveTime::MIN)
+        .and_utc();
+    let end = start
+        .checked_add_months(Months::new(1))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    (start, end)
+}
+
+/// Defers the strategy's lowest-confidence buys once this month's spent cost plus the plan's
+/// closing costs leave too little of `budget`; closes are never deferred. Returns one audit
+/// entry per deferred buy.
+fn defer_operations_over_budget(
+    strategy_plan: &mut StrategyPlan,
+    budget: f64,
+    spent: f64,
+) -> Vec<Value> {
+    let operations = &strategy_plan.plan.operations;
+    let closing_cost: f64 = operations
+        .iter()
+        .filter(|operation| operation.operation_type != AccountOperationType::OpenPosition)
+        .filter_map(|operation| operation.expected_cost)
+        .sum();
+    let buy_indexes: Vec<usize> = operations
+        .iter()
+        .enumerate()
+        .filter(|(_, operation)| operation.operation_type == AccountOperationType::OpenPosition)
+        .map(|(index, _)| index)
+        .collect();
+    let orders: Vec<BudgetedOrder> = buy_indexes
+        .iter()
+        .map(|&index| BudgetedOrder {
+            confidence: operations[index].signal_confidence.unwrap_or(0.0),
+            cost: operations[index].expected_cost.unwrap_or(0.0),
+        })
+        .collect();
+    let remaining = budget - spent - closing_cost;
+    let deferred = defer_orders_over_budget(&orders, remaining);
+
+    let mut decisions = Vec::new();
+    let mut removed = Vec::new();
+    for (&index, is_deferred) in buy_indexes.iter().zip(deferred) {
+        if !is_deferred {
+            continue;
+        }
+        let operation = &strategy_plan.plan.operations[index];
+        let cost = operation.expected_cost.unwrap_or(0.0);
+        info!(
+            "Cost budget deferred {} buy for strategy {}: expected cost {:.2}, {:.2} of {:.2} left this month",
+            operation.ticker, strategy_plan.strategy_name, cost, remaining.max(0.0), budget
+        );
+        decisions.push(json!({
+            "strategyId": strategy_plan.strategy_id,
+            "accountId": strategy_plan.account_id,
+            "ticker": operation.ticker,
+            "confidence": operation.signal_confidence,
+            "expectedCost": cost,
+            "spentThisMonth": spent,
+            "closingCost": closing_cost,
+        }));
+        strategy_plan.plan.skipped_signals.push(AccountSignalSkip {
+            ticker: operation.ticker.clone(),
+            signal_date: operation.triggered_at,
+            action: SignalAction::Buy,
+            reason: "cost_budget_exhausted".to_string(),
+            details: Some(format!(
+                "expected cost {:.2}; spent {:.2} of {:.2} this month",
+                cost, spent, budget
+            )),
+        });
+        removed.push(index);
+    }
+    for index in removed.into_iter().rev() {
+        strategy_plan.plan.operations.remove(index);
+    }
+    decisions
+}
+
+/// Pauses pending operations on halted tickers so dispatch skips them until a later plan
+/// rebuilds them, and records the pause in `system_logs`.
+pub async fn pause_halted_operations(
+    db: &Database,
+    halted_tickers: &HashSet<String>,
+    source: &str,
+) -> Result<u64> {
+    if halted_tickers.is_empty() {
+        return Ok(0);
+    }
+    let mut tickers: Vec<String> = halted_tickers.iter().cloned().collect();
+    tickers.sort();
+    let paused = db
+        .pause_pending_operations_for_tickers(&tickers, TRADING_HALTED_REASON)
+        .await?;
+    db.insert_system_log(
+        source,
+        "warn",
+        &format!(
+            "Paused {} pending operation{} on halted ticker{} {}",
+            paused,
+            if paused == 1 { "" } else { "s" },
+            if tickers.len() == 1 { "" } else { "s" },
+            tickers.join(", ")
+        ),
+        Some(json!({ "tickers": tickers, "paused": paused })),
+    )
+    .await?;
+    Ok(paused)
+}
+
+/// Caps each ticker's combined planned buy notional at `share` of its average dollar volume,
+/// deferring or shrinking orders in place and returning one audit entry per changed order.
+fn throttle_buy_operations(
+    strategy_plans: &mut [StrategyPlan],
+    dollar_volumes: &HashMap<String, f64>,
+    share: f64,
+) -> Vec<Value> {
+    let mut buys_by_ticker: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+    for (plan_index, strategy_plan) in strategy_plans.iter().enumerate() {
+        for (op_index, operation) in strategy_plan.plan.operations.iter().enumerate() {
+            let is_buy = operation.operation_type == AccountOperationType::OpenPosition
+                && operation.quantity.unwrap_or(0) > 0
+                && operation.price.unwrap_or(0.0) > 0.0;
+            if is_buy {
+                buys_by_ticker
+                    .entry(operation.ticker.clone())
+                    .or_default()
+                    .push((plan_index, op_index));
+            }
+        }
+    }
+
+    let mut tickers: Vec<&String> = buys_by_ticker.keys().collect();
+    tickers.sort();
+    let mut decisions = Vec::new();
+    let mut deferred: Vec<(usize, usize)> = Vec::new();
+    for ticker in tickers {
+        let Some(dollar_volume) = dollar_volumes.get(ticker) else {
+            continue;
+        };
+        let capacity = dollar_volume * share;
+        let refs = &buys_by_ticker[ticker];
+        let orders: Vec<CapacityOrder> = refs
+            .iter()
+            .map(|&(plan_index, op_index)| {
+                let operation = &strategy_plans[plan_index].plan.operations[op_index];
+                CapacityOrder {
+                    quantity: operation.quantity.unwrap_or(0),
+                    price: operation.price.unwrap_or(0.0),
+                    confidence: operation.signal_confidence.unwrap_or(0.0),
+                }
+            })
+            .collect();
+        let quantities = throttle_orders_to_capacity(&orders, capacity);
+        for ((&(plan_index, op_index), order), quantity) in refs.iter().zip(&orders).zip(quantities)
+        {
+            if quantity == order.quantity {
+                continue;
+            }
+            let strategy_plan = &mut strategy_plans[plan_index];
+            let operation = &mut strategy_plan.plan.operations[op_index];
+            let action = if quantity > 0 { "scaled" } else { "deferred" };
+            info!(
+                "Capacity throttle {} {} buy for strategy {}: {} -> {} shares (capacity {:.2} of {:.2} average dollar volume)",
+                action,
+                ticker,
+                strategy_plan.strategy_name,
+                order.quantity,
+                quantity,
+                capacity,
+                dollar_volume
+            );
+            decisions.push(json!({
+                "strategyId": strategy_plan.strategy_id,
+                "accountId": strategy_plan.account_id,
+                "ticker": ticker,
+                "action": action,
+                "confidence": operation.signal_confidence,
+                "originalQuantity": order.quantity,
+                "quantity": quantity,
+                "capacity": capacity,
+                "averageDollarVolume": dollar_volume,
+            }));
+            if quantity > 0 {
+                operation.quantity = Some(quantity);
+            } else {
+                strategy_plan.plan.skipped_signals.push(AccountSignalSkip {
+                    ticker: ticker.clone(),
+                    signal_date: operation.triggered_at,
+                    action: SignalAction::Buy,
+                    reason: "capacity_throttled".to_string(),
+                    details: Some(format!(
+                        "Deferred {} shares; combined buys exceeded {:.2} of capacity",
+                        order.quantity, capacity
+                    )),
+                });
+                deferred.push((plan_index, op_index));
+            }
+        }
+    }
+
+    deferred.sort_unstable();
+    for (plan_index, op_index) in deferred.into_iter().rev() {
+        strategy_plans[plan_index].plan.operations.remove(op_index);
+    }
+    decisions
+}
@@ -0,0 +1,175 @@
+use crate::context::AppContext;
+use crate::database::BacktestResultSummary;
+use anyhow::{anyhow, Result};
+use log::info;
+use std::collections::HashMap;
+
+/// `keep_last` results are kept per strategy, ticker scope and window; `keep_active` never deletes
+/// results of active strategies; `dry_run` only reports what would change.
+#[derive(Debug, Clone, Copy)]
+pub struct PruneBacktestsOptions {
+    pub keep_last: usize,
+    pub keep_active: bool,
+    pub dry_run: bool,
+}
+
+/// Results to delete, and older kept results whose daily snapshots are compacted to weekly.
+#[derive(Default)]
+struct PrunePlan<'a> {
+    delete: Vec<&'a BacktestResultSummary>,
+    compact: Vec<&'a BacktestResultSummary>,
+}
+
+fn plan_prune(results: &[BacktestResultSummary], options: PruneBacktestsOptions) -> PrunePlan<'_> {
+    let mut groups: HashMap<(&str, &str, i32), Vec<&BacktestResultSummary>> = HashMap::new();
+    for result in results {
+        groups
+            .entry((
+                result.strategy_id.as_str(),
+                result.ticker_scope.as_str(),
+                result.period_months,
+            ))
+            .or_default()
+            .push(result);
+    }
+
+    let mut plan = PrunePlan::default();
+    for mut group in groups.into_values() {
+        group.sort_by(|a, b| {
+            b.end_date
+                .cmp(&a.end_date)
+                .then_with(|| b.created_at.cmp(&a.created_at))
+        });
+        for (rank, result) in group.into_iter().enumerate() {
+            if rank >= options.keep_last && !(options.keep_active && result.strategy_active) {
+                plan.delete.push(result);
+            } else if rank > 0 {
+                plan.compact.push(result);
+            }
+        }
+    }
+    plan.delete.sort_by(|a, b| {
+        a.strategy_name
+            .cmp(&b.strategy_name)
+            .then_with(|| a.ticker_scope.cmp(&b.ticker_scope))
+            .then_with(|| a.period_months.cmp(&b.period_months))
+            .then_with(|| b.end_date.cmp(&a.end_date))
+    });
+    plan
+}
+
+pub async fn run(app: &AppContext, options: PruneBacktestsOptions) -> Result<()> {
+    if options.keep_last == 0 {
+        return Err(anyhow!("--keep-last must be at least 1"));
+    }
+    let db = app.database().await?;
+    let results = db.get_backtest_result_summaries().await?;
+    let plan = plan_prune(&results, options);
+
+    let prefix = if options.dry_run {
+        "Would delete"
+    } else {
+        "Deleting"
+    };
+    for result in &plan.delete {
+        info!(
+            "{} {} result {} of {} ({}m, ended {}, {} trades)",
+            prefix,
+            result.ticker_scope,
+            result.id,
+            result.strategy_name,
+            result.period_months,
+            result.end_date.date_naive(),
+            result.trade_count
+        );
+    }
+    let trade_count: i64 = plan.delete.iter().map(|result| result.trade_count).sum();
+    if options.dry_run {
+        info!(
+            "Dry run: would delete {} of {} backtest results ({} trades) and compact snapshots of up to {} older results",
+            plan.delete.len(),
+            results.len(),
+            trade_count,
+            plan.compact.len()
+        );
+        return Ok(());
+    }
+
+    let ids: Vec<String> = plan.delete.iter().map(|result| result.id.clone()).collect();
+    let (deleted_results, deleted_trades) = db.delete_backtest_results(&ids).await?;
+    let mut compacted = 0usize;
+    let mut dropped_snapshots = 0usize;
+    for result in &plan.compact {
+        let dropped = db.compact_backtest_snapshots(&result.id).await?;
+        if dropped > 0 {
+            compacted += 1;
+            dropped_snapshots += dropped;
+        }
+    }
+    info!(
+        "Deleted {} of {} backtest results ({} trades); compacted {} older results to weekly snapshots ({} daily snapshots dropped)",
+        deleted_results,
+        results.len(),
+        deleted_trades,
+        compacted,
+        dropped_snapshots
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_prune, PruneBacktestsOptions};
+    use crate::database::BacktestResultSummary;
+    use chrono::{TimeZone, Utc};
+
+    fn result(id: &str, strategy: &str, active: bool, day: u32) -> BacktestResultSummary {
+        BacktestResultSummary {
+            id: id.to_string(),
+            strategy_id: strategy.to_string(),
+            strategy_name: strategy.to_string(),
+            strategy_active: active,
+            ticker_scope: "validation".to_string(),
+            period_months: 12,
+            end_date: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            created_at: None,
+            trade_count: 3,
+        }
+    }
+
+    fn ids(results: &[&BacktestResultSummary]) -> Vec<String> {
+        let mut ids: Vec<String> = results.iter().map(|result| result.id.clone()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn keeps_newest_results_per_group_and_spares_active_strategies() {
+        let results = vec![
+            result("a1", "a", true, 1),
+            result("a3", "a", true, 3),
+            result("a2", "a", true, 2),
+            result("r1", "r", false, 1),
+            result("r2", "r", false, 2),
+            result("r3", "r", false, 3),
+        ];
+        let options = PruneBacktestsOptions {
+            keep_last: 2,
+            keep_active: false,
+            dry_run: true,
+        };
+        let plan = plan_prune(&results, options);
+        assert_eq!(ids(&plan.delete), ["a1", "r1"]);
+        assert_eq!(ids(&plan.compact), ["a2", "r2"]);
+
+        let plan = plan_prune(
+            &results,
+            PruneBacktestsOptions {
+                keep_active: true,
+                ..options
+            },
+        );
+        assert_eq!(ids(&plan.delete), ["r1"]);
+        assert_eq!(ids(&plan.compact), ["a1", "a2", "r2"]);
+    }
+}
@@ -1,10 +1,18 @@
 use crate::alpaca::{AlpacaClient, OrderEvaluation, OrderState};
+use crate::broker::{AccountBroker, Broker};
+use crate::candle_utils::SessionCalendar;
+use crate::commands::plan_operations::{self, pause_halted_operations, PlanOperationsOptions};
+use crate::config::{resolve_live_halt_check_enabled, NotificationEvent, NotificationSettings};
 use crate::context::AppContext;
 use crate::database::Database;
 use crate::engine::AccountPositionState;
 use crate::models::{Trade, TradeStatus};
+use crate::notifications::{
+    daily_pnl_text, deliver_notifications, position_mismatches, trade_notification, Notification,
+    NOTIFICATIONS_LOG_SOURCE,
+};
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Timelike, Utc};
 use log::{info, warn};
 use reqwest::Client;
 use std::{
@@ -13,21 +21,36 @@ use std::{
 };
 
 const PNL_EPSILON: f64 = 1e-6;
+/// System log event of a trade whose stop and take-profit orders both filled.
+const DOUBLE_EXIT_FILL_EVENT: &str = "double_exit_fill";
 
 pub async fn run(app: &AppContext) -> Result<()> {
     let db = app.database().await?;
-    let candidates = db.get_live_trades_with_accounts().await?;
-    if candidates.is_empty() {
-        info!("No live trades require reconciliation");
-        return Ok(());
-    }
-    let settings = db.get_all_settings().await?;
+    let settings = app.database_settings(&db).await?;
 
     let http_client = Client::builder()
         .timeout(StdDuration::from_secs(30))
         .build()
         .context("failed to construct HTTP client")?;
 
+    reconcile_live_trades(&db, &settings, &http_client).await?;
+    if resolve_live_halt_check_enabled(&settings) {
+        check_trading_halts(app, &db, &settings, &http_client).await?;
+    }
+    Ok(())
+}
+
+async fn reconcile_live_trades(
+    db: &Database,
+    settings: &HashMap<String, String>,
+    http_client: &Client,
+) -> Result<()> {
+    let candidates = db.get_live_trades_with_accounts().await?;
+    if candidates.is_empty() {
+        info!("No live trades require reconciliation");
+        return Ok(());
+    }
+
     let mut grouped: HashMap<String, Vec<Trade>> = HashMap::new();
     for candidate in candidates {
         grouped
@@ -36,6 +59,13 @@ pub async fn run(app: &AppContext) -> Result<()> {
             .push(candidate.trade);
     }
 
+    let notification_settings =
+        NotificationSettings::from_settings_map(settings).unwrap_or_else(|err| {
+            warn!("Account notifications disabled: {}", err);
+            NotificationSettings::default()
+        });
+    let calendar = SessionCalendar::from_settings_map(settings)?;
+    let mut notifications = Vec::new();
     let mut reconciled = 0usize;
     let mut skipped = 0usize;
 
@@ -50,7 +80,7 @@ pub async fn run(app: &AppContext) -> Result<()> {
             continue;
         };
 
-        if !credentials.provider.eq_ignore_ascii_case("alpaca") {
+        if !AccountBroker::supports(&credentials.provider) {
             warn!(
                 "Skipping {} trade(s) for unsupported provider {} on account {}",
                 trades.len(),
@@ -61,13 +91,14 @@ pub async fn run(app: &AppContext) -> Result<()> {
             continue;
         }
 
-        let client = match AlpacaClient::new(&http_client, &credentials, &settings) {
+        let client = match AccountBroker::connect(http_client, db, &credentials, settings) {
             Ok(client) => client,
             Err(err) => {
                 warn!(
-                    "Skipping {} trade(s) for account {}: Alpaca client init failed: {}",
+                    "Skipping {} trade(s) for account {}: {} broker init failed: {}",
                     trades.len(),
                     account_id,
+                    credentials.provider,
                     err
                 );
                 skipped += trades.len();
@@ -90,7 +121,7 @@ pub async fn run(app: &AppContext) -> Result<()> {
             .map(|state| state.positions.clone())
             .unwrap_or_default();
 
-        let mut position_prices = match fetch_last_candle_closes(&db, &trades, &positions).await {
+        let mut position_prices = match fetch_last_candle_closes(db, &trades, &positions).await {
             Ok(prices) => prices,
             Err(err) => {
                 warn!(
@@ -114,12 +145,69 @@ pub async fn run(app: &AppContext) -> Result<()> {
             }
         }
 
+        let notify = !notification_settings.channels_for(&account_id).is_empty();
+        let mut reconciled_trades = Vec::with_capacity(trades.len());
         for mut trade in trades {
-            match reconcile_trade(&client, &mut trade, &position_prices, &positions).await {
+            let before = notify.then(|| trade.clone());
+            let mut split_trades = Vec::new();
+            let mut alerts = Vec::new();
+            match reconcile_trade(
+                &client,
+                &mut trade,
+                &position_prices,
+                &positions,
+                &mut split_trades,
+                &mut alerts,
+                calendar,
+            )
+            .await
+            {
                 Ok(true) => {
                     db.ensure_ticker_exists(&trade.ticker).await?;
+                    for part in &split_trades {
+                        db.insert_split_trade(&trade.id, part).await?;
+                        info!(
+                            "{} {} of {} shares of trade {} as {}",
+                            if part.status == TradeStatus::Cancelled {
+                                "Cancelled unfilled"
+                            } else {
+                                "Closed"
+                            },
+                            part.quantity.abs(),
+                            part.quantity.abs() + trade.quantity.abs(),
+                            trade.id,
+                            part.id
+                        );
+                    }
                     db.persist_trade_reconciliation(&trade).await?;
                     reconciled += 1;
+                    for text in alerts {
+                        warn!("{}", text);
+                        db.insert_system_log(
+                            "reconcile-trades-job",
+                            "error",
+                            &text,
+                            Some(serde_json::json!({
+                                "accountId": account_id,
+                                "event": DOUBLE_EXIT_FILL_EVENT,
+                                "tradeId": trade.id,
+                            })),
+                        )
+                        .await?;
+                        if notify {
+                            notifications.push(Notification {
+                                account_id: account_id.clone(),
+                                event: NotificationEvent::Mismatch,
+                                text,
+                            });
+                        }
+                    }
+                    if let Some(before) = before {
+                        notifications.extend(trade_notification(&account_id, &before, &trade));
+                        for part in &split_trades {
+                            notifications.extend(trade_notification(&account_id, &before, part));
+                        }
+                    }
                 }
                 Ok(false) => {}
                 Err(err) => {
@@ -130,9 +218,25 @@ pub async fn run(app: &AppContext) -> Result<()> {
                     skipped += 1;
                 }
             }
+            reconciled_trades.push(trade);
+        }
+
+        if notify {
+            notifications.extend(
+                account_notifications(
+                    db,
+                    &notification_settings,
+                    &account_id,
+                    &reconciled_trades,
+                    account_state.is_some().then_some(positions.as_slice()),
+                )
+                .await?,
+            );
         }
     }
 
+    deliver_notifications(db, http_client, &notification_settings, &notifications).await?;
+
     info!(
         "Reconciled {} trade{} ({} skipped)",
         reconciled,
@@ -143,15 +247,154 @@ pub async fn run(app: &AppContext) -> Result<()> {
     Ok(())
 }
 
+/// Pauses pending operations on tickers the broker reports as halted and re-plans
+/// operations once a previously paused ticker trades again.
+/// Position mismatch and daily P&L notifications for one account. Each distinct mismatch is
+/// sent at most once per UTC day, and the P&L summary once per day from the configured hour.
+async fn account_notifications(
+    db: &Database,
+    settings: &NotificationSettings,
+    account_id: &str,
+    trades: &[Trade],
+    positions: Option<&[AccountPositionState]>,
+) -> Result<Vec<Notification>> {
+    let now = Utc::now();
+    let day_start = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight should always be valid")
+        .and_utc();
+    let mut notifications = Vec::new();
+
+    if let Some(positions) = positions {
+        let mismatches = position_mismatches(trades, positions);
+        if !mismatches.is_empty() {
+            let details = mismatches
+                .iter()
+                .map(|(ticker, expected, held)| {
+                    format!("{} trades {} vs broker {}", ticker, expected, held)
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let text = format!("Position mismatch on account {}: {}", account_id, details);
+            let event = NotificationEvent::Mismatch;
+            if !db
+                .has_account_event_log_since(
+                    NOTIFICATIONS_LOG_SOURCE,
+                    account_id,
+                    event.label(),
+                    Some(&text),
+                    day_start,
+                )
+                .await?
+            {
+                notifications.push(Notification {
+                    account_id: account_id.to_string(),
+                    event,
+                    text,
+                });
+            }
+        }
+    }
+
+    let event = NotificationEvent::DailyPnl;
+    let wants_summary = settings
+        .channels_for(account_id)
+        .iter()
+        .any(|subscription| subscription.wants(event));
+    if wants_summary
+        && now.hour() >= settings.daily_summary_hour_utc
+        && !db
+            .has_account_event_log_since(
+                NOTIFICATIONS_LOG_SOURCE,
+                account_id,
+                event.label(),
+                None,
+                day_start,
+            )
+            .await?
+    {
+        let realized = db
+            .get_account_realized_pnl_on(account_id, now.date_naive())
+            .await?;
+        notifications.push(Notification {
+            account_id: account_id.to_string(),
+            event,
+            text: daily_pnl_text(account_id, now.date_naive(), realized, trades),
+        });
+    }
+    Ok(notifications)
+}
+
+async fn check_trading_halts(
+    app: &AppContext,
+    db: &Database,
+    settings: &HashMap<String, String>,
+    http_client: &Client,
+) -> Result<()> {
+    let tickers = db
+        .get_operation_tickers_with_status(&["pending", "paused"])
+        .await?;
+    if tickers.is_empty() {
+        return Ok(());
+    }
+
+    let mut client = None;
+    for account_id in db.get_account_ids().await? {
+        let Some(credentials) = db.get_account_credentials(&account_id).await? else {
+            continue;
+        };
+        if !credentials.provider.eq_ignore_ascii_case("alpaca") {
+            continue;
+        }
+        if let Ok(found) = AlpacaClient::new(http_client, &credentials, settings) {
+            client = Some(found);
+            break;
+        }
+    }
+    let Some(client) = client else {
+        warn!("Skipping trading halt check: no Alpaca account available");
+        return Ok(());
+    };
+
+    let halted = client.fetch_halted_symbols(&tickers).await;
+    pause_halted_operations(db, &halted, "reconcile-trades-job").await?;
+
+    let resumed: Vec<String> = db
+        .get_operation_tickers_with_status(&["paused"])
+        .await?
+        .into_iter()
+        .filter(|ticker| !halted.contains(ticker))
+        .collect();
+    if resumed.is_empty() {
+        return Ok(());
+    }
+    db.insert_system_log(
+        "reconcile-trades-job",
+        "info",
+        &format!(
+            "Trading resumed for {}; re-planning account operations",
+            resumed.join(", ")
+        ),
+        Some(serde_json::json!({ "tickers": resumed })),
+    )
+    .await?;
+    plan_operations::run(app, PlanOperationsOptions::default(), None).await
+}
+
 async fn reconcile_trade(
-    client: &AlpacaClient<'_>,
+    client: &impl Broker,
     trade: &mut Trade,
     position_prices: &HashMap<String, f64>,
     positions: &[AccountPositionState],
+    split_trades: &mut Vec<Trade>,
+    alerts: &mut Vec<String>,
+    calendar: SessionCalendar,
 ) -> Result<bool> {
     if !(trade.entry_order_id.is_some()
         || trade.stop_order_id.is_some()
-        || trade.exit_order_id.is_some())
+        || trade.exit_order_id.is_some()
+        || trade.target_order_id.is_some())
     {
         return Ok(false);
     }
@@ -171,6 +414,14 @@ async fn reconcile_trade(
     } else {
         None
     };
+    let target_eval = if let Some(order_id) = trade.target_order_id.as_deref() {
+        client.evaluate_order(order_id).await?
+    } else {
+        None
+    };
+    let target_pending = target_eval
+        .as_ref()
+        .is_some_and(|evaluation| matches!(evaluation.state, OrderState::Pending));
 
     if entry_order_ready_for_cancellation(trade, &entry_eval) {
         if let Some(order_id) = trade
@@ -190,11 +441,17 @@ async fn reconcile_trade(
         }
     }
 
-    if let Some(eval) = stop_eval
-        .as_ref()
-        .filter(|evaluation| matches!(evaluation.state, OrderState::Filled))
-    {
-        apply_closure(trade, eval, true);
+    if let Some((eval, is_stop)) = protective_exit_fill(trade, &stop_eval, &target_eval, alerts) {
+        apply_closure(trade, eval, is_stop);
+        if let (Some(slippage), Some(fill)) = (trade.stop_slippage_rate(), trade.exit_price) {
+            info!(
+                "Stop of trade {} filled at {:.4} against its {:.4} stop ({:+.3}% slippage)",
+                trade.id,
+                fill,
+                trade.stop_loss.unwrap_or_default(),
+                slippage * 100.0
+            );
+        }
         return Ok(true);
     }
 
@@ -202,28 +459,71 @@ async fn reconcile_trade(
         .as_ref()
         .filter(|evaluation| matches!(evaluation.state, OrderState::Filled))
     {
+        // Time-decay exits sell part of the position; the filled part closes as its own trade.
+        if let Some(filled) = eval
+            .filled_quantity
+            .map(|quantity| quantity.round() as i32)
+            .filter(|quantity| *quantity > 0 && *quantity < trade.quantity.abs())
+        {
+            let changed_at = eval.changed_at();
+            let mut part = trade.split_off(filled * trade.quantity.signum(), changed_at);
+            part.exit_order_id = trade.exit_order_id.clone();
+            trade.set_exit_order_id(None, changed_at);
+            apply_closure(&mut part, eval, false);
+            split_trades.push(part);
+            return Ok(true);
+        }
         apply_closure(trade, eval, false);
         return Ok(true);
     }
 
     let mut changed = false;
 
-    if let Some(eval) = entry_eval
-        .as_ref()
-        .filter(|evaluation| matches!(evaluation.state, OrderState::Filled))
-    {
+    // An entry cancelled after a partial fill still opened the shares that filled.
+    if let Some(eval) = entry_eval.as_ref().filter(|evaluation| {
+        matches!(evaluation.state, OrderState::Filled)
+            || (matches!(evaluation.state, OrderState::Cancelled)
+                && evaluation.filled_quantity.is_some())
+    }) {
         let changed_at = eval.changed_at();
         if trade.status == TradeStatus::Pending {
             trade.set_status(TradeStatus::Active, changed_at);
             changed = true;
         }
+        // An entry done filling short of its size (e.g. done_for_day or cancelled) holds what
+        // filled; the rest becomes a cancelled trade and the stop shrinks to the filled lot.
+        if let Some(filled) = eval
+            .filled_quantity
+            .map(|quantity| quantity.round() as i32)
+            .filter(|quantity| *quantity > 0 && *quantity < trade.quantity.abs())
+        {
+            let filled = filled * trade.quantity.signum();
+            split_trades.push(trade.split_unfilled_remainder(filled, changed_at));
+            if let Some(order_id) = trade.stop_order_id.clone().filter(|_| {
+                stop_eval
+                    .as_ref()
+                    .is_some_and(|evaluation| matches!(evaluation.state, OrderState::Pending))
+            }) {
+                let resized = client.resize_order(&order_id, filled).await?;
+                if resized != order_id {
+                    trade.set_stop_order_id(Some(resized), changed_at);
+                }
+            }
+            if let Some(order_id) = trade.target_order_id.clone().filter(|_| target_pending) {
+                let resized = client.resize_order(&order_id, filled).await?;
+                if resized != order_id {
+                    trade.set_target_order_id(Some(resized), changed_at);
+                }
+            }
+            changed = true;
+        }
         if let Some(price) = eval.filled_price {
             if trade.price != price {
                 trade.set_price(price, changed_at);
                 changed = true;
             }
         }
-        let filled_date = normalize_trade_date(changed_at);
+        let filled_date = calendar.session_start(changed_at);
         if trade.date != filled_date {
             trade.set_date(filled_date, changed_at);
             changed = true;
@@ -261,15 +561,27 @@ async fn reconcile_trade(
         }
     }
 
+    // A stop cancelled while its take-profit sibling is still working is most likely the
+    // broker's one-cancels-other reaction to a target fill not reported yet; keep both linked.
     if stop_eval
         .as_ref()
         .map(|evaluation| matches!(evaluation.state, OrderState::Cancelled))
         .unwrap_or(false)
+        && position_match.is_some()
+        && trade.stop_order_id.is_some()
+        && !target_pending
     {
-        if position_match.is_some() && trade.stop_order_id.is_some() {
-            trade.set_stop_order_id(None, Utc::now());
-            changed = true;
-        }
+        trade.set_stop_order_id(None, Utc::now());
+        changed = true;
+    }
+
+    if target_eval
+        .as_ref()
+        .is_some_and(|evaluation| matches!(evaluation.state, OrderState::Cancelled))
+        && position_match.is_some()
+    {
+        trade.set_target_order_id(None, Utc::now());
+        changed = true;
     }
 
     if should_cancel_trade(
@@ -277,6 +589,7 @@ async fn reconcile_trade(
         &entry_eval,
         &stop_eval,
         &exit_eval,
+        target_pending,
         position_match.is_some(),
     ) {
         apply_cancellation(trade, Utc::now());
@@ -290,6 +603,43 @@ async fn reconcile_trade(
     Ok(changed)
 }
 
+/// The filled leg of a trade's stop and take-profit pair, with whether it is the stop. When
+/// both legs report a fill, the broker's sibling cancel lost the race: the earlier fill closes
+/// the trade and the later one left an opposite position at the broker, which is pushed onto
+/// `alerts` for the operator.
+fn protective_exit_fill<'a>(
+    trade: &Trade,
+    stop: &'a Option<OrderEvaluation>,
+    target: &'a Option<OrderEvaluation>,
+    alerts: &mut Vec<String>,
+) -> Option<(&'a OrderEvaluation, bool)> {
+    let filled = |evaluation: &'a Option<OrderEvaluation>| {
+        evaluation
+            .as_ref()
+            .filter(|evaluation| matches!(evaluation.state, OrderState::Filled))
+    };
+    match (filled(stop), filled(target)) {
+        (Some(stop), Some(target)) => {
+            let stop_first = stop.changed_at() <= target.changed_at();
+            alerts.push(format!(
+                "Both the stop and the take-profit order of trade {} on {} filled; closed it at the {} fill, the later {} fill left an opposite position at the broker that needs manual review",
+                trade.id,
+                trade.ticker,
+                if stop_first { "stop" } else { "take-profit" },
+                if stop_first { "take-profit" } else { "stop" }
+            ));
+            Some(if stop_first {
+                (stop, true)
+            } else {
+                (target, false)
+            })
+        }
+        (Some(stop), None) => Some((stop, true)),
+        (None, Some(target)) => Some((target, false)),
+        (None, None) => None,
+    }
+}
+
 fn apply_closure(trade: &mut Trade, evaluation: &OrderEvaluation, is_stop: bool) {
     let changed_at = evaluation.changed_at();
     trade.set_status(TradeStatus::Closed, changed_at);
@@ -393,13 +743,6 @@ fn prices_close(a: f64, b: f64) -> bool {
     (a - b).abs() <= abs_tolerance || (a - b).abs() <= rel_tolerance
 }
 
-fn normalize_trade_date(date: DateTime<Utc>) -> DateTime<Utc> {
-    date.date_naive()
-        .and_hms_opt(0, 0, 0)
-        .expect("midnight should always be valid")
-        .and_utc()
-}
-
 fn update_mark_to_market_pnl(
     trade: &mut Trade,
     last_close_by_ticker: &HashMap<String, f64>,
@@ -430,6 +773,7 @@ fn should_cancel_trade(
     entry: &Option<OrderEvaluation>,
     stop_order: &Option<OrderEvaluation>,
     exit_order: &Option<OrderEvaluation>,
+    target_pending: bool,
     has_position_match: bool,
 ) -> bool {
     if trade.status == TradeStatus::Pending {
@@ -448,7 +792,7 @@ fn should_cancel_trade(
                     .as_ref()
                     .map(|evaluation| matches!(evaluation.state, OrderState::Cancelled))
                     .unwrap_or(false);
-                if exit_missing || exit_cancelled {
+                if (exit_missing || exit_cancelled) && !target_pending {
                     return true;
                 }
             }
@@ -473,3 +817,383 @@ fn entry_order_ready_for_cancellation(trade: &Trade, entry: &Option<OrderEvaluat
         .map(|evaluation| matches!(evaluation.state, OrderState::Pending))
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AccountRiskOverrides, PaperBrokerSettings};
+    use crate::engine::AccountStateSnapshot;
+    use crate::models::Candle;
+    use crate::paper_broker::{simulate_fill, PaperOrder};
+    use chrono::TimeZone;
+    use std::cell::RefCell;
+
+    struct FakeBroker {
+        evaluations: HashMap<String, OrderEvaluation>,
+        resized: RefCell<Vec<(String, i32)>>,
+    }
+
+    impl Broker for FakeBroker {
+        async fn fetch_account_state(&self) -> Result<AccountStateSnapshot> {
+            unimplemented!("not used by reconcile_trade")
+        }
+
+        async fn evaluate_order(&self, order_id: &str) -> Result<Option<OrderEvaluation>> {
+            Ok(self.evaluations.get(order_id).cloned())
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<bool> {
+            Ok(true)
+        }
+
+        async fn resize_order(&self, order_id: &str, quantity: i32) -> Result<String> {
+            self.resized
+                .borrow_mut()
+                .push((order_id.to_string(), quantity));
+            Ok(format!("{}-resized", order_id))
+        }
+
+        async fn fetch_halted_symbols(&self, _symbols: &[String]) -> HashSet<String> {
+            HashSet::new()
+        }
+    }
+
+    #[tokio::test]
+    async fn cancelled_partial_entry_splits_off_unfilled_remainder() {
+        let filled_at = Utc.with_ymd_and_hms(2024, 3, 5, 15, 0, 0).unwrap();
+        let evaluation = |state, filled_price, filled_quantity| OrderEvaluation {
+            state,
+            filled_price,
+            filled_quantity,
+            timestamp: Some(filled_at),
+        };
+        let broker = FakeBroker {
+            evaluations: HashMap::from([
+                (
+                    "entry".to_string(),
+                    evaluation(OrderState::Cancelled, Some(10.5), Some(40.0)),
+                ),
+                (
+                    "stop".to_string(),
+                    evaluation(OrderState::Pending, None, None),
+                ),
+            ]),
+            resized: RefCell::new(Vec::new()),
+        };
+        let mut trade = Trade {
+            id: "trade-1".to_string(),
+            strategy_id: "strategy".to_string(),
+            ticker: "AAA".to_string(),
+            quantity: 100,
+            price: 10.0,
+            date: Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap(),
+            status: TradeStatus::Pending,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: Some(9.0),
+            stop_loss_triggered: None,
+            entry_order_id: Some("entry".to_string()),
+            entry_cancel_after: None,
+            stop_order_id: Some("stop".to_string()),
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        };
+
+        let mut split_trades = Vec::new();
+        let changed = reconcile_trade(
+            &broker,
+            &mut trade,
+            &HashMap::new(),
+            &[],
+            &mut split_trades,
+            &mut Vec::new(),
+            SessionCalendar::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(changed);
+        assert_eq!(trade.status, TradeStatus::Active);
+        assert_eq!((trade.quantity, trade.original_quantity()), (40, 40));
+        assert_eq!(trade.price, 10.5);
+        assert_eq!(trade.stop_order_id.as_deref(), Some("stop-resized"));
+        assert_eq!(*broker.resized.borrow(), vec![("stop".to_string(), 40)]);
+        assert_eq!(split_trades.len(), 1);
+        assert_eq!(split_trades[0].id, "trade-1-unfilled");
+        assert_eq!(split_trades[0].quantity, 60);
+        assert_eq!(split_trades[0].status, TradeStatus::Cancelled);
+        assert!(split_trades[0].entry_order_id.is_none());
+    }
+
+    fn bracketed_trade() -> Trade {
+        Trade {
+            id: "trade-2".to_string(),
+            strategy_id: "strategy".to_string(),
+            ticker: "AAA".to_string(),
+            quantity: 100,
+            price: 10.0,
+            date: Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap(),
+            status: TradeStatus::Active,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: Some(9.0),
+            stop_loss_triggered: Some(false),
+            entry_order_id: Some("entry".to_string()),
+            entry_cancel_after: None,
+            stop_order_id: Some("stop".to_string()),
+            exit_order_id: None,
+            target_order_id: Some("target".to_string()),
+            changes: Vec::new(),
+        }
+    }
+
+    async fn reconcile_bracket(
+        stop: OrderEvaluation,
+        target: OrderEvaluation,
+    ) -> (bool, Trade, Vec<String>) {
+        let broker = FakeBroker {
+            evaluations: HashMap::from([
+                ("stop".to_string(), stop),
+                ("target".to_string(), target),
+            ]),
+            resized: RefCell::new(Vec::new()),
+        };
+        let mut trade = bracketed_trade();
+        let mut alerts = Vec::new();
+        let changed = reconcile_trade(
+            &broker,
+            &mut trade,
+            &HashMap::new(),
+            &[],
+            &mut Vec::new(),
+            &mut alerts,
+            SessionCalendar::default(),
+        )
+        .await
+        .unwrap();
+        (changed, trade, alerts)
+    }
+
+    fn evaluation(state: OrderState, filled_price: Option<f64>, hour: u32) -> OrderEvaluation {
+        OrderEvaluation {
+            state,
+            filled_price,
+            filled_quantity: filled_price.map(|_| 100.0),
+            timestamp: Some(Utc.with_ymd_and_hms(2024, 3, 8, hour, 0, 0).unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn take_profit_fill_closes_the_trade_the_broker_cancelled_the_stop_for() {
+        let (changed, trade, alerts) = reconcile_bracket(
+            evaluation(OrderState::Cancelled, None, 15),
+            evaluation(OrderState::Filled, Some(12.0), 15),
+        )
+        .await;
+
+        assert!(changed);
+        assert!(alerts.is_empty());
+        assert_eq!(trade.status, TradeStatus::Closed);
+        assert_eq!(trade.exit_price, Some(12.0));
+        assert_eq!(trade.stop_loss_triggered, Some(false));
+        assert_eq!(trade.pnl, Some(200.0));
+    }
+
+    #[tokio::test]
+    async fn earlier_fill_closes_the_trade_when_both_exit_legs_fill() {
+        let (changed, trade, alerts) = reconcile_bracket(
+            evaluation(OrderState::Filled, Some(9.0), 16),
+            evaluation(OrderState::Filled, Some(12.0), 15),
+        )
+        .await;
+        assert!(changed);
+        assert_eq!(trade.status, TradeStatus::Closed);
+        assert_eq!(trade.exit_price, Some(12.0));
+        assert_eq!(trade.stop_loss_triggered, Some(false));
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].contains("closed it at the take-profit fill, the later stop fill"));
+
+        let (_, trade, alerts) = reconcile_bracket(
+            evaluation(OrderState::Filled, Some(9.0), 14),
+            evaluation(OrderState::Filled, Some(12.0), 15),
+        )
+        .await;
+        assert_eq!(trade.exit_price, Some(9.0));
+        assert_eq!(trade.stop_loss_triggered, Some(true));
+        assert_eq!(alerts.len(), 1);
+        assert!(alerts[0].contains("closed it at the stop fill, the later take-profit fill"));
+    }
+
+    #[tokio::test]
+    async fn cancelled_stop_with_working_take_profit_keeps_the_trade_open() {
+        let (changed, trade, _) = reconcile_bracket(
+            evaluation(OrderState::Cancelled, None, 15),
+            evaluation(OrderState::Pending, None, 15),
+        )
+        .await;
+
+        assert!(!changed);
+        assert_eq!(trade.status, TradeStatus::Active);
+        assert_eq!(trade.stop_order_id.as_deref(), Some("stop"));
+        assert_eq!(trade.target_order_id.as_deref(), Some("target"));
+    }
+
+    /// Evaluates paper orders with the paper broker's fill simulation over daily candles.
+    struct SimulatedBroker {
+        orders: HashMap<String, PaperOrder>,
+        candles: Vec<Candle>,
+    }
+
+    impl Broker for SimulatedBroker {
+        async fn fetch_account_state(&self) -> Result<AccountStateSnapshot> {
+            unimplemented!("not used by reconcile_trade")
+        }
+
+        async fn evaluate_order(&self, order_id: &str) -> Result<Option<OrderEvaluation>> {
+            let settings = PaperBrokerSettings {
+                latency_bars: 1,
+                partial_fill_probability: 0.0,
+                starting_cash: 100_000.0,
+            };
+            Ok(self
+                .orders
+                .get(order_id)
+                .map(|order| simulate_fill(order_id, order, &self.candles, &settings)))
+        }
+
+        async fn cancel_order(&self, _order_id: &str) -> Result<bool> {
+            Ok(true)
+        }
+
+        async fn resize_order(&self, order_id: &str, _quantity: i32) -> Result<String> {
+            Ok(order_id.to_string())
+        }
+
+        async fn fetch_halted_symbols(&self, _symbols: &[String]) -> HashSet<String> {
+            HashSet::new()
+        }
+    }
+
+    /// Plans a 100-share entry at 10 with a 9 stop on an account targeting twice the stop
+    /// risk, fills it, places its exits the way paper dispatch does and reconciles the trade
+    /// against `bars` of (open, low, high) from March 6.
+    async fn reconcile_paper_bracket(bars: &[(f64, f64, f64)]) -> (Trade, SimulatedBroker) {
+        let take_profit =
+            AccountRiskOverrides::new(None, None, Some(2.0)).take_profit(10.0, Some(9.0));
+        assert_eq!(take_profit, Some(12.0));
+        let mut trade = bracketed_trade();
+        trade.stop_order_id = Some("paper-stop-op".to_string());
+        trade.target_order_id = Some("paper-target-op".to_string());
+
+        let leg = |order_id: &str| {
+            let exit = |limit_price, stop_price| {
+                PaperOrder::protective_exit(
+                    "AAA",
+                    trade.quantity,
+                    limit_price,
+                    stop_price,
+                    trade.date,
+                    true,
+                )
+            };
+            PaperOrder::exit_leg(
+                order_id,
+                Some(("paper-stop-op", exit(None, trade.stop_loss))),
+                Some(("paper-target-op", exit(take_profit, None))),
+            )
+            .unwrap()
+        };
+        let broker = SimulatedBroker {
+            orders: HashMap::from([
+                ("paper-stop-op".to_string(), leg("paper-stop-op")),
+                ("paper-target-op".to_string(), leg("paper-target-op")),
+            ]),
+            candles: bars
+                .iter()
+                .enumerate()
+                .map(|(index, &(open, low, high))| Candle {
+                    ticker: "AAA".to_string(),
+                    date: Utc
+                        .with_ymd_and_hms(2024, 3, 6 + index as u32, 0, 0, 0)
+                        .unwrap(),
+                    open,
+                    high,
+                    low,
+                    close: open,
+                    unadjusted_close: None,
+                    volume_shares: 1_000_000,
+                })
+                .collect(),
+        };
+        reconcile_trade(
+            &broker,
+            &mut trade,
+            &HashMap::new(),
+            &[],
+            &mut Vec::new(),
+            &mut Vec::new(),
+            SessionCalendar::default(),
+        )
+        .await
+        .unwrap();
+        (trade, broker)
+    }
+
+    async fn state_of(broker: &SimulatedBroker, order_id: &str) -> OrderState {
+        broker
+            .evaluate_order(order_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .state
+    }
+
+    #[tokio::test]
+    async fn paper_bracket_closes_on_the_first_leg_and_cancels_the_other() {
+        // The target fills on March 7 and cancels the stop the March 8 low would have hit.
+        let (trade, broker) =
+            reconcile_paper_bracket(&[(10.0, 9.5, 11.0), (11.5, 11.0, 12.5), (9.0, 8.5, 9.5)])
+                .await;
+        assert_eq!(trade.status, TradeStatus::Closed);
+        assert_eq!(trade.exit_price, Some(12.0));
+        assert_eq!(trade.stop_loss_triggered, Some(false));
+        assert_eq!(trade.pnl, Some(200.0));
+        assert!(matches!(
+            state_of(&broker, "paper-stop-op").await,
+            OrderState::Cancelled
+        ));
+
+        // The stop fills on March 7 and cancels the target the March 8 high would have hit.
+        let (trade, broker) =
+            reconcile_paper_bracket(&[(10.0, 9.5, 11.0), (9.5, 8.5, 10.0), (11.5, 11.0, 12.5)])
+                .await;
+        assert_eq!(trade.exit_price, Some(9.0));
+        assert_eq!(trade.stop_loss_triggered, Some(true));
+        assert!(matches!(
+            state_of(&broker, "paper-target-op").await,
+            OrderState::Cancelled
+        ));
+
+        // A candle reaching both legs fills the stop.
+        let (trade, broker) = reconcile_paper_bracket(&[(10.0, 8.5, 12.5)]).await;
+        assert_eq!(trade.exit_price, Some(9.0));
+        assert!(matches!(
+            state_of(&broker, "paper-target-op").await,
+            OrderState::Cancelled
+        ));
+
+        // Neither leg reached keeps the trade open with both orders working.
+        let (trade, broker) = reconcile_paper_bracket(&[(10.0, 9.5, 11.0)]).await;
+        assert_eq!(trade.status, TradeStatus::Active);
+        assert_eq!(trade.target_order_id.as_deref(), Some("paper-target-op"));
+        assert!(matches!(
+            state_of(&broker, "paper-target-op").await,
+            OrderState::Pending
+        ));
+    }
+}
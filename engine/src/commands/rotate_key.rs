@@ -0,0 +1,31 @@
+use crate::context::AppContext;
+use crate::database::{encryption_key_id, load_encryption_key, DATABASE_KEY_ENV_VAR};
+use anyhow::{anyhow, Result};
+use log::info;
+
+pub async fn run(app: &AppContext, old_key_env: &str) -> Result<()> {
+    let old_key = load_encryption_key(old_key_env)?;
+    let new_key = load_encryption_key(DATABASE_KEY_ENV_VAR)?;
+    if old_key == new_key {
+        return Err(anyhow!(
+            "{} and {} hold the same key; set {} to the new key before rotating",
+            old_key_env,
+            DATABASE_KEY_ENV_VAR,
+            DATABASE_KEY_ENV_VAR
+        ));
+    }
+
+    info!(
+        "Re-encrypting secrets from key {} to key {}",
+        encryption_key_id(&old_key),
+        encryption_key_id(&new_key)
+    );
+    let db = app.database().await?;
+    let rotated = db.rotate_encryption_key(&old_key, &new_key).await?;
+    info!(
+        "Key rotation completed: re-encrypted {} value{}",
+        rotated,
+        if rotated == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use crate::context::AppContext;
+use crate::database::BacktestCacheEntry;
+use crate::storage::Storage;
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use log::info;
+use serde::Serialize;
+use serde_json::json;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SensitivityMetric {
+    Cagr,
+    Sharpe,
+    Calmar,
+}
+
+impl SensitivityMetric {
+    fn label(self) -> &'static str {
+        match self {
+            SensitivityMetric::Cagr => "cagr",
+            SensitivityMetric::Sharpe => "sharpe",
+            SensitivityMetric::Calmar => "calmar",
+        }
+    }
+
+    fn value(self, entry: &BacktestCacheEntry) -> f64 {
+        match self {
+            SensitivityMetric::Cagr => entry.cagr,
+            SensitivityMetric::Sharpe => entry.sharpe_ratio,
+            SensitivityMetric::Calmar => entry.calmar_ratio,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SensitivityFormat {
+    Csv,
+    Json,
+}
+
+pub struct SensitivityOptions<'a> {
+    pub param_x: &'a str,
+    pub param_y: &'a str,
+    pub metric: SensitivityMetric,
+    pub format: SensitivityFormat,
+    pub output: Option<&'a Path>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitivityCell {
+    pub value: Option<f64>,
+    /// Number of cached parameter sets at this point; 0 marks an interpolated cell.
+    pub samples: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitivityGrid {
+    pub x_values: Vec<f64>,
+    pub y_values: Vec<f64>,
+    /// Row-major by `y_values`, one cell per `x_values` entry.
+    pub cells: Vec<Vec<SensitivityCell>>,
+}
+
+pub async fn run(
+    app: &AppContext,
+    template_id: &str,
+    options: SensitivityOptions<'_>,
+) -> Result<()> {
+    if options.param_x == options.param_y {
+        return Err(anyhow!(
+            "--param-x and --param-y must name different parameters"
+        ));
+    }
+    let entries = if let Some(store) = app.local_store()? {
+        store
+            .backtest_cache_entries_for_template(template_id)
+            .await?
+    } else {
+        let db = app.database().await?;
+        db.backtest_cache_entries_for_template(template_id).await?
+    };
+
+    let points: Vec<(f64, f64, f64)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let x = *entry.parameters.get(options.param_x)?;
+            let y = *entry.parameters.get(options.param_y)?;
+            let value = options.metric.value(entry);
+            (x.is_finite() && y.is_finite() && value.is_finite()).then_some((x, y, value))
+        })
+        .collect();
+    if points.is_empty() {
+        return Err(anyhow!(
+            "No cached backtests for template {} have finite {} with both {} and {}",
+            template_id,
+            options.metric.label(),
+            options.param_x,
+            options.param_y
+        ));
+    }
+
+    let grid = build_sensitivity_grid(&points);
+    let evaluated = grid
+        .cells
+        .iter()
+        .flatten()
+        .filter(|cell| cell.samples > 0)
+        .count();
+    info!(
+        "Built {}x{} {} grid ({} x {}) from {} cached backtest(s); {} of {} cells evaluated, the rest filled from nearest neighbors",
+        grid.x_values.len(),
+        grid.y_values.len(),
+        options.metric.label(),
+        options.param_x,
+        options.param_y,
+        points.len(),
+        evaluated,
+        grid.x_values.len() * grid.y_values.len()
+    );
+    if let Some((x, y, best, neighbors)) = best_cell_with_neighbor_mean(&grid) {
+        info!(
+            "Best {} {:.4} at {}={}, {}={}; neighboring cells average {:.4}",
+            options.metric.label(),
+            best,
+            options.param_x,
+            x,
+            options.param_y,
+            y,
+            neighbors
+        );
+    }
+
+    let rendered = match options.format {
+        SensitivityFormat::Csv => render_csv(&grid, options.param_x, options.param_y),
+        SensitivityFormat::Json => serde_json::to_string_pretty(&json!({
+            "templateId": template_id,
+            "metric": options.metric.label(),
+            "paramX": options.param_x,
+            "paramY": options.param_y,
+            "xValues": grid.x_values,
+            "yValues": grid.y_values,
+            "cells": grid.cells,
+        }))?,
+    };
+    match options.output {
+        Some(path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            info!("Wrote sensitivity grid to {}", path.display());
+        }
+        None => println!("{}", rendered),
+    }
+    Ok(())
+}
+
+/// Lays `(x, y, value)` points on the grid of their distinct x and y values. Points sharing a
+/// cell keep the best value (other parameters differ); empty cells take the mean of the
+/// nearest evaluated cells by grid-index distance.
+pub fn build_sensitivity_grid(points: &[(f64, f64, f64)]) -> SensitivityGrid {
+    let axis = |pick: fn(&(f64, f64, f64)) -> f64| {
+        let mut values: Vec<f64> = points.iter().map(pick).collect();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        values.dedup();
+        values
+    };
+    let x_values = axis(|point| point.0);
+    let y_values = axis(|point| point.1);
+    let index_of = |values: &[f64], value: f64| {
+        values
+            .binary_search_by(|probe| probe.partial_cmp(&value).unwrap_or(Ordering::Equal))
+            .unwrap_or(0)
+    };
+
+    let mut evaluated: HashMap<(usize, usize), (f64, usize)> = HashMap::new();
+    for &(x, y, value) in points {
+        let key = (index_of(&y_values, y), index_of(&x_values, x));
+        let cell = evaluated.entry(key).or_insert((value, 0));
+        cell.0 = cell.0.max(value);
+        cell.1 += 1;
+    }
+
+    let cells = (0..y_values.len())
+        .map(|row| {
+            (0..x_values.len())
+                .map(|col| match evaluated.get(&(row, col)) {
+                    Some(&(value, samples)) => SensitivityCell {
+                        value: Some(value),
+                        samples,
+                    },
+                    None => SensitivityCell {
+                        value: nearest_mean(&evaluated, row, col),
+                        samples: 0,
+                    },
+                })
+                .collect()
+        })
+        .collect();
+
+    SensitivityGrid {
+        x_values,
+        y_values,
+        cells,
+    }
+}
+
+fn nearest_mean(
+    evaluated: &HashMap<(usize, usize), (f64, usize)>,
+    row: usize,
+    col: usize,
+) -> Option<f64> {
+    let distance = |(r, c): (usize, usize)| r.abs_diff(row).pow(2) + c.abs_diff(col).pow(2);
+    let nearest = evaluated.keys().copied().map(distance).min()?;
+    let values: Vec<f64> = evaluated
+        .iter()
+        .filter(|(key, _)| distance(**key) == nearest)
+        .map(|(_, (value, _))| *value)
+        .collect();
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// The best evaluated cell and the mean of its up to eight surrounding cells; a mean close to
+/// the best value suggests a plateau, a much lower one a spike.
+fn best_cell_with_neighbor_mean(grid: &SensitivityGrid) -> Option<(f64, f64, f64, f64)> {
+    let (row, col, best) = grid
+        .cells
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells.iter().enumerate().filter_map(move |(col, cell)| {
+                (cell.samples > 0).then_some((row, col, cell.value?))
+            })
+        })
+        .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal))?;
+
+    let neighbors: Vec<f64> = (row.saturating_sub(1)..=row + 1)
+        .flat_map(|r| (col.saturating_sub(1)..=col + 1).map(move |c| (r, c)))
+        .filter(|&(r, c)| (r, c) != (row, col))
+        .filter_map(|(r, c)| grid.cells.get(r)?.get(c)?.value)
+        .collect();
+    let neighbor_mean = if neighbors.is_empty() {
+        best
+    } else {
+        neighbors.iter().sum::<f64>() / neighbors.len() as f64
+    };
+    Some((grid.x_values[col], grid.y_values[row], best, neighbor_mean))
+}
+
+fn render_csv(grid: &SensitivityGrid, param_x: &str, param_y: &str) -> String {
+    let mut lines = vec![format!("{},{},value,samples", param_x, param_y)];
+    for (row, y) in grid.y_values.iter().enumerate() {
+        for (col, x) in grid.x_values.iter().enumerate() {
+            let cell = &grid.cells[row][col];
+            lines.push(format!(
+                "{},{},{},{}",
+                x,
+                y,
+                cell.value
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                cell.samples
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_missing_cells_from_nearest_evaluated_neighbors() {
+        let points = vec![
+            (1.0, 10.0, 0.1),
+            (1.0, 10.0, 0.3),
+            (3.0, 10.0, 0.5),
+            (2.0, 20.0, 0.2),
+            (3.0, 30.0, 0.4),
+        ];
+        let grid = build_sensitivity_grid(&points);
+        assert_eq!(grid.x_values, vec![1.0, 2.0, 3.0]);
+        assert_eq!(grid.y_values, vec![10.0, 20.0, 30.0]);
+
+        let value = |row: usize, col: usize| grid.cells[row][col].value.unwrap();
+        assert_eq!(grid.cells[0][0].samples, 2);
+        assert!((value(0, 0) - 0.3).abs() < 1e-9);
+        // (y=10, x=2) touches (10, 1), (10, 3) and (20, 2) at distance 1.
+        assert_eq!(grid.cells[0][1].samples, 0);
+        assert!((value(0, 1) - (0.3 + 0.5 + 0.2) / 3.0).abs() < 1e-9);
+        assert!((value(2, 0) - 0.2).abs() < 1e-9);
+
+        let (x, y, best, neighbors) = best_cell_with_neighbor_mean(&grid).unwrap();
+        assert_eq!((x, y, best), (3.0, 10.0, 0.5));
+        assert!((neighbors - (value(0, 1) + value(1, 1) + value(1, 2)) / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::cancellation::is_cancellation;
+use crate::commands::{
+    backtest_active::{self, BacktestScope, TickerSelection},
+    generate_signals,
+    optimize::{self, OptimizeOptions},
+    plan_operations::{self, PlanOperationsOptions},
+};
+use crate::context::AppContext;
+use crate::metrics::{self, CommandOutcome, Metrics};
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tonic::{service::Interceptor, transport::Server, Request, Response, Status};
+
+/// Shared secret every call must send as `authorization: Bearer <token>`.
+const GRPC_TOKEN_ENV: &str = "ENGINE_GRPC_TOKEN";
+
+mod proto {
+    tonic::include_proto!("stratcraft.engine.v1");
+}
+
+use proto::engine_server::{Engine, EngineServer};
+use proto::{
+    BacktestRequest, CommandReply, GenerateSignalsRequest, OptimizeRequest, PlanOperationsRequest,
+};
+
+/// Serves the `proto/engine.proto` RPCs on `address` until Ctrl-C, which also stops running calls
+/// at their next checkpoint. Refuses to start without `ENGINE_GRPC_TOKEN`. Prometheus metrics
+/// of the calls are served on `metrics_address` when set.
+pub async fn run(
+    app: &AppContext,
+    address: SocketAddr,
+    default_data_file: PathBuf,
+    metrics_address: Option<SocketAddr>,
+) -> Result<()> {
+    let token = std::env::var(GRPC_TOKEN_ENV)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("{} must be set to serve gRPC calls", GRPC_TOKEN_ENV))?;
+    let metrics = Arc::new(Metrics::new("serve"));
+    let endpoint = match metrics_address {
+        Some(metrics_address) => Some(metrics::spawn(metrics.clone(), metrics_address).await?),
+        None => None,
+    };
+    let service = EngineService {
+        app: app.clone(),
+        default_data_file,
+        running: Arc::new(Mutex::new(())),
+        metrics,
+    };
+    info!("Serving engine gRPC API on {}", address);
+    let served = Server::builder()
+        .add_service(EngineServer::with_interceptor(
+            service,
+            TokenInterceptor { token },
+        ))
+        .serve_with_shutdown(address, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await;
+    if let Some(endpoint) = endpoint {
+        endpoint.abort();
+    }
+    Ok(served?)
+}
+
+struct EngineService {
+    app: AppContext,
+    default_data_file: PathBuf,
+    /// Commands load full market data and use every core, so they run one at a time like the
+    /// CLI jobs they replace; later calls wait for the running one.
+    running: Arc<Mutex<()>>,
+    metrics: Arc<Metrics>,
+}
+
+impl EngineService {
+    async fn execute<F, Fut>(
+        &self,
+        label: &'static str,
+        command: F,
+    ) -> Result<Response<CommandReply>, Status>
+    where
+        F: FnOnce(AppContext) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<()>>,
+    {
+        let _running = self.running.lock().await;
+        info!("gRPC {} started", label);
+        self.metrics.set_running(true);
+        let started = Instant::now();
+        let app = self.app.clone();
+        let runtime = tokio::runtime::Handle::current();
+        // Command futures are not all `Send`, so each runs to completion on a blocking thread.
+        let outcome = tokio::task::spawn_blocking(move || runtime.block_on(command(app)))
+            .await
+            .map_err(|err| anyhow!("{} worker failed: {}", label, err))
+            .and_then(|outcome| outcome);
+        let duration_seconds = started.elapsed().as_secs_f64();
+        self.metrics.set_running(false);
+        match outcome {
+            Ok(()) => {
+                info!("gRPC {} finished in {:.1}s", label, duration_seconds);
+                self.metrics
+                    .record(label, CommandOutcome::Succeeded, duration_seconds);
+                Ok(Response::new(CommandReply { duration_seconds }))
+            }
+            Err(err) => {
+                warn!("gRPC {} failed: {:#}", label, err);
+                let outcome = if is_cancellation(&err) {
+                    CommandOutcome::Cancelled
+                } else {
+                    CommandOutcome::Failed
+                };
+                self.metrics.record(label, outcome, duration_seconds);
+                Err(Status::internal(format!("{:#}", err)))
+            }
+        }
+    }
+
+    fn data_file(&self, requested: Option<String>) -> Result<PathBuf, String> {
+        match requested {
+            Some(requested) => resolve_data_file(&self.default_data_file, &requested),
+            None => Ok(self.default_data_file.clone()),
+        }
+    }
+}
+
+/// Rejects calls whose `authorization` metadata is not `Bearer <token>`.
+#[derive(Clone)]
+struct TokenInterceptor {
+    token: String,
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let provided = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if constant_time_eq(provided.as_bytes(), self.token.as_bytes()) {
+            Ok(request)
+        } else {
+            Err(Status::unauthenticated("missing or invalid bearer token"))
+        }
+    }
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Resolves a requested `data_file` inside the directory of the default snapshot; absolute
+/// paths, `..` and symlinks leading out of it are rejected.
+fn resolve_data_file(default_data_file: &Path, requested: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(requested.trim());
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "data_file must be a relative path inside the data directory, got {}",
+            requested
+        ));
+    }
+    let directory = match default_data_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let not_found = || format!("data_file {} not found in the data directory", requested);
+    let directory = directory.canonicalize().map_err(|_| not_found())?;
+    let path = directory
+        .join(relative)
+        .canonicalize()
+        .map_err(|_| not_found())?;
+    if path.starts_with(&directory) {
+        Ok(path)
+    } else {
+        Err(format!(
+            "data_file {} is outside the data directory",
+            requested
+        ))
+    }
+}
+
+#[tonic::async_trait]
+impl Engine for EngineService {
+    async fn backtest(
+        &self,
+        request: Request<BacktestRequest>,
+    ) -> Result<Response<CommandReply>, Status> {
+        let request = request.into_inner();
+        let scope = match proto::BacktestScope::try_from(request.scope) {
+            Ok(proto::BacktestScope::Validation) => BacktestScope::Validation,
+            Ok(proto::BacktestScope::Training) => BacktestScope::Training,
+            Ok(proto::BacktestScope::All) => BacktestScope::All,
+            Err(_) => return Err(Status::invalid_argument("unknown backtest scope")),
+        };
+        let selection = TickerSelection {
+            tickers: request.tickers,
+            ..TickerSelection::default()
+        };
+        self.execute("backtest", move |app| async move {
+            backtest_active::run(&app, scope, &request.months, &selection).await
+        })
+        .await
+    }
+
+    async fn plan_operations(
+        &self,
+        request: Request<PlanOperationsRequest>,
+    ) -> Result<Response<CommandReply>, Status> {
+        let options = PlanOperationsOptions {
+            dry_run: request.into_inner().dry_run,
+            explain: false,
+        };
+        self.execute("plan-operations", move |app| async move {
+            plan_operations::run(&app, options, None).await
+        })
+        .await
+    }
+
+    async fn generate_signals(
+        &self,
+        _request: Request<GenerateSignalsRequest>,
+    ) -> Result<Response<CommandReply>, Status> {
+        self.execute("generate-signals", |app| async move {
+            generate_signals::run(&app).await
+        })
+        .await
+    }
+
+    async fn optimize(
+        &self,
+        request: Request<OptimizeRequest>,
+    ) -> Result<Response<CommandReply>, Status> {
+        let request = request.into_inner();
+        if request.template_id.trim().is_empty() {
+            return Err(Status::invalid_argument("template_id is required"));
+        }
+        let data_file = self
+            .data_file(request.data_file)
+            .map_err(Status::invalid_argument)?;
+        let options = OptimizeOptions {
+            max_evals: request.max_evals.map(|value| value as usize),
+            max_hours: request.max_hours,
+            plateau_evals: request.plateau_evals.map(|value| value as usize),
+            plateau_epsilon: request
+                .plateau_epsilon
+                .unwrap_or(optimize::DEFAULT_PLATEAU_EPSILON),
+            max_memory_gb: request.max_memory_gb,
+        };
+        self.execute("optimize", move |app| async move {
+            optimize::run(&app, &request.template_id, &data_file, &options).await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_need_the_bearer_token() {
+        let with_header = |value: &str| {
+            let mut request = Request::new(());
+            request
+                .metadata_mut()
+                .insert("authorization", value.parse().unwrap());
+            request
+        };
+        let mut interceptor = TokenInterceptor {
+            token: "secret".to_string(),
+        };
+        assert!(interceptor.call(with_header("Bearer secret")).is_ok());
+        let denied = interceptor.call(with_header("Bearer secreT")).unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::Unauthenticated);
+        assert!(interceptor.call(with_header("secret")).is_err());
+        assert!(interceptor.call(Request::new(())).is_err());
+    }
+
+    #[test]
+    fn data_files_must_stay_in_the_data_directory() {
+        let root = std::env::temp_dir().join(format!("serve-data-{}", uuid::Uuid::new_v4()));
+        let data_dir = root.join("data");
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(data_dir.join("other.bin"), b"").unwrap();
+        std::fs::write(root.join("secret.bin"), b"").unwrap();
+        let default_file = data_dir.join("market-data.bin");
+
+        assert_eq!(
+            resolve_data_file(&default_file, "other.bin").unwrap(),
+            data_dir.canonicalize().unwrap().join("other.bin")
+        );
+        assert!(resolve_data_file(&default_file, "../secret.bin").is_err());
+        let absolute = root.join("secret.bin");
+        assert!(resolve_data_file(&default_file, absolute.to_str().unwrap()).is_err());
+        assert!(resolve_data_file(&default_file, "missing.bin").is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secret.bin"), data_dir.join("link.bin")).unwrap();
+            assert!(resolve_data_file(&default_file, "link.bin").is_err());
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,37 @@
+use crate::context::AppContext;
+use crate::fx::normalize_currency;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDate, Utc};
+use log::info;
+
+fn currency_code(raw: &str) -> Result<String> {
+    let code = normalize_currency(Some(raw));
+    if code.len() != 3 || !code.chars().all(|ch| ch.is_ascii_alphabetic()) {
+        return Err(anyhow!("{} is not a three-letter currency code", raw));
+    }
+    Ok(code)
+}
+
+/// Records that one unit of `base` bought `rate` units of `quote` on `date` (default today).
+pub async fn run(
+    app: &AppContext,
+    base: &str,
+    quote: &str,
+    rate: f64,
+    date: Option<NaiveDate>,
+) -> Result<()> {
+    let base = currency_code(base)?;
+    let quote = currency_code(quote)?;
+    if base == quote {
+        return Err(anyhow!("--base and --quote are both {}", base));
+    }
+    if !(rate.is_finite() && rate > 0.0) {
+        return Err(anyhow!("--rate must be a positive number"));
+    }
+    let date = date.unwrap_or_else(|| Utc::now().date_naive());
+
+    let db = app.database().await?;
+    db.upsert_fx_rate(&base, &quote, date, rate).await?;
+    info!("Stored {}/{} rate {} for {}", base, quote, rate, date);
+    Ok(())
+}
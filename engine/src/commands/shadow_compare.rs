@@ -0,0 +1,279 @@
+use crate::blackout::BlackoutCalendar;
+use crate::config::{resolve_shadow_tracking_error_threshold, EngineConfig, EngineRuntimeSettings};
+use crate::context::AppContext;
+use crate::data_context::TickerScope;
+use crate::engine::Engine;
+use crate::models::{Trade, TradeStatus};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+
+pub const SHADOW_LOG_SOURCE: &str = "shadow-compare";
+const TRACKING_ERROR_EVENT: &str = "shadow_tracking_error";
+const HISTORY_BUFFER_DAYS: i64 = 30;
+
+/// Live P&L of an account strategy next to a backtest that replays the signals it generated
+/// in production over the same sessions.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowComparison {
+    pub strategy_id: String,
+    pub account_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub initial_capital: f64,
+    pub live_trades: usize,
+    pub shadow_trades: usize,
+    pub live_pnl: f64,
+    pub shadow_pnl: f64,
+    /// Live minus shadow P&L as a share of the initial capital.
+    pub tracking_error: f64,
+}
+
+/// Realized P&L of closed live trades (net of fees) plus the mark-to-market P&L of active ones
+/// at `last_closes`, falling back to their entry price.
+pub fn live_pnl(trades: &[Trade], last_closes: &HashMap<String, f64>) -> f64 {
+    trades
+        .iter()
+        .map(|trade| match trade.status {
+            TradeStatus::Closed => trade.pnl.unwrap_or_else(|| {
+                trade.exit_price.map_or(0.0, |exit_price| {
+                    (exit_price - trade.price) * trade.quantity as f64 - trade.fee.unwrap_or(0.0)
+                })
+            }),
+            TradeStatus::Active => {
+                let close = last_closes.get(&trade.ticker).copied();
+                (close.unwrap_or(trade.price) - trade.price) * trade.quantity as f64
+            }
+            _ => 0.0,
+        })
+        .sum()
+}
+
+/// Replays each account strategy's stored signals since its first filled live trade through the
+/// engine and compares the shadow P&L with the live trades' P&L. A tracking error beyond
+/// SHADOW_TRACKING_ERROR_THRESHOLD raises an `error` log, at most once per strategy and UTC day.
+pub async fn run(app: &AppContext) -> Result<()> {
+    let db = app.database().await?;
+    let settings = app.database_settings(&db).await?;
+    let threshold = resolve_shadow_tracking_error_threshold(&settings);
+
+    let mut strategies = Vec::new();
+    for strategy in db.get_active_strategies().await? {
+        let Some(account_id) = strategy.account_id.clone() else {
+            continue;
+        };
+        match db
+            .get_strategy_first_filled_trade_date(&strategy.id)
+            .await?
+        {
+            Some(first_fill) => strategies.push((strategy, account_id, first_fill)),
+            None => info!(
+                "Skipping strategy {}: no filled live trades yet",
+                strategy.name
+            ),
+        }
+    }
+    let Some(earliest) = strategies
+        .iter()
+        .map(|(_, _, first_fill)| *first_fill)
+        .min()
+    else {
+        info!("No account strategy has filled live trades to compare");
+        return Ok(());
+    };
+
+    let context = app
+        .engine_context_since(
+            TickerScope::AllTickers,
+            Some((earliest - Duration::days(HISTORY_BUFFER_DAYS)).date_naive()),
+        )
+        .await?;
+    let data = context.market_data();
+    let dates = data.unique_dates();
+    if dates.len() < 2 {
+        return Err(anyhow!("Not enough market data to replay live signals"));
+    }
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(data.settings())?;
+    let mut last_closes: HashMap<String, f64> = HashMap::new();
+    for candle in data.all_candles() {
+        last_closes.insert(candle.ticker.clone(), candle.close);
+    }
+    // The last session's signals have not been traded live yet.
+    let signal_end = dates[dates.len() - 2];
+    let end_date = dates[dates.len() - 1];
+    let now = Utc::now();
+    let day_start = now
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight should always be valid")
+        .and_utc();
+
+    let mut alerts = 0usize;
+    for (strategy, account_id, first_fill) in strategies {
+        // Start one session early so signals can open the first live entry.
+        let fill_index = dates.partition_point(|date| *date <= first_fill);
+        let start: DateTime<Utc> = dates[fill_index.saturating_sub(2)];
+        let signals = db
+            .get_signals_for_strategy_in_range(&strategy.id, start, signal_end)
+            .await?;
+        let tickers: Vec<String> = signals
+            .iter()
+            .map(|signal| signal.ticker.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if tickers.is_empty() {
+            warn!(
+                "Skipping strategy {}: no stored signals since {}",
+                strategy.name,
+                start.date_naive()
+            );
+            continue;
+        }
+
+        let mut engine = Engine::from_parameters(&strategy.parameters, runtime_settings.clone());
+        engine.set_auxiliary_data(data.auxiliary_data_arc());
+        engine.set_cancellation(app.cancellation().clone());
+        engine.set_blackout(BlackoutCalendar::build(
+            &strategy.blackout,
+            data.auxiliary_data(),
+        ));
+        let run = engine.backtest(
+            None,
+            &strategy.template_id,
+            &tickers,
+            data.all_candles(),
+            dates,
+            Some(&signals),
+            Some(start),
+            None,
+        )?;
+
+        let live_trades: Vec<Trade> = db
+            .get_strategy_live_trades(&strategy.id)
+            .await?
+            .into_iter()
+            .filter(|trade| matches!(trade.status, TradeStatus::Active | TradeStatus::Closed))
+            .collect();
+        let initial_capital = EngineConfig::from_parameters(&strategy.parameters).initial_capital;
+        let live = live_pnl(&live_trades, &last_closes);
+        let shadow = run.result.final_portfolio_value - initial_capital;
+        let comparison = ShadowComparison {
+            strategy_id: strategy.id.clone(),
+            account_id: account_id.clone(),
+            start_date: start.date_naive(),
+            end_date: end_date.date_naive(),
+            initial_capital,
+            live_trades: live_trades.len(),
+            shadow_trades: run.result.trades.len(),
+            live_pnl: live,
+            shadow_pnl: shadow,
+            tracking_error: if initial_capital > 0.0 {
+                (live - shadow) / initial_capital
+            } else {
+                0.0
+            },
+        };
+        info!(
+            "Strategy {}: live P&L {:.2} over {} trades, shadow P&L {:.2} over {} trades since {} (tracking error {:+.2}%)",
+            strategy.name,
+            comparison.live_pnl,
+            comparison.live_trades,
+            comparison.shadow_pnl,
+            comparison.shadow_trades,
+            comparison.start_date,
+            comparison.tracking_error * 100.0
+        );
+        if comparison.tracking_error.abs() <= threshold {
+            continue;
+        }
+
+        let message = format!(
+            "Live P&L of strategy {} on account {} diverges from the shadow replay of its signals beyond the tracking error threshold",
+            strategy.name, account_id
+        );
+        if db
+            .has_account_event_log_since(
+                SHADOW_LOG_SOURCE,
+                &account_id,
+                TRACKING_ERROR_EVENT,
+                Some(&message),
+                day_start,
+            )
+            .await?
+        {
+            continue;
+        }
+        warn!("{}", message);
+        db.insert_system_log(
+            SHADOW_LOG_SOURCE,
+            "error",
+            &message,
+            Some(json!({
+                "accountId": account_id,
+                "event": TRACKING_ERROR_EVENT,
+                "comparison": comparison,
+                "threshold": threshold,
+            })),
+        )
+        .await?;
+        alerts += 1;
+    }
+
+    info!(
+        "Shadow comparison finished: {} alert{} raised",
+        alerts,
+        if alerts == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn trade(ticker: &str, quantity: i32, price: f64, status: TradeStatus) -> Trade {
+        Trade {
+            id: format!("trade-{}", ticker),
+            strategy_id: "strategy".to_string(),
+            ticker: ticker.to_string(),
+            quantity,
+            price,
+            date: Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap(),
+            status,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: None,
+            stop_loss_triggered: None,
+            entry_order_id: Some("entry".to_string()),
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn live_pnl_marks_active_trades_and_nets_closed_fees() {
+        let mut closed = trade("AAA", 10, 20.0, TradeStatus::Closed);
+        closed.exit_price = Some(22.0);
+        closed.fee = Some(1.0);
+        let mut booked = trade("BBB", -5, 40.0, TradeStatus::Closed);
+        booked.pnl = Some(-7.5);
+        let active = trade("CCC", 4, 50.0, TradeStatus::Active);
+        let unpriced = trade("DDD", 3, 10.0, TradeStatus::Active);
+        let cancelled = trade("EEE", 100, 1.0, TradeStatus::Cancelled);
+        let closes = HashMap::from([("CCC".to_string(), 55.0), ("EEE".to_string(), 9.0)]);
+
+        let pnl = live_pnl(&[closed, booked, active, unpriced, cancelled], &closes);
+        assert!((pnl - (19.0 - 7.5 + 20.0)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::context::AppContext;
+use crate::models::{Candle, GeneratedSignal, SignalAction};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+
+const CONFIDENCE_BUCKETS: usize = 10;
+
+/// Average forward return of the signals in one confidence decile (1 = least confident).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceDecile {
+    pub decile: usize,
+    pub count: usize,
+    pub min_confidence: f64,
+    pub max_confidence: f64,
+    pub avg_return: f64,
+    pub hit_rate: f64,
+}
+
+/// Realized quality of a strategy's signals. Each signal enters at the next session's open;
+/// returns are signed so that a falling price counts in favour of a sell signal. Hit rate,
+/// average return and deciles use signals with the full `horizon_days` of candles, while
+/// `holding_day_returns[d]` averages every signal that has reached holding day `d + 1`.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalStats {
+    pub signal_count: usize,
+    pub evaluated_count: usize,
+    pub hit_rate: Option<f64>,
+    pub avg_return: Option<f64>,
+    pub confidence_deciles: Vec<ConfidenceDecile>,
+    pub holding_day_returns: Vec<f64>,
+}
+
+/// Signed close-to-entry returns for holding days 1..=`horizon`, as far as candles reach.
+fn forward_returns(signal: &GeneratedSignal, candles: &[&Candle], horizon: usize) -> Vec<f64> {
+    let direction = match signal.action {
+        SignalAction::Buy => 1.0,
+        SignalAction::Sell => -1.0,
+        SignalAction::Hold => return Vec::new(),
+    };
+    let signal_idx = candles.partition_point(|candle| candle.date <= signal.date);
+    let Some(entry) = candles.get(signal_idx).map(|candle| candle.open) else {
+        return Vec::new();
+    };
+    if !(entry.is_finite() && entry > 0.0) {
+        return Vec::new();
+    }
+    candles[signal_idx..]
+        .iter()
+        .take(horizon)
+        .map(|candle| direction * (candle.close / entry - 1.0))
+        .take_while(|value| value.is_finite())
+        .collect()
+}
+
+pub fn evaluate_signals(
+    signals: &[GeneratedSignal],
+    candles_by_ticker: &HashMap<String, Vec<&Candle>>,
+    horizon: usize,
+) -> SignalStats {
+    let mut day_sums = vec![(0.0, 0usize); horizon];
+    let mut complete: Vec<(f64, f64)> = Vec::new();
+    let mut unscored: Vec<f64> = Vec::new();
+    let mut signal_count = 0usize;
+    for signal in signals {
+        if matches!(signal.action, SignalAction::Hold) {
+            continue;
+        }
+        signal_count += 1;
+        let Some(candles) = candles_by_ticker.get(&signal.ticker) else {
+            continue;
+        };
+        let returns = forward_returns(signal, candles, horizon);
+        for (slot, value) in day_sums.iter_mut().zip(&returns) {
+            slot.0 += value;
+            slot.1 += 1;
+        }
+        if returns.len() == horizon {
+            let final_return = returns[horizon - 1];
+            match signal.confidence.filter(|value| value.is_finite()) {
+                Some(confidence) => complete.push((confidence, final_return)),
+                None => unscored.push(final_return),
+            }
+        }
+    }
+
+    let finals: Vec<f64> = complete
+        .iter()
+        .map(|(_, value)| *value)
+        .chain(unscored.iter().copied())
+        .collect();
+    let evaluated_count = finals.len();
+    let (hit_rate, avg_return) = if finals.is_empty() {
+        (None, None)
+    } else {
+        let hits = finals.iter().filter(|value| **value > 0.0).count();
+        (
+            Some(hits as f64 / evaluated_count as f64),
+            Some(finals.iter().sum::<f64>() / evaluated_count as f64),
+        )
+    };
+
+    complete.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut confidence_deciles = Vec::new();
+    if !complete.is_empty() {
+        for decile in 0..CONFIDENCE_BUCKETS {
+            let start = decile * complete.len() / CONFIDENCE_BUCKETS;
+            let end = (decile + 1) * complete.len() / CONFIDENCE_BUCKETS;
+            let bucket = &complete[start..end];
+            if bucket.is_empty() {
+                continue;
+            }
+            let count = bucket.len();
+            confidence_deciles.push(ConfidenceDecile {
+                decile: decile + 1,
+                count,
+                min_confidence: bucket[0].0,
+                max_confidence: bucket[count - 1].0,
+                avg_return: bucket.iter().map(|(_, value)| value).sum::<f64>() / count as f64,
+                hit_rate: bucket.iter().filter(|(_, value)| *value > 0.0).count() as f64
+                    / count as f64,
+            });
+        }
+    }
+
+    let holding_day_returns = day_sums
+        .into_iter()
+        .take_while(|(_, count)| *count > 0)
+        .map(|(sum, count)| sum / count as f64)
+        .collect();
+
+    SignalStats {
+        signal_count,
+        evaluated_count,
+        hit_rate,
+        avg_return,
+        confidence_deciles,
+        holding_day_returns,
+    }
+}
+
+fn format_percent(value: Option<f64>) -> String {
+    value
+        .map(|value| format!("{:.1}%", value * 100.0))
+        .unwrap_or_else(|| "n/a".to_string())
+}
+
+pub async fn run(app: &AppContext, months: u32, horizon: usize) -> Result<()> {
+    if months == 0 {
+        return Err(anyhow!("--months must be at least 1"));
+    }
+    if horizon == 0 {
+        return Err(anyhow!("--horizon must be at least 1"));
+    }
+    let db = app.database().await?;
+    let strategies = db.get_active_strategies().await?;
+    if strategies.is_empty() {
+        warn!("No active strategies to evaluate");
+        return Ok(());
+    }
+
+    let window_end: DateTime<Utc> = Utc::now();
+    let window_hours = ((months as f64) * 30.4 * 24.0).ceil() as i64;
+    let window_start = window_end - Duration::hours(window_hours);
+    let candles = db.get_all_candles(Some(window_start.date_naive())).await?;
+    let mut candles_by_ticker: HashMap<String, Vec<&Candle>> = HashMap::new();
+    for candle in &candles {
+        candles_by_ticker
+            .entry(candle.ticker.clone())
+            .or_default()
+            .push(candle);
+    }
+
+    info!(
+        "Evaluating signals of {} active strategies since {} over {} holding days",
+        strategies.len(),
+        window_start.date_naive(),
+        horizon
+    );
+    for strategy in &strategies {
+        let signals = db
+            .get_signals_for_strategy_in_range(&strategy.id, window_start, window_end)
+            .await?;
+        let stats = evaluate_signals(&signals, &candles_by_ticker, horizon);
+        let backtest_win_rate = db
+            .get_latest_backtest_performance(&strategy.id, "validation")
+            .await?
+            .map(|performance| performance.win_rate);
+        info!(
+            "{}: {} signals, {} with {} days of data; hit rate {} (validation backtest win rate {}), avg return {}",
+            strategy.name,
+            stats.signal_count,
+            stats.evaluated_count,
+            horizon,
+            format_percent(stats.hit_rate),
+            format_percent(backtest_win_rate),
+            format_percent(stats.avg_return)
+        );
+        for decile in &stats.confidence_deciles {
+            info!(
+                "  confidence decile {:>2} ({:.3}..{:.3}, {} signals): avg return {}, hit rate {}",
+                decile.decile,
+                decile.min_confidence,
+                decile.max_confidence,
+                decile.count,
+                format_percent(Some(decile.avg_return)),
+                format_percent(Some(decile.hit_rate))
+            );
+        }
+        if !stats.holding_day_returns.is_empty() {
+            let decay: Vec<String> = stats
+                .holding_day_returns
+                .iter()
+                .map(|value| format_percent(Some(*value)))
+                .collect();
+            info!("  avg return by holding day: {}", decay.join(" "));
+        }
+        db.upsert_signal_stats(
+            &strategy.id,
+            window_start.date_naive(),
+            window_end.date_naive(),
+            horizon,
+            &stats,
+            backtest_win_rate,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::evaluate_signals;
+    use crate::models::{Candle, GeneratedSignal, SignalAction};
+    use chrono::{Duration, TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn candles(ticker: &str, closes: &[f64]) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(idx, close)| Candle {
+                ticker: ticker.to_string(),
+                date: start + Duration::days(idx as i64),
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                unadjusted_close: None,
+                volume_shares: 1_000,
+            })
+            .collect()
+    }
+
+    fn signal(ticker: &str, day: i64, action: SignalAction, confidence: f64) -> GeneratedSignal {
+        GeneratedSignal {
+            date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day),
+            ticker: ticker.to_string(),
+            action,
+            confidence: Some(confidence),
+        }
+    }
+
+    #[test]
+    fn scores_signed_forward_returns_from_the_next_open() {
+        let up = candles("UP", &[100.0, 100.0, 110.0, 121.0, 121.0]);
+        let down = candles("DOWN", &[100.0, 100.0, 90.0, 80.0]);
+        let mut by_ticker = HashMap::new();
+        by_ticker.insert("UP".to_string(), up.iter().collect::<Vec<_>>());
+        by_ticker.insert("DOWN".to_string(), down.iter().collect::<Vec<_>>());
+        let signals = vec![
+            signal("UP", 0, SignalAction::Buy, 0.9),
+            signal("DOWN", 0, SignalAction::Sell, 0.2),
+            signal("DOWN", 0, SignalAction::Buy, 0.5),
+            signal("UP", 3, SignalAction::Buy, 0.7),
+            signal("UP", 0, SignalAction::Hold, 0.1),
+        ];
+
+        let stats = evaluate_signals(&signals, &by_ticker, 3);
+        assert_eq!(stats.signal_count, 4);
+        assert_eq!(stats.evaluated_count, 3);
+        assert!((stats.hit_rate.unwrap() - 2.0 / 3.0).abs() < 1e-12);
+        assert!((stats.holding_day_returns[0] - 0.0).abs() < 1e-12);
+        assert!((stats.holding_day_returns[2] - 0.21 / 3.0).abs() < 1e-12);
+        assert_eq!(stats.holding_day_returns.len(), 3);
+        let best = stats.confidence_deciles.last().unwrap();
+        assert!((best.max_confidence - 0.9).abs() < 1e-12);
+        assert!((best.avg_return - 0.21).abs() < 1e-12);
+    }
+}
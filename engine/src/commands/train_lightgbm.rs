@@ -14,15 +14,20 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::ValueEnum;
 use serde::Serialize;
 
-use crate::config::{require_setting_date, EngineRuntimeSettings};
+use crate::config::{require_setting_date, EngineRuntimeSettings, StopLossConfig};
 use crate::context::AppContext;
 use crate::data_context::{MarketData, TickerScope};
+use crate::database::Database;
+use crate::indicators;
 use crate::models::Candle;
+use crate::strategy::calibration::{brier_score, fit_calibration, Calibration};
+use crate::strategy::lightgbm::features::FeatureSet;
 use crate::strategy::lightgbm::{
     compute_features_from_precomputed, load_model_from_path, precompute_inputs_for_ticker,
-    CrossSectionalContext, FeatureConfig,
+    CrossSectionalContext, FeatureConfig, MarketInputs, CALIBRATION_LINE_PREFIX,
 };
 use crate::trading_rules::has_minimum_dollar_volume;
 
@@ -33,7 +38,7 @@ const MAX_LOOKAHEAD_BARS: usize = EXTREME_HORIZON_BARS;
 const EARLY_STOPPING_ROUNDS: u32 = 100;
 const EXTREME_LABEL_COUNT: usize = 2;
 const EXTREME_LABEL_NEGATIVE: u8 = 0;
-const EXTREME_LABEL_POSITIVE: u8 = 1;
+pub(crate) const EXTREME_LABEL_POSITIVE: u8 = 1;
 const EXTREME_LABEL_NAMES: [&str; EXTREME_LABEL_COUNT] = ["no_hit", "hit"];
 const TOP_K: usize = 10;
 const LABEL_GAINS: [u32; 6] = [0, 1, 3, 7, 15, 31];
@@ -46,14 +51,147 @@ const TRAIN_AUGMENT_NOISE_ABS: f64 = 1e-4;
 const TRAIN_AUGMENT_WEIGHT_SCALE: f64 = 0.5;
 const TRAIN_AUGMENT_SEED: u64 = 4242;
 
+/// How training rows are labeled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LabelingMethod {
+    /// Hit when the high reaches `EXTREME_TARGET_MULTIPLE` times the close within
+    /// `EXTREME_HORIZON_BARS` bars.
+    #[default]
+    Extreme,
+    /// Exit at the first of an ATR profit target, an ATR stop or the horizon close; the label
+    /// is the meta-label "the long trade closed with a gain".
+    TripleBarrier,
+}
+
+/// Barriers of triple-barrier labeling, in multiples of the ATR at the entry candle. The stop
+/// defaults follow `StopLossConfig`'s ATR stop.
+#[derive(Debug, Clone, Copy)]
+pub struct TripleBarrierConfig {
+    pub profit_atr_multiple: f64,
+    pub stop_atr_multiple: f64,
+    pub horizon_bars: usize,
+    pub atr_period: usize,
+}
+
+impl Default for TripleBarrierConfig {
+    fn default() -> Self {
+        let stop_loss = StopLossConfig::default();
+        Self {
+            profit_atr_multiple: 4.0,
+            stop_atr_multiple: stop_loss.atr_multiplier,
+            horizon_bars: 60,
+            atr_period: stop_loss.atr_period,
+        }
+    }
+}
+
+/// Extra training-row weights multiplied into the labeling weights; the training total is then
+/// rescaled back to its unweighted sum so regularization settings keep their meaning.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SampleWeighting {
+    /// Halve a row's weight for every this many days it precedes the newest training row.
+    pub recency_half_life_days: Option<f64>,
+    /// Scale each row by the average uniqueness of its label window: the mean over its bars of
+    /// one over the number of same-ticker label windows covering that bar.
+    pub uniqueness: bool,
+}
+
+impl SampleWeighting {
+    fn is_enabled(&self) -> bool {
+        self.recency_half_life_days.is_some() || self.uniqueness
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LabelingOptions {
+    pub method: LabelingMethod,
+    pub triple_barrier: TripleBarrierConfig,
+    /// CSV receiving every row's barrier outcome and meta-label (triple-barrier only).
+    pub output_path: Option<PathBuf>,
+    pub weighting: SampleWeighting,
+}
+
+impl LabelingOptions {
+    pub fn validate(&self) -> Result<()> {
+        let barriers = &self.triple_barrier;
+        if self.method == LabelingMethod::TripleBarrier
+            && !(barriers.profit_atr_multiple.is_finite()
+                && barriers.profit_atr_multiple > 0.0
+                && barriers.stop_atr_multiple.is_finite()
+                && barriers.stop_atr_multiple > 0.0
+                && barriers.horizon_bars > 0
+                && barriers.atr_period > 0)
+        {
+            return Err(anyhow!(
+                "Triple-barrier labeling needs positive profit/stop ATR multiples, horizon and ATR period"
+            ));
+        }
+        if self
+            .weighting
+            .recency_half_life_days
+            .is_some_and(|days| !(days.is_finite() && days > 0.0))
+        {
+            return Err(anyhow!(
+                "Recency half-life must be a positive number of days"
+            ));
+        }
+        if self.output_path.is_some() && self.method != LabelingMethod::TripleBarrier {
+            return Err(anyhow!(
+                "Label output is only available with triple-barrier labeling"
+            ));
+        }
+        Ok(())
+    }
+
+    fn horizon_bars(&self) -> usize {
+        match self.method {
+            LabelingMethod::Extreme => MAX_LOOKAHEAD_BARS,
+            LabelingMethod::TripleBarrier => self.triple_barrier.horizon_bars,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Barrier {
+    Profit,
+    Stop,
+    Horizon,
+}
+
+impl Barrier {
+    fn label(self) -> &'static str {
+        match self {
+            Barrier::Profit => "profit",
+            Barrier::Stop => "stop",
+            Barrier::Horizon => "horizon",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BarrierOutcome {
+    barrier: Barrier,
+    holding_bars: usize,
+    exit_multiple: f64,
+}
+
 #[derive(Clone)]
-struct TrainingRow {
-    date: DateTime<Utc>,
-    features: Vec<f64>,
-    label: u8,
-    rank_label: u8,
-    max_multiple: f64,
-    weight: f64,
+pub(crate) struct TrainingRow {
+    pub(crate) ticker: Arc<str>,
+    pub(crate) date: DateTime<Utc>,
+    pub(crate) features: Vec<f64>,
+    pub(crate) label: u8,
+    pub(crate) rank_label: u8,
+    pub(crate) max_multiple: f64,
+    pub(crate) weight: f64,
+    pub(crate) barrier: Option<BarrierOutcome>,
+    /// First and last bar (inclusive, indexes into the ticker's candles) the label looks at.
+    pub(crate) label_bars: (usize, usize),
+}
+
+pub(crate) struct TrainingDatasets {
+    pub(crate) train_rows: Vec<TrainingRow>,
+    pub(crate) validation_rows: Vec<TrainingRow>,
 }
 
 #[derive(Clone)]
@@ -85,26 +223,48 @@ struct DatasetFeatureSummary {
     std_dev: Option<f64>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct LightgbmHyperparametersSummary {
-    num_iterations: u32,
-    learning_rate: f64,
-    num_leaves: u32,
-    max_depth: i32,
-    min_data_in_leaf: u32,
-    min_gain_to_split: f64,
-    lambda_l1: f64,
-    lambda_l2: f64,
-    feature_fraction: f64,
-    bagging_fraction: f64,
-    bagging_freq: u32,
-    early_stopping_round: u32,
+pub struct LightgbmHyperparameters {
+    pub num_iterations: u32,
+    pub learning_rate: f64,
+    pub num_leaves: u32,
+    /// -1 means no limit.
+    pub max_depth: i32,
+    pub min_data_in_leaf: u32,
+    pub min_gain_to_split: f64,
+    pub lambda_l1: f64,
+    pub lambda_l2: f64,
+    pub feature_fraction: f64,
+    pub bagging_fraction: f64,
+    /// 0 disables bagging.
+    pub bagging_freq: u32,
+    /// 0 disables early stopping.
+    pub early_stopping_round: u32,
+}
+
+impl Default for LightgbmHyperparameters {
+    fn default() -> Self {
+        Self {
+            num_iterations: 800,
+            learning_rate: 0.05,
+            num_leaves: 15,
+            max_depth: 5,
+            min_data_in_leaf: 100,
+            min_gain_to_split: 0.01,
+            lambda_l1: 0.0,
+            lambda_l2: 5.0,
+            feature_fraction: 0.6,
+            bagging_fraction: 0.6,
+            bagging_freq: 5,
+            early_stopping_round: EARLY_STOPPING_ROUNDS,
+        }
+    }
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct LightgbmDatasetSummary {
+pub(crate) struct LightgbmDatasetSummary {
     row_count: usize,
     feature_count: usize,
     start_date: Option<String>,
@@ -114,7 +274,7 @@ struct LightgbmDatasetSummary {
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct LightgbmValidationMetricsSummary {
+pub(crate) struct LightgbmValidationMetricsSummary {
     top_k: usize,
     positive_rate: f64,
     positives: usize,
@@ -122,14 +282,14 @@ struct LightgbmValidationMetricsSummary {
     day_count: usize,
     precision_at_k: Option<f64>,
     hit_rate_at_k: Option<f64>,
-    ndcg_at_k: Option<f64>,
+    pub(crate) ndcg_at_k: Option<f64>,
     avg_max_multiple: Option<f64>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct LightgbmTrainingSummary {
-    hyperparameters: LightgbmHyperparametersSummary,
+    hyperparameters: LightgbmHyperparameters,
     train_dataset: LightgbmDatasetSummary,
     validation_dataset: LightgbmDatasetSummary,
     validation_metrics: Option<LightgbmValidationMetricsSummary>,
@@ -138,24 +298,11 @@ struct LightgbmTrainingSummary {
 pub async fn run(
     app: &AppContext,
     output_path: Option<PathBuf>,
-    num_iterations: Option<u32>,
-    learning_rate: Option<f64>,
-    num_leaves: Option<u32>,
-    max_depth: Option<i32>,
-    min_data_in_leaf: Option<u32>,
-    min_gain_to_split: Option<f64>,
-    lambda_l1: Option<f64>,
-    lambda_l2: Option<f64>,
-    feature_fraction: Option<f64>,
-    bagging_fraction: Option<f64>,
-    bagging_freq: Option<u32>,
-    early_stopping_round: Option<u32>,
+    hyperparameters: LightgbmHyperparameters,
+    features: FeatureSet,
+    labeling: LabelingOptions,
 ) -> Result<()> {
-    let db = app.database().await?;
-    info!("Starting LightGBM training");
-    let market_data = MarketData::load(&db, TickerScope::AllTickers).await?;
-    let runtime_settings = EngineRuntimeSettings::from_settings_map(market_data.settings())?;
-
+    labeling.validate()?;
     let output_path =
         output_path.ok_or_else(|| anyhow!("LightGBM training requires an output path"))?;
     let destination: PathBuf = if output_path.is_absolute() {
@@ -169,6 +316,230 @@ pub async fn run(
             .with_context(|| format!("Failed to create {}", parent.display()))?;
     }
 
+    let db = app.database().await?;
+    info!("Starting LightGBM training");
+    let TrainingDatasets {
+        train_rows,
+        validation_rows,
+    } = build_training_datasets(app, &db, &features, &labeling).await?;
+
+    let train_dataset_summary = summarize_dataset(&train_rows);
+    let validation_dataset_summary = summarize_dataset(&validation_rows);
+
+    let exe_path = resolve_lightgbm_executable()?;
+    info!("Using LightGBM executable at {}", exe_path.display());
+
+    let train_profile_path = write_dataset_profile_html(&train_rows, "training")?;
+    let validation_profile_path = write_dataset_profile_html(&validation_rows, "validation")?;
+    info!(
+        "Dataset profiles available at {} (training) and {} (validation)",
+        train_profile_path.display(),
+        validation_profile_path.display()
+    );
+    println!(
+        "Dataset profile reports: train={} validation={}",
+        train_profile_path.display(),
+        validation_profile_path.display()
+    );
+
+    let validation_scores = train_and_predict(
+        &exe_path,
+        &hyperparameters,
+        &train_rows,
+        &validation_rows,
+        &destination,
+    )?;
+    let validation_metrics = validation_scores
+        .as_ref()
+        .map(|scores| score_validation_rows(&validation_rows, scores));
+    // Lambdarank scores become probabilities through the booster's unit sigmoid.
+    let calibration = validation_scores.as_ref().and_then(|scores| {
+        let probabilities: Vec<f64> = scores
+            .iter()
+            .map(|score| 1.0 / (1.0 + (-score).exp()))
+            .collect();
+        fit_validation_calibration(&validation_rows, &probabilities)
+    });
+
+    info!("LightGBM training complete");
+    write_model_feature_names(&destination, &features)?;
+    if let Some(calibration) = &calibration {
+        write_model_calibration(&destination, calibration)?;
+    }
+    println!("Saved LightGBM model to {}", destination.display());
+
+    if let Err(err) = load_model_from_path(&destination) {
+        warn!("Model was trained and saved, but failed to register for inference: {err}");
+    }
+
+    let training_summary = LightgbmTrainingSummary {
+        hyperparameters,
+        train_dataset: train_dataset_summary,
+        validation_dataset: validation_dataset_summary,
+        validation_metrics,
+    };
+
+    match serde_json::to_string(&training_summary) {
+        Ok(payload) => println!("STRATCRAFT_LIGHTGBM_TRAIN_SUMMARY={payload}"),
+        Err(err) => warn!("Failed to serialize LightGBM training summary: {err}"),
+    }
+
+    Ok(())
+}
+
+/// Trains a lambdarank model on `train_rows` into `destination` and returns its scores for
+/// `validation_rows`, or None when the validation rows could not be predicted.
+pub(crate) fn train_and_predict(
+    exe_path: &Path,
+    hyperparameters: &LightgbmHyperparameters,
+    train_rows: &[TrainingRow],
+    validation_rows: &[TrainingRow],
+    destination: &Path,
+) -> Result<Option<Vec<f64>>> {
+    let train_dataset_path =
+        std::env::temp_dir().join(format!("lightgbm_train_{}.svm", Uuid::new_v4()));
+    let validation_dataset_path =
+        std::env::temp_dir().join(format!("lightgbm_valid_{}.svm", Uuid::new_v4()));
+    info!(
+        "Writing training dataset to {} ({} rows) and validation dataset to {} ({} rows)",
+        train_dataset_path.display(),
+        train_rows.len(),
+        validation_dataset_path.display(),
+        validation_rows.len()
+    );
+    write_libsvm_dataset(train_rows, &train_dataset_path)?;
+    let train_weight_path = write_dataset_weights(train_rows, &train_dataset_path)?;
+    let train_query_path = write_dataset_queries(train_rows, &train_dataset_path)?;
+    write_libsvm_dataset(validation_rows, &validation_dataset_path)?;
+    let validation_weight_path = write_dataset_weights(validation_rows, &validation_dataset_path)?;
+    let validation_query_path = write_dataset_queries(validation_rows, &validation_dataset_path)?;
+
+    let LightgbmHyperparameters {
+        num_iterations,
+        learning_rate,
+        num_leaves,
+        max_depth,
+        min_data_in_leaf,
+        min_gain_to_split,
+        lambda_l1,
+        lambda_l2,
+        feature_fraction,
+        bagging_fraction,
+        bagging_freq,
+        early_stopping_round,
+    } = *hyperparameters;
+    let eval_at = format!("eval_at={}", TOP_K);
+    let truncation_level = format!("lambdarank_truncation_level={}", TOP_K);
+    let label_gain = label_gain_param();
+    let num_iterations_param = format!("num_iterations={}", num_iterations);
+    let learning_rate_param = format!("learning_rate={}", learning_rate);
+    let num_leaves_param = format!("num_leaves={}", num_leaves);
+    let max_depth_param = format!("max_depth={}", max_depth);
+    let min_data_in_leaf_param = format!("min_data_in_leaf={}", min_data_in_leaf);
+    let min_gain_to_split_param = format!("min_gain_to_split={}", min_gain_to_split);
+    let lambda_l1_param = format!("lambda_l1={}", lambda_l1);
+    let lambda_l2_param = format!("lambda_l2={}", lambda_l2);
+    let feature_fraction_param = format!("feature_fraction={}", feature_fraction);
+    let bagging_fraction_param = format!("bagging_fraction={}", bagging_fraction);
+    let bagging_freq_param = format!("bagging_freq={}", bagging_freq);
+    let data_param = format!("data={}", train_dataset_path.to_string_lossy());
+    let valid_data_param = format!("valid_data={}", validation_dataset_path.to_string_lossy());
+    let output_model_param = format!("output_model={}", destination.to_string_lossy());
+    let early_stopping_round_display = if early_stopping_round == 0 {
+        String::from("disabled")
+    } else {
+        early_stopping_round.to_string()
+    };
+
+    info!(
+        "Launching LightGBM: num_iterations={}, learning_rate={}, num_leaves={}, max_depth={}, min_data_in_leaf={}, min_gain_to_split={}, lambda_l1={}, lambda_l2={}, feature_fraction={}, bagging_fraction={}, bagging_freq={}, early_stopping_round={}",
+        num_iterations,
+        learning_rate,
+        num_leaves,
+        max_depth,
+        min_data_in_leaf,
+        min_gain_to_split,
+        lambda_l1,
+        lambda_l2,
+        feature_fraction,
+        bagging_fraction,
+        bagging_freq,
+        early_stopping_round_display,
+    );
+
+    let mut args = vec![
+        String::from("task=train"),
+        String::from("objective=lambdarank"),
+        String::from("metric=ndcg"),
+        eval_at,
+        truncation_level,
+        label_gain,
+        num_iterations_param,
+        learning_rate_param,
+        num_leaves_param,
+        max_depth_param,
+        min_data_in_leaf_param,
+        min_gain_to_split_param,
+        lambda_l1_param,
+        lambda_l2_param,
+        feature_fraction_param,
+        bagging_fraction_param,
+        bagging_freq_param,
+        data_param,
+        valid_data_param,
+    ];
+    if early_stopping_round > 0 {
+        args.push(format!("early_stopping_round={}", early_stopping_round));
+    }
+    args.push(String::from("first_metric_only=false"));
+    args.push(output_model_param);
+    args.push(String::from("verbosity=2"));
+
+    let status = Command::new(exe_path)
+        .args(&args)
+        .status()
+        .context("Failed to spawn lightgbm.exe for training")?;
+
+    let validation_scores = if status.success() {
+        predict_validation_set(
+            exe_path,
+            destination,
+            &validation_dataset_path,
+            validation_rows,
+        )
+    } else {
+        None
+    };
+
+    let _ = fs::remove_file(&train_dataset_path);
+    let _ = fs::remove_file(&validation_dataset_path);
+    let _ = fs::remove_file(&train_weight_path);
+    let _ = fs::remove_file(&validation_weight_path);
+    let _ = fs::remove_file(&train_query_path);
+    let _ = fs::remove_file(&validation_query_path);
+
+    if !status.success() {
+        return Err(anyhow!("lightgbm.exe training failed with status {status}"));
+    }
+    Ok(validation_scores)
+}
+
+/// Training rows from the training tickers and validation rows from the remaining tickers plus
+/// every tracked ticker after `LIGHTGBM_TRAINING_END_DATE`, both sorted by date. Shared by the
+/// LightGBM and neural network trainers so both models see the same labels and splits.
+pub(crate) async fn build_training_datasets(
+    app: &AppContext,
+    db: &Database,
+    features: &FeatureSet,
+    labeling: &LabelingOptions,
+) -> Result<TrainingDatasets> {
+    let market_data = MarketData::load(db, TickerScope::AllTickers).await?;
+    let settings = app
+        .setting_layers()
+        .merge(None, Some(market_data.settings()));
+    let market_data = market_data.with_settings(settings);
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(market_data.settings())?;
+
     let training_start_date =
         require_setting_date(market_data.settings(), "LIGHTGBM_TRAINING_START_DATE")?;
     let training_end_date =
@@ -185,6 +556,9 @@ pub async fn run(
     let mut training_tickers: HashSet<String> = HashSet::new();
     let mut validation_tickers: HashSet<String> = HashSet::new();
     for info in ticker_infos {
+        if info.auxiliary {
+            continue;
+        }
         if info.training {
             training_tickers.insert(info.symbol);
         } else {
@@ -204,7 +578,8 @@ pub async fn run(
 
     let feature_config = FeatureConfig::default();
     info!(
-        "Building training rows with default feature config for {} to {} ({} training tickers)...",
+        "Building training rows with default feature config ({} features) for {} to {} ({} training tickers)...",
+        features.len(),
         training_start.date_naive(),
         training_end.date_naive(),
         training_tickers.len()
@@ -217,12 +592,33 @@ pub async fn run(
         Some(&training_tickers),
         runtime_settings.minimum_dollar_volume_for_entry,
         runtime_settings.minimum_dollar_volume_lookback,
+        features,
+        labeling,
     )?;
     if train_rows.is_empty() {
         return Err(anyhow!(
             "No training rows could be generated from available market data"
         ));
     }
+    if labeling.weighting.is_enabled() {
+        apply_sample_weighting(&mut train_rows, &labeling.weighting);
+        let (min_weight, max_weight) = train_rows
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), row| {
+                (min.min(row.weight), max.max(row.weight))
+            });
+        info!(
+            "Applied sample weighting (recency half-life {}, uniqueness {}): weights range {:.4} to {:.4}",
+            labeling
+                .weighting
+                .recency_half_life_days
+                .map(|days| format!("{days} days"))
+                .unwrap_or_else(|| "off".to_string()),
+            if labeling.weighting.uniqueness { "on" } else { "off" },
+            min_weight,
+            max_weight
+        );
+    }
     let augmented = augment_training_rows_with_noise(&mut train_rows);
     if augmented > 0 {
         info!(
@@ -248,6 +644,8 @@ pub async fn run(
         Some(&validation_tickers),
         runtime_settings.minimum_dollar_volume_for_entry,
         runtime_settings.minimum_dollar_volume_lookback,
+        features,
+        labeling,
     )?;
     let mut post_training_additions = 0usize;
     let mut post_training_end: Option<DateTime<Utc>> = None;
@@ -288,6 +686,8 @@ pub async fn run(
             Some(&post_training_tickers),
             runtime_settings.minimum_dollar_volume_for_entry,
             runtime_settings.minimum_dollar_volume_lookback,
+            features,
+            labeling,
         )?;
         post_training_additions = post_training_rows.len();
         if post_training_additions > 0 {
@@ -335,185 +735,75 @@ pub async fn run(
         format_histogram(&train_hist),
         validation_rows.len(),
         format_histogram(&valid_hist),
-        EXTREME_HORIZON_BARS,
+        labeling.horizon_bars(),
     );
-
-    let train_dataset_summary = summarize_dataset(&train_rows);
-    let validation_dataset_summary = summarize_dataset(&validation_rows);
-
-    let exe_path = resolve_lightgbm_executable()?;
-    info!("Using LightGBM executable at {}", exe_path.display());
-
-    let train_dataset_path =
-        std::env::temp_dir().join(format!("lightgbm_train_{}.svm", Uuid::new_v4()));
-    let validation_dataset_path =
-        std::env::temp_dir().join(format!("lightgbm_valid_{}.svm", Uuid::new_v4()));
-    info!(
-        "Writing training dataset to {} ({} rows) and validation dataset to {} ({} rows)",
-        train_dataset_path.display(),
-        train_rows.len(),
-        validation_dataset_path.display(),
-        validation_rows.len()
-    );
-    write_libsvm_dataset(&train_rows, &train_dataset_path)?;
-    let train_weight_path = write_dataset_weights(&train_rows, &train_dataset_path)?;
-    let train_query_path = write_dataset_queries(&train_rows, &train_dataset_path)?;
-    write_libsvm_dataset(&validation_rows, &validation_dataset_path)?;
-    let validation_weight_path = write_dataset_weights(&validation_rows, &validation_dataset_path)?;
-    let validation_query_path = write_dataset_queries(&validation_rows, &validation_dataset_path)?;
-    let train_profile_path = write_dataset_profile_html(&train_rows, "training")?;
-    let validation_profile_path = write_dataset_profile_html(&validation_rows, "validation")?;
-    info!(
-        "Dataset profiles available at {} (training) and {} (validation)",
-        train_profile_path.display(),
-        validation_profile_path.display()
-    );
-    println!(
-        "Dataset profile reports: train={} validation={}",
-        train_profile_path.display(),
-        validation_profile_path.display()
-    );
-
-    let num_iterations = num_iterations.unwrap_or(800);
-    let learning_rate = learning_rate.unwrap_or(0.05);
-    let num_leaves = num_leaves.unwrap_or(15);
-    let max_depth = max_depth.unwrap_or(5);
-    let min_data_in_leaf = min_data_in_leaf.unwrap_or(100);
-    let min_gain_to_split = min_gain_to_split.unwrap_or(0.01);
-    let lambda_l1 = lambda_l1.unwrap_or(0.0);
-    let lambda_l2 = lambda_l2.unwrap_or(5.0);
-    let feature_fraction = feature_fraction.unwrap_or(0.6);
-    let bagging_fraction = bagging_fraction.unwrap_or(0.6);
-    let bagging_freq = bagging_freq.unwrap_or(5);
-    let early_stopping_round = early_stopping_round.unwrap_or(EARLY_STOPPING_ROUNDS);
-
-    let eval_at = format!("eval_at={}", TOP_K);
-    let truncation_level = format!("lambdarank_truncation_level={}", TOP_K);
-    let label_gain = label_gain_param();
-    let num_iterations_param = format!("num_iterations={}", num_iterations);
-    let learning_rate_param = format!("learning_rate={}", learning_rate);
-    let num_leaves_param = format!("num_leaves={}", num_leaves);
-    let max_depth_param = format!("max_depth={}", max_depth);
-    let min_data_in_leaf_param = format!("min_data_in_leaf={}", min_data_in_leaf);
-    let min_gain_to_split_param = format!("min_gain_to_split={}", min_gain_to_split);
-    let lambda_l1_param = format!("lambda_l1={}", lambda_l1);
-    let lambda_l2_param = format!("lambda_l2={}", lambda_l2);
-    let feature_fraction_param = format!("feature_fraction={}", feature_fraction);
-    let bagging_fraction_param = format!("bagging_fraction={}", bagging_fraction);
-    let bagging_freq_param = format!("bagging_freq={}", bagging_freq);
-    let data_param = format!("data={}", train_dataset_path.to_string_lossy());
-    let valid_data_param = format!("valid_data={}", validation_dataset_path.to_string_lossy());
-    let output_model_param = format!("output_model={}", destination.to_string_lossy());
-    let early_stopping_round_display = if early_stopping_round == 0 {
-        String::from("disabled")
-    } else {
-        early_stopping_round.to_string()
-    };
-
-    info!(
-        "Launching LightGBM: num_iterations={}, learning_rate={}, num_leaves={}, max_depth={}, min_data_in_leaf={}, min_gain_to_split={}, lambda_l1={}, lambda_l2={}, feature_fraction={}, bagging_fraction={}, bagging_freq={}, early_stopping_round={}",
-        num_iterations,
-        learning_rate,
-        num_leaves,
-        max_depth,
-        min_data_in_leaf,
-        min_gain_to_split,
-        lambda_l1,
-        lambda_l2,
-        feature_fraction,
-        bagging_fraction,
-        bagging_freq,
-        early_stopping_round_display,
-    );
-
-    let mut args = vec![
-        String::from("task=train"),
-        String::from("objective=lambdarank"),
-        String::from("metric=ndcg"),
-        eval_at,
-        truncation_level,
-        label_gain,
-        num_iterations_param,
-        learning_rate_param,
-        num_leaves_param,
-        max_depth_param,
-        min_data_in_leaf_param,
-        min_gain_to_split_param,
-        lambda_l1_param,
-        lambda_l2_param,
-        feature_fraction_param,
-        bagging_fraction_param,
-        bagging_freq_param,
-        data_param,
-        valid_data_param,
-    ];
-    if early_stopping_round > 0 {
-        args.push(format!("early_stopping_round={}", early_stopping_round));
+    if let Some(path) = &labeling.output_path {
+        write_barrier_labels(&train_rows, &validation_rows, path)?;
+        println!("Triple-barrier labels written to {}", path.display());
     }
-    args.push(String::from("first_metric_only=false"));
-    args.push(output_model_param);
-    args.push(String::from("verbosity=2"));
 
-    let status = Command::new(&exe_path)
-        .args(&args)
-        .status()
-        .context("Failed to spawn lightgbm.exe for training")?;
-
-    let validation_metrics = if status.success() {
-        evaluate_validation_set(
-            &exe_path,
-            &destination,
-            &validation_dataset_path,
-            &validation_rows,
-        )
-    } else {
-        None
-    };
-
-    let _ = fs::remove_file(&train_dataset_path);
-    let _ = fs::remove_file(&validation_dataset_path);
-    let _ = fs::remove_file(&train_weight_path);
-    let _ = fs::remove_file(&validation_weight_path);
-    let _ = fs::remove_file(&train_query_path);
-    let _ = fs::remove_file(&validation_query_path);
+    Ok(TrainingDatasets {
+        train_rows,
+        validation_rows,
+    })
+}
 
-    if !status.success() {
-        return Err(anyhow!("lightgbm.exe training failed with status {status}"));
+/// Replaces the generic `Column_<n>` names LightGBM assigns to libsvm columns with the
+/// registry names of `features`, so the model text records the feature set it was trained on.
+fn write_model_feature_names(model_path: &Path, features: &FeatureSet) -> Result<()> {
+    let text = fs::read_to_string(model_path)
+        .with_context(|| format!("Failed to read {}", model_path.display()))?;
+    let names = format!("feature_names={}", features.names().join(" "));
+    let mut output = String::with_capacity(text.len());
+    for line in text.lines() {
+        if line.starts_with("feature_names=") {
+            output.push_str(&names);
+        } else {
+            output.push_str(line);
+        }
+        output.push('\n');
     }
+    fs::write(model_path, output)
+        .with_context(|| format!("Failed to write {}", model_path.display()))
+}
 
-    info!("LightGBM training complete");
-    println!("Saved LightGBM model to {}", destination.display());
-
-    if let Err(err) = load_model_from_path(&destination) {
-        warn!("Model was trained and saved, but failed to register for inference: {err}");
+fn write_model_calibration(model_path: &Path, calibration: &Calibration) -> Result<()> {
+    let mut text = fs::read_to_string(model_path)
+        .with_context(|| format!("Failed to read {}", model_path.display()))?;
+    if !text.ends_with('\n') {
+        text.push('\n');
     }
+    text.push_str(CALIBRATION_LINE_PREFIX);
+    text.push_str(&serde_json::to_string(calibration)?);
+    text.push('\n');
+    fs::write(model_path, text).with_context(|| format!("Failed to write {}", model_path.display()))
+}
 
-    let training_summary = LightgbmTrainingSummary {
-        hyperparameters: LightgbmHyperparametersSummary {
-            num_iterations,
-            learning_rate,
-            num_leaves,
-            max_depth,
-            min_data_in_leaf,
-            min_gain_to_split,
-            lambda_l1,
-            lambda_l2,
-            feature_fraction,
-            bagging_fraction,
-            bagging_freq,
-            early_stopping_round,
-        },
-        train_dataset: train_dataset_summary,
-        validation_dataset: validation_dataset_summary,
-        validation_metrics,
+/// Fits a probability calibration on the validation rows (in date order) against their
+/// positive label and logs the Brier score it achieves.
+pub(crate) fn fit_validation_calibration(
+    validation_rows: &[TrainingRow],
+    probabilities: &[f64],
+) -> Option<Calibration> {
+    let labels: Vec<bool> = validation_rows
+        .iter()
+        .map(|row| row.label == EXTREME_LABEL_POSITIVE)
+        .collect();
+    let Some(calibration) = fit_calibration(probabilities, &labels) else {
+        warn!("Skipping probability calibration: too few validation rows or a single class");
+        return None;
     };
-
-    match serde_json::to_string(&training_summary) {
-        Ok(payload) => println!("STRATCRAFT_LIGHTGBM_TRAIN_SUMMARY={payload}"),
-        Err(err) => warn!("Failed to serialize LightGBM training summary: {err}"),
-    }
-
-    Ok(())
+    let calibrated: Vec<f64> = probabilities
+        .iter()
+        .map(|probability| calibration.apply(*probability))
+        .collect();
+    info!(
+        "Fitted {} probability calibration: validation Brier score {:.5} -> {:.5}",
+        calibration.method(),
+        brier_score(probabilities, &labels),
+        brier_score(&calibrated, &labels)
+    );
+    Some(calibration)
 }
 
 fn write_libsvm_dataset(rows: &[TrainingRow], path: &Path) -> Result<()> {
@@ -549,7 +839,7 @@ fn dataset_weight_path(dataset_path: &Path) -> PathBuf {
     PathBuf::from(os)
 }
 
-fn resolve_lightgbm_executable() -> Result<PathBuf> {
+pub(crate) fn resolve_lightgbm_executable() -> Result<PathBuf> {
     if cfg!(windows) {
         let exe_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("vendor/lightgbm.exe");
         if exe_path.exists() {
@@ -621,6 +911,7 @@ fn dataset_query_path(dataset_path: &Path) -> PathBuf {
     PathBuf::from(os)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_training_rows(
     market_data: &MarketData,
     features_config: FeatureConfig,
@@ -629,6 +920,8 @@ fn build_training_rows(
     allowed_tickers: Option<&HashSet<String>>,
     min_dollar_volume_for_entry: f64,
     min_dollar_volume_lookback: usize,
+    features: &FeatureSet,
+    labeling: &LabelingOptions,
 ) -> Result<Vec<TrainingRow>> {
     if start_date > end_date {
         return Err(anyhow!(
@@ -666,6 +959,11 @@ fn build_training_rows(
     }
 
     let cross_context = CrossSectionalContext::new(&filtered_candles_by_ticker).map(Arc::new);
+    let volatility_index = market_data.volatility_index();
+    let market = MarketInputs {
+        volatility_index: volatility_index.as_deref(),
+        ..MarketInputs::from_auxiliary(market_data.auxiliary_data())
+    };
     if cross_context.is_some() {
         info!("Cross-sectional snapshots will be generated lazily during feature extraction");
     }
@@ -685,7 +983,8 @@ fn build_training_rows(
                 Some(value) => value,
                 None => return Vec::new(),
             };
-            let max_idx = candle_refs.len().saturating_sub(MAX_LOOKAHEAD_BARS);
+            let ticker_name: Arc<str> = Arc::from(ticker.as_str());
+            let max_idx = candle_refs.len().saturating_sub(labeling.horizon_bars());
             (0..max_idx)
                 .into_par_iter()
                 .filter_map(|idx| {
@@ -704,19 +1003,46 @@ fn build_training_rows(
                         features_config,
                         &precomputed,
                         cross_context.clone(),
+                        market,
                     )?;
-                    if snapshot.values.iter().any(|value| !value.is_finite()) {
+                    let values = features.select(&snapshot.values);
+                    if values.iter().any(|value| !value.is_finite()) {
                         return None;
                     }
-                    let (label, max_multiple) = compute_extreme_label(candle_refs, idx)?;
-                    Some(TrainingRow {
+                    let mut row = TrainingRow {
+                        ticker: ticker_name.clone(),
                         date: candle_refs[idx].date,
-                        features: snapshot.values.clone(),
-                        label,
-                        rank_label: compute_rank_label(max_multiple),
-                        max_multiple,
+                        features: values,
+                        label: EXTREME_LABEL_NEGATIVE,
+                        rank_label: 0,
+                        max_multiple: 1.0,
                         weight: 1.0,
-                    })
+                        barrier: None,
+                        label_bars: (idx + 1, idx + 1),
+                    };
+                    match labeling.method {
+                        LabelingMethod::Extreme => {
+                            let (label, max_multiple) = compute_extreme_label(candle_refs, idx)?;
+                            row.label = label;
+                            row.rank_label = compute_rank_label(max_multiple);
+                            row.max_multiple = max_multiple;
+                            row.label_bars.1 =
+                                (idx + EXTREME_HORIZON_BARS).min(candle_refs.len() - 1);
+                        }
+                        LabelingMethod::TripleBarrier => {
+                            let outcome = compute_triple_barrier_outcome(
+                                candle_refs,
+                                idx,
+                                &labeling.triple_barrier,
+                            )?;
+                            row.label = triple_barrier_meta_label(&outcome);
+                            row.rank_label = triple_barrier_rank_label(&outcome);
+                            row.max_multiple = outcome.exit_multiple;
+                            row.label_bars.1 = idx + outcome.holding_bars;
+                            row.barrier = Some(outcome);
+                        }
+                    }
+                    Some(row)
                 })
                 .collect::<Vec<TrainingRow>>()
         })
@@ -726,7 +1052,10 @@ fn build_training_rows(
         });
 
     let mut rows = rows;
-    apply_extreme_sampling_and_weights(&mut rows);
+    // Triple-barrier classes are not rare events, so they keep every row at unit weight.
+    if labeling.method == LabelingMethod::Extreme {
+        apply_extreme_sampling_and_weights(&mut rows);
+    }
     Ok(rows)
 }
 
@@ -749,7 +1078,7 @@ fn apply_extreme_sampling_and_weights(rows: &mut Vec<TrainingRow>) {
         let mut zero_rows = Vec::new();
         for mut row in day_rows.drain(..) {
             if row.rank_label > 0 {
-                let capped = row.max_multiple.min(RANK_LABEL_CAP_MULTIPLE).max(1.0);
+                let capped = row.max_multiple.clamp(1.0, RANK_LABEL_CAP_MULTIPLE);
                 let quality = (capped - 1.0) / (RANK_LABEL_CAP_MULTIPLE - 1.0);
                 row.weight = 1.0 + (quality * POS_WEIGHT_BONUS);
                 kept.push(row);
@@ -774,6 +1103,66 @@ fn apply_extreme_sampling_and_weights(rows: &mut Vec<TrainingRow>) {
     *rows = kept;
 }
 
+fn apply_sample_weighting(rows: &mut [TrainingRow], weighting: &SampleWeighting) {
+    let original_total: f64 = rows.iter().map(|row| row.weight).sum();
+    if let Some(half_life_days) = weighting.recency_half_life_days {
+        if let Some(newest) = rows.iter().map(|row| row.date).max() {
+            for row in rows.iter_mut() {
+                let age_days = (newest - row.date).num_seconds() as f64 / 86_400.0;
+                row.weight *= 0.5_f64.powf(age_days / half_life_days);
+            }
+        }
+    }
+    if weighting.uniqueness {
+        let uniqueness = label_uniqueness(rows);
+        for (row, value) in rows.iter_mut().zip(uniqueness) {
+            row.weight *= value;
+        }
+    }
+    let weighted_total: f64 = rows.iter().map(|row| row.weight).sum();
+    if weighted_total > 0.0 && weighted_total.is_finite() {
+        let scale = original_total / weighted_total;
+        rows.iter_mut().for_each(|row| row.weight *= scale);
+    }
+}
+
+/// Average uniqueness of every row's label window among the rows of the same ticker.
+fn label_uniqueness(rows: &[TrainingRow]) -> Vec<f64> {
+    let mut rows_by_ticker: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, row) in rows.iter().enumerate() {
+        rows_by_ticker.entry(&row.ticker).or_default().push(idx);
+    }
+    let mut uniqueness = vec![1.0; rows.len()];
+    for indices in rows_by_ticker.values() {
+        let last_bar = indices
+            .iter()
+            .map(|&idx| rows[idx].label_bars.1)
+            .max()
+            .unwrap_or(0);
+        let mut concurrency = vec![0i64; last_bar + 2];
+        for &idx in indices {
+            let (start, end) = rows[idx].label_bars;
+            concurrency[start] += 1;
+            concurrency[end + 1] -= 1;
+        }
+        // Prefix sums of 1/concurrency give each window's average in constant time.
+        let mut active = 0i64;
+        let mut inverse_sums = Vec::with_capacity(concurrency.len() + 1);
+        inverse_sums.push(0.0);
+        for delta in &concurrency {
+            active += delta;
+            let inverse = if active > 0 { 1.0 / active as f64 } else { 0.0 };
+            inverse_sums.push(inverse_sums.last().copied().unwrap_or(0.0) + inverse);
+        }
+        for &idx in indices {
+            let (start, end) = rows[idx].label_bars;
+            uniqueness[idx] =
+                (inverse_sums[end + 1] - inverse_sums[start]) / (end + 1 - start) as f64;
+        }
+    }
+    uniqueness
+}
+
 fn augment_training_rows_with_noise(rows: &mut Vec<TrainingRow>) -> usize {
     if TRAIN_AUGMENT_DUPLICATES_PER_ROW == 0 || rows.is_empty() {
         return 0;
@@ -841,12 +1230,13 @@ fn format_histogram(histogram: &[usize; EXTREME_LABEL_COUNT]) -> String {
     format!("label_counts({})", parts.join(", "))
 }
 
-fn evaluate_validation_set(
+/// Raw model scores for the validation rows, via lightgbm.exe prediction.
+fn predict_validation_set(
     exe_path: &Path,
     model_path: &Path,
     validation_dataset_path: &Path,
     validation_rows: &[TrainingRow],
-) -> Option<LightgbmValidationMetricsSummary> {
+) -> Option<Vec<f64>> {
     if validation_rows.is_empty() {
         return None;
     }
@@ -910,6 +1300,15 @@ fn evaluate_validation_set(
         return None;
     }
 
+    Some(scores)
+}
+
+/// Prints and returns the top-k ranking metrics of `scores`, one per validation row, computed
+/// per trading day.
+pub(crate) fn score_validation_rows(
+    validation_rows: &[TrainingRow],
+    scores: &[f64],
+) -> LightgbmValidationMetricsSummary {
     let mut rows_by_day: HashMap<NaiveDate, Vec<ScoredRow>> = HashMap::new();
     let mut positives = 0usize;
     for (row, score) in validation_rows.iter().zip(scores.iter()) {
@@ -1043,7 +1442,7 @@ fn evaluate_validation_set(
         None
     };
 
-    Some(LightgbmValidationMetricsSummary {
+    LightgbmValidationMetricsSummary {
         top_k: TOP_K,
         positive_rate,
         positives,
@@ -1053,7 +1452,7 @@ fn evaluate_validation_set(
         hit_rate_at_k,
         ndcg_at_k,
         avg_max_multiple,
-    })
+    }
 }
 
 fn write_dataset_profile_html(rows: &[TrainingRow], dataset_name: &str) -> Result<PathBuf> {
@@ -1137,7 +1536,7 @@ fn write_dataset_profile_html(rows: &[TrainingRow], dataset_name: &str) -> Resul
     Ok(output_path)
 }
 
-fn summarize_dataset(rows: &[TrainingRow]) -> LightgbmDatasetSummary {
+pub(crate) fn summarize_dataset(rows: &[TrainingRow]) -> LightgbmDatasetSummary {
     let row_count = rows.len();
     let feature_count = rows.iter().map(|row| row.features.len()).max().unwrap_or(0);
     let (start_date, end_date) = match rows.iter().map(|row| row.date.date_naive()).min() {
@@ -1297,11 +1696,11 @@ fn compute_max_multiple(candle_refs: &[&Candle], idx: usize, horizon: usize) ->
     let end = (idx + horizon).min(candle_refs.len() - 1);
 
     let mut max_price = f64::NEG_INFINITY;
-    for j in (idx + 1)..=end {
+    for candle in &candle_refs[(idx + 1)..=end] {
         let price = if EXTREME_USE_HIGH {
-            candle_refs[j].high
+            candle.high
         } else {
-            candle_refs[j].close
+            candle.close
         };
         if price.is_finite() && price > max_price {
             max_price = price;
@@ -1324,6 +1723,111 @@ fn compute_extreme_label(candle_refs: &[&Candle], idx: usize) -> Option<(u8, f64
     Some((label, multiple))
 }
 
+/// Walks forward from the close at `idx` until the low touches the ATR stop, the high touches
+/// the ATR profit target or `horizon_bars` pass. Gaps through a barrier exit at the open, and
+/// a bar touching both barriers counts as a stop, mirroring the engine's intraday stop fills.
+fn compute_triple_barrier_outcome(
+    candle_refs: &[&Candle],
+    idx: usize,
+    config: &TripleBarrierConfig,
+) -> Option<BarrierOutcome> {
+    let end = idx + config.horizon_bars;
+    if end >= candle_refs.len() {
+        return None;
+    }
+    let entry = candle_refs[idx].close;
+    let atr = indicators::calculate_atr_from_candles(candle_refs, idx, config.atr_period)?;
+    if !(entry.is_finite() && entry > 0.0 && atr.is_finite() && atr > 0.0) {
+        return None;
+    }
+    let profit_price = entry + config.profit_atr_multiple * atr;
+    let stop_price = (entry - config.stop_atr_multiple * atr).max(0.0);
+    let outcome = |barrier, holding_bars, exit_price: f64| BarrierOutcome {
+        barrier,
+        holding_bars,
+        exit_multiple: exit_price / entry,
+    };
+
+    for (offset, candle) in candle_refs[(idx + 1)..=end].iter().enumerate() {
+        let holding_bars = offset + 1;
+        if candle.open <= stop_price {
+            return Some(outcome(Barrier::Stop, holding_bars, candle.open));
+        }
+        if candle.open >= profit_price {
+            return Some(outcome(Barrier::Profit, holding_bars, candle.open));
+        }
+        if candle.low <= stop_price {
+            return Some(outcome(Barrier::Stop, holding_bars, stop_price));
+        }
+        if candle.high >= profit_price {
+            return Some(outcome(Barrier::Profit, holding_bars, profit_price));
+        }
+    }
+    Some(outcome(
+        Barrier::Horizon,
+        config.horizon_bars,
+        candle_refs[end].close,
+    ))
+}
+
+fn triple_barrier_meta_label(outcome: &BarrierOutcome) -> u8 {
+    if outcome.exit_multiple > 1.0 {
+        EXTREME_LABEL_POSITIVE
+    } else {
+        EXTREME_LABEL_NEGATIVE
+    }
+}
+
+/// Ranking grade: stops and losing horizon exits 0, winning horizon exits 1, profit targets 2.
+fn triple_barrier_rank_label(outcome: &BarrierOutcome) -> u8 {
+    match outcome.barrier {
+        Barrier::Profit => 2,
+        Barrier::Horizon if outcome.exit_multiple > 1.0 => 1,
+        Barrier::Horizon | Barrier::Stop => 0,
+    }
+}
+
+fn write_barrier_labels(
+    train_rows: &[TrainingRow],
+    validation_rows: &[TrainingRow],
+    path: &Path,
+) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    writeln!(
+        writer,
+        "split,ticker,date,barrier,holding_bars,return,meta_label"
+    )?;
+    for (split, rows) in [("training", train_rows), ("validation", validation_rows)] {
+        for row in rows {
+            let Some(outcome) = row.barrier else {
+                continue;
+            };
+            writeln!(
+                writer,
+                "{},{},{},{},{},{:.6},{}",
+                split,
+                row.ticker,
+                row.date.date_naive(),
+                outcome.barrier.label(),
+                outcome.holding_bars,
+                outcome.exit_multiple - 1.0,
+                row.label
+            )?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
 fn compute_rank_label(max_multiple: f64) -> u8 {
     if !max_multiple.is_finite() {
         return 0;
@@ -1388,7 +1892,7 @@ fn label_gain_param() -> String {
     output
 }
 
-fn sort_rows_by_date(rows: &mut Vec<TrainingRow>) {
+fn sort_rows_by_date(rows: &mut [TrainingRow]) {
     rows.sort_by(|a, b| {
         let date_cmp = a.date.cmp(&b.date);
         if date_cmp != Ordering::Equal {
@@ -1452,3 +1956,110 @@ fn percentile_value(sorted: &[usize], percentile: f64) -> usize {
     let idx = ((sorted.len() - 1) as f64 * clamped).round() as usize;
     sorted[idx]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_sample_weighting, compute_triple_barrier_outcome, label_uniqueness,
+        triple_barrier_rank_label, Barrier, SampleWeighting, TrainingRow, TripleBarrierConfig,
+    };
+    use crate::models::Candle;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn candles(bars: &[(f64, f64, f64, f64)]) -> Vec<Candle> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        bars.iter()
+            .enumerate()
+            .map(|(idx, &(open, high, low, close))| Candle {
+                ticker: "TEST".to_string(),
+                date: start + Duration::days(idx as i64),
+                open,
+                high,
+                low,
+                close,
+                unadjusted_close: None,
+                volume_shares: 1_000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_label_windows_share_their_weight() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let row = |ticker: &str, day: i64, label_bars: (usize, usize)| TrainingRow {
+            ticker: ticker.into(),
+            date: start + Duration::days(day),
+            features: Vec::new(),
+            label: 0,
+            rank_label: 0,
+            max_multiple: 1.0,
+            weight: 1.0,
+            barrier: None,
+            label_bars,
+        };
+        // The first two AAA windows overlap on bars 2..=3; BBB shares no bars with them.
+        let mut rows = vec![
+            row("AAA", 0, (0, 3)),
+            row("AAA", 2, (2, 5)),
+            row("BBB", 2, (2, 5)),
+        ];
+        assert_eq!(label_uniqueness(&rows), vec![0.75, 0.75, 1.0]);
+
+        apply_sample_weighting(
+            &mut rows,
+            &SampleWeighting {
+                recency_half_life_days: Some(2.0),
+                uniqueness: true,
+            },
+        );
+        let total: f64 = rows.iter().map(|row| row.weight).sum();
+        assert!((total - 3.0).abs() < 1e-12);
+        assert!((rows[0].weight * 2.0 - rows[1].weight).abs() < 1e-12);
+        assert!(rows[2].weight > rows[1].weight);
+    }
+
+    #[test]
+    fn triple_barrier_exits_at_the_first_touched_barrier() {
+        // ATR 2 at the entry bar, so the profit target is 108 and the stop 96.
+        let config = TripleBarrierConfig {
+            profit_atr_multiple: 4.0,
+            stop_atr_multiple: 2.0,
+            horizon_bars: 3,
+            atr_period: 2,
+        };
+        let flat = (100.0, 101.0, 99.0, 100.0);
+        let series = |tail: &[(f64, f64, f64, f64)]| {
+            let mut bars = vec![flat, flat];
+            bars.extend_from_slice(tail);
+            candles(&bars)
+        };
+        let outcome = |candles: &[Candle]| {
+            let refs: Vec<&Candle> = candles.iter().collect();
+            compute_triple_barrier_outcome(&refs, 1, &config)
+        };
+
+        let both = outcome(&series(&[flat, (101.0, 109.0, 95.0, 104.0), flat])).unwrap();
+        assert_eq!(
+            both.barrier,
+            Barrier::Stop,
+            "a bar touching both barriers stops out"
+        );
+
+        let profit = outcome(&series(&[flat, (101.0, 109.0, 99.0, 104.0), flat])).unwrap();
+        assert_eq!(profit.barrier, Barrier::Profit);
+        assert_eq!(profit.holding_bars, 2);
+        assert!((profit.exit_multiple - 1.08).abs() < 1e-12);
+        assert_eq!(triple_barrier_rank_label(&profit), 2);
+
+        let gap = outcome(&series(&[(90.0, 91.0, 89.0, 90.0), flat, flat])).unwrap();
+        assert_eq!(gap.barrier, Barrier::Stop);
+        assert!((gap.exit_multiple - 0.9).abs() < 1e-12);
+
+        let horizon = outcome(&series(&[flat, flat, (100.0, 103.0, 99.0, 102.0)])).unwrap();
+        assert_eq!(horizon.barrier, Barrier::Horizon);
+        assert_eq!(horizon.holding_bars, 3);
+        assert_eq!(triple_barrier_rank_label(&horizon), 1);
+
+        assert!(outcome(&series(&[flat, flat])).is_none());
+    }
+}
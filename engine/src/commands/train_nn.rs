@@ -0,0 +1,545 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use log::{info, warn};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::commands::train_lightgbm::{
+    build_training_datasets, fit_validation_calibration, score_validation_rows, summarize_dataset,
+    LabelingOptions, LightgbmDatasetSummary, LightgbmValidationMetricsSummary, TrainingDatasets,
+    TrainingRow,
+};
+use crate::context::AppContext;
+use crate::database::NewNeuralNetModel;
+use crate::strategy::lightgbm::features::FeatureSet;
+use crate::strategy::neural_net::{self, MlpModel};
+use crate::strategy::registry_template;
+
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
+const ADAM_EPSILON: f64 = 1e-8;
+const MIN_FEATURE_SCALE: f64 = 1e-9;
+const BASE_TEMPLATE_ID: &str = "lightgbm";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NeuralNetHyperparameters {
+    pub hidden_units: Vec<usize>,
+    pub epochs: u32,
+    pub learning_rate: f64,
+    pub batch_size: usize,
+    pub l2: f64,
+    pub early_stopping_epochs: u32,
+    pub seed: u64,
+}
+
+impl Default for NeuralNetHyperparameters {
+    fn default() -> Self {
+        Self {
+            hidden_units: vec![32, 16],
+            epochs: 50,
+            learning_rate: 0.001,
+            batch_size: 256,
+            l2: 1e-4,
+            early_stopping_epochs: 5,
+            seed: 4242,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NeuralNetTrainingSummary {
+    model_id: String,
+    template_id: String,
+    strategy_id: String,
+    hyperparameters: NeuralNetHyperparameters,
+    features: Vec<String>,
+    epochs_trained: u32,
+    best_epoch: u32,
+    best_validation_loss: f64,
+    train_dataset: LightgbmDatasetSummary,
+    validation_dataset: LightgbmDatasetSummary,
+    validation_metrics: LightgbmValidationMetricsSummary,
+}
+
+/// Gradients (or Adam moments) shaped like the layers of a model.
+struct LayerBuffers {
+    weights: Vec<Vec<f64>>,
+    biases: Vec<Vec<f64>>,
+}
+
+impl LayerBuffers {
+    fn zeros(model: &MlpModel) -> Self {
+        Self {
+            weights: model
+                .layers
+                .iter()
+                .map(|layer| vec![0.0; layer.weights.len()])
+                .collect(),
+            biases: model
+                .layers
+                .iter()
+                .map(|layer| vec![0.0; layer.biases.len()])
+                .collect(),
+        }
+    }
+
+    fn clear(&mut self) {
+        for values in self.weights.iter_mut().chain(self.biases.iter_mut()) {
+            values.iter_mut().for_each(|value| *value = 0.0);
+        }
+    }
+}
+
+struct AdamOptimizer {
+    learning_rate: f64,
+    l2: f64,
+    step: i32,
+    first: LayerBuffers,
+    second: LayerBuffers,
+}
+
+impl AdamOptimizer {
+    fn new(model: &MlpModel, learning_rate: f64, l2: f64) -> Self {
+        Self {
+            learning_rate,
+            l2,
+            step: 0,
+            first: LayerBuffers::zeros(model),
+            second: LayerBuffers::zeros(model),
+        }
+    }
+
+    fn apply(&mut self, model: &mut MlpModel, gradients: &LayerBuffers) {
+        self.step += 1;
+        let step_size = self.learning_rate * (1.0 - ADAM_BETA2.powi(self.step)).sqrt()
+            / (1.0 - ADAM_BETA1.powi(self.step));
+        for (idx, layer) in model.layers.iter_mut().enumerate() {
+            adam_update(
+                &mut layer.weights,
+                &gradients.weights[idx],
+                &mut self.first.weights[idx],
+                &mut self.second.weights[idx],
+                step_size,
+                self.l2,
+            );
+            adam_update(
+                &mut layer.biases,
+                &gradients.biases[idx],
+                &mut self.first.biases[idx],
+                &mut self.second.biases[idx],
+                step_size,
+                0.0,
+            );
+        }
+    }
+}
+
+fn adam_update(
+    params: &mut [f64],
+    gradients: &[f64],
+    first: &mut [f64],
+    second: &mut [f64],
+    step_size: f64,
+    l2: f64,
+) {
+    for (((param, gradient), m), v) in params
+        .iter_mut()
+        .zip(gradients)
+        .zip(first.iter_mut())
+        .zip(second.iter_mut())
+    {
+        let gradient = gradient + l2 * *param;
+        *m = ADAM_BETA1 * *m + (1.0 - ADAM_BETA1) * gradient;
+        *v = ADAM_BETA2 * *v + (1.0 - ADAM_BETA2) * gradient * gradient;
+        *param -= step_size * *m / (v.sqrt() + ADAM_EPSILON);
+    }
+}
+
+pub async fn run(
+    app: &AppContext,
+    name: Option<String>,
+    hyperparameters: NeuralNetHyperparameters,
+    features: FeatureSet,
+    labeling: LabelingOptions,
+) -> Result<()> {
+    validate_hyperparameters(&hyperparameters)?;
+    labeling.validate()?;
+    let db = app.database().await?;
+    info!("Starting neural network training");
+    let TrainingDatasets {
+        train_rows,
+        validation_rows,
+    } = build_training_datasets(app, &db, &features, &labeling).await?;
+    let feature_count = train_rows[0].features.len();
+    if let Some(row) = train_rows
+        .iter()
+        .chain(validation_rows.iter())
+        .find(|row| row.features.len() != feature_count)
+    {
+        return Err(anyhow!(
+            "Training rows have inconsistent feature counts ({} and {})",
+            feature_count,
+            row.features.len()
+        ));
+    }
+
+    let (means, scales) = feature_standardization(&train_rows, feature_count);
+    let mut rng = StdRng::seed_from_u64(hyperparameters.seed);
+    let mut model = MlpModel::random(
+        features,
+        means,
+        scales,
+        &hyperparameters.hidden_units,
+        &mut rng,
+    );
+    let train_inputs: Vec<Vec<f64>> = train_rows
+        .par_iter()
+        .map(|row| model.standardize(&row.features))
+        .collect();
+    let validation_inputs: Vec<Vec<f64>> = validation_rows
+        .par_iter()
+        .map(|row| model.standardize(&row.features))
+        .collect();
+
+    info!(
+        "Training MLP {:?} on {} rows x {} features (validation rows={})",
+        hyperparameters.hidden_units,
+        train_rows.len(),
+        feature_count,
+        validation_rows.len()
+    );
+    let mut optimizer =
+        AdamOptimizer::new(&model, hyperparameters.learning_rate, hyperparameters.l2);
+    let mut gradients = LayerBuffers::zeros(&model);
+    let mut order: Vec<usize> = (0..train_rows.len()).collect();
+    let mut best_model = model.clone();
+    let mut best_loss = weighted_log_loss(&model, &validation_inputs, &validation_rows);
+    let mut best_epoch = 0;
+    let mut epochs_trained = 0;
+    for epoch in 1..=hyperparameters.epochs {
+        order.shuffle(&mut rng);
+        let mut train_loss = 0.0;
+        let mut train_weight = 0.0;
+        for batch in order.chunks(hyperparameters.batch_size) {
+            gradients.clear();
+            let batch_weight: f64 = batch.iter().map(|&idx| train_rows[idx].weight).sum();
+            if batch_weight <= 0.0 {
+                continue;
+            }
+            for &idx in batch {
+                let row = &train_rows[idx];
+                train_loss += accumulate_gradients(
+                    &model,
+                    &train_inputs[idx],
+                    row.label > 0,
+                    row.weight / batch_weight,
+                    &mut gradients,
+                ) * batch_weight;
+            }
+            train_weight += batch_weight;
+            optimizer.apply(&mut model, &gradients);
+        }
+        epochs_trained = epoch;
+
+        let validation_loss = weighted_log_loss(&model, &validation_inputs, &validation_rows);
+        info!(
+            "Epoch {}/{}: train loss {:.5}, validation loss {:.5}",
+            epoch,
+            hyperparameters.epochs,
+            train_loss / train_weight.max(f64::EPSILON),
+            validation_loss
+        );
+        if validation_loss < best_loss {
+            best_loss = validation_loss;
+            best_model = model.clone();
+            best_epoch = epoch;
+        } else if hyperparameters.early_stopping_epochs > 0
+            && epoch - best_epoch >= hyperparameters.early_stopping_epochs
+        {
+            info!(
+                "Stopping early: validation loss has not improved since epoch {}",
+                best_epoch
+            );
+            break;
+        }
+    }
+
+    let scores: Vec<f64> = validation_inputs
+        .par_iter()
+        .map(|input| best_model.logit(input))
+        .collect();
+    let validation_metrics = score_validation_rows(&validation_rows, &scores);
+    let probabilities: Vec<f64> = scores
+        .iter()
+        .map(|logit| 1.0 / (1.0 + (-logit).exp()))
+        .collect();
+    best_model.calibration = fit_validation_calibration(&validation_rows, &probabilities);
+
+    let model_id = Uuid::new_v4().simple().to_string();
+    let model_name = name.unwrap_or_else(|| {
+        format!(
+            "MLP {} {}",
+            hyperparameters
+                .hidden_units
+                .iter()
+                .map(|units| units.to_string())
+                .collect::<Vec<_>>()
+                .join("x"),
+            Utc::now().format("%Y-%m-%d %H:%M")
+        )
+    });
+    let mut template = match db.get_template(BASE_TEMPLATE_ID).await? {
+        Some(template) => template,
+        None => registry_template(BASE_TEMPLATE_ID)
+            .ok_or_else(|| anyhow!("Unknown strategy template: {}", BASE_TEMPLATE_ID))?,
+    };
+    template.id = format!("nn_{}", model_id);
+    template.name = format!("Neural Network: {}", model_name);
+    template.description = Some(format!("Neural network model {}.", model_name));
+    let default_parameters: HashMap<String, f64> = template
+        .parameters
+        .iter()
+        .filter_map(|parameter| {
+            let value = parameter.default.as_ref()?.as_f64()?;
+            Some((parameter.name.clone(), value))
+        })
+        .collect();
+
+    let train_dataset = summarize_dataset(&train_rows);
+    let validation_dataset = summarize_dataset(&validation_rows);
+    let record = NewNeuralNetModel {
+        id: model_id.clone(),
+        name: model_name.clone(),
+        weights_json: serde_json::to_string(&best_model)?,
+        hyperparameters: serde_json::to_value(&hyperparameters)?,
+        train_dataset_stats: serde_json::to_value(&train_dataset)?,
+        validation_dataset_stats: serde_json::to_value(&validation_dataset)?,
+        validation_metrics: Some(serde_json::to_value(&validation_metrics)?),
+        trained_at: Utc::now(),
+    };
+    let strategy_id = db
+        .insert_neural_net_model(
+            &record,
+            &template,
+            &format!("Neural Network ({})", model_name),
+            &default_parameters,
+        )
+        .await?;
+    let feature_names = best_model.feature_names.clone();
+    neural_net::register_model(&model_id, best_model)?;
+    println!(
+        "Saved neural network {} as template {} with strategy {}",
+        model_name, template.id, strategy_id
+    );
+
+    let summary = NeuralNetTrainingSummary {
+        model_id,
+        template_id: template.id,
+        strategy_id,
+        hyperparameters,
+        features: feature_names,
+        epochs_trained,
+        best_epoch,
+        best_validation_loss: best_loss,
+        train_dataset,
+        validation_dataset,
+        validation_metrics,
+    };
+    match serde_json::to_string(&summary) {
+        Ok(payload) => println!("STRATCRAFT_NN_TRAIN_SUMMARY={payload}"),
+        Err(err) => warn!("Failed to serialize neural network training summary: {err}"),
+    }
+
+    Ok(())
+}
+
+fn validate_hyperparameters(hyperparameters: &NeuralNetHyperparameters) -> Result<()> {
+    if hyperparameters.hidden_units.contains(&0) {
+        return Err(anyhow!("Hidden layers need at least one unit"));
+    }
+    if hyperparameters.epochs == 0 || hyperparameters.batch_size == 0 {
+        return Err(anyhow!("Epochs and batch size must be positive"));
+    }
+    if !(hyperparameters.learning_rate.is_finite() && hyperparameters.learning_rate > 0.0) {
+        return Err(anyhow!("Learning rate must be a positive number"));
+    }
+    if !(hyperparameters.l2.is_finite() && hyperparameters.l2 >= 0.0) {
+        return Err(anyhow!("L2 penalty must be a non-negative number"));
+    }
+    Ok(())
+}
+
+/// Per-feature mean and standard deviation of the finite training values; constant or missing
+/// features get a unit scale.
+fn feature_standardization(rows: &[TrainingRow], feature_count: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut sums = vec![0.0; feature_count];
+    let mut squares = vec![0.0; feature_count];
+    let mut counts = vec![0usize; feature_count];
+    for row in rows {
+        for (idx, value) in row.features.iter().enumerate() {
+            if value.is_finite() {
+                sums[idx] += value;
+                squares[idx] += value * value;
+                counts[idx] += 1;
+            }
+        }
+    }
+    let mut means = Vec::with_capacity(feature_count);
+    let mut scales = Vec::with_capacity(feature_count);
+    for idx in 0..feature_count {
+        let count = counts[idx].max(1) as f64;
+        let mean = sums[idx] / count;
+        let variance = (squares[idx] / count - mean * mean).max(0.0);
+        let scale = variance.sqrt();
+        means.push(mean);
+        scales.push(if scale > MIN_FEATURE_SCALE {
+            scale
+        } else {
+            1.0
+        });
+    }
+    (means, scales)
+}
+
+/// Adds the gradient of `scale` times the binary cross-entropy of one row to `gradients` and
+/// returns that loss.
+fn accumulate_gradients(
+    model: &MlpModel,
+    input: &[f64],
+    positive: bool,
+    scale: f64,
+    gradients: &mut LayerBuffers,
+) -> f64 {
+    let last = model.layers.len() - 1;
+    let mut activations: Vec<Vec<f64>> = Vec::with_capacity(model.layers.len() + 1);
+    activations.push(input.to_vec());
+    for (idx, layer) in model.layers.iter().enumerate() {
+        let mut output = Vec::with_capacity(layer.outputs);
+        layer.forward(&activations[idx], &mut output);
+        if idx < last {
+            output.iter_mut().for_each(|value| *value = value.max(0.0));
+        }
+        activations.push(output);
+    }
+
+    let logit = activations[last + 1][0];
+    let target = if positive { 1.0 } else { 0.0 };
+    let probability = 1.0 / (1.0 + (-logit).exp());
+    let mut deltas = vec![(probability - target) * scale];
+    for idx in (0..=last).rev() {
+        let layer = &model.layers[idx];
+        let input = &activations[idx];
+        for (out, delta) in deltas.iter().enumerate() {
+            let row = &mut gradients.weights[idx][out * layer.inputs..(out + 1) * layer.inputs];
+            for (gradient, value) in row.iter_mut().zip(input) {
+                *gradient += delta * value;
+            }
+            gradients.biases[idx][out] += delta;
+        }
+        if idx > 0 {
+            deltas = (0..layer.inputs)
+                .map(|col| {
+                    if input[col] <= 0.0 {
+                        return 0.0;
+                    }
+                    deltas
+                        .iter()
+                        .enumerate()
+                        .map(|(out, delta)| delta * layer.weights[out * layer.inputs + col])
+                        .sum()
+                })
+                .collect();
+        }
+    }
+
+    binary_cross_entropy(logit, positive) * scale
+}
+
+/// Numerically stable cross-entropy of a logit against a binary label.
+fn binary_cross_entropy(logit: f64, positive: bool) -> f64 {
+    let target = if positive { 1.0 } else { 0.0 };
+    logit.max(0.0) - logit * target + (-logit.abs()).exp().ln_1p()
+}
+
+fn weighted_log_loss(model: &MlpModel, inputs: &[Vec<f64>], rows: &[TrainingRow]) -> f64 {
+    let (loss, weight) = inputs
+        .par_iter()
+        .zip(rows.par_iter())
+        .map(|(input, row)| {
+            (
+                binary_cross_entropy(model.logit(input), row.label > 0) * row.weight,
+                row.weight,
+            )
+        })
+        .reduce(|| (0.0, 0.0), |a, b| (a.0 + b.0, a.1 + b.1));
+    if weight > 0.0 {
+        loss / weight
+    } else {
+        f64::INFINITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn row(features: Vec<f64>, positive: bool) -> TrainingRow {
+        TrainingRow {
+            ticker: "TEST".into(),
+            date: Utc::now(),
+            features,
+            label: u8::from(positive),
+            rank_label: 0,
+            max_multiple: 1.0,
+            weight: 1.0,
+            barrier: None,
+            label_bars: (0, 0),
+        }
+    }
+
+    #[test]
+    fn gradient_steps_learn_a_separable_dataset() {
+        let rows: Vec<TrainingRow> = (0..200)
+            .map(|idx| {
+                let x = idx as f64 / 100.0 - 1.0;
+                row(vec![x, 5.0], x > 0.1)
+            })
+            .collect();
+        let (means, scales) = feature_standardization(&rows, 2);
+        assert_eq!(scales[1], 1.0);
+        let mut rng = StdRng::seed_from_u64(1);
+        let features = FeatureSet::from_names(&["momentum_short", "rsi"]).unwrap();
+        let mut model = MlpModel::random(features, means, scales, &[8], &mut rng);
+        let inputs: Vec<Vec<f64>> = rows
+            .iter()
+            .map(|row| model.standardize(&row.features))
+            .collect();
+        let initial_loss = weighted_log_loss(&model, &inputs, &rows);
+
+        let mut optimizer = AdamOptimizer::new(&model, 0.05, 0.0);
+        let mut gradients = LayerBuffers::zeros(&model);
+        for _ in 0..200 {
+            gradients.clear();
+            for (input, row) in inputs.iter().zip(&rows) {
+                accumulate_gradients(
+                    &model,
+                    input,
+                    row.label > 0,
+                    1.0 / rows.len() as f64,
+                    &mut gradients,
+                );
+            }
+            optimizer.apply(&mut model, &gradients);
+        }
+
+        assert!(weighted_log_loss(&model, &inputs, &rows) < initial_loss * 0.5);
+        assert!(model.predict_probability(&[0.9, 5.0]).unwrap() > 0.9);
+        assert!(model.predict_probability(&[-0.9, 5.0]).unwrap() < 0.1);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::commands::market_data_snapshot::ensure_market_data_file;
+use crate::context::{AppContext, MarketDataFilters};
+use crate::data_context::TickerScope;
+use crate::models::BacktestResult;
+use anyhow::{anyhow, Context, Result};
+use log::info;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+
+/// Prefix of the stdout line carrying the JSON summary read by the web app's try-parameters job.
+const SUMMARY_PREFIX: &str = "STRATCRAFT_TRY_PARAMETERS_SUMMARY=";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TryParametersSummary {
+    template_id: String,
+    start_date: String,
+    end_date: String,
+    initial_capital: f64,
+    final_portfolio_value: f64,
+    total_return: f64,
+    cagr: f64,
+    sharpe_ratio: f64,
+    calmar_ratio: f64,
+    max_drawdown_ratio: f64,
+    win_rate: f64,
+    total_trades: i32,
+    equity_curve: Vec<EquityPoint>,
+    duration_seconds: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EquityPoint {
+    date: String,
+    portfolio_value: f64,
+}
+
+impl TryParametersSummary {
+    fn new(template_id: &str, result: &BacktestResult, duration_seconds: f64) -> Self {
+        let performance = &result.performance;
+        Self {
+            template_id: template_id.to_string(),
+            start_date: result.start_date.format("%Y-%m-%d").to_string(),
+            end_date: result.end_date.format("%Y-%m-%d").to_string(),
+            initial_capital: result.initial_capital,
+            final_portfolio_value: result.final_portfolio_value,
+            total_return: performance.total_return,
+            cagr: performance.cagr,
+            sharpe_ratio: performance.sharpe_ratio,
+            calmar_ratio: performance.calmar_ratio,
+            max_drawdown_ratio: performance.max_drawdown_percent / 100.0,
+            win_rate: performance.win_rate,
+            total_trades: performance.total_trades,
+            equity_curve: result
+                .daily_snapshots
+                .iter()
+                .map(|snapshot| EquityPoint {
+                    date: snapshot.date.format("%Y-%m-%d").to_string(),
+                    portfolio_value: snapshot.portfolio_value,
+                })
+                .collect(),
+            duration_seconds,
+        }
+    }
+}
+
+/// Parses `--parameters`, a JSON object of parameter names to numbers.
+pub fn parse_parameters(raw: &str) -> Result<HashMap<String, f64>> {
+    serde_json::from_str(raw)
+        .with_context(|| format!("--parameters must be a JSON object of numbers, got {}", raw))
+}
+
+/// Backtests one parameter set of `template_id` (template defaults fill in the rest) on the
+/// snapshot between the filter dates and prints its metrics and equity curve as one JSON line.
+/// Nothing is cached or stored.
+pub async fn run(
+    app: &AppContext,
+    template_id: &str,
+    parameters: &HashMap<String, f64>,
+    filters: MarketDataFilters,
+    market_data_file: &Path,
+) -> Result<()> {
+    if let (Some(start), Some(end)) = (filters.start_date, filters.end_date) {
+        if start > end {
+            return Err(anyhow!(
+                "--start-date {} is after --end-date {}",
+                start,
+                end
+            ));
+        }
+    }
+    ensure_market_data_file(market_data_file).await?;
+    let started = Instant::now();
+    let mut context = app
+        .engine_context_from_file(market_data_file, TickerScope::AllTickers, Some(filters))
+        .await?;
+    let result = context
+        .optimizer()
+        .backtest_parameters(template_id, parameters)
+        .await?;
+    let summary = TryParametersSummary::new(template_id, &result, started.elapsed().as_secs_f64());
+    info!(
+        "{} {} - {}: CAGR {:.2}%, Sharpe {:.2}, max drawdown {:.2}%, {} trades",
+        template_id,
+        summary.start_date,
+        summary.end_date,
+        summary.cagr * 100.0,
+        summary.sharpe_ratio,
+        summary.max_drawdown_ratio * 100.0,
+        summary.total_trades
+    );
+    println!("{}{}", SUMMARY_PREFIX, serde_json::to_string(&summary)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters_are_a_json_object_of_numbers() {
+        let parameters = parse_parameters(r#"{"period": 14, "minConfidence": 0.4}"#).unwrap();
+        assert_eq!(parameters["period"], 14.0);
+        assert_eq!(parameters["minConfidence"], 0.4);
+        assert!(parse_parameters(r#"{"period": "fourteen"}"#).is_err());
+        assert!(parse_parameters("period=14").is_err());
+    }
+}
@@ -1,14 +1,58 @@
-use crate::commands::market_data_snapshot::ensure_market_data_file;
-use crate::config::require_setting_date;
-use crate::context::{AppContext, MarketDataFilters};
+use crate::commands::market_data_snapshot::{ensure_market_data_file, local_store_settings};
+use crate::config::{
+    require_setting_date, resolve_verify_max_entry_concentration, resolve_verify_min_capacity,
+    resolve_verify_min_expectancy, resolve_verify_require_beat_buy_and_hold, CostScenario,
+    EngineRuntimeSettings, LocalOptimizationObjective, BASE_COST_SCENARIO_LABEL,
+    VERIFY_COST_SCENARIOS,
+};
+use crate::context::{AppContext, EngineContext, MarketDataFilters};
 use crate::data_context::TickerScope;
+use crate::models::{Candle, MonthlyReturns, OptimizationResult};
 use crate::optimizer::parameter_signature;
-use anyhow::Result;
+use crate::storage::{Storage, VerificationMetrics};
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDate};
 use log::{info, warn};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -> Result<()> {
+/// Best cached parameter sets re-scored by `--ticker-folds`, `--perturb` and
+/// `--anchored-windows`.
+const TOP_PARAMETER_SETS: usize = 10;
+/// Fixed so repeated runs split the tickers the same way.
+const TICKER_FOLD_SEED: u64 = 4242;
+/// Fixed so repeated runs replay the same noise.
+const PERTURB_SEED: u64 = 2718;
+/// Perturbed replays of each parameter set.
+const PERTURB_RUNS: u64 = 5;
+/// Standard deviation of the multiplicative gaussian noise applied to every close.
+const PERTURB_CLOSE_SIGMA: f64 = 0.002;
+/// Each replay draws its slippage uniformly between these multiples of the configured rate.
+const PERTURB_SLIPPAGE_MULTIPLIERS: (f64, f64) = (0.5, 1.5);
+/// Share of the unperturbed objective the perturbed mean must keep to pass.
+const PERTURB_MIN_RETAINED: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VerifyOptions {
+    /// Re-score the best cached sets on this many disjoint ticker subsets.
+    pub ticker_folds: Option<usize>,
+    /// Re-score the best cached sets on noise-perturbed prices and slippage.
+    pub perturb: bool,
+    /// Re-verify the best cached sets on this many successive windows after the optimizer
+    /// training window.
+    pub anchored_windows: Option<usize>,
+}
+
+/// Verifies cached parameter sets under the cost scenarios, or runs the robustness check
+/// selected in `options` on the best ones instead.
+pub async fn run(
+    app: &AppContext,
+    template_id: &str,
+    market_data_file: &Path,
+    options: VerifyOptions,
+) -> Result<()> {
     info!("Received verify command for template_id={}", template_id);
     ensure_market_data_file(market_data_file).await?;
     info!(
@@ -16,8 +60,678 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
         market_data_file.display()
     );
 
-    let db = app.database().await?;
-    let cache_entries = db.backtest_cache_entries_for_template(template_id).await?;
+    if let Some(store) = app.local_store()? {
+        let settings =
+            app.snapshot_settings(&local_store_settings(&store, market_data_file).await?);
+        verify_with_storage(
+            app,
+            &store,
+            &settings,
+            template_id,
+            market_data_file,
+            options,
+        )
+        .await
+    } else {
+        let db = app.database().await?;
+        let settings = app.database_settings(&db).await?;
+        verify_with_storage(app, &db, &settings, template_id, market_data_file, options).await
+    }
+}
+
+async fn verify_with_storage<S: Storage>(
+    app: &AppContext,
+    storage: &S,
+    settings: &HashMap<String, String>,
+    template_id: &str,
+    market_data_file: &Path,
+    options: VerifyOptions,
+) -> Result<()> {
+    if let Some(folds) = options.ticker_folds {
+        cross_validate_ticker_folds(app, storage, settings, template_id, market_data_file, folds)
+            .await
+    } else if options.perturb {
+        perturbation_test(app, storage, settings, template_id, market_data_file).await
+    } else if let Some(count) = options.anchored_windows {
+        verify_anchored_windows(app, storage, settings, template_id, market_data_file, count).await
+    } else {
+        verify_cached_entries(app, storage, settings, template_id, market_data_file).await
+    }
+}
+
+pub(crate) fn objective_score(
+    objective: LocalOptimizationObjective,
+    cagr: f64,
+    sharpe_ratio: f64,
+) -> f64 {
+    match objective {
+        LocalOptimizationObjective::Cagr => cagr,
+        LocalOptimizationObjective::Sharpe => sharpe_ratio,
+    }
+}
+
+/// Distinct cached parameter sets with the best training objective, best first.
+async fn top_parameter_sets<S: Storage>(
+    storage: &S,
+    template_id: &str,
+    objective: LocalOptimizationObjective,
+) -> Result<Vec<HashMap<String, f64>>> {
+    let mut cache_entries = storage
+        .backtest_cache_entries_for_template(template_id)
+        .await?;
+    let ranking_score = |cagr: f64, sharpe_ratio: f64| {
+        let score = objective_score(objective, cagr, sharpe_ratio);
+        if score.is_finite() {
+            score
+        } else {
+            f64::NEG_INFINITY
+        }
+    };
+    cache_entries.sort_by(|a, b| {
+        ranking_score(b.cagr, b.sharpe_ratio).total_cmp(&ranking_score(a.cagr, a.sharpe_ratio))
+    });
+    let mut seen_signatures = HashSet::new();
+    Ok(cache_entries
+        .into_iter()
+        .filter(|entry| seen_signatures.insert(parameter_signature(&entry.parameters)))
+        .take(TOP_PARAMETER_SETS)
+        .map(|entry| entry.parameters)
+        .collect())
+}
+
+/// Settings of the local store when `--sqlite-file` is set (the database otherwise) and the
+/// cached parameter set of `template_id` with the best training objective.
+pub(crate) async fn best_cached_parameter_set(
+    app: &AppContext,
+    template_id: &str,
+    market_data_file: &Path,
+) -> Result<(HashMap<String, String>, HashMap<String, f64>)> {
+    let (settings, parameter_sets) = if let Some(store) = app.local_store()? {
+        let settings =
+            app.snapshot_settings(&local_store_settings(&store, market_data_file).await?);
+        let objective =
+            EngineRuntimeSettings::from_settings_map(&settings)?.local_optimization_objective;
+        let parameter_sets = top_parameter_sets(&store, template_id, objective).await?;
+        (settings, parameter_sets)
+    } else {
+        let db = app.database().await?;
+        let settings = app.database_settings(&db).await?;
+        let objective =
+            EngineRuntimeSettings::from_settings_map(&settings)?.local_optimization_objective;
+        let parameter_sets = top_parameter_sets(&db, template_id, objective).await?;
+        (settings, parameter_sets)
+    };
+    let parameters = parameter_sets
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No cached backtest rows found for template {}", template_id))?;
+    Ok((settings, parameters))
+}
+
+pub(crate) async fn verify_window_context(
+    app: &AppContext,
+    settings: &HashMap<String, String>,
+    market_data_file: &Path,
+) -> Result<EngineContext> {
+    app.engine_context_from_file(
+        market_data_file,
+        TickerScope::AllTickers,
+        Some(MarketDataFilters {
+            start_date: Some(require_setting_date(settings, "VERIFY_WINDOW_START_DATE")?),
+            end_date: Some(require_setting_date(settings, "VERIFY_WINDOW_END_DATE")?),
+        }),
+    )
+    .await
+}
+
+/// Finite objective scores of `results` keyed by parameter signature.
+fn scores_by_signature(
+    results: Vec<OptimizationResult>,
+    objective: LocalOptimizationObjective,
+) -> Vec<(String, f64)> {
+    results
+        .into_iter()
+        .filter_map(|result| {
+            let score = objective_score(objective, result.cagr, result.sharpe_ratio);
+            score
+                .is_finite()
+                .then(|| (parameter_signature(&result.parameters), score))
+        })
+        .collect()
+}
+
+/// Re-scores the best cached parameter sets on `folds` random disjoint ticker subsets of the
+/// verification window and flags sets whose score depends on which tickers they trade.
+async fn cross_validate_ticker_folds<S: Storage>(
+    app: &AppContext,
+    storage: &S,
+    settings: &HashMap<String, String>,
+    template_id: &str,
+    market_data_file: &Path,
+    folds: usize,
+) -> Result<()> {
+    if folds < 2 {
+        return Err(anyhow!("--ticker-folds must be at least 2"));
+    }
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(settings)?;
+    let objective = runtime_settings.local_optimization_objective;
+    let parameter_sets = top_parameter_sets(storage, template_id, objective).await?;
+    if parameter_sets.is_empty() {
+        info!(
+            "No cached backtest rows found for template {} to cross-validate",
+            template_id
+        );
+        return Ok(());
+    }
+
+    let context = verify_window_context(app, settings, market_data_file).await?;
+    if context.tickers().len() < folds {
+        return Err(anyhow!(
+            "--ticker-folds {} exceeds the {} tickers with verification data",
+            folds,
+            context.tickers().len()
+        ));
+    }
+
+    let mut fold_scores: HashMap<String, Vec<f64>> = HashMap::new();
+    for (index, tickers) in split_ticker_folds(context.tickers(), folds, TICKER_FOLD_SEED)
+        .iter()
+        .enumerate()
+    {
+        info!(
+            "Scoring {} parameter set(s) on ticker fold {}/{} ({} tickers)",
+            parameter_sets.len(),
+            index + 1,
+            folds,
+            tickers.len()
+        );
+        let mut fold_context = context.ticker_subset(tickers)?;
+        let results = fold_context
+            .optimizer()
+            .run_parameter_batch_with_settings(
+                template_id,
+                &parameter_sets,
+                runtime_settings.clone(),
+            )
+            .await?;
+        for (signature, score) in scores_by_signature(results, objective) {
+            fold_scores.entry(signature).or_default().push(score);
+        }
+    }
+
+    let mut flagged = 0;
+    for (signature, scores) in &fold_scores {
+        let Some(dispersion) = FoldDispersion::from_scores(scores) else {
+            continue;
+        };
+        let complete = scores.len() == folds;
+        let concentrated = !complete || dispersion.is_concentrated();
+        if concentrated {
+            flagged += 1;
+        }
+        info!(
+            "Parameter set {}: {} over {}/{} ticker folds mean {:.4}, std dev {:.4}, min {:.4}, max {:.4}{}",
+            signature,
+            objective.label(),
+            scores.len(),
+            folds,
+            dispersion.mean,
+            dispersion.std_dev,
+            dispersion.min,
+            dispersion.max,
+            if concentrated {
+                " (flagged: performance is carried by a subset of tickers)"
+            } else {
+                ""
+            }
+        );
+    }
+    let missing = parameter_sets.len().saturating_sub(fold_scores.len());
+    if missing > 0 {
+        warn!(
+            "{} parameter set(s) produced no fold results for template {}",
+            missing, template_id
+        );
+    }
+    info!(
+        "Ticker fold cross-validation completed: {} of {} parameter set(s) flagged for template {}",
+        flagged,
+        fold_scores.len(),
+        template_id
+    );
+    Ok(())
+}
+
+/// Replays the best cached parameter sets on copies of the verification window with gaussian
+/// noise on every close and resampled slippage, rejecting sets whose objective collapses.
+async fn perturbation_test<S: Storage>(
+    app: &AppContext,
+    storage: &S,
+    settings: &HashMap<String, String>,
+    template_id: &str,
+    market_data_file: &Path,
+) -> Result<()> {
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(settings)?;
+    let objective = runtime_settings.local_optimization_objective;
+    let parameter_sets = top_parameter_sets(storage, template_id, objective).await?;
+    if parameter_sets.is_empty() {
+        info!(
+            "No cached backtest rows found for template {} to perturb",
+            template_id
+        );
+        return Ok(());
+    }
+
+    let mut context = verify_window_context(app, settings, market_data_file).await?;
+    info!(
+        "Scoring {} parameter set(s) on unperturbed verification data",
+        parameter_sets.len()
+    );
+    let baseline: HashMap<String, f64> = scores_by_signature(
+        context
+            .optimizer()
+            .run_parameter_batch_with_settings(
+                template_id,
+                &parameter_sets,
+                runtime_settings.clone(),
+            )
+            .await?,
+        objective,
+    )
+    .into_iter()
+    .collect();
+
+    let mut perturbed_scores: HashMap<String, Vec<f64>> = HashMap::new();
+    for run in 0..PERTURB_RUNS {
+        let mut rng = StdRng::seed_from_u64(PERTURB_SEED + run);
+        let (min_slippage, max_slippage) = PERTURB_SLIPPAGE_MULTIPLIERS;
+        let slippage_multiplier = rng.gen_range(min_slippage..=max_slippage);
+        info!(
+            "Perturbed run {}/{}: {:.2}% gaussian noise on closes, slippage x{:.2}",
+            run + 1,
+            PERTURB_RUNS,
+            PERTURB_CLOSE_SIGMA * 100.0,
+            slippage_multiplier
+        );
+        let market_data = context
+            .market_data()
+            .with_mapped_candles(|candle| perturb_close(candle, gaussian_noise(&mut rng)));
+        let results = context
+            .with_market_data(market_data)
+            .optimizer()
+            .run_parameter_batch_with_settings(
+                template_id,
+                &parameter_sets,
+                runtime_settings.with_slippage_multiplier(slippage_multiplier),
+            )
+            .await?;
+        for (signature, score) in scores_by_signature(results, objective) {
+            perturbed_scores.entry(signature).or_default().push(score);
+        }
+    }
+
+    let mut rejected = 0;
+    let mut evaluated = 0;
+    for (signature, base_score) in &baseline {
+        evaluated += 1;
+        let scores = perturbed_scores
+            .get(signature)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let mean = if scores.is_empty() {
+            f64::NEG_INFINITY
+        } else {
+            scores.iter().sum::<f64>() / scores.len() as f64
+        };
+        let collapsed = scores.len() < PERTURB_RUNS as usize
+            || *base_score <= 0.0
+            || mean < PERTURB_MIN_RETAINED * base_score;
+        if collapsed {
+            rejected += 1;
+        }
+        info!(
+            "Parameter set {}: {} {:.4} unperturbed, {:.4} mean over {}/{} perturbed runs{}",
+            signature,
+            objective.label(),
+            base_score,
+            mean,
+            scores.len(),
+            PERTURB_RUNS,
+            if collapsed {
+                " (rejected: objective collapses under noise)"
+            } else {
+                ""
+            }
+        );
+    }
+    let missing = parameter_sets.len().saturating_sub(baseline.len());
+    if missing > 0 {
+        warn!(
+            "{} parameter set(s) produced no unperturbed result for template {}",
+            missing, template_id
+        );
+    }
+    info!(
+        "Perturbation test completed: {} of {} parameter set(s) rejected for template {}",
+        rejected, evaluated, template_id
+    );
+    Ok(())
+}
+
+/// Standard normal sample by the Box-Muller transform.
+fn gaussian_noise(rng: &mut StdRng) -> f64 {
+    let radius = (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt();
+    radius * (std::f64::consts::TAU * rng.gen::<f64>()).cos()
+}
+
+/// Scales the close by `1 + PERTURB_CLOSE_SIGMA * noise`, widening the range to contain it.
+fn perturb_close(candle: &Candle, noise: f64) -> Candle {
+    let factor = 1.0 + PERTURB_CLOSE_SIGMA * noise;
+    let close = candle.close * factor;
+    Candle {
+        close,
+        high: candle.high.max(close),
+        low: candle.low.min(close),
+        unadjusted_close: candle.unadjusted_close.map(|value| value * factor),
+        ..candle.clone()
+    }
+}
+
+/// Shuffles `tickers` with `seed` and deals them into `folds` disjoint subsets of near-equal
+/// size.
+fn split_ticker_folds(tickers: &[String], folds: usize, seed: u64) -> Vec<HashSet<String>> {
+    let mut shuffled = tickers.to_vec();
+    shuffled.shuffle(&mut StdRng::seed_from_u64(seed));
+    let mut subsets = vec![HashSet::new(); folds];
+    for (index, ticker) in shuffled.into_iter().enumerate() {
+        subsets[index % folds].insert(ticker);
+    }
+    subsets
+}
+
+/// Spread of one parameter set's objective across ticker folds or anchored windows.
+struct FoldDispersion {
+    mean: f64,
+    std_dev: f64,
+    min: f64,
+    max: f64,
+}
+
+impl FoldDispersion {
+    fn from_scores(scores: &[f64]) -> Option<Self> {
+        if scores.is_empty() {
+            return None;
+        }
+        let count = scores.len() as f64;
+        let mean = scores.iter().sum::<f64>() / count;
+        let variance = scores
+            .iter()
+            .map(|score| (score - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        Some(Self {
+            mean,
+            std_dev: variance.sqrt(),
+            min: scores.iter().copied().fold(f64::INFINITY, f64::min),
+            max: scores.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// A set relies on a few tickers when some fold fails to score above zero or the folds
+    /// disagree by more than their average.
+    fn is_concentrated(&self) -> bool {
+        self.min <= 0.0 || self.std_dev > self.mean.abs()
+    }
+}
+
+/// Splits the days after `training_end` through `verify_end` into `count` consecutive windows
+/// of equal length, the last one also taking the remainder.
+fn anchored_windows(
+    training_end: NaiveDate,
+    verify_end: NaiveDate,
+    count: usize,
+) -> Result<Vec<(NaiveDate, NaiveDate)>> {
+    let days = (verify_end - training_end).num_days();
+    if count == 0 || days < count as i64 {
+        return Err(anyhow!(
+            "{} anchored window(s) do not fit between OPTIMIZER_TRAINING_END_DATE {} and VERIFY_WINDOW_END_DATE {}",
+            count,
+            training_end,
+            verify_end
+        ));
+    }
+    let count = count as i64;
+    let length = days / count;
+    Ok((0..count)
+        .map(|index| {
+            let start = training_end + Duration::days(index * length + 1);
+            let end = if index + 1 == count {
+                verify_end
+            } else {
+                training_end + Duration::days((index + 1) * length)
+            };
+            (start, end)
+        })
+        .collect())
+}
+
+/// Cost scenario results of one parameter set in one anchored window.
+struct AnchoredWindow {
+    start: NaiveDate,
+    end: NaiveDate,
+    results: Vec<(CostScenario, OptimizationResult)>,
+}
+
+impl AnchoredWindow {
+    fn base(&self) -> Option<&OptimizationResult> {
+        self.results
+            .iter()
+            .find(|(scenario, _)| scenario.label == BASE_COST_SCENARIO_LABEL)
+            .map(|(_, result)| result)
+    }
+}
+
+/// Cost-verifies the best cached parameter sets on `count` successive out-of-sample windows
+/// after the optimizer training window. Each set stores its per-window scores and the
+/// verification metrics of its weakest window, and is only marked robust when it is
+/// cost-robust in every window and its objective holds up across them.
+async fn verify_anchored_windows<S: Storage>(
+    app: &AppContext,
+    storage: &S,
+    settings: &HashMap<String, String>,
+    template_id: &str,
+    market_data_file: &Path,
+    count: usize,
+) -> Result<()> {
+    if count < 2 {
+        return Err(anyhow!("--anchored-windows must be at least 2"));
+    }
+    let windows = anchored_windows(
+        require_setting_date(settings, "OPTIMIZER_TRAINING_END_DATE")?,
+        require_setting_date(settings, "VERIFY_WINDOW_END_DATE")?,
+        count,
+    )?;
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(settings)?;
+    let objective = runtime_settings.local_optimization_objective;
+    let parameter_sets = top_parameter_sets(storage, template_id, objective).await?;
+    if parameter_sets.is_empty() {
+        info!(
+            "No cached backtest rows found for template {} to verify on anchored windows",
+            template_id
+        );
+        return Ok(());
+    }
+    let mut ids_by_signature: HashMap<String, Vec<String>> = HashMap::new();
+    for entry in storage
+        .backtest_cache_entries_for_template(template_id)
+        .await?
+    {
+        ids_by_signature
+            .entry(parameter_signature(&entry.parameters))
+            .or_default()
+            .push(entry.id);
+    }
+
+    let mut windows_by_signature: HashMap<String, Vec<AnchoredWindow>> = HashMap::new();
+    for (index, (start, end)) in windows.iter().enumerate() {
+        info!(
+            "Verifying {} parameter set(s) on anchored window {}/{} ({} - {})",
+            parameter_sets.len(),
+            index + 1,
+            count,
+            start,
+            end
+        );
+        let mut context = app
+            .engine_context_from_file(
+                market_data_file,
+                TickerScope::AllTickers,
+                Some(MarketDataFilters {
+                    start_date: Some(*start),
+                    end_date: Some(*end),
+                }),
+            )
+            .await?;
+        let mut optimizer = context.optimizer();
+        let mut scenario_results: HashMap<String, Vec<(CostScenario, OptimizationResult)>> =
+            HashMap::new();
+        for scenario in VERIFY_COST_SCENARIOS {
+            let results = optimizer
+                .run_parameter_batch_with_settings(
+                    template_id,
+                    &parameter_sets,
+                    runtime_settings.with_cost_multiplier(scenario.cost_multiplier),
+                )
+                .await?;
+            for result in results {
+                scenario_results
+                    .entry(parameter_signature(&result.parameters))
+                    .or_default()
+                    .push((scenario, result));
+            }
+        }
+        for (signature, results) in scenario_results {
+            windows_by_signature
+                .entry(signature)
+                .or_default()
+                .push(AnchoredWindow {
+                    start: *start,
+                    end: *end,
+                    results,
+                });
+        }
+    }
+
+    let finite = |value: f64| value.is_finite().then_some(value);
+    let window_score = |window: &AnchoredWindow| {
+        window
+            .base()
+            .map(|base| objective_score(objective, base.cagr, base.sharpe_ratio))
+            .filter(|score| score.is_finite())
+            .unwrap_or(f64::NEG_INFINITY)
+    };
+    let mut updated = 0;
+    let mut stable_count = 0;
+    for (signature, set_windows) in &windows_by_signature {
+        let Some(ids) = ids_by_signature.get(signature) else {
+            warn!(
+                "Anchored window result with signature {} did not match cached entries",
+                signature
+            );
+            continue;
+        };
+        let scores: Vec<f64> = set_windows.iter().map(window_score).collect();
+        let Some((weakest, weakest_base)) = set_windows
+            .iter()
+            .zip(&scores)
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .and_then(|(window, _)| Some((window, window.base()?)))
+        else {
+            continue;
+        };
+        let steady = FoldDispersion::from_scores(&scores)
+            .is_some_and(|dispersion| !dispersion.is_concentrated());
+        let stable = set_windows.len() == count
+            && steady
+            && set_windows.iter().all(|window| {
+                is_cost_robust(&window.results, runtime_settings.max_allowed_drawdown_ratio)
+            });
+        if stable {
+            stable_count += ids.len();
+        }
+        info!(
+            "Parameter set {}: {} by window [{}]{}",
+            signature,
+            objective.label(),
+            scores
+                .iter()
+                .map(|score| format!("{:.4}", score))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if stable {
+                ""
+            } else {
+                " (not stable: a window is missing, unprofitable, over the drawdown limit or far off the others)"
+            }
+        );
+
+        let window_scores = Value::Array(
+            set_windows
+                .iter()
+                .zip(&scores)
+                .map(|(window, score)| {
+                    let base = window.base();
+                    json!({
+                        "startDate": window.start.to_string(),
+                        "endDate": window.end.to_string(),
+                        "score": finite(*score),
+                        "sharpeRatio": base.and_then(|base| finite(base.sharpe_ratio)),
+                        "calmarRatio": base.and_then(|base| finite(base.calmar_ratio)),
+                        "cagr": base.and_then(|base| finite(base.cagr)),
+                        "maxDrawdownRatio": base.and_then(|base| finite(base.max_drawdown_ratio)),
+                        "totalTrades": base.map(|base| base.total_trades),
+                        "costRobust": is_cost_robust(
+                            &window.results,
+                            runtime_settings.max_allowed_drawdown_ratio
+                        ),
+                    })
+                })
+                .collect(),
+        );
+        let scenarios_json = cost_scenarios_json(&weakest.results);
+        for cache_id in ids {
+            storage
+                .update_backtest_cache_verification(
+                    cache_id,
+                    VerificationMetrics::from_result(weakest_base),
+                    &scenarios_json,
+                    stable,
+                )
+                .await?;
+            storage
+                .update_backtest_cache_window_scores(cache_id, &window_scores)
+                .await?;
+            updated += 1;
+        }
+    }
+
+    info!(
+        "Anchored window verification completed: {} of {} row(s) stable across {} windows for template {}",
+        stable_count, updated, count, template_id
+    );
+    Ok(())
+}
+
+async fn verify_cached_entries<S: Storage>(
+    app: &AppContext,
+    storage: &S,
+    settings: &HashMap<String, String>,
+    template_id: &str,
+    market_data_file: &Path,
+) -> Result<()> {
+    let cache_entries = storage
+        .backtest_cache_entries_for_template(template_id)
+        .await?;
     if cache_entries.is_empty() {
         info!(
             "No cached backtest rows found for template {} to verify",
@@ -26,9 +740,8 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
         return Ok(());
     }
 
-    let settings = db.get_all_settings().await?;
-    let verify_start = require_setting_date(&settings, "VERIFY_WINDOW_START_DATE")?;
-    let verify_end = require_setting_date(&settings, "VERIFY_WINDOW_END_DATE")?;
+    let verify_start = require_setting_date(settings, "VERIFY_WINDOW_START_DATE")?;
+    let verify_end = require_setting_date(settings, "VERIFY_WINDOW_END_DATE")?;
     info!(
         "Preparing to verify {} cached parameter set(s) across all tickers on {} - {} data",
         cache_entries.len(),
@@ -79,17 +792,44 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
         );
     }
 
-    info!(
-        "Running verification backtests for {} parameter set(s) on {} - {} candles (all tickers)",
-        parameter_sets.len(),
-        verify_start.format("%Y-%m-%d"),
-        verify_end.format("%Y-%m-%d")
-    );
-    let results = optimizer
-        .run_parameter_batch(template_id, &parameter_sets, false)
-        .await?;
+    let runtime_settings = EngineRuntimeSettings::from_settings_map(settings)?;
+    let require_beat_buy_and_hold = resolve_verify_require_beat_buy_and_hold(settings);
+    let min_capacity = resolve_verify_min_capacity(settings);
+    let max_entry_concentration = resolve_verify_max_entry_concentration(settings);
+    let min_expectancy = resolve_verify_min_expectancy(settings);
+    let mut scenario_results: HashMap<String, Vec<(CostScenario, OptimizationResult)>> =
+        HashMap::new();
+    for scenario in VERIFY_COST_SCENARIOS {
+        info!(
+            "Running {} cost scenario (x{:.2} slippage and fees) verification backtests for {} parameter set(s) on {} - {} candles (all tickers)",
+            scenario.label,
+            scenario.cost_multiplier,
+            parameter_sets.len(),
+            verify_start.format("%Y-%m-%d"),
+            verify_end.format("%Y-%m-%d")
+        );
+        let results = optimizer
+            .run_parameter_batch_with_settings(
+                template_id,
+                &parameter_sets,
+                runtime_settings.with_cost_multiplier(scenario.cost_multiplier),
+            )
+            .await?;
+        info!(
+            "Received {} {} scenario result(s) for {} requested parameter set(s)",
+            results.len(),
+            scenario.label,
+            parameter_sets.len()
+        );
+        for result in results {
+            scenario_results
+                .entry(parameter_signature(&result.parameters))
+                .or_default()
+                .push((scenario, result));
+        }
+    }
 
-    if results.is_empty() {
+    if scenario_results.is_empty() {
         info!(
             "Verification produced no results for template {}",
             template_id
@@ -97,35 +837,112 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
         return Ok(());
     }
 
-    info!(
-        "Received {} verification result(s) for {} requested parameter set(s)",
-        results.len(),
-        parameter_sets.len()
-    );
-
     let mut updated = 0;
-    for result in results {
-        let signature = parameter_signature(&result.parameters);
-        if let Some(ids) = ids_by_signature.get(&signature) {
-            for cache_id in ids {
-                db.update_backtest_cache_verification(
-                    cache_id,
-                    Some(result.sharpe_ratio),
-                    Some(result.calmar_ratio),
-                    Some(result.cagr),
-                    Some(result.max_drawdown_ratio),
-                )
-                .await?;
-                updated += 1;
-            }
-        } else {
+    let mut robust_count = 0;
+    let mut trailing_buy_and_hold = 0;
+    let mut under_capacity = 0;
+    let mut concentrated = 0;
+    let mut low_expectancy = 0;
+    for (signature, results) in scenario_results {
+        let Some(ids) = ids_by_signature.get(&signature) else {
             warn!(
                 "Verification result with signature {} did not match cached entries",
                 signature
             );
+            continue;
+        };
+        let Some((_, base)) = results
+            .iter()
+            .find(|(scenario, _)| scenario.label == BASE_COST_SCENARIO_LABEL)
+        else {
+            warn!(
+                "Verification for signature {} produced no base cost scenario result",
+                signature
+            );
+            continue;
+        };
+        log_monthly_returns(&signature, &base.monthly_returns);
+        let beats_buy_and_hold = base.excess_return.is_none_or(|excess| excess > 0.0);
+        if !beats_buy_and_hold {
+            trailing_buy_and_hold += ids.len();
+        }
+        let meets_capacity = min_capacity.is_none_or(|min| {
+            base.capacity_aum
+                .is_some_and(|capacity| capacity.is_finite() && capacity >= min)
+        });
+        if !meets_capacity {
+            under_capacity += ids.len();
+        }
+        let spread_out = max_entry_concentration.is_none_or(|max| {
+            base.entry_week_concentration
+                .is_none_or(|concentration| concentration <= max)
+        });
+        if !spread_out {
+            concentrated += ids.len();
+        }
+        let meets_expectancy = min_expectancy.is_none_or(|min| {
+            base.r_expectancy
+                .is_some_and(|expectancy| expectancy.is_finite() && expectancy >= min)
+        });
+        if !meets_expectancy {
+            low_expectancy += ids.len();
+        }
+        let robust = is_cost_robust(&results, runtime_settings.max_allowed_drawdown_ratio)
+            && (beats_buy_and_hold || !require_beat_buy_and_hold)
+            && meets_capacity
+            && spread_out
+            && meets_expectancy;
+        if robust {
+            robust_count += ids.len();
+        }
+        let scenarios_json = cost_scenarios_json(&results);
+        for cache_id in ids {
+            storage
+                .update_backtest_cache_verification(
+                    cache_id,
+                    VerificationMetrics::from_result(base),
+                    &scenarios_json,
+                    robust,
+                )
+                .await?;
+            updated += 1;
         }
     }
 
+    info!(
+        "{} of {} verified row(s) stayed profitable within the drawdown limit across all cost scenarios",
+        robust_count, updated
+    );
+    info!(
+        "{} of {} verified row(s) trailed equal-weight buy-and-hold after base costs{}",
+        trailing_buy_and_hold,
+        updated,
+        if require_beat_buy_and_hold {
+            " and were not marked robust"
+        } else {
+            ""
+        }
+    );
+    if let Some(min) = min_capacity {
+        info!(
+            "{} of {} verified row(s) had an estimated capacity below ${:.0} and were not marked robust",
+            under_capacity, updated, min
+        );
+    }
+    if let Some(max) = max_entry_concentration {
+        info!(
+            "{} of {} verified row(s) earned more than {:.0}% of their P&L from entries in one week and were not marked robust",
+            concentrated,
+            updated,
+            max * 100.0
+        );
+    }
+    if let Some(min) = min_expectancy {
+        info!(
+            "{} of {} verified row(s) had an expectancy below {:.2}R or no initial stops and were not marked robust",
+            low_expectancy, updated, min
+        );
+    }
     info!(
         "Verification completed: updated {} cached row(s) for template {}",
         updated, template_id
@@ -133,3 +950,139 @@ pub async fn run(app: &AppContext, template_id: &str, market_data_file: &Path) -
 
     Ok(())
 }
+
+const MONTH_LABELS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Logs the base scenario's year × month return calendar, in percent.
+fn log_monthly_returns(signature: &str, rows: &[MonthlyReturns]) {
+    if rows.is_empty() {
+        return;
+    }
+    let header: String = MONTH_LABELS
+        .iter()
+        .map(|label| format!("{:>7}", label))
+        .collect();
+    let mut lines = vec![format!("Year{}{:>8}", header, "YTD")];
+    for row in rows {
+        let months: String = row
+            .months
+            .iter()
+            .map(|month| match month {
+                Some(value) => format!("{:>7.1}", value * 100.0),
+                None => format!("{:>7}", "-"),
+            })
+            .collect();
+        lines.push(format!("{}{}{:>8.1}", row.year, months, row.ytd * 100.0));
+    }
+    info!(
+        "Monthly returns (%) for parameter set {}:\n{}",
+        signature,
+        lines.join("\n")
+    );
+}
+
+/// A parameter set is robust when every cost scenario ran, stayed profitable and kept
+/// drawdown within the configured limit.
+fn is_cost_robust(results: &[(CostScenario, OptimizationResult)], max_drawdown_ratio: f64) -> bool {
+    results.len() == VERIFY_COST_SCENARIOS.len()
+        && results.iter().all(|(_, result)| {
+            result.cagr.is_finite()
+                && result.cagr > 0.0
+                && result.max_drawdown_ratio.is_finite()
+                && result.max_drawdown_ratio <= max_drawdown_ratio
+        })
+}
+
+fn cost_scenarios_json(results: &[(CostScenario, OptimizationResult)]) -> Value {
+    let finite = |value: f64| value.is_finite().then_some(value);
+    Value::Array(
+        results
+            .iter()
+            .map(|(scenario, result)| {
+                json!({
+                    "label": scenario.label,
+                    "costMultiplier": scenario.cost_multiplier,
+                    "sharpeRatio": finite(result.sharpe_ratio),
+                    "calmarRatio": finite(result.calmar_ratio),
+                    "cagr": finite(result.cagr),
+                    "maxDrawdownRatio": finite(result.max_drawdown_ratio),
+                    "totalTrades": result.total_trades,
+                    "excessReturn": result.excess_return.and_then(finite),
+                    "capacityAum": result.capacity_aum.and_then(finite),
+                    "entryWeekConcentration": result.entry_week_concentration.and_then(finite),
+                    "rExpectancy": result.r_expectancy.and_then(finite),
+                })
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{anchored_windows, perturb_close, split_ticker_folds, FoldDispersion};
+    use crate::models::Candle;
+    use chrono::{NaiveDate, Utc};
+    use std::collections::HashSet;
+
+    #[test]
+    fn splits_disjoint_folds_and_flags_concentrated_scores() {
+        let tickers: Vec<String> = (0..10).map(|index| format!("T{}", index)).collect();
+        let folds = split_ticker_folds(&tickers, 3, 7);
+        assert_eq!(
+            folds.iter().map(HashSet::len).collect::<Vec<_>>(),
+            vec![4, 3, 3]
+        );
+        let union: HashSet<&String> = folds.iter().flatten().collect();
+        assert_eq!(union.len(), tickers.len());
+        assert_eq!(folds, split_ticker_folds(&tickers, 3, 7));
+
+        let steady = FoldDispersion::from_scores(&[0.10, 0.12, 0.11]).unwrap();
+        assert!((steady.mean - 0.11).abs() < 1e-12);
+        assert!(!steady.is_concentrated());
+        assert!(FoldDispersion::from_scores(&[0.40, 0.01, 0.02])
+            .unwrap()
+            .is_concentrated());
+        assert!(FoldDispersion::from_scores(&[0.20, -0.01, 0.15])
+            .unwrap()
+            .is_concentrated());
+        assert!(FoldDispersion::from_scores(&[]).is_none());
+    }
+
+    #[test]
+    fn anchored_windows_follow_the_training_window_back_to_back() {
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let windows = anchored_windows(date(1, 31), date(4, 30), 3).unwrap();
+        assert_eq!(
+            windows,
+            vec![
+                (date(2, 1), date(3, 1)),
+                (date(3, 2), date(3, 31)),
+                (date(4, 1), date(4, 30)),
+            ]
+        );
+        assert!(anchored_windows(date(1, 31), date(2, 2), 3).is_err());
+        assert!(anchored_windows(date(4, 30), date(1, 31), 2).is_err());
+    }
+
+    #[test]
+    fn perturbed_close_stays_within_widened_range() {
+        let candle = Candle {
+            ticker: "AAPL".to_string(),
+            date: Utc::now(),
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.9,
+            unadjusted_close: Some(201.8),
+            volume_shares: 1_000,
+        };
+        let perturbed = perturb_close(&candle, 1.0);
+        assert!((perturbed.close - 100.9 * 1.002).abs() < 1e-9);
+        assert_eq!(perturbed.high, perturbed.close);
+        assert_eq!(perturbed.low, 99.0);
+        assert!((perturbed.unadjusted_close.unwrap() - 201.8 * 1.002).abs() < 1e-9);
+        assert_eq!(perturb_close(&candle, 0.0).close, candle.close);
+    }
+}
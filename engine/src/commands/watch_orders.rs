@@ -0,0 +1,185 @@
+use crate::alpaca::{AlpacaClient, StuckOrder};
+use crate::config::{StuckOrderPolicy, StuckOrderWatchdogSettings};
+use crate::context::AppContext;
+use crate::database::Database;
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use log::{info, warn};
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration as StdDuration;
+
+const SYSTEM_LOG_SOURCE: &str = "order-watchdog";
+
+struct IncidentOutcome {
+    action: &'static str,
+    new_order_id: Option<String>,
+    error: Option<String>,
+}
+
+pub async fn run(app: &AppContext) -> Result<()> {
+    let db = app.database().await?;
+    let settings = app.database_settings(&db).await?;
+    let watchdog = StuckOrderWatchdogSettings::from_settings_map(&settings)?;
+    let now = Utc::now();
+    let submitted_before = now - Duration::minutes(watchdog.timeout_minutes);
+
+    let http_client = Client::builder()
+        .timeout(StdDuration::from_secs(30))
+        .build()
+        .context("failed to construct HTTP client")?;
+
+    let mut incidents = 0usize;
+    for account_id in db.get_account_ids().await? {
+        let Some(credentials) = db.get_account_credentials(&account_id).await? else {
+            continue;
+        };
+        if !credentials.provider.eq_ignore_ascii_case("alpaca") {
+            continue;
+        }
+        let client = match AlpacaClient::new(&http_client, &credentials, &settings) {
+            Ok(client) => client,
+            Err(err) => {
+                warn!(
+                    "Skipping account {}: Alpaca client init failed: {}",
+                    account_id, err
+                );
+                continue;
+            }
+        };
+        let stuck_orders = match client.fetch_stuck_orders(submitted_before).await {
+            Ok(orders) => orders,
+            Err(err) => {
+                warn!(
+                    "Failed to fetch open orders for account {}: {}",
+                    account_id, err
+                );
+                continue;
+            }
+        };
+
+        for order in stuck_orders {
+            let outcome = handle_stuck_order(&db, &client, &order, watchdog.policy).await;
+            record_incident(&db, &account_id, &order, &watchdog, &outcome, now).await?;
+            incidents += 1;
+        }
+    }
+
+    info!(
+        "Order watchdog finished: {} stuck order{} older than {} minutes (policy: {})",
+        incidents,
+        if incidents == 1 { "" } else { "s" },
+        watchdog.timeout_minutes,
+        watchdog.policy.label()
+    );
+    Ok(())
+}
+
+async fn handle_stuck_order(
+    db: &Database,
+    client: &AlpacaClient<'_>,
+    order: &StuckOrder,
+    policy: StuckOrderPolicy,
+) -> IncidentOutcome {
+    if policy == StuckOrderPolicy::Flag {
+        return IncidentOutcome {
+            action: "flagged",
+            new_order_id: None,
+            error: None,
+        };
+    }
+
+    match client.cancel_order(&order.order_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return IncidentOutcome {
+                action: "cancel_failed",
+                new_order_id: None,
+                error: Some("broker did not accept the cancel request".to_string()),
+            };
+        }
+        Err(err) => {
+            return IncidentOutcome {
+                action: "cancel_failed",
+                new_order_id: None,
+                error: Some(err.to_string()),
+            };
+        }
+    }
+
+    if policy == StuckOrderPolicy::Cancel || !order.is_simple {
+        return IncidentOutcome {
+            action: "cancelled",
+            new_order_id: None,
+            error: (!order.is_simple && policy == StuckOrderPolicy::Resubmit)
+                .then(|| "multi-leg orders are cancelled but not resubmitted".to_string()),
+        };
+    }
+
+    let new_order_id = match client.resubmit_order(order).await {
+        Ok(id) => id,
+        Err(err) => {
+            return IncidentOutcome {
+                action: "resubmit_failed",
+                new_order_id: None,
+                error: Some(err.to_string()),
+            };
+        }
+    };
+
+    let mut error = None;
+    for old_id in std::iter::once(order.order_id.as_str()).chain(order.client_order_id.as_deref()) {
+        if let Err(err) = db.replace_trade_order_id(old_id, &new_order_id).await {
+            error = Some(format!("failed to update trade order id: {}", err));
+            break;
+        }
+    }
+    IncidentOutcome {
+        action: "resubmitted",
+        new_order_id: Some(new_order_id),
+        error,
+    }
+}
+
+async fn record_incident(
+    db: &Database,
+    account_id: &str,
+    order: &StuckOrder,
+    watchdog: &StuckOrderWatchdogSettings,
+    outcome: &IncidentOutcome,
+    now: chrono::DateTime<Utc>,
+) -> Result<()> {
+    let age_minutes = (now - order.submitted_at).num_minutes();
+    let message = format!(
+        "{} {} order {} on account {} stuck in {} for {} minutes ({})",
+        order.symbol,
+        order.side,
+        order.order_id,
+        account_id,
+        order.status,
+        age_minutes,
+        outcome.action
+    );
+    info!("{}", message);
+    db.insert_system_log(
+        SYSTEM_LOG_SOURCE,
+        "error",
+        &message,
+        Some(json!({
+            "accountId": account_id,
+            "orderId": order.order_id,
+            "clientOrderId": order.client_order_id,
+            "symbol": order.symbol,
+            "side": order.side,
+            "status": order.status,
+            "submittedAt": order.submitted_at.to_rfc3339(),
+            "ageMinutes": age_minutes,
+            "timeoutMinutes": watchdog.timeout_minutes,
+            "policy": watchdog.policy.label(),
+            "action": outcome.action,
+            "newOrderId": outcome.new_order_id,
+            "error": outcome.error,
+        })),
+    )
+    .await
+}
@@ -0,0 +1,83 @@
+use crate::commands::plan_operations::{self, PlanOperationsOptions};
+use crate::context::AppContext;
+use crate::models::SignalAction;
+use anyhow::{anyhow, Result};
+
+/// Hypothetical changes to one account's next plan.
+#[derive(Debug, Clone, Default)]
+pub struct WhatIfScenario {
+    pub account_id: String,
+    /// Extra signals dated on the plan date, on top of the stored ones.
+    pub signals: Vec<(SignalAction, String)>,
+    /// Replaces the broker-reported cash and buying power.
+    pub cash: Option<f64>,
+}
+
+/// Parses `ACTION:TICKER`, e.g. `BUY:AAPL`; the ticker is uppercased.
+fn parse_signal(value: &str) -> Result<(SignalAction, String)> {
+    let invalid = || anyhow!("--signal {} is not BUY:TICKER or SELL:TICKER", value);
+    let (action, ticker) = value.split_once(':').ok_or_else(invalid)?;
+    let action = match action.parse::<SignalAction>() {
+        Ok(SignalAction::Hold) | Err(_) => return Err(invalid()),
+        Ok(action) => action,
+    };
+    let ticker = ticker.trim().to_ascii_uppercase();
+    if ticker.is_empty() || ticker.contains(char::is_whitespace) {
+        return Err(invalid());
+    }
+    Ok((action, ticker))
+}
+
+/// Plans `account_id`'s strategies with the hypothetical `signals` and `cash` and prints each
+/// decision trace, writing nothing.
+pub async fn run(
+    app: &AppContext,
+    account_id: &str,
+    signals: &[String],
+    cash: Option<f64>,
+) -> Result<()> {
+    if signals.is_empty() && cash.is_none() {
+        return Err(anyhow!("what-if needs at least one --signal or --cash"));
+    }
+    if cash.is_some_and(|cash| !cash.is_finite() || cash < 0.0) {
+        return Err(anyhow!("--cash must be a non-negative amount"));
+    }
+    let scenario = WhatIfScenario {
+        account_id: account_id.trim().to_string(),
+        signals: signals
+            .iter()
+            .map(|signal| parse_signal(signal))
+            .collect::<Result<_>>()?,
+        cash,
+    };
+    plan_operations::plan_accounts(
+        app,
+        PlanOperationsOptions {
+            dry_run: true,
+            explain: true,
+        },
+        Some(&scenario.account_id),
+        Some(&scenario),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_buy_and_sell_signals_only() {
+        let (action, ticker) = parse_signal("buy: aapl").unwrap();
+        assert!(matches!(action, SignalAction::Buy));
+        assert_eq!(ticker, "AAPL");
+        assert!(matches!(
+            parse_signal("SELL:MSFT").unwrap().0,
+            SignalAction::Sell
+        ));
+        assert!(parse_signal("HOLD:AAPL").is_err());
+        assert!(parse_signal("BUY:").is_err());
+        assert!(parse_signal("AAPL").is_err());
+        assert!(parse_signal("BUY:BRK B").is_err());
+    }
+}
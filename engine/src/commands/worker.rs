@@ -0,0 +1,202 @@
+use crate::cancellation::{is_cancellation, CancellationToken};
+use crate::commands::pipeline;
+use crate::context::AppContext;
+use crate::database::{ClaimedJob, Database, JobOutcome};
+use crate::logging::ProgressTap;
+use crate::metrics::{self, CommandOutcome, Metrics};
+use anyhow::Result;
+use log::{error, info, warn};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinHandle;
+
+/// Minimum time between two progress updates of a running job.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone)]
+pub struct WorkerOptions {
+    /// Name stored on claimed jobs (default: engine-<pid>).
+    pub worker_id: Option<String>,
+    pub poll_interval: Duration,
+    /// Exit once the queue is empty instead of polling.
+    pub once: bool,
+    /// Address serving Prometheus metrics at `/metrics`, if any.
+    pub metrics_address: Option<SocketAddr>,
+}
+
+/// Claims jobs from the `jobs` table one at a time and runs each as a pipeline step until
+/// interrupted (or, with `once`, until the queue is empty).
+pub async fn run(
+    app: &AppContext,
+    default_data_file: &Path,
+    options: &WorkerOptions,
+) -> Result<()> {
+    let db = Arc::new(app.database().await?);
+    let worker_id = options
+        .worker_id
+        .clone()
+        .unwrap_or_else(|| format!("engine-{}", std::process::id()));
+    info!(
+        "Worker {} polling the job queue every {}s",
+        worker_id,
+        options.poll_interval.as_secs()
+    );
+    let metrics = Arc::new(Metrics::new("worker"));
+    let endpoint = match options.metrics_address {
+        Some(address) => Some(metrics::spawn(metrics.clone(), address).await?),
+        None => None,
+    };
+
+    let result = poll_jobs(app, &db, &worker_id, default_data_file, options, &metrics).await;
+    if let Some(endpoint) = endpoint {
+        endpoint.abort();
+    }
+    result
+}
+
+async fn poll_jobs(
+    app: &AppContext,
+    db: &Arc<Database>,
+    worker_id: &str,
+    default_data_file: &Path,
+    options: &WorkerOptions,
+    metrics: &Metrics,
+) -> Result<()> {
+    loop {
+        if app.cancellation().is_cancelled() {
+            info!("Worker {} stopping", worker_id);
+            return Ok(());
+        }
+        if let Some(job) = db.claim_next_job(worker_id).await? {
+            metrics.set_running(true);
+            let command = job.command.clone();
+            let started = Instant::now();
+            let outcome = run_job(app, db, job, default_data_file, options.poll_interval).await;
+            metrics.set_running(false);
+            if let Ok(Some(outcome)) = &outcome {
+                metrics.record(&command, *outcome, started.elapsed().as_secs_f64());
+            }
+            outcome?;
+            continue;
+        }
+        if options.once {
+            info!("Job queue is empty; worker {} exiting", worker_id);
+            return Ok(());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(options.poll_interval) => {}
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+}
+
+/// Runs one claimed job and stores its outcome, which is `None` when the job went back to
+/// the queue.
+async fn run_job(
+    app: &AppContext,
+    db: &Arc<Database>,
+    job: ClaimedJob,
+    default_data_file: &Path,
+    poll_interval: Duration,
+) -> Result<Option<CommandOutcome>> {
+    let step = match pipeline::parse_job_step(&job.command, &job.payload) {
+        Ok(step) => step,
+        Err(err) => {
+            warn!("Job {} rejected: {:#}", job.id, err);
+            let error = format!("{:#}", err);
+            db.finish_job(&job.id, JobOutcome::Failed(&error)).await?;
+            return Ok(Some(CommandOutcome::Failed));
+        }
+    };
+    info!("Job {} started: {}", job.id, step.label());
+    let started = Instant::now();
+
+    // The job stops on the worker's shutdown or on its own cancel_requested flag.
+    let cancellation = app.cancellation().child();
+    let job_app = app.clone().with_cancellation(cancellation.clone());
+    let watcher = spawn_cancel_watcher(db.clone(), job.id.clone(), cancellation, poll_interval);
+    let (tap, receiver) = ProgressTap::start();
+    let writer = spawn_progress_writer(db.clone(), job.id.clone(), receiver);
+    let outcome = step.execute(&job_app, default_data_file).await;
+    drop(tap);
+    watcher.abort();
+    let _ = writer.await;
+
+    let elapsed = started.elapsed().as_secs_f64();
+    match outcome {
+        Ok(()) => {
+            info!("Job {} succeeded in {:.1}s", job.id, elapsed);
+            db.finish_job(&job.id, JobOutcome::Succeeded).await?;
+            Ok(Some(CommandOutcome::Succeeded))
+        }
+        Err(err) if is_cancellation(&err) && app.cancellation().is_cancelled() => {
+            info!(
+                "Job {} interrupted by shutdown; returning it to the queue",
+                job.id
+            );
+            db.requeue_job(&job.id).await?;
+            Ok(None)
+        }
+        Err(err) if is_cancellation(&err) => {
+            info!("Job {} cancelled after {:.1}s", job.id, elapsed);
+            db.finish_job(&job.id, JobOutcome::Cancelled).await?;
+            Ok(Some(CommandOutcome::Cancelled))
+        }
+        Err(err) => {
+            error!("Job {} failed after {:.1}s: {:#}", job.id, elapsed, err);
+            let error = format!("{:#}", err);
+            db.finish_job(&job.id, JobOutcome::Failed(&error)).await?;
+            Ok(Some(CommandOutcome::Failed))
+        }
+    }
+}
+
+/// Cancels `cancellation` once the job's `cancel_requested` flag is set.
+fn spawn_cancel_watcher(
+    db: Arc<Database>,
+    job_id: String,
+    cancellation: CancellationToken,
+    poll_interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(poll_interval).await;
+            match db.is_job_cancel_requested(&job_id).await {
+                Ok(true) => {
+                    info!(
+                        "Job {} cancel requested; stopping at the next checkpoint",
+                        job_id
+                    );
+                    cancellation.cancel();
+                    return;
+                }
+                Ok(false) => {}
+                Err(err) => warn!("Failed to check job {} for cancellation: {}", job_id, err),
+            }
+        }
+    })
+}
+
+/// Stores the latest log message of the running job as its progress, at most once per
+/// `PROGRESS_INTERVAL`, until the tap closes.
+fn spawn_progress_writer(
+    db: Arc<Database>,
+    job_id: String,
+    mut receiver: UnboundedReceiver<String>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        while let Some(mut message) = receiver.recv().await {
+            while let Ok(newer) = receiver.try_recv() {
+                message = newer;
+            }
+            if let Err(err) = db.update_job_progress(&job_id, &message).await {
+                // Logging here would feed the failure back into the tap.
+                eprintln!("Failed to store progress of job {job_id}: {err}");
+            }
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+        }
+    })
+}
@@ -1,20 +1,30 @@
 use crate::app_url::resolve_api_base_url;
 use crate::backtester::ActiveStrategyBacktester;
 use crate::cache::CacheManager;
+use crate::cancellation::CancellationToken;
 use crate::data_context::{MarketData, TickerScope};
 use crate::database::Database;
 use crate::optimizer::OptimizationEngine;
 use crate::optimizer_status::OptimizerStatus;
+use crate::settings_layers::SettingLayers;
 use crate::signals::SignalManager;
+use crate::storage::SqliteStorage;
+use crate::strategy::lightgbm::CrossSectionalCache;
 use anyhow::{anyhow, Result};
 use chrono::NaiveDate;
 use log::{info, warn};
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct AppContext {
     database_url: Option<String>,
+    threads: usize,
+    sqlite_file: Option<PathBuf>,
+    setting_layers: SettingLayers,
+    cross_sectional_cache: Arc<CrossSectionalCache>,
+    cancellation: CancellationToken,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -31,7 +41,92 @@ impl MarketDataFilters {
 
 impl AppContext {
     pub async fn initialize(database_url: Option<String>) -> Result<Self> {
-        Ok(Self { database_url })
+        let threads = std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1);
+        Ok(Self {
+            database_url,
+            threads,
+            sqlite_file: None,
+            setting_layers: SettingLayers::default(),
+            cross_sectional_cache: Arc::new(CrossSectionalCache::default()),
+            cancellation: CancellationToken::default(),
+        })
+    }
+
+    /// Layers `engine.toml` and `ENGINE_SETTING_*` values beneath database settings.
+    pub fn with_setting_layers(mut self, layers: SettingLayers) -> Self {
+        self.setting_layers = layers;
+        self
+    }
+
+    pub fn setting_layers(&self) -> &SettingLayers {
+        &self.setting_layers
+    }
+
+    /// Database settings over the `engine.toml` and environment layers.
+    pub async fn database_settings(&self, db: &Database) -> Result<HashMap<String, String>> {
+        let database = db.get_all_settings().await?;
+        Ok(self.setting_layers.merge(None, Some(&database)))
+    }
+
+    /// Snapshot (or local store) settings under the `engine.toml` and environment layers.
+    pub fn snapshot_settings(&self, snapshot: &HashMap<String, String>) -> HashMap<String, String> {
+        self.setting_layers.merge(Some(snapshot), None)
+    }
+
+    /// Uses a local SQLite store for settings and the backtest cache when `path` is provided.
+    pub fn with_sqlite_file(mut self, path: Option<PathBuf>) -> Self {
+        self.sqlite_file = path;
+        self
+    }
+
+    /// Persists cross-sectional contexts under `directory` when provided. Every engine context
+    /// created afterwards shares the same in-memory cache either way.
+    pub fn with_cross_sectional_cache_dir(mut self, directory: Option<PathBuf>) -> Self {
+        if let Some(directory) = directory {
+            self.cross_sectional_cache = Arc::new(CrossSectionalCache::with_directory(directory));
+        }
+        self
+    }
+
+    /// Stops databases and engine contexts created afterwards at their next checkpoint once
+    /// `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Opens the local SQLite store, if one was configured.
+    pub fn local_store(&self) -> Result<Option<SqliteStorage>> {
+        self.sqlite_file
+            .as_deref()
+            .map(SqliteStorage::open)
+            .transpose()
+    }
+
+    /// Overrides the worker thread budget when `threads` is provided.
+    pub fn with_threads(mut self, threads: Option<usize>) -> Result<Self> {
+        match threads {
+            Some(0) => Err(anyhow!("Thread count must be greater than zero")),
+            Some(value) => {
+                self.threads = value;
+                Ok(self)
+            }
+            None => Ok(self),
+        }
+    }
+
+    pub fn has_database(&self) -> bool {
+        self.database_url.is_some()
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
     }
 
     pub async fn database(&self) -> Result<Database> {
@@ -40,28 +135,30 @@ impl AppContext {
                 "DATABASE_URL must be set to use database-backed engine commands."
             ));
         };
-        Database::new(database_url).await
+        Ok(Database::new(database_url)
+            .await?
+            .with_cancellation(self.cancellation.clone()))
     }
 
-    pub async fn engine_context_training_tickers(&self) -> Result<EngineContext> {
-        let database_url = self.database_url.as_deref().ok_or_else(|| {
-            anyhow!("DATABASE_URL must be set to load market data from the database.")
-        })?;
-        EngineContext::initialize(database_url, TickerScope::TrainingOnly).await
-    }
-
-    pub async fn engine_context_validation_tickers(&self) -> Result<EngineContext> {
+    pub async fn engine_context_all_tickers(&self) -> Result<EngineContext> {
         let database_url = self.database_url.as_deref().ok_or_else(|| {
             anyhow!("DATABASE_URL must be set to load market data from the database.")
         })?;
-        EngineContext::initialize(database_url, TickerScope::ValidationOnly).await
+        let context = EngineContext::initialize(database_url, TickerScope::AllTickers).await?;
+        Ok(self.layer_database_settings(context))
     }
 
-    pub async fn engine_context_all_tickers(&self) -> Result<EngineContext> {
+    pub async fn engine_context_since(
+        &self,
+        ticker_scope: TickerScope,
+        candles_since: Option<NaiveDate>,
+    ) -> Result<EngineContext> {
         let database_url = self.database_url.as_deref().ok_or_else(|| {
             anyhow!("DATABASE_URL must be set to load market data from the database.")
         })?;
-        EngineContext::initialize(database_url, TickerScope::AllTickers).await
+        let context =
+            EngineContext::initialize_since(database_url, ticker_scope, candles_since).await?;
+        Ok(self.layer_database_settings(context))
     }
 
     pub async fn engine_context_from_file<P: AsRef<Path>>(
@@ -70,13 +167,40 @@ impl AppContext {
         ticker_scope: TickerScope,
         filters: Option<MarketDataFilters>,
     ) -> Result<EngineContext> {
-        EngineContext::initialize_with_market_data_file(
+        let mut context = EngineContext::initialize_with_market_data_file(
             self.database_url.as_deref(),
             data_file,
             ticker_scope,
             filters,
         )
-        .await
+        .await?;
+        let settings = self.snapshot_settings(context.market_data.settings());
+        context.market_data = context.market_data.with_settings(settings);
+        if let Some(store) = self.local_store()? {
+            info!("Caching backtest results in {}", store.path().display());
+            context.cache_manager = context.cache_manager.with_local_store(store);
+        }
+        Ok(self.share_runtime_state(context))
+    }
+
+    fn share_runtime_state(&self, mut context: EngineContext) -> EngineContext {
+        context.cache_manager = context
+            .cache_manager
+            .with_cross_sectional_cache(self.cross_sectional_cache.clone());
+        context.db = context
+            .db
+            .take()
+            .map(|db| db.with_cancellation(self.cancellation.clone()));
+        context.cancellation = self.cancellation.clone();
+        context
+    }
+
+    fn layer_database_settings(&self, mut context: EngineContext) -> EngineContext {
+        let settings = self
+            .setting_layers
+            .merge(None, Some(context.market_data.settings()));
+        context.market_data = context.market_data.with_settings(settings);
+        self.share_runtime_state(context)
     }
 }
 
@@ -87,18 +211,27 @@ pub struct EngineContext {
     status: OptimizerStatus,
     backtested_strategy_ids: HashSet<String>,
     ticker_scope: TickerScope,
+    cancellation: CancellationToken,
 }
 
 impl EngineContext {
     pub async fn initialize<S: AsRef<str>>(
         database_url: S,
         ticker_scope: TickerScope,
+    ) -> Result<Self> {
+        Self::initialize_since(database_url, ticker_scope, None).await
+    }
+
+    pub async fn initialize_since<S: AsRef<str>>(
+        database_url: S,
+        ticker_scope: TickerScope,
+        candles_since: Option<NaiveDate>,
     ) -> Result<Self> {
         let status = OptimizerStatus::new();
         status.set_phase("Connecting to database");
         let db = Database::new(database_url).await?;
         status.set_phase("Loading market data");
-        let market_data = MarketData::load(&db, ticker_scope).await?;
+        let market_data = MarketData::load_since(&db, ticker_scope, candles_since).await?;
         Ok(Self::from_components(
             Some(db),
             market_data,
@@ -147,7 +280,8 @@ impl EngineContext {
         let has_db = db.is_some();
         let backtest_secret = market_data.settings().get("BACKTEST_API_SECRET").cloned();
         let api_base_url = resolve_api_base_url(market_data.settings());
-        let cache_manager = CacheManager::new(backtest_secret, api_base_url, has_db);
+        let cache_manager = CacheManager::new(backtest_secret, api_base_url, has_db)
+            .with_data_hash(market_data.content_hash());
         status.set_phase("Idle");
 
         Self {
@@ -157,11 +291,17 @@ impl EngineContext {
             status,
             backtested_strategy_ids: HashSet::new(),
             ticker_scope,
+            cancellation: CancellationToken::default(),
         }
     }
 
     pub fn optimizer(&mut self) -> OptimizationEngine<'_> {
-        OptimizationEngine::new(self.db.as_mut(), &self.cache_manager, &self.market_data)
+        OptimizationEngine::new(
+            self.db.as_mut(),
+            &self.cache_manager,
+            &self.market_data,
+            &self.cancellation,
+        )
     }
 
     pub fn backtester(&mut self) -> ActiveStrategyBacktester<'_> {
@@ -175,6 +315,7 @@ impl EngineContext {
             &self.market_data,
             &mut self.backtested_strategy_ids,
             self.ticker_scope,
+            &self.cancellation,
         )
     }
 
@@ -183,7 +324,65 @@ impl EngineContext {
             .db
             .as_mut()
             .expect("SignalManager requires a database connection");
-        SignalManager::new(db, &self.status, &self.market_data)
+        SignalManager::new(
+            db,
+            &self.status,
+            &self.market_data,
+            self.cache_manager.cross_sectional_cache(),
+        )
+    }
+
+    /// Keeps only the tickers `keep` accepts and stores results under the `custom` scope.
+    pub fn restrict_to_custom_tickers(mut self, keep: impl Fn(&str) -> bool) -> Result<Self> {
+        let allowed: HashSet<String> = self
+            .market_data
+            .tickers()
+            .iter()
+            .filter(|ticker| keep(ticker))
+            .cloned()
+            .collect();
+        if allowed.is_empty() {
+            return Err(anyhow!("None of the selected tickers have market data"));
+        }
+        let before = self.market_data.tickers().len();
+        self.market_data = self.market_data.restrict_to_tickers(&allowed)?;
+        self.ticker_scope = TickerScope::Custom;
+        info!(
+            "Restricted market data to {} selected tickers (from {})",
+            self.market_data.tickers().len(),
+            before
+        );
+        Ok(self)
+    }
+
+    pub fn tickers(&self) -> &[String] {
+        self.market_data.tickers()
+    }
+
+    pub fn market_data(&self) -> &MarketData {
+        &self.market_data
+    }
+
+    /// Database-less copy of this context over `market_data`, sharing its caches, for
+    /// re-scoring parameter sets on altered data.
+    pub fn with_market_data(&self, market_data: MarketData) -> EngineContext {
+        EngineContext {
+            db: None,
+            cache_manager: self.cache_manager.clone(),
+            market_data,
+            status: self.status.clone(),
+            backtested_strategy_ids: HashSet::new(),
+            ticker_scope: self.ticker_scope,
+            cancellation: self.cancellation.clone(),
+        }
+    }
+
+    /// Database-less copy of this context over `tickers` alone.
+    pub fn ticker_subset(&self, tickers: &HashSet<String>) -> Result<EngineContext> {
+        let mut subset =
+            self.with_market_data(self.market_data.clone().restrict_to_tickers(tickers)?);
+        subset.ticker_scope = TickerScope::Custom;
+        Ok(subset)
     }
 
     pub fn status_handle(&self) -> OptimizerStatus {
@@ -195,7 +394,7 @@ impl EngineContext {
         ticker_scope: TickerScope,
         db: Option<&Database>,
     ) -> Result<MarketData> {
-        if matches!(ticker_scope, TickerScope::AllTickers) {
+        if matches!(ticker_scope, TickerScope::AllTickers | TickerScope::Custom) {
             return Ok(market_data);
         }
 
@@ -1,24 +1,43 @@
+use crate::candle_utils::{normalize_session_dates, SessionCalendar};
+use crate::config::volatility_index_ticker;
 use crate::database::Database;
-use crate::models::{Candle, StrategyParameter, StrategyTemplate, TickerInfo};
+use crate::models::{
+    AuxiliaryData, AuxiliarySeries, AuxiliarySeriesMap, Candle, EconomicCalendar,
+    EconomicEventKind, StrategyParameter, StrategyTemplate, TickerDistributionMap,
+    TickerEarningsMap, TickerInfo, TickerSentimentMap, TickerShortInterestMap,
+    UniverseMembershipMap,
+};
 use crate::optimizer_status::OptimizerStatus;
 use anyhow::{anyhow, Context, Result};
 use chrono::prelude::*;
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::Path;
-use std::sync::Arc;
-
-const MARKET_DATA_SNAPSHOT_VERSION: u32 = 5;
-const SNAPSHOT_ALLOWED_SETTINGS: [&str; 22] = [
+use std::sync::{Arc, OnceLock};
+
+/// Written on its own ahead of the snapshot body, so files of another version are rejected
+/// before their body is decoded.
+const MARKET_DATA_SNAPSHOT_VERSION: u32 = 13;
+/// zstd level `export-market-data` compresses snapshots with unless told otherwise.
+pub const DEFAULT_SNAPSHOT_COMPRESSION_LEVEL: i32 = 3;
+/// First bytes of a zstd frame; uncompressed snapshots start with their version instead.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+const SNAPSHOT_ALLOWED_SETTINGS: [&str; 38] = [
     "BACKTEST_INITIAL_CAPITAL",
     "BACKTEST_API_SECRET",
+    "BACKTEST_UNIVERSE",
+    "BACKTEST_WARMUP_POLICY",
     "BALANCE_WINDOW_END_DATE",
     "BALANCE_WINDOW_START_DATE",
+    "CAPACITY_MAX_VOLUME_SHARE",
     "DOMAIN",
+    "EXCHANGE_TIMEZONE",
+    "HEDGE_TICKER",
     "LIGHTGBM_TRAINING_END_DATE",
     "LIGHTGBM_TRAINING_START_DATE",
     "LOCAL_OPTIMIZATION_STEP_MULTIPLIERS",
@@ -26,16 +45,27 @@ const SNAPSHOT_ALLOWED_SETTINGS: [&str; 22] = [
     "MAX_ALLOWED_DRAWDOWN_RATIO",
     "MINIMUM_DOLLAR_VOLUME_FOR_ENTRY",
     "MINIMUM_DOLLAR_VOLUME_LOOKBACK",
+    "MIN_TICKER_HISTORY_CANDLES",
     "OPTIMIZATION_OBJECTIVE",
     "OPTIMIZER_TRAINING_END_DATE",
     "OPTIMIZER_TRAINING_START_DATE",
+    "ORDER_REJECTION_RATE",
+    "PARTIAL_FILL_MAX_VOLUME_SHARE",
+    "PARTIAL_FILL_REMAINDER",
     "SHORT_BORROW_FEE_ANNUAL_RATE",
+    "STOP_FILL_AT_STOP_PRICE",
+    "TICKER_RUNTIME_OVERRIDES",
     "TRADE_CLOSE_FEE_RATE",
     "TRADE_ENTRY_PRICE_MAX",
     "TRADE_ENTRY_PRICE_MIN",
     "TRADE_SLIPPAGE_RATE",
+    "VERIFY_MAX_ENTRY_CONCENTRATION",
+    "VERIFY_MIN_CAPACITY",
+    "VERIFY_MIN_EXPECTANCY",
+    "VERIFY_REQUIRE_BEAT_BUY_AND_HOLD",
     "VERIFY_WINDOW_END_DATE",
     "VERIFY_WINDOW_START_DATE",
+    "VOLATILITY_INDEX_TICKER",
 ];
 
 #[derive(Clone, Copy)]
@@ -43,12 +73,15 @@ pub enum TickerScope {
     AllTickers,
     TrainingOnly,
     ValidationOnly,
+    /// Tickers picked per run (watchlists, exclusion files). Loads every ticker for the caller to
+    /// narrow, and keeps its results apart from the database universes.
+    Custom,
 }
 
 impl TickerScope {
     pub fn allows(self, info: &TickerInfo) -> bool {
         match self {
-            TickerScope::AllTickers => true,
+            TickerScope::AllTickers | TickerScope::Custom => true,
             TickerScope::TrainingOnly => info.training,
             TickerScope::ValidationOnly => !info.training,
         }
@@ -59,13 +92,15 @@ impl TickerScope {
             TickerScope::AllTickers => "all",
             TickerScope::TrainingOnly => "training",
             TickerScope::ValidationOnly => "validation",
+            TickerScope::Custom => "custom",
         }
     }
 }
 
 #[derive(Serialize, Deserialize)]
 struct MarketDataSnapshot {
-    version: u32,
+    /// `MarketData::content_hash` of the data when it was written, checked again on load.
+    content_hash: String,
     generated_at: DateTime<Utc>,
     tickers: Vec<String>,
     unique_dates: Vec<DateTime<Utc>>,
@@ -75,6 +110,20 @@ struct MarketDataSnapshot {
     #[serde(default)]
     ticker_expense_map: HashMap<String, f64>,
     #[serde(default)]
+    ticker_distributions: TickerDistributionMap,
+    #[serde(default)]
+    ticker_earnings: TickerEarningsMap,
+    #[serde(default)]
+    ticker_short_interest: TickerShortInterestMap,
+    #[serde(default)]
+    ticker_sentiment: TickerSentimentMap,
+    #[serde(default)]
+    universe_membership: UniverseMembershipMap,
+    #[serde(default)]
+    auxiliary_series: HashMap<String, AuxiliarySeries>,
+    #[serde(default)]
+    economic_calendar: EconomicCalendar,
+    #[serde(default)]
     settings: HashMap<String, String>,
 }
 
@@ -193,18 +242,168 @@ fn scrub_snapshot_settings(settings: &HashMap<String, String>) -> HashMap<String
         .collect()
 }
 
+#[derive(Clone)]
 pub struct MarketData {
     all_candles: Arc<Vec<Candle>>,
     unique_dates: Arc<Vec<DateTime<Utc>>>,
     tickers: Arc<Vec<String>>,
     candles_by_ticker_indices: Arc<HashMap<String, Vec<usize>>>,
     templates: Arc<HashMap<String, StrategyTemplate>>,
-    ticker_expense_map: Arc<HashMap<String, f64>>,
+    /// Auxiliary series are non-tradable, so their tickers are not in `tickers`.
+    auxiliary: Arc<AuxiliaryData>,
     settings: Arc<HashMap<String, String>>,
+    content_hash: Arc<OnceLock<String>>,
+}
+
+/// Drops the distribution schedules of tickers whose closes are dividend-adjusted (an
+/// `unadjusted_close` that differs from `close`): their prices already include the payouts.
+fn drop_adjusted_distributions(distributions: &mut TickerDistributionMap, candles: &[Candle]) {
+    let adjusted: HashSet<&str> = candles
+        .iter()
+        .filter(|candle| {
+            candle.unadjusted_close.is_some_and(|unadjusted| {
+                (unadjusted - candle.close).abs() > 1e-6 * candle.close.abs()
+            })
+        })
+        .map(|candle| candle.ticker.as_str())
+        .collect();
+    distributions.retain(|ticker, _| {
+        let keep = !adjusted.contains(ticker.as_str());
+        if !keep {
+            warn!(
+                "Ignoring the distributions of {}: its candles are dividend-adjusted",
+                ticker
+            );
+        }
+        keep
+    });
+}
+
+fn hash_text(hasher: &mut Sha256, value: &str) {
+    hasher.update((value.len() as u64).to_le_bytes());
+    hasher.update(value.as_bytes());
+}
+
+/// SHA-256 over everything a backtest reads from the market data: tickers, candles in order,
+/// expense ratios, the distributions, earnings dates, short interest, sentiment and universe membership of the loaded
+/// tickers, the auxiliary series and the economic calendar.
+fn compute_content_hash(
+    tickers: &[String],
+    candles: &[Candle],
+    auxiliary: &AuxiliaryData,
+) -> String {
+    let AuxiliaryData {
+        ticker_expense_map,
+        ticker_distributions,
+        ticker_earnings,
+        ticker_short_interest,
+        ticker_sentiment,
+        universe_membership,
+        auxiliary_series,
+        economic_calendar,
+    } = auxiliary;
+    let mut hasher = Sha256::new();
+    hasher.update((tickers.len() as u64).to_le_bytes());
+    for ticker in tickers {
+        hash_text(&mut hasher, ticker);
+    }
+    hasher.update((candles.len() as u64).to_le_bytes());
+    for candle in candles {
+        hash_text(&mut hasher, &candle.ticker);
+        hasher.update(candle.date.timestamp().to_le_bytes());
+        for price in [candle.open, candle.high, candle.low, candle.close] {
+            hasher.update(price.to_bits().to_le_bytes());
+        }
+        hasher.update(
+            candle
+                .unadjusted_close
+                .map_or(u64::MAX, f64::to_bits)
+                .to_le_bytes(),
+        );
+        hasher.update(candle.volume_shares.to_le_bytes());
+    }
+    let mut sorted_tickers: Vec<&String> = tickers.iter().collect();
+    sorted_tickers.sort();
+    for ticker in sorted_tickers {
+        if let Some(ratio) = ticker_expense_map.get(ticker) {
+            hash_text(&mut hasher, ticker);
+            hasher.update(ratio.to_bits().to_le_bytes());
+        }
+        if let Some(distributions) = ticker_distributions.get(ticker) {
+            hash_text(&mut hasher, ticker);
+            for distribution in distributions {
+                hasher.update(distribution.ex_date.timestamp().to_le_bytes());
+                hasher.update(distribution.amount.to_bits().to_le_bytes());
+            }
+        }
+        if let Some(earnings) = ticker_earnings.get(ticker) {
+            hash_text(&mut hasher, ticker);
+            for date in earnings {
+                hasher.update(date.timestamp().to_le_bytes());
+            }
+        }
+        if let Some(points) = ticker_short_interest.get(ticker) {
+            hash_text(&mut hasher, ticker);
+            for point in points {
+                hasher.update(point.date.timestamp().to_le_bytes());
+                for value in [point.short_interest_ratio, point.borrow_fee_rate] {
+                    hasher.update(value.map_or(u64::MAX, f64::to_bits).to_le_bytes());
+                }
+            }
+        }
+        if let Some(points) = ticker_sentiment.get(ticker) {
+            hash_text(&mut hasher, ticker);
+            for point in points {
+                hasher.update(point.date.timestamp().to_le_bytes());
+                hasher.update(point.score.to_bits().to_le_bytes());
+            }
+        }
+        if let Some(intervals) = universe_membership.get(ticker) {
+            hash_text(&mut hasher, ticker);
+            for interval in intervals {
+                hasher.update(interval.start.timestamp().to_le_bytes());
+                hasher.update(
+                    interval
+                        .end
+                        .map_or(i64::MAX, |end| end.timestamp())
+                        .to_le_bytes(),
+                );
+            }
+        }
+    }
+    let mut auxiliary_tickers: Vec<&String> = auxiliary_series.keys().collect();
+    auxiliary_tickers.sort();
+    for ticker in auxiliary_tickers {
+        let series = &auxiliary_series[ticker];
+        hash_text(&mut hasher, ticker);
+        hasher.update((series.len() as u64).to_le_bytes());
+        for (date, close) in series.dates().iter().zip(series.closes()) {
+            hasher.update(date.timestamp().to_le_bytes());
+            hasher.update(close.to_bits().to_le_bytes());
+        }
+    }
+    for kind in EconomicEventKind::ALL {
+        let dates = economic_calendar.dates(kind);
+        hash_text(&mut hasher, kind.as_str());
+        hasher.update((dates.len() as u64).to_le_bytes());
+        for date in dates {
+            hasher.update(date.num_days_from_ce().to_le_bytes());
+        }
+    }
+    hex::encode(hasher.finalize())
 }
 
 impl MarketData {
     pub async fn load(db: &Database, scope: TickerScope) -> Result<Self> {
+        Self::load_since(db, scope, None).await
+    }
+
+    /// Loads market data, skipping candles before `candles_since` when provided.
+    pub async fn load_since(
+        db: &Database,
+        scope: TickerScope,
+        candles_since: Option<NaiveDate>,
+    ) -> Result<Self> {
         info!("Getting tickers with candle data...");
         let ticker_infos = db.get_tickers_with_candle_counts().await?;
 
@@ -218,7 +417,12 @@ impl MarketData {
         };
 
         let mut tickers: Vec<String> = Vec::new();
+        let mut auxiliary_tickers: HashSet<String> = HashSet::new();
         for info in &ticker_infos {
+            if info.auxiliary {
+                auxiliary_tickers.insert(info.symbol.clone());
+                continue;
+            }
             if !scope.allows(info) {
                 continue;
             }
@@ -228,12 +432,36 @@ impl MarketData {
             }
         }
 
-        info!("Loading candle data from database...");
-        let mut all_candles = db.get_all_candles().await?;
+        match candles_since {
+            Some(since) => info!("Loading candle data from database since {}...", since),
+            None => info!("Loading candle data from database..."),
+        }
+        let mut all_candles = db.get_all_candles(candles_since).await?;
+        // Auxiliary tickers are read as series and never traded, so their candles leave the set.
+        let mut auxiliary_candles: Vec<Candle> = Vec::new();
+        all_candles.retain(|candle| {
+            if auxiliary_tickers.contains(&candle.ticker) {
+                auxiliary_candles.push(candle.clone());
+                false
+            } else {
+                true
+            }
+        });
 
         let ticker_set: HashSet<String> = tickers.iter().cloned().collect();
         all_candles.retain(|c| ticker_set.contains(&c.ticker));
 
+        let settings = db.get_all_settings().await?;
+        let calendar = SessionCalendar::from_settings_map(&settings)?;
+        let moved = normalize_session_dates(&mut all_candles, calendar)
+            + normalize_session_dates(&mut auxiliary_candles, calendar);
+        if moved > 0 {
+            info!(
+                "Moved {} candle timestamps onto exchange session dates",
+                moved
+            );
+        }
+
         let mut ticker_expense_map: HashMap<String, f64> = HashMap::new();
         for info in &ticker_infos {
             if !ticker_set.contains(&info.symbol) {
@@ -287,7 +515,61 @@ impl MarketData {
             .map(|template| (template.id.clone(), template))
             .collect();
 
-        let settings = db.get_all_settings().await?;
+        let mut ticker_distributions = db.get_etf_distributions().await?;
+        ticker_distributions.retain(|ticker, _| ticker_set.contains(ticker));
+        drop_adjusted_distributions(&mut ticker_distributions, &all_candles);
+        let mut ticker_earnings = db.get_ticker_earnings().await?;
+        ticker_earnings.retain(|ticker, _| ticker_set.contains(ticker));
+        let mut ticker_short_interest = db.get_ticker_short_interest().await?;
+        ticker_short_interest.retain(|ticker, _| ticker_set.contains(ticker));
+        let mut ticker_sentiment = db.get_ticker_sentiment().await?;
+        ticker_sentiment.retain(|ticker, _| ticker_set.contains(ticker));
+        let universe_membership = match settings
+            .get("BACKTEST_UNIVERSE")
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+        {
+            Some(universe) => {
+                let mut membership = db.get_universe_membership(universe).await?;
+                if membership.is_empty() {
+                    return Err(anyhow!(
+                        "BACKTEST_UNIVERSE is {} but that universe has no memberships; import them with `import-universe`",
+                        universe
+                    ));
+                }
+                membership.retain(|ticker, _| ticker_set.contains(ticker));
+                info!(
+                    "Restricting backtest entries to universe {} ({} loaded tickers were members)",
+                    universe,
+                    membership.len()
+                );
+                membership
+            }
+            None => UniverseMembershipMap::new(),
+        };
+        let mut auxiliary_candles_by_ticker: HashMap<String, Vec<Candle>> = HashMap::new();
+        for candle in auxiliary_candles {
+            auxiliary_candles_by_ticker
+                .entry(candle.ticker.clone())
+                .or_default()
+                .push(candle);
+        }
+        let auxiliary_series: AuxiliarySeriesMap = auxiliary_candles_by_ticker
+            .into_iter()
+            .map(|(ticker, candles)| (ticker, Arc::new(AuxiliarySeries::from_candles(&candles))))
+            .collect();
+        if !auxiliary_series.is_empty() {
+            let mut names: Vec<&str> = auxiliary_series.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            info!("Loaded auxiliary series: {}", names.join(", "));
+        }
+        let economic_calendar = EconomicCalendar::from_events(&db.get_economic_events().await?);
+        if !economic_calendar.is_empty() {
+            info!(
+                "Loaded {} economic calendar events",
+                economic_calendar.len()
+            );
+        }
 
         Self::from_components(
             tickers,
@@ -295,7 +577,16 @@ impl MarketData {
             all_candles,
             candles_by_ticker_indices,
             templates,
-            ticker_expense_map,
+            AuxiliaryData {
+                ticker_expense_map,
+                ticker_distributions,
+                ticker_earnings,
+                ticker_short_interest,
+                ticker_sentiment,
+                universe_membership,
+                auxiliary_series,
+                economic_calendar,
+            },
             settings,
         )
     }
@@ -309,17 +600,31 @@ impl MarketData {
         let file = File::open(path).with_context(|| {
             format!("Failed to open market data snapshot at {}", path.display())
         })?;
-        let reader = BufReader::new(file);
-        let snapshot: MarketDataSnapshot =
-            bincode::deserialize_from(reader).context("Snapshot decode failed")?;
-
-        if snapshot.version != MARKET_DATA_SNAPSHOT_VERSION {
+        let mut buffered = BufReader::new(file);
+        let compressed = buffered
+            .fill_buf()
+            .with_context(|| format!("Failed to read market data snapshot {}", path.display()))?
+            .starts_with(&ZSTD_MAGIC);
+        let mut reader: Box<dyn Read> = if compressed {
+            Box::new(
+                zstd::Decoder::with_buffer(buffered)
+                    .context("Failed to start decompressing market data snapshot")?,
+            )
+        } else {
+            Box::new(buffered)
+        };
+        let version: u32 =
+            bincode::deserialize_from(&mut reader).context("Snapshot header decode failed")?;
+        if version != MARKET_DATA_SNAPSHOT_VERSION {
             return Err(anyhow!(
-                "Market data snapshot version mismatch (found {}, expected {})",
-                snapshot.version,
+                "Market data snapshot version mismatch (found {}, expected {}); regenerate it with `export-market-data`",
+                version,
                 MARKET_DATA_SNAPSHOT_VERSION
             ));
         }
+        let snapshot: MarketDataSnapshot =
+            bincode::deserialize_from(reader).context("Snapshot decode failed")?;
+        let expected_hash = snapshot.content_hash;
 
         status.set_phase("Reconstructing market data snapshot");
         let candles_by_ticker_indices = Self::build_candle_index(&snapshot.candles);
@@ -335,18 +640,52 @@ impl MarketData {
             })
             .collect::<Result<HashMap<_, _>>>()?;
 
-        Self::from_components(
+        let market_data = Self::from_components(
             snapshot.tickers,
             snapshot.unique_dates,
             snapshot.candles,
             candles_by_ticker_indices,
             templates,
-            snapshot.ticker_expense_map,
+            AuxiliaryData {
+                ticker_expense_map: snapshot.ticker_expense_map,
+                ticker_distributions: snapshot.ticker_distributions,
+                ticker_earnings: snapshot.ticker_earnings,
+                ticker_short_interest: snapshot.ticker_short_interest,
+                ticker_sentiment: snapshot.ticker_sentiment,
+                universe_membership: snapshot.universe_membership,
+                auxiliary_series: snapshot
+                    .auxiliary_series
+                    .into_iter()
+                    .map(|(ticker, series)| (ticker, Arc::new(series)))
+                    .collect(),
+                economic_calendar: snapshot.economic_calendar,
+            },
             snapshot.settings,
-        )
+        )?;
+        status.set_phase("Verifying market data snapshot");
+        if market_data.content_hash() != expected_hash {
+            return Err(anyhow!(
+                "Market data snapshot {} is corrupted or was modified (content hash {} does not match the recorded {}); regenerate it with `export-market-data`",
+                path.display(),
+                market_data.content_hash(),
+                expected_hash
+            ));
+        }
+        info!(
+            "Loaded market data snapshot {} (content hash {})",
+            path.display(),
+            market_data.content_hash()
+        );
+        Ok(market_data)
     }
 
-    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+    /// Writes the snapshot, zstd-compressed at `compression_level` when one is given.
+    /// `load_from_file` reads either form.
+    pub fn save_to_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+        compression_level: Option<i32>,
+    ) -> Result<()> {
         let path = path.as_ref();
         if let Some(parent) = path.parent() {
             if !parent.as_os_str().is_empty() {
@@ -362,10 +701,10 @@ impl MarketData {
                 path.display()
             )
         })?;
-        let mut writer = BufWriter::new(file);
+        let writer = BufWriter::new(file);
         let settings = scrub_snapshot_settings(self.settings.as_ref());
         let snapshot = MarketDataSnapshot {
-            version: MARKET_DATA_SNAPSHOT_VERSION,
+            content_hash: self.content_hash().to_string(),
             generated_at: Utc::now(),
             tickers: self.tickers.as_ref().clone(),
             unique_dates: self.unique_dates.as_ref().clone(),
@@ -381,11 +720,42 @@ impl MarketData {
                     )
                 })
                 .collect(),
-            ticker_expense_map: self.ticker_expense_map.as_ref().clone(),
+            ticker_expense_map: self.auxiliary.ticker_expense_map.clone(),
+            ticker_distributions: self.auxiliary.ticker_distributions.clone(),
+            ticker_earnings: self.auxiliary.ticker_earnings.clone(),
+            ticker_short_interest: self.auxiliary.ticker_short_interest.clone(),
+            ticker_sentiment: self.auxiliary.ticker_sentiment.clone(),
+            universe_membership: self.auxiliary.universe_membership.clone(),
+            auxiliary_series: self
+                .auxiliary
+                .auxiliary_series
+                .iter()
+                .map(|(ticker, series)| (ticker.clone(), series.as_ref().clone()))
+                .collect(),
+            economic_calendar: self.auxiliary.economic_calendar.clone(),
             settings,
         };
-        bincode::serialize_into(&mut writer, &snapshot)
-            .context("Failed to serialize market data snapshot")?;
+        let write = |mut writer: &mut dyn Write| -> Result<()> {
+            bincode::serialize_into(&mut writer, &MARKET_DATA_SNAPSHOT_VERSION)
+                .context("Failed to serialize market data snapshot header")?;
+            bincode::serialize_into(&mut writer, &snapshot)
+                .context("Failed to serialize market data snapshot")
+        };
+        let mut writer = match compression_level {
+            Some(level) => {
+                let mut encoder = zstd::Encoder::new(writer, level)
+                    .context("Failed to start compressing market data snapshot")?;
+                write(&mut encoder)?;
+                encoder
+                    .finish()
+                    .context("Failed to finish compressing market data snapshot")?
+            }
+            None => {
+                let mut writer = writer;
+                write(&mut writer)?;
+                writer
+            }
+        };
         writer
             .flush()
             .context("Failed to flush market data snapshot to disk")?;
@@ -398,7 +768,7 @@ impl MarketData {
         all_candles: Vec<Candle>,
         candles_by_ticker_indices: HashMap<String, Vec<usize>>,
         templates: HashMap<String, StrategyTemplate>,
-        ticker_expense_map: HashMap<String, f64>,
+        auxiliary: AuxiliaryData,
         settings: HashMap<String, String>,
     ) -> Result<Self> {
         if tickers.is_empty() || unique_dates.is_empty() || all_candles.is_empty() {
@@ -418,8 +788,9 @@ impl MarketData {
             tickers: Arc::new(tickers),
             candles_by_ticker_indices: Arc::new(candles_by_ticker_indices),
             templates: Arc::new(templates),
-            ticker_expense_map: Arc::new(ticker_expense_map),
+            auxiliary: Arc::new(auxiliary),
             settings: Arc::new(settings),
+            content_hash: Arc::default(),
         })
     }
 
@@ -457,8 +828,18 @@ impl MarketData {
         Arc::clone(&self.tickers)
     }
 
-    pub fn ticker_expense_map_arc(&self) -> Arc<HashMap<String, f64>> {
-        Arc::clone(&self.ticker_expense_map)
+    pub fn auxiliary_data(&self) -> &AuxiliaryData {
+        self.auxiliary.as_ref()
+    }
+
+    pub fn auxiliary_data_arc(&self) -> Arc<AuxiliaryData> {
+        Arc::clone(&self.auxiliary)
+    }
+
+    /// Series of the `VOLATILITY_INDEX_TICKER` auxiliary ticker, when it was loaded.
+    pub fn volatility_index(&self) -> Option<Arc<AuxiliarySeries>> {
+        let ticker = volatility_index_ticker(self.settings()).ok()?;
+        self.auxiliary.auxiliary_series.get(&ticker).cloned()
     }
 
     pub fn settings(&self) -> &HashMap<String, String> {
@@ -506,6 +887,19 @@ impl MarketData {
         self.templates.get(template_id).cloned()
     }
 
+    /// Identifies the data backtests and optimization scores are computed from; computed once
+    /// on first use. Settings and templates are not part of it.
+    pub fn content_hash(&self) -> &str {
+        self.content_hash
+            .get_or_init(|| compute_content_hash(&self.tickers, &self.all_candles, &self.auxiliary))
+    }
+
+    /// Replaces the settings, e.g. with runtime setting layers applied on top.
+    pub fn with_settings(mut self, settings: HashMap<String, String>) -> Self {
+        self.settings = Arc::new(settings);
+        self
+    }
+
     pub fn setting_value(&self, setting_key: &str) -> Option<&str> {
         self.settings
             .as_ref()
@@ -519,7 +913,7 @@ impl MarketData {
         tickers: Vec<String>,
         candles: Vec<Candle>,
         templates: Arc<HashMap<String, StrategyTemplate>>,
-        ticker_expense_map: HashMap<String, f64>,
+        auxiliary: AuxiliaryData,
         settings: Arc<HashMap<String, String>>,
     ) -> Result<Self> {
         if tickers.is_empty() {
@@ -553,11 +947,20 @@ impl MarketData {
             tickers: Arc::new(tickers),
             candles_by_ticker_indices: Arc::new(candles_by_ticker_indices),
             templates,
-            ticker_expense_map: Arc::new(ticker_expense_map),
+            auxiliary: Arc::new(auxiliary),
             settings,
+            content_hash: Arc::default(),
         })
     }
 
+    /// Copy with every candle replaced by `map`, which must keep the candle's ticker and date.
+    pub fn with_mapped_candles(&self, map: impl FnMut(&Candle) -> Candle) -> Self {
+        let mut mapped = self.clone();
+        mapped.all_candles = Arc::new(self.all_candles.iter().map(map).collect());
+        mapped.content_hash = Arc::default();
+        mapped
+    }
+
     pub fn restrict_to_tickers(self, allowed_tickers: &HashSet<String>) -> Result<Self> {
         if allowed_tickers.is_empty() {
             return Err(anyhow!(
@@ -569,7 +972,7 @@ impl MarketData {
             all_candles,
             tickers,
             templates,
-            ticker_expense_map,
+            auxiliary,
             settings,
             ..
         } = self;
@@ -602,18 +1005,16 @@ impl MarketData {
             ));
         }
 
-        let filtered_expense_map: HashMap<String, f64> = ticker_expense_map
-            .as_ref()
-            .iter()
-            .filter(|(ticker, _)| allowed_intersection.contains(*ticker))
-            .map(|(ticker, value)| (ticker.clone(), *value))
-            .collect();
+        let mut auxiliary = Arc::unwrap_or_clone(auxiliary);
+        auxiliary
+            .ticker_expense_map
+            .retain(|ticker, _| allowed_intersection.contains(ticker));
 
         Self::rebuild_from_filtered_components(
             filtered_tickers,
             filtered_candles,
             templates,
-            filtered_expense_map,
+            auxiliary,
             settings,
         )
     }
@@ -653,7 +1054,7 @@ impl MarketData {
             all_candles,
             tickers,
             templates,
-            ticker_expense_map,
+            auxiliary,
             settings,
             ..
         } = self;
@@ -704,19 +1105,211 @@ impl MarketData {
             ));
         }
 
-        let filtered_expense_map: HashMap<String, f64> = ticker_expense_map
-            .as_ref()
-            .iter()
-            .filter(|(ticker, _)| remaining_ticker_set.contains(*ticker))
-            .map(|(ticker, value)| (ticker.clone(), *value))
-            .collect();
+        let mut auxiliary = Arc::unwrap_or_clone(auxiliary);
+        auxiliary
+            .ticker_expense_map
+            .retain(|ticker, _| remaining_ticker_set.contains(ticker));
 
         Self::rebuild_from_filtered_components(
             filtered_tickers,
             filtered_candles,
             templates,
-            filtered_expense_map,
+            auxiliary,
             settings,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_content_hash, drop_adjusted_distributions, MarketData, ZSTD_MAGIC};
+    use crate::models::{
+        AuxiliaryData, AuxiliarySeries, Candle, EconomicCalendar, EconomicEventKind,
+        MembershipInterval, SentimentPoint, ShortInterestPoint, TickerDistribution,
+    };
+    use crate::optimizer_status::OptimizerStatus;
+    use crate::strategy::registry_template;
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn snapshots_round_trip_with_and_without_compression() {
+        let candles: Vec<Candle> = (0..200)
+            .map(|day| Candle {
+                ticker: "AAA".to_string(),
+                date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day),
+                open: 10.0,
+                high: 11.0,
+                low: 9.0,
+                close: 10.0 + (day % 7) as f64 * 0.1,
+                unadjusted_close: None,
+                volume_shares: 1_000,
+            })
+            .collect();
+        let unique_dates = candles.iter().map(|candle| candle.date).collect();
+        let template = registry_template("weighted_momentum").unwrap();
+        let vix: Vec<Candle> = candles
+            .iter()
+            .map(|candle| Candle {
+                ticker: "VIX".to_string(),
+                ..candle.clone()
+            })
+            .collect();
+        let market_data = MarketData::from_components(
+            vec!["AAA".to_string()],
+            unique_dates,
+            candles.clone(),
+            MarketData::build_candle_index(&candles),
+            HashMap::from([(template.id.clone(), template)]),
+            AuxiliaryData {
+                auxiliary_series: HashMap::from([(
+                    "VIX".to_string(),
+                    Arc::new(AuxiliarySeries::from_candles(&vix)),
+                )]),
+                economic_calendar: EconomicCalendar::from_events(&[(
+                    EconomicEventKind::Fomc,
+                    NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(),
+                )]),
+                ..AuxiliaryData::default()
+            },
+            HashMap::new(),
+        )
+        .unwrap();
+
+        let dir = std::env::temp_dir();
+        let id = uuid::Uuid::new_v4();
+        let compressed = dir.join(format!("snapshot-{}.zst.bin", id));
+        let plain = dir.join(format!("snapshot-{}.bin", id));
+        market_data.save_to_file(&compressed, Some(3)).unwrap();
+        market_data.save_to_file(&plain, None).unwrap();
+        let compressed_bytes = std::fs::read(&compressed).unwrap();
+        assert!(compressed_bytes.starts_with(&ZSTD_MAGIC));
+        assert!(compressed_bytes.len() < std::fs::metadata(&plain).unwrap().len() as usize);
+
+        let status = OptimizerStatus::new();
+        for path in [&compressed, &plain] {
+            let loaded = MarketData::load_from_file(path, &status).unwrap();
+            assert_eq!(loaded.all_candles().len(), 200);
+            assert_eq!(loaded.volatility_index().unwrap().len(), 200);
+            assert_eq!(loaded.auxiliary_data().economic_calendar.len(), 1);
+            assert_eq!(loaded.content_hash(), market_data.content_hash());
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn distributions_of_adjusted_tickers_are_dropped() {
+        let candle = |ticker: &str, unadjusted_close| Candle {
+            ticker: ticker.to_string(),
+            date: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            open: 10.0,
+            high: 11.0,
+            low: 9.0,
+            close: 10.0,
+            unadjusted_close,
+            volume_shares: 1_000,
+        };
+        let candles = vec![
+            candle("RAW", Some(10.0)),
+            candle("ADJ", Some(10.4)),
+            candle("NONE", None),
+        ];
+        let schedule = vec![TickerDistribution {
+            ex_date: candles[0].date,
+            amount: 0.4,
+        }];
+        let mut distributions = ["RAW", "ADJ", "NONE"]
+            .map(|ticker| (ticker.to_string(), schedule.clone()))
+            .into_iter()
+            .collect();
+        drop_adjusted_distributions(&mut distributions, &candles);
+        let mut kept: Vec<&String> = distributions.keys().collect();
+        kept.sort();
+        assert_eq!(kept, ["NONE", "RAW"]);
+    }
+
+    #[test]
+    fn content_hash_tracks_the_loaded_data_only() {
+        let tickers = vec!["AAA".to_string()];
+        let mut candles = vec![Candle {
+            ticker: "AAA".to_string(),
+            date: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            open: 10.0,
+            high: 11.0,
+            low: 9.0,
+            close: 10.5,
+            unadjusted_close: None,
+            volume_shares: 1_000,
+        }];
+        let mut data = AuxiliaryData {
+            ticker_expense_map: HashMap::from([("AAA".to_string(), 0.001)]),
+            ..AuxiliaryData::default()
+        };
+        let hash = compute_content_hash(&tickers, &candles, &data);
+        assert_eq!(hash.len(), 64);
+
+        data.ticker_distributions.insert(
+            "BBB".to_string(),
+            vec![TickerDistribution {
+                ex_date: candles[0].date,
+                amount: 0.5,
+            }],
+        );
+        assert_eq!(compute_content_hash(&tickers, &candles, &data), hash);
+
+        data.ticker_earnings
+            .insert("AAA".to_string(), vec![candles[0].date]);
+        let with_earnings = compute_content_hash(&tickers, &candles, &data);
+        assert_ne!(with_earnings, hash);
+        data.ticker_earnings.clear();
+
+        data.ticker_short_interest.insert(
+            "AAA".to_string(),
+            vec![ShortInterestPoint {
+                date: candles[0].date,
+                short_interest_ratio: Some(0.12),
+                borrow_fee_rate: None,
+            }],
+        );
+        assert_ne!(compute_content_hash(&tickers, &candles, &data), hash);
+        data.ticker_short_interest.clear();
+
+        data.ticker_sentiment.insert(
+            "AAA".to_string(),
+            vec![SentimentPoint {
+                date: candles[0].date,
+                score: -0.4,
+            }],
+        );
+        assert_ne!(compute_content_hash(&tickers, &candles, &data), hash);
+        data.ticker_sentiment.clear();
+
+        data.universe_membership.insert(
+            "AAA".to_string(),
+            vec![MembershipInterval {
+                start: candles[0].date,
+                end: None,
+            }],
+        );
+        assert_ne!(compute_content_hash(&tickers, &candles, &data), hash);
+        data.universe_membership.clear();
+
+        data.auxiliary_series.insert(
+            "VIX".to_string(),
+            Arc::new(AuxiliarySeries::from_candles(&candles)),
+        );
+        assert_ne!(compute_content_hash(&tickers, &candles, &data), hash);
+        data.auxiliary_series.clear();
+
+        data.economic_calendar = EconomicCalendar::from_events(&[(
+            EconomicEventKind::Cpi,
+            candles[0].date.date_naive(),
+        )]);
+        assert_ne!(compute_content_hash(&tickers, &candles, &data), hash);
+        data.economic_calendar = EconomicCalendar::default();
+
+        candles[0].close = 10.6;
+        assert_ne!(compute_content_hash(&tickers, &candles, &data), hash);
+    }
+}
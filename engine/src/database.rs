@@ -1,68 +1,256 @@
+use crate::alerts::SkipOccurrence;
+use crate::blackout::BlackoutConfig;
+use crate::cancellation::CancellationToken;
+use crate::commands::signal_stats::SignalStats;
+use crate::config::AccountRiskOverrides;
+use crate::engine::AccountStateSnapshot;
+use crate::fx::{normalize_currency, AccountFx, FxRates};
 use crate::models::*;
+use crate::paper_broker::PaperOrder;
+use crate::performance::{PerformanceCalculator, SnapshotResolution};
+use crate::storage::VerificationMetrics;
 use aes_gcm::aead::Aead;
 use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
 use anyhow::{anyhow, Context, Result};
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 use chrono::{DateTime, NaiveDate, Utc};
+use deadpool_postgres::{Client as PooledClient, Manager, ManagerConfig, Pool, RecyclingMethod};
 use hex;
 use log::{error, warn};
 use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::pin;
 use std::str::FromStr;
-use tokio_postgres::{Client, NoTls, Row};
+use std::time::Duration;
+use tokio_postgres::binary_copy::BinaryCopyInWriter;
+use tokio_postgres::types::{ToSql, Type};
+use tokio_postgres::{NoTls, Row};
 use uuid::Uuid;
 
 // Limit per-transaction inserts to keep massive signal batches manageable.
 const SIGNAL_INSERT_CHUNK_SIZE: usize = 500_000;
+// Backtest trades are staged in batches of this size before the result is published.
+const BACKTEST_TRADE_BATCH_SIZE: usize = 5_000;
+const DATABASE_POOL_SIZE: usize = 8;
+const DATABASE_RETRY_ATTEMPTS: u32 = 3;
+const DATABASE_RETRY_DELAY: Duration = Duration::from_secs(1);
 const ENCRYPTION_PREFIX: &str = "enc:v1:";
+const ENCRYPTION_PREFIX_V2: &str = "enc:v2:";
 const ENCRYPTION_IV_LENGTH: usize = 12;
-const DATABASE_KEY_ENV_VAR: &str = "DATABASE_KEY";
+const ENCRYPTION_TAG_LENGTH: usize = 16;
+const ENCRYPTION_KEY_ID_BYTES: usize = 8;
+pub const DATABASE_KEY_ENV_VAR: &str = "DATABASE_KEY";
+/// Columns holding values encrypted with `DATABASE_KEY`, as (table, id column, value column).
+const ENCRYPTED_COLUMNS: [(&str, &str, &str); 3] = [
+    ("settings", "setting_key", "value"),
+    ("accounts", "id", "api_key"),
+    ("accounts", "id", "api_secret"),
+];
 
 pub struct TradeReconciliationCandidate {
     pub trade: Trade,
     pub account_id: String,
 }
 
+/// A row of the `jobs` queue claimed by `engine worker`.
+pub struct ClaimedJob {
+    pub id: String,
+    pub command: String,
+    pub payload: String,
+}
+
+pub enum JobOutcome<'a> {
+    Succeeded,
+    Failed(&'a str),
+    Cancelled,
+}
+
+pub struct JournalTrade {
+    pub trade: Trade,
+    pub strategy_name: String,
+    pub close_reason: Option<String>,
+}
+
 pub struct BacktestCacheEntry {
     pub id: String,
     pub template_id: String,
     pub parameters: HashMap<String, f64>,
     pub calmar_ratio: f64,
+    pub sharpe_ratio: f64,
+    pub cagr: f64,
     pub verify_complete: bool,
     pub balance_training_complete: bool,
     pub balance_validation_complete: bool,
 }
 
+/// Stored backtest result without its payload, for retention decisions.
+pub struct BacktestResultSummary {
+    pub id: String,
+    pub strategy_id: String,
+    pub strategy_name: String,
+    pub strategy_active: bool,
+    pub ticker_scope: String,
+    pub period_months: i32,
+    pub end_date: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub trade_count: i64,
+}
+
+pub struct TickerActivity {
+    pub symbol: String,
+    pub candle_count: i64,
+    pub has_signals: bool,
+    pub has_trades: bool,
+}
+
 pub struct LightgbmModelRecord {
     pub id: String,
     pub name: String,
     pub tree_text: String,
 }
 
+pub struct NeuralNetModelRecord {
+    pub id: String,
+    pub name: String,
+    pub weights_json: String,
+}
+
+/// Output of `train-nn`, stored together with its `nn_<id>` template and default strategy.
+pub struct NewNeuralNetModel {
+    pub id: String,
+    pub name: String,
+    pub weights_json: String,
+    pub hyperparameters: Value,
+    pub train_dataset_stats: Value,
+    pub validation_dataset_stats: Value,
+    pub validation_metrics: Option<Value>,
+    pub trained_at: DateTime<Utc>,
+}
+
+/// Pooled PostgreSQL access. Broken connections are replaced by the pool, and queries or
+/// transactional batches that fail with a transient error are retried on a fresh connection.
 pub struct Database {
-    client: Client,
+    pool: Pool,
+    cancellation: CancellationToken,
 }
 
 impl Database {
     pub async fn new<S: AsRef<str>>(database_url: S) -> Result<Self> {
         let database_url = database_url.as_ref().to_string();
-        let (client, connection) = tokio_postgres::connect(&database_url, NoTls)
+        let config = tokio_postgres::Config::from_str(&database_url)
+            .with_context(|| format!("invalid PostgreSQL URL {}", database_url))?;
+        let manager = Manager::from_config(
+            config,
+            NoTls,
+            ManagerConfig {
+                recycling_method: RecyclingMethod::Fast,
+            },
+        );
+        let pool = Pool::builder(manager)
+            .max_size(DATABASE_POOL_SIZE)
+            .build()
+            .context("failed to build PostgreSQL connection pool")?;
+
+        let database = Self {
+            pool,
+            cancellation: CancellationToken::default(),
+        };
+        // Connect eagerly so an unreachable database fails here rather than on first use.
+        let _client = database
+            .client()
             .await
             .with_context(|| format!("failed to connect to PostgreSQL at {}", database_url))?;
+        Ok(database)
+    }
+
+    /// Makes batched writes stop between batches, before committing, once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    async fn client(&self) -> Result<PooledClient> {
+        Ok(self.pool.get().await?)
+    }
+
+    /// Runs a read or an idempotent write again on transient connection failures, up to
+    /// `DATABASE_RETRY_ATTEMPTS`.
+    async fn with_retry<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.retry_on(operation, is_transient_error).await
+    }
+
+    /// Runs a write that must not be applied twice again only when it cannot have taken
+    /// effect: the connection was never checked out or the server rolled it back. A connection
+    /// lost mid-statement may have committed, so that error is returned.
+    async fn with_write_retry<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.retry_on(operation, is_unapplied_error).await
+    }
 
-        tokio::spawn(async move {
-            if let Err(err) = connection.await {
-                error!("PostgreSQL connection error: {}", err);
+    async fn retry_on<T, F, Fut>(
+        &self,
+        mut operation: F,
+        retryable: fn(&anyhow::Error) -> bool,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(err) if attempt < DATABASE_RETRY_ATTEMPTS && retryable(&err) => {
+                    warn!(
+                        "Database attempt {}/{} failed: {}. Retrying on a fresh connection.",
+                        attempt, DATABASE_RETRY_ATTEMPTS, err
+                    );
+                    tokio::time::sleep(DATABASE_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                result => return result,
             }
-        });
+        }
+    }
+
+    async fn query(&self, statement: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<Row>> {
+        self.with_retry(|| async { Ok(self.client().await?.query(statement, params).await?) })
+            .await
+    }
 
-        Ok(Self { client })
+    async fn query_one(&self, statement: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Row> {
+        self.with_retry(|| async { Ok(self.client().await?.query_one(statement, params).await?) })
+            .await
+    }
+
+    async fn query_opt(
+        &self,
+        statement: &str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> Result<Option<Row>> {
+        self.with_retry(|| async { Ok(self.client().await?.query_opt(statement, params).await?) })
+            .await
+    }
+
+    /// Writes are not all idempotent, so they only retry failures that left nothing applied.
+    async fn execute(&self, statement: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64> {
+        self.with_write_retry(|| async {
+            Ok(self.client().await?.execute(statement, params).await?)
+        })
+        .await
     }
 
     pub async fn get_setting_value(&self, setting_key: &str) -> Result<Option<String>> {
         let row = self
-            .client
             .query_opt(
                 "SELECT value FROM settings WHERE setting_key = $1",
                 &[&setting_key],
@@ -73,7 +261,6 @@ impl Database {
 
     pub async fn get_all_settings(&self) -> Result<HashMap<String, String>> {
         let rows = self
-            .client
             .query("SELECT setting_key, value FROM settings", &[])
             .await?;
         let mut settings = HashMap::with_capacity(rows.len());
@@ -89,7 +276,6 @@ impl Database {
 
     pub async fn get_lightgbm_models(&self) -> Result<Vec<LightgbmModelRecord>> {
         let rows = self
-            .client
             .query(
                 "SELECT id, name, tree_text
                  FROM lightgbm_models
@@ -107,6 +293,142 @@ impl Database {
             .collect())
     }
 
+    pub async fn get_neural_net_models(&self) -> Result<Vec<NeuralNetModelRecord>> {
+        let rows = self
+            .query(
+                "SELECT id, name, weights_json
+                 FROM nn_models
+                 ORDER BY updated_at DESC",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| NeuralNetModelRecord {
+                id: row.get(0),
+                name: row.get(1),
+                weights_json: row.get(2),
+            })
+            .collect())
+    }
+
+    /// Stores `model` with its `template` and an active `default_nn_<id>` strategy using
+    /// `default_parameters`, in one transaction.
+    pub async fn insert_neural_net_model(
+        &self,
+        model: &NewNeuralNetModel,
+        template: &StrategyTemplate,
+        strategy_name: &str,
+        default_parameters: &HashMap<String, f64>,
+    ) -> Result<String> {
+        let template_parameters = serde_json::to_string(&template.parameters)?;
+        let strategy_parameters = serde_json::to_string(default_parameters)?;
+        let strategy_id = format!("default_{}", template.id);
+        self.with_write_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            tx.execute(
+                "INSERT INTO nn_models
+                     (id, name, weights_json, hyperparameters, train_dataset_stats,
+                      validation_dataset_stats, validation_metrics, trained_at)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &model.id,
+                    &model.name,
+                    &model.weights_json,
+                    &model.hyperparameters,
+                    &model.train_dataset_stats,
+                    &model.validation_dataset_stats,
+                    &model.validation_metrics,
+                    &model.trained_at,
+                ],
+            )
+            .await?;
+            tx.execute(
+                "INSERT INTO templates
+                     (id, name, description, category, author, version, parameters, example_usage)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 ON CONFLICT (id) DO UPDATE SET
+                     name = EXCLUDED.name,
+                     description = EXCLUDED.description,
+                     parameters = EXCLUDED.parameters",
+                &[
+                    &template.id,
+                    &template.name,
+                    &template.description,
+                    &template.category,
+                    &template.author,
+                    &template.version,
+                    &template_parameters,
+                    &template.example_usage,
+                ],
+            )
+            .await?;
+            tx.execute(
+                "INSERT INTO strategies (id, name, template_id, parameters, status)
+                 VALUES ($1, $2, $3, $4, 'active')
+                 ON CONFLICT (id) DO NOTHING",
+                &[
+                    &strategy_id,
+                    &strategy_name,
+                    &template.id,
+                    &strategy_parameters,
+                ],
+            )
+            .await?;
+            tx.commit().await?;
+            Ok(())
+        })
+        .await?;
+        Ok(strategy_id)
+    }
+
+    /// Candle volume per tracked ticker and whether any active strategy produced a signal or
+    /// trade for it on or after `since`.
+    pub async fn get_ticker_activity(&self, since: NaiveDate) -> Result<Vec<TickerActivity>> {
+        let rows = self
+            .query(
+                "SELECT
+                    t.symbol,
+                    (SELECT COUNT(*) FROM candles c WHERE c.ticker = t.symbol) AS candle_count,
+                    EXISTS (
+                        SELECT 1
+                        FROM signals sg
+                        JOIN strategies s ON s.id = sg.strategy_id
+                        WHERE s.status = 'active' AND sg.ticker = t.symbol AND sg.date >= $1
+                    ) AS has_signals,
+                    EXISTS (
+                        SELECT 1
+                        FROM trades tr
+                        JOIN strategies s ON s.id = tr.strategy_id
+                        WHERE s.status = 'active' AND tr.ticker = t.symbol AND tr.date >= $1
+                    ) AS has_trades
+                 FROM tickers t
+                 ORDER BY t.symbol",
+                &[&since],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| TickerActivity {
+                symbol: row.get(0),
+                candle_count: row.get(1),
+                has_signals: row.get(2),
+                has_trades: row.get(3),
+            })
+            .collect())
+    }
+
+    pub async fn count_active_strategies(&self) -> Result<i64> {
+        let row = self
+            .query_one(
+                "SELECT COUNT(*) FROM strategies WHERE status = 'active'",
+                &[],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
     pub async fn insert_system_log(
         &self,
         source: &str,
@@ -117,13 +439,12 @@ impl Database {
         let created_at = Utc::now();
         let metadata_text = metadata.map(|value| value.to_string());
 
-        self.client
-            .execute(
-                "INSERT INTO system_logs (source, level, message, metadata, created_at)
+        self.execute(
+            "INSERT INTO system_logs (source, level, message, metadata, created_at)
                  VALUES ($1, $2, $3, $4, $5)",
-                &[&source, &level, &message, &metadata_text, &created_at],
-            )
-            .await?;
+            &[&source, &level, &message, &metadata_text, &created_at],
+        )
+        .await?;
 
         Ok(())
     }
@@ -176,31 +497,55 @@ impl Database {
 
         let created_at = Utc::now();
         let account_id = account_id.filter(|value| !value.trim().is_empty());
-        let tx = self.client.transaction().await?;
-
-        for skip in skips {
-            let signal_date = skip.signal_date.date_naive();
-            let action = skip.action.as_str();
-            tx.execute(
-                "INSERT INTO account_signal_skips (strategy_id, account_id, ticker, signal_date, action, source, reason, details, created_at)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        self.with_write_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+
+            let sink = tx
+                .copy_in(
+                    "COPY account_signal_skips (strategy_id, account_id, ticker, signal_date, action, source, reason, details, created_at)
+                     FROM STDIN BINARY",
+                )
+                .await?;
+            let writer = BinaryCopyInWriter::new(
+                sink,
                 &[
-                    &strategy_id,
-                    &account_id,
-                    &skip.ticker,
-                    &signal_date,
-                    &action,
-                    &source,
-                    &skip.reason,
-                    &skip.details,
-                    &created_at,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::DATE,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::TIMESTAMPTZ,
                 ],
-            )
-            .await?;
-        }
+            );
+            let mut writer = pin!(writer);
+            for skip in skips {
+                let signal_date = skip.signal_date.date_naive();
+                let action = skip.action.as_str();
+                writer
+                    .as_mut()
+                    .write(&[
+                        &strategy_id,
+                        &account_id,
+                        &skip.ticker,
+                        &signal_date,
+                        &action,
+                        &source,
+                        &skip.reason,
+                        &skip.details,
+                        &created_at,
+                    ])
+                    .await?;
+            }
+            writer.finish().await?;
 
         tx.commit().await?;
         Ok(())
+        })
+        .await
     }
 
     pub async fn persist_strategy_event(
@@ -222,14 +567,14 @@ impl Database {
         }
     }
 
-    pub async fn get_all_candles(&self) -> Result<Vec<Candle>> {
+    pub async fn get_all_candles(&self, since: Option<NaiveDate>) -> Result<Vec<Candle>> {
         let rows = self
-            .client
             .query(
                 "SELECT ticker, date, open, high, low, close, unadjusted_close, volume_shares
                  FROM candles
+                 WHERE $1::date IS NULL OR date >= $1
                  ORDER BY date, ticker",
-                &[],
+                &[&since],
             )
             .await?;
 
@@ -251,6 +596,47 @@ impl Database {
         Ok(candles)
     }
 
+    /// Moves the candles of `rows` into `candle_quarantine` with the reason they were flagged,
+    /// returning how many were moved.
+    pub async fn quarantine_candles(&self, rows: &[(String, NaiveDate, &str)]) -> Result<usize> {
+        self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            let mut moved = 0usize;
+            for (ticker, date, reason) in rows {
+                moved += tx
+                    .execute(
+                        "WITH removed AS (
+                             DELETE FROM candles WHERE ticker = $1 AND date = $2
+                             RETURNING ticker, date, open, high, low, close, unadjusted_close, volume_shares
+                         )
+                         INSERT INTO candle_quarantine (ticker, date, open, high, low, close, unadjusted_close, volume_shares, reason)
+                         SELECT ticker, date, open, high, low, close, unadjusted_close, volume_shares, $3 FROM removed
+                         ON CONFLICT (ticker, date) DO UPDATE SET
+                             open = EXCLUDED.open,
+                             high = EXCLUDED.high,
+                             low = EXCLUDED.low,
+                             close = EXCLUDED.close,
+                             unadjusted_close = EXCLUDED.unadjusted_close,
+                             volume_shares = EXCLUDED.volume_shares,
+                             reason = EXCLUDED.reason,
+                             quarantined_at = CURRENT_TIMESTAMP",
+                        &[ticker, date, reason],
+                    )
+                    .await? as usize;
+            }
+            tx.commit().await?;
+            Ok(moved)
+        })
+        .await
+    }
+
+    pub async fn get_latest_candle_date(&self) -> Result<Option<DateTime<Utc>>> {
+        let row = self.query_one("SELECT MAX(date) FROM candles", &[]).await?;
+        let date: Option<NaiveDate> = row.get(0);
+        Ok(date.map(naive_date_to_datetime))
+    }
+
     pub async fn get_candles_for_tickers(&self, symbols: &[String]) -> Result<Vec<Candle>> {
         if symbols.is_empty() {
             return Ok(Vec::new());
@@ -258,7 +644,6 @@ impl Database {
 
         let symbols_param: Vec<&str> = symbols.iter().map(|s| s.as_str()).collect();
         let rows = self
-            .client
             .query(
                 "SELECT ticker, date, open, high, low, close, unadjusted_close, volume_shares
                  FROM candles
@@ -286,113 +671,531 @@ impl Database {
         Ok(candles)
     }
 
-    pub async fn get_tickers_with_candle_counts(&self) -> Result<Vec<TickerInfo>> {
+    /// Candles for `ticker` from `since` onwards, oldest first.
+    pub async fn get_ticker_candles_since(
+        &self,
+        ticker: &str,
+        since: NaiveDate,
+    ) -> Result<Vec<Candle>> {
         let rows = self
-            .client
             .query(
-                "SELECT t.symbol, t.name, t.tradable, t.shortable, t.easy_to_borrow, t.asset_type, t.expense_ratio, t.market_cap, t.volume_usd, t.max_fluctuation_ratio, t.last_updated, t.training,
-                        COUNT(c.id) AS candle_count
-                 FROM tickers t
-                 LEFT JOIN candles c ON t.symbol = c.ticker
-                 GROUP BY t.symbol, t.name, t.tradable, t.shortable, t.easy_to_borrow, t.asset_type, t.expense_ratio, t.market_cap, t.volume_usd, t.max_fluctuation_ratio, t.last_updated, t.training
-                 ORDER BY candle_count DESC",
+                "SELECT ticker, date, open, high, low, close, unadjusted_close, volume_shares
+                 FROM candles
+                 WHERE ticker = $1 AND date >= $2
+                 ORDER BY date",
+                &[&ticker, &since],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let date: NaiveDate = row.get(1);
+                Candle {
+                    ticker: row.get(0),
+                    date: naive_date_to_datetime(date),
+                    open: row.get(2),
+                    high: row.get(3),
+                    low: row.get(4),
+                    close: row.get(5),
+                    unadjusted_close: row.get::<_, Option<f64>>(6),
+                    volume_shares: row.get(7),
+                }
+            })
+            .collect())
+    }
+
+    /// Ingested cash distribution schedules for ETF tickers.
+    pub async fn get_etf_distributions(&self) -> Result<TickerDistributionMap> {
+        let rows = self
+            .query(
+                "SELECT d.ticker, d.ex_date, d.amount
+                 FROM ticker_distributions d
+                 JOIN tickers t ON t.symbol = d.ticker
+                 WHERE t.asset_type LIKE '%etf' AND d.amount > 0
+                 ORDER BY d.ticker, d.ex_date",
                 &[],
             )
             .await?;
 
-        let mut tickers = Vec::with_capacity(rows.len());
+        let mut distributions = TickerDistributionMap::new();
         for row in rows {
-            tickers.push(TickerInfo {
-                symbol: row.get(0),
-                name: row.get(1),
-                tradable: row.get(2),
-                shortable: row.get(3),
-                easy_to_borrow: row.get(4),
-                asset_type: row.get(5),
-                expense_ratio: row.get(6),
-                market_cap: row.get(7),
-                volume_usd: row.get(8),
-                max_fluctuation_ratio: row.get(9),
-                last_updated: row.get(10),
-                training: row.get(11),
-                candle_count: Some(row.get(12)),
-            });
+            let ex_date: NaiveDate = row.get(1);
+            distributions
+                .entry(row.get(0))
+                .or_default()
+                .push(TickerDistribution {
+                    ex_date: naive_date_to_datetime(ex_date),
+                    amount: row.get(2),
+                });
         }
-
-        Ok(tickers)
+        Ok(distributions)
     }
 
-    pub async fn get_ticker_metadata(
+    /// Upserts `(ticker, ex_date, amount)` rows of known tickers and returns how many were
+    /// stored.
+    pub async fn upsert_ticker_distributions(
         &self,
-        symbols: &[String],
-    ) -> Result<HashMap<String, TickerInfo>> {
-        if symbols.is_empty() {
-            return Ok(HashMap::new());
+        rows: &[(String, NaiveDate, f64)],
+    ) -> Result<usize> {
+        let mut stored = 0usize;
+        for (ticker, ex_date, amount) in rows {
+            let known = self
+                .query_opt("SELECT 1 FROM tickers WHERE symbol = $1", &[ticker])
+                .await?
+                .is_some();
+            if !known {
+                continue;
+            }
+            self.execute(
+                "INSERT INTO ticker_distributions (ticker, ex_date, amount) VALUES ($1, $2, $3)
+                 ON CONFLICT (ticker, ex_date) DO UPDATE SET amount = EXCLUDED.amount",
+                &[ticker, ex_date, amount],
+            )
+            .await?;
+            stored += 1;
         }
+        Ok(stored)
+    }
 
+    /// Earnings report dates by ticker, for blackout windows around earnings.
+    pub async fn get_earnings_dates(&self) -> Result<HashMap<String, Vec<NaiveDate>>> {
         let rows = self
-            .client
             .query(
-                "SELECT symbol, name, tradable, shortable, easy_to_borrow, asset_type, expense_ratio, training
-                 FROM tickers
-                 WHERE symbol = ANY($1)",
-                &[&symbols],
+                "SELECT ticker, report_date FROM earnings ORDER BY ticker, report_date",
+                &[],
             )
             .await?;
 
-        let mut map = HashMap::with_capacity(rows.len());
+        let mut earnings: HashMap<String, Vec<NaiveDate>> = HashMap::new();
         for row in rows {
-            let symbol: String = row.get(0);
-            map.insert(
-                symbol.clone(),
-                TickerInfo {
-                    symbol,
-                    name: row.get(1),
-                    tradable: row.get(2),
-                    shortable: row.get(3),
-                    easy_to_borrow: row.get(4),
-                    asset_type: row.get(5),
-                    expense_ratio: row.get(6),
-                    market_cap: None,
-                    volume_usd: None,
-                    max_fluctuation_ratio: None,
-                    last_updated: None,
-                    candle_count: None,
-                    training: row.get(7),
-                },
-            );
+            earnings.entry(row.get(0)).or_default().push(row.get(1));
         }
+        Ok(earnings)
+    }
 
-        Ok(map)
+    /// Inserts `(ticker, report_date)` rows of known tickers and returns how many were stored
+    /// or already present.
+    pub async fn upsert_earnings_dates(&self, rows: &[(String, NaiveDate)]) -> Result<usize> {
+        let mut stored = 0usize;
+        for (ticker, date) in rows {
+            let known = self
+                .query_opt("SELECT 1 FROM tickers WHERE symbol = $1", &[ticker])
+                .await?
+                .is_some();
+            if !known {
+                continue;
+            }
+            self.execute(
+                "INSERT INTO earnings (ticker, report_date) VALUES ($1, $2)
+                 ON CONFLICT (ticker, report_date) DO NOTHING",
+                &[ticker, date],
+            )
+            .await?;
+            stored += 1;
+        }
+        Ok(stored)
     }
 
-    pub async fn ensure_ticker_exists(&self, symbol: &str) -> Result<()> {
-        self.client
-            .execute(
-                "INSERT INTO tickers (symbol, tradable, shortable, easy_to_borrow, training)
-                 VALUES ($1, false, false, false, false)
-                 ON CONFLICT (symbol) DO NOTHING",
-                &[&symbol],
+    /// `get_earnings_dates` keyed the way the engine reads dates, for the pre-earnings rule.
+    pub async fn get_ticker_earnings(&self) -> Result<TickerEarningsMap> {
+        Ok(self
+            .get_earnings_dates()
+            .await?
+            .into_iter()
+            .map(|(ticker, dates)| {
+                let dates = dates.into_iter().map(naive_date_to_datetime).collect();
+                (ticker, dates)
+            })
+            .collect())
+    }
+
+    /// Short interest and borrow fee reports by ticker, sorted by date.
+    pub async fn get_ticker_short_interest(&self) -> Result<TickerShortInterestMap> {
+        let rows = self
+            .query(
+                "SELECT ticker, report_date, short_interest_ratio, borrow_fee_rate
+                 FROM short_interest ORDER BY ticker, report_date",
+                &[],
             )
             .await?;
 
-        Ok(())
+        let mut short_interest = TickerShortInterestMap::new();
+        for row in rows {
+            short_interest
+                .entry(row.get(0))
+                .or_default()
+                .push(ShortInterestPoint {
+                    date: naive_date_to_datetime(row.get(1)),
+                    short_interest_ratio: row.get(2),
+                    borrow_fee_rate: row.get(3),
+                });
+        }
+        Ok(short_interest)
     }
 
-    pub async fn get_template(&self, template_id: &str) -> Result<Option<StrategyTemplate>> {
-        let row = self
-            .client
-            .query_opt(
-                "SELECT id, name, description, category, author, version, local_optimization_version, parameters, example_usage, created_at
-                 FROM templates
-                 WHERE id = $1",
-                &[&template_id],
+    /// Upserts `(ticker, report_date, short_interest_ratio, borrow_fee_rate)` rows of known
+    /// tickers and returns how many were stored.
+    pub async fn upsert_short_interest(
+        &self,
+        rows: &[(String, NaiveDate, Option<f64>, Option<f64>)],
+    ) -> Result<usize> {
+        let mut stored = 0usize;
+        for (ticker, date, short_interest_ratio, borrow_fee_rate) in rows {
+            let known = self
+                .query_opt("SELECT 1 FROM tickers WHERE symbol = $1", &[ticker])
+                .await?
+                .is_some();
+            if !known {
+                continue;
+            }
+            self.execute(
+                "INSERT INTO short_interest (ticker, report_date, short_interest_ratio, borrow_fee_rate)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (ticker, report_date) DO UPDATE SET
+                     short_interest_ratio = EXCLUDED.short_interest_ratio,
+                     borrow_fee_rate = EXCLUDED.borrow_fee_rate",
+                &[ticker, date, short_interest_ratio, borrow_fee_rate],
             )
             .await?;
+            stored += 1;
+        }
+        Ok(stored)
+    }
 
-        let Some(row) = row else {
-            return Ok(None);
-        };
+    /// Daily news sentiment scores by ticker, sorted by date.
+    pub async fn get_ticker_sentiment(&self) -> Result<TickerSentimentMap> {
+        let rows = self
+            .query(
+                "SELECT ticker, date, score FROM ticker_sentiment ORDER BY ticker, date",
+                &[],
+            )
+            .await?;
+
+        let mut sentiment = TickerSentimentMap::new();
+        for row in rows {
+            sentiment
+                .entry(row.get(0))
+                .or_default()
+                .push(SentimentPoint {
+                    date: naive_date_to_datetime(row.get(1)),
+                    score: row.get(2),
+                });
+        }
+        Ok(sentiment)
+    }
+
+    /// Upserts `(ticker, date, score)` rows of known tickers and returns how many were stored.
+    pub async fn upsert_ticker_sentiment(
+        &self,
+        rows: &[(String, NaiveDate, f64)],
+    ) -> Result<usize> {
+        let mut stored = 0usize;
+        for (ticker, date, score) in rows {
+            let known = self
+                .query_opt("SELECT 1 FROM tickers WHERE symbol = $1", &[ticker])
+                .await?
+                .is_some();
+            if !known {
+                continue;
+            }
+            self.execute(
+                "INSERT INTO ticker_sentiment (ticker, date, score) VALUES ($1, $2, $3)
+                 ON CONFLICT (ticker, date) DO UPDATE SET score = EXCLUDED.score",
+                &[ticker, date, score],
+            )
+            .await?;
+            stored += 1;
+        }
+        Ok(stored)
+    }
+
+    /// FOMC and CPI release days; rows of unknown event types are ignored.
+    pub async fn get_economic_events(&self) -> Result<Vec<(EconomicEventKind, NaiveDate)>> {
+        let rows = self
+            .query(
+                "SELECT event_type, event_date FROM economic_events ORDER BY event_date",
+                &[],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| {
+                let kind: String = row.get(0);
+                Some((kind.parse().ok()?, row.get(1)))
+            })
+            .collect())
+    }
+
+    /// Inserts `(kind, event_date)` rows and returns how many were new.
+    pub async fn upsert_economic_events(
+        &self,
+        rows: &[(EconomicEventKind, NaiveDate)],
+    ) -> Result<usize> {
+        let mut stored = 0usize;
+        for (kind, date) in rows {
+            stored += self
+                .execute(
+                    "INSERT INTO economic_events (event_type, event_date) VALUES ($1, $2)
+                     ON CONFLICT (event_type, event_date) DO NOTHING",
+                    &[&kind.as_str(), date],
+                )
+                .await? as usize;
+        }
+        Ok(stored)
+    }
+
+    /// Membership intervals of `universe` by ticker, for point-in-time backtest universes.
+    pub async fn get_universe_membership(&self, universe: &str) -> Result<UniverseMembershipMap> {
+        let rows = self
+            .query(
+                "SELECT ticker, start_date, end_date FROM universe_memberships
+                 WHERE universe = $1 ORDER BY ticker, start_date",
+                &[&universe],
+            )
+            .await?;
+
+        let mut membership = UniverseMembershipMap::new();
+        for row in rows {
+            let end: Option<NaiveDate> = row.get(2);
+            membership
+                .entry(row.get(0))
+                .or_default()
+                .push(MembershipInterval {
+                    start: naive_date_to_datetime(row.get(1)),
+                    end: end.map(naive_date_to_datetime),
+                });
+        }
+        Ok(membership)
+    }
+
+    /// Replaces the memberships of `universe` with `(ticker, start, end)` rows of known tickers
+    /// and returns how many were stored.
+    pub async fn replace_universe_memberships(
+        &self,
+        universe: &str,
+        rows: &[(String, NaiveDate, Option<NaiveDate>)],
+    ) -> Result<usize> {
+        self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            let symbols: Vec<&str> = rows.iter().map(|(ticker, _, _)| ticker.as_str()).collect();
+            let known: HashSet<String> = tx
+                .query(
+                    "SELECT symbol FROM tickers WHERE symbol = ANY($1)",
+                    &[&symbols],
+                )
+                .await?
+                .iter()
+                .map(|row| row.get(0))
+                .collect();
+
+            tx.execute(
+                "DELETE FROM universe_memberships WHERE universe = $1",
+                &[&universe],
+            )
+            .await?;
+            let mut stored = 0usize;
+            for (ticker, start, end) in rows {
+                if !known.contains(ticker) {
+                    continue;
+                }
+                tx.execute(
+                    "INSERT INTO universe_memberships (universe, ticker, start_date, end_date)
+                     VALUES ($1, $2, $3, $4)
+                     ON CONFLICT (universe, ticker, start_date) DO UPDATE SET end_date = EXCLUDED.end_date",
+                    &[&universe, ticker, start, end],
+                )
+                .await?;
+                stored += 1;
+            }
+            tx.commit().await?;
+            Ok(stored)
+        })
+        .await
+    }
+
+    /// Registers `ticker` as a non-tradable auxiliary series and upserts its candles.
+    pub async fn upsert_auxiliary_candles(&self, ticker: &str, candles: &[Candle]) -> Result<()> {
+        // A single upsert cannot touch the same row twice, so keep the last candle per date.
+        let mut dates = HashSet::with_capacity(candles.len());
+        let mut rows: Vec<(NaiveDate, &Candle)> = candles
+            .iter()
+            .rev()
+            .filter_map(|candle| {
+                let date = candle.date.date_naive();
+                dates.insert(date).then_some((date, candle))
+            })
+            .collect();
+        rows.reverse();
+
+        self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            tx.execute(
+                "INSERT INTO tickers (symbol, tradable, shortable, easy_to_borrow, training, auxiliary)
+                 VALUES ($1, false, false, false, false, true)
+                 ON CONFLICT (symbol) DO UPDATE SET tradable = false, training = false, auxiliary = true",
+                &[&ticker],
+            )
+            .await?;
+            // COPY cannot resolve conflicts, so stage the candles and upsert from the staging table.
+            tx.batch_execute(
+                "CREATE TEMP TABLE candles_staging
+                     (LIKE candles INCLUDING DEFAULTS) ON COMMIT DROP",
+            )
+            .await?;
+            let sink = tx
+                .copy_in(
+                    "COPY candles_staging (ticker, date, open, high, low, close, volume_shares)
+                     FROM STDIN BINARY",
+                )
+                .await?;
+            let writer = BinaryCopyInWriter::new(
+                sink,
+                &[
+                    Type::TEXT,
+                    Type::DATE,
+                    Type::FLOAT8,
+                    Type::FLOAT8,
+                    Type::FLOAT8,
+                    Type::FLOAT8,
+                    Type::INT8,
+                ],
+            );
+            let mut writer = pin!(writer);
+            for (date, candle) in &rows {
+                writer
+                    .as_mut()
+                    .write(&[
+                        &ticker,
+                        date,
+                        &candle.open,
+                        &candle.high,
+                        &candle.low,
+                        &candle.close,
+                        &candle.volume_shares,
+                    ])
+                    .await?;
+            }
+            writer.finish().await?;
+            tx.execute(
+                "INSERT INTO candles (ticker, date, open, high, low, close, volume_shares)
+                 SELECT ticker, date, open, high, low, close, volume_shares
+                 FROM candles_staging
+                 ON CONFLICT (ticker, date) DO UPDATE SET open = EXCLUDED.open, high = EXCLUDED.high,
+                    low = EXCLUDED.low, close = EXCLUDED.close, volume_shares = EXCLUDED.volume_shares",
+                &[],
+            )
+            .await?;
+            tx.commit().await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_tickers_with_candle_counts(&self) -> Result<Vec<TickerInfo>> {
+        let rows = self.query(
+                "SELECT t.symbol, t.name, t.tradable, t.shortable, t.easy_to_borrow, t.asset_type, t.expense_ratio, t.market_cap, t.volume_usd, t.max_fluctuation_ratio, t.last_updated, t.training,
+                        COUNT(c.id) AS candle_count, t.auxiliary
+                 FROM tickers t
+                 LEFT JOIN candles c ON t.symbol = c.ticker
+                 GROUP BY t.symbol, t.name, t.tradable, t.shortable, t.easy_to_borrow, t.asset_type, t.expense_ratio, t.market_cap, t.volume_usd, t.max_fluctuation_ratio, t.last_updated, t.training, t.auxiliary
+                 ORDER BY candle_count DESC",
+                &[],
+            )
+            .await?;
+
+        let mut tickers = Vec::with_capacity(rows.len());
+        for row in rows {
+            tickers.push(TickerInfo {
+                symbol: row.get(0),
+                name: row.get(1),
+                tradable: row.get(2),
+                shortable: row.get(3),
+                easy_to_borrow: row.get(4),
+                asset_type: row.get(5),
+                expense_ratio: row.get(6),
+                market_cap: row.get(7),
+                volume_usd: row.get(8),
+                max_fluctuation_ratio: row.get(9),
+                last_updated: row.get(10),
+                training: row.get(11),
+                candle_count: Some(row.get(12)),
+                auxiliary: row.get(13),
+            });
+        }
+
+        Ok(tickers)
+    }
+
+    pub async fn get_ticker_metadata(
+        &self,
+        symbols: &[String],
+    ) -> Result<HashMap<String, TickerInfo>> {
+        if symbols.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = self.query(
+                "SELECT symbol, name, tradable, shortable, easy_to_borrow, asset_type, expense_ratio, training, auxiliary
+                 FROM tickers
+                 WHERE symbol = ANY($1)",
+                &[&symbols],
+            )
+            .await?;
+
+        let mut map = HashMap::with_capacity(rows.len());
+        for row in rows {
+            let symbol: String = row.get(0);
+            map.insert(
+                symbol.clone(),
+                TickerInfo {
+                    symbol,
+                    name: row.get(1),
+                    tradable: row.get(2),
+                    shortable: row.get(3),
+                    easy_to_borrow: row.get(4),
+                    asset_type: row.get(5),
+                    expense_ratio: row.get(6),
+                    market_cap: None,
+                    volume_usd: None,
+                    max_fluctuation_ratio: None,
+                    last_updated: None,
+                    candle_count: None,
+                    training: row.get(7),
+                    auxiliary: row.get(8),
+                },
+            );
+        }
+
+        Ok(map)
+    }
+
+    pub async fn ensure_ticker_exists(&self, symbol: &str) -> Result<()> {
+        self.execute(
+            "INSERT INTO tickers (symbol, tradable, shortable, easy_to_borrow, training)
+                 VALUES ($1, false, false, false, false)
+                 ON CONFLICT (symbol) DO NOTHING",
+            &[&symbol],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_template(&self, template_id: &str) -> Result<Option<StrategyTemplate>> {
+        let row = self.query_opt(
+                "SELECT id, name, description, category, author, version, local_optimization_version, parameters, example_usage, created_at
+                 FROM templates
+                 WHERE id = $1",
+                &[&template_id],
+            )
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
 
         let parameters_json: String = row.get(7);
         let parameters: Vec<StrategyParameter> = serde_json::from_str(&parameters_json)
@@ -418,9 +1221,7 @@ impl Database {
     }
 
     pub async fn get_all_templates(&self) -> Result<Vec<StrategyTemplate>> {
-        let rows = self
-            .client
-            .query(
+        let rows = self.query(
                 "SELECT id, name, description, category, author, version, local_optimization_version, parameters, example_usage, created_at
                  FROM templates",
                 &[],
@@ -461,12 +1262,11 @@ impl Database {
         template_id: &str,
         version: i32,
     ) -> Result<()> {
-        self.client
-            .execute(
-                "UPDATE templates SET local_optimization_version = $1 WHERE id = $2",
-                &[&version, &template_id],
-            )
-            .await?;
+        self.execute(
+            "UPDATE templates SET local_optimization_version = $1 WHERE id = $2",
+            &[&version, &template_id],
+        )
+        .await?;
         Ok(())
     }
 
@@ -475,45 +1275,48 @@ impl Database {
     /// strategies (for example `default_<template_id>`) so they can be recreated
     /// with updated parameters on next server/registry startup.
     pub async fn delete_strategy_and_related(&mut self, strategy_id: &str) -> Result<()> {
-        let tx = self.client.transaction().await?;
-
-        tx.execute(
-            "DELETE FROM signals WHERE strategy_id = $1",
-            &[&strategy_id],
-        )
-        .await?;
+        self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
 
-        tx.execute(
-            "DELETE FROM account_operations WHERE strategy_id = $1",
-            &[&strategy_id],
-        )
-        .await?;
+            tx.execute(
+                "DELETE FROM signals WHERE strategy_id = $1",
+                &[&strategy_id],
+            )
+            .await?;
 
-        tx.execute(
-            "DELETE FROM account_signal_skips WHERE strategy_id = $1",
-            &[&strategy_id],
-        )
-        .await?;
+            tx.execute(
+                "DELETE FROM account_operations WHERE strategy_id = $1",
+                &[&strategy_id],
+            )
+            .await?;
 
-        tx.execute("DELETE FROM trades WHERE strategy_id = $1", &[&strategy_id])
+            tx.execute(
+                "DELETE FROM account_signal_skips WHERE strategy_id = $1",
+                &[&strategy_id],
+            )
             .await?;
 
-        tx.execute(
-            "DELETE FROM backtest_results WHERE strategy_id = $1",
-            &[&strategy_id],
-        )
-        .await?;
+            tx.execute("DELETE FROM trades WHERE strategy_id = $1", &[&strategy_id])
+                .await?;
 
-        tx.execute("DELETE FROM strategies WHERE id = $1", &[&strategy_id])
+            tx.execute(
+                "DELETE FROM backtest_results WHERE strategy_id = $1",
+                &[&strategy_id],
+            )
             .await?;
 
-        tx.commit().await?;
-        Ok(())
+            tx.execute("DELETE FROM strategies WHERE id = $1", &[&strategy_id])
+                .await?;
+
+            tx.commit().await?;
+            Ok(())
+        })
+        .await
     }
 
     pub async fn get_active_strategies(&self) -> Result<Vec<StrategyConfig>> {
         let rows = self
-            .client
             .query(
                 "SELECT
                     s.id,
@@ -523,7 +1326,8 @@ impl Database {
                     s.parameters,
                     s.backtest_start_date,
                     COALESCE(a.excluded_tickers, '[]') AS excluded_tickers,
-                    COALESCE(a.excluded_keywords, '[]') AS excluded_keywords
+                    COALESCE(a.excluded_keywords, '[]') AS excluded_keywords,
+                    s.blackout_windows
                  FROM strategies s
                  LEFT JOIN accounts a ON s.account_id = a.id
                  WHERE s.status = 'active'
@@ -553,6 +1357,7 @@ impl Database {
                 excluded_keywords,
                 parameters,
                 backtest_start_date: row.get(5),
+                blackout: BlackoutConfig::parse(row.get::<_, Option<String>>(8).as_deref()),
             });
         }
 
@@ -561,7 +1366,6 @@ impl Database {
 
     pub async fn get_strategy_config(&self, strategy_id: &str) -> Result<Option<StrategyConfig>> {
         let row = self
-            .client
             .query_opt(
                 "SELECT
                     s.id,
@@ -569,7 +1373,8 @@ impl Database {
                     s.template_id,
                     s.account_id,
                     s.parameters,
-                    s.backtest_start_date
+                    s.backtest_start_date,
+                    s.blackout_windows
                  FROM strategies s
                  WHERE s.id = $1",
                 &[&strategy_id],
@@ -593,59 +1398,249 @@ impl Database {
             excluded_keywords: Vec::new(),
             parameters,
             backtest_start_date: row.get(5),
+            blackout: BlackoutConfig::parse(row.get::<_, Option<String>>(6).as_deref()),
         }))
     }
 
-    pub async fn get_account_credentials(
+    pub async fn get_account_ids(&self) -> Result<Vec<String>> {
+        let rows = self
+            .query("SELECT id FROM accounts ORDER BY id", &[])
+            .await?;
+        Ok(rows.into_iter().map(|row| row.get(0)).collect())
+    }
+
+    /// Stop, risk and take-profit settings of `account_id`; missing accounts have none.
+    pub async fn get_account_risk_overrides(
         &self,
         account_id: &str,
-    ) -> Result<Option<AccountCredentials>> {
+    ) -> Result<AccountRiskOverrides> {
         let row = self
-            .client
             .query_opt(
-                "SELECT id, provider, environment, api_key, api_secret
-                 FROM accounts
-                 WHERE id = $1",
+                "SELECT max_stop_ratio, max_risk_per_trade, take_profit_r_multiple FROM accounts WHERE id = $1",
                 &[&account_id],
             )
             .await?;
-
-        let Some(row) = row else {
-            return Ok(None);
-        };
-
-        let id: String = row.get(0);
-        let api_key: String = row.get(3);
-        let api_secret: String = row.get(4);
-
-        Ok(Some(AccountCredentials {
-            id: id.clone(),
-            provider: row.get(1),
-            environment: row.get(2),
-            api_key: decrypt_database_value(&api_key)
-                .with_context(|| format!("failed to decrypt api_key for account {}", id))?,
-            api_secret: decrypt_database_value(&api_secret)
-                .with_context(|| format!("failed to decrypt api_secret for account {}", id))?,
-        }))
+        Ok(row
+            .map(|row| AccountRiskOverrides::new(row.get(0), row.get(1), row.get(2)))
+            .unwrap_or_default())
     }
 
-    pub async fn update_strategy_backtest_duration(
-        &self,
-        strategy_id: &str,
-        duration_minutes: f64,
-    ) -> Result<()> {
-        self.client
-            .execute(
-                "UPDATE strategies
-                 SET last_backtest_duration_minutes = $1, updated_at = CURRENT_TIMESTAMP
-                 WHERE id = $2",
-                &[&duration_minutes, &strategy_id],
+    /// Buy quorum of every account configured as a strategy ensemble.
+    pub async fn get_account_ensemble_quorums(&self) -> Result<HashMap<String, usize>> {
+        let rows = self
+            .query(
+                "SELECT id, ensemble_quorum FROM accounts WHERE ensemble_quorum > 0",
+                &[],
             )
             .await?;
-        Ok(())
-    }
-
-    pub async fn get_latest_backtest_end_date(
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let quorum: i32 = row.get(1);
+                (row.get(0), quorum as usize)
+            })
+            .collect())
+    }
+
+    /// Every stored exchange rate.
+    pub async fn get_fx_rates(&self) -> Result<FxRates> {
+        let rows = self
+            .query(
+                "SELECT base_currency, quote_currency, date, rate FROM fx_rates",
+                &[],
+            )
+            .await?;
+        let mut rates = FxRates::default();
+        for row in rows {
+            let base: String = row.get(0);
+            let quote: String = row.get(1);
+            rates.insert(&base, &quote, row.get(2), row.get(3));
+        }
+        Ok(rates)
+    }
+
+    pub async fn upsert_fx_rate(
+        &self,
+        base: &str,
+        quote: &str,
+        date: NaiveDate,
+        rate: f64,
+    ) -> Result<()> {
+        self.execute(
+            "INSERT INTO fx_rates (base_currency, quote_currency, date, rate)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (base_currency, quote_currency, date) DO UPDATE SET rate = EXCLUDED.rate",
+            &[
+                &normalize_currency(Some(base)),
+                &normalize_currency(Some(quote)),
+                &date,
+                &rate,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Currency of `account_id` with every stored rate and the currency of each ticker not
+    /// priced in the base currency; missing accounts are taken to be in the base currency.
+    pub async fn get_account_fx(&self, account_id: &str) -> Result<AccountFx> {
+        let currency: Option<String> = self
+            .query_opt(
+                "SELECT currency FROM accounts WHERE id = $1",
+                &[&account_id],
+            )
+            .await?
+            .and_then(|row| row.get(0));
+        let ticker_currencies = self
+            .query(
+                "SELECT symbol, currency FROM tickers WHERE UPPER(currency) <> 'USD'",
+                &[],
+            )
+            .await?
+            .into_iter()
+            .map(|row| {
+                let currency: String = row.get(1);
+                (row.get(0), normalize_currency(Some(&currency)))
+            })
+            .collect();
+        Ok(AccountFx::new(
+            currency.as_deref(),
+            self.get_fx_rates().await?,
+            ticker_currencies,
+        ))
+    }
+
+    /// Stores the broker's view of `account_id` for `date`, replacing an earlier snapshot taken
+    /// the same day.
+    pub async fn upsert_account_state_snapshot(
+        &self,
+        account_id: &str,
+        date: NaiveDate,
+        state: &AccountStateSnapshot,
+    ) -> Result<()> {
+        let positions: Vec<Value> = state
+            .positions
+            .iter()
+            .map(|position| {
+                json!({
+                    "ticker": position.ticker,
+                    "quantity": position.quantity,
+                    "avgEntryPrice": position.avg_entry_price,
+                    "currentPrice": position.current_price,
+                })
+            })
+            .collect();
+        let mut stop_orders = Map::new();
+        for (ticker, orders) in &state.stop_orders {
+            let orders: Vec<Value> = orders
+                .iter()
+                .map(|order| {
+                    json!({
+                        "quantity": order.quantity,
+                        "stopPrice": order.stop_price,
+                        "side": order.side,
+                    })
+                })
+                .collect();
+            stop_orders.insert(ticker.clone(), Value::Array(orders));
+        }
+        let positions = Value::Array(positions).to_string();
+        let stop_orders = Value::Object(stop_orders).to_string();
+        self.execute(
+            "INSERT INTO account_state_snapshots
+                 (account_id, snapshot_date, available_cash, buying_power, equity, positions, stop_orders)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (account_id, snapshot_date) DO UPDATE SET
+                 available_cash = EXCLUDED.available_cash,
+                 buying_power = EXCLUDED.buying_power,
+                 equity = EXCLUDED.equity,
+                 positions = EXCLUDED.positions,
+                 stop_orders = EXCLUDED.stop_orders,
+                 updated_at = CURRENT_TIMESTAMP",
+            &[
+                &account_id,
+                &date,
+                &state.available_cash,
+                &state.buying_power,
+                &state.equity,
+                &positions,
+                &stop_orders,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Points trades that reference broker order `old_order_id` at `new_order_id`.
+    pub async fn replace_trade_order_id(
+        &self,
+        old_order_id: &str,
+        new_order_id: &str,
+    ) -> Result<u64> {
+        self.execute(
+            "UPDATE trades
+                 SET entry_order_id = CASE WHEN entry_order_id = $1 THEN $2 ELSE entry_order_id END,
+                     stop_order_id = CASE WHEN stop_order_id = $1 THEN $2 ELSE stop_order_id END,
+                     exit_order_id = CASE WHEN exit_order_id = $1 THEN $2 ELSE exit_order_id END,
+                     target_order_id = CASE WHEN target_order_id = $1 THEN $2 ELSE target_order_id END
+                 WHERE entry_order_id = $1 OR stop_order_id = $1 OR exit_order_id = $1 OR target_order_id = $1",
+            &[&old_order_id, &new_order_id],
+        )
+        .await
+    }
+
+    pub async fn get_account_credentials(
+        &self,
+        account_id: &str,
+    ) -> Result<Option<AccountCredentials>> {
+        let row = self
+            .query_opt(
+                "SELECT id, provider, environment, api_key, api_secret
+                 FROM accounts
+                 WHERE id = $1",
+                &[&account_id],
+            )
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let id: String = row.get(0);
+        let api_key: String = row.get(3);
+        let api_secret: String = row.get(4);
+
+        Ok(Some(AccountCredentials {
+            id: id.clone(),
+            provider: row.get(1),
+            environment: row.get(2),
+            api_key: SecretString::new(
+                decrypt_database_value(&api_key)
+                    .with_context(|| format!("failed to decrypt api_key for account {}", id))?,
+            ),
+            api_secret: SecretString::new(
+                decrypt_database_value(&api_secret)
+                    .with_context(|| format!("failed to decrypt api_secret for account {}", id))?,
+            ),
+        }))
+    }
+
+    pub async fn update_strategy_backtest_duration(
+        &self,
+        strategy_id: &str,
+        duration_minutes: f64,
+    ) -> Result<()> {
+        self.execute(
+            "UPDATE strategies
+                 SET last_backtest_duration_minutes = $1, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = $2",
+            &[&duration_minutes, &strategy_id],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get_latest_backtest_end_date(
         &self,
         strategy_id: &str,
         months_filter: Option<i64>,
@@ -653,27 +1648,25 @@ impl Database {
     ) -> Result<Option<DateTime<Utc>>> {
         let months_filter = months_filter.map(clamp_i64_to_i32);
         let row = if let Some(months) = months_filter {
-            self.client
-                .query_opt(
-                    "SELECT end_date
+            self.query_opt(
+                "SELECT end_date
                      FROM backtest_results
                      WHERE strategy_id = $1 AND period_months = $2 AND ticker_scope = $3
                      ORDER BY end_date DESC
                      LIMIT 1",
-                    &[&strategy_id, &months, &ticker_scope],
-                )
-                .await?
+                &[&strategy_id, &months, &ticker_scope],
+            )
+            .await?
         } else {
-            self.client
-                .query_opt(
-                    "SELECT end_date
+            self.query_opt(
+                "SELECT end_date
                      FROM backtest_results
                      WHERE strategy_id = $1 AND ticker_scope = $2
                      ORDER BY end_date DESC
                      LIMIT 1",
-                    &[&strategy_id, &ticker_scope],
-                )
-                .await?
+                &[&strategy_id, &ticker_scope],
+            )
+            .await?
         };
 
         Ok(row.map(|row| row.get(0)))
@@ -687,9 +1680,8 @@ impl Database {
     ) -> Result<Option<BacktestResult>> {
         let months_filter_i32 = months_filter.map(clamp_i64_to_i32);
         let row = if let Some(months) = months_filter_i32 {
-            self.client
-                .query_opt(
-                    "SELECT id, start_date, end_date, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state, created_at
+            self.query_opt(
+                    "SELECT id, start_date, end_date, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state, created_at, baseline_return, insufficient_history_tickers
                      FROM backtest_results
                      WHERE strategy_id = $1 AND period_months = $2 AND ticker_scope = $3
                      ORDER BY end_date DESC
@@ -698,9 +1690,8 @@ impl Database {
                 )
                 .await?
         } else {
-            self.client
-                .query_opt(
-                    "SELECT id, start_date, end_date, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state, created_at
+            self.query_opt(
+                    "SELECT id, start_date, end_date, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state, created_at, baseline_return, insufficient_history_tickers
                      FROM backtest_results
                      WHERE strategy_id = $1 AND ticker_scope = $2
                      ORDER BY end_date DESC
@@ -720,11 +1711,20 @@ impl Database {
         let tickers_json: String = row.get(7);
         let scope_label: String = row.get(8);
         let strategy_state_json: Option<String> = row.get(9);
+        let insufficient_history_json: Option<String> = row.get(12);
 
         let performance = deserialize_performance(&performance_json)?;
         let daily_snapshots = deserialize_snapshots(&snapshots_json)?;
         let tickers: Vec<String> = serde_json::from_str(&tickers_json)
             .map_err(|err| anyhow!("Failed to parse tickers JSON: {}", err))?;
+        let insufficient_history_tickers: Vec<String> = insufficient_history_json
+            .map(|raw| {
+                serde_json::from_str(&raw).map_err(|err| {
+                    anyhow!("Failed to parse insufficient history tickers JSON: {}", err)
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
         let strategy_state = strategy_state_json
             .map(|raw| {
                 serde_json::from_str(&raw)
@@ -746,8 +1746,10 @@ impl Database {
             daily_snapshots,
             trades,
             tickers,
+            insufficient_history_tickers,
             ticker_scope: Some(scope_label),
             strategy_state,
+            baseline_return: row.get(11),
             created_at: row.get(10),
         }))
     }
@@ -758,24 +1760,45 @@ impl Database {
         result: &BacktestResult,
         months_filter: Option<i64>,
         ticker_scope: &str,
+        data_hash: Option<&str>,
     ) -> Result<()> {
         let performance_json = serialize_performance(&result.performance)?;
         let snapshots_json = serialize_snapshots(&result.daily_snapshots)?;
+        let weekly_snapshots_json = serialize_snapshots(&PerformanceCalculator::rollup_snapshots(
+            &result.daily_snapshots,
+            SnapshotResolution::Weekly,
+        ))?;
+        let monthly_snapshots_json =
+            serialize_snapshots(&PerformanceCalculator::rollup_snapshots(
+                &result.daily_snapshots,
+                SnapshotResolution::Monthly,
+            ))?;
         let tickers_json = serde_json::to_string(&result.tickers)?;
+        let insufficient_history_json =
+            serde_json::to_string(&result.insufficient_history_tickers)?;
         let period_days = calculate_period_days(&result.start_date, &result.end_date);
         let target_months = months_filter;
         let period_months = target_months.unwrap_or_else(|| calculate_period_months(period_days));
         let strategy_state_json = result
             .strategy_state
             .as_ref()
-            .map(|snapshot| serde_json::to_string(snapshot))
+            .map(serde_json::to_string)
             .transpose()?;
+        let excess_return = result.excess_return();
+        let is_live = ticker_scope.eq_ignore_ascii_case("live");
+        if !is_live {
+            self.stage_backtest_trades(strategy_id, result).await?;
+        }
+        // Staged trades are resumed by the next run, so stopping before publishing is safe.
+        self.cancellation.check()?;
 
-        let tx = self.client.transaction().await?;
-        if ticker_scope.eq_ignore_ascii_case("live") {
+        self.with_retry(|| async {
+        let mut client = self.client().await?;
+        let tx = client.transaction().await?;
+        if is_live {
             tx.execute(
-                "INSERT INTO backtest_results (id, strategy_id, start_date, end_date, period_days, period_months, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state)
-                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                "INSERT INTO backtest_results (id, strategy_id, start_date, end_date, period_days, period_months, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state, baseline_return, excess_return, weekly_snapshots, monthly_snapshots, data_hash, insufficient_history_tickers)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)
                  ON CONFLICT (id) DO UPDATE SET
                     strategy_id = EXCLUDED.strategy_id,
                     start_date = EXCLUDED.start_date,
@@ -789,6 +1812,12 @@ impl Database {
                     tickers = EXCLUDED.tickers,
                     ticker_scope = EXCLUDED.ticker_scope,
                     strategy_state = EXCLUDED.strategy_state,
+                    baseline_return = EXCLUDED.baseline_return,
+                    excess_return = EXCLUDED.excess_return,
+                    weekly_snapshots = EXCLUDED.weekly_snapshots,
+                    monthly_snapshots = EXCLUDED.monthly_snapshots,
+                    data_hash = EXCLUDED.data_hash,
+                    insufficient_history_tickers = EXCLUDED.insufficient_history_tickers,
                     created_at = CURRENT_TIMESTAMP",
                 &[
                     &result.id,
@@ -804,6 +1833,12 @@ impl Database {
                     &tickers_json,
                     &ticker_scope,
                     &strategy_state_json,
+                    &result.baseline_return,
+                    &excess_return,
+                    &weekly_snapshots_json,
+                    &monthly_snapshots_json,
+                    &data_hash,
+                    &insufficient_history_json,
                 ],
             )
             .await?;
@@ -812,6 +1847,16 @@ impl Database {
             return Ok(());
         }
 
+        // The result row is the completion marker: once it exists, an earlier attempt already
+        // published this run and its staged trades are gone.
+        let published = tx
+            .query_opt("SELECT 1 FROM backtest_results WHERE id = $1", &[&result.id])
+            .await?;
+        if published.is_some() {
+            tx.commit().await?;
+            return Ok(());
+        }
+
         let target_months_i32 = target_months.map(clamp_i64_to_i32);
         let existing_rows = if let Some(target_months) = target_months_i32 {
             tx.query(
@@ -855,8 +1900,8 @@ impl Database {
         }
 
         tx.execute(
-            "INSERT INTO backtest_results (id, strategy_id, start_date, end_date, period_days, period_months, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)",
+            "INSERT INTO backtest_results (id, strategy_id, start_date, end_date, period_days, period_months, initial_capital, final_portfolio_value, performance, daily_snapshots, tickers, ticker_scope, strategy_state, baseline_return, excess_return, weekly_snapshots, monthly_snapshots, data_hash, insufficient_history_tickers)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)",
             &[
                 &result.id,
                 &strategy_id,
@@ -871,69 +1916,505 @@ impl Database {
                 &tickers_json,
                 &ticker_scope,
                 &strategy_state_json,
+                &result.baseline_return,
+                &excess_return,
+                &weekly_snapshots_json,
+                &monthly_snapshots_json,
+                &data_hash,
+                &insufficient_history_json,
             ],
         )
         .await?;
 
-        if !result.trades.is_empty() {
-            let stmt = tx
-                .prepare(
-                    "INSERT INTO trades (id, strategy_id, backtest_result_id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes)
-                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+        tx.execute(
+            "INSERT INTO trades (id, strategy_id, backtest_result_id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes)
+             SELECT id, strategy_id, backtest_result_id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes
+             FROM backtest_trades_staging
+             WHERE backtest_result_id = $1
+             ORDER BY batch_index",
+            &[&result.id],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM backtest_trades_staging WHERE backtest_result_id = $1",
+            &[&result.id],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+        })
+        .await
+    }
+
+    /// Writes the run's trades to `backtest_trades_staging` one batch per transaction, so a large
+    /// run never sits in a single insert and a retried run continues after its last staged batch.
+    /// Staging left behind by earlier, unfinished runs of the strategy is dropped first.
+    async fn stage_backtest_trades(
+        &self,
+        strategy_id: &str,
+        result: &BacktestResult,
+    ) -> Result<()> {
+        self.execute(
+            "DELETE FROM backtest_trades_staging WHERE strategy_id = $1 AND backtest_result_id <> $2",
+            &[&strategy_id, &result.id],
+        )
+        .await?;
+        let staged: i32 = self
+            .query_one(
+                "SELECT COALESCE(MAX(batch_index) + 1, 0) FROM backtest_trades_staging WHERE backtest_result_id = $1",
+                &[&result.id],
+            )
+            .await?
+            .get(0);
+
+        for (batch_index, chunk) in result
+            .trades
+            .chunks(BACKTEST_TRADE_BATCH_SIZE)
+            .enumerate()
+            .skip(staged.max(0) as usize)
+        {
+            self.cancellation.check()?;
+            let batch_index = batch_index as i32;
+            self.with_write_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            let sink = tx
+                .copy_in(
+                    "COPY backtest_trades_staging (backtest_result_id, batch_index, id, strategy_id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes)
+                     FROM STDIN BINARY",
                 )
                 .await?;
-
-            for trade in &result.trades {
+            let writer = BinaryCopyInWriter::new(
+                sink,
+                &[
+                    Type::TEXT,
+                    Type::INT4,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::INT4,
+                    Type::FLOAT8,
+                    Type::DATE,
+                    Type::TEXT,
+                    Type::FLOAT8,
+                    Type::FLOAT8,
+                    Type::FLOAT8,
+                    Type::DATE,
+                    Type::FLOAT8,
+                    Type::BOOL,
+                    Type::TEXT,
+                ],
+            );
+            let mut writer = pin!(writer);
+            for trade in chunk {
                 let trade_date = trade.date.date_naive();
                 let exit_date = trade.exit_date.map(|d| d.date_naive());
                 let changes_json =
                     serde_json::to_string(&trade.changes).context("Failed to serialize trades")?;
-                let fee_value = trade.fee.unwrap_or(0.0);
-
-                tx.execute(
-                    &stmt,
-                    &[
+                writer
+                    .as_mut()
+                    .write(&[
+                        &result.id,
+                        &batch_index,
                         &trade.id,
                         &strategy_id,
-                        &result.id,
                         &trade.ticker,
                         &trade.quantity,
                         &trade.price,
                         &trade_date,
                         &trade.status.as_str(),
                         &trade.pnl,
-                        &fee_value,
+                        &trade.fee.unwrap_or(0.0),
                         &trade.exit_price,
                         &exit_date,
                         &trade.stop_loss,
                         &trade.stop_loss_triggered.unwrap_or(false),
                         &changes_json,
-                    ],
+                    ])
+                    .await?;
+            }
+            writer.finish().await?;
+            tx.commit().await?;
+            Ok(())
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_backtest_result_summaries(&self) -> Result<Vec<BacktestResultSummary>> {
+        let rows = self
+            .query(
+                "SELECT
+                    br.id,
+                    br.strategy_id,
+                    s.name,
+                    s.status = 'active' AS strategy_active,
+                    COALESCE(br.ticker_scope, 'training'),
+                    br.period_months,
+                    br.end_date,
+                    br.created_at,
+                    (SELECT COUNT(*) FROM trades t WHERE t.backtest_result_id = br.id) AS trade_count
+                 FROM backtest_results br
+                 JOIN strategies s ON s.id = br.strategy_id
+                 ORDER BY br.strategy_id, br.end_date DESC, br.created_at DESC",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| BacktestResultSummary {
+                id: row.get(0),
+                strategy_id: row.get(1),
+                strategy_name: row.get(2),
+                strategy_active: row.get(3),
+                ticker_scope: row.get(4),
+                period_months: row.get(5),
+                end_date: row.get(6),
+                created_at: row.get(7),
+                trade_count: row.get(8),
+            })
+            .collect())
+    }
+
+    /// Deletes backtest results with their simulated trades and returns (results, trades)
+    /// removed. Live trades linked to a deleted result are kept and only unlinked.
+    pub async fn delete_backtest_results(&self, ids: &[String]) -> Result<(u64, u64)> {
+        if ids.is_empty() {
+            return Ok((0, 0));
+        }
+        self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            let trades = tx
+                .execute(
+                    "DELETE FROM trades WHERE backtest_result_id = ANY($1) AND entry_order_id IS NULL",
+                    &[&ids],
                 )
                 .await?;
-            }
+            tx.execute(
+                "UPDATE trades SET backtest_result_id = NULL WHERE backtest_result_id = ANY($1)",
+                &[&ids],
+            )
+            .await?;
+            let results = tx
+                .execute("DELETE FROM backtest_results WHERE id = ANY($1)", &[&ids])
+                .await?;
+            tx.commit().await?;
+            Ok((results, trades))
+        })
+        .await
+    }
+
+    /// Replaces a result's daily snapshots with their week-end rollup. Returns the number of
+    /// snapshots dropped, zero when the series is already weekly.
+    pub async fn compact_backtest_snapshots(&self, id: &str) -> Result<usize> {
+        let Some(row) = self
+            .query_opt(
+                "SELECT daily_snapshots FROM backtest_results WHERE id = $1",
+                &[&id],
+            )
+            .await?
+        else {
+            return Ok(0);
+        };
+        let daily = deserialize_snapshots(&row.get::<_, String>(0))?;
+        let weekly = PerformanceCalculator::rollup_snapshots(&daily, SnapshotResolution::Weekly);
+        if weekly.len() >= daily.len() {
+            return Ok(0);
         }
+        let weekly_json = serialize_snapshots(&weekly)?;
+        self.execute(
+            "UPDATE backtest_results SET daily_snapshots = $2, weekly_snapshots = $2 WHERE id = $1",
+            &[&id, &weekly_json],
+        )
+        .await?;
+        Ok(daily.len() - weekly.len())
+    }
 
-        tx.commit().await?;
+    pub async fn get_latest_backtest_performance(
+        &self,
+        strategy_id: &str,
+        ticker_scope: &str,
+    ) -> Result<Option<StrategyPerformance>> {
+        let row = self
+            .query_opt(
+                "SELECT performance
+                 FROM backtest_results
+                 WHERE strategy_id = $1 AND COALESCE(ticker_scope, 'training') = $2
+                 ORDER BY end_date DESC, created_at DESC
+                 LIMIT 1",
+                &[&strategy_id, &ticker_scope],
+            )
+            .await?;
+        row.map(|row| deserialize_performance(&row.get::<_, String>(0)))
+            .transpose()
+    }
+
+    pub async fn upsert_signal_stats(
+        &self,
+        strategy_id: &str,
+        window_start: NaiveDate,
+        window_end: NaiveDate,
+        horizon_days: usize,
+        stats: &SignalStats,
+        backtest_win_rate: Option<f64>,
+    ) -> Result<()> {
+        let deciles_json = serde_json::to_string(&stats.confidence_deciles)?;
+        let decay_json = serde_json::to_string(&stats.holding_day_returns)?;
+        self.execute(
+            "INSERT INTO signal_stats (strategy_id, window_start, window_end, horizon_days, signal_count, evaluated_count, hit_rate, avg_return, confidence_deciles, holding_day_returns, backtest_win_rate, computed_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, CURRENT_TIMESTAMP)
+             ON CONFLICT (strategy_id) DO UPDATE SET
+                window_start = EXCLUDED.window_start,
+                window_end = EXCLUDED.window_end,
+                horizon_days = EXCLUDED.horizon_days,
+                signal_count = EXCLUDED.signal_count,
+                evaluated_count = EXCLUDED.evaluated_count,
+                hit_rate = EXCLUDED.hit_rate,
+                avg_return = EXCLUDED.avg_return,
+                confidence_deciles = EXCLUDED.confidence_deciles,
+                holding_day_returns = EXCLUDED.holding_day_returns,
+                backtest_win_rate = EXCLUDED.backtest_win_rate,
+                computed_at = EXCLUDED.computed_at",
+            &[
+                &strategy_id,
+                &window_start,
+                &window_end,
+                &clamp_i64_to_i32(horizon_days as i64),
+                &clamp_i64_to_i32(stats.signal_count as i64),
+                &clamp_i64_to_i32(stats.evaluated_count as i64),
+                &stats.hit_rate,
+                &stats.avg_return,
+                &deciles_json,
+                &decay_json,
+                &backtest_win_rate,
+            ],
+        )
+        .await?;
         Ok(())
     }
 
+    /// Strategy state saved by the last live signal run with the last signal date it covers.
+    pub async fn get_strategy_live_state(
+        &self,
+        strategy_id: &str,
+    ) -> Result<Option<(StrategyStateSnapshot, DateTime<Utc>)>> {
+        let row = self
+            .query_opt(
+                "SELECT state, as_of FROM strategy_live_state WHERE strategy_id = $1",
+                &[&strategy_id],
+            )
+            .await?;
+        row.map(|row| {
+            let state: StrategyStateSnapshot = serde_json::from_str(&row.get::<_, String>(0))
+                .map_err(|err| anyhow!("Failed to parse strategy state JSON: {}", err))?;
+            Ok((state, naive_date_to_datetime(row.get(1))))
+        })
+        .transpose()
+    }
+
+    pub async fn upsert_strategy_live_state(
+        &self,
+        strategy_id: &str,
+        state: &StrategyStateSnapshot,
+        as_of: DateTime<Utc>,
+    ) -> Result<()> {
+        let state_json = serde_json::to_string(state)?;
+        self.execute(
+            "INSERT INTO strategy_live_state (strategy_id, state, as_of, updated_at)
+             VALUES ($1, $2, $3, CURRENT_TIMESTAMP)
+             ON CONFLICT (strategy_id) DO UPDATE SET
+                state = EXCLUDED.state,
+                as_of = EXCLUDED.as_of,
+                updated_at = EXCLUDED.updated_at",
+            &[&strategy_id, &state_json, &as_of.date_naive()],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Marks the highest-priority, oldest queued job as running for `worker_id` and returns
+    /// it. Rows locked by other workers are skipped, so concurrent workers never share a job.
+    pub async fn claim_next_job(&self, worker_id: &str) -> Result<Option<ClaimedJob>> {
+        let row = self
+            .query_opt(
+                "UPDATE jobs
+                 SET status = 'running',
+                     worker_id = $1,
+                     started_at = CURRENT_TIMESTAMP,
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE id = (
+                     SELECT id FROM jobs
+                     WHERE status = 'queued'
+                     ORDER BY priority DESC, created_at
+                     LIMIT 1
+                     FOR UPDATE SKIP LOCKED
+                 )
+                 RETURNING id::text, command, payload",
+                &[&worker_id],
+            )
+            .await?;
+        Ok(row.map(|row| ClaimedJob {
+            id: row.get(0),
+            command: row.get(1),
+            payload: row.get(2),
+        }))
+    }
+
+    pub async fn update_job_progress(&self, job_id: &str, progress: &str) -> Result<()> {
+        self.execute(
+            "UPDATE jobs
+             SET progress = $2, updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1::uuid",
+            &[&job_id, &progress],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Records how a running job ended.
+    pub async fn finish_job(&self, job_id: &str, outcome: JobOutcome<'_>) -> Result<()> {
+        let (status, error) = match outcome {
+            JobOutcome::Succeeded => ("succeeded", None),
+            JobOutcome::Failed(error) => ("failed", Some(error)),
+            JobOutcome::Cancelled => ("cancelled", None),
+        };
+        self.execute(
+            "UPDATE jobs
+             SET status = $2,
+                 error = $3,
+                 finished_at = CURRENT_TIMESTAMP,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1::uuid",
+            &[&job_id, &status, &error],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Puts a running job back in the queue for another worker, e.g. when its worker shuts down.
+    pub async fn requeue_job(&self, job_id: &str) -> Result<()> {
+        self.execute(
+            "UPDATE jobs
+             SET status = 'queued',
+                 worker_id = NULL,
+                 started_at = NULL,
+                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = $1::uuid",
+            &[&job_id],
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn is_job_cancel_requested(&self, job_id: &str) -> Result<bool> {
+        let row = self
+            .query_one(
+                "SELECT cancel_requested FROM jobs WHERE id = $1::uuid",
+                &[&job_id],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
     pub async fn link_live_trades_to_backtest(
         &self,
         strategy_id: &str,
         backtest_result_id: &str,
     ) -> Result<()> {
-        self.client
-            .execute(
-                "UPDATE trades
+        self.execute(
+            "UPDATE trades
                  SET backtest_result_id = $1
                  WHERE strategy_id = $2
                    AND entry_order_id IS NOT NULL",
-                &[&backtest_result_id, &strategy_id],
+            &[&backtest_result_id, &strategy_id],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Expected cost of the strategy's sent operations triggered in `[start, end)`.
+    pub async fn sum_sent_operation_costs(
+        &self,
+        strategy_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<f64> {
+        let row = self
+            .query_one(
+                "SELECT COALESCE(SUM(expected_cost), 0)::DOUBLE PRECISION
+                 FROM account_operations
+                 WHERE strategy_id = $1
+                   AND status = 'sent'
+                   AND triggered_at >= $2
+                   AND triggered_at < $3",
+                &[&strategy_id, &start, &end],
             )
             .await?;
+        Ok(row.get(0))
+    }
 
-        Ok(())
+    /// Holds pending operations on `tickers` back from dispatch by marking them paused.
+    pub async fn pause_pending_operations_for_tickers(
+        &self,
+        tickers: &[String],
+        reason: &str,
+    ) -> Result<u64> {
+        if tickers.is_empty() {
+            return Ok(0);
+        }
+        self.execute(
+            "UPDATE account_operations
+                 SET status = 'paused', status_reason = $2, status_updated_at = CURRENT_TIMESTAMP
+                 WHERE status = 'pending' AND ticker = ANY($1)",
+            &[&tickers, &reason],
+        )
+        .await
+    }
+
+    /// Distinct tickers of operations in `statuses`, sorted.
+    pub async fn get_operation_tickers_with_status(
+        &self,
+        statuses: &[&str],
+    ) -> Result<Vec<String>> {
+        let rows = self
+            .query(
+                "SELECT DISTINCT ticker FROM account_operations
+                 WHERE status = ANY($1)
+                 ORDER BY ticker",
+                &[&statuses],
+            )
+            .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    /// Distinct (account, ticker, reason, signal date) skips recorded by `source` since `since`.
+    pub async fn get_signal_skip_occurrences(
+        &self,
+        source: &str,
+        reasons: &[String],
+        since: NaiveDate,
+    ) -> Result<Vec<SkipOccurrence>> {
+        let rows = self
+            .query(
+                "SELECT DISTINCT account_id, ticker, reason, signal_date FROM account_signal_skips
+                 WHERE source = $1 AND reason = ANY($2) AND signal_date >= $3
+                   AND account_id IS NOT NULL",
+                &[&source, &reasons, &since],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| SkipOccurrence {
+                account_id: row.get(0),
+                ticker: row.get(1),
+                reason: row.get(2),
+                date: row.get(3),
+            })
+            .collect())
     }
 
     pub async fn replace_account_operations_for_strategy(
@@ -942,11 +2423,13 @@ impl Database {
         strategy_id: &str,
         operations: &[AccountOperationPlan],
     ) -> Result<()> {
-        let tx = self.client.transaction().await?;
+        self.with_retry(|| async {
+        let mut client = self.client().await?;
+        let tx = client.transaction().await?;
         tx.execute(
             "DELETE FROM account_operations
              WHERE strategy_id = $1
-               AND status IN ('pending', 'approved', 'failed', 'ignored')",
+               AND status IN ('pending', 'approved', 'failed', 'ignored', 'paused')",
             &[&strategy_id],
         )
         .await?;
@@ -955,8 +2438,8 @@ impl Database {
             let stmt = tx
                 .prepare(
                     "INSERT INTO account_operations
-                     (id, account_id, strategy_id, trade_id, ticker, operation_type, quantity, price, stop_loss, previous_stop_loss, triggered_at, reason, order_type, discount_applied, signal_confidence, account_cash_at_plan, days_held)
-                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)",
+                     (id, account_id, strategy_id, trade_id, ticker, operation_type, quantity, price, stop_loss, previous_stop_loss, triggered_at, reason, order_type, discount_applied, signal_confidence, account_cash_at_plan, days_held, expected_cost, replaces_order_id, take_profit)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)",
                 )
                 .await?;
 
@@ -983,6 +2466,9 @@ impl Database {
                         &op.signal_confidence,
                         &op.account_cash_at_plan,
                         &op.days_held,
+                        &op.expected_cost,
+                        &op.replaces_order_id,
+                        &op.take_profit,
                     ],
                 )
                 .await?;
@@ -991,16 +2477,18 @@ impl Database {
 
         tx.commit().await?;
         Ok(())
+        })
+        .await
     }
 
+    /// Live trades of `strategy_id` placed through an entry order, with the closed parts split
+    /// off by partial exits.
     pub async fn get_strategy_live_trades(&self, strategy_id: &str) -> Result<Vec<Trade>> {
-        let rows = self
-            .client
-            .query(
-                "SELECT id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes, entry_order_id, entry_cancel_after, stop_order_id, exit_order_id
+        let rows = self.query(
+                "SELECT id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes, entry_order_id, entry_cancel_after, stop_order_id, exit_order_id, target_order_id
                  FROM trades t
                  WHERE t.strategy_id = $1
-                   AND t.entry_order_id IS NOT NULL
+                   AND (t.entry_order_id IS NOT NULL OR t.id LIKE '%-partial-%')
                  ORDER BY t.date, t.id",
                 &[&strategy_id],
             )
@@ -1018,7 +2506,6 @@ impl Database {
         strategy_id: &str,
     ) -> Result<Option<DateTime<Utc>>> {
         let row = self
-            .client
             .query_one(
                 "SELECT MIN(date)
                  FROM trades
@@ -1034,10 +2521,8 @@ impl Database {
     }
 
     pub async fn get_live_trades_with_accounts(&self) -> Result<Vec<TradeReconciliationCandidate>> {
-        let rows = self
-            .client
-            .query(
-                "SELECT t.id, t.ticker, t.quantity, t.price, t.date, t.status, t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered, t.changes, t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, s.account_id, t.strategy_id
+        let rows = self.query(
+                "SELECT t.id, t.ticker, t.quantity, t.price, t.date, t.status, t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered, t.changes, t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, s.account_id, t.strategy_id
                  FROM trades t
                  INNER JOIN strategies s ON s.id = t.strategy_id
                  WHERE s.account_id IS NOT NULL
@@ -1048,17 +2533,243 @@ impl Database {
             )
             .await?;
 
-        let mut result = Vec::with_capacity(rows.len());
-        for row in rows {
-            let account_id: String = row.get(17);
-            if account_id.trim().is_empty() {
-                continue;
-            }
-            let strategy_id: String = row.get(18);
-            let trade = Self::map_trade_row(&row, &strategy_id)?;
-            result.push(TradeReconciliationCandidate { trade, account_id });
-        }
-        Ok(result)
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let account_id: String = row.get(18);
+            if account_id.trim().is_empty() {
+                continue;
+            }
+            let strategy_id: String = row.get(19);
+            let trade = Self::map_trade_row(&row, &strategy_id)?;
+            result.push(TradeReconciliationCandidate { trade, account_id });
+        }
+        Ok(result)
+    }
+
+    /// Realized P&L, in the account currency, and count of live trades on `account_id` closed
+    /// on `date`.
+    pub async fn get_account_realized_pnl_on(
+        &self,
+        account_id: &str,
+        date: NaiveDate,
+    ) -> Result<(f64, i64)> {
+        let rows = self
+            .query(
+                "SELECT t.ticker, t.exit_date, COALESCE(SUM(t.pnl), 0)::DOUBLE PRECISION, COUNT(*)
+                 FROM trades t
+                 INNER JOIN strategies s ON s.id = t.strategy_id
+                 WHERE s.account_id = $1 AND t.status = 'closed' AND t.exit_date = $2
+                 GROUP BY t.ticker, t.exit_date",
+                &[&account_id, &date],
+            )
+            .await?;
+        let count = rows.iter().map(|row| row.get::<_, i64>(3)).sum();
+        Ok((
+            self.sum_in_account_currency(account_id, &rows).await?,
+            count,
+        ))
+    }
+
+    /// Adds up `(ticker, exit_date, pnl, ..)` rows converted from each ticker's currency into
+    /// the account's at the exit date's rate.
+    async fn sum_in_account_currency(&self, account_id: &str, rows: &[Row]) -> Result<f64> {
+        if rows.is_empty() {
+            return Ok(0.0);
+        }
+        let fx = self.get_account_fx(account_id).await?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let ticker: String = row.get(0);
+                let exit_date: Option<NaiveDate> = row.get(1);
+                let pnl: f64 = row.get(2);
+                fx.to_account_on(pnl, &ticker, exit_date.unwrap_or(NaiveDate::MAX))
+            })
+            .sum())
+    }
+
+    /// Filled live trades of `account_id` (including the closed parts split off by partial exits)
+    /// entered or exited between `from` and `to` (inclusive), with the strategy name and the reason of the last sent close operation for the trade.
+    pub async fn get_account_journal_trades(
+        &self,
+        account_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<JournalTrade>> {
+        let rows = self.query(
+                "SELECT t.id, t.ticker, t.quantity, t.price, t.date, t.status, t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered, t.changes, t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.strategy_id, s.name,
+                        (SELECT ao.reason
+                         FROM account_operations ao
+                         WHERE ao.account_id = s.account_id
+                           AND ao.operation_type = 'close_position'
+                           AND ao.status = 'sent'
+                           AND (ao.trade_id = t.id OR t.id LIKE ao.trade_id || '-partial-%')
+                         ORDER BY ao.triggered_at DESC
+                         LIMIT 1)
+                 FROM trades t
+                 INNER JOIN strategies s ON s.id = t.strategy_id
+                 WHERE s.account_id = $1
+                   AND t.backtest_result_id IS NULL
+                   AND (t.entry_order_id IS NOT NULL OR t.id LIKE '%-partial-%')
+                   AND t.status IN ('active', 'closed')
+                   AND ((t.date >= $2 AND t.date <= $3) OR (t.exit_date >= $2 AND t.exit_date <= $3))
+                 ORDER BY t.date, t.id",
+                &[&account_id, &from, &to],
+            )
+            .await?;
+
+        let mut trades = Vec::with_capacity(rows.len());
+        for row in rows {
+            let strategy_id: String = row.get(18);
+            trades.push(JournalTrade {
+                trade: Self::map_trade_row(&row, &strategy_id)?,
+                strategy_name: row.get(19),
+                close_reason: row.get(20),
+            });
+        }
+        Ok(trades)
+    }
+
+    /// Realized P&L, in the account currency, of every closed live trade on `account_id`.
+    pub async fn get_account_realized_pnl(&self, account_id: &str) -> Result<f64> {
+        let rows = self
+            .query(
+                "SELECT t.ticker, t.exit_date, COALESCE(SUM(t.pnl), 0)::DOUBLE PRECISION
+                 FROM trades t
+                 INNER JOIN strategies s ON s.id = t.strategy_id
+                 WHERE s.account_id = $1 AND t.status = 'closed' AND t.entry_order_id IS NOT NULL
+                 GROUP BY t.ticker, t.exit_date",
+                &[&account_id],
+            )
+            .await?;
+        self.sum_in_account_currency(account_id, &rows).await
+    }
+
+    /// Looks up a dispatched paper order: first as the stop order of a trade, then as the order
+    /// of an open or close operation.
+    pub async fn get_paper_order(&self, order_id: &str) -> Result<Option<PaperOrder>> {
+        if let Some(row) = self
+            .query_opt(
+                "SELECT t.ticker, t.quantity, t.stop_loss, t.date, t.status,
+                        (SELECT MAX(o.last_attempt_at) FROM account_operations o WHERE o.order_id = t.stop_order_id),
+                        t.stop_order_id, t.target_order_id,
+                        (SELECT o.take_profit FROM account_operations o
+                         WHERE o.trade_id = t.id AND o.operation_type = 'open_position'
+                         ORDER BY o.last_attempt_at DESC NULLS LAST LIMIT 1)
+                 FROM trades t
+                 WHERE t.stop_order_id = $1 OR t.target_order_id = $1
+                 LIMIT 1",
+                &[&order_id],
+            )
+            .await?
+        {
+            let ticker: String = row.get(0);
+            let trade_date = naive_date_to_datetime(row.get(3));
+            let status: String = row.get(4);
+            let updated_at: Option<DateTime<Utc>> = row.get(5);
+            let stop_order_id: Option<String> = row.get(6);
+            let target_order_id: Option<String> = row.get(7);
+            let stop = PaperOrder::protective_exit(
+                &ticker,
+                row.get(1),
+                None,
+                row.get(2),
+                updated_at.map_or(trade_date, |at| at.max(trade_date)),
+                status == "active",
+            );
+            let target = PaperOrder::protective_exit(
+                &ticker,
+                row.get(1),
+                row.get(8),
+                None,
+                trade_date,
+                status == "active",
+            );
+            return Ok(PaperOrder::exit_leg(
+                order_id,
+                stop_order_id.as_deref().map(|id| (id, stop)),
+                target_order_id.as_deref().map(|id| (id, target)),
+            ));
+        }
+
+        let Some(row) = self
+            .query_opt(
+                "SELECT o.operation_type, o.ticker, o.quantity, o.price, o.order_type,
+                        COALESCE(o.last_attempt_at, o.status_updated_at, o.triggered_at), t.quantity
+                 FROM account_operations o
+                 LEFT JOIN trades t ON t.id = o.trade_id
+                 WHERE o.order_id = $1 AND o.operation_type IN ('open_position', 'close_position')
+                 ORDER BY o.last_attempt_at DESC NULLS LAST
+                 LIMIT 1",
+                &[&order_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let operation_type: String = row.get(0);
+        let operation_quantity: Option<i32> = row.get(2);
+        let trade_quantity: Option<i32> = row.get(6);
+        let order_type: Option<String> = row.get(4);
+        let long = trade_quantity.unwrap_or(1) > 0;
+        Ok(Some(PaperOrder {
+            ticker: row.get(1),
+            buy: (operation_type == "open_position") == long,
+            quantity: operation_quantity.or(trade_quantity).unwrap_or(0).abs(),
+            limit_price: order_type
+                .filter(|order_type| order_type.eq_ignore_ascii_case("limit"))
+                .and(row.get::<_, Option<f64>>(3)),
+            stop_price: None,
+            submitted_at: row.get(5),
+            live: true,
+            good_till_cancelled: false,
+            one_cancels_other: None,
+        }))
+    }
+
+    /// Whether `source` already logged `event` for `account_id` at or after `since`, optionally
+    /// with exactly `message`.
+    pub async fn has_account_event_log_since(
+        &self,
+        source: &str,
+        account_id: &str,
+        event: &str,
+        message: Option<&str>,
+        since: DateTime<Utc>,
+    ) -> Result<bool> {
+        let row = self
+            .query_one(
+                "SELECT EXISTS (
+                     SELECT 1 FROM system_logs
+                     WHERE source = $1
+                       AND created_at >= $2
+                       AND (NULLIF(metadata, '')::jsonb ->> 'accountId') = $3
+                       AND (NULLIF(metadata, '')::jsonb ->> 'event') = $4
+                       AND ($5::TEXT IS NULL OR message = $5)
+                 )",
+                &[&source, &since, &account_id, &event, &message],
+            )
+            .await?;
+        Ok(row.get(0))
+    }
+
+    /// When `event` was last logged for `account_id` under `source`.
+    pub async fn latest_account_event_log_at(
+        &self,
+        source: &str,
+        account_id: &str,
+        event: &str,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let row = self
+            .query_one(
+                "SELECT MAX(created_at) FROM system_logs
+                 WHERE source = $1
+                   AND (NULLIF(metadata, '')::jsonb ->> 'accountId') = $2
+                   AND (NULLIF(metadata, '')::jsonb ->> 'event') = $3",
+                &[&source, &account_id, &event],
+            )
+            .await?;
+        Ok(row.get(0))
     }
 
     pub async fn get_latest_account_operation_date(
@@ -1066,7 +2777,6 @@ impl Database {
         strategy_id: &str,
     ) -> Result<Option<DateTime<Utc>>> {
         let row = self
-            .client
             .query_opt(
                 "SELECT triggered_at
                  FROM account_operations
@@ -1088,7 +2798,6 @@ impl Database {
         let op_type = AccountOperationType::OpenPosition.as_str();
         let date_only = target_date.date_naive();
         let row = self
-            .client
             .query_one(
                 "SELECT COUNT(*)
                  FROM account_operations
@@ -1116,7 +2825,6 @@ impl Database {
         let end = end_date.date_naive();
 
         let rows = self
-            .client
             .query(
                 "SELECT date, ticker, action, confidence
                  FROM signals
@@ -1152,7 +2860,6 @@ impl Database {
 
     pub async fn get_latest_signal_date(&self, strategy_id: &str) -> Result<Option<DateTime<Utc>>> {
         let row = self
-            .client
             .query_one(
                 "SELECT MAX(date) FROM signals WHERE strategy_id = $1",
                 &[&strategy_id],
@@ -1173,7 +2880,6 @@ impl Database {
         }
 
         let user_id: Option<i64> = self
-            .client
             .query_opt(
                 "SELECT user_id FROM strategies WHERE id = $1",
                 &[&strategy_id],
@@ -1181,13 +2887,72 @@ impl Database {
             .await?
             .and_then(|row| row.get(0));
 
+        // A single upsert cannot touch the same row twice, so keep the last signal per id.
+        let mut signal_ids = HashSet::with_capacity(signals.len());
+        let mut rows: Vec<(String, &GeneratedSignal)> = signals
+            .iter()
+            .rev()
+            .filter_map(|signal| {
+                let signal_id = generate_signal_id(strategy_id, &signal.ticker, signal.date);
+                signal_ids
+                    .insert(signal_id.clone())
+                    .then_some((signal_id, signal))
+            })
+            .collect();
+        rows.reverse();
+
         let mut inserted = 0usize;
-        for chunk in signals.chunks(SIGNAL_INSERT_CHUNK_SIZE) {
-            let tx = self.client.transaction().await?;
-            let stmt = tx
-                .prepare(
+        for chunk in rows.chunks(SIGNAL_INSERT_CHUNK_SIZE) {
+            self.cancellation.check()?;
+            inserted += self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            // COPY cannot resolve conflicts, so stage the chunk and upsert from the staging table.
+            tx.batch_execute(
+                "CREATE TEMP TABLE signals_staging
+                     (LIKE signals INCLUDING DEFAULTS) ON COMMIT DROP",
+            )
+            .await?;
+            let sink = tx
+                .copy_in(
+                    "COPY signals_staging (id, date, ticker, strategy_id, user_id, action, confidence)
+                     FROM STDIN BINARY",
+                )
+                .await?;
+            let writer = BinaryCopyInWriter::new(
+                sink,
+                &[
+                    Type::TEXT,
+                    Type::DATE,
+                    Type::TEXT,
+                    Type::TEXT,
+                    Type::INT8,
+                    Type::TEXT,
+                    Type::FLOAT8,
+                ],
+            );
+            let mut writer = pin!(writer);
+            for (signal_id, signal) in chunk {
+                let signal_date = signal.date.date_naive();
+                writer
+                    .as_mut()
+                    .write(&[
+                        signal_id,
+                        &signal_date,
+                        &signal.ticker,
+                        &strategy_id,
+                        &user_id,
+                        &signal.action.as_str(),
+                        &signal.confidence,
+                    ])
+                    .await?;
+            }
+            writer.finish().await?;
+            let chunk_inserted = tx
+                .execute(
                     "INSERT INTO signals (id, date, ticker, strategy_id, user_id, action, confidence)
-                     VALUES ($1, $2, $3, $4, $5, $6, $7)
+                     SELECT id, date, ticker, strategy_id, user_id, action, confidence
+                     FROM signals_staging
                      ON CONFLICT (id) DO UPDATE
                      SET date = EXCLUDED.date,
                          ticker = EXCLUDED.ticker,
@@ -1195,32 +2960,14 @@ impl Database {
                          user_id = EXCLUDED.user_id,
                          action = EXCLUDED.action,
                          confidence = EXCLUDED.confidence",
+                    &[],
                 )
-                .await?;
-
-            for signal in chunk {
-                let signal_id = generate_signal_id(strategy_id, &signal.ticker, signal.date);
-                let signal_date = signal.date.date_naive();
-                let changed = tx
-                    .execute(
-                        &stmt,
-                        &[
-                            &signal_id,
-                            &signal_date,
-                            &signal.ticker,
-                            &strategy_id,
-                            &user_id,
-                            &signal.action.as_str(),
-                            &signal.confidence,
-                        ],
-                    )
-                    .await?;
-                if changed > 0 {
-                    inserted += 1;
-                }
-            }
+                .await? as usize;
 
             tx.commit().await?;
+            Ok(chunk_inserted)
+            })
+            .await?;
         }
 
         Ok(inserted)
@@ -1231,10 +2978,8 @@ impl Database {
         backtest_id: &str,
         strategy_id: &str,
     ) -> Result<Vec<Trade>> {
-        let rows = self
-            .client
-            .query(
-                "SELECT id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes, entry_order_id, entry_cancel_after, stop_order_id, exit_order_id
+        let rows = self.query(
+                "SELECT id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, changes, entry_order_id, entry_cancel_after, stop_order_id, exit_order_id, target_order_id
                  FROM trades
                  WHERE backtest_result_id = $1
                  ORDER BY date, id",
@@ -1259,9 +3004,8 @@ impl Database {
         let fee_value = trade.fee.unwrap_or(0.0);
         let status = trade.status.as_str();
 
-        self.client
-            .execute(
-                "UPDATE trades
+        self.execute(
+            "UPDATE trades
                  SET status = $1,
                      pnl = $2,
                      fee = $3,
@@ -1272,43 +3016,207 @@ impl Database {
                      price = $8,
                      date = $9,
                      ticker = $10,
-                     stop_order_id = $11
-                 WHERE id = $12",
-                &[
-                    &status,
-                    &trade.pnl,
-                    &fee_value,
-                    &trade.exit_price,
-                    &exit_date,
-                    &stop_loss_triggered,
-                    &changes_json,
-                    &trade.price,
-                    &trade_date,
-                    &trade.ticker,
-                    &trade.stop_order_id,
-                    &trade.id,
-                ],
+                     stop_order_id = $11,
+                     quantity = $12,
+                     exit_order_id = $13,
+                     target_order_id = $14
+                 WHERE id = $15",
+            &[
+                &status,
+                &trade.pnl,
+                &fee_value,
+                &trade.exit_price,
+                &exit_date,
+                &stop_loss_triggered,
+                &changes_json,
+                &trade.price,
+                &trade_date,
+                &trade.ticker,
+                &trade.stop_order_id,
+                &trade.quantity,
+                &trade.exit_order_id,
+                &trade.target_order_id,
+                &trade.id,
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Inserts a trade split off live trade `parent_id`, owned by the parent's user: the
+    /// closed portion of a partial exit fill or the cancelled rest of a partial entry fill.
+    pub async fn insert_split_trade(&self, parent_id: &str, part: &Trade) -> Result<()> {
+        let changes_json = serde_json::to_string(&part.changes)
+            .map_err(|err| anyhow!("Failed to serialize trade changes: {}", err))?;
+        self.execute(
+            "INSERT INTO trades (id, strategy_id, user_id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, exit_order_id, changes)
+             SELECT $1, strategy_id, user_id, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14
+               FROM trades
+              WHERE id = $15
+             ON CONFLICT (id) DO NOTHING",
+            &[
+                &part.id,
+                &part.ticker,
+                &part.quantity,
+                &part.price,
+                &part.date.date_naive(),
+                &part.status.as_str(),
+                &part.pnl,
+                &part.fee.unwrap_or(0.0),
+                &part.exit_price,
+                &part.exit_date.map(|date| date.date_naive()),
+                &part.stop_loss,
+                &part.stop_loss_triggered.unwrap_or(false),
+                &part.exit_order_id,
+                &changes_json,
+                &parent_id,
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Broker order ids the engine already knows for `account_id`: the entry, stop, exit and
+    /// target orders of its strategies' trades and the orders of its dispatched operations.
+    pub async fn get_account_order_ids(&self, account_id: &str) -> Result<HashSet<String>> {
+        let rows = self
+            .query(
+                "SELECT UNNEST(ARRAY[t.entry_order_id, t.stop_order_id, t.exit_order_id, t.target_order_id])
+                 FROM trades t
+                 INNER JOIN strategies s ON s.id = t.strategy_id
+                 WHERE s.account_id = $1
+                 UNION
+                 SELECT order_id FROM account_operations WHERE account_id = $1",
+                &[&account_id],
             )
             .await?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| row.get::<_, Option<String>>(0))
+            .collect())
+    }
 
-        Ok(())
+    /// Inserts live trades rebuilt from broker history under `strategy_id`, owned by the
+    /// strategy's user. Trades whose id already exists or whose ticker is unknown are skipped;
+    /// returns how many were inserted.
+    pub async fn insert_imported_trades(
+        &self,
+        strategy_id: &str,
+        trades: &[Trade],
+    ) -> Result<usize> {
+        self.with_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            let mut inserted = 0usize;
+            for trade in trades {
+                let changes_json = serde_json::to_string(&trade.changes)
+                    .map_err(|err| anyhow!("Failed to serialize trade changes: {}", err))?;
+                inserted += tx
+                    .execute(
+                        "INSERT INTO trades (id, strategy_id, user_id, ticker, quantity, price, date, status, pnl, fee, exit_price, exit_date, stop_loss, stop_loss_triggered, entry_order_id, stop_order_id, exit_order_id, target_order_id, changes)
+                         SELECT $1, s.id, s.user_id, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17
+                           FROM strategies s
+                          WHERE s.id = $18
+                            AND EXISTS (SELECT 1 FROM tickers WHERE symbol = $2)
+                         ON CONFLICT (id) DO NOTHING",
+                        &[
+                            &trade.id,
+                            &trade.ticker,
+                            &trade.quantity,
+                            &trade.price,
+                            &trade.date.date_naive(),
+                            &trade.status.as_str(),
+                            &trade.pnl,
+                            &trade.fee.unwrap_or(0.0),
+                            &trade.exit_price,
+                            &trade.exit_date.map(|date| date.date_naive()),
+                            &trade.stop_loss,
+                            &trade.stop_loss_triggered.unwrap_or(false),
+                            &trade.entry_order_id,
+                            &trade.stop_order_id,
+                            &trade.exit_order_id,
+                            &trade.target_order_id,
+                            &changes_json,
+                            &strategy_id,
+                        ],
+                    )
+                    .await? as usize;
+            }
+            tx.commit().await?;
+            Ok(inserted)
+        })
+        .await
+    }
+
+    /// Re-encrypts every value in `ENCRYPTED_COLUMNS` from `old_key` to `new_key` in one
+    /// transaction, returning how many values were rewritten. Values already encrypted with
+    /// `new_key` and plaintext values are left untouched.
+    pub async fn rotate_encryption_key(
+        &self,
+        old_key: &[u8; 32],
+        new_key: &[u8; 32],
+    ) -> Result<usize> {
+        let new_key_id = encryption_key_id(new_key);
+        self.with_write_retry(|| async {
+            let mut client = self.client().await?;
+            let tx = client.transaction().await?;
+            let mut rotated = 0usize;
+            for (table, id_column, value_column) in ENCRYPTED_COLUMNS {
+                let rows = tx
+                    .query(
+                        &format!(
+                            "SELECT {id_column}, {value_column} FROM {table}
+                             WHERE {value_column} LIKE 'enc:%'
+                             FOR UPDATE"
+                        ),
+                        &[],
+                    )
+                    .await?;
+                let update =
+                    format!("UPDATE {table} SET {value_column} = $1 WHERE {id_column} = $2");
+                for row in rows {
+                    let id: String = row.get(0);
+                    let value: String = row.get(1);
+                    if !is_encrypted_database_value(&value) {
+                        continue;
+                    }
+                    if let (Some(key_id), _) = split_encrypted_value(&value)? {
+                        if key_id == new_key_id {
+                            continue;
+                        }
+                    }
+                    let plaintext =
+                        decrypt_database_value_with_key(&value, old_key).with_context(|| {
+                            format!("failed to decrypt {}.{} for {}", table, value_column, id)
+                        })?;
+                    let encrypted = encrypt_database_value_with_key(&plaintext, new_key)?;
+                    tx.execute(&update, &[&encrypted, &id]).await?;
+                    rotated += 1;
+                }
+            }
+            tx.commit().await?;
+            Ok(rotated)
+        })
+        .await
     }
 
     pub async fn backtest_cache_entries_for_template(
         &self,
         template_id: &str,
     ) -> Result<Vec<BacktestCacheEntry>> {
-        let rows = self
-            .client
-            .query(
+        let rows = self.query(
                 "SELECT id,
                         template_id,
                         parameters,
                         calmar_ratio,
+                        sharpe_ratio,
+                        cagr,
                         (verify_sharpe_ratio IS NOT NULL
                          AND verify_calmar_ratio IS NOT NULL
                          AND verify_cagr IS NOT NULL
-                         AND verify_max_drawdown_ratio IS NOT NULL) AS verify_complete,
+                         AND verify_max_drawdown_ratio IS NOT NULL
+                         AND verify_cost_scenarios IS NOT NULL) AS verify_complete,
                         (balance_training_sharpe_ratio IS NOT NULL
                          AND balance_training_calmar_ratio IS NOT NULL
                          AND balance_training_cagr IS NOT NULL
@@ -1334,6 +3242,8 @@ impl Database {
                     template_id: row.get("template_id"),
                     parameters,
                     calmar_ratio: row.get("calmar_ratio"),
+                    sharpe_ratio: row.get("sharpe_ratio"),
+                    cagr: row.get("cagr"),
                     verify_complete: row.get("verify_complete"),
                     balance_training_complete: row.get("balance_training_complete"),
                     balance_validation_complete: row.get("balance_validation_complete"),
@@ -1351,38 +3261,51 @@ impl Database {
     pub async fn update_backtest_cache_verification(
         &self,
         cache_id: &str,
-        sharpe_ratio: Option<f64>,
-        calmar_ratio: Option<f64>,
-        cagr: Option<f64>,
-        max_drawdown_ratio: Option<f64>,
+        metrics: VerificationMetrics,
+        cost_scenarios: &Value,
+        cost_robust: bool,
     ) -> Result<()> {
-        let normalize_metric = |value: Option<f64>| -> Option<f64> {
-            value.and_then(|v| if v.is_finite() { Some(v) } else { None })
-        };
+        let normalize_metric = |value: f64| Some(value).filter(|v| v.is_finite());
 
-        let normalized_sharpe = normalize_metric(sharpe_ratio);
-        let normalized_calmar = normalize_metric(calmar_ratio);
-        let normalized_cagr = normalize_metric(cagr);
-        let normalized_dd_ratio = normalize_metric(max_drawdown_ratio);
+        let normalized_sharpe = normalize_metric(metrics.sharpe_ratio);
+        let normalized_calmar = normalize_metric(metrics.calmar_ratio);
+        let normalized_cagr = normalize_metric(metrics.cagr);
+        let normalized_dd_ratio = normalize_metric(metrics.max_drawdown_ratio);
 
-        self.client
-            .execute(
-                "UPDATE backtest_cache
+        self.execute(
+            "UPDATE backtest_cache
                  SET verify_sharpe_ratio = $1,
                      verify_calmar_ratio = $2,
                      verify_cagr = $3,
-                     verify_max_drawdown_ratio = $4
-                 WHERE id = $5",
-                &[
-                    &normalized_sharpe,
-                    &normalized_calmar,
-                    &normalized_cagr,
-                    &normalized_dd_ratio,
-                    &cache_id,
-                ],
-            )
-            .await?;
+                     verify_max_drawdown_ratio = $4,
+                     verify_cost_scenarios = $5,
+                     verify_cost_robust = $6
+                 WHERE id = $7",
+            &[
+                &normalized_sharpe,
+                &normalized_calmar,
+                &normalized_cagr,
+                &normalized_dd_ratio,
+                cost_scenarios,
+                &cost_robust,
+                &cache_id,
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
 
+    pub async fn update_backtest_cache_window_scores(
+        &self,
+        cache_id: &str,
+        window_scores: &Value,
+    ) -> Result<()> {
+        self.execute(
+            "UPDATE backtest_cache SET verify_window_scores = $1 WHERE id = $2",
+            &[window_scores, &cache_id],
+        )
+        .await?;
         Ok(())
     }
 
@@ -1403,23 +3326,22 @@ impl Database {
         let normalized_cagr = normalize_metric(cagr);
         let normalized_dd_ratio = normalize_metric(max_drawdown_ratio);
 
-        self.client
-            .execute(
-                "UPDATE backtest_cache
+        self.execute(
+            "UPDATE backtest_cache
                  SET balance_training_sharpe_ratio = $1,
                      balance_training_calmar_ratio = $2,
                      balance_training_cagr = $3,
                      balance_training_max_drawdown_ratio = $4
                  WHERE id = $5",
-                &[
-                    &normalized_sharpe,
-                    &normalized_calmar,
-                    &normalized_cagr,
-                    &normalized_dd_ratio,
-                    &cache_id,
-                ],
-            )
-            .await?;
+            &[
+                &normalized_sharpe,
+                &normalized_calmar,
+                &normalized_cagr,
+                &normalized_dd_ratio,
+                &cache_id,
+            ],
+        )
+        .await?;
 
         Ok(())
     }
@@ -1441,23 +3363,22 @@ impl Database {
         let normalized_cagr = normalize_metric(cagr);
         let normalized_dd_ratio = normalize_metric(max_drawdown_ratio);
 
-        self.client
-            .execute(
-                "UPDATE backtest_cache
+        self.execute(
+            "UPDATE backtest_cache
                  SET balance_validation_sharpe_ratio = $1,
                      balance_validation_calmar_ratio = $2,
                      balance_validation_cagr = $3,
                      balance_validation_max_drawdown_ratio = $4
                  WHERE id = $5",
-                &[
-                    &normalized_sharpe,
-                    &normalized_calmar,
-                    &normalized_cagr,
-                    &normalized_dd_ratio,
-                    &cache_id,
-                ],
-            )
-            .await?;
+            &[
+                &normalized_sharpe,
+                &normalized_calmar,
+                &normalized_cagr,
+                &normalized_dd_ratio,
+                &cache_id,
+            ],
+        )
+        .await?;
 
         Ok(())
     }
@@ -1472,6 +3393,7 @@ impl Database {
         let entry_cancel_after: Option<DateTime<Utc>> = row.get(14);
         let stop_order_id: Option<String> = row.get(15);
         let exit_order_id: Option<String> = row.get(16);
+        let target_order_id: Option<String> = row.get(17);
         let changes: Vec<TradeChange> = serde_json::from_str(&changes_json)
             .map_err(|err| anyhow!("Failed to parse trade changes JSON: {}", err))?;
         let fee_value: Option<f64> = row.get(7);
@@ -1494,11 +3416,55 @@ impl Database {
             entry_cancel_after,
             stop_order_id,
             exit_order_id,
+            target_order_id,
             changes,
         })
     }
 }
 
+/// Pool checkout and connect failures (SQLSTATE 08001 and 08006 included), which happen
+/// before a statement is sent, and serialization failures and deadlocks, which the server rolls
+/// back. A write failing this way left nothing applied.
+fn is_unapplied_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if cause.is::<deadpool_postgres::PoolError>() {
+            return true;
+        }
+        cause
+            .downcast_ref::<tokio_postgres::Error>()
+            .and_then(tokio_postgres::Error::as_db_error)
+            .is_some_and(|db_error| matches!(db_error.code().code(), "40001" | "40P01"))
+    })
+}
+
+/// `is_unapplied_error` plus connection losses and server shutdowns mid-statement, which a
+/// fresh attempt can succeed on but which may have applied a write; constraint violations and
+/// bad SQL are not retried.
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    if is_unapplied_error(err) {
+        return true;
+    }
+    err.chain().any(|cause| {
+        if cause.is::<std::io::Error>() {
+            return true;
+        }
+        let Some(pg_error) = cause.downcast_ref::<tokio_postgres::Error>() else {
+            return false;
+        };
+        if pg_error.is_closed() {
+            return true;
+        }
+        match pg_error.as_db_error() {
+            Some(db_error) => {
+                let code = db_error.code().code();
+                code.starts_with("08") || matches!(code, "40001" | "40P01" | "57P01" | "57P02")
+            }
+            None => std::error::Error::source(pg_error)
+                .is_some_and(|source| source.is::<std::io::Error>()),
+        }
+    })
+}
+
 fn parse_excluded_tickers(json: &str) -> Vec<String> {
     let parsed: Vec<String> = serde_json::from_str(json).unwrap_or_default();
     let mut seen = HashSet::new();
@@ -1532,14 +3498,49 @@ fn parse_excluded_keywords(json: &str) -> Vec<String> {
 }
 
 fn decrypt_database_value(value: &str) -> Result<String> {
-    if value.is_empty() || !value.starts_with(ENCRYPTION_PREFIX) {
+    if !is_encrypted_database_value(value) {
         return Ok(value.to_string());
     }
 
-    let key = load_database_key()?;
+    let key = load_encryption_key(DATABASE_KEY_ENV_VAR)?;
+    decrypt_database_value_with_key(value, &key)
+}
+
+fn is_encrypted_database_value(value: &str) -> bool {
+    value.starts_with(ENCRYPTION_PREFIX) || value.starts_with(ENCRYPTION_PREFIX_V2)
+}
+
+/// Short identifier stored in `enc:v2:` envelopes so a value can be matched to its key.
+pub fn encryption_key_id(key: &[u8; 32]) -> String {
+    hex::encode(&Sha256::digest(key)[..ENCRYPTION_KEY_ID_BYTES])
+}
+
+/// Splits an encrypted value into its optional key id and the `iv:data:tag` payload.
+fn split_encrypted_value(value: &str) -> Result<(Option<&str>, &str)> {
+    if let Some(payload) = value.strip_prefix(ENCRYPTION_PREFIX) {
+        return Ok((None, payload));
+    }
     let payload = value
-        .strip_prefix(ENCRYPTION_PREFIX)
+        .strip_prefix(ENCRYPTION_PREFIX_V2)
         .ok_or_else(|| anyhow!("Encrypted value has an invalid format."))?;
+    let (key_id, payload) = payload
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Encrypted value has an invalid format."))?;
+    Ok((Some(key_id), payload))
+}
+
+fn decrypt_database_value_with_key(value: &str, key: &[u8; 32]) -> Result<String> {
+    let (key_id, payload) = split_encrypted_value(value)?;
+    if let Some(key_id) = key_id {
+        let expected = encryption_key_id(key);
+        if key_id != expected {
+            return Err(anyhow!(
+                "Encrypted value uses key id {} but the provided key has id {}.",
+                key_id,
+                expected
+            ));
+        }
+    }
     let parts: Vec<&str> = payload.split(':').collect();
     if parts.len() != 3 {
         return Err(anyhow!("Encrypted value has an invalid format."));
@@ -1560,7 +3561,7 @@ fn decrypt_database_value(value: &str) -> Result<String> {
     }
 
     let cipher =
-        Aes256Gcm::new_from_slice(&key).map_err(|_| anyhow!("Failed to initialize cipher."))?;
+        Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("Failed to initialize cipher."))?;
     let nonce = Nonce::from_slice(&iv);
     let mut encrypted = Vec::with_capacity(data.len() + tag.len());
     encrypted.extend_from_slice(&data);
@@ -1572,35 +3573,55 @@ fn decrypt_database_value(value: &str) -> Result<String> {
     String::from_utf8(plaintext).context("Decrypted value is not valid UTF-8.")
 }
 
-fn load_database_key() -> Result<[u8; 32]> {
-    let raw = std::env::var(DATABASE_KEY_ENV_VAR).unwrap_or_default();
+/// Encrypts `value` into an `enc:v2:<key id>:<iv>:<data>:<tag>` envelope.
+fn encrypt_database_value_with_key(value: &str, key: &[u8; 32]) -> Result<String> {
+    let cipher =
+        Aes256Gcm::new_from_slice(key).map_err(|_| anyhow!("Failed to initialize cipher."))?;
+    let iv: [u8; ENCRYPTION_IV_LENGTH] = rand::random();
+    let encrypted = cipher
+        .encrypt(Nonce::from_slice(&iv), value.as_bytes())
+        .map_err(|_| anyhow!("Failed to encrypt value."))?;
+    let (data, tag) = encrypted.split_at(encrypted.len() - ENCRYPTION_TAG_LENGTH);
+    Ok(format!(
+        "{}{}:{}:{}:{}",
+        ENCRYPTION_PREFIX_V2,
+        encryption_key_id(key),
+        BASE64_STANDARD.encode(iv),
+        BASE64_STANDARD.encode(data),
+        BASE64_STANDARD.encode(tag)
+    ))
+}
+
+/// Loads a 32-byte AES key (64 hex characters or base64) from the environment variable `var`.
+pub fn load_encryption_key(var: &str) -> Result<[u8; 32]> {
+    let raw = std::env::var(var).unwrap_or_default();
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return Err(anyhow!(
             "{} is required to encrypt and decrypt secrets. Generate one with \"openssl rand -hex 32\".",
-            DATABASE_KEY_ENV_VAR
+            var
         ));
     }
 
     if trimmed.len() == 64 && trimmed.bytes().all(|byte| byte.is_ascii_hexdigit()) {
-        let decoded = hex::decode(trimmed).map_err(|_| invalid_database_key_error(0))?;
+        let decoded = hex::decode(trimmed).map_err(|_| invalid_database_key_error(var, 0))?;
         let key: [u8; 32] = decoded
             .try_into()
-            .map_err(|decoded: Vec<u8>| invalid_database_key_error(decoded.len()))?;
+            .map_err(|decoded: Vec<u8>| invalid_database_key_error(var, decoded.len()))?;
         return Ok(key);
     }
 
     let decoded = BASE64_STANDARD.decode(trimmed).unwrap_or_default();
     let key: [u8; 32] = decoded
         .try_into()
-        .map_err(|decoded: Vec<u8>| invalid_database_key_error(decoded.len()))?;
+        .map_err(|decoded: Vec<u8>| invalid_database_key_error(var, decoded.len()))?;
     Ok(key)
 }
 
-fn invalid_database_key_error(key_length: usize) -> anyhow::Error {
+fn invalid_database_key_error(var: &str, key_length: usize) -> anyhow::Error {
     anyhow!(
         "{} must be a 32-byte key encoded as 64 hex characters or base64. Key length {}",
-        DATABASE_KEY_ENV_VAR,
+        var,
         key_length
     )
 }
@@ -1634,6 +3655,15 @@ fn serialize_performance(perf: &StrategyPerformance) -> Result<String> {
         "avgWinningPnl": perf.avg_winning_pnl,
         "avgWinningPnlPercent": perf.avg_winning_pnl_percent,
         "lastUpdated": perf.last_updated.to_rfc3339(),
+        "monthlyReturns": perf.monthly_returns,
+        "annualTurnover": perf.annual_turnover,
+        "avgDailyTradedNotional": perf.avg_daily_traded_notional,
+        "capacityAum": perf.capacity_aum,
+        "entryWeekConcentration": perf.entry_week_concentration,
+        "rExpectancy": perf.r_expectancy,
+        "rMultipleHistogram": perf.r_multiple_histogram,
+        "systemQualityNumber": perf.system_quality_number,
+        "holdingPeriodReturns": perf.holding_period_returns,
     });
 
     serde_json::to_string(&value)
@@ -1692,6 +3722,8 @@ fn deserialize_performance(json_str: &str) -> Result<StrategyPerformance> {
             "avgLosingPnlPercent",
             "avgWinningPnl",
             "avgWinningPnlPercent",
+            "annualTurnover",
+            "avgDailyTradedNotional",
         ];
         const INT_FIELDS: &[&str] = &[
             "totalTrades",
@@ -1765,6 +3797,40 @@ fn naive_date_to_datetime(date: NaiveDate) -> DateTime<Utc> {
         .and_utc()
 }
 
+fn clamp_i64_to_i32(value: i64) -> i32 {
+    if value > i32::MAX as i64 {
+        i32::MAX
+    } else if value < i32::MIN as i64 {
+        i32::MIN
+    } else {
+        value as i32
+    }
+}
+
+fn calculate_period_days(start: &DateTime<Utc>, end: &DateTime<Utc>) -> i64 {
+    let start_date = start.date_naive();
+    let end_date = end.date_naive();
+
+    if end_date < start_date {
+        return 0;
+    }
+
+    let diff = (end_date - start_date).num_days();
+    if diff <= 0 {
+        1
+    } else {
+        diff
+    }
+}
+
+fn calculate_period_months(period_days: i64) -> i64 {
+    if period_days <= 0 {
+        0
+    } else {
+        ((period_days as f64) / 30.4).round() as i64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1862,6 +3928,46 @@ mod tests {
         drop(guard);
     }
 
+    #[test]
+    fn enc_v2_values_round_trip_and_check_key_id() {
+        let key = [3u8; 32];
+        let other_key = [4u8; 32];
+        let encrypted = encrypt_database_value_with_key("api-secret", &key).unwrap();
+        assert!(encrypted.starts_with(&format!(
+            "{}{}:",
+            ENCRYPTION_PREFIX_V2,
+            encryption_key_id(&key)
+        )));
+        assert_eq!(
+            decrypt_database_value_with_key(&encrypted, &key).unwrap(),
+            "api-secret"
+        );
+        let error = decrypt_database_value_with_key(&encrypted, &other_key).unwrap_err();
+        assert!(error.to_string().contains("key id"));
+
+        let legacy = encrypt_test_value("api-secret", &key);
+        assert_eq!(
+            decrypt_database_value_with_key(&legacy, &key).unwrap(),
+            "api-secret"
+        );
+    }
+
+    #[test]
+    fn account_credentials_debug_output_redacts_secrets() {
+        let credentials = AccountCredentials {
+            id: "acct-1".to_string(),
+            provider: "alpaca".to_string(),
+            environment: "paper".to_string(),
+            api_key: SecretString::new("PKTESTKEY".to_string()),
+            api_secret: SecretString::new("topsecret".to_string()),
+        };
+        let printed = format!("{:?}", credentials);
+        assert!(printed.contains("acct-1"));
+        assert!(!printed.contains("PKTESTKEY"));
+        assert!(!printed.contains("topsecret"));
+        assert_eq!(credentials.api_secret.expose(), "topsecret");
+    }
+
     fn encrypt_test_value(value: &str, key: &[u8; 32]) -> String {
         let iv: [u8; ENCRYPTION_IV_LENGTH] = [7, 10, 14, 22, 31, 33, 46, 59, 61, 72, 81, 99];
         let cipher =
@@ -1884,38 +3990,20 @@ mod tests {
             BASE64_STANDARD.encode(tag)
         )
     }
-}
-
-fn clamp_i64_to_i32(value: i64) -> i32 {
-    if value > i32::MAX as i64 {
-        i32::MAX
-    } else if value < i32::MIN as i64 {
-        i32::MIN
-    } else {
-        value as i32
-    }
-}
-
-fn calculate_period_days(start: &DateTime<Utc>, end: &DateTime<Utc>) -> i64 {
-    let start_date = start.date_naive();
-    let end_date = end.date_naive();
-
-    if end_date < start_date {
-        return 0;
-    }
-
-    let diff = (end_date - start_date).num_days();
-    if diff <= 0 {
-        1
-    } else {
-        diff
-    }
-}
 
-fn calculate_period_months(period_days: i64) -> i64 {
-    if period_days <= 0 {
-        0
-    } else {
-        ((period_days as f64) / 30.4).round() as i64
+    #[test]
+    fn only_connection_failures_are_transient() {
+        let io_failure = anyhow::Error::new(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "connection reset by peer",
+        ))
+        .context("loading candles");
+        assert!(is_transient_error(&io_failure));
+        assert!(!is_unapplied_error(&io_failure));
+        let checkout_failure = anyhow::Error::new(deadpool_postgres::PoolError::Timeout(
+            deadpool_postgres::TimeoutType::Wait,
+        ));
+        assert!(is_unapplied_error(&checkout_failure));
+        assert!(!is_transient_error(&anyhow!("duplicate key value")));
     }
 }
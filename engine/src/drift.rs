@@ -0,0 +1,212 @@
+use crate::config::DriftToleranceSettings;
+use crate::engine::AccountStateSnapshot;
+use crate::models::{Trade, TradeStatus};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    Quantity,
+    Stop,
+}
+
+impl DriftKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Quantity => "quantity",
+            Self::Stop => "stop",
+        }
+    }
+}
+
+/// One divergence between what the account's active trades expect and what the broker holds.
+/// `actual` is `None` when the broker has no stop order for the ticker at all.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionDrift {
+    pub ticker: String,
+    pub kind: DriftKind,
+    pub trade_id: Option<String>,
+    pub expected: f64,
+    pub actual: Option<f64>,
+}
+
+impl PositionDrift {
+    pub fn describe(&self) -> String {
+        let actual = self
+            .actual
+            .map(|value| format!("{}", value))
+            .unwrap_or_else(|| "none".to_string());
+        match &self.trade_id {
+            Some(trade_id) => format!(
+                "{} {} drift on trade {}: expected {}, broker {}",
+                self.ticker,
+                self.kind.label(),
+                trade_id,
+                self.expected,
+                actual
+            ),
+            None => format!(
+                "{} {} drift: expected {}, broker {}",
+                self.ticker,
+                self.kind.label(),
+                self.expected,
+                actual
+            ),
+        }
+    }
+}
+
+/// Compares active trades against a broker snapshot. A ticker drifts on quantity when the
+/// summed trade quantity and the broker position differ by more than the tolerance, and a
+/// trade drifts on its stop when no broker stop order for the ticker lies within the stop
+/// tolerance ratio of the trade's stop loss.
+pub fn detect_position_drift(
+    trades: &[Trade],
+    state: &AccountStateSnapshot,
+    tolerance: &DriftToleranceSettings,
+) -> Vec<PositionDrift> {
+    let active: Vec<&Trade> = trades
+        .iter()
+        .filter(|trade| trade.status == TradeStatus::Active)
+        .collect();
+
+    let mut quantities: BTreeMap<&str, (i32, i32)> = BTreeMap::new();
+    for trade in &active {
+        quantities.entry(&trade.ticker).or_default().0 += trade.quantity;
+    }
+    for position in &state.positions {
+        quantities.entry(&position.ticker).or_default().1 += position.quantity;
+    }
+    let mut drifts: Vec<PositionDrift> = quantities
+        .into_iter()
+        .filter(|(_, (expected, held))| (expected - held).abs() > tolerance.quantity)
+        .map(|(ticker, (expected, held))| PositionDrift {
+            ticker: ticker.to_string(),
+            kind: DriftKind::Quantity,
+            trade_id: None,
+            expected: expected as f64,
+            actual: Some(held as f64),
+        })
+        .collect();
+
+    for trade in &active {
+        let Some(stop_loss) = trade
+            .stop_loss
+            .filter(|stop| stop.is_finite() && *stop > 0.0)
+        else {
+            continue;
+        };
+        let nearest = state
+            .stop_orders
+            .get(&trade.ticker)
+            .into_iter()
+            .flatten()
+            .map(|order| order.stop_price)
+            .min_by(|a, b| (a - stop_loss).abs().total_cmp(&(b - stop_loss).abs()));
+        let within = nearest
+            .is_some_and(|price| (price - stop_loss).abs() / stop_loss <= tolerance.stop_ratio);
+        if !within {
+            drifts.push(PositionDrift {
+                ticker: trade.ticker.clone(),
+                kind: DriftKind::Stop,
+                trade_id: Some(trade.id.clone()),
+                expected: stop_loss,
+                actual: nearest,
+            });
+        }
+    }
+    drifts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{AccountPositionState, AccountStopOrderState};
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn trade(id: &str, ticker: &str, quantity: i32, stop_loss: Option<f64>) -> Trade {
+        Trade {
+            id: id.to_string(),
+            strategy_id: "strategy".to_string(),
+            ticker: ticker.to_string(),
+            quantity,
+            price: 100.0,
+            date: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            status: TradeStatus::Active,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss,
+            stop_loss_triggered: None,
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn flags_quantity_and_stop_drift_beyond_tolerance() {
+        let position = |ticker: &str, quantity| AccountPositionState {
+            ticker: ticker.to_string(),
+            quantity,
+            avg_entry_price: 100.0,
+            current_price: None,
+        };
+        let stop = |stop_price| AccountStopOrderState {
+            quantity: 10,
+            stop_price,
+            side: "sell".to_string(),
+        };
+        let state = AccountStateSnapshot {
+            available_cash: 1_000.0,
+            buying_power: None,
+            held_tickers: Default::default(),
+            open_buy_orders: Default::default(),
+            open_sell_orders: Default::default(),
+            positions: vec![position("AAA", 11), position("BBB", 7), position("CCC", 10)],
+            stop_orders: HashMap::from([
+                ("AAA".to_string(), vec![stop(90.2)]),
+                ("BBB".to_string(), vec![stop(80.0), stop(93.0)]),
+            ]),
+            equity: None,
+            last_equity: None,
+            circuit_breaker_triggered_at: None,
+            risk_overrides: Default::default(),
+            fx: Default::default(),
+        };
+        let trades = vec![
+            trade("t1", "AAA", 10, Some(90.0)),
+            trade("t2", "BBB", 10, Some(95.0)),
+            trade("t3", "CCC", 10, Some(90.0)),
+        ];
+        let tolerance = DriftToleranceSettings {
+            quantity: 1,
+            stop_ratio: 0.005,
+        };
+
+        let drifts = detect_position_drift(&trades, &state, &tolerance);
+        let summary: Vec<(&str, DriftKind, Option<f64>)> = drifts
+            .iter()
+            .map(|drift| (drift.ticker.as_str(), drift.kind, drift.actual))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("BBB", DriftKind::Quantity, Some(7.0)),
+                ("BBB", DriftKind::Stop, Some(93.0)),
+                ("CCC", DriftKind::Stop, None),
+            ]
+        );
+        assert_eq!(
+            drifts[2].describe(),
+            "CCC stop drift on trade t3: expected 90, broker none"
+        );
+    }
+}
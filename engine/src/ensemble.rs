@@ -0,0 +1,225 @@
+use crate::models::{AccountSignalSkip, GeneratedSignal, SignalAction};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Skip reason for a buy that fewer than the quorum of the account's strategies agreed on.
+pub const ENSEMBLE_DISAGREEMENT_REASON: &str = "ensemble_disagreement";
+/// Skip reason for an agreed buy that a more confident peer strategy opens instead.
+pub const ENSEMBLE_PEER_ENTRY_REASON: &str = "ensemble_peer_entry";
+
+#[derive(Debug, Clone)]
+struct Vote {
+    strategy_id: String,
+    confidence: Option<f64>,
+}
+
+/// Buy votes of every strategy driving one ensemble account. A buy opens only when at least
+/// `quorum` member strategies emitted a buy for the same ticker and date; the agreed buy is
+/// planned by the most confident voter so the account opens a single position.
+#[derive(Debug, Clone)]
+pub struct EnsembleVotes {
+    quorum: usize,
+    members: Vec<String>,
+    votes: HashMap<(String, DateTime<Utc>), Vec<Vote>>,
+}
+
+impl EnsembleVotes {
+    pub fn new(quorum: usize) -> Self {
+        Self {
+            quorum: quorum.max(1),
+            members: Vec::new(),
+            votes: HashMap::new(),
+        }
+    }
+
+    pub fn quorum(&self) -> usize {
+        self.quorum
+    }
+
+    pub fn member_count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Records one member's signals; repeated buys for the same ticker and date count once.
+    pub fn record(&mut self, strategy_id: &str, signals: &[GeneratedSignal]) {
+        if !self.members.iter().any(|member| member == strategy_id) {
+            self.members.push(strategy_id.to_string());
+        }
+        for signal in signals
+            .iter()
+            .filter(|signal| matches!(signal.action, SignalAction::Buy))
+        {
+            let ticker = signal.ticker.trim().to_uppercase();
+            if ticker.is_empty() {
+                continue;
+            }
+            let voters = self.votes.entry((ticker, signal.date)).or_default();
+            match voters
+                .iter_mut()
+                .find(|vote| vote.strategy_id == strategy_id)
+            {
+                Some(vote) => {
+                    vote.confidence = max_confidence(vote.confidence, signal.confidence);
+                }
+                None => voters.push(Vote {
+                    strategy_id: strategy_id.to_string(),
+                    confidence: signal.confidence,
+                }),
+            }
+        }
+    }
+
+    /// Number of distinct ticker/date buys that reached the quorum.
+    pub fn agreed_count(&self) -> usize {
+        self.votes
+            .values()
+            .filter(|voters| voters.len() >= self.quorum)
+            .count()
+    }
+
+    /// Keeps `strategy_id`'s sell and hold signals and the agreed buys it owns; every other buy
+    /// becomes a skip whose details carry the vote as JSON.
+    pub fn filter_signals(
+        &self,
+        strategy_id: &str,
+        signals: Vec<GeneratedSignal>,
+    ) -> (Vec<GeneratedSignal>, Vec<AccountSignalSkip>) {
+        let mut kept = Vec::with_capacity(signals.len());
+        let mut skips = Vec::new();
+        for signal in signals {
+            if !matches!(signal.action, SignalAction::Buy) {
+                kept.push(signal);
+                continue;
+            }
+            let ticker = signal.ticker.trim().to_uppercase();
+            let voters = self
+                .votes
+                .get(&(ticker.clone(), signal.date))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let voter_ids: Vec<&str> = voters
+                .iter()
+                .map(|vote| vote.strategy_id.as_str())
+                .collect();
+            if voters.len() < self.quorum {
+                skips.push(AccountSignalSkip {
+                    ticker,
+                    signal_date: signal.date,
+                    action: SignalAction::Buy,
+                    reason: ENSEMBLE_DISAGREEMENT_REASON.to_string(),
+                    details: Some(
+                        json!({
+                            "votes": voters.len(),
+                            "quorum": self.quorum,
+                            "members": self.members.len(),
+                            "voters": voter_ids,
+                        })
+                        .to_string(),
+                    ),
+                });
+                continue;
+            }
+            match owner(voters) {
+                Some(owner) if owner != strategy_id => skips.push(AccountSignalSkip {
+                    ticker,
+                    signal_date: signal.date,
+                    action: SignalAction::Buy,
+                    reason: ENSEMBLE_PEER_ENTRY_REASON.to_string(),
+                    details: Some(
+                        json!({
+                            "owner": owner,
+                            "votes": voters.len(),
+                            "quorum": self.quorum,
+                        })
+                        .to_string(),
+                    ),
+                }),
+                _ => kept.push(signal),
+            }
+        }
+        (kept, skips)
+    }
+}
+
+fn max_confidence(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Most confident voter; missing confidence ranks last and ties go to the lowest strategy id.
+fn owner(voters: &[Vote]) -> Option<&str> {
+    voters
+        .iter()
+        .max_by(|a, b| {
+            let a_confidence = a.confidence.filter(|value| value.is_finite());
+            let b_confidence = b.confidence.filter(|value| value.is_finite());
+            a_confidence
+                .partial_cmp(&b_confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.strategy_id.cmp(&a.strategy_id))
+        })
+        .map(|vote| vote.strategy_id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnsembleVotes, ENSEMBLE_DISAGREEMENT_REASON, ENSEMBLE_PEER_ENTRY_REASON};
+    use crate::models::{GeneratedSignal, SignalAction};
+    use chrono::{TimeZone, Utc};
+
+    fn signal(ticker: &str, action: SignalAction, confidence: Option<f64>) -> GeneratedSignal {
+        GeneratedSignal {
+            date: Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            ticker: ticker.to_string(),
+            action,
+            confidence,
+        }
+    }
+
+    #[test]
+    fn agreed_buys_go_to_the_most_confident_voter() {
+        let a = vec![
+            signal("AAPL", SignalAction::Buy, Some(0.6)),
+            signal("MSFT", SignalAction::Buy, Some(0.9)),
+            signal("TSLA", SignalAction::Sell, None),
+        ];
+        let b = vec![signal("aapl", SignalAction::Buy, Some(0.8))];
+        let c = vec![signal("AAPL", SignalAction::Buy, None)];
+        let mut votes = EnsembleVotes::new(2);
+        votes.record("a", &a);
+        votes.record("b", &b);
+        votes.record("c", &c);
+        assert_eq!(votes.member_count(), 3);
+        assert_eq!(votes.agreed_count(), 1);
+
+        let (kept, skips) = votes.filter_signals("a", a);
+        let kept: Vec<&str> = kept.iter().map(|signal| signal.ticker.as_str()).collect();
+        assert_eq!(kept, ["TSLA"]);
+        let reasons: Vec<(&str, &str)> = skips
+            .iter()
+            .map(|skip| (skip.ticker.as_str(), skip.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                ("AAPL", ENSEMBLE_PEER_ENTRY_REASON),
+                ("MSFT", ENSEMBLE_DISAGREEMENT_REASON)
+            ]
+        );
+        assert!(skips[1].details.as_deref().unwrap().contains("\"votes\":1"));
+
+        let (kept, skips) = votes.filter_signals("b", b);
+        assert_eq!(kept.len(), 1);
+        assert!(skips.is_empty());
+        let (kept, skips) = votes.filter_signals("c", c);
+        assert!(kept.is_empty());
+        assert!(skips[0]
+            .details
+            .as_deref()
+            .unwrap()
+            .contains("\"owner\":\"b\""));
+    }
+}
@@ -1,23 +1,31 @@
+pub mod alerts;
 pub mod alpaca;
 pub mod app_url;
 pub mod backtest_api_client;
 pub mod backtester;
+pub mod broker;
 pub mod cache;
-pub mod candle_utils;
+pub mod cli_config;
 pub mod commands;
-pub mod config;
 pub mod context;
 pub mod data_context;
 pub mod database;
-pub mod engine;
-pub mod indicators;
-pub mod models;
+pub mod drift;
+pub mod ensemble;
+pub mod logging;
+pub mod memory_guard;
+pub mod metrics;
+pub mod notifications;
 pub mod optimizer;
 pub mod optimizer_status;
-pub mod param_utils;
-pub mod performance;
+pub mod paper_broker;
 pub mod retry;
+pub mod settings_layers;
 pub mod signals;
-pub mod strategy;
-pub mod strategy_utils;
-pub mod trading_rules;
+pub mod storage;
+pub mod xlsx;
+
+pub use stratcraft_core::{
+    benchmark, blackout, cancellation, candle_utils, config, engine, fx, indicators, models,
+    param_utils, performance, strategy, strategy_utils, trading_rules,
+};
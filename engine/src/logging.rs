@@ -0,0 +1,236 @@
+use crate::database::Database;
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use log::kv::{self, VisitSource};
+use log::{Level, Log, Metadata, Record};
+use serde_json::{json, Map, Value};
+use std::io::Write;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+const SYSTEM_LOG_SOURCE: &str = "engine";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+struct MirroredLog {
+    level: &'static str,
+    message: String,
+    metadata: Value,
+}
+
+static MIRROR_SENDER: Mutex<Option<UnboundedSender<MirroredLog>>> = Mutex::new(None);
+static PROGRESS_SENDER: Mutex<Option<UnboundedSender<String>>> = Mutex::new(None);
+
+/// Wraps env_logger so warnings and errors can also be forwarded to `system_logs` once a
+/// mirror is started.
+struct EngineLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for EngineLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        if is_database_driver(record.target()) {
+            return;
+        }
+        if record.level() <= Level::Warn {
+            mirror_record(record);
+        }
+        if record.level() <= Level::Info {
+            tap_record(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs the global logger (`RUST_LOG` filters, `info` by default).
+pub fn init(format: LogFormat) {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"));
+    if format == LogFormat::Json {
+        builder.format(|buf, record| writeln!(buf, "{}", json_line(record)));
+    }
+    let inner = builder.build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(EngineLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Background writer that copies warnings and errors into `system_logs`.
+pub struct SystemLogMirror {
+    handle: JoinHandle<()>,
+}
+
+impl SystemLogMirror {
+    pub fn start(db: Database) -> Self {
+        let (sender, mut receiver) = unbounded_channel::<MirroredLog>();
+        *MIRROR_SENDER.lock().unwrap_or_else(|err| err.into_inner()) = Some(sender);
+        let handle = tokio::spawn(async move {
+            while let Some(entry) = receiver.recv().await {
+                if let Err(err) = db
+                    .insert_system_log(
+                        SYSTEM_LOG_SOURCE,
+                        entry.level,
+                        &entry.message,
+                        Some(entry.metadata),
+                    )
+                    .await
+                {
+                    // Logging here would feed the failure back into the mirror.
+                    eprintln!("Failed to mirror log entry into system_logs: {err}");
+                }
+            }
+        });
+        Self { handle }
+    }
+
+    /// Stops accepting entries and waits until the queued ones are written.
+    pub async fn finish(self) {
+        MIRROR_SENDER
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        let _ = self.handle.await;
+    }
+}
+
+/// Copies info and more severe log messages to a channel while alive, so `engine worker` can
+/// report what the running job is doing.
+pub struct ProgressTap {
+    _private: (),
+}
+
+impl ProgressTap {
+    /// Starts the tap; the receiver ends once the tap is dropped.
+    pub fn start() -> (Self, UnboundedReceiver<String>) {
+        let (sender, receiver) = unbounded_channel();
+        *PROGRESS_SENDER
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(sender);
+        (Self { _private: () }, receiver)
+    }
+}
+
+impl Drop for ProgressTap {
+    fn drop(&mut self) {
+        PROGRESS_SENDER
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+    }
+}
+
+fn tap_record(record: &Record) {
+    let guard = PROGRESS_SENDER
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    if let Some(sender) = guard.as_ref() {
+        let _ = sender.send(record.args().to_string());
+    }
+}
+
+fn mirror_record(record: &Record) {
+    let guard = MIRROR_SENDER.lock().unwrap_or_else(|err| err.into_inner());
+    let Some(sender) = guard.as_ref() else {
+        return;
+    };
+    let level = match record.level() {
+        Level::Error => "error",
+        _ => "warn",
+    };
+    let metadata = json!({
+        "module": record.module_path().unwrap_or(record.target()),
+        "file": record.file(),
+        "line": record.line(),
+        "fields": record_fields(record),
+    });
+    let _ = sender.send(MirroredLog {
+        level,
+        message: record.args().to_string(),
+        metadata,
+    });
+}
+
+fn is_database_driver(target: &str) -> bool {
+    target.starts_with("tokio_postgres") || target.starts_with("postgres")
+}
+
+fn json_line(record: &Record) -> Value {
+    json!({
+        "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "level": record.level().as_str(),
+        "module": record.module_path().unwrap_or(record.target()),
+        "message": record.args().to_string(),
+        "fields": record_fields(record),
+    })
+}
+
+struct FieldCollector(Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: kv::Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(number) = value.to_i64() {
+            Value::from(number)
+        } else if let Some(number) = value.to_f64() {
+            Value::from(number)
+        } else if let Some(flag) = value.to_bool() {
+            Value::from(flag)
+        } else {
+            Value::from(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+fn record_fields(record: &Record) -> Value {
+    let mut collector = FieldCollector(Map::new());
+    let _ = record.key_values().visit(&mut collector);
+    Value::Object(collector.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_line_includes_structured_fields() {
+        let args = format_args!("Planned {} operations", 3);
+        let fields: [(&str, kv::Value); 2] = [
+            ("strategy", kv::Value::from("alpha")),
+            ("count", kv::Value::from(3)),
+        ];
+        let record = Record::builder()
+            .args(args)
+            .level(Level::Warn)
+            .target("engine::commands::plan_operations")
+            .module_path_static(Some("engine::commands::plan_operations"))
+            .key_values(&fields)
+            .build();
+
+        let line = json_line(&record);
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["module"], "engine::commands::plan_operations");
+        assert_eq!(line["message"], "Planned 3 operations");
+        assert_eq!(line["fields"]["strategy"], "alpha");
+        assert_eq!(line["fields"]["count"], 3);
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+}
@@ -1,15 +1,44 @@
 use anyhow::{anyhow, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use engine::{
+    cancellation::CancellationToken,
+    cli_config::{load_cli_defaults, resolve_thread_count, CliDefaults},
     commands::{
-        backtest_accounts, backtest_active, balance, export_market_data, generate_signals,
-        optimize, plan_operations, reconcile_trades, train_lightgbm, verify,
+        audit_lookahead, backtest_accounts, backtest_active, balance,
+        bench::{self, BenchOptions},
+        calibration_report, capital_scaling,
+        check_data::{self, CheckDataOptions},
+        config_show, detect_drift, execution_quality, export_market_data,
+        export_trades::{self, ExportTradesOptions, JournalFormat},
+        exposure::{self, ExposureOptions},
+        generate_signals, idle_tickers, import_auxiliary,
+        import_broker_history::{self, ImportBrokerHistoryOptions},
+        import_distributions, import_earnings, import_economic_events, import_sentiment,
+        import_short_interest, import_universe,
+        lightgbm_permutation::{self, DEFAULT_PERMUTATION_SEED},
+        optimize, pipeline,
+        plan_operations::{self, PlanOperationsOptions},
+        prune_backtests::{self, PruneBacktestsOptions},
+        reconcile_trades, rotate_key,
+        sensitivity::{self, SensitivityFormat, SensitivityMetric, SensitivityOptions},
+        serve, set_fx_rate, shadow_compare, signal_stats,
+        train_lightgbm::{
+            self, LabelingMethod, LabelingOptions, LightgbmHyperparameters, SampleWeighting,
+            TripleBarrierConfig,
+        },
+        train_nn::{self, NeuralNetHyperparameters},
+        try_parameters, verify, watch_orders, what_if,
+        worker::{self, WorkerOptions},
     },
-    context::AppContext,
-    strategy,
+    context::{AppContext, MarketDataFilters},
+    data_context::DEFAULT_SNAPSHOT_COMPRESSION_LEVEL,
+    logging::{self, LogFormat, SystemLogMirror},
+    settings_layers::SettingLayers,
+    strategy::{self, lightgbm::features::FeatureSet},
 };
-use log::{info, warn};
+use log::{error, info, warn};
 use std::env;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 const DEFAULT_LGBM_MODEL_REL_PATH: &str = "src/models/lightgbm_model.txt";
@@ -19,6 +48,27 @@ const DEFAULT_MARKET_DATA_FILE: &str = "../data/market-data.bin";
 #[command(name = "engine")]
 #[command(about = "A high-performance strategy optimization tool")]
 struct Cli {
+    /// Worker threads for parallel workloads (defaults to ENGINE_THREADS, then the config file, then available parallelism)
+    #[arg(long, global = true, value_name = "N")]
+    threads: Option<usize>,
+    /// CLI defaults file (defaults to ./stratcraft.toml when present)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Config file environment whose values override the top-level defaults (defaults to STRATCRAFT_ENV)
+    #[arg(long = "env", global = true, value_name = "NAME")]
+    environment: Option<String>,
+    /// Engine runtime settings file layered beneath environment and database settings (defaults to ./engine.toml when present)
+    #[arg(long, global = true, value_name = "PATH")]
+    engine_config: Option<PathBuf>,
+    /// Log output format; json also mirrors warnings and errors into system_logs
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+    /// Local SQLite store for settings and the backtest cache, letting optimize and verify run without PostgreSQL
+    #[arg(long, global = true, value_name = "PATH")]
+    sqlite_file: Option<PathBuf>,
+    /// Directory persisting cross-sectional feature contexts between runs over the same candles
+    #[arg(long, global = true, value_name = "DIR")]
+    cross_sectional_cache_dir: Option<PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
@@ -32,6 +82,21 @@ enum Commands {
         /// Path to the market data snapshot file
         #[arg(long = "data-file", value_name = "PATH")]
         data_file: Option<PathBuf>,
+        /// Stop after this many backtest evaluations
+        #[arg(long = "max-evals", value_name = "COUNT")]
+        max_evals: Option<usize>,
+        /// Stop once the search has run this many hours
+        #[arg(long = "max-hours", value_name = "HOURS")]
+        max_hours: Option<f64>,
+        /// Stop after this many evaluations without the best objective improving by the epsilon
+        #[arg(long = "plateau-evals", value_name = "COUNT")]
+        plateau_evals: Option<usize>,
+        /// Smallest objective improvement that counts as progress for --plateau-evals
+        #[arg(long = "plateau-epsilon", value_name = "VALUE", default_value_t = optimize::DEFAULT_PLATEAU_EPSILON)]
+        plateau_epsilon: f64,
+        /// Resident memory limit; parallelism shrinks near it and the run fails at it
+        #[arg(long = "max-memory-gb", value_name = "GB")]
+        max_memory_gb: Option<f64>,
     },
     /// Verify top cached parameter sets over the configured verification window across all tickers
     Verify {
@@ -40,6 +105,75 @@ enum Commands {
         /// Path to the market data snapshot file
         #[arg(long = "data-file", value_name = "PATH")]
         data_file: Option<PathBuf>,
+        /// Instead of cost scenarios, re-score the best cached sets on K disjoint ticker subsets
+        #[arg(long = "ticker-folds", value_name = "K")]
+        ticker_folds: Option<usize>,
+        /// Instead of cost scenarios, re-score the best cached sets on noise-perturbed prices
+        #[arg(long, conflicts_with = "ticker_folds")]
+        perturb: bool,
+        /// Re-verify the best cached sets on N successive windows after the optimizer training window
+        #[arg(
+            long = "anchored-windows",
+            value_name = "N",
+            conflicts_with_all = ["ticker_folds", "perturb"]
+        )]
+        anchored_windows: Option<usize>,
+    },
+    /// Grid a cached objective over two parameters to see whether the optimum is a plateau or a spike
+    Sensitivity {
+        /// Template ID whose cached backtests to read
+        template_id: String,
+        /// Parameter on the grid's x axis
+        #[arg(long = "param-x", value_name = "NAME")]
+        param_x: String,
+        /// Parameter on the grid's y axis
+        #[arg(long = "param-y", value_name = "NAME")]
+        param_y: String,
+        /// Cached metric to plot
+        #[arg(long, value_enum, default_value_t = SensitivityMetric::Cagr)]
+        metric: SensitivityMetric,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = SensitivityFormat::Csv)]
+        format: SensitivityFormat,
+        /// Write the grid to this file instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Check whether a template's best cached parameter set collapses when its signals are delayed a day
+    AuditLookahead {
+        /// Template ID whose best cached parameter set to audit
+        template_id: String,
+        /// Path to the market data snapshot file
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
+    },
+    /// Backtest a template's best cached parameter set across a ladder of initial capitals
+    CapitalScaling {
+        /// Template ID whose best cached parameter set to backtest
+        template_id: String,
+        /// Path to the market data snapshot file
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
+        /// Comma separated initial capitals (defaults to 1k through 1M)
+        #[arg(long, value_delimiter = ',')]
+        capitals: Vec<f64>,
+    },
+    /// Backtest one parameter set of a template and print its metrics and equity curve without storing anything
+    TryParameters {
+        /// Template ID to backtest
+        template_id: String,
+        /// Parameters to test as a JSON object; template defaults fill in the rest
+        #[arg(long, value_name = "JSON", default_value = "{}")]
+        parameters: String,
+        /// First candle date to backtest (default: first snapshot date)
+        #[arg(long = "start-date", value_name = "YYYY-MM-DD")]
+        start_date: Option<chrono::NaiveDate>,
+        /// Last candle date to backtest (default: last snapshot date)
+        #[arg(long = "end-date", value_name = "YYYY-MM-DD")]
+        end_date: Option<chrono::NaiveDate>,
+        /// Path to the market data snapshot file
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
     },
     /// Compute training/validation balance metrics for cached parameter sets
     Balance {
@@ -59,166 +193,1012 @@ enum Commands {
         /// Comma or space separated list of months of history to include (approx. 30.4 days per month)
         #[arg(value_delimiter = ',', num_args = 1..)]
         months: Vec<u32>,
+        /// Backtest only these tickers (comma separated) instead of the scope's tickers
+        #[arg(long, value_delimiter = ',')]
+        tickers: Vec<String>,
+        /// Backtest only the tickers listed in this file (comma, space or newline separated, # comments)
+        #[arg(long)]
+        tickers_file: Option<PathBuf>,
+        /// Leave out the tickers listed in this file
+        #[arg(long)]
+        exclude_file: Option<PathBuf>,
     },
     /// Backtest strategies linked to live accounts using all tickers
-    BacktestAccounts,
+    BacktestAccounts {
+        /// Only backtest the strategies of this account
+        #[arg(long = "account", value_name = "ID")]
+        account_id: Option<String>,
+    },
     /// Rebuild account operations for strategies that have both account and start date defined
-    PlanOperations,
+    PlanOperations {
+        /// Plan without writing operations, skip reasons or logs to the database
+        #[arg(long)]
+        dry_run: bool,
+        /// Print a per-signal decision trace with sizing inputs and stops
+        #[arg(long)]
+        explain: bool,
+        /// Only plan the strategies of this account
+        #[arg(long = "account", value_name = "ID")]
+        account_id: Option<String>,
+    },
+    /// Print one account's plan with hypothetical signals or cash, without writing anything
+    WhatIf {
+        /// Account whose strategies to plan
+        #[arg(long, value_name = "ID")]
+        account: String,
+        /// Extra signal on the plan date, e.g. BUY:AAPL; repeatable
+        #[arg(long = "signal", value_name = "ACTION:TICKER")]
+        signals: Vec<String>,
+        /// Plan with this much cash and buying power instead of the broker's
+        #[arg(long)]
+        cash: Option<f64>,
+    },
     /// Reconcile live trades with broker order states
     ReconcileTrades,
+    /// Flag, cancel or resubmit broker orders stuck before routing past STUCK_ORDER_TIMEOUT_MINUTES
+    WatchOrders,
+    /// Snapshot each account's broker state for the day and raise reconciliation tasks where positions or stops drift from active trades
+    DetectDrift,
+    /// Replay each account strategy's production signals through the engine and alert when live P&L drifts from the shadow P&L
+    ShadowCompare,
+    /// Check candles for calendar gaps, impossible prices, unexplained jumps and stale tickers and write a quality report
+    CheckData {
+        /// Check this market data snapshot instead of the candles table
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
+        /// Report file
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            default_value = "data-quality-report.json"
+        )]
+        output: PathBuf,
+        /// Close-to-close move, as a fraction, reported as a possible unadjusted split
+        #[arg(long, default_value_t = 0.5)]
+        jump_threshold: f64,
+        /// Sessions a ticker may lag the latest candles before it is reported as stale
+        #[arg(long, default_value_t = 5)]
+        stale_sessions: usize,
+        /// Move candles with impossible prices from the candles table to candle_quarantine
+        #[arg(long)]
+        quarantine: bool,
+    },
+    /// Report tickers with no signals or trades from any active strategy and the candle data they hold
+    IdleTickers {
+        /// Months of recent history to check for activity (approx. 30.4 days per month)
+        #[arg(long, default_value_t = 6)]
+        months: u32,
+    },
+    /// Print reliability curves of a model's raw and calibrated probabilities on the validation tickers
+    CalibrationReport {
+        /// Registered LightGBM or neural network model id (default LightGBM model when omitted)
+        #[arg(long = "model", value_name = "ID")]
+        model_id: Option<String>,
+        /// Number of equal-width probability bins
+        #[arg(long, default_value_t = 10)]
+        bins: usize,
+    },
+    /// Score the realized forward returns of active strategies' recent signals (hit rate, return by confidence decile and holding day) and store them in signal_stats
+    SignalStats {
+        /// Months of signal history to evaluate (approx. 30.4 days per month)
+        #[arg(long, default_value_t = 6)]
+        months: u32,
+        /// Holding days to follow each signal for
+        #[arg(long, default_value_t = 10)]
+        horizon: usize,
+    },
+    /// Delete superseded backtest results and compact daily snapshots of older kept runs to weekly
+    PruneBacktests {
+        /// Results to keep per strategy, ticker scope and window, newest first
+        #[arg(long, default_value_t = 1)]
+        keep_last: usize,
+        /// Never delete results of active strategies (older ones are still compacted)
+        #[arg(long)]
+        keep_active: bool,
+        /// Report what would be deleted and compacted without changing the database
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Report an account's net/gross exposure, asset-type weights, leverage and cash drag, and log it for the dashboard
+    Exposure {
+        /// Account to report on
+        #[arg(long = "account", value_name = "ID")]
+        account_id: String,
+        /// Share of equity above which a single position is flagged as concentrated
+        #[arg(long = "max-weight", default_value_t = 0.2)]
+        max_weight: f64,
+    },
+    /// Re-encrypt stored secrets under DATABASE_KEY, reading the current ciphertexts with the key in --old-key-env
+    RotateKey {
+        /// Environment variable holding the key the secrets are currently encrypted with
+        #[arg(long, value_name = "VAR")]
+        old_key_env: String,
+    },
+    /// Store the exchange rate between two currencies for a day
+    SetFxRate {
+        /// Currency being priced, e.g. EUR
+        #[arg(long, value_name = "CODE")]
+        base: String,
+        /// Currency the rate is quoted in, e.g. USD
+        #[arg(long, value_name = "CODE")]
+        quote: String,
+        /// Units of the quote currency one unit of the base currency buys
+        #[arg(long)]
+        rate: f64,
+        /// Day the rate applies from (default: today)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        date: Option<chrono::NaiveDate>,
+    },
     /// Export market data snapshot for remote optimizers
     ExportMarketData {
         /// Destination file for the snapshot
         #[arg(short, long = "output", value_name = "PATH")]
         output: Option<PathBuf>,
+        /// zstd compression level (higher is smaller and slower)
+        #[arg(long, default_value_t = DEFAULT_SNAPSHOT_COMPRESSION_LEVEL, allow_negative_numbers = true)]
+        compression_level: i32,
+        /// Write the snapshot uncompressed
+        #[arg(long, conflicts_with = "compression_level")]
+        no_compress: bool,
     },
-    /// Train the LightGBM model using in-database market data
-    TrainLightgbm {
-        /// Destination for the trained model (defaults to engine/src/models/lightgbm_model.txt)
-        #[arg(short, long)]
+    /// Compare an account's live entry slippage, stop slippage and fees with the backtest cost settings
+    ExecutionQuality {
+        /// Account whose filled trades to measure
+        #[arg(long = "account", value_name = "ID")]
+        account_id: String,
+        /// First entry or exit date to include (default: one year before --to)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        from: Option<chrono::NaiveDate>,
+        /// Last entry or exit date to include (default: today)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        to: Option<chrono::NaiveDate>,
+    },
+    /// Export an account's filled live trades as a journal checked against broker fills
+    ExportTrades {
+        /// Account whose trades to export
+        #[arg(long = "account", value_name = "ID")]
+        account_id: String,
+        /// First entry or exit date to include (default: January 1 of the --to year)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        from: Option<chrono::NaiveDate>,
+        /// Last entry or exit date to include (default: today)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        to: Option<chrono::NaiveDate>,
+        /// Journal file format
+        #[arg(long, value_enum, default_value_t = JournalFormat::Csv)]
+        format: JournalFormat,
+        /// Destination file (default: trade-journal-<account>.<format>)
+        #[arg(short, long, value_name = "PATH")]
         output: Option<PathBuf>,
-        /// Number of boosting iterations
+        /// Skip looking up order fills at the broker
         #[arg(long)]
-        num_iterations: Option<u32>,
-        /// Learning rate
+        offline: bool,
+    },
+    /// Rebuild an account's pre-existing broker positions and fills as live trades of a strategy
+    ImportBrokerHistory {
+        /// Account whose broker order history to import
+        #[arg(long = "account", value_name = "ID")]
+        account_id: String,
+        /// Strategy of the account that takes over the imported trades
+        #[arg(long = "strategy", value_name = "ID")]
+        strategy_id: String,
+        /// Only replay orders submitted on or after this date
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        since: Option<chrono::NaiveDate>,
+        /// Log the trades that would be imported without writing them
         #[arg(long)]
-        learning_rate: Option<f64>,
-        /// Number of leaves in one tree
+        dry_run: bool,
+    },
+    /// Store the daily values of a non-tradable auxiliary series such as VIX from a CSV file
+    ImportAuxiliary {
+        /// Ticker of the series, e.g. VIX
         #[arg(long)]
-        num_leaves: Option<u32>,
-        /// Maximum tree depth (-1 means no limit)
+        ticker: String,
+        /// CSV file with one date,close or date,open,high,low,close[,volume] row per day
+        file: PathBuf,
+    },
+    /// Store ETF cash distributions from a ticker,ex_date,amount CSV file
+    ImportDistributions {
+        /// CSV file with one ticker,YYYY-MM-DD,amount row per ex-date, amounts per share
+        file: PathBuf,
+    },
+    /// Store earnings report dates from a ticker,report_date CSV file
+    ImportEarnings {
+        /// CSV file with one ticker,YYYY-MM-DD row per report
+        file: PathBuf,
+    },
+    /// Store FOMC and CPI release days from an event_type,event_date CSV file
+    ImportEconomicEvents {
+        /// CSV file with one FOMC|CPI,YYYY-MM-DD row per release
+        file: PathBuf,
+    },
+    /// Store daily news sentiment scores from a ticker,date,score CSV file
+    ImportSentiment {
+        /// CSV file with one ticker,YYYY-MM-DD,score row per day, scores from -1 to 1
+        file: PathBuf,
+    },
+    /// Store short interest and borrow fee reports from a CSV file
+    ImportShortInterest {
+        /// CSV file with one ticker,YYYY-MM-DD,short_interest_ratio,borrow_fee_rate row per report
+        file: PathBuf,
+    },
+    /// Replace the membership intervals of a point-in-time universe from a CSV file
+    ImportUniverse {
+        /// Universe name that BACKTEST_UNIVERSE selects, e.g. SP500
         #[arg(long)]
-        max_depth: Option<i32>,
-        /// Minimum number of observations in one leaf
+        name: String,
+        /// CSV file with one ticker,start_date,end_date row per interval; a blank end date
+        /// means the ticker is still a member
+        file: PathBuf,
+    },
+    /// Run an ordered list of engine steps from a pipeline file, stopping at the first failure
+    Pipeline {
+        /// Pipeline file listing the steps to run
+        file: PathBuf,
+        /// Market data snapshot used by steps that do not set their own data_file
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
+    },
+    /// Serve backtest, plan-operations, generate-signals and optimize as gRPC calls
+    Serve {
+        /// Address to listen on; calls must carry the ENGINE_GRPC_TOKEN bearer token
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:7070")]
+        grpc: SocketAddr,
+        /// Market data snapshot used by calls that do not set their own data_file; requested
+        /// data_file paths must lie in its directory
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
+        /// Serve Prometheus metrics of the calls at http://ADDR/metrics, e.g. 127.0.0.1:9464
+        #[arg(long, value_name = "ADDR")]
+        metrics: Option<SocketAddr>,
+    },
+    /// Run jobs queued in the jobs table, one at a time
+    Worker {
+        /// Market data snapshot used by jobs that do not set their own data_file
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
+        /// Name recorded on claimed jobs (default: engine-<pid>)
+        #[arg(long, value_name = "ID")]
+        worker_id: Option<String>,
+        /// Seconds to wait before polling an empty queue again
+        #[arg(long, value_name = "SECONDS", default_value_t = 5)]
+        poll_interval: u64,
+        /// Exit once the queue is empty
         #[arg(long)]
-        min_data_in_leaf: Option<u32>,
-        /// Minimum gain to split
+        once: bool,
+        /// Serve Prometheus metrics of the jobs at http://ADDR/metrics, e.g. 127.0.0.1:9464
+        #[arg(long, value_name = "ADDR")]
+        metrics: Option<SocketAddr>,
+    },
+    /// Time synthetic backtests and report candles per second, to catch hot-loop regressions
+    Bench {
+        /// Number of synthetic tickers
+        #[arg(long, default_value_t = 50)]
+        tickers: usize,
+        /// Years of daily candles per ticker
+        #[arg(long, default_value_t = 10)]
+        years: usize,
+        /// Number of built-in strategies to run
+        #[arg(long, default_value_t = 8)]
+        strategies: usize,
+    },
+    /// Inspect the engine runtime settings
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Train the LightGBM model using in-database market data
+    TrainLightgbm {
+        /// Destination for the trained model (defaults to engine/src/models/lightgbm_model.txt)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        hyperparameters: LightgbmArgs,
+        /// Comma separated feature names to train on (defaults to every registered feature)
+        #[arg(long, value_delimiter = ',')]
+        features: Vec<String>,
+        /// Comma separated feature names to leave out
+        #[arg(long, value_delimiter = ',')]
+        exclude_features: Vec<String>,
+        #[command(flatten)]
+        labeling: LabelingArgs,
+    },
+    /// Compare the LightGBM validation score on real labels with models trained on shuffled labels
+    LightgbmPermutationTest {
+        /// Number of label shuffles forming the null distribution
+        #[arg(long, default_value_t = 20)]
+        permutations: usize,
+        /// Seed of the first label shuffle
+        #[arg(long, default_value_t = DEFAULT_PERMUTATION_SEED)]
+        seed: u64,
+        #[command(flatten)]
+        hyperparameters: LightgbmArgs,
+        /// Comma separated feature names to train on (defaults to every registered feature)
+        #[arg(long, value_delimiter = ',')]
+        features: Vec<String>,
+        /// Comma separated feature names to leave out
+        #[arg(long, value_delimiter = ',')]
+        exclude_features: Vec<String>,
+        #[command(flatten)]
+        labeling: LabelingArgs,
+    },
+    /// Train a neural network on the LightGBM feature matrix and labels, store it in the database and register an nn_<id> strategy
+    TrainNn {
+        /// Model name (defaults to the layer sizes and training time)
         #[arg(long)]
-        min_gain_to_split: Option<f64>,
-        /// L1 regularization
+        name: Option<String>,
+        /// Comma separated hidden layer sizes (default 32,16)
+        #[arg(long, value_delimiter = ',')]
+        hidden_units: Option<Vec<usize>>,
+        /// Maximum number of passes over the training rows
         #[arg(long)]
-        lambda_l1: Option<f64>,
-        /// L2 regularization
+        epochs: Option<u32>,
+        /// Adam learning rate
         #[arg(long)]
-        lambda_l2: Option<f64>,
-        /// Feature fraction (0..=1)
+        learning_rate: Option<f64>,
+        /// Training rows per gradient step
         #[arg(long)]
-        feature_fraction: Option<f64>,
-        /// Bagging fraction (0..=1)
+        batch_size: Option<usize>,
+        /// L2 penalty on the weights
         #[arg(long)]
-        bagging_fraction: Option<f64>,
-        /// Bagging frequency (0 disables bagging)
+        l2: Option<f64>,
+        /// Epochs without validation loss improvement before stopping (0 disables early stopping)
         #[arg(long)]
-        bagging_freq: Option<u32>,
-        /// Early stopping rounds (0 disables early stopping)
+        early_stopping_epochs: Option<u32>,
+        /// Seed for weight initialization and shuffling
         #[arg(long)]
-        early_stopping_round: Option<u32>,
+        seed: Option<u64>,
+        /// Comma separated feature names to train on (defaults to every registered feature)
+        #[arg(long, value_delimiter = ',')]
+        features: Vec<String>,
+        /// Comma separated feature names to leave out
+        #[arg(long, value_delimiter = ',')]
+        exclude_features: Vec<String>,
+        #[command(flatten)]
+        labeling: LabelingArgs,
+    },
+}
+
+#[derive(Args)]
+struct LightgbmArgs {
+    /// Number of boosting iterations
+    #[arg(long)]
+    num_iterations: Option<u32>,
+    /// Learning rate
+    #[arg(long)]
+    learning_rate: Option<f64>,
+    /// Number of leaves in one tree
+    #[arg(long)]
+    num_leaves: Option<u32>,
+    /// Maximum tree depth (-1 means no limit)
+    #[arg(long)]
+    max_depth: Option<i32>,
+    /// Minimum number of observations in one leaf
+    #[arg(long)]
+    min_data_in_leaf: Option<u32>,
+    /// Minimum gain to split
+    #[arg(long)]
+    min_gain_to_split: Option<f64>,
+    /// L1 regularization
+    #[arg(long)]
+    lambda_l1: Option<f64>,
+    /// L2 regularization
+    #[arg(long)]
+    lambda_l2: Option<f64>,
+    /// Feature fraction (0..=1)
+    #[arg(long)]
+    feature_fraction: Option<f64>,
+    /// Bagging fraction (0..=1)
+    #[arg(long)]
+    bagging_fraction: Option<f64>,
+    /// Bagging frequency (0 disables bagging)
+    #[arg(long)]
+    bagging_freq: Option<u32>,
+    /// Early stopping rounds (0 disables early stopping)
+    #[arg(long)]
+    early_stopping_round: Option<u32>,
+}
+
+impl LightgbmArgs {
+    fn into_hyperparameters(self) -> LightgbmHyperparameters {
+        let defaults = LightgbmHyperparameters::default();
+        LightgbmHyperparameters {
+            num_iterations: self.num_iterations.unwrap_or(defaults.num_iterations),
+            learning_rate: self.learning_rate.unwrap_or(defaults.learning_rate),
+            num_leaves: self.num_leaves.unwrap_or(defaults.num_leaves),
+            max_depth: self.max_depth.unwrap_or(defaults.max_depth),
+            min_data_in_leaf: self.min_data_in_leaf.unwrap_or(defaults.min_data_in_leaf),
+            min_gain_to_split: self.min_gain_to_split.unwrap_or(defaults.min_gain_to_split),
+            lambda_l1: self.lambda_l1.unwrap_or(defaults.lambda_l1),
+            lambda_l2: self.lambda_l2.unwrap_or(defaults.lambda_l2),
+            feature_fraction: self.feature_fraction.unwrap_or(defaults.feature_fraction),
+            bagging_fraction: self.bagging_fraction.unwrap_or(defaults.bagging_fraction),
+            bagging_freq: self.bagging_freq.unwrap_or(defaults.bagging_freq),
+            early_stopping_round: self
+                .early_stopping_round
+                .unwrap_or(defaults.early_stopping_round),
+        }
+    }
+}
+
+#[derive(Args)]
+struct LabelingArgs {
+    /// Training label: extreme (high reaches the target multiple) or triple-barrier (ATR profit target, ATR stop or horizon, labeled by whether the trade gained)
+    #[arg(long, value_enum, default_value_t = LabelingMethod::Extreme)]
+    labeling: LabelingMethod,
+    /// Triple-barrier profit target in ATRs above the entry close (default 4)
+    #[arg(long)]
+    profit_atr_multiple: Option<f64>,
+    /// Triple-barrier stop in ATRs below the entry close (default 2, the engine's ATR stop)
+    #[arg(long)]
+    stop_atr_multiple: Option<f64>,
+    /// Triple-barrier maximum holding period in bars (default 60)
+    #[arg(long)]
+    barrier_horizon_bars: Option<usize>,
+    /// ATR period for the triple-barrier barriers (default 20)
+    #[arg(long)]
+    barrier_atr_period: Option<usize>,
+    /// Write each row's barrier outcome and meta-label to this CSV (triple-barrier only)
+    #[arg(long, value_name = "PATH")]
+    labels_output: Option<PathBuf>,
+    /// Halve a training row's weight for every this many days before the newest training row
+    #[arg(long)]
+    recency_half_life_days: Option<f64>,
+    /// Weight training rows by the uniqueness of their label windows (overlapping windows of one ticker share weight)
+    #[arg(long)]
+    uniqueness_weights: bool,
+}
+
+impl LabelingArgs {
+    fn into_options(self) -> LabelingOptions {
+        let defaults = TripleBarrierConfig::default();
+        LabelingOptions {
+            method: self.labeling,
+            triple_barrier: TripleBarrierConfig {
+                profit_atr_multiple: self
+                    .profit_atr_multiple
+                    .unwrap_or(defaults.profit_atr_multiple),
+                stop_atr_multiple: self.stop_atr_multiple.unwrap_or(defaults.stop_atr_multiple),
+                horizon_bars: self.barrier_horizon_bars.unwrap_or(defaults.horizon_bars),
+                atr_period: self.barrier_atr_period.unwrap_or(defaults.atr_period),
+            },
+            output_path: self.labels_output,
+            weighting: SampleWeighting {
+                recency_half_life_days: self.recency_half_life_days,
+                uniqueness: self.uniqueness_weights,
+            },
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print the effective settings merged from engine.toml, ENGINE_SETTING_* variables and the database, with each value's source
+    Show {
+        /// Market data snapshot whose settings form the bottom layer when no database is reachable
+        #[arg(long = "data-file", value_name = "PATH")]
+        data_file: Option<PathBuf>,
     },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
-    let Cli { command } = cli;
+    let Cli {
+        threads,
+        config,
+        environment,
+        engine_config,
+        log_format,
+        sqlite_file,
+        cross_sectional_cache_dir,
+        command,
+    } = cli;
+    let cli_defaults = load_cli_defaults(config.as_deref(), environment.as_deref())?;
+    let threads = resolve_thread_count(threads, &cli_defaults)?;
+    let setting_layers = SettingLayers::load(engine_config.as_deref())?;
+    let resolve_data_path =
+        |cli_value: Option<PathBuf>| resolve_market_data_path(cli_value, &cli_defaults);
 
-    // Pin Rayon to 16 logical processors for consistent parallelism during heavy workloads.
-    env::set_var("RAYON_NUM_THREADS", "16");
-
-    let database_url = env::var("DATABASE_URL").ok();
-    if database_url.is_none() && command_requires_database(&command) {
+    let database_url = env::var("DATABASE_URL")
+        .ok()
+        .or(cli_defaults.database_url.clone());
+    let sqlite_file = sqlite_file.or(cli_defaults.sqlite_file.clone());
+    if database_url.is_none()
+        && command_requires_database(&command, &cli_defaults, sqlite_file.is_some())
+    {
         return Err(anyhow!(
             "DATABASE_URL must be set for this command. For offline runs, use a market data snapshot."
         ));
     }
-    let app_context = AppContext::initialize(database_url).await?;
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let mirror_logs = log_format == LogFormat::Json && database_url.is_some();
+    let app_context = AppContext::initialize(database_url)
+        .await?
+        .with_threads(threads)?
+        .with_sqlite_file(sqlite_file)
+        .with_cross_sectional_cache_dir(
+            cross_sectional_cache_dir.or(cli_defaults.cross_sectional_cache_dir.clone()),
+        )
+        .with_setting_layers(setting_layers)
+        .with_cancellation(CancellationToken::new());
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(app_context.threads())
+        .build_global()?;
+    logging::init(log_format);
+    spawn_interrupt_handler(app_context.cancellation().clone());
+    let log_mirror = if mirror_logs {
+        Some(SystemLogMirror::start(app_context.database().await?))
+    } else {
+        None
+    };
 
     info!("Starting engine. Not financial advice. Most retail traders lose money. Use at your own risk.");
+    info!("Using up to {} worker threads", app_context.threads());
 
-    if !matches!(command, Commands::TrainLightgbm { .. }) {
+    if !matches!(
+        command,
+        Commands::TrainLightgbm { .. }
+            | Commands::LightgbmPermutationTest { .. }
+            | Commands::TrainNn { .. }
+    ) {
         if let Err(err) = load_lightgbm_model(&app_context).await {
             warn!("LightGBM model load skipped: {err}");
         }
+        if let Err(err) = load_neural_net_models(&app_context).await {
+            warn!("Neural network model load skipped: {err}");
+        }
     }
 
-    match command {
-        Commands::Optimize {
-            template_id,
-            data_file,
-        } => {
-            let market_data_path = resolve_market_data_path(data_file);
-            optimize::run(&app_context, &template_id, &market_data_path).await?;
-        }
-        Commands::Verify {
-            template_id,
-            data_file,
-        } => {
-            let market_data_path = resolve_market_data_path(data_file);
-            verify::run(&app_context, &template_id, &market_data_path).await?;
-        }
-        Commands::Balance {
-            template_id,
-            data_file,
-        } => {
-            let market_data_path = resolve_market_data_path(data_file);
-            balance::run(&app_context, &template_id, &market_data_path).await?;
-        }
-        Commands::GenerateSignals => {
-            generate_signals::run(&app_context).await?;
-        }
-        Commands::BacktestActive { scope, months } => {
-            backtest_active::run(&app_context, scope, &months).await?;
-        }
-        Commands::BacktestAccounts => {
-            backtest_accounts::run(&app_context).await?;
-        }
-        Commands::PlanOperations => {
-            plan_operations::run(&app_context).await?;
-        }
-        Commands::ReconcileTrades => {
-            reconcile_trades::run(&app_context).await?;
-        }
-        Commands::ExportMarketData { output } => {
-            let output_path = resolve_market_data_path(output);
-            export_market_data::run(&app_context, &output_path).await?;
-        }
-        Commands::TrainLightgbm {
-            output,
-            num_iterations,
-            learning_rate,
-            num_leaves,
-            max_depth,
-            min_data_in_leaf,
-            min_gain_to_split,
-            lambda_l1,
-            lambda_l2,
-            feature_fraction,
-            bagging_fraction,
-            bagging_freq,
-            early_stopping_round,
-        } => {
-            let fallback_path = PathBuf::from(DEFAULT_LGBM_MODEL_REL_PATH);
-            train_lightgbm::run(
-                &app_context,
-                output.or_else(|| Some(fallback_path)),
-                num_iterations,
+    let result: Result<()> = async {
+        match command {
+            Commands::Optimize {
+                template_id,
+                data_file,
+                max_evals,
+                max_hours,
+                plateau_evals,
+                plateau_epsilon,
+                max_memory_gb,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                optimize::run(
+                    &app_context,
+                    &template_id,
+                    &market_data_path,
+                    &optimize::OptimizeOptions {
+                        max_evals,
+                        max_hours,
+                        plateau_evals,
+                        plateau_epsilon,
+                        max_memory_gb,
+                    },
+                )
+                .await?;
+            }
+            Commands::Verify {
+                template_id,
+                data_file,
+                ticker_folds,
+                perturb,
+                anchored_windows,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                verify::run(
+                    &app_context,
+                    &template_id,
+                    &market_data_path,
+                    verify::VerifyOptions {
+                        ticker_folds,
+                        perturb,
+                        anchored_windows,
+                    },
+                )
+                .await?;
+            }
+            Commands::Sensitivity {
+                template_id,
+                param_x,
+                param_y,
+                metric,
+                format,
+                output,
+            } => {
+                sensitivity::run(
+                    &app_context,
+                    &template_id,
+                    SensitivityOptions {
+                        param_x: &param_x,
+                        param_y: &param_y,
+                        metric,
+                        format,
+                        output: output.as_deref(),
+                    },
+                )
+                .await?;
+            }
+            Commands::AuditLookahead {
+                template_id,
+                data_file,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                audit_lookahead::run(&app_context, &template_id, &market_data_path).await?;
+            }
+            Commands::CapitalScaling {
+                template_id,
+                data_file,
+                capitals,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                capital_scaling::run(&app_context, &template_id, &market_data_path, &capitals)
+                    .await?;
+            }
+            Commands::TryParameters {
+                template_id,
+                parameters,
+                start_date,
+                end_date,
+                data_file,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                let parameters = try_parameters::parse_parameters(&parameters)?;
+                try_parameters::run(
+                    &app_context,
+                    &template_id,
+                    &parameters,
+                    MarketDataFilters {
+                        start_date,
+                        end_date,
+                    },
+                    &market_data_path,
+                )
+                .await?;
+            }
+            Commands::Balance {
+                template_id,
+                data_file,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                balance::run(&app_context, &template_id, &market_data_path).await?;
+            }
+            Commands::GenerateSignals => {
+                generate_signals::run(&app_context).await?;
+            }
+            Commands::BacktestActive {
+                scope,
+                months,
+                tickers,
+                tickers_file,
+                exclude_file,
+            } => {
+                let selection = backtest_active::TickerSelection {
+                    tickers,
+                    tickers_file,
+                    exclude_file,
+                };
+                backtest_active::run(&app_context, scope, &months, &selection).await?;
+            }
+            Commands::BacktestAccounts { account_id } => {
+                backtest_accounts::run(&app_context, account_id.as_deref()).await?;
+            }
+            Commands::PlanOperations {
+                dry_run,
+                explain,
+                account_id,
+            } => {
+                plan_operations::run(
+                    &app_context,
+                    PlanOperationsOptions { dry_run, explain },
+                    account_id.as_deref(),
+                )
+                .await?;
+            }
+            Commands::WhatIf {
+                account,
+                signals,
+                cash,
+            } => {
+                what_if::run(&app_context, &account, &signals, cash).await?;
+            }
+            Commands::ReconcileTrades => {
+                reconcile_trades::run(&app_context).await?;
+            }
+            Commands::WatchOrders => {
+                watch_orders::run(&app_context).await?;
+            }
+            Commands::DetectDrift => {
+                detect_drift::run(&app_context).await?;
+            }
+            Commands::ShadowCompare => {
+                shadow_compare::run(&app_context).await?;
+            }
+            Commands::CheckData {
+                data_file,
+                output,
+                jump_threshold,
+                stale_sessions,
+                quarantine,
+            } => {
+                check_data::run(
+                    &app_context,
+                    CheckDataOptions {
+                        data_file,
+                        output,
+                        jump_threshold,
+                        stale_sessions,
+                        quarantine,
+                    },
+                )
+                .await?;
+            }
+            Commands::IdleTickers { months } => {
+                idle_tickers::run(&app_context, months).await?;
+            }
+            Commands::CalibrationReport { model_id, bins } => {
+                calibration_report::run(&app_context, model_id.as_deref(), bins).await?;
+            }
+            Commands::SignalStats { months, horizon } => {
+                signal_stats::run(&app_context, months, horizon).await?;
+            }
+            Commands::PruneBacktests {
+                keep_last,
+                keep_active,
+                dry_run,
+            } => {
+                prune_backtests::run(
+                    &app_context,
+                    PruneBacktestsOptions {
+                        keep_last,
+                        keep_active,
+                        dry_run,
+                    },
+                )
+                .await?;
+            }
+            Commands::Exposure {
+                account_id,
+                max_weight,
+            } => {
+                exposure::run(
+                    &app_context,
+                    ExposureOptions {
+                        account_id: &account_id,
+                        max_position_weight: max_weight,
+                    },
+                )
+                .await?;
+            }
+            Commands::RotateKey { old_key_env } => {
+                rotate_key::run(&app_context, &old_key_env).await?;
+            }
+            Commands::SetFxRate {
+                base,
+                quote,
+                rate,
+                date,
+            } => {
+                set_fx_rate::run(&app_context, &base, &quote, rate, date).await?;
+            }
+            Commands::ExportMarketData {
+                output,
+                compression_level,
+                no_compress,
+            } => {
+                let output_path = resolve_data_path(output);
+                export_market_data::run(
+                    &app_context,
+                    &output_path,
+                    (!no_compress).then_some(compression_level),
+                )
+                .await?;
+            }
+            Commands::ExecutionQuality {
+                account_id,
+                from,
+                to,
+            } => {
+                execution_quality::run(&app_context, &account_id, from, to).await?;
+            }
+            Commands::ExportTrades {
+                account_id,
+                from,
+                to,
+                format,
+                output,
+                offline,
+            } => {
+                export_trades::run(
+                    &app_context,
+                    ExportTradesOptions {
+                        account_id,
+                        from,
+                        to,
+                        format,
+                        output,
+                        offline,
+                    },
+                )
+                .await?;
+            }
+            Commands::ImportBrokerHistory {
+                account_id,
+                strategy_id,
+                since,
+                dry_run,
+            } => {
+                import_broker_history::run(
+                    &app_context,
+                    ImportBrokerHistoryOptions {
+                        account_id,
+                        strategy_id,
+                        since,
+                        dry_run,
+                    },
+                )
+                .await?;
+            }
+            Commands::ImportAuxiliary { ticker, file } => {
+                import_auxiliary::run(&app_context, &ticker, &file).await?;
+            }
+            Commands::ImportDistributions { file } => {
+                import_distributions::run(&app_context, &file).await?;
+            }
+            Commands::ImportEarnings { file } => {
+                import_earnings::run(&app_context, &file).await?;
+            }
+            Commands::ImportEconomicEvents { file } => {
+                import_economic_events::run(&app_context, &file).await?;
+            }
+            Commands::ImportSentiment { file } => {
+                import_sentiment::run(&app_context, &file).await?;
+            }
+            Commands::ImportShortInterest { file } => {
+                import_short_interest::run(&app_context, &file).await?;
+            }
+            Commands::ImportUniverse { name, file } => {
+                import_universe::run(&app_context, &name, &file).await?;
+            }
+            Commands::Pipeline { file, data_file } => {
+                let market_data_path = resolve_data_path(data_file);
+                pipeline::run(&app_context, &file, &market_data_path).await?;
+            }
+            Commands::Worker {
+                data_file,
+                worker_id,
+                poll_interval,
+                once,
+                metrics,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                let options = WorkerOptions {
+                    worker_id,
+                    poll_interval: std::time::Duration::from_secs(poll_interval),
+                    once,
+                    metrics_address: metrics,
+                };
+                worker::run(&app_context, &market_data_path, &options).await?;
+            }
+            Commands::Serve {
+                grpc,
+                data_file,
+                metrics,
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                serve::run(&app_context, grpc, market_data_path, metrics).await?;
+            }
+            Commands::Bench {
+                tickers,
+                years,
+                strategies,
+            } => {
+                bench::run(&BenchOptions {
+                    tickers,
+                    years,
+                    strategies,
+                })?;
+            }
+            Commands::Config {
+                command: ConfigCommands::Show { data_file },
+            } => {
+                let market_data_path = resolve_data_path(data_file);
+                config_show::run(&app_context, &market_data_path).await?;
+            }
+            Commands::TrainLightgbm {
+                output,
+                hyperparameters,
+                features,
+                exclude_features,
+                labeling,
+            } => {
+                let fallback_path = PathBuf::from(DEFAULT_LGBM_MODEL_REL_PATH);
+                train_lightgbm::run(
+                    &app_context,
+                    output.or(Some(fallback_path)),
+                    hyperparameters.into_hyperparameters(),
+                    FeatureSet::from_lists(&features, &exclude_features)?,
+                    labeling.into_options(),
+                )
+                .await?;
+            }
+            Commands::LightgbmPermutationTest {
+                permutations,
+                seed,
+                hyperparameters,
+                features,
+                exclude_features,
+                labeling,
+            } => {
+                lightgbm_permutation::run(
+                    &app_context,
+                    hyperparameters.into_hyperparameters(),
+                    FeatureSet::from_lists(&features, &exclude_features)?,
+                    labeling.into_options(),
+                    permutations,
+                    seed,
+                )
+                .await?;
+            }
+            Commands::TrainNn {
+                name,
+                hidden_units,
+                epochs,
                 learning_rate,
-                num_leaves,
-                max_depth,
-                min_data_in_leaf,
-                min_gain_to_split,
-                lambda_l1,
-                lambda_l2,
-                feature_fraction,
-                bagging_fraction,
-                bagging_freq,
-                early_stopping_round,
-            )
-            .await?;
+                batch_size,
+                l2,
+                early_stopping_epochs,
+                seed,
+                features,
+                exclude_features,
+                labeling,
+            } => {
+                let defaults = NeuralNetHyperparameters::default();
+                let hyperparameters = NeuralNetHyperparameters {
+                    hidden_units: hidden_units.unwrap_or(defaults.hidden_units),
+                    epochs: epochs.unwrap_or(defaults.epochs),
+                    learning_rate: learning_rate.unwrap_or(defaults.learning_rate),
+                    batch_size: batch_size.unwrap_or(defaults.batch_size),
+                    l2: l2.unwrap_or(defaults.l2),
+                    early_stopping_epochs: early_stopping_epochs
+                        .unwrap_or(defaults.early_stopping_epochs),
+                    seed: seed.unwrap_or(defaults.seed),
+                };
+                train_nn::run(
+                    &app_context,
+                    name,
+                    hyperparameters,
+                    FeatureSet::from_lists(&features, &exclude_features)?,
+                    labeling.into_options(),
+                )
+                .await?;
+            }
         }
+        Ok(())
     }
+    .await;
 
-    Ok(())
+    if let Err(err) = &result {
+        error!("{:#}", err);
+    }
+    if let Some(mirror) = log_mirror {
+        mirror.finish().await;
+    }
+    result
+}
+
+/// The first Ctrl-C stops the running command at its next safe checkpoint; a second one exits
+/// immediately.
+fn spawn_interrupt_handler(cancellation: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        warn!("Interrupted; stopping at the next safe checkpoint (press Ctrl-C again to exit now)");
+        cancellation.cancel();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            error!("Interrupted again; exiting without waiting for a checkpoint");
+            std::process::exit(130);
+        }
+    });
 }
 
 async fn load_lightgbm_model(app_context: &AppContext) -> Result<()> {
@@ -236,25 +1216,81 @@ async fn load_lightgbm_model(app_context: &AppContext) -> Result<()> {
     Ok(())
 }
 
-fn resolve_market_data_path(cli_value: Option<PathBuf>) -> PathBuf {
-    if let Some(path) = cli_value {
+async fn load_neural_net_models(app_context: &AppContext) -> Result<()> {
+    let db = app_context.database().await?;
+    let models = db.get_neural_net_models().await?;
+    for model in &models {
+        strategy::neural_net::register_model_json(&model.id, &model.weights_json)?;
+    }
+    if !models.is_empty() {
+        info!(
+            "Loaded {} neural network model(s) from database",
+            models.len()
+        );
+    }
+    Ok(())
+}
+
+fn resolve_market_data_path(cli_value: Option<PathBuf>, cli_defaults: &CliDefaults) -> PathBuf {
+    if let Some(path) = cli_value.or_else(|| cli_defaults.data_file.clone()) {
         return path;
     }
 
     PathBuf::from(DEFAULT_MARKET_DATA_FILE)
 }
 
-fn command_requires_database(command: &Commands) -> bool {
+fn command_requires_database(
+    command: &Commands,
+    cli_defaults: &CliDefaults,
+    has_local_store: bool,
+) -> bool {
     match command {
-        Commands::Optimize { data_file, .. } => data_file.is_none(),
+        Commands::Optimize { data_file, .. } => {
+            data_file.is_none() && cli_defaults.data_file.is_none()
+        }
         Commands::Verify { .. }
-        | Commands::Balance { .. }
+        | Commands::Sensitivity { .. }
+        | Commands::AuditLookahead { .. }
+        | Commands::CapitalScaling { .. } => !has_local_store,
+        Commands::Config { .. } | Commands::Bench { .. } | Commands::TryParameters { .. } => false,
+        Commands::CheckData {
+            data_file,
+            quarantine,
+            ..
+        } => data_file.is_none() || *quarantine,
+        Commands::Balance { .. }
         | Commands::GenerateSignals
         | Commands::BacktestActive { .. }
-        | Commands::BacktestAccounts
-        | Commands::PlanOperations
+        | Commands::BacktestAccounts { .. }
+        | Commands::PlanOperations { .. }
+        | Commands::WhatIf { .. }
         | Commands::ReconcileTrades
+        | Commands::WatchOrders
+        | Commands::DetectDrift
+        | Commands::ShadowCompare
+        | Commands::IdleTickers { .. }
+        | Commands::CalibrationReport { .. }
+        | Commands::SignalStats { .. }
+        | Commands::PruneBacktests { .. }
+        | Commands::Exposure { .. }
+        | Commands::RotateKey { .. }
+        | Commands::SetFxRate { .. }
+        | Commands::ImportAuxiliary { .. }
+        | Commands::ImportDistributions { .. }
+        | Commands::ImportEarnings { .. }
+        | Commands::ImportEconomicEvents { .. }
+        | Commands::ImportSentiment { .. }
+        | Commands::ImportShortInterest { .. }
+        | Commands::ImportUniverse { .. }
         | Commands::ExportMarketData { .. }
-        | Commands::TrainLightgbm { .. } => true,
+        | Commands::ExecutionQuality { .. }
+        | Commands::ExportTrades { .. }
+        | Commands::ImportBrokerHistory { .. }
+        | Commands::Pipeline { .. }
+        | Commands::Serve { .. }
+        | Commands::Worker { .. }
+        | Commands::TrainLightgbm { .. }
+        | Commands::LightgbmPermutationTest { .. }
+        | Commands::TrainNn { .. } => true,
     }
 }
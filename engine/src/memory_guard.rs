@@ -0,0 +1,86 @@
+use anyhow::{anyhow, Error};
+use std::fs;
+
+/// Share of the limit above which the optimizer sheds caches and parallelism.
+const HIGH_WATER_RATIO: f64 = 0.8;
+
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryPressure {
+    Normal,
+    /// Above the high-water mark; shrink before the limit is reached.
+    High,
+    /// Resident memory (in bytes) reached the limit.
+    Exceeded(u64),
+}
+
+/// Watches this process's resident memory against a limit so long runs can back off, or stop
+/// with an explanation, before the kernel's OOM killer ends them.
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryGuard {
+    limit_bytes: u64,
+}
+
+impl MemoryGuard {
+    pub fn new(limit_bytes: u64) -> Self {
+        Self { limit_bytes }
+    }
+
+    pub fn from_gigabytes(limit_gb: f64) -> Self {
+        Self::new((limit_gb * BYTES_PER_GB) as u64)
+    }
+
+    /// Current pressure, or `Normal` where resident memory cannot be read.
+    pub fn pressure(&self) -> MemoryPressure {
+        resident_bytes()
+            .map(|resident| classify(resident, self.limit_bytes))
+            .unwrap_or(MemoryPressure::Normal)
+    }
+
+    pub fn exceeded_error(&self, resident_bytes: u64) -> Error {
+        anyhow!(
+            "Memory use reached {:.1} GB, over the {:.1} GB limit; stopping before the system runs out of memory. Lower --threads or raise --max-memory-gb.",
+            resident_bytes as f64 / BYTES_PER_GB,
+            self.limit_bytes as f64 / BYTES_PER_GB
+        )
+    }
+}
+
+/// Resident set size of this process, read from `/proc/self/status` on Linux.
+pub fn resident_bytes() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    parse_vm_rss(&status)
+}
+
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+fn classify(resident: u64, limit: u64) -> MemoryPressure {
+    if resident >= limit {
+        MemoryPressure::Exceeded(resident)
+    } else if resident as f64 >= limit as f64 * HIGH_WATER_RATIO {
+        MemoryPressure::High
+    } else {
+        MemoryPressure::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_rss_and_classifies_against_limit() {
+        let status = "Name:\tengine\nVmPeak:\t  900 kB\nVmRSS:\t    512 kB\nThreads:\t4\n";
+        assert_eq!(parse_vm_rss(status), Some(512 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tengine\n"), None);
+
+        assert_eq!(classify(700, 1000), MemoryPressure::Normal);
+        assert_eq!(classify(800, 1000), MemoryPressure::High);
+        assert_eq!(classify(1000, 1000), MemoryPressure::Exceeded(1000));
+    }
+}
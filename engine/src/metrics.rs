@@ -0,0 +1,182 @@
+use anyhow::Result;
+use axum::{http::header, routing::get, Router};
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+/// How a served call or queued job ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommandOutcome {
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl CommandOutcome {
+    fn label(self) -> &'static str {
+        match self {
+            CommandOutcome::Succeeded => "succeeded",
+            CommandOutcome::Failed => "failed",
+            CommandOutcome::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CommandStats {
+    outcomes: BTreeMap<CommandOutcome, u64>,
+    duration_seconds_sum: f64,
+    duration_seconds_count: u64,
+    last_finished_unix_seconds: f64,
+}
+
+/// Counters of the commands run by `serve --grpc` and `worker`, rendered in the Prometheus
+/// text format. `mode` labels every series with the process kind.
+#[derive(Debug)]
+pub struct Metrics {
+    mode: &'static str,
+    running: AtomicBool,
+    commands: Mutex<BTreeMap<String, CommandStats>>,
+}
+
+impl Metrics {
+    pub fn new(mode: &'static str) -> Self {
+        Self {
+            mode,
+            running: AtomicBool::new(false),
+            commands: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn set_running(&self, running: bool) {
+        self.running.store(running, Ordering::Relaxed);
+    }
+
+    pub fn record(&self, command: &str, outcome: CommandOutcome, duration_seconds: f64) {
+        let finished = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        let mut commands = self.commands.lock().unwrap();
+        let stats = commands.entry(command.to_string()).or_default();
+        *stats.outcomes.entry(outcome).or_default() += 1;
+        stats.duration_seconds_sum += duration_seconds;
+        stats.duration_seconds_count += 1;
+        stats.last_finished_unix_seconds = finished;
+    }
+
+    pub fn render(&self) -> String {
+        let mode = self.mode;
+        let commands = self.commands.lock().unwrap();
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# HELP stratcraft_engine_command_running Whether a command is running now.\n\
+             # TYPE stratcraft_engine_command_running gauge\n\
+             stratcraft_engine_command_running{{mode=\"{}\"}} {}",
+            mode,
+            u8::from(self.running.load(Ordering::Relaxed))
+        );
+        let _ = writeln!(
+            out,
+            "# HELP stratcraft_engine_commands_total Finished commands by outcome.\n\
+             # TYPE stratcraft_engine_commands_total counter"
+        );
+        for (command, stats) in commands.iter() {
+            for (outcome, count) in &stats.outcomes {
+                let _ = writeln!(
+                    out,
+                    "stratcraft_engine_commands_total{{mode=\"{}\",command=\"{}\",outcome=\"{}\"}} {}",
+                    mode,
+                    command,
+                    outcome.label(),
+                    count
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP stratcraft_engine_command_duration_seconds Wall time of finished commands.\n\
+             # TYPE stratcraft_engine_command_duration_seconds summary"
+        );
+        for (command, stats) in commands.iter() {
+            let _ = writeln!(
+                out,
+                "stratcraft_engine_command_duration_seconds_sum{{mode=\"{}\",command=\"{}\"}} {}\n\
+                 stratcraft_engine_command_duration_seconds_count{{mode=\"{}\",command=\"{}\"}} {}",
+                mode,
+                command,
+                stats.duration_seconds_sum,
+                mode,
+                command,
+                stats.duration_seconds_count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP stratcraft_engine_command_last_finished_timestamp_seconds Unix time the command last finished.\n\
+             # TYPE stratcraft_engine_command_last_finished_timestamp_seconds gauge"
+        );
+        for (command, stats) in commands.iter() {
+            let _ = writeln!(
+                out,
+                "stratcraft_engine_command_last_finished_timestamp_seconds{{mode=\"{}\",command=\"{}\"}} {}",
+                mode, command, stats.last_finished_unix_seconds
+            );
+        }
+        out
+    }
+}
+
+/// Binds `address` and serves `GET /metrics` from a background task; abort the returned
+/// handle to stop serving.
+pub async fn spawn(metrics: Arc<Metrics>, address: SocketAddr) -> Result<JoinHandle<()>> {
+    let router = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move {
+                (
+                    [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+                    metrics.render(),
+                )
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    info!("Serving Prometheus metrics on http://{}/metrics", address);
+    Ok(tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, router).await {
+            warn!("Metrics endpoint stopped: {}", err);
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_counters_per_command_and_outcome() {
+        let metrics = Metrics::new("worker");
+        metrics.record("optimize", CommandOutcome::Succeeded, 2.5);
+        metrics.record("optimize", CommandOutcome::Failed, 1.5);
+        metrics.set_running(true);
+        let text = metrics.render();
+        assert!(text.contains("stratcraft_engine_command_running{mode=\"worker\"} 1"));
+        assert!(text.contains(
+            "stratcraft_engine_commands_total{mode=\"worker\",command=\"optimize\",outcome=\"failed\"} 1"
+        ));
+        assert!(text.contains(
+            "stratcraft_engine_command_duration_seconds_sum{mode=\"worker\",command=\"optimize\"} 4"
+        ));
+        assert!(text.contains(
+            "stratcraft_engine_command_duration_seconds_count{mode=\"worker\",command=\"optimize\"} 2"
+        ));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::config::{NotificationChannel, NotificationEvent, NotificationSettings};
+use crate::database::Database;
+use crate::engine::AccountPositionState;
+use crate::models::{Trade, TradeStatus};
+use anyhow::Result;
+use chrono::NaiveDate;
+use log::warn;
+use reqwest::Client;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+pub const NOTIFICATIONS_LOG_SOURCE: &str = "notifications";
+// Discord rejects messages over 2000 characters; Telegram allows 4096.
+const MAX_MESSAGE_CHARS: usize = 1900;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub account_id: String,
+    pub event: NotificationEvent,
+    pub text: String,
+}
+
+/// Describes what reconciliation changed on a trade: an entry or exit fill, or a stop trigger.
+pub fn trade_notification(account_id: &str, before: &Trade, after: &Trade) -> Option<Notification> {
+    let (event, text) = match (&before.status, &after.status) {
+        (TradeStatus::Pending, TradeStatus::Active) => (
+            NotificationEvent::Fill,
+            format!(
+                "Entry filled: {} {} @ {:.2} (strategy {})",
+                after.ticker, after.quantity, after.price, after.strategy_id
+            ),
+        ),
+        (TradeStatus::Pending | TradeStatus::Active, TradeStatus::Closed) => {
+            let stop = after.stop_loss_triggered == Some(true);
+            (
+                if stop {
+                    NotificationEvent::StopTriggered
+                } else {
+                    NotificationEvent::Fill
+                },
+                format!(
+                    "{}: {} {} @ {}, P&L {} (strategy {})",
+                    if stop {
+                        "Stop triggered"
+                    } else {
+                        "Exit filled"
+                    },
+                    after.ticker,
+                    after.quantity,
+                    after
+                        .exit_price
+                        .map(|price| format!("{:.2}", price))
+                        .unwrap_or_else(|| "n/a".to_string()),
+                    after
+                        .pnl
+                        .map(|pnl| format!("{:+.2}", pnl))
+                        .unwrap_or_else(|| "n/a".to_string()),
+                    after.strategy_id
+                ),
+            )
+        }
+        _ => return None,
+    };
+    Some(Notification {
+        account_id: account_id.to_string(),
+        event,
+        text,
+    })
+}
+
+/// Tickers where the broker's position differs from the sum of active trades on the account.
+pub fn position_mismatches(
+    trades: &[Trade],
+    positions: &[AccountPositionState],
+) -> Vec<(String, i32, i32)> {
+    let mut quantities: BTreeMap<&str, (i32, i32)> = BTreeMap::new();
+    for trade in trades
+        .iter()
+        .filter(|trade| trade.status == TradeStatus::Active)
+    {
+        quantities.entry(&trade.ticker).or_default().0 += trade.quantity;
+    }
+    for position in positions {
+        quantities.entry(&position.ticker).or_default().1 += position.quantity;
+    }
+    quantities
+        .into_iter()
+        .filter(|(_, (expected, held))| expected != held)
+        .map(|(ticker, (expected, held))| (ticker.to_string(), expected, held))
+        .collect()
+}
+
+pub fn daily_pnl_text(
+    account_id: &str,
+    date: NaiveDate,
+    realized: (f64, i64),
+    open_trades: &[Trade],
+) -> String {
+    let open: Vec<&Trade> = open_trades
+        .iter()
+        .filter(|trade| trade.status == TradeStatus::Active)
+        .collect();
+    let unrealized: f64 = open.iter().filter_map(|trade| trade.pnl).sum();
+    format!(
+        "Daily P&L for account {} on {}: realized {:+.2} from {} closed trade{}, unrealized {:+.2} across {} open trade{}",
+        account_id,
+        date.format("%Y-%m-%d"),
+        realized.0,
+        realized.1,
+        if realized.1 == 1 { "" } else { "s" },
+        unrealized,
+        open.len(),
+        if open.len() == 1 { "" } else { "s" }
+    )
+}
+
+/// Sends each notification to the account's subscribed channels and records it in
+/// `system_logs` (source `notifications`), where email recipients are picked up by the
+/// reconcile-trades job. Delivery failures are logged and never fail reconciliation.
+pub async fn deliver_notifications(
+    db: &Database,
+    http_client: &Client,
+    settings: &NotificationSettings,
+    notifications: &[Notification],
+) -> Result<()> {
+    for notification in notifications {
+        let subscriptions: Vec<_> = settings
+            .channels_for(&notification.account_id)
+            .iter()
+            .filter(|subscription| subscription.wants(notification.event))
+            .collect();
+        if subscriptions.is_empty() {
+            continue;
+        }
+
+        let text: String = notification.text.chars().take(MAX_MESSAGE_CHARS).collect();
+        let mut delivered = Vec::new();
+        let mut failed = Vec::new();
+        let mut email_to = Vec::new();
+        for subscription in subscriptions {
+            let channel = &subscription.channel;
+            if let NotificationChannel::Email { to } = channel {
+                email_to.push(to.clone());
+                continue;
+            }
+            match send_to_channel(http_client, channel, &text).await {
+                Ok(()) => delivered.push(channel.label()),
+                Err(err) => {
+                    warn!(
+                        "Failed to send {} notification for account {}: {}",
+                        channel.label(),
+                        notification.account_id,
+                        err
+                    );
+                    failed.push(json!({ "channel": channel.label(), "error": err.to_string() }));
+                }
+            }
+        }
+
+        db.insert_system_log(
+            NOTIFICATIONS_LOG_SOURCE,
+            "info",
+            &text,
+            Some(json!({
+                "accountId": notification.account_id,
+                "event": notification.event.label(),
+                "delivered": delivered,
+                "failed": failed,
+                "emailTo": email_to,
+            })),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn send_to_channel(
+    http_client: &Client,
+    channel: &NotificationChannel,
+    text: &str,
+) -> reqwest::Result<()> {
+    let request = match channel {
+        NotificationChannel::Telegram { bot_token, chat_id } => http_client
+            .post(format!(
+                "https://api.telegram.org/bot{}/sendMessage",
+                bot_token
+            ))
+            .json(&json!({ "chat_id": chat_id, "text": text })),
+        NotificationChannel::Discord { webhook_url } => http_client
+            .post(webhook_url)
+            .json(&json!({ "content": text })),
+        NotificationChannel::Email { .. } => return Ok(()),
+    };
+    // Strip URLs from errors; Telegram URLs embed the bot token.
+    request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|err| err.without_url())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NotificationSubscription;
+    use chrono::{TimeZone, Utc};
+    use std::collections::HashMap;
+
+    fn trade(ticker: &str, quantity: i32, status: TradeStatus) -> Trade {
+        Trade {
+            id: format!("trade-{}", ticker),
+            strategy_id: "strategy".to_string(),
+            ticker: ticker.to_string(),
+            quantity,
+            price: 100.0,
+            date: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            status,
+            pnl: None,
+            fee: None,
+            exit_price: None,
+            exit_date: None,
+            stop_loss: None,
+            stop_loss_triggered: None,
+            entry_order_id: None,
+            entry_cancel_after: None,
+            stop_order_id: None,
+            exit_order_id: None,
+            target_order_id: None,
+            changes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn classifies_fills_and_stop_triggers() {
+        let pending = trade("AAA", 10, TradeStatus::Pending);
+        let active = trade("AAA", 10, TradeStatus::Active);
+        let fill = trade_notification("acct", &pending, &active).expect("entry fill");
+        assert_eq!(fill.event, NotificationEvent::Fill);
+
+        let mut stopped = trade("AAA", 10, TradeStatus::Closed);
+        stopped.stop_loss_triggered = Some(true);
+        stopped.exit_price = Some(95.0);
+        stopped.pnl = Some(-50.0);
+        let stop = trade_notification("acct", &active, &stopped).expect("stop trigger");
+        assert_eq!(stop.event, NotificationEvent::StopTriggered);
+        assert!(stop.text.contains("-50.00"), "{}", stop.text);
+
+        assert!(trade_notification("acct", &active, &active).is_none());
+    }
+
+    #[test]
+    fn reports_position_quantity_mismatches() {
+        let trades = vec![
+            trade("AAA", 10, TradeStatus::Active),
+            trade("AAA", 5, TradeStatus::Active),
+            trade("BBB", 7, TradeStatus::Active),
+            trade("CCC", 3, TradeStatus::Pending),
+        ];
+        let position = |ticker: &str, quantity| AccountPositionState {
+            ticker: ticker.to_string(),
+            quantity,
+            avg_entry_price: 100.0,
+            current_price: None,
+        };
+        let positions = vec![position("AAA", 15), position("BBB", 4), position("DDD", 2)];
+
+        assert_eq!(
+            position_mismatches(&trades, &positions),
+            vec![("BBB".to_string(), 7, 4), ("DDD".to_string(), 0, 2)]
+        );
+    }
+
+    #[test]
+    fn account_channels_fall_back_to_wildcard_and_filter_events() {
+        let raw = r#"{
+            "*": [{"channel": "discord", "webhookUrl": "https://discord.test/hook"}],
+            "acct": [{"channel": "email", "to": "ops@example.com", "events": ["daily_pnl"]}]
+        }"#;
+        let mut map = HashMap::new();
+        map.insert("ACCOUNT_NOTIFICATION_CHANNELS".to_string(), raw.to_string());
+        let settings = NotificationSettings::from_settings_map(&map).unwrap();
+
+        let own: &[NotificationSubscription] = settings.channels_for("acct");
+        assert_eq!(own.len(), 1);
+        assert!(own[0].wants(NotificationEvent::DailyPnl));
+        assert!(!own[0].wants(NotificationEvent::Fill));
+        let fallback = settings.channels_for("other");
+        assert_eq!(fallback[0].channel.label(), "discord");
+        assert!(fallback[0].wants(NotificationEvent::Mismatch));
+    }
+}
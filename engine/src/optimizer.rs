@@ -1,51 +1,153 @@
 use crate::app_url::resolve_api_base_url;
 use crate::backtest_api_client::build_async_client;
 use crate::cache::{CacheManager, CacheStoreParams};
+use crate::cancellation::CancellationToken;
 use crate::config::{
     resolve_backtest_initial_capital, EngineRuntimeSettings, LocalOptimizationObjective,
 };
 use crate::data_context::MarketData;
 use crate::database::Database;
 use crate::engine::Engine;
+use crate::memory_guard::{MemoryGuard, MemoryPressure};
 use crate::models::{
-    encode_string_parameter, BacktestTask, BacktestTaskResult, Candle, OptimizationResult,
-    ParameterRange, StrategyTemplate, Trade,
+    encode_string_parameter, BacktestResult, BacktestRun, BacktestTask, BacktestTaskResult,
+    GeneratedSignal, OptimizationResult, ParameterRange, StrategyTemplate, TickerWarmup, Trade,
 };
 use crate::param_utils::{add_single_parameter_neighbor_variations, clamp_to_bounds};
-use crate::strategy::create_strategy;
+use crate::strategy::{create_strategy, parameter_specs, registry_template};
 use anyhow::{anyhow, Result};
 use chrono::prelude::*;
 use crossbeam_channel::{bounded, Receiver, Sender};
 use indicatif::{ProgressBar, ProgressStyle};
 use log::{info, warn};
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 enum VariationOutcome {
     NoChange,
     Improved(OptimizationResult),
 }
 
+/// Limits that end a local search before it converges; `None` leaves a limit off.
+#[derive(Debug, Clone, Default)]
+pub struct OptimizationBudget {
+    pub max_evals: Option<usize>,
+    pub max_duration: Option<Duration>,
+    /// Stop after this many evaluations without the best objective improving by more than
+    /// `plateau_epsilon`.
+    pub plateau_evals: Option<usize>,
+    pub plateau_epsilon: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizationStopReason {
+    Converged,
+    MaxEvals,
+    MaxHours,
+    Plateau,
+}
+
+impl OptimizationStopReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            OptimizationStopReason::Converged => "converged",
+            OptimizationStopReason::MaxEvals => "max_evals",
+            OptimizationStopReason::MaxHours => "max_hours",
+            OptimizationStopReason::Plateau => "plateau",
+        }
+    }
+
+    /// Whether the run was cut short by an evaluation or time limit rather than finishing.
+    pub fn is_budget_limit(self) -> bool {
+        matches!(
+            self,
+            OptimizationStopReason::MaxEvals | OptimizationStopReason::MaxHours
+        )
+    }
+}
+
+/// Counts evaluations of one local search run against its budget.
+struct BudgetTracker<'a> {
+    budget: &'a OptimizationBudget,
+    started: Instant,
+    evaluations: usize,
+    evals_since_improvement: usize,
+}
+
+impl<'a> BudgetTracker<'a> {
+    fn new(budget: &'a OptimizationBudget) -> Self {
+        Self {
+            budget,
+            started: Instant::now(),
+            evaluations: 0,
+            evals_since_improvement: 0,
+        }
+    }
+
+    /// How many of `requested` variations the next batch may evaluate, or why the run must
+    /// stop before it.
+    fn next_batch(&self, requested: usize) -> Result<usize, OptimizationStopReason> {
+        if let Some(max_duration) = self.budget.max_duration {
+            if self.started.elapsed() >= max_duration {
+                return Err(OptimizationStopReason::MaxHours);
+            }
+        }
+        match self.budget.max_evals {
+            Some(max_evals) if self.evaluations >= max_evals => {
+                Err(OptimizationStopReason::MaxEvals)
+            }
+            Some(max_evals) => Ok(requested.min(max_evals - self.evaluations)),
+            None => Ok(requested),
+        }
+    }
+
+    /// Records a batch of `evaluated` variations that raised the best objective by
+    /// `improvement` and reports a plateau once too many pass without real progress.
+    fn record(&mut self, evaluated: usize, improvement: f64) -> Option<OptimizationStopReason> {
+        self.evaluations += evaluated;
+        if improvement > self.budget.plateau_epsilon {
+            self.evals_since_improvement = 0;
+        } else {
+            self.evals_since_improvement += evaluated;
+        }
+        match self.budget.plateau_evals {
+            Some(limit) if self.evals_since_improvement >= limit => {
+                Some(OptimizationStopReason::Plateau)
+            }
+            _ => None,
+        }
+    }
+}
+
 pub(crate) fn parameter_signature(parameters: &HashMap<String, f64>) -> String {
     let mut sorted: Vec<_> = parameters.iter().collect();
     sorted.sort_by(|a, b| a.0.cmp(b.0));
     format!("{:?}", sorted)
 }
 
+/// Numeric template parameters with bounds; bounds the template leaves out come from the
+/// strategy's declared `ParameterSpec`s.
 fn collect_numeric_parameter_ranges(
     template: &StrategyTemplate,
 ) -> (Vec<String>, HashMap<String, ParameterRange>) {
     let mut parameters_to_optimize = Vec::new();
     let mut parameter_ranges = HashMap::new();
+    let specs = parameter_specs(&template.id).unwrap_or_default();
 
     for param in &template.parameters {
         if param.r#type != "number" {
             continue;
         }
 
-        let (Some(min), Some(max), Some(step)) = (param.min, param.max, param.step) else {
+        let spec = specs
+            .iter()
+            .find(|spec| spec.name == param.name && spec.is_tunable());
+        let (Some(min), Some(max), Some(step)) = (
+            param.min.or(spec.map(|spec| spec.min)),
+            param.max.or(spec.map(|spec| spec.max)),
+            param.step.or(spec.map(|spec| spec.step)),
+        ) else {
             continue;
         };
 
@@ -61,6 +163,10 @@ pub struct OptimizationEngine<'a> {
     db: Option<&'a mut Database>,
     cache_manager: &'a CacheManager,
     data: &'a MarketData,
+    cancellation: &'a CancellationToken,
+    memory_guard: Option<MemoryGuard>,
+    /// Parallel backtests allowed after memory pressure forced a reduction.
+    worker_cap: Option<usize>,
 }
 
 impl<'a> OptimizationEngine<'a> {
@@ -109,10 +215,12 @@ impl<'a> OptimizationEngine<'a> {
             );
         }
 
-        Err(anyhow!(
-            "Template {} not found in cached snapshot or database",
-            template_id
-        ))
+        registry_template(template_id).ok_or_else(|| {
+            anyhow!(
+                "Template {} not found in cached snapshot, database or strategy registry",
+                template_id
+            )
+        })
     }
 
     fn db_ref(&self) -> Option<&Database> {
@@ -127,14 +235,48 @@ impl<'a> OptimizationEngine<'a> {
         db: Option<&'a mut Database>,
         cache_manager: &'a CacheManager,
         data: &'a MarketData,
+        cancellation: &'a CancellationToken,
     ) -> Self {
         Self {
             db,
             cache_manager,
             data,
+            cancellation,
+            memory_guard: None,
+            worker_cap: None,
         }
     }
 
+    /// Keeps parameter batches within `guard`'s memory limit: near the limit the shared caches
+    /// are dropped and fewer backtests run in parallel; at the limit the batch fails.
+    pub fn with_memory_guard(mut self, guard: Option<MemoryGuard>) -> Self {
+        self.memory_guard = guard;
+        self
+    }
+
+    /// Number of parallel backtests for the next batch after checking memory pressure.
+    fn worker_count(&mut self, requested: usize) -> Result<usize> {
+        let mut workers = self.worker_cap.map_or(requested, |cap| requested.min(cap));
+        let Some(guard) = self.memory_guard else {
+            return Ok(workers);
+        };
+        match guard.pressure() {
+            MemoryPressure::Normal => {}
+            MemoryPressure::High => {
+                self.cache_manager.cross_sectional_cache().clear();
+                let reduced = (workers / 2).max(1);
+                warn!(
+                    "Memory use is near the limit; dropped cached cross-sectional contexts and reduced parallel backtests from {} to {}",
+                    workers, reduced
+                );
+                self.worker_cap = Some(reduced);
+                workers = reduced;
+            }
+            MemoryPressure::Exceeded(resident) => return Err(guard.exceeded_error(resident)),
+        }
+        Ok(workers)
+    }
+
     pub async fn detect_optimizable_parameters(
         &mut self,
         template_id: &str,
@@ -161,6 +303,7 @@ impl<'a> OptimizationEngine<'a> {
         template_id: &str,
         parameters_to_optimize: &[String],
         parameter_ranges: &HashMap<String, ParameterRange>,
+        budget: &OptimizationBudget,
     ) -> Result<()> {
         let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
         let backtest_initial_capital = resolve_backtest_initial_capital(self.data.settings());
@@ -189,8 +332,9 @@ impl<'a> OptimizationEngine<'a> {
 
         let mut best_result: Option<OptimizationResult> = None;
         let mut best_score = f64::NEG_INFINITY;
+        let mut tracker = BudgetTracker::new(budget);
 
-        loop {
+        let stop_reason = loop {
             let mut seen_variations = HashSet::new();
             let mut neighbor_variations = Vec::new();
 
@@ -214,8 +358,14 @@ impl<'a> OptimizationEngine<'a> {
             );
 
             if neighbor_variations.is_empty() {
-                break;
+                break OptimizationStopReason::Converged;
             }
+            let allowed = match tracker.next_batch(neighbor_variations.len()) {
+                Ok(allowed) => allowed,
+                Err(reason) => break reason,
+            };
+            let truncated = allowed < neighbor_variations.len();
+            neighbor_variations.truncate(allowed);
 
             match self
                 .evaluate_variation_batch(
@@ -249,17 +399,24 @@ impl<'a> OptimizationEngine<'a> {
                     }
 
                     let params_changed = result.parameters != current_params;
+                    let plateau = tracker.record(neighbor_variations.len(), score - best_score);
                     best_score = score;
                     current_params = result.parameters.clone();
                     best_result = Some(result);
 
                     if !params_changed {
-                        break;
+                        break OptimizationStopReason::Converged;
+                    }
+                    if let Some(reason) = plateau {
+                        break reason;
                     }
                 }
-                VariationOutcome::NoChange => break,
+                VariationOutcome::NoChange if truncated => {
+                    break OptimizationStopReason::MaxEvals;
+                }
+                VariationOutcome::NoChange => break OptimizationStopReason::Converged,
             }
-        }
+        };
 
         let Some(best_result) = best_result else {
             info!(
@@ -270,24 +427,45 @@ impl<'a> OptimizationEngine<'a> {
 
         let final_score = Self::objective_score(&best_result, objective);
         info!(
-            "Local search finished. Best {}: {:.4} (CAGR {:.2}%) with max drawdown {:.2}%.",
+            "Local search finished ({}) after {} evaluation(s) in {:.1}h. Best {}: {:.4} (CAGR {:.2}%) with max drawdown {:.2}%.",
+            stop_reason.label(),
+            tracker.evaluations,
+            tracker.started.elapsed().as_secs_f64() / 3600.0,
             objective_label,
             final_score,
             best_result.cagr * 100.0,
             best_result.max_drawdown_ratio * 100.0
         );
 
-        let final_results = self
-            .run_parallel_backtests(template_id, &[current_params.clone()], true)
-            .await?;
+        let final_task = BacktestTask {
+            id: format!("{}_final", template_id),
+            template_id: template_id.to_string(),
+            parameters: current_params.clone(),
+        };
+        let final_result = Self::run_single_backtest(
+            self.data,
+            runtime_settings.clone(),
+            self.cache_manager,
+            self.cancellation,
+            &final_task,
+            true,
+            Some(stop_reason),
+        );
+        self.cancellation.check()?;
 
-        if final_results.is_empty() {
-            info!("Final validation produced no results; reusing best observed variation.");
-            self.print_results(std::slice::from_ref(&best_result), 1);
-        } else {
-            self.print_results(&final_results, 1);
+        match final_result.result {
+            Some(result) => self.print_results(std::slice::from_ref(&result), 1),
+            None => {
+                info!("Final validation produced no results; reusing best observed variation.");
+                self.print_results(std::slice::from_ref(&best_result), 1);
+            }
         }
-        if let Some(db) = self.db_ref() {
+        if stop_reason.is_budget_limit() {
+            info!(
+                "Leaving local_optimization_version of template {} unchanged so the next run resumes the search",
+                template_id
+            );
+        } else if let Some(db) = self.db_ref() {
             match db
                 .update_template_local_optimization_version(template_id, local_optimization_version)
                 .await
@@ -334,8 +512,9 @@ impl<'a> OptimizationEngine<'a> {
             return Ok(VariationOutcome::NoChange);
         }
 
+        let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
         let results = self
-            .run_parallel_backtests(template_id, variations, true)
+            .run_parallel_backtests(template_id, variations, true, runtime_settings)
             .await?;
 
         if results.is_empty() {
@@ -417,10 +596,161 @@ impl<'a> OptimizationEngine<'a> {
         variations: &[HashMap<String, f64>],
         use_cache: bool,
     ) -> Result<Vec<OptimizationResult>> {
-        self.run_parallel_backtests(template_id, variations, use_cache)
+        let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
+        self.run_parallel_backtests(template_id, variations, use_cache, runtime_settings)
+            .await
+    }
+
+    /// Runs the batch with caller-provided runtime settings instead of the snapshot settings.
+    pub async fn run_parameter_batch_with_settings(
+        &mut self,
+        template_id: &str,
+        variations: &[HashMap<String, f64>],
+        runtime_settings: EngineRuntimeSettings,
+    ) -> Result<Vec<OptimizationResult>> {
+        self.run_parallel_backtests(template_id, variations, false, runtime_settings)
             .await
     }
 
+    /// Backtests one parameter set, filled in with the template defaults, and returns the full
+    /// result including daily snapshots. Nothing is read from or written to the cache.
+    pub async fn backtest_parameters(
+        &mut self,
+        template_id: &str,
+        parameters: &HashMap<String, f64>,
+    ) -> Result<BacktestResult> {
+        let template = self.load_strategy_template(template_id).await?;
+        let mut parameters = self.merge_with_template_defaults_numeric(&template, parameters);
+        parameters.insert(
+            "initialCapital".to_string(),
+            resolve_backtest_initial_capital(self.data.settings()),
+        );
+        let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
+        let strategy = create_strategy(template_id, parameters.clone())?;
+        let run = self.data_engine(&parameters, runtime_settings).backtest(
+            Some(strategy.as_ref()),
+            template_id,
+            self.data.tickers_arc().as_slice(),
+            self.data.all_candles_arc().as_slice(),
+            self.data.unique_dates_arc().as_slice(),
+            None,
+            None,
+            None,
+        )?;
+        Ok(run.result)
+    }
+
+    /// Backtests one parameter set, filled in with the template defaults, once per initial
+    /// capital in `capitals`. The strategy runs once to generate its signals; every capital
+    /// then replays them from the same start date so the runs record their skipped buys.
+    pub async fn backtest_capital_ladder(
+        &mut self,
+        template_id: &str,
+        parameters: &HashMap<String, f64>,
+        capitals: &[f64],
+    ) -> Result<Vec<BacktestRun>> {
+        let template = self.load_strategy_template(template_id).await?;
+        let mut parameters = self.merge_with_template_defaults_numeric(&template, parameters);
+        let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
+        let tickers = self.data.tickers_arc();
+        let candles = self.data.all_candles_arc();
+        let dates = self.data.unique_dates_arc();
+
+        let strategy = create_strategy(template_id, parameters.clone())?;
+        let signal_run = self
+            .data_engine(&parameters, runtime_settings.clone())
+            .backtest(
+                Some(strategy.as_ref()),
+                template_id,
+                tickers.as_slice(),
+                candles.as_slice(),
+                dates.as_slice(),
+                None,
+                None,
+                None,
+            )?;
+        let start_date = signal_run.result.start_date;
+
+        let mut runs = Vec::with_capacity(capitals.len());
+        for capital in capitals {
+            parameters.insert("initialCapital".to_string(), *capital);
+            runs.push(
+                self.data_engine(&parameters, runtime_settings.clone())
+                    .backtest(
+                        None,
+                        template_id,
+                        tickers.as_slice(),
+                        candles.as_slice(),
+                        dates.as_slice(),
+                        Some(&signal_run.signals),
+                        Some(start_date),
+                        None,
+                    )?,
+            );
+        }
+        Ok(runs)
+    }
+
+    /// Backtests one parameter set like `backtest_parameters`, then replays the signals it
+    /// generated `sessions` trading days late from the same start date. Returns the strategy
+    /// run and the delayed replay.
+    pub async fn backtest_with_delayed_signals(
+        &mut self,
+        template_id: &str,
+        parameters: &HashMap<String, f64>,
+        sessions: usize,
+    ) -> Result<(BacktestRun, BacktestRun)> {
+        let template = self.load_strategy_template(template_id).await?;
+        let mut parameters = self.merge_with_template_defaults_numeric(&template, parameters);
+        parameters.insert(
+            "initialCapital".to_string(),
+            resolve_backtest_initial_capital(self.data.settings()),
+        );
+        let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
+        let tickers = self.data.tickers_arc();
+        let candles = self.data.all_candles_arc();
+        let dates = self.data.unique_dates_arc();
+
+        let strategy = create_strategy(template_id, parameters.clone())?;
+        let run = self
+            .data_engine(&parameters, runtime_settings.clone())
+            .backtest(
+                Some(strategy.as_ref()),
+                template_id,
+                tickers.as_slice(),
+                candles.as_slice(),
+                dates.as_slice(),
+                None,
+                None,
+                None,
+            )?;
+        let delayed_signals = delay_signals(&run.signals, dates.as_slice(), sessions);
+        let delayed = self.data_engine(&parameters, runtime_settings).backtest(
+            None,
+            template_id,
+            tickers.as_slice(),
+            candles.as_slice(),
+            dates.as_slice(),
+            Some(&delayed_signals),
+            Some(run.result.start_date),
+            None,
+        )?;
+        Ok((run, delayed))
+    }
+
+    /// Engine over this optimizer's market data, fundamentals and cross-sectional cache.
+    fn data_engine(
+        &self,
+        parameters: &HashMap<String, f64>,
+        runtime_settings: EngineRuntimeSettings,
+    ) -> Engine {
+        let mut engine = Engine::from_parameters(parameters, runtime_settings);
+        engine.set_auxiliary_data(self.data.auxiliary_data_arc());
+        engine.set_cross_sectional_cache(self.cache_manager.cross_sectional_cache().clone());
+        engine.set_cancellation(self.cancellation.clone());
+        engine
+    }
+
     async fn load_baseline_parameters(
         &self,
         template_id: &str,
@@ -499,18 +829,24 @@ impl<'a> OptimizationEngine<'a> {
         template_id: &str,
         variations: &[HashMap<String, f64>],
         use_cache: bool,
+        runtime_settings: EngineRuntimeSettings,
     ) -> Result<Vec<OptimizationResult>> {
         if variations.is_empty() {
             return Ok(Vec::new());
         }
 
         let variation_count = variations.len();
-        let runtime_settings = EngineRuntimeSettings::from_settings_map(self.data.settings())?;
         let backtest_initial_capital = resolve_backtest_initial_capital(self.data.settings());
         info!("Running {} backtests...", variation_count);
 
-        let num_workers = std::cmp::min(variation_count, std::cmp::max(1, num_cpus::get()));
+        let num_workers = self.worker_count(std::cmp::min(
+            variation_count,
+            std::cmp::max(1, rayon::current_num_threads()),
+        ))?;
         info!("Using {} worker threads", num_workers);
+        // Cancelled by the caller's token, or here when memory runs out mid-batch.
+        let batch_cancellation = self.cancellation.child();
+        let mut memory_error = None;
 
         let (tx, rx): (Sender<BacktestTask>, Receiver<BacktestTask>) = bounded(variation_count);
         let (result_tx, result_rx): (Sender<BacktestTaskResult>, Receiver<BacktestTaskResult>) =
@@ -520,26 +856,25 @@ impl<'a> OptimizationEngine<'a> {
         for _worker_id in 0..num_workers {
             let rx = rx.clone();
             let result_tx = result_tx.clone();
-            let all_candles = self.data.all_candles_arc();
-            let unique_dates = self.data.unique_dates_arc();
-            let tickers = self.data.tickers_arc();
-            let ticker_expense_map = self.data.ticker_expense_map_arc();
+            let data = self.data.clone();
             let cache_manager = self.cache_manager.clone();
-            let use_cache = use_cache;
             let runtime_settings = runtime_settings.clone();
+            let cancellation = batch_cancellation.clone();
 
             let handle = thread::spawn(move || {
                 while let Ok(task) = rx.recv() {
+                    if cancellation.is_cancelled() {
+                        break;
+                    }
                     let start_time = Instant::now();
                     let result = Self::run_single_backtest(
-                        all_candles.as_slice(),
-                        unique_dates.as_slice(),
-                        tickers.as_slice(),
-                        ticker_expense_map.clone(),
+                        &data,
                         runtime_settings.clone(),
                         &cache_manager,
+                        &cancellation,
                         &task,
                         use_cache,
+                        None,
                     );
                     let duration = start_time.elapsed();
 
@@ -615,6 +950,13 @@ impl<'a> OptimizationEngine<'a> {
         );
 
         while completed < variation_count {
+            if let Some(guard) = self.memory_guard {
+                if let MemoryPressure::Exceeded(resident) = guard.pressure() {
+                    batch_cancellation.cancel();
+                    memory_error = Some(guard.exceeded_error(resident));
+                    break;
+                }
+            }
             match result_rx.recv_timeout(std::time::Duration::from_millis(200)) {
                 Ok(result) => {
                     completed += 1;
@@ -626,7 +968,11 @@ impl<'a> OptimizationEngine<'a> {
                         failed_workers += 1;
                     }
                 }
-                Err(crossbeam_channel::RecvTimeoutError::Timeout) => {}
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+                    if self.cancellation.is_cancelled() {
+                        break;
+                    }
+                }
                 Err(crossbeam_channel::RecvTimeoutError::Disconnected) => {
                     warn!("Result channel closed unexpectedly. Some results may be lost.");
                     break;
@@ -647,20 +993,25 @@ impl<'a> OptimizationEngine<'a> {
         for handle in handles {
             handle.join().unwrap();
         }
+        if let Some(error) = memory_error {
+            return Err(error);
+        }
+        self.cancellation.check()?;
         Ok(results)
     }
 
     fn run_single_backtest(
-        all_candles: &[Candle],
-        unique_dates: &[DateTime<Utc>],
-        tickers: &[String],
-        ticker_expense_map: Arc<HashMap<String, f64>>,
+        data: &MarketData,
         runtime_settings: EngineRuntimeSettings,
         cache_manager: &CacheManager,
+        cancellation: &CancellationToken,
         task: &BacktestTask,
         use_cache: bool,
+        stop_reason: Option<OptimizationStopReason>,
     ) -> BacktestTaskResult {
-        if use_cache {
+        // A stop reason marks the final run of an optimization, which always recomputes so the
+        // reason is stored alongside fresh metrics.
+        if use_cache && stop_reason.is_none() {
             if let Some(cached_result) =
                 cache_manager.check_cache(&task.template_id, &task.parameters)
             {
@@ -685,13 +1036,15 @@ impl<'a> OptimizationEngine<'a> {
 
         let start_time = Instant::now();
         let mut engine = Engine::from_parameters(&task.parameters, runtime_settings);
-        engine.set_ticker_expense_map(ticker_expense_map);
+        engine.set_auxiliary_data(data.auxiliary_data_arc());
+        engine.set_cross_sectional_cache(cache_manager.cross_sectional_cache().clone());
+        engine.set_cancellation(cancellation.clone());
         let backtest_run = match engine.backtest(
             Some(strategy.as_ref()),
             &task.template_id,
-            tickers,
-            all_candles,
-            unique_dates,
+            data.tickers(),
+            data.all_candles(),
+            data.unique_dates(),
             None,
             None,
             None,
@@ -706,6 +1059,9 @@ impl<'a> OptimizationEngine<'a> {
             }
         };
         let duration_minutes = start_time.elapsed().as_secs_f64() / 60.0;
+        if stop_reason.is_some() {
+            warn_unfinished_warmups(&task.template_id, &backtest_run.warmup);
+        }
         let (top_abs_gain_ticker, top_rel_gain_ticker) =
             extract_top_ticker_gains(&backtest_run.result.trades);
 
@@ -719,18 +1075,24 @@ impl<'a> OptimizationEngine<'a> {
             win_rate: backtest_run.result.performance.win_rate,
             total_trades: backtest_run.result.performance.total_trades,
             calmar_ratio: backtest_run.result.performance.calmar_ratio,
+            monthly_returns: backtest_run.result.performance.monthly_returns.clone(),
+            excess_return: backtest_run.result.excess_return(),
+            capacity_aum: backtest_run.result.performance.capacity_aum,
+            entry_week_concentration: backtest_run.result.performance.entry_week_concentration,
+            r_expectancy: backtest_run.result.performance.r_expectancy,
         };
         if use_cache {
             cache_manager.store_cache(CacheStoreParams {
                 template_id: task.template_id.clone(),
                 parameters: task.parameters.clone(),
                 result: optimization_result.clone(),
-                ticker_count: tickers.len() as i32,
-                start_date: unique_dates[0],
-                end_date: unique_dates[unique_dates.len() - 1],
+                ticker_count: data.tickers().len() as i32,
+                start_date: data.unique_dates()[0],
+                end_date: data.unique_dates()[data.unique_dates().len() - 1],
                 duration_minutes,
                 top_absolute_gain_ticker: top_abs_gain_ticker,
                 top_relative_gain_ticker: top_rel_gain_ticker,
+                stop_reason: stop_reason.map(|reason| reason.label().to_string()),
             });
         }
 
@@ -770,6 +1132,26 @@ impl<'a> OptimizationEngine<'a> {
     }
 }
 
+/// Warns about tickers whose history is too short for the strategy's warm-up, since they never
+/// trade.
+fn warn_unfinished_warmups(template_id: &str, warmup: &[TickerWarmup]) {
+    let cold: Vec<&str> = warmup
+        .iter()
+        .filter(|ticker| ticker.ready_date.is_none())
+        .map(|ticker| ticker.ticker.as_str())
+        .collect();
+    if cold.is_empty() {
+        return;
+    }
+    warn!(
+        "{} of {} tickers have too little history for the {} warm-up and never traded: {}",
+        cold.len(),
+        warmup.len(),
+        template_id,
+        cold.join(", ")
+    );
+}
+
 fn extract_top_ticker_gains(trades: &[Trade]) -> (Option<String>, Option<String>) {
     let mut aggregated: HashMap<String, (f64, f64)> = HashMap::new();
 
@@ -824,3 +1206,82 @@ fn extract_top_ticker_gains(trades: &[Trade]) -> (Option<String>, Option<String>
         top_relative.map(|(ticker, _)| ticker),
     )
 }
+
+/// `signals` moved `sessions` entries later in `dates`; signals that would land after the last
+/// date are dropped.
+pub fn delay_signals(
+    signals: &[GeneratedSignal],
+    dates: &[DateTime<Utc>],
+    sessions: usize,
+) -> Vec<GeneratedSignal> {
+    signals
+        .iter()
+        .filter_map(|signal| {
+            let index = dates.partition_point(|date| *date < signal.date);
+            let date = *dates.get(index + sessions)?;
+            Some(GeneratedSignal {
+                date,
+                ..signal.clone()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{delay_signals, BudgetTracker, OptimizationBudget, OptimizationStopReason};
+    use crate::models::{GeneratedSignal, SignalAction};
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    #[test]
+    fn delays_signals_by_trading_sessions() {
+        let dates: Vec<_> = [4, 5, 6, 7]
+            .iter()
+            .map(|day| Utc.with_ymd_and_hms(2024, 3, *day, 0, 0, 0).unwrap())
+            .collect();
+        let signal = |date| GeneratedSignal {
+            date,
+            ticker: "AAA".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(0.5),
+        };
+        let delayed = delay_signals(
+            &[signal(dates[0]), signal(dates[2]), signal(dates[3])],
+            &dates,
+            1,
+        );
+        let delayed_dates: Vec<_> = delayed.iter().map(|signal| signal.date).collect();
+        assert_eq!(delayed_dates, vec![dates[1], dates[3]]);
+        assert_eq!(delayed[0].confidence, Some(0.5));
+    }
+
+    #[test]
+    fn budget_tracker_caps_evaluations_and_detects_plateaus() {
+        let budget = OptimizationBudget {
+            max_evals: Some(10),
+            plateau_evals: Some(6),
+            plateau_epsilon: 0.01,
+            ..OptimizationBudget::default()
+        };
+        let mut tracker = BudgetTracker::new(&budget);
+        assert_eq!(tracker.next_batch(4), Ok(4));
+        assert_eq!(tracker.record(4, f64::INFINITY), None);
+        assert_eq!(tracker.record(3, 0.005), None);
+        assert_eq!(tracker.next_batch(4), Ok(3));
+        assert_eq!(
+            tracker.record(3, 0.001),
+            Some(OptimizationStopReason::Plateau)
+        );
+        assert_eq!(tracker.next_batch(1), Err(OptimizationStopReason::MaxEvals));
+
+        let timed = OptimizationBudget {
+            max_duration: Some(Duration::ZERO),
+            ..OptimizationBudget::default()
+        };
+        assert_eq!(
+            BudgetTracker::new(&timed).next_batch(5),
+            Err(OptimizationStopReason::MaxHours)
+        );
+    }
+}
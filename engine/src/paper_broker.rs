@@ -0,0 +1,457 @@
+use crate::alpaca::{OrderEvaluation, OrderState};
+use crate::broker::Broker;
+use crate::config::PaperBrokerSettings;
+use crate::database::Database;
+use crate::engine::{AccountPositionState, AccountStateSnapshot, AccountStopOrderState};
+use crate::models::{Candle, TradeStatus};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A dispatched paper order as recorded in `account_operations` (entries and exits) or on the
+/// trade it protects (stops and take-profit targets). `live` is false for a protective order
+/// whose entry has not filled yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperOrder {
+    pub ticker: String,
+    pub buy: bool,
+    pub quantity: i32,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub submitted_at: DateTime<Utc>,
+    pub live: bool,
+    /// Limit orders that keep working past their first session, as take-profit targets do.
+    pub good_till_cancelled: bool,
+    /// The other leg of a stop and take-profit pair; whichever fills first cancels this one.
+    pub one_cancels_other: Option<Box<PaperOrder>>,
+}
+
+impl PaperOrder {
+    /// Exit protecting a trade of `quantity` shares (negative for shorts): a stop at
+    /// `stop_price` or a good-till-cancelled take-profit limit at `limit_price`.
+    pub fn protective_exit(
+        ticker: &str,
+        quantity: i32,
+        limit_price: Option<f64>,
+        stop_price: Option<f64>,
+        submitted_at: DateTime<Utc>,
+        live: bool,
+    ) -> Self {
+        Self {
+            ticker: ticker.to_string(),
+            buy: quantity < 0,
+            quantity: quantity.abs(),
+            limit_price,
+            stop_price,
+            submitted_at,
+            live: live && (limit_price.is_some() || stop_price.is_some()),
+            good_till_cancelled: limit_price.is_some(),
+            one_cancels_other: None,
+        }
+    }
+
+    /// The leg of a trade's stop and take-profit pair named `order_id`, linked to the other
+    /// leg when the trade has both.
+    pub fn exit_leg(
+        order_id: &str,
+        stop: Option<(&str, PaperOrder)>,
+        target: Option<(&str, PaperOrder)>,
+    ) -> Option<Self> {
+        let (requested, sibling) = match (stop, target) {
+            (Some((stop_id, stop)), target) if stop_id == order_id => (stop, target),
+            (stop, Some((_, target))) => (target, stop),
+            (Some((_, stop)), None) => (stop, None),
+            (None, None) => return None,
+        };
+        Some(match sibling {
+            Some((_, sibling)) => Self {
+                one_cancels_other: Some(Box::new(sibling)),
+                ..requested
+            },
+            None => requested,
+        })
+    }
+}
+
+/// Simulated broker for `paper` accounts. Orders are the ones dispatch recorded in the
+/// database; fills come from daily candles, and the account state is derived from the
+/// account's live trades on top of `BACKTEST_INITIAL_CAPITAL`.
+pub struct PaperBroker<'a> {
+    db: &'a Database,
+    account_id: String,
+    settings: PaperBrokerSettings,
+}
+
+impl<'a> PaperBroker<'a> {
+    pub fn new(db: &'a Database, account_id: &str, settings: PaperBrokerSettings) -> Self {
+        Self {
+            db,
+            account_id: account_id.to_string(),
+            settings,
+        }
+    }
+}
+
+impl Broker for PaperBroker<'_> {
+    async fn fetch_account_state(&self) -> Result<AccountStateSnapshot> {
+        let trades: Vec<_> = self
+            .db
+            .get_live_trades_with_accounts()
+            .await?
+            .into_iter()
+            .filter(|candidate| candidate.account_id == self.account_id)
+            .map(|candidate| candidate.trade)
+            .collect();
+        let realized = self.db.get_account_realized_pnl(&self.account_id).await?;
+        let fx = self.db.get_account_fx(&self.account_id).await?;
+
+        let mut symbols: Vec<String> = trades.iter().map(|trade| trade.ticker.clone()).collect();
+        symbols.sort();
+        symbols.dedup();
+        // Candles come back ordered by date, so the last close per ticker wins.
+        let closes: HashMap<String, f64> = self
+            .db
+            .get_candles_for_tickers(&symbols)
+            .await?
+            .into_iter()
+            .map(|candle| (candle.ticker, candle.close))
+            .collect();
+
+        let mut cash = self.settings.starting_cash + realized;
+        let mut market_value = 0.0;
+        let mut holdings: BTreeMap<&str, (i32, f64)> = BTreeMap::new();
+        let mut open_buy_orders = HashSet::new();
+        let mut open_sell_orders = HashSet::new();
+        let mut stop_orders: HashMap<String, Vec<AccountStopOrderState>> = HashMap::new();
+        for trade in &trades {
+            let long = trade.quantity > 0;
+            match trade.status {
+                TradeStatus::Pending => {
+                    if long {
+                        open_buy_orders.insert(trade.ticker.clone());
+                    } else {
+                        open_sell_orders.insert(trade.ticker.clone());
+                    }
+                }
+                TradeStatus::Active => {
+                    let cost = trade.quantity as f64 * trade.price;
+                    cash -= fx.to_account(cost, &trade.ticker);
+                    market_value += fx.to_account(
+                        trade.quantity as f64
+                            * closes.get(&trade.ticker).copied().unwrap_or(trade.price),
+                        &trade.ticker,
+                    );
+                    let holding = holdings.entry(&trade.ticker).or_default();
+                    holding.0 += trade.quantity;
+                    holding.1 += cost;
+                    if trade.exit_order_id.is_some() || trade.target_order_id.is_some() {
+                        if long {
+                            open_sell_orders.insert(trade.ticker.clone());
+                        } else {
+                            open_buy_orders.insert(trade.ticker.clone());
+                        }
+                    }
+                    if let (Some(_), Some(stop_price)) = (&trade.stop_order_id, trade.stop_loss) {
+                        stop_orders.entry(trade.ticker.clone()).or_default().push(
+                            AccountStopOrderState {
+                                quantity: trade.quantity.abs(),
+                                stop_price,
+                                side: if long { "sell" } else { "buy" }.to_string(),
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let positions: Vec<AccountPositionState> = holdings
+            .into_iter()
+            .filter(|(_, (quantity, _))| *quantity != 0)
+            .map(|(ticker, (quantity, cost))| AccountPositionState {
+                ticker: ticker.to_string(),
+                quantity,
+                avg_entry_price: cost / quantity as f64,
+                current_price: closes.get(ticker).copied(),
+            })
+            .collect();
+        Ok(AccountStateSnapshot {
+            available_cash: cash.max(0.0),
+            buying_power: None,
+            held_tickers: positions
+                .iter()
+                .map(|position| position.ticker.clone())
+                .collect(),
+            open_buy_orders,
+            open_sell_orders,
+            positions,
+            stop_orders,
+            equity: Some(cash + market_value),
+            last_equity: None,
+            circuit_breaker_triggered_at: None,
+            risk_overrides: Default::default(),
+            fx,
+        })
+    }
+
+    async fn evaluate_order(&self, order_id: &str) -> Result<Option<OrderEvaluation>> {
+        let trimmed = order_id.trim();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+        let Some(order) = self.db.get_paper_order(trimmed).await? else {
+            warn!("Paper order {} not found", trimmed);
+            return Ok(None);
+        };
+        let candles = self
+            .db
+            .get_ticker_candles_since(&order.ticker, order.submitted_at.date_naive())
+            .await?;
+        Ok(Some(simulate_fill(
+            trimmed,
+            &order,
+            &candles,
+            &self.settings,
+        )))
+    }
+
+    /// Unfilled paper orders exist only as database rows, so there is nothing to pull back.
+    async fn cancel_order(&self, _order_id: &str) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Paper stops cover whatever quantity their trade holds, so the order needs no change.
+    async fn resize_order(&self, order_id: &str, _quantity: i32) -> Result<String> {
+        Ok(order_id.to_string())
+    }
+
+    async fn fetch_halted_symbols(&self, _symbols: &[String]) -> HashSet<String> {
+        HashSet::new()
+    }
+}
+
+/// Fills `order` against daily `candles` (ascending). Market orders fill at the open of the
+/// first candle past the latency, limit orders are day orders that fill on that candle when
+/// its range reaches the limit (at the better of open and limit) and are cancelled otherwise,
+/// and stops fill on the first candle that touches them, at the worse of open and stop.
+/// Good-till-cancelled limits fill on the first candle that reaches them. Day orders fill half
+/// their shares with the configured probability, drawn from a hash of the order id so
+/// repeated evaluations agree. An order linked one-cancels-other is cancelled once its
+/// sibling fills first; when both are touched by the same candle the stop wins.
+pub fn simulate_fill(
+    order_id: &str,
+    order: &PaperOrder,
+    candles: &[Candle],
+    settings: &PaperBrokerSettings,
+) -> OrderEvaluation {
+    let evaluation = simulate_order(order_id, order, candles, settings);
+    let Some(sibling) = order.one_cancels_other.as_deref() else {
+        return evaluation;
+    };
+    let sibling_fill = simulate_order(order_id, sibling, candles, settings);
+    let Some(sibling_filled_at) = sibling_fill
+        .timestamp
+        .filter(|_| matches!(sibling_fill.state, OrderState::Filled))
+    else {
+        return evaluation;
+    };
+    let sibling_first = match (&evaluation.state, evaluation.timestamp) {
+        (OrderState::Filled, Some(filled_at)) => {
+            sibling_filled_at < filled_at
+                || (sibling_filled_at == filled_at && sibling.stop_price.is_some())
+        }
+        _ => true,
+    };
+    if sibling_first {
+        OrderEvaluation {
+            state: OrderState::Cancelled,
+            filled_price: None,
+            filled_quantity: None,
+            timestamp: Some(sibling_filled_at),
+        }
+    } else {
+        evaluation
+    }
+}
+
+fn simulate_order(
+    order_id: &str,
+    order: &PaperOrder,
+    candles: &[Candle],
+    settings: &PaperBrokerSettings,
+) -> OrderEvaluation {
+    let pending = OrderEvaluation {
+        state: OrderState::Pending,
+        filled_price: None,
+        filled_quantity: None,
+        timestamp: None,
+    };
+    if !order.live {
+        return pending;
+    }
+    let submitted_day = order.submitted_at.date_naive();
+    let mut bars = candles
+        .iter()
+        .filter(|candle| {
+            let day = candle.date.date_naive();
+            if settings.latency_bars == 0 {
+                day >= submitted_day
+            } else {
+                day > submitted_day
+            }
+        })
+        .skip(settings.latency_bars.saturating_sub(1));
+
+    let partial = order.stop_price.is_none()
+        && !order.good_till_cancelled
+        && order.quantity > 1
+        && unit_draw(order_id) < settings.partial_fill_probability;
+    let quantity = if partial {
+        order.quantity / 2
+    } else {
+        order.quantity
+    };
+    let fill = |candle: &Candle, price: f64| OrderEvaluation {
+        state: OrderState::Filled,
+        filled_price: Some(price),
+        filled_quantity: Some(quantity as f64),
+        timestamp: Some(candle.date),
+    };
+
+    if let Some(stop) = order.stop_price {
+        return bars
+            .find_map(|candle| {
+                let price = if order.buy {
+                    (candle.high >= stop).then(|| candle.open.max(stop))
+                } else {
+                    (candle.low <= stop).then(|| candle.open.min(stop))
+                };
+                price.map(|price| fill(candle, price))
+            })
+            .unwrap_or(pending);
+    }
+    let limit_fill = |candle: &Candle, limit: f64| {
+        if order.buy {
+            (candle.low <= limit).then(|| candle.open.min(limit))
+        } else {
+            (candle.high >= limit).then(|| candle.open.max(limit))
+        }
+    };
+    if let Some(limit) = order.limit_price.filter(|_| order.good_till_cancelled) {
+        return bars
+            .find_map(|candle| limit_fill(candle, limit).map(|price| fill(candle, price)))
+            .unwrap_or(pending);
+    }
+    let Some(candle) = bars.next() else {
+        return pending;
+    };
+    match order.limit_price {
+        Some(limit) => {
+            let price = limit_fill(candle, limit);
+            match price {
+                Some(price) => fill(candle, price),
+                None => OrderEvaluation {
+                    state: OrderState::Cancelled,
+                    filled_price: None,
+                    filled_quantity: None,
+                    timestamp: Some(candle.date),
+                },
+            }
+        }
+        None => fill(candle, candle.open),
+    }
+}
+
+/// FNV-1a hash of `key` mapped onto [0, 1).
+fn unit_draw(key: &str) -> f64 {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn candle(day: u32, open: f64, low: f64, high: f64) -> Candle {
+        Candle {
+            ticker: "AAA".to_string(),
+            date: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            open,
+            high,
+            low,
+            close: open,
+            unadjusted_close: None,
+            volume_shares: 1_000_000,
+        }
+    }
+
+    fn order(limit_price: Option<f64>, stop_price: Option<f64>, buy: bool) -> PaperOrder {
+        PaperOrder {
+            ticker: "AAA".to_string(),
+            buy,
+            quantity: 10,
+            limit_price,
+            stop_price,
+            submitted_at: Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap(),
+            live: true,
+            good_till_cancelled: false,
+            one_cancels_other: None,
+        }
+    }
+
+    #[test]
+    fn fills_after_latency_and_respects_limits_and_stops() {
+        let candles = vec![
+            candle(2, 100.0, 99.0, 101.0),
+            candle(3, 102.0, 98.0, 103.0),
+            candle(4, 97.0, 90.0, 99.0),
+        ];
+        let settings = PaperBrokerSettings {
+            latency_bars: 1,
+            partial_fill_probability: 0.0,
+            starting_cash: 100_000.0,
+        };
+
+        let market = simulate_fill("m", &order(None, None, true), &candles, &settings);
+        assert!(matches!(market.state, OrderState::Filled));
+        assert_eq!(market.filled_price, Some(102.0));
+        assert_eq!(market.timestamp, Some(candles[1].date));
+        let delayed = PaperBrokerSettings {
+            latency_bars: 3,
+            ..settings
+        };
+        let waiting = simulate_fill("m", &order(None, None, true), &candles, &delayed);
+        assert!(matches!(waiting.state, OrderState::Pending));
+
+        let limit = simulate_fill("l", &order(Some(99.0), None, true), &candles, &settings);
+        assert_eq!(limit.filled_price, Some(99.0));
+        let missed = simulate_fill("l", &order(Some(97.0), None, true), &candles, &settings);
+        assert!(matches!(missed.state, OrderState::Cancelled));
+
+        let stop = simulate_fill("s", &order(None, Some(95.0), false), &candles, &settings);
+        assert_eq!(stop.filled_price, Some(95.0));
+        assert_eq!(stop.timestamp, Some(candles[2].date));
+        let mut waiting_stop = order(None, Some(95.0), false);
+        waiting_stop.live = false;
+        let waiting = simulate_fill("s", &waiting_stop, &candles, &settings);
+        assert!(matches!(waiting.state, OrderState::Pending));
+
+        let always_partial = PaperBrokerSettings {
+            partial_fill_probability: 1.0,
+            ..settings
+        };
+        let partial = simulate_fill("p", &order(None, None, true), &candles, &always_partial);
+        assert_eq!(partial.filled_quantity, Some(5.0));
+        let whole = simulate_fill(
+            "s",
+            &order(None, Some(95.0), false),
+            &candles,
+            &always_partial,
+        );
+        assert_eq!(whole.filled_quantity, Some(10.0));
+    }
+}
@@ -10,6 +10,7 @@ macro_rules! retry_db_operation {
             match ($operation).await {
                 Ok(value) => break Ok(value),
                 Err(err) if attempt >= MAX_ATTEMPTS => break Err(err),
+                Err(err) if $crate::cancellation::is_cancellation(&err) => break Err(err),
                 Err(err) => {
                     log::warn!(
                         "Attempt {}/{} for {} failed: {}. Retrying in {}s.",
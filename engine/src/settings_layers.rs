@@ -0,0 +1,168 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_ENGINE_CONFIG_FILE: &str = "engine.toml";
+pub const ENGINE_SETTING_ENV_PREFIX: &str = "ENGINE_SETTING_";
+
+/// Where an effective setting value came from, lowest precedence first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SettingSource {
+    Snapshot,
+    File,
+    Environment,
+    Database,
+}
+
+impl SettingSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Snapshot => "snapshot",
+            Self::File => "engine.toml",
+            Self::Environment => "env",
+            Self::Database => "database",
+        }
+    }
+}
+
+/// Runtime settings layered under the database: `engine.toml` values, then
+/// `ENGINE_SETTING_<KEY>` environment variables. Database settings override both; without a
+/// database, the market data snapshot's settings sit beneath them instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SettingLayers {
+    file: HashMap<String, String>,
+    environment: HashMap<String, String>,
+}
+
+impl SettingLayers {
+    /// Reads `path`, or `engine.toml` in the working directory when it exists, plus the
+    /// process environment. A missing explicit file is an error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let default_path = Path::new(DEFAULT_ENGINE_CONFIG_FILE);
+        let path = path.or_else(|| default_path.exists().then_some(default_path));
+        let file = match path {
+            Some(path) => {
+                let text = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read engine config {}", path.display()))?;
+                parse_engine_config(&text)
+                    .with_context(|| format!("Invalid engine config {}", path.display()))?
+            }
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            file,
+            environment: environment_settings(std::env::vars()),
+        })
+    }
+
+    /// Effective settings with the source of each value; `snapshot` and `database` are the
+    /// bottom and top layers when available.
+    pub fn resolve(
+        &self,
+        snapshot: Option<&HashMap<String, String>>,
+        database: Option<&HashMap<String, String>>,
+    ) -> BTreeMap<String, (String, SettingSource)> {
+        let mut resolved = BTreeMap::new();
+        let layers = [
+            (SettingSource::Snapshot, snapshot),
+            (SettingSource::File, Some(&self.file)),
+            (SettingSource::Environment, Some(&self.environment)),
+            (SettingSource::Database, database),
+        ];
+        for (source, values) in layers {
+            for (key, value) in values.into_iter().flatten() {
+                resolved.insert(key.clone(), (value.clone(), source));
+            }
+        }
+        resolved
+    }
+
+    /// `resolve` without the sources.
+    pub fn merge(
+        &self,
+        snapshot: Option<&HashMap<String, String>>,
+        database: Option<&HashMap<String, String>>,
+    ) -> HashMap<String, String> {
+        self.resolve(snapshot, database)
+            .into_iter()
+            .map(|(key, (value, _))| (key, value))
+            .collect()
+    }
+}
+
+/// Top-level `KEY = value` pairs; strings, numbers and booleans are stored as their text.
+fn parse_engine_config(text: &str) -> Result<HashMap<String, String>> {
+    let table: toml::Table = toml::from_str(text)?;
+    table
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                other => {
+                    return Err(anyhow!(
+                        "{} must be a string, number or boolean (found {})",
+                        key,
+                        other.type_str()
+                    ))
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+fn environment_settings(vars: impl Iterator<Item = (String, String)>) -> HashMap<String, String> {
+    vars.filter_map(|(name, value)| {
+        let key = name.strip_prefix(ENGINE_SETTING_ENV_PREFIX)?;
+        (!key.is_empty()).then(|| (key.to_string(), value))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_file_then_environment_then_database() {
+        let layers = SettingLayers {
+            file: parse_engine_config(
+                "TRADE_SLIPPAGE_RATE = 0.004\nSTOP_FILL_AT_STOP_PRICE = true\nMAX_ALLOWED_DRAWDOWN_RATIO = \"0.25\"",
+            )
+            .unwrap(),
+            environment: environment_settings(
+                [
+                    ("ENGINE_SETTING_TRADE_SLIPPAGE_RATE", "0.005"),
+                    ("ENGINE_THREADS", "4"),
+                ]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+            ),
+        };
+        let snapshot = HashMap::from([
+            ("MAX_ALLOWED_DRAWDOWN_RATIO".to_string(), "0.30".to_string()),
+            ("TRADE_CLOSE_FEE_RATE".to_string(), "0.0005".to_string()),
+        ]);
+        let offline = layers.resolve(Some(&snapshot), None);
+        assert_eq!(
+            offline["TRADE_SLIPPAGE_RATE"],
+            ("0.005".to_string(), SettingSource::Environment)
+        );
+        assert_eq!(
+            offline["MAX_ALLOWED_DRAWDOWN_RATIO"],
+            ("0.25".to_string(), SettingSource::File)
+        );
+        assert_eq!(offline["TRADE_CLOSE_FEE_RATE"].1, SettingSource::Snapshot);
+        assert_eq!(offline["STOP_FILL_AT_STOP_PRICE"].0, "true");
+        assert!(!offline.contains_key("THREADS"));
+
+        let database = HashMap::from([("TRADE_SLIPPAGE_RATE".to_string(), "0.003".to_string())]);
+        let online = layers.merge(None, Some(&database));
+        assert_eq!(online["TRADE_SLIPPAGE_RATE"], "0.003");
+        assert!(parse_engine_config("[section]\nkey = 1").is_err());
+    }
+}
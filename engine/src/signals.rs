@@ -6,91 +6,42 @@ use futures::stream::{FuturesUnordered, StreamExt};
 use log::{info, warn};
 use serde_json::json;
 
+use crate::blackout::BlackoutCalendar;
+use crate::config::EngineRuntimeSettings;
 use crate::data_context::MarketData;
 use crate::database::Database;
-use crate::models::SignalAction;
-use crate::models::{Candle, GeneratedSignal, StrategyConfig};
+use crate::models::{
+    AccountSignalSkip, Candle, GeneratedSignal, StrategyConfig, StrategyStateSnapshot,
+};
 use crate::retry::retry_db_operation;
-use crate::strategy::{create_strategy, Strategy};
-use chrono::{DateTime, Utc};
+use crate::strategy::lightgbm::CrossSectionalCache;
+use crate::strategy::{create_strategy, Strategy, StrategyContext};
+use stratcraft_core::signal_generation::{
+    generate_signal_with_filters, SignalFilterOutcome, SignalGenerationParams,
+};
 
 use crate::optimizer_status::OptimizerStatus;
 
-/// Builds a `GeneratedSignal` when the action is tradable and confidence is usable.
-/// Returns `None` for non-trading actions or invalid confidence values.
-pub fn maybe_create_generated_signal(
-    date: DateTime<Utc>,
-    ticker: &str,
-    action: &SignalAction,
-    confidence: f64,
-) -> Option<GeneratedSignal> {
-    if !matches!(action, SignalAction::Buy | SignalAction::Sell) {
-        return None;
-    }
-
-    let confidence = if confidence.is_finite() {
-        Some(confidence)
-    } else {
-        None
-    };
-
-    Some(GeneratedSignal {
-        date,
-        ticker: ticker.to_string(),
-        action: action.clone(),
-        confidence,
-    })
-}
-
-/// Parameters for generating a signal with filtering
-pub struct SignalGenerationParams<'a> {
-    pub strategy: &'a dyn Strategy,
-    pub ticker: &'a str,
-    pub candles: &'a [Candle],
-    pub candle_index: usize,
-    pub date: DateTime<Utc>,
-    pub excluded_tickers: &'a HashSet<String>,
-}
-
-pub fn generate_signal_with_filters(params: SignalGenerationParams) -> Option<GeneratedSignal> {
-    let SignalGenerationParams {
-        strategy,
-        ticker,
-        candles,
-        candle_index,
-        date,
-        excluded_tickers,
-    } = params;
-
-    // Check if ticker is excluded
-    if !excluded_tickers.is_empty() {
-        let ticker_upper = ticker.to_ascii_uppercase();
-        if excluded_tickers.contains(&ticker_upper) {
-            return None;
-        }
-    }
-
-    // Check minimum data requirements
-    let min_data_points = strategy.get_min_data_points();
-    if candle_index < min_data_points || candle_index >= candles.len() {
-        return None;
-    }
-    // Generate the signal
-    let signal = strategy.generate_signal(ticker, &candles[..=candle_index], candle_index);
-
-    // Convert to GeneratedSignal if it's a tradable action
-    maybe_create_generated_signal(date, ticker, &signal.action, signal.confidence)
-}
-
 pub struct SignalManager<'a> {
     db: &'a mut Database,
     status: &'a OptimizerStatus,
     data: &'a MarketData,
+    cross_sectional: &'a CrossSectionalCache,
 }
 
 impl<'a> SignalManager<'a> {
-    pub fn new(db: &'a mut Database, status: &'a OptimizerStatus, data: &'a MarketData) -> Self {
-        Self { db, status, data }
+    pub fn new(
+        db: &'a mut Database,
+        status: &'a OptimizerStatus,
+        data: &'a MarketData,
+        cross_sectional: &'a CrossSectionalCache,
+    ) -> Self {
+        Self {
+            db,
+            status,
+            data,
+            cross_sectional,
+        }
     }
 
     pub async fn generate_missing_signals(&mut self) -> Result<()> {
@@ -112,6 +63,10 @@ impl<'a> SignalManager<'a> {
             .first()
             .expect("unique_dates is confirmed non-empty");
 
+        let volatility_index = self.data.volatility_index();
+        let min_history_candles = EngineRuntimeSettings::from_settings_map(self.data.settings())?
+            .min_ticker_history_candles;
+        let auxiliary = self.data.auxiliary_data_arc();
         let candles_by_ticker = self.data.cloned_candles_by_ticker();
         let shared_candles = Arc::new(candles_by_ticker);
         let shared_tickers = Arc::new(tickers.to_vec());
@@ -127,7 +82,10 @@ impl<'a> SignalManager<'a> {
         let mut failed_jobs = 0usize;
         let mut total_inserted = 0usize;
         let mut signal_jobs = Vec::new();
-        let mut cached_lightgbm_refs: Option<HashMap<String, Vec<&Candle>>> = None;
+        let candle_refs: HashMap<String, Vec<&Candle>> = shared_candles
+            .iter()
+            .map(|(ticker, candles)| (ticker.clone(), candles.iter().collect()))
+            .collect();
 
         for strategy in strategies {
             let StrategyConfig {
@@ -137,6 +95,7 @@ impl<'a> SignalManager<'a> {
                 parameters,
                 backtest_start_date: strategy_start_date,
                 excluded_tickers,
+                blackout,
                 ..
             } = strategy;
             info!("Preparing signal generation for strategy {}", id);
@@ -154,17 +113,6 @@ impl<'a> SignalManager<'a> {
                 }
             };
 
-            if strategy_instance.get_template_id().starts_with("lightgbm") {
-                let ref_map = cached_lightgbm_refs.get_or_insert_with(|| {
-                    let mut map: HashMap<String, Vec<&Candle>> = HashMap::new();
-                    for (ticker, candle_list) in shared_candles.iter() {
-                        map.insert(ticker.clone(), candle_list.iter().collect());
-                    }
-                    map
-                });
-                crate::strategy::lightgbm::prime_cross_sectional_context_from_ref_map(ref_map);
-            }
-
             let latest_signal_date = self.db.get_latest_signal_date(&id).await?;
             let mut start_date = latest_signal_date.unwrap_or(earliest_candle_date);
             if let Some(strategy_start) = strategy_start_date {
@@ -248,12 +196,38 @@ impl<'a> SignalManager<'a> {
                 continue;
             }
 
+            // State saved after an earlier run only carries forward when that run ended before
+            // the dates generated now; otherwise the strategy replays them from a fresh state.
+            match self.db.get_strategy_live_state(&id).await? {
+                Some((state, as_of))
+                    if state.template_id == template_id && as_of < dates_to_generate[0] =>
+                {
+                    if let Err(err) = strategy_instance.restore_state(&state.data) {
+                        warn!("Failed to restore live state for strategy {}: {}", id, err);
+                    }
+                }
+                Some((state, as_of)) => info!(
+                    "Not restoring live state of strategy {} saved for template {} as of {}",
+                    id,
+                    state.template_id,
+                    as_of.format("%Y-%m-%d")
+                ),
+                None => {}
+            }
+
             signal_jobs.push(SignalGenerationJob {
                 id,
                 name,
-                strategy: strategy_instance,
                 dates_to_generate,
                 excluded_tickers,
+                blackout: BlackoutCalendar::build(&blackout, &auxiliary),
+                min_history_candles,
+                context: StrategyContext {
+                    volatility_index: volatility_index.clone(),
+                    auxiliary: Some(Arc::clone(&auxiliary)),
+                    ..strategy_instance.prepare_context(&candle_refs, self.cross_sectional)
+                },
+                strategy: strategy_instance,
             });
         }
 
@@ -265,7 +239,7 @@ impl<'a> SignalManager<'a> {
             return Ok(());
         }
 
-        let cpu_budget = num_cpus::get().saturating_sub(1).max(1);
+        let cpu_budget = rayon::current_num_threads().saturating_sub(1).max(1);
         let worker_limit = std::cmp::max(1, std::cmp::min(signal_jobs.len(), cpu_budget));
         info!(
             "Launching signal generation with {} concurrent worker{}",
@@ -294,8 +268,28 @@ impl<'a> SignalManager<'a> {
                         name,
                         requested_dates,
                         signals,
+                        signal_skips,
+                        state,
                     } = result;
 
+                    if let Err(err) = self
+                        .db
+                        .insert_account_signal_skips(&id, None, "signal_generation", &signal_skips)
+                        .await
+                    {
+                        warn!("Failed to record signal skips for strategy {}: {}", id, err);
+                    }
+
+                    if let (Some(state), Some(as_of)) = (state, requested_dates.last()) {
+                        if let Err(err) = self
+                            .db
+                            .upsert_strategy_live_state(&id, &state, *as_of)
+                            .await
+                        {
+                            warn!("Failed to save live state for strategy {}: {}", id, err);
+                        }
+                    }
+
                     if signals.is_empty() {
                         self.db
                             .persist_strategy_event(
@@ -390,6 +384,9 @@ struct SignalGenerationJob {
     strategy: Box<dyn Strategy + Send + Sync>,
     dates_to_generate: Vec<chrono::DateTime<chrono::Utc>>,
     excluded_tickers: Vec<String>,
+    blackout: BlackoutCalendar,
+    min_history_candles: usize,
+    context: StrategyContext,
 }
 
 struct SignalGenerationJobResult {
@@ -397,6 +394,11 @@ struct SignalGenerationJobResult {
     name: String,
     requested_dates: Vec<chrono::DateTime<chrono::Utc>>,
     signals: Vec<GeneratedSignal>,
+    /// Buy signals suppressed by the strategy's blackout windows, and tickers skipped for
+    /// having fewer candles than `MIN_TICKER_HISTORY_CANDLES`.
+    signal_skips: Vec<AccountSignalSkip>,
+    /// Strategy state after the last requested date, for stateful strategies.
+    state: Option<StrategyStateSnapshot>,
 }
 
 fn run_signal_generation_job(
@@ -410,9 +412,13 @@ fn run_signal_generation_job(
         strategy,
         dates_to_generate,
         excluded_tickers,
+        blackout,
+        min_history_candles,
+        context,
     } = job;
 
     let mut generated_signals = Vec::new();
+    let mut signal_skips = Vec::new();
     let target_ticker = strategy.target_ticker();
     let single_ticker: Option<Vec<String>> = target_ticker.as_ref().map(|target| {
         let mut list = Vec::with_capacity(1);
@@ -445,15 +451,20 @@ fn run_signal_generation_job(
 
             if let Ok(candle_index) = candles.binary_search_by(|c| c.date.cmp(date)) {
                 // Use the shared signal generation function
-                if let Some(generated) = generate_signal_with_filters(SignalGenerationParams {
+                match generate_signal_with_filters(SignalGenerationParams {
                     strategy: strategy.as_ref(),
                     ticker,
                     candles,
                     candle_index,
                     date: *date,
                     excluded_tickers: &blocked_tickers,
+                    blackout: (!blackout.is_empty()).then_some(&blackout),
+                    context: &context,
+                    min_history_candles,
                 }) {
-                    generated_signals.push(generated);
+                    SignalFilterOutcome::Generated(generated) => generated_signals.push(generated),
+                    SignalFilterOutcome::Suppressed(skip) => signal_skips.push(skip),
+                    SignalFilterOutcome::Filtered => {}
                 }
             }
         }
@@ -464,10 +475,17 @@ fn run_signal_generation_job(
         dedup.insert((signal.date, signal.ticker.clone()), signal);
     }
 
+    let state = strategy.snapshot_state().map(|data| StrategyStateSnapshot {
+        template_id: strategy.get_template_id().to_string(),
+        data,
+    });
+
     SignalGenerationJobResult {
         id,
         name,
         requested_dates: dates_to_generate,
         signals: dedup.into_values().collect(),
+        signal_skips,
+        state,
     }
 }
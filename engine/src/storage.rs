@@ -0,0 +1,562 @@
+use crate::database::{BacktestCacheEntry, Database};
+use crate::models::{parse_parameter_map_from_json, OptimizationResult};
+use crate::optimizer::parameter_signature;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use log::warn;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS settings (
+    setting_key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS backtest_cache (
+    id TEXT PRIMARY KEY,
+    template_id TEXT NOT NULL,
+    parameter_signature TEXT NOT NULL,
+    parameters TEXT NOT NULL,
+    sharpe_ratio REAL,
+    calmar_ratio REAL,
+    cagr REAL,
+    total_return REAL,
+    max_drawdown REAL,
+    max_drawdown_ratio REAL,
+    win_rate REAL,
+    total_trades INTEGER,
+    ticker_count INTEGER,
+    start_date TEXT,
+    end_date TEXT,
+    duration_minutes REAL,
+    top_absolute_gain_ticker TEXT,
+    top_relative_gain_ticker TEXT,
+    verify_sharpe_ratio REAL,
+    verify_calmar_ratio REAL,
+    verify_cagr REAL,
+    verify_max_drawdown_ratio REAL,
+    verify_cost_scenarios TEXT,
+    verify_cost_robust INTEGER,
+    verify_window_scores TEXT,
+    data_hash TEXT,
+    stop_reason TEXT,
+    created_at TEXT NOT NULL,
+    UNIQUE (template_id, parameter_signature)
+);
+";
+
+/// Base cost scenario metrics verify records for a cached parameter set; non-finite values
+/// are stored as NULL.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VerificationMetrics {
+    pub sharpe_ratio: f64,
+    pub calmar_ratio: f64,
+    pub cagr: f64,
+    pub max_drawdown_ratio: f64,
+}
+
+impl VerificationMetrics {
+    pub fn from_result(result: &OptimizationResult) -> Self {
+        Self {
+            sharpe_ratio: result.sharpe_ratio,
+            calmar_ratio: result.calmar_ratio,
+            cagr: result.cagr,
+            max_drawdown_ratio: result.max_drawdown_ratio,
+        }
+    }
+}
+
+/// Settings and backtest cache access shared by the PostgreSQL database and the local SQLite
+/// store, so research commands can run without a PostgreSQL server.
+#[allow(async_fn_in_trait)]
+pub trait Storage {
+    async fn get_all_settings(&self) -> Result<HashMap<String, String>>;
+
+    async fn backtest_cache_entries_for_template(
+        &self,
+        template_id: &str,
+    ) -> Result<Vec<BacktestCacheEntry>>;
+
+    async fn update_backtest_cache_verification(
+        &self,
+        cache_id: &str,
+        metrics: VerificationMetrics,
+        cost_scenarios: &Value,
+        cost_robust: bool,
+    ) -> Result<()>;
+
+    /// Stores the per-window scores of an anchored walk-forward verification.
+    async fn update_backtest_cache_window_scores(
+        &self,
+        cache_id: &str,
+        window_scores: &Value,
+    ) -> Result<()>;
+}
+
+impl Storage for Database {
+    async fn get_all_settings(&self) -> Result<HashMap<String, String>> {
+        Database::get_all_settings(self).await
+    }
+
+    async fn backtest_cache_entries_for_template(
+        &self,
+        template_id: &str,
+    ) -> Result<Vec<BacktestCacheEntry>> {
+        Database::backtest_cache_entries_for_template(self, template_id).await
+    }
+
+    async fn update_backtest_cache_verification(
+        &self,
+        cache_id: &str,
+        metrics: VerificationMetrics,
+        cost_scenarios: &Value,
+        cost_robust: bool,
+    ) -> Result<()> {
+        Database::update_backtest_cache_verification(
+            self,
+            cache_id,
+            metrics,
+            cost_scenarios,
+            cost_robust,
+        )
+        .await
+    }
+
+    async fn update_backtest_cache_window_scores(
+        &self,
+        cache_id: &str,
+        window_scores: &Value,
+    ) -> Result<()> {
+        Database::update_backtest_cache_window_scores(self, cache_id, window_scores).await
+    }
+}
+
+/// Cached backtest metrics written to the local store after an optimizer run.
+pub struct LocalCacheRecord<'a> {
+    pub template_id: &'a str,
+    pub result: &'a OptimizationResult,
+    pub ticker_count: i32,
+    pub start_date: chrono::DateTime<Utc>,
+    pub end_date: chrono::DateTime<Utc>,
+    pub duration_minutes: f64,
+    pub top_absolute_gain_ticker: Option<&'a str>,
+    pub top_relative_gain_ticker: Option<&'a str>,
+    /// Content hash of the market data the result was computed from.
+    pub data_hash: Option<&'a str>,
+    /// Why the optimization run that stored this result as its best ended.
+    pub stop_reason: Option<&'a str>,
+}
+
+/// Single-file SQLite store holding settings and the backtest cache for offline runs.
+///
+/// It never holds accounts, strategies, trades or signals; commands that manage live accounts
+/// still require PostgreSQL.
+#[derive(Clone)]
+pub struct SqliteStorage {
+    path: PathBuf,
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    /// Opens (or creates) the store at `path` and ensures its tables exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite store {}", path.display()))?;
+        connection
+            .execute_batch(SQLITE_SCHEMA)
+            .with_context(|| format!("Failed to prepare SQLite store {}", path.display()))?;
+        for column in ["data_hash", "stop_reason", "verify_window_scores"] {
+            let exists = connection
+                .prepare("SELECT 1 FROM pragma_table_info('backtest_cache') WHERE name = ?1")?
+                .exists([column])?;
+            if !exists {
+                connection.execute(
+                    &format!("ALTER TABLE backtest_cache ADD COLUMN {} TEXT", column),
+                    [],
+                )?;
+            }
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Copies settings that are not yet stored locally, keeping values edited in the store.
+    pub fn seed_settings(&self, settings: &HashMap<String, String>) -> Result<usize> {
+        let mut connection = self.connection();
+        let tx = connection.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut statement =
+                tx.prepare("INSERT OR IGNORE INTO settings (setting_key, value) VALUES (?1, ?2)")?;
+            for (key, value) in settings {
+                inserted += statement.execute(params![key, value])?;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// Cached metrics for a parameter set, skipping results computed from market data other
+    /// than `data_hash`. Results stored without a hash match any data.
+    pub fn cached_result(
+        &self,
+        template_id: &str,
+        parameters: &HashMap<String, f64>,
+        data_hash: Option<&str>,
+    ) -> Result<Option<OptimizationResult>> {
+        let connection = self.connection();
+        let row = connection
+            .query_row(
+                "SELECT sharpe_ratio, calmar_ratio, cagr, total_return, max_drawdown,
+                        max_drawdown_ratio, win_rate, total_trades
+                 FROM backtest_cache
+                 WHERE template_id = ?1 AND parameter_signature = ?2
+                   AND (?3 IS NULL OR data_hash IS NULL OR data_hash = ?3)",
+                params![template_id, parameter_signature(parameters), data_hash],
+                |row| {
+                    let metric = |index: usize| -> rusqlite::Result<f64> {
+                        Ok(row.get::<_, Option<f64>>(index)?.unwrap_or(f64::NAN))
+                    };
+                    Ok(OptimizationResult {
+                        parameters: parameters.clone(),
+                        sharpe_ratio: metric(0)?,
+                        calmar_ratio: metric(1)?,
+                        cagr: metric(2)?,
+                        total_return: metric(3)?,
+                        max_drawdown: metric(4)?,
+                        max_drawdown_ratio: metric(5)?,
+                        win_rate: metric(6)?,
+                        total_trades: row.get::<_, Option<i32>>(7)?.unwrap_or(0),
+                        monthly_returns: Vec::new(),
+                        excess_return: None,
+                        capacity_aum: None,
+                        entry_week_concentration: None,
+                        r_expectancy: None,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(row)
+    }
+
+    /// Stores or replaces the cached metrics for a template parameter set.
+    pub fn store_result(&self, record: LocalCacheRecord<'_>) -> Result<()> {
+        let result = record.result;
+        let finite = |value: f64| value.is_finite().then_some(value);
+        let parameters = serde_json::to_string(&result.parameters)?;
+        self.connection().execute(
+            "INSERT INTO backtest_cache (
+                 id, template_id, parameter_signature, parameters, sharpe_ratio, calmar_ratio,
+                 cagr, total_return, max_drawdown, max_drawdown_ratio, win_rate, total_trades,
+                 ticker_count, start_date, end_date, duration_minutes, top_absolute_gain_ticker,
+                 top_relative_gain_ticker, data_hash, stop_reason, created_at
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)
+             ON CONFLICT (template_id, parameter_signature) DO UPDATE SET
+                 sharpe_ratio = excluded.sharpe_ratio,
+                 calmar_ratio = excluded.calmar_ratio,
+                 cagr = excluded.cagr,
+                 total_return = excluded.total_return,
+                 max_drawdown = excluded.max_drawdown,
+                 max_drawdown_ratio = excluded.max_drawdown_ratio,
+                 win_rate = excluded.win_rate,
+                 total_trades = excluded.total_trades,
+                 ticker_count = excluded.ticker_count,
+                 start_date = excluded.start_date,
+                 end_date = excluded.end_date,
+                 duration_minutes = excluded.duration_minutes,
+                 top_absolute_gain_ticker = excluded.top_absolute_gain_ticker,
+                 top_relative_gain_ticker = excluded.top_relative_gain_ticker,
+                 data_hash = excluded.data_hash,
+                 stop_reason = COALESCE(excluded.stop_reason, backtest_cache.stop_reason)",
+            params![
+                Uuid::new_v4().to_string(),
+                record.template_id,
+                parameter_signature(&result.parameters),
+                parameters,
+                finite(result.sharpe_ratio),
+                finite(result.calmar_ratio),
+                finite(result.cagr),
+                finite(result.total_return),
+                finite(result.max_drawdown),
+                finite(result.max_drawdown_ratio),
+                finite(result.win_rate),
+                result.total_trades,
+                record.ticker_count,
+                record.start_date.to_rfc3339(),
+                record.end_date.to_rfc3339(),
+                record.duration_minutes,
+                record.top_absolute_gain_ticker,
+                record.top_relative_gain_ticker,
+                record.data_hash,
+                record.stop_reason,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStorage {
+    async fn get_all_settings(&self) -> Result<HashMap<String, String>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare("SELECT setting_key, value FROM settings")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<rusqlite::Result<HashMap<String, String>>>()
+            .map_err(Into::into)
+    }
+
+    async fn backtest_cache_entries_for_template(
+        &self,
+        template_id: &str,
+    ) -> Result<Vec<BacktestCacheEntry>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT id,
+                    parameters,
+                    calmar_ratio,
+                    sharpe_ratio,
+                    cagr,
+                    (verify_sharpe_ratio IS NOT NULL
+                     AND verify_calmar_ratio IS NOT NULL
+                     AND verify_cagr IS NOT NULL
+                     AND verify_max_drawdown_ratio IS NOT NULL
+                     AND verify_cost_scenarios IS NOT NULL) AS verify_complete
+             FROM backtest_cache
+             WHERE template_id = ?1
+             ORDER BY created_at DESC",
+        )?;
+        let rows = statement.query_map(params![template_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<f64>>(2)?,
+                row.get::<_, Option<f64>>(3)?,
+                row.get::<_, Option<f64>>(4)?,
+                row.get::<_, bool>(5)?,
+            ))
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, params_text, calmar_ratio, sharpe_ratio, cagr, verify_complete) = row?;
+            match parse_parameter_map_from_json(&params_text) {
+                Ok(parameters) => entries.push(BacktestCacheEntry {
+                    id,
+                    template_id: template_id.to_string(),
+                    parameters,
+                    calmar_ratio: calmar_ratio.unwrap_or(f64::NAN),
+                    sharpe_ratio: sharpe_ratio.unwrap_or(f64::NAN),
+                    cagr: cagr.unwrap_or(f64::NAN),
+                    verify_complete,
+                    // Balance metrics are only tracked in PostgreSQL.
+                    balance_training_complete: false,
+                    balance_validation_complete: false,
+                }),
+                Err(error) => warn!(
+                    "Skipping cached parameters {} for template {} due to parse error: {}",
+                    id, template_id, error
+                ),
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn update_backtest_cache_verification(
+        &self,
+        cache_id: &str,
+        metrics: VerificationMetrics,
+        cost_scenarios: &Value,
+        cost_robust: bool,
+    ) -> Result<()> {
+        let finite = |value: f64| Some(value).filter(|v| v.is_finite());
+        let updated = self.connection().execute(
+            "UPDATE backtest_cache
+                 SET verify_sharpe_ratio = ?1,
+                     verify_calmar_ratio = ?2,
+                     verify_cagr = ?3,
+                     verify_max_drawdown_ratio = ?4,
+                     verify_cost_scenarios = ?5,
+                     verify_cost_robust = ?6
+                 WHERE id = ?7",
+            params![
+                finite(metrics.sharpe_ratio),
+                finite(metrics.calmar_ratio),
+                finite(metrics.cagr),
+                finite(metrics.max_drawdown_ratio),
+                cost_scenarios.to_string(),
+                cost_robust,
+                cache_id,
+            ],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!(
+                "Cached backtest {} not found in {}",
+                cache_id,
+                self.path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    async fn update_backtest_cache_window_scores(
+        &self,
+        cache_id: &str,
+        window_scores: &Value,
+    ) -> Result<()> {
+        let updated = self.connection().execute(
+            "UPDATE backtest_cache SET verify_window_scores = ?1 WHERE id = ?2",
+            params![window_scores.to_string(), cache_id],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!(
+                "Cached backtest {} not found in {}",
+                cache_id,
+                self.path.display()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_result(cagr: f64) -> OptimizationResult {
+        OptimizationResult {
+            parameters: HashMap::from([("lookback".to_string(), 20.0), ("exit".to_string(), 2.5)]),
+            cagr,
+            sharpe_ratio: 1.2,
+            total_return: 0.4,
+            max_drawdown: 1_500.0,
+            max_drawdown_ratio: 0.15,
+            win_rate: 0.55,
+            total_trades: 42,
+            calmar_ratio: f64::NAN,
+            monthly_returns: Vec::new(),
+            excess_return: None,
+            capacity_aum: None,
+            entry_week_concentration: None,
+            r_expectancy: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_round_trips_cache_and_verification() {
+        let store = SqliteStorage::open(Path::new(":memory:")).unwrap();
+        let now = Utc::now();
+        for cagr in [0.1, 0.2] {
+            store
+                .store_result(LocalCacheRecord {
+                    template_id: "momentum",
+                    result: &sample_result(cagr),
+                    ticker_count: 10,
+                    start_date: now,
+                    end_date: now,
+                    duration_minutes: 1.0,
+                    top_absolute_gain_ticker: Some("AAPL"),
+                    top_relative_gain_ticker: None,
+                    data_hash: Some("snapshot-a"),
+                    stop_reason: None,
+                })
+                .unwrap();
+        }
+
+        let parameters = sample_result(0.0).parameters;
+        let cached = store
+            .cached_result("momentum", &parameters, Some("snapshot-a"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cached.cagr, 0.2);
+        assert!(cached.calmar_ratio.is_nan());
+        assert!(store
+            .cached_result("momentum", &parameters, Some("snapshot-b"))
+            .unwrap()
+            .is_none());
+        assert!(store
+            .cached_result("momentum", &parameters, None)
+            .unwrap()
+            .is_some());
+
+        let entries = store
+            .backtest_cache_entries_for_template("momentum")
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].verify_complete);
+        store
+            .update_backtest_cache_verification(
+                &entries[0].id,
+                VerificationMetrics {
+                    sharpe_ratio: 1.0,
+                    calmar_ratio: 0.5,
+                    cagr: 0.1,
+                    max_drawdown_ratio: 0.2,
+                },
+                &json!([]),
+                true,
+            )
+            .await
+            .unwrap();
+        let entries = store
+            .backtest_cache_entries_for_template("momentum")
+            .await
+            .unwrap();
+        assert!(entries[0].verify_complete);
+
+        store
+            .update_backtest_cache_window_scores(&entries[0].id, &json!([{ "cagr": 0.1 }]))
+            .await
+            .unwrap();
+        let stored: String = store
+            .connection()
+            .query_row(
+                "SELECT verify_window_scores FROM backtest_cache WHERE id = ?1",
+                [&entries[0].id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored, r#"[{"cagr":0.1}]"#);
+        assert!(store
+            .update_backtest_cache_window_scores("missing", &json!([]))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn seeded_settings_keep_local_edits() {
+        let store = SqliteStorage::open(Path::new(":memory:")).unwrap();
+        let snapshot = HashMap::from([(
+            "VERIFY_WINDOW_START_DATE".to_string(),
+            "2020-01-01".to_string(),
+        )]);
+        assert_eq!(store.seed_settings(&snapshot).unwrap(), 1);
+        store
+            .connection()
+            .execute(
+                "UPDATE settings SET value = '2021-01-01' WHERE setting_key = 'VERIFY_WINDOW_START_DATE'",
+                [],
+            )
+            .unwrap();
+        assert_eq!(store.seed_settings(&snapshot).unwrap(), 0);
+        let settings = store.get_all_settings().await.unwrap();
+        assert_eq!(settings["VERIFY_WINDOW_START_DATE"], "2021-01-01");
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// One cell of a worksheet row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum XlsxCell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl From<Option<f64>> for XlsxCell {
+    fn from(value: Option<f64>) -> Self {
+        match value {
+            Some(value) if value.is_finite() => XlsxCell::Number(value),
+            _ => XlsxCell::Empty,
+        }
+    }
+}
+
+impl From<Option<String>> for XlsxCell {
+    fn from(value: Option<String>) -> Self {
+        value.map(XlsxCell::Text).unwrap_or(XlsxCell::Empty)
+    }
+}
+
+/// Writes a single-sheet workbook whose first row is `header`. Strings are stored inline and
+/// the zip entries uncompressed, so no archive or spreadsheet dependency is needed.
+pub fn write_xlsx(
+    path: &Path,
+    sheet_name: &str,
+    header: &[&str],
+    rows: &[Vec<XlsxCell>],
+) -> Result<()> {
+    std::fs::write(path, render_xlsx(sheet_name, header, rows))
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn render_xlsx(sheet_name: &str, header: &[&str], rows: &[Vec<XlsxCell>]) -> Vec<u8> {
+    let header_row: Vec<XlsxCell> = header
+        .iter()
+        .map(|name| XlsxCell::Text(name.to_string()))
+        .collect();
+    let mut sheet = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>",
+    );
+    for (row_idx, row) in std::iter::once(&header_row).chain(rows).enumerate() {
+        sheet.push_str(&format!("<row r=\"{}\">", row_idx + 1));
+        for (col_idx, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(col_idx), row_idx + 1);
+            match cell {
+                XlsxCell::Text(text) => sheet.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"><is><t>{}</t></is></c>",
+                    reference,
+                    escape_xml(text)
+                )),
+                XlsxCell::Number(value) if value.is_finite() => {
+                    sheet.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, value))
+                }
+                XlsxCell::Number(_) | XlsxCell::Empty => {}
+            }
+        }
+        sheet.push_str("</row>");
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    let workbook = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets><sheet name=\"{}\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>",
+        escape_xml(sheet_name)
+    );
+    let parts: [(&str, String); 5] = [
+        (
+            "[Content_Types].xml",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\"><Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/><Default Extension=\"xml\" ContentType=\"application/xml\"/><Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/><Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/></Types>".to_string(),
+        ),
+        (
+            "_rels/.rels",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\"><Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/></Relationships>".to_string(),
+        ),
+        ("xl/workbook.xml", workbook),
+        (
+            "xl/_rels/workbook.xml.rels",
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\"><Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/></Relationships>".to_string(),
+        ),
+        ("xl/worksheets/sheet1.xml", sheet),
+    ];
+    zip_stored(&parts)
+}
+
+/// Spreadsheet column letters: 0 -> A, 25 -> Z, 26 -> AA.
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("column letters are ASCII")
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            ch if (ch as u32) < 0x20 && !matches!(ch, '\t' | '\n' | '\r') => {}
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Zip archive with every entry stored uncompressed, dated 1980-01-01.
+fn zip_stored(entries: &[(&str, String)]) -> Vec<u8> {
+    const DOS_DATE: u16 = 0x21;
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, content) in entries {
+        let data = content.as_bytes();
+        let crc = crc32(data);
+        let offset = out.len() as u32;
+        let header = |signature: u32, central_entry: bool| {
+            let mut bytes = Vec::new();
+            bytes.extend_from_slice(&signature.to_le_bytes());
+            if central_entry {
+                bytes.extend_from_slice(&20u16.to_le_bytes());
+            }
+            bytes.extend_from_slice(&20u16.to_le_bytes());
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            bytes.extend_from_slice(&DOS_DATE.to_le_bytes());
+            bytes.extend_from_slice(&crc.to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            if central_entry {
+                bytes.extend_from_slice(&[0u8; 6]);
+                bytes.extend_from_slice(&0u32.to_le_bytes());
+                bytes.extend_from_slice(&offset.to_le_bytes());
+            }
+            bytes.extend_from_slice(name.as_bytes());
+            bytes
+        };
+        out.extend(header(0x0403_4b50, false));
+        out.extend_from_slice(data);
+        central.extend(header(0x0201_4b50, true));
+    }
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0u8; 4]);
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{column_name, crc32, render_xlsx, XlsxCell};
+
+    #[test]
+    fn renders_a_stored_zip_with_an_inline_string_sheet() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(27), "AB");
+
+        let rows = vec![vec![
+            XlsxCell::Text("A&B <Co>".to_string()),
+            XlsxCell::Number(12.5),
+            XlsxCell::Empty,
+        ]];
+        let bytes = render_xlsx("Trades", &["name", "pnl", "note"], &rows);
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        let end = &bytes[bytes.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 5);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("<c r=\"A2\" t=\"inlineStr\"><is><t>A&amp;B &lt;Co&gt;</t></is></c>"));
+        assert!(text.contains("<c r=\"B2\"><v>12.5</v></c>"));
+        assert!(!text.contains("r=\"C2\""));
+    }
+}
@@ -3,15 +3,15 @@ use std::collections::HashMap;
 use chrono::{Duration, TimeZone, Utc};
 use engine::models::{Candle, SignalAction};
 use engine::strategy;
-use engine::strategy::lightgbm::register_model_text;
+use engine::strategy::lightgbm::{register_model_text, CrossSectionalCache};
 
 fn sample_model_text() -> &'static str {
     "objective=binary sigmoid:1\nnum_class=1\nnum_tree_per_iteration=1\nmax_feature_idx=0\nTree=0\nnum_leaves=2\nsplit_feature=0\nthreshold=0.5\nleft_child=-1\nright_child=-2\nleaf_value=0.1 0.2\nshrinkage=1\n"
 }
 
 fn constant_buy_model_text() -> &'static str {
-    // LightGBMStrategy computes 51 input features, so max_feature_idx must be 50.
-    "objective=binary sigmoid:1\nnum_class=1\nnum_tree_per_iteration=1\nmax_feature_idx=50\nTree=0\nnum_leaves=2\nsplit_feature=0\nthreshold=1000000000\nleft_child=-1\nright_child=-2\nleaf_value=6 -6\nshrinkage=1\n"
+    // LightGBMStrategy computes 60 input features, so max_feature_idx must be 59.
+    "objective=binary sigmoid:1\nnum_class=1\nnum_tree_per_iteration=1\nmax_feature_idx=59\nTree=0\nnum_leaves=2\nsplit_feature=0\nthreshold=1000000000\nleft_child=-1\nright_child=-2\nleaf_value=6 -6\nshrinkage=1\n"
 }
 
 fn build_candles(ticker: &str, count: usize) -> Vec<Candle> {
@@ -76,3 +76,40 @@ fn lightgbm_end_to_end_fake_trainer_produces_buy_signal() {
     assert!(matches!(signal.action, SignalAction::Buy));
     assert!(signal.confidence > 0.9, "confidence={}", signal.confidence);
 }
+
+#[test]
+fn prepared_context_keeps_the_model_resolved_at_run_start() {
+    register_model_text("model-ctx", constant_buy_model_text(), false).expect("register model-ctx");
+    let strategy = strategy::create_strategy("lightgbm_model-ctx", HashMap::new())
+        .expect("create strategy for model-ctx");
+    let candles = build_candles("AAPL", strategy.get_min_data_points() + 5);
+    let mut candles_by_ticker = HashMap::new();
+    candles_by_ticker.insert("AAPL".to_string(), candles.iter().collect::<Vec<_>>());
+    let context = strategy.prepare_context(&candles_by_ticker, &CrossSectionalCache::default());
+    assert!(context.cross_sectional.is_some());
+
+    let never_buy = constant_buy_model_text().replace("leaf_value=6 -6", "leaf_value=-6 6");
+    register_model_text("model-ctx", &never_buy, false).expect("re-register model-ctx");
+
+    let last = candles.len() - 1;
+    let prepared = strategy.generate_signal_with_context("AAPL", &candles, last, &context);
+    let fresh = strategy.generate_signal("AAPL", &candles, last);
+    assert!(matches!(prepared.action, SignalAction::Buy));
+    assert!(matches!(fresh.action, SignalAction::Hold));
+}
+
+#[test]
+fn stored_calibration_replaces_the_raw_model_probability() {
+    // Without calibration the model buys with near-certain confidence; the stored isotonic
+    // calibration maps every raw probability to a 30% hit rate, which never buys.
+    let calibrated = format!(
+        "{}stratcraft_calibration={{\"method\":\"isotonic\",\"thresholds\":[0.0],\"values\":[0.3]}}\n",
+        constant_buy_model_text()
+    );
+    register_model_text("model-calibrated", &calibrated, false).expect("register model-calibrated");
+    let strategy = strategy::create_strategy("lightgbm_model-calibrated", HashMap::new())
+        .expect("create strategy for model-calibrated");
+    let candles = build_candles("AAPL", strategy.get_min_data_points() + 5);
+    let signal = strategy.generate_signal("AAPL", &candles, candles.len() - 1);
+    assert!(matches!(signal.action, SignalAction::Hold));
+}
@@ -2,13 +2,17 @@ use anyhow::{anyhow, Context, Result};
 use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
 use engine::commands::{
     backtest_accounts, backtest_active, balance, export_market_data, generate_signals, optimize,
-    plan_operations, reconcile_trades, verify,
+    plan_operations::{self, PlanOperationsOptions},
+    reconcile_trades,
+    train_lightgbm::{self, LabelingOptions, LightgbmHyperparameters},
+    verify,
 };
 use engine::context::AppContext;
-use engine::data_context::MarketData;
+use engine::data_context::{MarketData, DEFAULT_SNAPSHOT_COMPRESSION_LEVEL};
 use engine::database::Database;
 use engine::models::{GeneratedSignal, SignalAction, TradeStatus};
 use engine::optimizer_status::OptimizerStatus;
+use engine::strategy::lightgbm::features::FeatureSet;
 use reqwest::Client as HttpClient;
 use serde::Deserialize;
 use serde_json::{Map as JsonMap, Value};
@@ -19,6 +23,7 @@ use std::fs;
 use std::io::{BufRead, BufReader, Write as IoWrite};
 use std::net::TcpListener;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::{mpsc, Arc, Once, OnceLock};
 use std::thread;
 use std::time::Duration;
@@ -34,14 +39,19 @@ const PLAN_OPERATIONS_DB_NAME: &str = "stratcraft_test_plan_operations";
 const ORDER_LIFECYCLE_DB_NAME: &str = "stratcraft_test_order_lifecycle";
 const RECONCILE_TRADES_DB_NAME: &str = "stratcraft_test_reconcile_trades";
 const VERIFY_DB_NAME: &str = "stratcraft_test_verify";
+const END_TO_END_DB_NAME: &str = "stratcraft_test_end_to_end";
+const END_TO_END_POSTGRES_IMAGE: &str = "postgres:16";
 const TOTAL_DAYS: i64 = 730;
 const SMOKE_TEST_DAYS: i64 = 45;
+const END_TO_END_DAYS: i64 = 240;
 const APPROX_DAYS_PER_MONTH: f64 = 30.4;
 const SUMMARY_SNAPSHOT: &str = "backtest_pipeline.txt";
 const TRADES_DIR: &str = "trades";
 const EXPORT_SNAPSHOT_FILE: &str = "market-data-smoke.bin";
 const OPTIMIZE_SNAPSHOT_FILE: &str = "market-data-optimize.bin";
 const VERIFY_SNAPSHOT_FILE: &str = "market-data-verify.bin";
+const END_TO_END_SNAPSHOT_FILE: &str = "market-data-end-to-end.bin";
+const END_TO_END_MODEL_FILE: &str = "lightgbm-end-to-end.txt";
 
 fn ensure_test_env() {
     static INIT: Once = Once::new();
@@ -110,7 +120,13 @@ async fn pipeline_smoke_small_dataset() -> Result<()> {
     generate_signals::run(&app_context).await?;
     let approx_months = ((SMOKE_TEST_DAYS as f64) / APPROX_DAYS_PER_MONTH).ceil() as u32;
     let months = vec![approx_months.max(1)];
-    backtest_active::run(&app_context, backtest_active::BacktestScope::All, &months).await?;
+    backtest_active::run(
+        &app_context,
+        backtest_active::BacktestScope::All,
+        &months,
+        &backtest_active::TickerSelection::default(),
+    )
+    .await?;
 
     let db = Database::new(test_db.database_url()).await?;
     for seed in strategy_seeds {
@@ -193,7 +209,7 @@ async fn backtest_accounts_smoke() -> Result<()> {
         .await?;
 
     let app_context = AppContext::initialize(Some(test_db.database_url().to_string())).await?;
-    backtest_accounts::run(&app_context).await?;
+    backtest_accounts::run(&app_context, None).await?;
 
     let result = db
         .load_latest_backtest_result(&account_strategy.id, None, "all")
@@ -239,7 +255,12 @@ async fn export_market_data_smoke() -> Result<()> {
     if output_path.exists() {
         fs::remove_file(&output_path)?;
     }
-    export_market_data::run(&app_context, &output_path).await?;
+    export_market_data::run(
+        &app_context,
+        &output_path,
+        Some(DEFAULT_SNAPSHOT_COMPRESSION_LEVEL),
+    )
+    .await?;
     assert!(
         output_path.exists(),
         "expected market data snapshot at {}",
@@ -296,7 +317,12 @@ async fn optimize_smoke() -> Result<()> {
     if output_path.exists() {
         fs::remove_file(&output_path)?;
     }
-    export_market_data::run(&app_context, &output_path).await?;
+    export_market_data::run(
+        &app_context,
+        &output_path,
+        Some(DEFAULT_SNAPSHOT_COMPRESSION_LEVEL),
+    )
+    .await?;
 
     let template = load_templates()?
         .into_iter()
@@ -314,7 +340,13 @@ async fn optimize_smoke() -> Result<()> {
         })
         .ok_or_else(|| anyhow!("No optimizable template found"))?;
 
-    optimize::run(&app_context, &template.id, &output_path).await?;
+    optimize::run(
+        &app_context,
+        &template.id,
+        &output_path,
+        &optimize::OptimizeOptions::default(),
+    )
+    .await?;
 
     let db = Database::new(test_db.database_url()).await?;
     let updated_template = db
@@ -384,7 +416,24 @@ async fn plan_operations_smoke() -> Result<()> {
         .await?;
 
     let app_context = AppContext::initialize(Some(test_db.database_url().to_string())).await?;
-    plan_operations::run(&app_context).await?;
+    plan_operations::run(
+        &app_context,
+        PlanOperationsOptions {
+            dry_run: true,
+            explain: true,
+        },
+        None,
+    )
+    .await?;
+    assert_eq!(
+        test_db
+            .count_account_operations(&account_strategy.id)
+            .await?,
+        0,
+        "dry run should not write account operations"
+    );
+
+    plan_operations::run(&app_context, PlanOperationsOptions::default(), None).await?;
 
     let operation_count = test_db
         .count_account_operations(&account_strategy.id)
@@ -453,7 +502,7 @@ async fn order_lifecycle_end_to_end() -> Result<()> {
         .await?;
 
     let app_context = AppContext::initialize(Some(test_db.database_url().to_string())).await?;
-    plan_operations::run(&app_context).await?;
+    plan_operations::run(&app_context, PlanOperationsOptions::default(), None).await?;
 
     let operation = test_db
         .fetch_pending_open_operation(&account_strategy.id)
@@ -618,9 +667,20 @@ async fn verify_balance_smoke() -> Result<()> {
     if output_path.exists() {
         fs::remove_file(&output_path)?;
     }
-    export_market_data::run(&app_context, &output_path).await?;
-
-    verify::run(&app_context, &template.id, &output_path).await?;
+    export_market_data::run(
+        &app_context,
+        &output_path,
+        Some(DEFAULT_SNAPSHOT_COMPRESSION_LEVEL),
+    )
+    .await?;
+
+    verify::run(
+        &app_context,
+        &template.id,
+        &output_path,
+        verify::VerifyOptions::default(),
+    )
+    .await?;
     balance::run(&app_context, &template.id, &output_path).await?;
 
     let verification = test_db.get_backtest_cache_verify_values(cache_id).await?;
@@ -644,6 +704,22 @@ async fn verify_balance_smoke() -> Result<()> {
         "expected verify_max_drawdown_ratio for {}",
         cache_id
     );
+    let scenario_count = verification
+        .verify_cost_scenarios
+        .as_ref()
+        .and_then(|value| value.as_array())
+        .map(|scenarios| scenarios.len());
+    assert_eq!(
+        scenario_count,
+        Some(3),
+        "expected low/base/high cost scenarios for {}",
+        cache_id
+    );
+    assert!(
+        verification.verify_cost_robust.is_some(),
+        "expected verify_cost_robust for {}",
+        cache_id
+    );
 
     let balance_values = test_db.get_backtest_cache_balance_values(cache_id).await?;
     assert!(
@@ -662,6 +738,159 @@ async fn verify_balance_smoke() -> Result<()> {
     Ok(())
 }
 
+/// Runs every stage against one database: ingest the fixture candles into a snapshot, train
+/// LightGBM (when the CLI is installed), optimize with a single-step budget, generate signals,
+/// plan operations and reconcile a filled order from the Alpaca stub. Without DATABASE_URL it
+/// starts a throwaway Postgres container. Run with `cargo test --test pipeline -- --ignored`.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore = "end-to-end run; needs DATABASE_URL or Docker"]
+async fn end_to_end_stack() -> Result<()> {
+    ensure_test_env();
+    let _guard = acquire_pipeline_test_lock().await;
+    let _container = PostgresContainer::start_unless_configured().await?;
+    std::env::set_var("RAYON_NUM_THREADS", "2");
+    let test_db = TestDatabase::create_with_name(END_TO_END_DB_NAME).await?;
+    test_db.apply_schema().await?;
+    test_db.seed_market_data_for_days(END_TO_END_DAYS).await?;
+    let strategy_seeds = test_db
+        .seed_strategies(StrategySeedConfig {
+            allow_short_selling_override: None,
+        })
+        .await?;
+    let templates = load_templates()?;
+    let template = templates
+        .first()
+        .ok_or_else(|| anyhow!("No templates available for end-to-end run"))?;
+    let account_strategy = test_db.seed_account_strategy(template).await?;
+    test_db
+        .update_setting("LOCAL_OPTIMIZATION_STEP_MULTIPLIERS", "0")
+        .await?;
+    test_db
+        .update_setting("LOCAL_OPTIMIZATION_VERSION", "1")
+        .await?;
+    let baseline = baseline_start_date();
+    test_db
+        .update_setting(
+            "LIGHTGBM_TRAINING_START_DATE",
+            &baseline.format("%Y-%m-%d").to_string(),
+        )
+        .await?;
+    test_db
+        .update_setting(
+            "LIGHTGBM_TRAINING_END_DATE",
+            &(baseline + ChronoDuration::days(END_TO_END_DAYS))
+                .format("%Y-%m-%d")
+                .to_string(),
+        )
+        .await?;
+
+    let app_context = AppContext::initialize(Some(test_db.database_url().to_string())).await?;
+
+    let snapshot_path = export_snapshot_file_path(END_TO_END_SNAPSHOT_FILE);
+    export_market_data::run(&app_context, &snapshot_path, None).await?;
+    let status = OptimizerStatus::new();
+    let market_data = MarketData::load_from_file(&snapshot_path, &status)?;
+    assert!(
+        !market_data.tickers().is_empty(),
+        "expected ingested tickers in snapshot"
+    );
+
+    if lightgbm_cli_available() {
+        let model_path = export_snapshot_file_path(END_TO_END_MODEL_FILE);
+        train_lightgbm::run(
+            &app_context,
+            Some(model_path.clone()),
+            LightgbmHyperparameters {
+                num_iterations: 5,
+                num_leaves: 4,
+                min_data_in_leaf: 1,
+                early_stopping_round: 0,
+                ..LightgbmHyperparameters::default()
+            },
+            FeatureSet::default(),
+            LabelingOptions::default(),
+        )
+        .await?;
+        assert!(
+            model_path.exists(),
+            "expected trained model at {}",
+            model_path.display()
+        );
+        fs::remove_file(&model_path)?;
+    } else {
+        eprintln!("LightGBM CLI not found; skipping the training stage");
+    }
+
+    optimize::run(
+        &app_context,
+        &template.id,
+        &snapshot_path,
+        &optimize::OptimizeOptions::default(),
+    )
+    .await?;
+    let mut db = Database::new(test_db.database_url()).await?;
+    let optimized_template = db
+        .get_template(&template.id)
+        .await?
+        .ok_or_else(|| anyhow!("Missing template {} after optimize", template.id))?;
+    assert_eq!(optimized_template.local_optimization_version, 1);
+
+    generate_signals::run(&app_context).await?;
+    assert!(
+        test_db.count_signals().await? > 0,
+        "expected generated signals for {} seeded strategies",
+        strategy_seeds.len()
+    );
+
+    // Pin one buy on the account strategy so the broker leg does not depend on which
+    // signals the fixture happens to produce.
+    let signal_date = baseline.and_hms_opt(0, 0, 0).expect("valid date").and_utc()
+        + ChronoDuration::days(END_TO_END_DAYS - 1);
+    db.upsert_strategy_signals(
+        &account_strategy.id,
+        &[GeneratedSignal {
+            date: signal_date,
+            ticker: "AAA".to_string(),
+            action: SignalAction::Buy,
+            confidence: Some(0.9),
+        }],
+    )
+    .await?;
+
+    let stub = AlpacaStub::start(AlpacaStubResponses::filled_order(
+        "order-entry",
+        "AAA",
+        101.0,
+        signal_date + ChronoDuration::days(1),
+    ))?;
+    wait_for_alpaca_stub(&stub.base_url).await?;
+    test_db
+        .update_setting("ALPACA_PAPER_URL", &stub.base_url)
+        .await?;
+
+    plan_operations::run(&app_context, PlanOperationsOptions::default(), None).await?;
+    let operation = test_db
+        .fetch_pending_open_operation(&account_strategy.id)
+        .await?;
+    test_db
+        .dispatch_open_operation(&operation, "order-entry")
+        .await?;
+    reconcile_trades::run(&app_context).await?;
+
+    let trades = db.get_strategy_live_trades(&account_strategy.id).await?;
+    let trade = trades
+        .iter()
+        .find(|candidate| candidate.id == operation.trade_id)
+        .ok_or_else(|| anyhow!("Missing trade {} after reconciliation", operation.trade_id))?;
+    assert_eq!(trade.status, TradeStatus::Active);
+    assert!((trade.price - 101.0).abs() < 1e-6);
+
+    drop(stub);
+    fs::remove_file(&snapshot_path)?;
+    test_db.cleanup().await?;
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn full_pipeline_snapshot() -> Result<()> {
     ensure_test_env();
@@ -705,7 +934,13 @@ async fn run_pipeline_snapshot(config: PipelineRunConfig) -> Result<()> {
     generate_signals::run(&app_context).await?;
     let backtest_scope = backtest_active::BacktestScope::All;
     let months = vec![((TOTAL_DAYS as f64) / APPROX_DAYS_PER_MONTH).ceil() as u32];
-    backtest_active::run(&app_context, backtest_scope, &months).await?;
+    backtest_active::run(
+        &app_context,
+        backtest_scope,
+        &months,
+        &backtest_active::TickerSelection::default(),
+    )
+    .await?;
 
     let snapshots =
         capture_snapshot(test_db.database_url(), &strategy_seeds, backtest_scope).await?;
@@ -802,6 +1037,8 @@ struct BacktestCacheVerifyValues {
     verify_calmar_ratio: Option<f64>,
     verify_cagr: Option<f64>,
     verify_max_drawdown_ratio: Option<f64>,
+    verify_cost_scenarios: Option<Value>,
+    verify_cost_robust: Option<bool>,
 }
 
 struct BacktestCacheBalanceValues {
@@ -1136,6 +1373,14 @@ impl TestDatabase {
         Ok(())
     }
 
+    async fn count_signals(&self) -> Result<i64> {
+        let client = connect(self.database_url()).await?;
+        let row = client
+            .query_one("SELECT COUNT(*) FROM signals", &[])
+            .await?;
+        Ok(row.get::<_, i64>(0))
+    }
+
     async fn count_account_operations(&self, strategy_id: &str) -> Result<i64> {
         let client = connect(self.database_url()).await?;
         let row = client
@@ -1281,7 +1526,8 @@ impl TestDatabase {
         let client = connect(self.database_url()).await?;
         let row = client
             .query_one(
-                "SELECT verify_sharpe_ratio, verify_calmar_ratio, verify_cagr, verify_max_drawdown_ratio
+                "SELECT verify_sharpe_ratio, verify_calmar_ratio, verify_cagr, verify_max_drawdown_ratio,
+                        verify_cost_scenarios, verify_cost_robust
                  FROM backtest_cache WHERE id = $1",
                 &[&cache_id],
             )
@@ -1291,6 +1537,8 @@ impl TestDatabase {
             verify_calmar_ratio: row.get(1),
             verify_cagr: row.get(2),
             verify_max_drawdown_ratio: row.get(3),
+            verify_cost_scenarios: row.get(4),
+            verify_cost_robust: row.get(5),
         })
     }
 
@@ -1318,6 +1566,72 @@ impl TestDatabase {
     }
 }
 
+/// Throwaway Postgres container for runs without DATABASE_URL; stopped (and removed) on drop.
+struct PostgresContainer {
+    id: String,
+}
+
+impl PostgresContainer {
+    async fn start_unless_configured() -> Result<Option<Self>> {
+        dotenvy::dotenv().ok();
+        if std::env::var("DATABASE_URL").is_ok() {
+            return Ok(None);
+        }
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        let output = Command::new("docker")
+            .args([
+                "run",
+                "--detach",
+                "--rm",
+                "--env",
+                "POSTGRES_HOST_AUTH_METHOD=trust",
+                "--publish",
+                &format!("127.0.0.1:{}:5432", port),
+                END_TO_END_POSTGRES_IMAGE,
+            ])
+            .output()
+            .context("failed to run docker; set DATABASE_URL or install Docker")?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "docker run {} failed: {}",
+                END_TO_END_POSTGRES_IMAGE,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let container = Self {
+            id: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        };
+
+        let url = format!("postgres://postgres@127.0.0.1:{}/postgres", port);
+        for _ in 0..120 {
+            if connect(&url).await.is_ok() {
+                std::env::set_var("DATABASE_URL", &url);
+                return Ok(Some(container));
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        Err(anyhow!(
+            "Postgres container {} did not accept connections",
+            container.id
+        ))
+    }
+}
+
+impl Drop for PostgresContainer {
+    fn drop(&mut self) {
+        let _ = Command::new("docker").args(["stop", &self.id]).output();
+    }
+}
+
+fn lightgbm_cli_available() -> bool {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("vendor/lightgbm")
+        .exists()
+        || std::env::var_os("PATH").is_some_and(|paths| {
+            std::env::split_paths(&paths).any(|dir| dir.join("lightgbm").is_file())
+        })
+}
+
 fn test_db_root_url() -> Result<String> {
     dotenvy::dotenv().ok();
     let url =
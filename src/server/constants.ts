@@ -14,6 +14,9 @@ export const SETTING_KEYS = {
   BACKTEST_ACTIVE_MONTHS: 'BACKTEST_ACTIVE_MONTHS',
   BACKTEST_INITIAL_CAPITAL: 'BACKTEST_INITIAL_CAPITAL',
   BACKTEST_API_SECRET: 'BACKTEST_API_SECRET',
+  BACKTEST_UNIVERSE: 'BACKTEST_UNIVERSE',
+  BACKTEST_WARMUP_POLICY: 'BACKTEST_WARMUP_POLICY',
+  MIN_TICKER_HISTORY_CANDLES: 'MIN_TICKER_HISTORY_CANDLES',
   LOCAL_OPTIMIZATION_STEP_MULTIPLIERS: 'LOCAL_OPTIMIZATION_STEP_MULTIPLIERS',
   OPTIMIZATION_OBJECTIVE: 'OPTIMIZATION_OBJECTIVE',
   AUTO_OPTIMIZATION_ENABLED: 'AUTO_OPTIMIZATION_ENABLED',
@@ -61,6 +64,35 @@ export const SETTING_KEYS = {
   MAX_ALLOWED_DRAWDOWN_RATIO: 'MAX_ALLOWED_DRAWDOWN_RATIO',
   MINIMUM_DOLLAR_VOLUME_FOR_ENTRY: 'MINIMUM_DOLLAR_VOLUME_FOR_ENTRY',
   MINIMUM_DOLLAR_VOLUME_LOOKBACK: 'MINIMUM_DOLLAR_VOLUME_LOOKBACK',
+  LIVE_CAPACITY_MAX_VOLUME_SHARE: 'LIVE_CAPACITY_MAX_VOLUME_SHARE',
+  LIVE_HALT_CHECK_ENABLED: 'LIVE_HALT_CHECK_ENABLED',
+  LIVE_COST_IMPACT_COEFFICIENT: 'LIVE_COST_IMPACT_COEFFICIENT',
+  LIVE_MONTHLY_COST_BUDGET: 'LIVE_MONTHLY_COST_BUDGET',
+  STUCK_ORDER_TIMEOUT_MINUTES: 'STUCK_ORDER_TIMEOUT_MINUTES',
+  STUCK_ORDER_POLICY: 'STUCK_ORDER_POLICY',
+  DRIFT_QUANTITY_TOLERANCE: 'DRIFT_QUANTITY_TOLERANCE',
+  DRIFT_STOP_TOLERANCE_RATIO: 'DRIFT_STOP_TOLERANCE_RATIO',
+  SHADOW_TRACKING_ERROR_THRESHOLD: 'SHADOW_TRACKING_ERROR_THRESHOLD',
+  PAPER_BROKER_LATENCY_BARS: 'PAPER_BROKER_LATENCY_BARS',
+  PAPER_BROKER_PARTIAL_FILL_PROBABILITY: 'PAPER_BROKER_PARTIAL_FILL_PROBABILITY',
+  SKIP_ALERT_RULES: 'SKIP_ALERT_RULES',
+  SKIP_ALERT_WEBHOOK_URL: 'SKIP_ALERT_WEBHOOK_URL',
+  SKIP_ALERT_EMAIL: 'SKIP_ALERT_EMAIL',
+  ACCOUNT_NOTIFICATION_CHANNELS: 'ACCOUNT_NOTIFICATION_CHANNELS',
+  NOTIFICATION_DAILY_SUMMARY_HOUR_UTC: 'NOTIFICATION_DAILY_SUMMARY_HOUR_UTC',
+  VERIFY_REQUIRE_BEAT_BUY_AND_HOLD: 'VERIFY_REQUIRE_BEAT_BUY_AND_HOLD',
+  VERIFY_MIN_CAPACITY: 'VERIFY_MIN_CAPACITY',
+  VERIFY_MAX_ENTRY_CONCENTRATION: 'VERIFY_MAX_ENTRY_CONCENTRATION',
+  VERIFY_MIN_EXPECTANCY: 'VERIFY_MIN_EXPECTANCY',
+  CAPACITY_MAX_VOLUME_SHARE: 'CAPACITY_MAX_VOLUME_SHARE',
+  STOP_FILL_AT_STOP_PRICE: 'STOP_FILL_AT_STOP_PRICE',
+  ORDER_REJECTION_RATE: 'ORDER_REJECTION_RATE',
+  PARTIAL_FILL_MAX_VOLUME_SHARE: 'PARTIAL_FILL_MAX_VOLUME_SHARE',
+  PARTIAL_FILL_REMAINDER: 'PARTIAL_FILL_REMAINDER',
+  HEDGE_TICKER: 'HEDGE_TICKER',
+  VOLATILITY_INDEX_TICKER: 'VOLATILITY_INDEX_TICKER',
+  TICKER_RUNTIME_OVERRIDES: 'TICKER_RUNTIME_OVERRIDES',
+  EXCHANGE_TIMEZONE: 'EXCHANGE_TIMEZONE',
   SHORT_BORROW_FEE_ANNUAL_RATE: 'SHORT_BORROW_FEE_ANNUAL_RATE',
   TRADE_CLOSE_FEE_RATE: 'TRADE_CLOSE_FEE_RATE',
   TRADE_ENTRY_PRICE_MAX: 'TRADE_ENTRY_PRICE_MAX',
@@ -18,6 +18,19 @@ CREATE TABLE IF NOT EXISTS tickers (
     training BOOLEAN NOT NULL DEFAULT TRUE
 );
 
+ALTER TABLE tickers
+    ADD COLUMN IF NOT EXISTS currency TEXT NOT NULL DEFAULT 'USD',
+    ADD COLUMN IF NOT EXISTS auxiliary BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- One unit of base_currency buys rate units of quote_currency from date on.
+CREATE TABLE IF NOT EXISTS fx_rates (
+    base_currency TEXT NOT NULL,
+    quote_currency TEXT NOT NULL,
+    date DATE NOT NULL,
+    rate DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (base_currency, quote_currency, date)
+);
+
 CREATE TABLE IF NOT EXISTS candles (
     id BIGSERIAL PRIMARY KEY,
     ticker TEXT NOT NULL,
@@ -33,6 +46,80 @@ CREATE TABLE IF NOT EXISTS candles (
     FOREIGN KEY (ticker) REFERENCES tickers(symbol)
 );
 
+-- Candles moved out of candles by engine check-data --quarantine.
+CREATE TABLE IF NOT EXISTS candle_quarantine (
+    ticker TEXT NOT NULL,
+    date DATE NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    unadjusted_close DOUBLE PRECISION,
+    volume_shares BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    quarantined_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticker, date)
+);
+
+CREATE TABLE IF NOT EXISTS ticker_distributions (
+    ticker TEXT NOT NULL,
+    ex_date DATE NOT NULL,
+    amount DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticker, ex_date),
+    FOREIGN KEY (ticker) REFERENCES tickers(symbol)
+);
+
+-- Earnings report dates used by strategy blackout windows.
+CREATE TABLE IF NOT EXISTS earnings (
+    ticker TEXT NOT NULL,
+    report_date DATE NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticker, report_date),
+    FOREIGN KEY (ticker) REFERENCES tickers(symbol)
+);
+
+-- Short interest (fraction of float) and annual borrow fee reports by ticker; either may be NULL.
+CREATE TABLE IF NOT EXISTS short_interest (
+    ticker TEXT NOT NULL,
+    report_date DATE NOT NULL,
+    short_interest_ratio DOUBLE PRECISION,
+    borrow_fee_rate DOUBLE PRECISION,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticker, report_date),
+    FOREIGN KEY (ticker) REFERENCES tickers(symbol)
+);
+
+-- Daily news sentiment scores by ticker, from -1 (negative) to 1 (positive).
+CREATE TABLE IF NOT EXISTS ticker_sentiment (
+    ticker TEXT NOT NULL,
+    date DATE NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (ticker, date),
+    FOREIGN KEY (ticker) REFERENCES tickers(symbol)
+);
+
+-- Scheduled macro release days (FOMC, CPI) read by model features and entry pauses.
+CREATE TABLE IF NOT EXISTS economic_events (
+    event_type TEXT NOT NULL,
+    event_date DATE NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (event_type, event_date)
+);
+
+-- Point-in-time universe memberships (e.g. index constituents); end_date is exclusive and
+-- NULL while the ticker is still a member.
+CREATE TABLE IF NOT EXISTS universe_memberships (
+    universe TEXT NOT NULL,
+    ticker TEXT NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (universe, ticker, start_date),
+    FOREIGN KEY (ticker) REFERENCES tickers(symbol)
+);
+
 CREATE TABLE IF NOT EXISTS templates (
     id TEXT PRIMARY KEY,
     name TEXT NOT NULL,
@@ -80,6 +167,19 @@ CREATE TABLE IF NOT EXISTS lightgbm_models (
 
 CREATE INDEX IF NOT EXISTS idx_lightgbm_models_active ON lightgbm_models(is_active);
 
+CREATE TABLE IF NOT EXISTS nn_models (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    weights_json TEXT NOT NULL,
+    hyperparameters JSONB,
+    train_dataset_stats JSONB,
+    validation_dataset_stats JSONB,
+    validation_metrics JSONB,
+    trained_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
 CREATE TABLE IF NOT EXISTS users (
     id BIGSERIAL PRIMARY KEY,
     email TEXT UNIQUE NOT NULL,
@@ -135,6 +235,13 @@ CREATE TABLE IF NOT EXISTS accounts (
     FOREIGN KEY (user_id) REFERENCES users(id)
 );
 
+ALTER TABLE accounts
+    ADD COLUMN IF NOT EXISTS ensemble_quorum INTEGER,
+    ADD COLUMN IF NOT EXISTS max_stop_ratio DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS max_risk_per_trade DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS take_profit_r_multiple DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS currency TEXT NOT NULL DEFAULT 'USD';
+
 CREATE TABLE IF NOT EXISTS strategies (
     id TEXT PRIMARY KEY,
     name TEXT NOT NULL,
@@ -152,6 +259,9 @@ CREATE TABLE IF NOT EXISTS strategies (
     FOREIGN KEY (account_id) REFERENCES accounts(id)
 );
 
+ALTER TABLE strategies
+    ADD COLUMN IF NOT EXISTS blackout_windows TEXT;
+
 CREATE TABLE IF NOT EXISTS backtest_results (
     id TEXT PRIMARY KEY,
     strategy_id TEXT NOT NULL,
@@ -166,10 +276,22 @@ CREATE TABLE IF NOT EXISTS backtest_results (
     tickers TEXT NOT NULL,
     ticker_scope TEXT NOT NULL DEFAULT 'training',
     strategy_state TEXT,
+    baseline_return DOUBLE PRECISION,
+    excess_return DOUBLE PRECISION,
+    weekly_snapshots TEXT,
+    monthly_snapshots TEXT,
     created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY (strategy_id) REFERENCES strategies(id)
 );
 
+ALTER TABLE backtest_results
+    ADD COLUMN IF NOT EXISTS baseline_return DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS excess_return DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS weekly_snapshots TEXT,
+    ADD COLUMN IF NOT EXISTS monthly_snapshots TEXT,
+    ADD COLUMN IF NOT EXISTS data_hash TEXT,
+    ADD COLUMN IF NOT EXISTS insufficient_history_tickers TEXT;
+
 CREATE TABLE IF NOT EXISTS trades (
     id TEXT PRIMARY KEY,
     strategy_id TEXT NOT NULL,
@@ -190,6 +312,7 @@ CREATE TABLE IF NOT EXISTS trades (
     entry_cancel_after TIMESTAMPTZ,
     stop_order_id TEXT,
     exit_order_id TEXT,
+    target_order_id TEXT,
     changes TEXT NOT NULL DEFAULT '[]',
     created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY (strategy_id) REFERENCES strategies(id),
@@ -198,6 +321,32 @@ CREATE TABLE IF NOT EXISTS trades (
     FOREIGN KEY (backtest_result_id) REFERENCES backtest_results(id)
 );
 
+ALTER TABLE trades
+    ADD COLUMN IF NOT EXISTS target_order_id TEXT;
+
+-- Engine-written batches of backtest trades; moved into trades when their backtest result is stored.
+CREATE TABLE IF NOT EXISTS backtest_trades_staging (
+    backtest_result_id TEXT NOT NULL,
+    batch_index INTEGER NOT NULL,
+    id TEXT NOT NULL,
+    strategy_id TEXT NOT NULL,
+    ticker TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    date DATE NOT NULL,
+    status TEXT NOT NULL,
+    pnl DOUBLE PRECISION,
+    fee DOUBLE PRECISION NOT NULL DEFAULT 0,
+    exit_price DOUBLE PRECISION,
+    exit_date DATE,
+    stop_loss DOUBLE PRECISION,
+    stop_loss_triggered BOOLEAN DEFAULT FALSE,
+    changes TEXT NOT NULL DEFAULT '[]'
+);
+
+CREATE INDEX IF NOT EXISTS idx_backtest_trades_staging_result ON backtest_trades_staging(backtest_result_id, batch_index);
+CREATE INDEX IF NOT EXISTS idx_backtest_trades_staging_strategy ON backtest_trades_staging(strategy_id);
+
 CREATE TABLE IF NOT EXISTS account_operations (
     id TEXT PRIMARY KEY,
     account_id TEXT NOT NULL,
@@ -223,12 +372,20 @@ CREATE TABLE IF NOT EXISTS account_operations (
     signal_confidence DOUBLE PRECISION,
     account_cash_at_plan DOUBLE PRECISION,
     days_held INTEGER,
+    expected_cost DOUBLE PRECISION,
+    replaces_order_id TEXT,
+    take_profit DOUBLE PRECISION,
     created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
     updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
     FOREIGN KEY (account_id) REFERENCES accounts(id),
     FOREIGN KEY (strategy_id) REFERENCES strategies(id) ON DELETE CASCADE
 );
 
+ALTER TABLE account_operations
+    ADD COLUMN IF NOT EXISTS expected_cost DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS replaces_order_id TEXT,
+    ADD COLUMN IF NOT EXISTS take_profit DOUBLE PRECISION;
+
 CREATE TABLE IF NOT EXISTS account_signal_skips (
     id BIGSERIAL PRIMARY KEY,
     strategy_id TEXT NOT NULL,
@@ -244,6 +401,20 @@ CREATE TABLE IF NOT EXISTS account_signal_skips (
     FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS account_state_snapshots (
+    account_id TEXT NOT NULL,
+    snapshot_date DATE NOT NULL,
+    available_cash DOUBLE PRECISION NOT NULL,
+    buying_power DOUBLE PRECISION,
+    equity DOUBLE PRECISION,
+    positions TEXT NOT NULL,
+    stop_orders TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (account_id, snapshot_date),
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
 CREATE TABLE IF NOT EXISTS system_logs (
     id BIGSERIAL PRIMARY KEY,
     source TEXT NOT NULL,
@@ -273,6 +444,9 @@ CREATE TABLE IF NOT EXISTS backtest_cache (
     verify_calmar_ratio DOUBLE PRECISION,
     verify_cagr DOUBLE PRECISION,
     verify_max_drawdown_ratio DOUBLE PRECISION,
+    verify_cost_scenarios JSONB,
+    verify_cost_robust BOOLEAN,
+    verify_window_scores JSONB,
     balance_training_sharpe_ratio DOUBLE PRECISION,
     balance_training_calmar_ratio DOUBLE PRECISION,
     balance_training_cagr DOUBLE PRECISION,
@@ -303,7 +477,12 @@ ALTER TABLE backtest_cache
     ADD COLUMN IF NOT EXISTS balance_validation_sharpe_ratio DOUBLE PRECISION,
     ADD COLUMN IF NOT EXISTS balance_validation_calmar_ratio DOUBLE PRECISION,
     ADD COLUMN IF NOT EXISTS balance_validation_cagr DOUBLE PRECISION,
-    ADD COLUMN IF NOT EXISTS balance_validation_max_drawdown_ratio DOUBLE PRECISION;
+    ADD COLUMN IF NOT EXISTS balance_validation_max_drawdown_ratio DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS verify_cost_scenarios JSONB,
+    ADD COLUMN IF NOT EXISTS verify_cost_robust BOOLEAN,
+    ADD COLUMN IF NOT EXISTS verify_window_scores JSONB,
+    ADD COLUMN IF NOT EXISTS data_hash TEXT,
+    ADD COLUMN IF NOT EXISTS stop_reason TEXT;
 
 CREATE TABLE IF NOT EXISTS signals (
     id TEXT PRIMARY KEY,
@@ -319,6 +498,50 @@ CREATE TABLE IF NOT EXISTS signals (
     FOREIGN KEY (user_id) REFERENCES users(id)
 );
 
+-- Realized forward returns of each active strategy's recent signals, written by the engine's signal-stats command.
+CREATE TABLE IF NOT EXISTS signal_stats (
+    strategy_id TEXT PRIMARY KEY,
+    window_start DATE NOT NULL,
+    window_end DATE NOT NULL,
+    horizon_days INTEGER NOT NULL,
+    signal_count INTEGER NOT NULL DEFAULT 0,
+    evaluated_count INTEGER NOT NULL DEFAULT 0,
+    hit_rate DOUBLE PRECISION,
+    avg_return DOUBLE PRECISION,
+    confidence_deciles TEXT NOT NULL DEFAULT '[]',
+    holding_day_returns TEXT NOT NULL DEFAULT '[]',
+    backtest_win_rate DOUBLE PRECISION,
+    computed_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (strategy_id) REFERENCES strategies(id) ON DELETE CASCADE
+);
+
+-- State of stateful strategies carried between live signal runs, written by the engine's generate-signals command.
+CREATE TABLE IF NOT EXISTS strategy_live_state (
+    strategy_id TEXT PRIMARY KEY,
+    state TEXT NOT NULL,
+    as_of DATE NOT NULL,
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (strategy_id) REFERENCES strategies(id) ON DELETE CASCADE
+);
+
+-- Engine commands queued for `engine worker`. `command` is a pipeline step name such as optimize or plan-operations and `payload` holds that step's options as a JSON object.
+-- Cancel a queued job by setting its status to 'cancelled' and a running one by setting cancel_requested.
+CREATE TABLE IF NOT EXISTS jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    command TEXT NOT NULL,
+    payload TEXT NOT NULL DEFAULT '{}',
+    priority INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'cancelled')),
+    cancel_requested BOOLEAN NOT NULL DEFAULT FALSE,
+    progress TEXT,
+    error TEXT,
+    worker_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMPTZ,
+    finished_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
 CREATE TABLE IF NOT EXISTS remote_optimizer_jobs (
     id UUID PRIMARY KEY,
     template_id TEXT NOT NULL,
@@ -385,7 +608,39 @@ VALUES
     ('TRADE_ENTRY_PRICE_MAX', '1000'),
     ('MINIMUM_DOLLAR_VOLUME_FOR_ENTRY', '150000'),
     ('MINIMUM_DOLLAR_VOLUME_LOOKBACK', '5'),
+    ('LIVE_CAPACITY_MAX_VOLUME_SHARE', '0'),
+    ('LIVE_HALT_CHECK_ENABLED', 'false'),
+    ('LIVE_COST_IMPACT_COEFFICIENT', '0'),
+    ('LIVE_MONTHLY_COST_BUDGET', '0'),
+    ('STUCK_ORDER_TIMEOUT_MINUTES', '30'),
+    ('STUCK_ORDER_POLICY', 'flag'),
+    ('DRIFT_QUANTITY_TOLERANCE', '0'),
+    ('DRIFT_STOP_TOLERANCE_RATIO', '0.005'),
+    ('SHADOW_TRACKING_ERROR_THRESHOLD', '0.05'),
+    ('PAPER_BROKER_LATENCY_BARS', '1'),
+    ('PAPER_BROKER_PARTIAL_FILL_PROBABILITY', '0'),
+    ('SKIP_ALERT_RULES', ''),
+    ('SKIP_ALERT_WEBHOOK_URL', ''),
+    ('SKIP_ALERT_EMAIL', ''),
+    ('ACCOUNT_NOTIFICATION_CHANNELS', ''),
+    ('NOTIFICATION_DAILY_SUMMARY_HOUR_UTC', '21'),
+    ('VERIFY_REQUIRE_BEAT_BUY_AND_HOLD', 'false'),
+    ('VERIFY_MIN_CAPACITY', '0'),
+    ('VERIFY_MAX_ENTRY_CONCENTRATION', '0'),
+    ('VERIFY_MIN_EXPECTANCY', '0'),
+    ('CAPACITY_MAX_VOLUME_SHARE', '0.01'),
+    ('STOP_FILL_AT_STOP_PRICE', 'false'),
+    ('ORDER_REJECTION_RATE', '0'),
+    ('PARTIAL_FILL_MAX_VOLUME_SHARE', '0'),
+    ('PARTIAL_FILL_REMAINDER', 'cancel'),
+    ('HEDGE_TICKER', 'SPY'),
+    ('VOLATILITY_INDEX_TICKER', 'VIX'),
+    ('TICKER_RUNTIME_OVERRIDES', ''),
+    ('EXCHANGE_TIMEZONE', 'America/New_York'),
     ('BACKTEST_INITIAL_CAPITAL', '100000'),
+    ('BACKTEST_UNIVERSE', ''),
+    ('BACKTEST_WARMUP_POLICY', 'skip'),
+    ('MIN_TICKER_HISTORY_CANDLES', '0'),
     ('BACKTEST_ACTIVE_MONTHS', '1,3,6,12,24,36,48,60,120'),
     ('PARAM_SCORE_MIN_TRADES', '20'),
     ('PARAM_SCORE_DRAWDOWN_LAMBDA', '3.5'),
@@ -410,6 +665,14 @@ VALUES
     ('SESSION_COOKIE_VALID_DAYS', '30')
 ON CONFLICT (setting_key) DO NOTHING;
 
+-- The FOMC_DATES setting was replaced by the FOMC rows of economic_events.
+INSERT INTO economic_events (event_type, event_date)
+SELECT 'FOMC', day::date
+FROM settings, regexp_split_to_table(value, '[,\s]+') AS day
+WHERE setting_key = 'FOMC_DATES' AND day ~ '^\d{4}-\d{2}-\d{2}$'
+ON CONFLICT (event_type, event_date) DO NOTHING;
+DELETE FROM settings WHERE setting_key = 'FOMC_DATES';
+
 -- Indexes
 CREATE INDEX IF NOT EXISTS idx_candles_ticker_date ON candles(ticker, date);
 CREATE INDEX IF NOT EXISTS idx_candles_date ON candles(date);
@@ -444,3 +707,4 @@ CREATE INDEX IF NOT EXISTS idx_signals_user_id ON signals(user_id);
 CREATE INDEX IF NOT EXISTS idx_signals_user_date_ticker ON signals(user_id, date, ticker);
 CREATE INDEX IF NOT EXISTS idx_remote_optimizer_jobs_template_id ON remote_optimizer_jobs(template_id);
 CREATE INDEX IF NOT EXISTS idx_remote_optimizer_jobs_status ON remote_optimizer_jobs(status);
+CREATE INDEX IF NOT EXISTS idx_jobs_queue ON jobs(priority DESC, created_at) WHERE status = 'queued';
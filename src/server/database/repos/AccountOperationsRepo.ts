@@ -50,11 +50,15 @@ type AccountOperationRow = QueryResultRow & {
   signal_confidence: number | null;
   account_cash_at_plan: number | null;
   days_held: number | null;
+  expected_cost?: number | null;
+  replaces_order_id?: string | null;
+  take_profit?: number | null;
   created_at: Date;
   updated_at: Date;
   trade_entry_order_id?: string | null;
   trade_stop_order_id?: string | null;
   trade_exit_order_id?: string | null;
+  trade_target_order_id?: string | null;
   entry_order_id?: string | null;
   stop_order_id?: string | null;
   exit_order_id?: string | null;
@@ -71,6 +75,11 @@ type PendingDispatchRow = AccountOperationRow & {
   account_environment: string;
   account_excluded_tickers: string;
   account_excluded_keywords: string;
+  account_ensemble_quorum: number | null;
+  account_max_stop_ratio: number | null;
+  account_max_risk_per_trade: number | null;
+  account_take_profit_r_multiple: number | null;
+  account_currency: string | null;
   account_api_key: string;
   account_api_secret: string;
   account_created_at: Date;
@@ -88,6 +97,11 @@ type AccountRow = QueryResultRow & {
   environment: string;
   excluded_tickers: string;
   excluded_keywords: string;
+  ensemble_quorum: number | null;
+  max_stop_ratio: number | null;
+  max_risk_per_trade: number | null;
+  take_profit_r_multiple: number | null;
+  currency: string | null;
   api_key: string;
   api_secret: string;
   created_at: Date;
@@ -181,6 +195,11 @@ export class AccountOperationsRepo {
       environment: row.environment,
       excludedTickers: this.parseExcludedTickers(row.excluded_tickers),
       excludedKeywords: this.parseExcludedKeywords(row.excluded_keywords),
+      ensembleQuorum: toNullableInteger(row.ensemble_quorum),
+      maxStopRatio: toNullableNumber(row.max_stop_ratio),
+      maxRiskPerTrade: toNullableNumber(row.max_risk_per_trade),
+      takeProfitRMultiple: toNullableNumber(row.take_profit_r_multiple),
+      currency: row.currency || 'USD',
       apiKey: rawApiKey ? decryptValue(rawApiKey) : '',
       apiSecret: rawApiSecret ? decryptValue(rawApiSecret) : '',
       createdAt: new Date(row.created_at),
@@ -197,6 +216,7 @@ export class AccountOperationsRepo {
     const signalConfidence = toNullableNumber(row.signal_confidence);
     const accountCashAtPlan = toNullableNumber(row.account_cash_at_plan);
     const daysHeld = toNullableInteger(row.days_held);
+    const expectedCost = toNullableNumber(row.expected_cost);
     const lastPayload = this.parseAccountOperationPayload(row.last_payload);
 
     return {
@@ -224,11 +244,15 @@ export class AccountOperationsRepo {
       signalConfidence,
       accountCashAtPlan,
       daysHeld,
+      expectedCost,
+      replacesOrderId: trimToNull(row.replaces_order_id),
+      takeProfit: toNullableNumber(row.take_profit),
       createdAt: new Date(row.created_at),
       updatedAt: new Date(row.updated_at),
       entryOrderId: trimToNull(row.trade_entry_order_id ?? row.entry_order_id),
       stopOrderId: trimToNull(row.trade_stop_order_id ?? row.stop_order_id),
-      exitOrderId: trimToNull(row.trade_exit_order_id ?? row.exit_order_id)
+      exitOrderId: trimToNull(row.trade_exit_order_id ?? row.exit_order_id),
+      targetOrderId: trimToNull(row.trade_target_order_id)
     };
   }
 
@@ -391,7 +415,7 @@ export class AccountOperationsRepo {
       `SELECT id, account_id, strategy_id, trade_id, ticker, operation_type,
               quantity, price, stop_loss, previous_stop_loss, triggered_at, status, status_reason, status_updated_at,
               attempt_count, last_attempt_at, reason, order_id, order_type, discount_applied,
-              signal_confidence, account_cash_at_plan, days_held, created_at, updated_at
+              signal_confidence, account_cash_at_plan, days_held, expected_cost, created_at, updated_at
        FROM account_operations
        WHERE ${whereClause}
        ORDER BY ${orderColumn} ${orderDirection}${limitClause}${offsetClause}`,
@@ -442,7 +466,7 @@ export class AccountOperationsRepo {
       `SELECT id, account_id, strategy_id, trade_id, ticker, operation_type,
               quantity, price, stop_loss, previous_stop_loss, triggered_at, status, status_reason, status_updated_at,
               attempt_count, last_attempt_at, reason, order_id, last_payload, order_type, discount_applied,
-              signal_confidence, account_cash_at_plan, days_held, created_at, updated_at
+              signal_confidence, account_cash_at_plan, days_held, expected_cost, created_at, updated_at
        FROM account_operations
        WHERE id = ?`,
       [operationId]
@@ -480,6 +504,7 @@ export class AccountOperationsRepo {
          t.entry_order_id as trade_entry_order_id,
          t.stop_order_id as trade_stop_order_id,
          t.exit_order_id as trade_exit_order_id,
+         t.target_order_id as trade_target_order_id,
          a.id as account_id,
          a.user_id as account_user_id,
          a.name as account_name,
@@ -487,6 +512,11 @@ export class AccountOperationsRepo {
          a.environment as account_environment,
          a.excluded_tickers as account_excluded_tickers,
          a.excluded_keywords as account_excluded_keywords,
+         a.ensemble_quorum as account_ensemble_quorum,
+         a.max_stop_ratio as account_max_stop_ratio,
+         a.max_risk_per_trade as account_max_risk_per_trade,
+         a.take_profit_r_multiple as account_take_profit_r_multiple,
+         a.currency as account_currency,
          a.api_key as account_api_key,
          a.api_secret as account_api_secret,
          a.created_at as account_created_at,
@@ -512,6 +542,11 @@ export class AccountOperationsRepo {
         environment: row.account_environment,
         excluded_tickers: row.account_excluded_tickers,
         excluded_keywords: row.account_excluded_keywords,
+        ensemble_quorum: row.account_ensemble_quorum,
+        max_stop_ratio: row.account_max_stop_ratio,
+        max_risk_per_trade: row.account_max_risk_per_trade,
+        take_profit_r_multiple: row.account_take_profit_r_multiple,
+        currency: row.account_currency,
         api_key: row.account_api_key,
         api_secret: row.account_api_secret,
         created_at: row.account_created_at,
@@ -2,7 +2,7 @@ import type { PoolClient, QueryResultRow } from 'pg';
 import type { CreateTradingAccountInput, TradingAccount } from '../../../shared/types/Account';
 import { decryptValue, encryptValue } from '../../utils/encryption';
 import { DbClient } from '../core/DbClient';
-import { toInteger } from '../core/valueParsers';
+import { toInteger, toNullableInteger, toNullableNumber } from '../core/valueParsers';
 import { StrategiesRepo } from './StrategiesRepo';
 
 type AccountRow = QueryResultRow & {
@@ -13,6 +13,11 @@ type AccountRow = QueryResultRow & {
   environment: string;
   excluded_tickers: string;
   excluded_keywords: string;
+  ensemble_quorum: number | null;
+  max_stop_ratio: number | null;
+  max_risk_per_trade: number | null;
+  take_profit_r_multiple: number | null;
+  currency: string | null;
   api_key: string;
   api_secret: string;
   created_at: Date;
@@ -86,6 +91,11 @@ export class AccountsRepo {
       environment: row.environment,
       excludedTickers: this.parseExcludedTickers(row.excluded_tickers),
       excludedKeywords: this.parseExcludedKeywords(row.excluded_keywords),
+      ensembleQuorum: toNullableInteger(row.ensemble_quorum),
+      maxStopRatio: toNullableNumber(row.max_stop_ratio),
+      maxRiskPerTrade: toNullableNumber(row.max_risk_per_trade),
+      takeProfitRMultiple: toNullableNumber(row.take_profit_r_multiple),
+      currency: row.currency || 'USD',
       apiKey: row.api_key ? decryptValue(row.api_key) : '',
       apiSecret: row.api_secret ? decryptValue(row.api_secret) : '',
       createdAt: row.created_at,
@@ -113,7 +123,7 @@ export class AccountsRepo {
 
   async getAccountsForUser(userId: number): Promise<TradingAccount[]> {
     const rows = await this.db.all<AccountRow>(
-      `SELECT id, user_id, name, provider, environment, excluded_tickers, excluded_keywords, api_key, api_secret, created_at, updated_at
+      `SELECT id, user_id, name, provider, environment, excluded_tickers, excluded_keywords, ensemble_quorum, max_stop_ratio, max_risk_per_trade, take_profit_r_multiple, currency, api_key, api_secret, created_at, updated_at
        FROM accounts
        WHERE user_id = ?
        ORDER BY created_at DESC`,
@@ -129,7 +139,7 @@ export class AccountsRepo {
 
   async getAccountById(id: string, userId: number): Promise<TradingAccount | null> {
     const row = await this.db.get<AccountRow>(
-      `SELECT id, user_id, name, provider, environment, excluded_tickers, excluded_keywords, api_key, api_secret, created_at, updated_at
+      `SELECT id, user_id, name, provider, environment, excluded_tickers, excluded_keywords, ensemble_quorum, max_stop_ratio, max_risk_per_trade, take_profit_r_multiple, currency, api_key, api_secret, created_at, updated_at
        FROM accounts
        WHERE id = ? AND user_id = ?`,
       [id, userId]
@@ -152,7 +162,7 @@ export class AccountsRepo {
     }
     const placeholders = uniqueIds.map(() => '?').join(', ');
     const rows = await this.db.all<AccountRow>(
-      `SELECT id, user_id, name, provider, environment, excluded_tickers, excluded_keywords, api_key, api_secret, created_at, updated_at
+      `SELECT id, user_id, name, provider, environment, excluded_tickers, excluded_keywords, ensemble_quorum, max_stop_ratio, max_risk_per_trade, take_profit_r_multiple, currency, api_key, api_secret, created_at, updated_at
        FROM accounts
        WHERE id IN (${placeholders})
        ORDER BY created_at DESC`,
@@ -181,6 +191,42 @@ export class AccountsRepo {
     return (result?.changes ?? 0) > 0;
   }
 
+  async updateAccountEnsembleQuorum(id: string, userId: number, quorum: number | null): Promise<boolean> {
+    const result = await this.db.run(
+      `UPDATE accounts
+       SET ensemble_quorum = ?, updated_at = CURRENT_TIMESTAMP
+       WHERE id = ? AND user_id = ?`,
+      [quorum, id, userId]
+    );
+    return (result?.changes ?? 0) > 0;
+  }
+
+  async updateAccountRiskOverrides(
+    id: string,
+    userId: number,
+    maxStopRatio: number | null,
+    maxRiskPerTrade: number | null,
+    takeProfitRMultiple: number | null
+  ): Promise<boolean> {
+    const result = await this.db.run(
+      `UPDATE accounts
+       SET max_stop_ratio = ?, max_risk_per_trade = ?, take_profit_r_multiple = ?, updated_at = CURRENT_TIMESTAMP
+       WHERE id = ? AND user_id = ?`,
+      [maxStopRatio, maxRiskPerTrade, takeProfitRMultiple, id, userId]
+    );
+    return (result?.changes ?? 0) > 0;
+  }
+
+  async updateAccountCurrency(id: string, userId: number, currency: string): Promise<boolean> {
+    const result = await this.db.run(
+      `UPDATE accounts
+       SET currency = ?, updated_at = CURRENT_TIMESTAMP
+       WHERE id = ? AND user_id = ?`,
+      [currency, id, userId]
+    );
+    return (result?.changes ?? 0) > 0;
+  }
+
   async deleteAccount(id: string, userId: number): Promise<boolean> {
     return await this.db.withTransaction(async (client) => {
       const account = await this.db.get<IdRow>(
@@ -182,17 +182,32 @@ export class BacktestCacheRepo {
     };
   }
 
-  async getBacktestCache(templateId: string, parameters: Record<string, unknown>): Promise<BacktestCacheLookupResult | null> {
+  // Results computed from market data other than dataHash are skipped; rows stored without a
+  // hash match any data.
+  async getBacktestCache(
+    templateId: string,
+    parameters: Record<string, unknown>,
+    dataHash: string | null = null
+  ): Promise<BacktestCacheLookupResult | null> {
     try {
       const parametersJson = this.serializeJsonConsistently(parameters);
-      const cachedResult = await this.db.get<BacktestCacheFullRow>(
-        `
-          SELECT *
-          FROM backtest_cache
-          WHERE template_id = ? AND parameters = ?
-        `,
-        [templateId, parametersJson]
-      );
+      const cachedResult = dataHash
+        ? await this.db.get<BacktestCacheFullRow>(
+          `
+            SELECT *
+            FROM backtest_cache
+            WHERE template_id = ? AND parameters = ? AND (data_hash IS NULL OR data_hash = ?)
+          `,
+          [templateId, parametersJson, dataHash]
+        )
+        : await this.db.get<BacktestCacheFullRow>(
+          `
+            SELECT *
+            FROM backtest_cache
+            WHERE template_id = ? AND parameters = ?
+          `,
+          [templateId, parametersJson]
+        );
 
       if (!cachedResult) {
         return null;
@@ -413,7 +428,9 @@ export class BacktestCacheRepo {
     durationMinutes: number,
     tool: string,
     topAbsoluteGainTicker?: string | null,
-    topRelativeGainTicker?: string | null
+    topRelativeGainTicker?: string | null,
+    dataHash?: string | null,
+    stopReason?: string | null
   ): Promise<void> {
     if (!Number.isFinite(totalTrades) || totalTrades <= 0) {
       return;
@@ -431,8 +448,8 @@ export class BacktestCacheRepo {
       await this.db.run(
         `
           INSERT INTO backtest_cache
-          (id, template_id, parameters, sharpe_ratio, calmar_ratio, total_return, cagr, max_drawdown, max_drawdown_ratio, win_rate, total_trades, ticker_count, start_date, end_date, period_days, period_months, duration_minutes, tool, top_abs_gain_ticker, top_rel_gain_ticker, created_at)
-          VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+          (id, template_id, parameters, sharpe_ratio, calmar_ratio, total_return, cagr, max_drawdown, max_drawdown_ratio, win_rate, total_trades, ticker_count, start_date, end_date, period_days, period_months, duration_minutes, tool, top_abs_gain_ticker, top_rel_gain_ticker, data_hash, stop_reason, created_at)
+          VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
           ON CONFLICT (id) DO UPDATE
           SET template_id = EXCLUDED.template_id,
               parameters = EXCLUDED.parameters,
@@ -452,7 +469,9 @@ export class BacktestCacheRepo {
               duration_minutes = EXCLUDED.duration_minutes,
               tool = EXCLUDED.tool,
               top_abs_gain_ticker = EXCLUDED.top_abs_gain_ticker,
-              top_rel_gain_ticker = EXCLUDED.top_rel_gain_ticker
+              top_rel_gain_ticker = EXCLUDED.top_rel_gain_ticker,
+              data_hash = EXCLUDED.data_hash,
+              stop_reason = COALESCE(EXCLUDED.stop_reason, backtest_cache.stop_reason)
         `,
         [
           id,
@@ -475,6 +494,8 @@ export class BacktestCacheRepo {
           tool,
           topAbsoluteGainTicker ?? null,
           topRelativeGainTicker ?? null,
+          dataHash ?? null,
+          stopReason ?? null,
           createdAt
         ]
       );
@@ -162,7 +162,13 @@ export class BacktestResultsRepo {
   }
 
   private toBacktestScope(value: unknown): BacktestScope {
-    if (value === 'validation' || value === 'training' || value === 'all' || value === 'live') {
+    if (
+      value === 'validation' ||
+      value === 'training' ||
+      value === 'all' ||
+      value === 'live' ||
+      value === 'custom'
+    ) {
       return value;
     }
     return 'training';
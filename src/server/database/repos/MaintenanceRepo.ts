@@ -19,6 +19,7 @@ export class MaintenanceRepo {
       await this.db.run('DELETE FROM trades');
       await this.db.run('DELETE FROM strategies');
       await this.db.run('DELETE FROM candles');
+      await this.db.run('DELETE FROM ticker_distributions');
       await this.db.run('DELETE FROM tickers');
       await this.db.run('DELETE FROM backtest_results');
       await this.db.run('DELETE FROM templates');
@@ -135,14 +136,20 @@ export class MaintenanceRepo {
       'backtest_cache',
       'backtest_results',
       'candles',
+      'earnings',
+      'economic_events',
       'lightgbm_models',
       'remote_optimizer_jobs',
+      'short_interest',
       'signals',
       'strategies',
       'system_logs',
       'templates',
+      'ticker_distributions',
+      'ticker_sentiment',
       'tickers',
-      'trades'
+      'trades',
+      'universe_memberships'
     ];
 
     return this.db.withTransaction(async (client: PoolClient) => {
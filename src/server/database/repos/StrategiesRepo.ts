@@ -16,6 +16,7 @@ type StrategyRow = QueryResultRow & {
   updated_at: Date;
   backtest_start_date: Date | null;
   last_backtest_duration_minutes: number | null;
+  blackout_windows: string | null;
 };
 
 type StrategyIdTemplateRow = QueryResultRow & {
@@ -43,6 +44,7 @@ export class StrategiesRepo {
       updatedAt: new Date(row.updated_at),
       backtestStartDate: row.backtest_start_date ? new Date(row.backtest_start_date) : null,
       lastBacktestDurationMinutes: toNullableNumber(row.last_backtest_duration_minutes) ?? undefined,
+      blackoutWindows: row.blackout_windows ?? null,
       performance: undefined
     };
   }
@@ -73,6 +75,15 @@ export class StrategiesRepo {
     );
   }
 
+  async updateStrategyBlackoutWindows(strategyId: string, blackoutWindows: string | null): Promise<void> {
+    await this.db.run(
+      `UPDATE strategies
+       SET blackout_windows = ?, updated_at = CURRENT_TIMESTAMP
+       WHERE id = ?`,
+      [blackoutWindows, strategyId]
+    );
+  }
+
   async updateStrategyName(strategyId: string, name: string): Promise<void> {
     await this.db.run(
       `UPDATE strategies
@@ -86,7 +97,7 @@ export class StrategiesRepo {
     const rows = await this.db.all<StrategyRow>(
       `
         SELECT s.id, s.name, s.user_id, s.account_id, s.template_id, s.parameters, s.status, s.created_at, s.updated_at,
-               s.backtest_start_date, s.last_backtest_duration_minutes
+               s.backtest_start_date, s.last_backtest_duration_minutes, s.blackout_windows
         FROM strategies s
         WHERE (s.user_id = ? OR s.user_id IS NULL)
         ORDER BY s.created_at DESC
@@ -101,7 +112,7 @@ export class StrategiesRepo {
     const rows = await this.db.all<StrategyRow>(
       `
         SELECT s.id, s.name, s.user_id, s.account_id, s.template_id, s.parameters, s.status, s.created_at, s.updated_at,
-               s.backtest_start_date, s.last_backtest_duration_minutes
+               s.backtest_start_date, s.last_backtest_duration_minutes, s.blackout_windows
         FROM strategies s
         WHERE s.template_id = ?
           AND (s.user_id = ? OR s.user_id IS NULL)
@@ -117,7 +128,7 @@ export class StrategiesRepo {
     const row = await this.db.get<StrategyRow>(
       `
         SELECT s.id, s.name, s.user_id, s.account_id, s.template_id, s.parameters, s.status, s.created_at, s.updated_at,
-               s.backtest_start_date, s.last_backtest_duration_minutes
+               s.backtest_start_date, s.last_backtest_duration_minutes, s.blackout_windows
         FROM strategies s
         WHERE s.id = ?
           AND (s.user_id = ? OR s.user_id IS NULL)
@@ -266,8 +277,8 @@ export class StrategiesRepo {
         }
 
         await this.db.run(
-          'DELETE FROM account_signal_skips WHERE strategy_id = ? AND source = ?',
-          [strategyId, 'backtest'],
+          'DELETE FROM account_signal_skips WHERE strategy_id = ? AND source = ANY(?::text[])',
+          [strategyId, ['backtest', 'signal_generation']],
           client
         );
 
@@ -25,6 +25,7 @@ type TradeRow = QueryResultRow & {
   entry_cancel_after?: Date | null;
   stop_order_id: string | null;
   exit_order_id: string | null;
+  target_order_id: string | null;
   changes: unknown;
   created_at: Date;
   user_id?: number | null;
@@ -127,6 +128,7 @@ export class TradesRepo {
       entryCancelAfter: row.entry_cancel_after ? new Date(row.entry_cancel_after) : undefined,
       stopOrderId: trimToNull(row.stop_order_id),
       exitOrderId: trimToNull(row.exit_order_id),
+      targetOrderId: trimToNull(row.target_order_id),
       createdAt: new Date(row.created_at),
       changes: this.parseTradeChanges(row.changes)
     };
@@ -194,11 +196,73 @@ export class TradesRepo {
     if (!normalizedOrderId) {
       return;
     }
+    const row = await this.db.get<QueryResultRow & { stop_order_id: unknown; changes: unknown }>(
+      `SELECT stop_order_id, changes
+         FROM trades
+        WHERE id = ?`,
+      [normalizedTradeId]
+    );
+    if (!row) {
+      return;
+    }
+    const currentOrderId = trimToNull(row.stop_order_id);
+    if (currentOrderId === normalizedOrderId) {
+      return;
+    }
+
+    // Keeps the lineage of replaced stop orders, oldest first.
+    const changes = this.parseTradeChanges(row.changes);
+    changes.push({
+      field: 'stopOrderId',
+      oldValue: currentOrderId,
+      newValue: normalizedOrderId,
+      changedAt: new Date()
+    });
     await this.db.run(
       `UPDATE trades
-          SET stop_order_id = ?
+          SET stop_order_id = ?,
+              changes = ?
+        WHERE id = ?`,
+      [normalizedOrderId, JSON.stringify(changes), normalizedTradeId]
+    );
+  }
+
+  async updateTradeTargetOrderId(
+    tradeId: string | null | undefined,
+    targetOrderId: string | null | undefined
+  ): Promise<void> {
+    const normalizedTradeId = typeof tradeId === 'string' ? tradeId.trim() : '';
+    const normalizedOrderId = trimToNull(targetOrderId);
+    if (!normalizedTradeId || !normalizedOrderId) {
+      return;
+    }
+    const row = await this.db.get<QueryResultRow & { target_order_id: unknown; changes: unknown }>(
+      `SELECT target_order_id, changes
+         FROM trades
+        WHERE id = ?`,
+      [normalizedTradeId]
+    );
+    if (!row) {
+      return;
+    }
+    const currentOrderId = trimToNull(row.target_order_id);
+    if (currentOrderId === normalizedOrderId) {
+      return;
+    }
+
+    const changes = this.parseTradeChanges(row.changes);
+    changes.push({
+      field: 'targetOrderId',
+      oldValue: currentOrderId,
+      newValue: normalizedOrderId,
+      changedAt: new Date()
+    });
+    await this.db.run(
+      `UPDATE trades
+          SET target_order_id = ?,
+              changes = ?
         WHERE id = ?`,
-      [normalizedOrderId, normalizedTradeId]
+      [normalizedOrderId, JSON.stringify(changes), normalizedTradeId]
     );
   }
 
@@ -291,7 +355,7 @@ export class TradesRepo {
     let sql = `
       SELECT t.id, t.strategy_id, t.backtest_result_id, t.ticker, t.quantity, t.price, t.date, t.status,
              t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered,
-             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.changes, t.created_at,
+             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.changes, t.created_at,
              COALESCE(t.user_id, s.user_id) as user_id
       FROM trades t
       LEFT JOIN strategies s ON s.id = t.strategy_id
@@ -475,7 +539,7 @@ export class TradesRepo {
       `
       SELECT t.id, t.strategy_id, t.backtest_result_id, t.ticker, t.quantity, t.price, t.date, t.status,
              t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered,
-             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.changes, t.created_at,
+             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.changes, t.created_at,
              COALESCE(t.user_id, s.user_id) as user_id
       FROM trades t
       LEFT JOIN strategies s ON s.id = t.strategy_id
@@ -508,7 +572,7 @@ export class TradesRepo {
       `
       SELECT t.id, t.strategy_id, t.backtest_result_id, t.ticker, t.quantity, t.price, t.date, t.status,
              t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered,
-             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.changes, t.created_at,
+             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.changes, t.created_at,
              COALESCE(t.user_id, s.user_id) as user_id,
              s.account_id,
              s.name as strategy_name
@@ -594,7 +658,7 @@ export class TradesRepo {
       `
       SELECT t.id, t.strategy_id, t.backtest_result_id, t.ticker, t.quantity, t.price, t.date, t.status,
              t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered,
-             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.changes, t.created_at,
+             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.changes, t.created_at,
              COALESCE(t.user_id, s.user_id) as user_id
       FROM trades t
       LEFT JOIN strategies s ON s.id = t.strategy_id
@@ -630,7 +694,7 @@ export class TradesRepo {
       `
       SELECT t.id, t.strategy_id, t.backtest_result_id, t.ticker, t.quantity, t.price, t.date, t.status,
              t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered,
-             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.changes, t.created_at,
+             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.changes, t.created_at,
              COALESCE(t.user_id, s.user_id) as user_id
       FROM trades t
       LEFT JOIN strategies s ON s.id = t.strategy_id
@@ -673,7 +737,7 @@ export class TradesRepo {
     const sql = `
       SELECT t.id, t.strategy_id, t.backtest_result_id, t.ticker, t.quantity, t.price, t.date, t.status,
              t.pnl, t.fee, t.exit_price, t.exit_date, t.stop_loss, t.stop_loss_triggered,
-             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.changes, t.created_at, t.user_id as user_id
+             t.entry_order_id, t.entry_cancel_after, t.stop_order_id, t.exit_order_id, t.target_order_id, t.changes, t.created_at, t.user_id as user_id
       FROM trades t
       WHERE t.id = ? AND (t.user_id = ? OR t.user_id IS NULL)
     `;
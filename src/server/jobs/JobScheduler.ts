@@ -13,7 +13,8 @@ export type JobType =
   | 'plan-operations'
   | 'dispatch-operations'
   | 'optimize'
-  | 'train-lightgbm';
+  | 'train-lightgbm'
+  | 'try-parameters';
 
 export type JobStatus = 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
 
@@ -10,6 +10,7 @@ import { createDispatchOperationsHandler } from './handlers/dispatchOperationsHa
 import { createReconcileTradesHandler } from './handlers/reconcileTradesHandler';
 import { createOptimizeHandler } from './handlers/optimizeHandler';
 import { createTrainLightgbmHandler } from './handlers/trainLightgbmHandler';
+import { createTryParametersHandler } from './handlers/tryParametersHandler';
 
 type HandlerMap = Record<JobType, JobHandler>;
 
@@ -24,6 +25,7 @@ export function createJobHandlers(deps: JobHandlerDependencies): HandlerMap {
     'plan-operations': createPlanOperationsHandler(deps),
     'dispatch-operations': createDispatchOperationsHandler(deps),
     'optimize': createOptimizeHandler(deps),
-    'train-lightgbm': createTrainLightgbmHandler(deps)
+    'train-lightgbm': createTrainLightgbmHandler(deps),
+    'try-parameters': createTryParametersHandler(deps)
   };
 }
@@ -61,7 +61,8 @@ export function createDispatchOperationsHandler(deps: JobHandlerDependencies): J
 
       if (result.status === 'sent') {
         try {
-          if (result.orderId) {
+          // Stop order ids go through updateTradeStopOrderId below, which keeps their lineage.
+          if (result.orderId && candidate.operation.operationType !== 'update_stop_loss') {
             await deps.db.trades.updateTradeOrderIdForOperation(candidate.operation, result.orderId);
           }
           if (candidate.operation.operationType === 'open_position') {
@@ -73,6 +74,9 @@ export function createDispatchOperationsHandler(deps: JobHandlerDependencies): J
             if (result.stopOrderId) {
               await deps.db.trades.updateTradeStopOrderId(candidate.operation.tradeId, result.stopOrderId);
             }
+            if (result.targetOrderId) {
+              await deps.db.trades.updateTradeTargetOrderId(candidate.operation.tradeId, result.targetOrderId);
+            }
           } else if (candidate.operation.operationType === 'update_stop_loss') {
             await deps.db.trades.updateTradeStopLossFromOperation(candidate.operation);
             if (result.orderId) {
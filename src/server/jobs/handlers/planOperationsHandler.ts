@@ -1,13 +1,23 @@
 import { JobHandler } from '../JobScheduler';
 import { JobHandlerDependencies } from '../types';
+import { SETTING_KEYS } from '../../constants';
+import type { SkipAlertPayload } from '../../services/EmailService';
 
 const PLAN_SOURCE = 'plan-operations-job';
+const SKIP_ALERT_SOURCE = 'skip-alerts';
 
 export function createPlanOperationsHandler(deps: JobHandlerDependencies): JobHandler {
   return async (ctx) => {
     const logMetadata = { jobId: ctx.job.id };
     ctx.loggingService.info(PLAN_SOURCE, 'Running plan-operations job', logMetadata);
+    const startedAt = new Date();
     await deps.engineCli.run('plan-operations', [], ctx.abortSignal, logMetadata);
+    await emailSkipAlerts(deps, startedAt).catch(error => {
+      ctx.loggingService.error(PLAN_SOURCE, 'Failed to send skip alert email', {
+        ...logMetadata,
+        error: error instanceof Error ? error.message : String(error)
+      });
+    });
     const hasDispatchJob = ctx.scheduler.hasPendingJob(job => job.type === 'dispatch-operations');
     if (!hasDispatchJob) {
       ctx.scheduler.scheduleJob('dispatch-operations', {
@@ -20,3 +30,15 @@ export function createPlanOperationsHandler(deps: JobHandlerDependencies): JobHa
     };
   };
 }
+
+async function emailSkipAlerts(deps: JobHandlerDependencies, since: Date): Promise<void> {
+  const email = (await deps.db.settings.getSettingValue(SETTING_KEYS.SKIP_ALERT_EMAIL))?.trim() ?? '';
+  if (!email) {
+    return;
+  }
+  const logs = await deps.db.systemLogs.getSystemLogs({ source: SKIP_ALERT_SOURCE, limit: 20 });
+  const alerts = logs
+    .filter(log => new Date(log.created_at).getTime() >= since.getTime() && log.metadata)
+    .flatMap(log => (JSON.parse(log.metadata as string).alerts ?? []) as SkipAlertPayload[]);
+  await deps.emailService.sendSkipAlertSummary(email, alerts);
+}
@@ -2,12 +2,20 @@ import { JobHandler } from '../JobScheduler';
 import { JobHandlerDependencies } from '../types';
 
 const RECONCILE_SOURCE = 'reconcile-trades-job';
+const NOTIFICATIONS_SOURCE = 'notifications';
 
 export function createReconcileTradesHandler(deps: JobHandlerDependencies): JobHandler {
   return async (ctx) => {
     const logMetadata = { jobId: ctx.job.id };
     ctx.loggingService.info(RECONCILE_SOURCE, 'Running reconcile-trades job', logMetadata);
+    const startedAt = new Date();
     await deps.engineCli.run('reconcile-trades', [], ctx.abortSignal, logMetadata);
+    await emailAccountNotifications(deps, startedAt).catch(error => {
+      ctx.loggingService.error(RECONCILE_SOURCE, 'Failed to send account notification email', {
+        ...logMetadata,
+        error: error instanceof Error ? error.message : String(error)
+      });
+    });
 
     const skipPlanOperations = Boolean(ctx.job.metadata?.skipPlanOperations);
     if (!skipPlanOperations) {
@@ -25,3 +33,27 @@ export function createReconcileTradesHandler(deps: JobHandlerDependencies): JobH
     };
   };
 }
+
+async function emailAccountNotifications(deps: JobHandlerDependencies, since: Date): Promise<void> {
+  const logs = await deps.db.systemLogs.getSystemLogs({ source: NOTIFICATIONS_SOURCE, limit: 200 });
+  const messagesByEmail = new Map<string, string[]>();
+  for (const log of logs.reverse()) {
+    if (new Date(log.created_at).getTime() < since.getTime() || !log.metadata) {
+      continue;
+    }
+    const recipients: unknown = JSON.parse(log.metadata).emailTo;
+    if (!Array.isArray(recipients)) {
+      continue;
+    }
+    for (const recipient of recipients) {
+      if (typeof recipient === 'string' && recipient.trim()) {
+        const messages = messagesByEmail.get(recipient.trim()) ?? [];
+        messages.push(log.message);
+        messagesByEmail.set(recipient.trim(), messages);
+      }
+    }
+  }
+  for (const [email, messages] of messagesByEmail) {
+    await deps.emailService.sendAccountNotifications(email, messages);
+  }
+}
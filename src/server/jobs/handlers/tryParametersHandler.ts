@@ -0,0 +1,75 @@
+import { JobHandler } from '../JobScheduler';
+import { JobHandlerDependencies } from '../types';
+
+const TRY_PARAMETERS_SOURCE = 'try-parameters-job';
+const TRY_PARAMETERS_SUMMARY_PREFIX = 'STRATCRAFT_TRY_PARAMETERS_SUMMARY=';
+
+export interface TryParametersMetadata {
+  templateId: string;
+  parameters: Record<string, number>;
+  startDate?: string;
+  endDate?: string;
+}
+
+export interface TryParametersSummary {
+  templateId: string;
+  startDate: string;
+  endDate: string;
+  initialCapital: number;
+  finalPortfolioValue: number;
+  totalReturn: number;
+  cagr: number;
+  sharpeRatio: number;
+  calmarRatio: number;
+  maxDrawdownRatio: number;
+  winRate: number;
+  totalTrades: number;
+  equityCurve: Array<{ date: string; portfolioValue: number }>;
+  durationSeconds: number;
+}
+
+const parseSummary = (output: string): TryParametersSummary | null => {
+  const lines = output.split(/\r?\n/);
+  for (let idx = lines.length - 1; idx >= 0; idx -= 1) {
+    const line = lines[idx];
+    if (!line.startsWith(TRY_PARAMETERS_SUMMARY_PREFIX)) {
+      continue;
+    }
+    try {
+      return JSON.parse(line.slice(TRY_PARAMETERS_SUMMARY_PREFIX.length)) as TryParametersSummary;
+    } catch {
+      return null;
+    }
+  }
+  return null;
+};
+
+export function createTryParametersHandler(deps: JobHandlerDependencies): JobHandler {
+  return async (ctx) => {
+    const metadata = (ctx.job.metadata ?? {}) as Partial<TryParametersMetadata>;
+    const templateId = typeof metadata.templateId === 'string' ? metadata.templateId : '';
+    if (!templateId) {
+      throw new Error('Try parameters job is missing a template id.');
+    }
+    const logMetadata = { jobId: ctx.job.id, templateId };
+    const args = [templateId, '--parameters', JSON.stringify(metadata.parameters ?? {})];
+    if (metadata.startDate) {
+      args.push('--start-date', metadata.startDate);
+    }
+    if (metadata.endDate) {
+      args.push('--end-date', metadata.endDate);
+    }
+
+    ctx.loggingService.info(TRY_PARAMETERS_SOURCE, `Backtesting parameters of template ${templateId}`, logMetadata);
+    const { stdout } = await deps.engineCli.runWithOutput('try-parameters', args, ctx.abortSignal, logMetadata);
+    const summary = parseSummary(stdout);
+    if (!summary) {
+      throw new Error('Engine did not report a try-parameters summary.');
+    }
+
+    return {
+      message: `Backtested ${templateId}: CAGR ${(summary.cagr * 100).toFixed(2)}%, ${summary.totalTrades} trades`,
+      meta: { summary }
+    };
+  };
+}
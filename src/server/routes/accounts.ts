@@ -60,6 +60,16 @@ const parseExcludedKeywordsInput = (rawValue: unknown) => {
   };
 };
 
+// Blank input clears the value (null); anything that is not a finite number is undefined.
+const parseOptionalNumberInput = (rawValue: unknown): number | null | undefined => {
+  const text = typeof rawValue === 'string' ? rawValue.trim() : '';
+  if (text.length === 0) {
+    return null;
+  }
+  const value = Number(text);
+  return Number.isFinite(value) ? value : undefined;
+};
+
 const extractQueryMessage = (param: unknown): string | undefined => {
   if (typeof param === 'string' && param.length > 0) {
     return param;
@@ -322,6 +332,11 @@ router.get<AccountParams>('/:id', requireAuth, async (req, res) => {
       excludedKeywords,
       excludedKeywordCount: excludedKeywords.length,
       excludedKeywordsInputValue: excludedKeywords.join(' '),
+      ensembleQuorum: tradingAccount.ensembleQuorum,
+      maxStopPercent: tradingAccount.maxStopRatio !== null ? Number((tradingAccount.maxStopRatio * 100).toFixed(4)) : null,
+      maxRiskPerTrade: tradingAccount.maxRiskPerTrade,
+      takeProfitRMultiple: tradingAccount.takeProfitRMultiple,
+      currency: tradingAccount.currency,
       snapshot,
       snapshotBadge: getSnapshotBadgeMeta(snapshot),
       snapshotMessage: snapshot?.message ?? null,
@@ -472,6 +487,88 @@ router.post<AccountParams>('/:id/keyword-restrictions', requireAuth, async (req,
   }
 });
 
+router.post<AccountParams>('/:id/ensemble-quorum', requireAuth, async (req, res) => {
+  const { id } = req.params;
+  try {
+    const userId = getReqUserId(req);
+    const account = await req.db.accounts.getAccountById(id, userId);
+    if (!account) {
+      return res.redirect('/?error=' + encodeURIComponent('Account not found or inaccessible.'));
+    }
+
+    const rawValue = typeof req.body?.ensembleQuorum === 'string' ? req.body.ensembleQuorum.trim() : '';
+    const quorum = rawValue.length === 0 ? null : Number(rawValue);
+    if (quorum !== null && (!Number.isInteger(quorum) || quorum < 1)) {
+      return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Ensemble quorum must be a whole number of at least 1.')}`);
+    }
+    await req.db.accounts.updateAccountEnsembleQuorum(id, userId, quorum);
+    const successMessage =
+      quorum !== null
+        ? `${account.name} now opens positions only when ${quorum} strateg${quorum === 1 ? 'y agrees' : 'ies agree'}.`
+        : `Disabled strategy voting for ${account.name}.`;
+    return res.redirect(`/accounts/${id}?success=${encodeURIComponent(successMessage)}`);
+  } catch (error) {
+    console.error('Failed to update account ensemble quorum:', error);
+    return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Unable to save the ensemble quorum right now.')}`);
+  }
+});
+
+router.post<AccountParams>('/:id/risk-overrides', requireAuth, async (req, res) => {
+  const { id } = req.params;
+  try {
+    const userId = getReqUserId(req);
+    const account = await req.db.accounts.getAccountById(id, userId);
+    if (!account) {
+      return res.redirect('/?error=' + encodeURIComponent('Account not found or inaccessible.'));
+    }
+
+    const maxStopPercent = parseOptionalNumberInput(req.body?.maxStopPercent);
+    const maxRiskPerTrade = parseOptionalNumberInput(req.body?.maxRiskPerTrade);
+    const takeProfitRMultiple = parseOptionalNumberInput(req.body?.takeProfitRMultiple);
+    if (maxStopPercent === undefined || (maxStopPercent !== null && (maxStopPercent <= 0 || maxStopPercent >= 100))) {
+      return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Maximum stop distance must be between 0 and 100 percent.')}`);
+    }
+    if (maxRiskPerTrade === undefined || (maxRiskPerTrade !== null && maxRiskPerTrade <= 0)) {
+      return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Maximum risk per trade must be a positive amount.')}`);
+    }
+    if (takeProfitRMultiple === undefined || (takeProfitRMultiple !== null && takeProfitRMultiple <= 0)) {
+      return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Take-profit target must be a positive multiple of the stop risk.')}`);
+    }
+    await req.db.accounts.updateAccountRiskOverrides(
+      id,
+      userId,
+      maxStopPercent !== null ? maxStopPercent / 100 : null,
+      maxRiskPerTrade,
+      takeProfitRMultiple
+    );
+    return res.redirect(`/accounts/${id}?success=${encodeURIComponent(`Saved risk limits for ${account.name}.`)}`);
+  } catch (error) {
+    console.error('Failed to update account risk overrides:', error);
+    return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Unable to save risk limits right now.')}`);
+  }
+});
+
+router.post<AccountParams>('/:id/currency', requireAuth, async (req, res) => {
+  const { id } = req.params;
+  try {
+    const userId = getReqUserId(req);
+    const account = await req.db.accounts.getAccountById(id, userId);
+    if (!account) {
+      return res.redirect('/?error=' + encodeURIComponent('Account not found or inaccessible.'));
+    }
+
+    const currency = typeof req.body?.currency === 'string' ? req.body.currency.trim().toUpperCase() : '';
+    if (!/^[A-Z]{3}$/.test(currency)) {
+      return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Currency must be a three-letter code such as USD or EUR.')}`);
+    }
+    await req.db.accounts.updateAccountCurrency(id, userId, currency);
+    return res.redirect(`/accounts/${id}?success=${encodeURIComponent(`${account.name} now reports in ${currency}.`)}`);
+  } catch (error) {
+    console.error('Failed to update account currency:', error);
+    return res.redirect(`/accounts/${id}?error=${encodeURIComponent('Unable to save the account currency right now.')}`);
+  }
+});
+
 router.post<AccountParams>('/:id/reconcile-trades', requireAuth, async (req, res) => {
   const { id } = req.params;
   try {
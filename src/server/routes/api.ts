@@ -67,17 +67,25 @@ router.get('/health', async (req: Request, res: Response) => {
   }
 });
 
+// Market data content hash sent by the engine with cache lookups and stores.
+const normalizeDataHash = (value: unknown): string | null =>
+  typeof value === 'string' && value.trim().length > 0 ? value.trim() : null;
+
 // Backtest cache endpoints
 // Check if backtest result exists in cache
 router.post('/backtest/check', requireBacktestSecret, async (req: Request, res: Response) => {
   try {
-    const { templateId, parameters } = req.body;
+    const { templateId, parameters, dataHash } = req.body;
 
     if (!templateId || !parameters) {
       return res.status(400).json({ error: 'Missing required fields: templateId and parameters' });
     }
 
-    const cachedResult = await req.db.backtestCache.getBacktestCache(templateId, parameters);
+    const cachedResult = await req.db.backtestCache.getBacktestCache(
+      templateId,
+      parameters,
+      normalizeDataHash(dataHash),
+    );
 
     if (cachedResult) {
       res.json({
@@ -143,6 +151,8 @@ router.post('/backtest/store', requireBacktestSecret, async (req: Request, res:
       tool = 'unknown',
       topAbsoluteGainTicker,
       topRelativeGainTicker,
+      dataHash,
+      stopReason,
     } = req.body;
 
     if (!templateId || !parameters || sharpeRatio === undefined ||
@@ -198,6 +208,8 @@ router.post('/backtest/store', requireBacktestSecret, async (req: Request, res:
       tool,
       normalizedTopAbsoluteGainTicker,
       normalizedTopRelativeGainTicker,
+      normalizeDataHash(dataHash),
+      typeof stopReason === 'string' && stopReason.trim().length > 0 ? stopReason.trim() : null,
     );
 
     res.json({ success: true, message: 'Backtest result cached successfully' });
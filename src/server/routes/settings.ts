@@ -318,7 +318,7 @@ const SETTINGS_DEFINITIONS: SettingDefinition[] = [
     key: SETTING_KEYS.SHORT_BORROW_FEE_ANNUAL_RATE,
     group: 'engine',
     label: 'Short Borrow Fee (Annual)',
-    description: 'Annualized borrow cost for short positions (e.g. 0.003 = 0.3% per year).',
+    description: 'Annualized borrow cost for short positions (e.g. 0.003 = 0.3% per year) in tickers without imported borrow fee reports; imported reports replace it from their date on.',
     placeholder: '0.003',
     inputType: 'number',
     min: '0'
@@ -359,6 +359,249 @@ const SETTINGS_DEFINITIONS: SettingDefinition[] = [
     inputType: 'number',
     min: '0'
   },
+  {
+    key: SETTING_KEYS.LIVE_CAPACITY_MAX_VOLUME_SHARE,
+    group: 'engine',
+    label: 'Live Capacity Max Volume Share',
+    description: 'Share of a ticker\'s 20-day average dollar volume that one day\'s planned buys across all accounts may use, e.g. 0.01 = 1% (0 disables throttling).',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.LIVE_HALT_CHECK_ENABLED,
+    group: 'engine',
+    label: 'Live Halt Check Enabled',
+    description: 'Set to true to pause pending operations on tickers the broker reports as halted and re-plan once they trade again (true/false).',
+    placeholder: 'false',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.LIVE_COST_IMPACT_COEFFICIENT,
+    group: 'engine',
+    label: 'Live Cost Impact Coefficient',
+    description: 'Square-root market impact coefficient for expected operation costs: impact = coefficient * sqrt(order / 20-day dollar volume) * order (0 disables the impact term).',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.LIVE_MONTHLY_COST_BUDGET,
+    group: 'engine',
+    label: 'Live Monthly Cost Budget',
+    description: 'Dollars of expected transaction cost each strategy may spend per calendar month; once exhausted, lower-confidence buys are deferred (0 disables the budget).',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.STUCK_ORDER_TIMEOUT_MINUTES,
+    group: 'engine',
+    label: 'Stuck Order Timeout Minutes',
+    description: 'Minutes an order may stay accepted or pending at the broker before the watch-orders command treats it as stuck.',
+    placeholder: '30',
+    inputType: 'number',
+    min: '1'
+  },
+  {
+    key: SETTING_KEYS.STUCK_ORDER_POLICY,
+    group: 'engine',
+    label: 'Stuck Order Policy',
+    description: 'What watch-orders does with stuck orders: flag (log only), cancel, or resubmit (cancel and place a fresh copy).',
+    placeholder: 'flag',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.DRIFT_QUANTITY_TOLERANCE,
+    group: 'engine',
+    label: 'Drift Quantity Tolerance',
+    description: 'Shares a broker position may differ from the summed active trades on its ticker before detect-drift raises a reconciliation task.',
+    placeholder: '0',
+    inputType: 'number'
+  },
+  {
+    key: SETTING_KEYS.DRIFT_STOP_TOLERANCE_RATIO,
+    group: 'engine',
+    label: 'Drift Stop Tolerance Ratio',
+    description: 'Relative gap allowed between a trade stop loss and the closest broker stop order before detect-drift raises a reconciliation task.',
+    placeholder: '0.005',
+    inputType: 'number'
+  },
+  {
+    key: SETTING_KEYS.SHADOW_TRACKING_ERROR_THRESHOLD,
+    group: 'engine',
+    label: 'Shadow Tracking Error Threshold',
+    description: 'Gap between live P&L and the shadow replay of the same signals, as a share of strategy capital, above which shadow-compare raises an alert.',
+    placeholder: '0.05',
+    inputType: 'number'
+  },
+  {
+    key: SETTING_KEYS.PAPER_BROKER_LATENCY_BARS,
+    group: 'engine',
+    label: 'Paper Broker Latency (candles)',
+    description: 'Daily candles after the submission day before a paper account order can fill; 0 lets it fill on the submission day candle.',
+    placeholder: '1',
+    inputType: 'number'
+  },
+  {
+    key: SETTING_KEYS.PAPER_BROKER_PARTIAL_FILL_PROBABILITY,
+    group: 'engine',
+    label: 'Paper Broker Partial Fill Probability',
+    description: 'Chance (0-1) that a paper account entry or exit order fills only half its shares.',
+    placeholder: '0',
+    inputType: 'number'
+  },
+  {
+    key: SETTING_KEYS.SKIP_ALERT_RULES,
+    group: 'engine',
+    label: 'Skip Alert Rules',
+    description: 'Comma-separated skip or operation reasons that raise an alert after plan-operations: reason (any occurrence today), reason:N (N of the last 7 days) or reason:N/W (N of the last W days) for the same account and ticker. Empty disables alerts.',
+    placeholder: 'stop_missing, insufficient_cash:3, sell_exit_order_pending:4/6',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.SKIP_ALERT_WEBHOOK_URL,
+    group: 'engine',
+    label: 'Skip Alert Webhook URL',
+    description: 'Optional URL that receives each batch of skip alerts as a JSON POST with text and alerts fields.',
+    placeholder: 'https://hooks.example.com/...',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.SKIP_ALERT_EMAIL,
+    group: 'engine',
+    label: 'Skip Alert Email',
+    description: 'Optional address that receives skip alerts by email after each plan-operations job.',
+    placeholder: 'ops@example.com',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.ACCOUNT_NOTIFICATION_CHANNELS,
+    group: 'engine',
+    label: 'Account Notification Channels',
+    description: 'JSON object mapping account ids (or * for all others) to channel lists that reconcile-trades notifies of fills, stop triggers, position mismatches and the daily P&L: {"channel":"telegram","botToken","chatId"}, {"channel":"discord","webhookUrl"} or {"channel":"email","to"}, each with optional "events" (fill, stop_triggered, mismatch, daily_pnl). Empty disables notifications.',
+    placeholder: '{"*": [{"channel": "discord", "webhookUrl": "https://discord.com/api/webhooks/..."}]}',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.NOTIFICATION_DAILY_SUMMARY_HOUR_UTC,
+    group: 'engine',
+    label: 'Notification Daily Summary Hour (UTC)',
+    description: 'UTC hour from which the first reconcile-trades run of the day sends each account its daily P&L summary.',
+    placeholder: '21',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.VERIFY_REQUIRE_BEAT_BUY_AND_HOLD,
+    group: 'engine',
+    label: 'Verify Require Beat Buy-and-Hold',
+    description: 'Set to true so verify only marks parameter sets cost-robust when their base-cost return beats equal-weight buy-and-hold of the same tickers and window (true/false).',
+    placeholder: 'false',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.VERIFY_MIN_CAPACITY,
+    group: 'engine',
+    label: 'Verify Min Capacity',
+    description: 'Minimum estimated capacity in dollars of AUM a parameter set needs in the base cost scenario to be marked cost-robust by verify (0 disables the check).',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.VERIFY_MAX_ENTRY_CONCENTRATION,
+    group: 'engine',
+    label: 'Verify Max Entry Concentration',
+    description: 'Largest share of net P&L a parameter set may earn from trades entered in a single week, in the base cost scenario, to be marked cost-robust by verify, e.g. 0.5 = 50% (0 disables the check).',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.VERIFY_MIN_EXPECTANCY,
+    group: 'engine',
+    label: 'Verify Min Expectancy',
+    description: 'Smallest mean R-multiple (net P&L per trade in units of the risk to its initial stop-loss) a parameter set needs in the base cost scenario to be marked cost-robust by verify, e.g. 0.2 = 0.2R (0 disables the check).',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.CAPACITY_MAX_VOLUME_SHARE,
+    group: 'engine',
+    label: 'Capacity Max Volume Share',
+    description: 'Share of 20-day average dollar volume the average backtest entry may take when estimating strategy capacity, e.g. 0.01 = 1%.',
+    placeholder: '0.01',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.STOP_FILL_AT_STOP_PRICE,
+    group: 'engine',
+    label: 'Stop Fill At Stop Price',
+    description: 'Set to true so backtest stops fill at the stop price even when a candle gaps through it, instead of at the open (true/false). Only for comparing against gap-aware fills.',
+    placeholder: 'false',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.ORDER_REJECTION_RATE,
+    group: 'engine',
+    label: 'Order Rejection Rate',
+    description: 'Share of backtest entry orders the simulated broker rejects, e.g. 0.02 = 2%. The same orders are rejected on every run. 0 disables rejections.',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.PARTIAL_FILL_MAX_VOLUME_SHARE,
+    group: 'engine',
+    label: 'Partial Fill Max Volume Share',
+    description: 'Largest share of the fill candle\'s volume one backtest entry may take, e.g. 0.01 = 1%; larger orders fill partially. 0 disables the cap.',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
+  {
+    key: SETTING_KEYS.PARTIAL_FILL_REMAINDER,
+    group: 'engine',
+    label: 'Partial Fill Remainder',
+    description: 'What happens to the unfilled part of a capped backtest entry: cancel drops it, carry keeps filling it at the next candles\' opens under the same cap until it fills or an opposite signal cancels it.',
+    placeholder: 'cancel',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.HEDGE_TICKER,
+    group: 'engine',
+    label: 'Hedge Ticker',
+    description: 'ETF that strategies with hedgeBeta above 0 hold short against their long exposure, in backtests and planned operations. Signals on this ticker are ignored while a strategy hedges with it.',
+    placeholder: 'SPY',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.VOLATILITY_INDEX_TICKER,
+    group: 'engine',
+    label: 'Volatility Index Ticker',
+    description: 'Auxiliary ticker, imported with import-auxiliary, whose daily closes feed the volatility_index model features and the maxVolatilityIndex entry filter.',
+    placeholder: 'VIX',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.TICKER_RUNTIME_OVERRIDES,
+    group: 'engine',
+    label: 'Ticker Runtime Overrides',
+    description: 'JSON object mapping tickers to replacements for TRADE_SLIPPAGE_RATE, MINIMUM_DOLLAR_VOLUME_FOR_ENTRY, TRADE_ENTRY_PRICE_MIN and TRADE_ENTRY_PRICE_MAX, applied in backtests and planning. Unlisted fields keep the global value.',
+    placeholder: '{"TQQQ": {"TRADE_SLIPPAGE_RATE": 0.002, "MINIMUM_DOLLAR_VOLUME_FOR_ENTRY": 5000000}}',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.EXCHANGE_TIMEZONE,
+    group: 'engine',
+    label: 'Exchange Timezone',
+    description: 'Clock whose local date names each daily session: America/New_York (with daylight saving), UTC, or a fixed offset such as +09:00. Candles, signals and fills are bucketed by this date.',
+    placeholder: 'America/New_York',
+    inputType: 'text'
+  },
   {
     key: SETTING_KEYS.BACKTEST_ACTIVE_MONTHS,
     group: 'engine',
@@ -376,6 +619,31 @@ const SETTINGS_DEFINITIONS: SettingDefinition[] = [
     inputType: 'number',
     min: '1'
   },
+  {
+    key: SETTING_KEYS.BACKTEST_UNIVERSE,
+    group: 'engine',
+    label: 'Backtest Universe',
+    description: 'Point-in-time universe imported with import-universe (e.g. SP500). Optimization and backtests only enter tickers that were members on the signal date. Leave blank to trade every loaded ticker.',
+    placeholder: 'SP500',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.BACKTEST_WARMUP_POLICY,
+    group: 'engine',
+    label: 'Backtest Warm-up Policy',
+    description: 'How strategy backtests treat the history a strategy needs before it signals: skip starts each ticker once its own warm-up ends, strict also moves a start date inside the warm-up to its end, error fails the run on such a start date or on tickers with too little history to ever trade.',
+    placeholder: 'skip',
+    inputType: 'text'
+  },
+  {
+    key: SETTING_KEYS.MIN_TICKER_HISTORY_CANDLES,
+    group: 'engine',
+    label: 'Minimum Ticker History (candles)',
+    description: 'Candles a ticker needs up to a date before strategies signal on it, e.g. 250 for about a year. Younger tickers are skipped with the reason insufficient_history in signal generation, backtests and planning. 0 disables the minimum.',
+    placeholder: '0',
+    inputType: 'number',
+    min: '0'
+  },
   {
     key: SETTING_KEYS.ALPACA_PAPER_URL,
     group: 'alpaca',
@@ -47,7 +47,7 @@ const getSnapshotBadgeMeta = (snapshot?: AccountSnapshot) => {
   }
 };
 
-const ACCOUNT_OPERATION_STATUS_ORDER: AccountOperationStatus[] = ['pending', 'sent', 'skipped', 'failed'];
+const ACCOUNT_OPERATION_STATUS_ORDER: AccountOperationStatus[] = ['pending', 'paused', 'sent', 'skipped', 'failed'];
 
 const ACCOUNT_OPERATION_STATUS_META: Record<AccountOperationStatus, { label: string; badge: string }> = {
   pending: { label: 'Pending', badge: 'warning' },
@@ -1880,6 +1880,35 @@ router.post<StrategyParams>('/strategies/:id/rename', requireAuth, async (req, r
   }
 });
 
+router.post<StrategyParams>('/strategies/:id/blackout', requireAuth, async (req, res) => {
+  try {
+    const { id } = req.params;
+    const raw = typeof req.body.blackoutWindows === 'string' ? req.body.blackoutWindows.trim() : '';
+
+    const strategy = await loadStrategyOrRenderNotFound(req, res, id);
+    if (!strategy) return;
+
+    if (raw) {
+      let parsed: unknown;
+      try {
+        parsed = JSON.parse(raw);
+      } catch {
+        return res.redirect(`/strategies/${id}?error=Blackout windows must be valid JSON`);
+      }
+      if (!parsed || typeof parsed !== 'object' || Array.isArray(parsed)) {
+        return res.redirect(`/strategies/${id}?error=Blackout windows must be a JSON object`);
+      }
+    }
+
+    await req.db.strategies.updateStrategyBlackoutWindows(id, raw || null);
+
+    res.redirect(`/strategies/${id}?success=Blackout windows saved; they apply to signals generated from now on`);
+  } catch (error) {
+    console.error('Error saving blackout windows:', error);
+    res.redirect(`/strategies/${req.params.id}?error=Failed to save blackout windows`);
+  }
+});
+
 router.post<StrategyParams>('/strategies/:id/delete', requireAuth, async (req, res) => {
   try {
     const { id } = req.params;
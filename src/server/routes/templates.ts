@@ -704,6 +704,95 @@ router.get<TemplateParams>('/:templateId', requireAuth, async (req, res) => {
   }
 });
 
+const TRY_PARAMETERS_DATE_PATTERN = /^\d{4}-\d{2}-\d{2}$/;
+
+// Queue a one-off backtest of the submitted parameter values
+router.post<TemplateParams>('/:templateId/try-parameters', requireAuth, requireAdmin, async (req, res) => {
+  const { templateId } = req.params;
+  const templatePath = `/templates/${encodeURIComponent(templateId)}`;
+  try {
+    const template = req.strategyRegistry.getTemplate(templateId, { includeDisabled: true });
+    if (!template) {
+      return res.redirect(`/templates?error=${encodeURIComponent(`Template ${templateId} not found`)}`);
+    }
+
+    const parameters: Record<string, number> = {};
+    for (const param of template.parameters) {
+      if (param.type !== 'number') {
+        continue;
+      }
+      const raw = req.body?.[`param_${param.name}`];
+      if (typeof raw !== 'string' || raw.trim() === '') {
+        continue;
+      }
+      const value = Number(raw);
+      const outOfBounds = (typeof param.min === 'number' && value < param.min)
+        || (typeof param.max === 'number' && value > param.max);
+      if (!Number.isFinite(value) || outOfBounds) {
+        const message = `${param.label || param.name} must be a number between ${param.min ?? '-∞'} and ${param.max ?? '∞'}.`;
+        return res.redirect(`${templatePath}?error=${encodeURIComponent(message)}`);
+      }
+      parameters[param.name] = value;
+    }
+
+    const startDate = typeof req.body?.startDate === 'string' ? req.body.startDate.trim() : '';
+    const endDate = typeof req.body?.endDate === 'string' ? req.body.endDate.trim() : '';
+    if ((startDate && !TRY_PARAMETERS_DATE_PATTERN.test(startDate)) || (endDate && !TRY_PARAMETERS_DATE_PATTERN.test(endDate))) {
+      return res.redirect(`${templatePath}?error=${encodeURIComponent('Dates must be YYYY-MM-DD.')}`);
+    }
+    if (startDate && endDate && startDate > endDate) {
+      return res.redirect(`${templatePath}?error=${encodeURIComponent('Start date must not be after the end date.')}`);
+    }
+
+    const job = req.jobScheduler.scheduleJob('try-parameters', {
+      description: `Try parameters: ${template.name}`,
+      maxRetries: 0,
+      metadata: {
+        templateId,
+        parameters,
+        startDate: startDate || undefined,
+        endDate: endDate || undefined
+      }
+    });
+    return res.redirect(`${templatePath}/try-parameters/${encodeURIComponent(job.id)}`);
+  } catch (error) {
+    req.loggingService?.error?.('templates', 'Failed to queue try-parameters backtest', {
+      templateId,
+      error: error instanceof Error ? error.message : String(error)
+    });
+    const message = error instanceof Error ? error.message : 'Failed to queue the backtest';
+    return res.redirect(`${templatePath}?error=${encodeURIComponent(message)}`);
+  }
+});
+
+// Show the status or result of a try-parameters backtest
+router.get<TemplateParams & { jobId: string }>('/:templateId/try-parameters/:jobId', requireAuth, requireAdmin, async (req, res) => {
+  const { templateId, jobId } = req.params;
+  const template = req.strategyRegistry.getTemplate(templateId, { includeDisabled: true });
+  const job = req.jobScheduler.getJob(jobId);
+  if (!template || !job || job.type !== 'try-parameters' || job.metadata?.templateId !== templateId) {
+    return res.status(404).render('pages/error', {
+      title: 'Backtest Not Found',
+      error: 'This backtest is unknown or the server restarted since it ran.'
+    });
+  }
+
+  const submitted = (job.metadata?.parameters ?? {}) as Record<string, number>;
+  const summary = job.status === 'succeeded' ? (job.result?.meta?.summary ?? null) : null;
+  res.render('pages/try-parameters', {
+    title: `Try Parameters · ${template.name}`,
+    page: 'templates',
+    user: req.user,
+    template,
+    job,
+    isPending: job.status === 'queued' || job.status === 'running',
+    submittedParameters: Object.entries(submitted).map(([name, value]) => ({ name, value })),
+    startDate: job.metadata?.startDate ?? null,
+    endDate: job.metadata?.endDate ?? null,
+    summary
+  });
+});
+
 router.post<TemplateParams>('/:templateId/toggle-enabled', requireAuth, requireAdmin, async (req, res) => {
   const { templateId } = req.params;
   try {
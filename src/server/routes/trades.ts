@@ -26,8 +26,8 @@ const TRADE_SORT_OPTION_LABELS: Record<TradeSortField, string> = {
   pnl: 'P&L',
   ticker: 'Ticker'
 };
-const TRADE_OPERATION_STATUS_ORDER: AccountOperationStatus[] = ['pending', 'sent', 'skipped', 'failed'];
-type TradeOrderType = 'entry' | 'stop' | 'exit' | 'operation';
+const TRADE_OPERATION_STATUS_ORDER: AccountOperationStatus[] = ['pending', 'paused', 'sent', 'skipped', 'failed'];
+type TradeOrderType = 'entry' | 'stop' | 'target' | 'exit' | 'operation';
 type TradeOrderStatus = AccountOperationStatus | 'unknown';
 
 interface TradeOrderView {
@@ -44,7 +44,7 @@ interface TradeOrderView {
   stopLoss: number | null | undefined;
 }
 
-const TRADE_ORDER_TYPE_ORDER: TradeOrderType[] = ['entry', 'stop', 'exit', 'operation'];
+const TRADE_ORDER_TYPE_ORDER: TradeOrderType[] = ['entry', 'stop', 'target', 'exit', 'operation'];
 
 interface TradeOperationsSummary {
   total: number;
@@ -261,6 +261,7 @@ function buildTradeOrderViews(trade: Trade, operations: AccountOperation[] | und
 
   registerOrder('Entry Order', trade.entryOrderId, 'entry');
   registerOrder('Stop Loss Order', trade.stopOrderId, 'stop');
+  registerOrder('Take Profit Order', trade.targetOrderId, 'target');
   registerOrder('Exit Order', trade.exitOrderId, 'exit');
 
   operationsByOrder.forEach((operationEntries, key) => {
@@ -86,11 +86,18 @@ export const BACKTEST_SCOPE_META: Record<BacktestScope, { label: string; badge:
   validation: { label: 'Validation tickers', badge: 'bg-warning text-dark' },
   training: { label: 'Training tickers', badge: 'bg-secondary' },
   all: { label: 'All tickers', badge: 'bg-info text-dark' },
-  live: { label: 'Live backtest', badge: 'bg-success' }
+  live: { label: 'Live backtest', badge: 'bg-success' },
+  custom: { label: 'Custom tickers', badge: 'bg-light text-dark' }
 };
 
 export const normalizeBacktestScope = (value: unknown): BacktestScope => {
-  if (value === 'validation' || value === 'training' || value === 'all' || value === 'live') {
+  if (
+    value === 'validation' ||
+    value === 'training' ||
+    value === 'all' ||
+    value === 'live' ||
+    value === 'custom'
+  ) {
     return value;
   }
   return 'training';
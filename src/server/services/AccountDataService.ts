@@ -10,6 +10,7 @@ import {
 import { AccountOperation, AccountOperationStatus } from '../../shared/types/StrategyTemplate';
 import { LoggingService, LogSource } from './LoggingService';
 import { AlpacaAccountConnector } from './AlpacaAccountConnector';
+import { PaperAccountConnector } from './PaperAccountConnector';
 import { Database } from '../database/Database';
 
 export type DispatchResult = {
@@ -17,6 +18,7 @@ export type DispatchResult = {
   reason?: string;
   orderId?: string | null;
   stopOrderId?: string | null;
+  targetOrderId?: string | null;
   payload?: Record<string, unknown> | null;
   cancelAfter?: Date | string | null;
 };
@@ -49,7 +51,8 @@ export class AccountDataService {
     private db: Database
   ) {
     this.connectors = [
-      new AlpacaAccountConnector(this.loggingService, this.db)
+      new AlpacaAccountConnector(this.loggingService, this.db),
+      new PaperAccountConnector()
     ];
   }
 
@@ -350,6 +350,7 @@ export class AlpacaAccountConnector implements AccountConnector {
         ticker,
         abortSignal
       );
+      await this.cancelTakeProfitOrder(baseUrl, headers, account, operation, ticker, abortSignal);
     }
 
     const payload = this.buildAlpacaOrderPayload(operation, ticker);
@@ -390,6 +391,10 @@ export class AlpacaAccountConnector implements AccountConnector {
       operation.operationType === 'open_position'
         ? this.extractStopLossOrderId(response.data)
         : null;
+    const targetOrderId =
+      operation.operationType === 'open_position' && payload.order_class === 'bracket'
+        ? this.extractTakeProfitOrderId(response.data)
+        : null;
     let cancelAfter: Date | null = null;
     if (operation.operationType === 'open_position') {
       cancelAfter = await this.fetchNextMarketClose(baseUrl, headers, account, abortSignal);
@@ -400,6 +405,7 @@ export class AlpacaAccountConnector implements AccountConnector {
       reason: orderId ? `Order ${orderId}` : undefined,
       orderId: orderId ?? null,
       stopOrderId,
+      targetOrderId,
       payload,
       cancelAfter
     };
@@ -474,9 +480,11 @@ export class AlpacaAccountConnector implements AccountConnector {
 
   private buildAlpacaOrderPayload(operation: AccountOperation, ticker: string) {
     const quantity = operation.quantity ?? 0;
-    if (!Number.isFinite(quantity) || quantity <= 0) {
+    if (!Number.isFinite(quantity) || quantity === 0) {
       throw new Error('invalid_quantity');
     }
+    // Negative quantities are short positions: opened with a sell and closed with a buy.
+    const isShort = quantity < 0;
 
     const payload: Record<string, any> = {
       symbol: ticker,
@@ -487,7 +495,7 @@ export class AlpacaAccountConnector implements AccountConnector {
 
     switch (operation.operationType) {
       case 'open_position': {
-        payload.side = 'buy';
+        payload.side = isShort ? 'sell' : 'buy';
         const metadataOrderType = this.getOperationOrderType(operation);
         if (metadataOrderType === 'limit' && operation.price) {
           payload.type = 'limit';
@@ -495,17 +503,24 @@ export class AlpacaAccountConnector implements AccountConnector {
         } else {
           payload.type = 'market';
         }
-        const stopLossPrice = this.extractStopLossPrice(operation.stopLoss);
+        const stopLossPrice = this.extractLegPrice(operation.stopLoss);
+        const takeProfitPrice = this.extractLegPrice(operation.takeProfit);
         if (stopLossPrice !== null) {
-          payload.order_class = 'oto';
+          // A bracket links the take-profit and stop legs one-cancels-other once the entry fills.
+          payload.order_class = takeProfitPrice !== null ? 'bracket' : 'oto';
           payload.stop_loss = {
             stop_price: stopLossPrice
           };
+          if (takeProfitPrice !== null) {
+            payload.take_profit = {
+              limit_price: takeProfitPrice
+            };
+          }
         }
         break;
       }
       case 'close_position': {
-        payload.side = 'sell';
+        payload.side = isShort ? 'buy' : 'sell';
         const metadataOrderType = this.getOperationOrderType(operation);
         if (metadataOrderType === 'limit' && operation.price) {
           payload.type = 'limit';
@@ -626,6 +641,43 @@ export class AlpacaAccountConnector implements AccountConnector {
     await new Promise((resolve) => setTimeout(resolve, 300));
   }
 
+  private async cancelTakeProfitOrder(
+    baseUrl: string,
+    headers: Record<string, string>,
+    account: TradingAccount,
+    operation: AccountOperation,
+    ticker: string,
+    abortSignal: AbortSignal
+  ): Promise<void> {
+    const orderId = this.normalizeOrderId(operation.targetOrderId);
+    if (!orderId) {
+      return;
+    }
+    try {
+      await axios.delete(`${baseUrl}/orders/${orderId}`, {
+        headers,
+        timeout: this.orderRequestTimeout,
+        signal: abortSignal
+      });
+    } catch (error) {
+      // Cancelling the stop leg of a bracket usually takes its take-profit sibling with it.
+      const status = axios.isAxiosError(error) ? error.response?.status : undefined;
+      if (status === 404 || status === 422) {
+        this.loggingService.info('system', 'Take-profit order already closed before close', {
+          provider: account.provider,
+          accountId: account.id,
+          ticker,
+          orderId
+        });
+        return;
+      }
+      this.attachDispatchPayload(error, { cancelOrderId: orderId });
+      throw error;
+    }
+
+    await new Promise((resolve) => setTimeout(resolve, 300));
+  }
+
   private async replaceStopLossOrder(
     baseUrl: string,
     headers: Record<string, string>,
@@ -640,12 +692,36 @@ export class AlpacaAccountConnector implements AccountConnector {
       throw new Error('invalid_quantity');
     }
 
-    const previousStopPrice = this.extractStopLossPrice(operation.previousStopLoss);
+    const previousStopPrice = this.extractLegPrice(operation.previousStopLoss);
     const payloadStopPrice = payload.stop_price;
     if (payloadStopPrice === undefined) {
       throw new Error('missing_stop_price');
     }
 
+    const patchPayload: Record<string, any> = {
+      stop_price: payloadStopPrice,
+      time_in_force: 'gtc'
+    };
+
+    const replacesOrderId = this.normalizeOrderId(operation.replacesOrderId);
+    if (replacesOrderId) {
+      try {
+        return await this.patchStopLossOrder(baseUrl, headers, replacesOrderId, patchPayload, abortSignal);
+      } catch (error) {
+        // 404 and 422 mean the planned order was filled, cancelled or already replaced.
+        const status = axios.isAxiosError(error) ? error.response?.status : undefined;
+        if (status !== 404 && status !== 422) {
+          throw error;
+        }
+      }
+      this.loggingService.warn('system', 'Planned stop loss order is no longer open; looking up the current one', {
+        provider: account.provider,
+        accountId: account.id,
+        ticker,
+        orderId: replacesOrderId
+      });
+    }
+
     const desiredSide =
       typeof payload.side === 'string'
         ? payload.side.trim().toLowerCase()
@@ -687,11 +763,20 @@ export class AlpacaAccountConnector implements AccountConnector {
       };
     }
 
-    const patchPayload: Record<string, any> = {
-      stop_price: payloadStopPrice,
-      time_in_force: 'gtc'
-    };
+    return this.patchStopLossOrder(baseUrl, headers, orderId, patchPayload, abortSignal);
+  }
 
+  /**
+   * Replaces an open stop order in place; Alpaca answers with the replacement order, whose id
+   * becomes the trade's stop order.
+   */
+  private async patchStopLossOrder(
+    baseUrl: string,
+    headers: Record<string, string>,
+    orderId: string,
+    patchPayload: Record<string, any>,
+    abortSignal: AbortSignal
+  ): Promise<DispatchResult> {
     let response;
     try {
       response = await axios.patch(
@@ -716,7 +801,7 @@ export class AlpacaAccountConnector implements AccountConnector {
       status: 'sent',
       reason: responseOrderId ? `Order ${responseOrderId}` : undefined,
       orderId: responseOrderId ?? null,
-      payload: patchPayload
+      payload: { ...patchPayload, replacedOrderId: orderId }
     };
   }
 
@@ -873,6 +958,23 @@ export class AlpacaAccountConnector implements AccountConnector {
     return null;
   }
 
+  private extractTakeProfitOrderId(order: any): string | null {
+    const legs = order && typeof order === 'object' && Array.isArray(order.legs) ? order.legs : [];
+    for (const leg of legs) {
+      if (!leg || typeof leg !== 'object') {
+        continue;
+      }
+      const orderType = typeof leg.type === 'string' ? leg.type.trim().toLowerCase() : null;
+      if (orderType === 'limit' || (leg.limit_price !== undefined && leg.stop_price === undefined)) {
+        const normalized = this.normalizeOrderId(leg.id ?? leg.order_id ?? leg.client_order_id);
+        if (normalized) {
+          return normalized;
+        }
+      }
+    }
+    return null;
+  }
+
   private areQuantitiesClose(a: number, b: number): boolean {
     return Math.abs(a - b) <= 1e-6;
   }
@@ -916,7 +1018,7 @@ export class AlpacaAccountConnector implements AccountConnector {
     return trimmed.length > 0 ? trimmed : null;
   }
 
-  private extractStopLossPrice(value?: number | null): number | null {
+  private extractLegPrice(value?: number | null): number | null {
     if (typeof value !== 'number' || !Number.isFinite(value) || value <= 0) {
       return null;
     }
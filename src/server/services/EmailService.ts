@@ -68,6 +68,14 @@ export interface OperationDispatchSummaryPayload {
   }>;
 }
 
+export interface SkipAlertPayload {
+  accountId: string;
+  ticker: string;
+  reason: string;
+  days: number;
+  windowDays: number;
+  lastDate: string;
+}
 
 export class EmailService {
   private static readonly USD_FORMATTER = new Intl.NumberFormat('en-US', {
@@ -332,6 +340,67 @@ export class EmailService {
     await this.sendEmailWithKey(context.fromEmail, context.apiKey, { to: email, subject, html });
   }
 
+  async sendSkipAlertSummary(email: string, alerts: SkipAlertPayload[]): Promise<void> {
+    if (alerts.length === 0) {
+      return;
+    }
+
+    const context = await this.resolveSendContext();
+    if (!context) {
+      return;
+    }
+
+    const subject = `${context.siteName} skip alerts | ${alerts.length} triggered`;
+    const rows = alerts.map(alert => `
+          <tr>
+            <td style="padding:6px 8px;border-bottom:1px solid #eee;">${escapeHtml(alert.ticker)}</td>
+            <td style="padding:6px 8px;border-bottom:1px solid #eee;">${escapeHtml(alert.reason)}</td>
+            <td style="padding:6px 8px;border-bottom:1px solid #eee;">${escapeHtml(alert.accountId)}</td>
+            <td style="padding:6px 8px;border-bottom:1px solid #eee;">${alert.days} of ${alert.windowDays} days</td>
+            <td style="padding:6px 8px;border-bottom:1px solid #eee;">${escapeHtml(alert.lastDate)}</td>
+          </tr>`).join('');
+    const html = `
+      <div style="font-family: Arial, sans-serif; max-width: 600px; margin: 0 auto;">
+        <h2 style="color: #333;">${escapeHtml(context.siteName)} skip alerts</h2>
+        <p>Plan operations recorded these skip reasons often enough to match your alert rules.</p>
+        <table style="border-collapse:collapse;width:100%;font-size:14px;">
+          <tr>
+            <th align="left" style="padding:6px 8px;">Ticker</th>
+            <th align="left" style="padding:6px 8px;">Reason</th>
+            <th align="left" style="padding:6px 8px;">Account</th>
+            <th align="left" style="padding:6px 8px;">Seen</th>
+            <th align="left" style="padding:6px 8px;">Last</th>
+          </tr>${rows}
+        </table>
+        <p><a href="${context.baseUrl}/admin/logs">Open system logs</a></p>
+      </div>
+    `;
+
+    await this.sendEmailWithKey(context.fromEmail, context.apiKey, { to: email, subject, html });
+  }
+
+  async sendAccountNotifications(email: string, messages: string[]): Promise<void> {
+    if (messages.length === 0) {
+      return;
+    }
+
+    const context = await this.resolveSendContext();
+    if (!context) {
+      return;
+    }
+
+    const subject = `${context.siteName} account notifications | ${messages.length} update${messages.length === 1 ? '' : 's'}`;
+    const items = messages.map(message => `<li style="margin:6px 0;">${escapeHtml(message)}</li>`).join('');
+    const html = `
+      <div style="font-family: Arial, sans-serif; max-width: 600px; margin: 0 auto;">
+        <h2 style="color: #333;">${escapeHtml(context.siteName)} account notifications</h2>
+        <ul style="padding-left:20px;font-size:14px;">${items}</ul>
+      </div>
+    `;
+
+    await this.sendEmailWithKey(context.fromEmail, context.apiKey, { to: email, subject, html });
+  }
+
   async sendAdhocEmail(options: EmailOptions): Promise<boolean> {
     const context = await this.resolveSendContext();
     if (!context) {
@@ -6,11 +6,15 @@ export type LogSource =
   | 'engine-cli'
   | 'engine-compile-job'
   | 'train-lightgbm-job'
+  | 'try-parameters-job'
   | 'market-data-job'
   | 'candle-job'
   | 'signal-job'
   | 'backtest-job'
   | 'reconcile-trades-job'
+  | 'order-watchdog'
+  | 'skip-alerts'
+  | 'notifications'
   | 'plan-operations-job'
   | 'dispatch-operations-job'
   | 'optimize-job'
@@ -0,0 +1,75 @@
+import { AccountSnapshot, TradingAccount } from '../../shared/types/Account';
+import { AccountOperation } from '../../shared/types/StrategyTemplate';
+import type { AccountConnector, DispatchResult } from './AccountDataService';
+
+const PAPER_ORDER_PREFIX = 'paper-';
+const PAPER_STOP_ORDER_PREFIX = 'paper-stop-';
+const PAPER_TARGET_ORDER_PREFIX = 'paper-target-';
+
+/**
+ * Accounts with provider `paper` have no brokerage behind them. Dispatch only records order ids
+ * derived from the operation id; the engine's paper broker simulates fills from daily candles
+ * when reconcile-trades evaluates those orders.
+ */
+export class PaperAccountConnector implements AccountConnector {
+  supports(provider: string): boolean {
+    return provider.trim().toLowerCase() === 'paper';
+  }
+
+  async fetchSnapshot(account: TradingAccount): Promise<AccountSnapshot> {
+    return {
+      accountId: account.id,
+      provider: account.provider,
+      environment: account.environment,
+      balance: null,
+      cash: null,
+      longMarketValue: null,
+      shortMarketValue: null,
+      equity: null,
+      liquidationValue: null,
+      openTrades: null,
+      openLongPositions: null,
+      openShortPositions: null,
+      openOrders: null,
+      openBuyOrders: null,
+      openSellOrders: null,
+      currency: 'USD',
+      fetchedAt: new Date(),
+      status: 'ready',
+      source: 'paper-broker',
+      message: 'Simulated by the engine paper broker; balances follow the account trades'
+    };
+  }
+
+  async dispatchOperation(
+    _account: TradingAccount,
+    operation: AccountOperation,
+    _abortSignal: AbortSignal
+  ): Promise<DispatchResult> {
+    const orderId = `${PAPER_ORDER_PREFIX}${operation.id}`;
+    const stopOrderId =
+      operation.operationType === 'open_position' && typeof operation.stopLoss === 'number'
+        ? `${PAPER_STOP_ORDER_PREFIX}${operation.id}`
+        : null;
+    const targetOrderId =
+      operation.operationType === 'open_position' && typeof operation.takeProfit === 'number'
+        ? `${PAPER_TARGET_ORDER_PREFIX}${operation.id}`
+        : null;
+    return {
+      status: 'sent',
+      reason: `Paper order ${orderId}`,
+      orderId,
+      stopOrderId,
+      targetOrderId,
+      payload: {
+        ticker: operation.ticker,
+        operationType: operation.operationType,
+        quantity: operation.quantity ?? null,
+        price: operation.price ?? null,
+        stopLoss: operation.stopLoss ?? null,
+        takeProfit: operation.takeProfit ?? null,
+        orderType: operation.orderType ?? null
+      }
+    };
+  }
+}
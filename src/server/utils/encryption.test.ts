@@ -20,6 +20,30 @@ describe('encryption utils', () => {
     expect(decryptValue(ciphertext)).toBe('super-secret');
   });
 
+  test('writes enc:v2 envelopes and still reads enc:v1 values', async () => {
+    const { encryptValue, decryptValue } = await loadEncryptionModule(KEY_HEX);
+
+    const ciphertext = encryptValue('super-secret');
+    expect(ciphertext.startsWith('enc:v2:')).toBe(true);
+
+    const crypto = await import('crypto');
+    const iv = Buffer.alloc(12, 7);
+    const cipher = crypto.createCipheriv('aes-256-gcm', Buffer.from(KEY_HEX, 'hex'), iv);
+    const data = Buffer.concat([cipher.update('legacy-secret', 'utf8'), cipher.final()]);
+    const legacy = `enc:v1:${iv.toString('base64')}:${data.toString('base64')}:${cipher
+      .getAuthTag()
+      .toString('base64')}`;
+    expect(decryptValue(legacy)).toBe('legacy-secret');
+  });
+
+  test('decryptValue rejects enc:v2 values from another key', async () => {
+    const { encryptValue } = await loadEncryptionModule(KEY_HEX);
+    const ciphertext = encryptValue('super-secret');
+
+    const { decryptValue } = await loadEncryptionModule('fedcba9876543210'.repeat(4));
+    expect(() => decryptValue(ciphertext)).toThrow(/key id/);
+  });
+
   test('decryptValue returns plaintext unchanged when not encrypted', async () => {
     const { decryptValue, isEncryptedValue } = await loadEncryptionModule();
 
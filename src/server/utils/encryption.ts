@@ -1,7 +1,9 @@
 import crypto from 'crypto';
 
 const ENCRYPTION_PREFIX = 'enc:v1:';
+const ENCRYPTION_PREFIX_V2 = 'enc:v2:';
 const IV_LENGTH = 12;
+const KEY_ID_BYTES = 8;
 const KEY_ENV_VAR = 'DATABASE_KEY';
 
 let cachedKey: Buffer | null | undefined;
@@ -46,8 +48,15 @@ function loadEncryptionKey(): Buffer {
   return key;
 }
 
+function keyId(key: Buffer): string {
+  return crypto.createHash('sha256').update(key).digest().subarray(0, KEY_ID_BYTES).toString('hex');
+}
+
 export function isEncryptedValue(value: string): boolean {
-  return typeof value === 'string' && value.startsWith(ENCRYPTION_PREFIX);
+  return (
+    typeof value === 'string' &&
+    (value.startsWith(ENCRYPTION_PREFIX) || value.startsWith(ENCRYPTION_PREFIX_V2))
+  );
 }
 
 export function encryptValue(value: string): string {
@@ -65,7 +74,9 @@ export function encryptValue(value: string): string {
   const tag = cipher.getAuthTag();
 
   return [
-    ENCRYPTION_PREFIX,
+    ENCRYPTION_PREFIX_V2,
+    keyId(key),
+    ':',
     iv.toString('base64'),
     ':',
     ciphertext.toString('base64'),
@@ -83,7 +94,20 @@ export function decryptValue(value: string): string {
   }
 
   const key = loadEncryptionKey();
-  const payload = value.slice(ENCRYPTION_PREFIX.length);
+  let payload = value.slice(ENCRYPTION_PREFIX.length);
+  if (value.startsWith(ENCRYPTION_PREFIX_V2)) {
+    const separator = payload.indexOf(':');
+    if (separator < 0) {
+      throw new Error('Encrypted value has an invalid format.');
+    }
+    const valueKeyId = payload.slice(0, separator);
+    if (valueKeyId !== keyId(key)) {
+      throw new Error(
+        `Encrypted value uses key id ${valueKeyId} but ${KEY_ENV_VAR} has id ${keyId(key)}.`
+      );
+    }
+    payload = payload.slice(separator + 1);
+  }
   const parts = payload.split(':');
   if (parts.length !== 3) {
     throw new Error('Encrypted value has an invalid format.');
@@ -312,6 +312,8 @@ export const viewHelpers = {
     switch ((status || '').toLowerCase()) {
       case 'pending':
         return 'Pending';
+      case 'paused':
+        return 'Paused';
       case 'failed':
         return 'Failed';
       case 'sent':
@@ -328,6 +330,8 @@ export const viewHelpers = {
     switch ((status || '').toLowerCase()) {
       case 'pending':
         return 'warning';
+      case 'paused':
+        return 'info';
       case 'failed':
         return 'danger';
       case 'sent':
@@ -8,6 +8,11 @@ export interface TradingAccount {
   environment: AccountEnvironment;
   excludedTickers: string[];
   excludedKeywords: string[];
+  ensembleQuorum: number | null;
+  maxStopRatio: number | null;
+  maxRiskPerTrade: number | null;
+  takeProfitRMultiple: number | null;
+  currency: string;
   apiKey: string;
   apiSecret: string;
   createdAt: Date;
@@ -3,7 +3,7 @@ export type AccountOperationType =
   | 'close_position'
   | 'update_stop_loss';
 
-export type AccountOperationStatus = 'pending' | 'sent' | 'skipped' | 'failed';
+export type AccountOperationStatus = 'pending' | 'paused' | 'sent' | 'skipped' | 'failed';
 
 export interface AccountOperation {
   id: string;
@@ -28,11 +28,15 @@ export interface AccountOperation {
   entryOrderId?: string | null;
   stopOrderId?: string | null;
   exitOrderId?: string | null;
+  targetOrderId?: string | null;
   orderType?: 'market' | 'limit' | null;
   discountApplied?: boolean | null;
   signalConfidence?: number | null;
   accountCashAtPlan?: number | null;
   daysHeld?: number | null;
+  expectedCost?: number | null;
+  replacesOrderId?: string | null;
+  takeProfit?: number | null;
   createdAt: Date;
   updatedAt: Date;
 }
@@ -69,6 +73,8 @@ export interface Strategy {
   userId: number | null;
   accountId?: string | null;
   backtestStartDate?: Date | null;
+  // JSON blackout windows that suppress buy signals (earnings, FOMC days, custom ranges)
+  blackoutWindows?: string | null;
   parameters: Record<string, string | number | boolean>;
   status: 'active' | 'inactive' | 'error';
   createdAt: Date;
@@ -115,7 +121,7 @@ export interface StrategyPerformance {
   tickerScope?: BacktestScope;
 }
 
-export type BacktestScope = 'training' | 'validation' | 'all' | 'live';
+export type BacktestScope = 'training' | 'validation' | 'all' | 'live' | 'custom';
 
 export interface BacktestDataPoint {
   date: Date;
@@ -174,6 +180,7 @@ export interface Trade {
   entryCancelAfter?: Date | null;
   stopOrderId?: string | null;
   exitOrderId?: string | null;
+  targetOrderId?: string | null;
   createdAt: Date;
   changes: TradeChange[];
 }